@@ -0,0 +1,570 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use std::collections::{BTreeMap, HashMap};
+
+use graph::adjlist::{Graph, NeighborList, SmallNeighbors};
+use graph::arc_cache::ArcCache;
+use graph::arena_graph::ArenaGraph;
+use graph::avl_tree::AvlTreeMap;
+use graph::b_plus_tree::BPlusTree;
+use graph::b_tree;
+use graph::binary_heap::BinaryHeap;
+use graph::binomial_heap::BinomialHeap;
+use graph::bitmatrix::BitMatrix;
+use graph::cache::Cache;
+use graph::csr::CsrGraph;
+use graph::cuckoo_hash::CuckooHashMap;
+use graph::fibonacci_heap::FibonacciHeap;
+use graph::generators::{random_connected, undirected};
+use graph::lfu_cache::LfuCache;
+use graph::lru_cache::LruCache;
+use graph::matrix;
+use graph::mst::{kruskal, prim};
+use graph::ordered_map::OrderedMap;
+use graph::pairing_heap::PairingHeap;
+use graph::parallel::{par_bfs, threads};
+use graph::red_black_tree::RedBlackTreeMap;
+use graph::rng::{Rng, SplitMix64, Zipf};
+use graph::robin_hood::RobinHoodMap;
+use graph::scapegoat_tree::ScapegoatTree;
+use graph::shortest_path::{dijkstra, dijkstra_with};
+use graph::skip_list::SkipListMap;
+use graph::sort::{
+    heapsort, introsort, merge_sort, msd_radix_sort, quicksort, radix_sort, tim_sort, ParSort,
+};
+use graph::strings::{BoyerMoore, Horspool, Kmp, Matcher};
+
+// Minimal timing harness: `cargo bench [filter]` runs every benchmark whose
+// name contains the filter and prints the mean time per iteration.
+
+const BUDGET: Duration = Duration::from_millis(300);
+const SIZES: [usize; 3] = [100, 1_000, 5_000];
+
+struct Runner {
+    filter: Option<String>,
+}
+
+impl Runner {
+    fn run<R>(&self, name: &str, mut f: impl FnMut() -> R) {
+        if self.filter.as_ref().is_some_and(|pat| !name.contains(pat)) {
+            return;
+        }
+        black_box(f());
+        let start = Instant::now();
+        let mut iters = 0u32;
+        while iters < 3 || start.elapsed() < BUDGET {
+            black_box(f());
+            iters += 1;
+        }
+        let per_iter = start.elapsed() / iters;
+        println!("{name:<40} {per_iter:>12.2?}/iter ({iters} iters)");
+    }
+
+    // prints a figure other than time, under the same filter
+    fn report(&self, name: &str, value: impl std::fmt::Display) {
+        if self.filter.as_ref().is_some_and(|pat| !name.contains(pat)) {
+            return;
+        }
+        println!("{name:<40} {value:>12}");
+    }
+}
+
+// inline neighbor storage, sized for the average degree of the inputs
+type Small = SmallNeighbors<usize, 8>;
+
+fn adjlist<S: NeighborList<usize>>(edges: &[(usize, usize, i32)]) -> Graph<usize, S> {
+    let mut g = Graph::with_storage();
+    for &(a, b, wt) in edges {
+        g.add_edge(&a, &b, wt);
+    }
+    g
+}
+
+fn arena(
+    n: usize,
+    edges: &[(usize, usize, i32)],
+) -> (ArenaGraph<usize>, Vec<graph::arena_graph::VertexId>) {
+    let mut g = ArenaGraph::new();
+    let ids: Vec<_> = (0..n).map(|v| g.add_vertex(v)).collect();
+    for &(a, b, wt) in edges {
+        g.add_edge(ids[a], ids[b], wt);
+    }
+    (g, ids)
+}
+
+fn matrix(n: usize, edges: &[(usize, usize, i32)]) -> matrix::Graph {
+    let mut g = matrix::Graph::new(n);
+    for &(a, b, _) in edges {
+        g.add_edge(&matrix::Vertex::new(a, ""), &matrix::Vertex::new(b, ""));
+    }
+    g
+}
+
+fn representations(r: &Runner, rng: &mut SplitMix64) {
+    for n in SIZES {
+        let edges = undirected(&random_connected(n, 2 * n, 100, rng));
+        let queries: Vec<(usize, usize)> = (0..1000)
+            .map(|_| (rng.gen_range(n), rng.gen_range(n)))
+            .collect();
+
+        r.run(&format!("add_edge/adjlist/{n}"), || {
+            adjlist::<Vec<_>>(&edges)
+        });
+        r.run(&format!("add_edge/adjlist_small/{n}"), || {
+            adjlist::<Small>(&edges)
+        });
+        r.run(&format!("add_edge/arena/{n}"), || arena(n, &edges));
+        r.run(&format!("add_edge/matrix/{n}"), || matrix(n, &edges));
+        r.run(&format!("add_edge/csr/{n}"), || {
+            CsrGraph::from_edges(n, &edges)
+        });
+
+        let (al, small, mx, csr) = (
+            adjlist::<Vec<_>>(&edges),
+            adjlist::<Small>(&edges),
+            matrix(n, &edges),
+            CsrGraph::from_edges(n, &edges),
+        );
+        r.run(&format!("contains/adjlist/{n}"), || {
+            queries.iter().filter(|(a, b)| al.adjacent(a, b)).count()
+        });
+        r.run(&format!("contains/adjlist_small/{n}"), || {
+            queries.iter().filter(|(a, b)| small.adjacent(a, b)).count()
+        });
+        r.run(&format!("contains/matrix/{n}"), || {
+            queries.iter().filter(|&&(a, b)| mx.adjacent(a, b)).count()
+        });
+        r.run(&format!("contains/csr/{n}"), || {
+            queries.iter().filter(|&&(a, b)| csr.has_edge(a, b)).count()
+        });
+
+        r.run(&format!("neighbors/adjlist/{n}"), || {
+            (0..n)
+                .map(|v| al.get_vertex(&v).unwrap().get_neighbors().len())
+                .sum::<usize>()
+        });
+        r.run(&format!("neighbors/adjlist_small/{n}"), || {
+            (0..n)
+                .map(|v| small.get_vertex(&v).unwrap().get_neighbors().len())
+                .sum::<usize>()
+        });
+        r.run(&format!("neighbors/matrix/{n}"), || {
+            (0..n).map(|v| mx.neighbors(v).len()).sum::<usize>()
+        });
+        r.run(&format!("neighbors/csr/{n}"), || {
+            (0..n)
+                .map(|v| csr.neighbors(v).iter().sum::<usize>())
+                .sum::<usize>()
+        });
+
+        // building included, subtract add_edge above
+        let doomed: Vec<usize> = (0..n / 10).map(|_| rng.gen_range(n)).collect();
+        r.run(&format!("remove_vertex/adjlist/{n}"), || {
+            let mut g = adjlist::<Vec<_>>(&edges);
+            doomed.iter().filter_map(|v| g.remove_vertex(v)).count()
+        });
+        r.run(&format!("remove_vertex/arena/{n}"), || {
+            let (mut g, ids) = arena(n, &edges);
+            doomed
+                .iter()
+                .filter_map(|&v| g.remove_vertex(ids[v]))
+                .count()
+        });
+    }
+}
+
+fn algorithms(r: &Runner, rng: &mut SplitMix64) {
+    for n in SIZES {
+        let edges = undirected(&random_connected(n, 4 * n, 100, rng));
+        let al = adjlist::<Vec<_>>(&edges);
+        let small = adjlist::<Small>(&edges);
+        r.run(&format!("dijkstra/adjlist/{n}"), || dijkstra(&al, &0));
+        r.run(&format!("dijkstra/adjlist_small/{n}"), || {
+            dijkstra(&small, &0)
+        });
+        r.run(&format!("dijkstra/binary_heap/{n}"), || {
+            dijkstra_with(&al, &0, BinaryHeap::new())
+        });
+        r.run(&format!("dijkstra/pairing_heap/{n}"), || {
+            dijkstra_with(&al, &0, PairingHeap::new())
+        });
+        r.run(&format!("dijkstra/fibonacci_heap/{n}"), || {
+            dijkstra_with(&al, &0, FibonacciHeap::new())
+        });
+        r.run(&format!("dijkstra/binomial_heap/{n}"), || {
+            dijkstra_with(&al, &0, BinomialHeap::new())
+        });
+        r.run(&format!("mst/kruskal/{n}"), || kruskal(&al));
+        r.run(&format!("mst/prim/{n}"), || prim(&al));
+    }
+    for n in [10_000, 100_000, 1_000_000] {
+        let csr = CsrGraph::from_edges(n, &undirected(&random_connected(n, 4 * n, 1, rng)));
+        r.run(&format!("bfs/csr/{n}"), || csr.bfs(0));
+        r.run(&format!("bfs/csr_parallel/{n}"), || par_bfs(&csr, 0));
+    }
+}
+
+// bit-packed kernels against the per-cell matrix versions
+fn dense(r: &Runner, rng: &mut SplitMix64) {
+    for n in [64, 256, 512] {
+        let mut edges = Vec::new();
+        for a in 0..n {
+            for b in a + 1..n {
+                if rng.gen_bool(0.1) {
+                    edges.push((a, b, 1));
+                }
+            }
+        }
+        let edges = undirected(&edges);
+        let mx = matrix(n, &edges);
+        let bits = BitMatrix::from_matrix(&mx);
+        r.run(&format!("closure/matrix/{n}"), || mx.transitive_closure());
+        r.run(&format!("closure/bitmatrix/{n}"), || {
+            bits.transitive_closure()
+        });
+        r.run(&format!("triangles/matrix/{n}"), || mx.triangle_count());
+        r.run(&format!("triangles/bitmatrix/{n}"), || {
+            bits.triangle_count()
+        });
+        r.run(&format!("common_neighbors/matrix/{n}"), || {
+            (1..n).map(|v| mx.common_neighbors(0, v)).sum::<usize>()
+        });
+        r.run(&format!("common_neighbors/bitmatrix/{n}"), || {
+            (1..n).map(|v| bits.common_neighbors(0, v)).sum::<usize>()
+        });
+    }
+}
+
+fn ordered_map<M: OrderedMap<u64, u64> + Default>(r: &Runner, name: &str, keys: &[u64]) {
+    let n = keys.len();
+    let filled = || {
+        let mut m = M::default();
+        for &k in keys {
+            m.insert(k, k);
+        }
+        m
+    };
+    r.run(&format!("map_insert/{name}/{n}"), filled);
+    let m = filled();
+    r.run(&format!("map_get/{name}/{n}"), || {
+        keys.iter().filter(|k| m.get(k).is_some()).count()
+    });
+    r.run(&format!("map_remove/{name}/{n}"), || {
+        let mut m = filled();
+        for k in keys {
+            m.remove(k);
+        }
+        m.len()
+    });
+}
+
+fn ordered_maps(r: &Runner, rng: &mut SplitMix64) {
+    for n in [1_000, 10_000, 100_000] {
+        let keys: Vec<u64> = (0..n).map(|_| rng.next_u64()).collect();
+        ordered_map::<BTreeMap<_, _>>(r, "std_btree", &keys);
+        ordered_map::<AvlTreeMap<_, _>>(r, "avl", &keys);
+        ordered_map::<RedBlackTreeMap<_, _>>(r, "red_black", &keys);
+        ordered_map::<SkipListMap<_, _>>(r, "skip_list", &keys);
+        ordered_map::<ScapegoatTree<_, _>>(r, "scapegoat", &keys);
+        // branching factor sweep, nodes hold up to 2B - 1 keys
+        ordered_map::<b_tree::BTreeMap<_, _, 2>>(r, "b_tree_2", &keys);
+        ordered_map::<b_tree::BTreeMap<_, _, 6>>(r, "b_tree_6", &keys);
+        ordered_map::<b_tree::BTreeMap<_, _, 16>>(r, "b_tree_16", &keys);
+        ordered_map::<b_tree::BTreeMap<_, _, 64>>(r, "b_tree_64", &keys);
+        ordered_map::<BPlusTree<_, _>>(r, "b_plus_tree", &keys);
+
+        // sum over the middle half of the key space
+        let (lo, hi) = (u64::MAX / 4, u64::MAX / 4 * 3);
+        let std_map: BTreeMap<_, _> = keys.iter().map(|&k| (k, k)).collect();
+        let b_map: b_tree::BTreeMap<_, _> = keys.iter().map(|&k| (k, k)).collect();
+        let mut sorted = keys.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        let bp_map = BPlusTree::<_, _>::from_sorted(sorted.iter().map(|&k| (k, k)));
+        r.run(&format!("map_scan/std_btree/{n}"), || {
+            std_map.range(lo..hi).map(|(_, v)| v).sum::<u64>()
+        });
+        r.run(&format!("map_scan/b_tree_6/{n}"), || {
+            b_map.range(lo..hi).map(|(_, v)| v).sum::<u64>()
+        });
+        r.run(&format!("map_scan/b_plus_tree/{n}"), || {
+            bp_map.range(lo..hi).map(|(_, v)| v).sum::<u64>()
+        });
+    }
+}
+
+fn hash_maps(r: &Runner, rng: &mut SplitMix64) {
+    for n in [10_000, 100_000] {
+        let keys: Vec<u64> = (0..n).map(|_| rng.next_u64()).collect();
+        let lookups: Vec<u64> = (0..n)
+            .map(|i| if i % 2 == 0 { keys[i] } else { rng.next_u64() })
+            .collect();
+        r.run(&format!("hash_insert/std_hashmap/{n}"), || {
+            keys.iter().map(|&k| (k, k)).collect::<HashMap<_, _>>()
+        });
+        r.run(&format!("hash_insert/robin_hood/{n}"), || {
+            keys.iter().map(|&k| (k, k)).collect::<RobinHoodMap<_, _>>()
+        });
+        r.run(&format!("hash_insert/cuckoo/{n}"), || {
+            keys.iter()
+                .map(|&k| (k, k))
+                .collect::<CuckooHashMap<_, _>>()
+        });
+        let std_map: HashMap<_, _> = keys.iter().map(|&k| (k, k)).collect();
+        let rh_map: RobinHoodMap<_, _> = keys.iter().map(|&k| (k, k)).collect();
+        // half hits, half misses
+        r.run(&format!("hash_get/std_hashmap/{n}"), || {
+            lookups.iter().filter(|k| std_map.contains_key(k)).count()
+        });
+        r.run(&format!("hash_get/robin_hood/{n}"), || {
+            lookups.iter().filter(|k| rh_map.contains_key(k)).count()
+        });
+        let cuckoo_map: CuckooHashMap<_, _> = keys.iter().map(|&k| (k, k)).collect();
+        r.run(&format!("hash_get/cuckoo/{n}"), || {
+            lookups
+                .iter()
+                .filter(|k| cuckoo_map.contains_key(k))
+                .count()
+        });
+        r.run(&format!("hash_remove/std_hashmap/{n}"), || {
+            let mut m = std_map.clone();
+            keys.iter().filter(|k| m.remove(k).is_some()).count()
+        });
+        r.run(&format!("hash_remove/robin_hood/{n}"), || {
+            let mut m = rh_map.clone();
+            keys.iter().filter(|k| m.remove(k).is_some()).count()
+        });
+        for load in [0.5, 0.75, 0.9, 0.95] {
+            let mut m = RobinHoodMap::new();
+            m.set_max_load_factor(load);
+            m.extend(keys.iter().map(|&k| (k, k)));
+            let stats = m.probe_stats();
+            r.report(
+                &format!("hash_probes/robin_hood_{load}/{n}"),
+                format!("{:.2} mean {} max", stats.mean, stats.max),
+            );
+        }
+    }
+}
+
+// fraction of gets that hit, a miss putting the key
+fn hit_rate<C: Cache<u64, u64>>(mut cache: C, trace: &[u64]) -> f64 {
+    let mut hits = 0;
+    for &k in trace {
+        if cache.get(&k).is_some() {
+            hits += 1;
+        } else {
+            cache.put(k, k);
+        }
+    }
+    hits as f64 / trace.len() as f64
+}
+
+fn cache<C: Cache<u64, u64>>(r: &Runner, name: &str, new: impl Fn() -> C, trace: &[u64]) {
+    r.report(name, format!("{:.2}% hits", 100.0 * hit_rate(new(), trace)));
+    r.run(name, || hit_rate(new(), trace));
+}
+
+fn caches(r: &Runner, rng: &mut SplitMix64) {
+    const KEYS: usize = 10_000;
+    for exponent in [0.8, 1.0, 1.2] {
+        let zipf = Zipf::new(KEYS, exponent);
+        let mut trace: Vec<u64> = (0..200_000).map(|_| zipf.sample(rng) as u64).collect();
+        // every 50k requests a scan of 5k keys never seen again
+        for (i, k) in trace.iter_mut().enumerate() {
+            if i % 50_000 < 5_000 {
+                *k = (KEYS + i) as u64;
+            }
+        }
+        for cap in [100, 1_000] {
+            let tag = format!("zipf_{exponent}/{cap}");
+            cache(
+                r,
+                &format!("cache/lru/{tag}"),
+                || LruCache::new(cap),
+                &trace,
+            );
+            cache(
+                r,
+                &format!("cache/lfu/{tag}"),
+                || LfuCache::new(cap),
+                &trace,
+            );
+            cache(
+                r,
+                &format!("cache/arc/{tag}"),
+                || ArcCache::new(cap),
+                &trace,
+            );
+        }
+    }
+}
+
+fn sort_run<T: Clone>(r: &Runner, name: &str, input: &[T], sort: impl Fn(&mut [T])) {
+    r.run(name, || {
+        let mut v = input.to_vec();
+        sort(&mut v);
+        v
+    });
+}
+
+fn sorts(r: &Runner, rng: &mut SplitMix64) {
+    for n in [100_000, 1_000_000] {
+        let ints: Vec<u32> = (0..n).map(|_| rng.next_u64() as u32).collect();
+        // the copy alone, to subtract from the others
+        sort_run(r, &format!("sort_u32/copy/{n}"), &ints, |_| {});
+        sort_run(r, &format!("sort_u32/std_unstable/{n}"), &ints, |v| {
+            v.sort_unstable()
+        });
+        sort_run(r, &format!("sort_u32/std_stable/{n}"), &ints, |v| v.sort());
+        sort_run(r, &format!("sort_u32/quicksort/{n}"), &ints, quicksort);
+        sort_run(r, &format!("sort_u32/introsort/{n}"), &ints, introsort);
+        sort_run(r, &format!("sort_u32/merge_sort/{n}"), &ints, merge_sort);
+        sort_run(r, &format!("sort_u32/heapsort/{n}"), &ints, heapsort);
+        sort_run(r, &format!("sort_u32/radix_lsd/{n}"), &ints, radix_sort);
+
+        let words: Vec<String> = ints
+            .iter()
+            .map(|&x| format!("{:x}", x >> (x % 24)))
+            .collect();
+        sort_run(r, &format!("sort_str/std_unstable/{n}"), &words, |v| {
+            v.sort_unstable()
+        });
+        sort_run(
+            r,
+            &format!("sort_str/radix_msd/{n}"),
+            &words,
+            msd_radix_sort,
+        );
+
+        let sorted: Vec<u32> = (0..n as u32).collect();
+        let mut swapped = sorted.clone();
+        for _ in 0..n / 100 {
+            swapped.swap(rng.gen_range(n), rng.gen_range(n));
+        }
+        let mut runs = ints.clone();
+        for run in runs.chunks_mut(1000) {
+            run.sort();
+        }
+        let mut tail = ints.clone();
+        tail[..n - n / 100].sort();
+        let partial = [
+            ("random", &ints),
+            ("sorted", &sorted),
+            ("1pct_swapped", &swapped),
+            ("runs_1000", &runs),
+            ("sorted_tail", &tail),
+        ];
+        for (shape, input) in partial {
+            sort_run(
+                r,
+                &format!("sort_adaptive/{shape}/tim_sort/{n}"),
+                input,
+                tim_sort,
+            );
+            sort_run(
+                r,
+                &format!("sort_adaptive/{shape}/merge_sort/{n}"),
+                input,
+                merge_sort,
+            );
+            sort_run(
+                r,
+                &format!("sort_adaptive/{shape}/std_stable/{n}"),
+                input,
+                |v| v.sort(),
+            );
+        }
+    }
+}
+
+// Scaling of the parallel sorts with the thread count, doubling up to one
+// per core, against the sequential sorts they fall back on.
+fn par_sorts(r: &Runner, rng: &mut SplitMix64) {
+    for n in [2_000_000, 8_000_000] {
+        let ints: Vec<u32> = (0..n).map(|_| rng.next_u64() as u32).collect();
+        sort_run(r, &format!("sort_par/copy/{n}"), &ints, |_| {});
+        sort_run(r, &format!("sort_par/std_unstable/{n}"), &ints, |v| {
+            v.sort_unstable()
+        });
+        sort_run(r, &format!("sort_par/introsort/{n}"), &ints, introsort);
+        sort_run(r, &format!("sort_par/merge_sort/{n}"), &ints, merge_sort);
+        let mut counts: Vec<usize> = (0..)
+            .map(|i| 1 << i)
+            .take_while(|&t| t < threads())
+            .collect();
+        counts.push(threads());
+        for t in counts {
+            let par = ParSort::new().threads(t);
+            sort_run(
+                r,
+                &format!("sort_par/par_quicksort/threads_{t}/{n}"),
+                &ints,
+                |v| par.quicksort(v),
+            );
+            sort_run(
+                r,
+                &format!("sort_par/par_merge_sort/threads_{t}/{n}"),
+                &ints,
+                |v| par.merge_sort(v),
+            );
+        }
+    }
+}
+
+// the most common English words, drawn by Zipf rank into a text that has
+// English's skewed letter frequencies, though not its grammar
+const WORDS: [&str; 64] = [
+    "the", "of", "and", "to", "a", "in", "is", "it", "you", "that", "he", "was", "for", "on",
+    "are", "with", "as", "his", "they", "be", "at", "one", "have", "this", "from", "or", "had",
+    "by", "not", "word", "but", "what", "some", "we", "can", "out", "other", "were", "all",
+    "there", "when", "up", "use", "your", "how", "said", "an", "each", "she", "which", "do",
+    "their", "time", "if", "will", "way", "about", "many", "then", "them", "write", "would",
+    "like", "so",
+];
+
+fn string_run(r: &Runner, name: &str, matcher: &impl Matcher, text: &[u8]) {
+    r.run(name, || matcher.count(text));
+}
+
+// KMP against Boyer-Moore and Horspool, which skip more of the text the
+// longer the pattern and the larger the alphabet. Patterns are cut from
+// the text, so each has at least one match.
+fn string_search(r: &Runner, rng: &mut SplitMix64) {
+    const LEN: usize = 1 << 22;
+    let zipf = Zipf::new(WORDS.len(), 1.0);
+    let mut english = Vec::with_capacity(LEN + 8);
+    while english.len() < LEN {
+        english.extend_from_slice(WORDS[zipf.sample(rng)].as_bytes());
+        english.push(b' ');
+    }
+    let dna: Vec<u8> = (0..LEN).map(|_| b"ACGT"[rng.gen_range(4)]).collect();
+    for (alphabet, text) in [("english", &english), ("dna", &dna)] {
+        for m in [4, 16, 64, 256] {
+            let at = rng.gen_range(text.len() - m);
+            let pattern = &text[at..at + m];
+            let name = |algorithm| format!("strings/{alphabet}/{algorithm}/{m}");
+            string_run(r, &name("kmp"), &Kmp::new(pattern), text);
+            string_run(r, &name("boyer_moore"), &BoyerMoore::new(pattern), text);
+            string_run(r, &name("horspool"), &Horspool::new(pattern), text);
+        }
+    }
+}
+
+fn main() {
+    let filter = std::env::args().skip(1).find(|a| !a.starts_with("--"));
+    let r = Runner { filter };
+    let mut rng = SplitMix64::new(114);
+    representations(&r, &mut rng);
+    algorithms(&r, &mut rng);
+    dense(&r, &mut rng);
+    ordered_maps(&r, &mut rng);
+    caches(&r, &mut rng);
+    hash_maps(&r, &mut rng);
+    sorts(&r, &mut rng);
+    par_sorts(&r, &mut rng);
+    string_search(&r, &mut rng);
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../tests/mutations/mod.rs"]
+mod mutations;
+
+fuzz_target!(|data: &[u8]| {
+    mutations::run(data);
+});
@@ -0,0 +1,52 @@
+#ifndef GRAPH_H
+#define GRAPH_H
+
+/* Kept in sync by hand with cbindgen.toml and src/capi.rs;
+   tests/capi.rs checks it against libgraph.a. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define GRAPH_UNREACHABLE -1
+
+#define GRAPH_NO_VERTEX UINT32_MAX
+
+typedef enum GraphStatus {
+  GRAPH_STATUS_OK = 0,
+  GRAPH_STATUS_NULL_POINTER = 1,
+  GRAPH_STATUS_VERTEX_OUT_OF_RANGE = 2,
+  GRAPH_STATUS_NEGATIVE_WEIGHT = 3,
+  GRAPH_STATUS_BUFFER_TOO_SMALL = 4,
+} GraphStatus;
+
+typedef struct graph_t graph_t;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+graph_t *graph_new(uint32_t vertex_count);
+
+void graph_free(graph_t *graph);
+
+uint32_t graph_vertex_count(const graph_t *graph);
+
+uint32_t graph_edge_count(const graph_t *graph);
+
+GraphStatus graph_add_vertex(graph_t *graph, uint32_t *id);
+
+GraphStatus graph_add_edge(graph_t *graph, uint32_t from, uint32_t to, int32_t weight);
+
+GraphStatus graph_dijkstra(const graph_t *graph,
+                           uint32_t source,
+                           int64_t *dist,
+                           uint32_t *prev,
+                           size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GRAPH_H */
@@ -0,0 +1,217 @@
+// Python module over the graph crate, built with `maturin develop`.
+// Vertices are Python ints; results come back as dicts keyed by vertex.
+
+use std::collections::HashMap;
+
+use numpy::PyReadonlyArray2;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+
+use graph::adjlist::Graph as AdjGraph;
+use graph::community::{label_propagation, modularity};
+use graph::csr::CsrGraph;
+use graph::rng::SplitMix64;
+use graph::scoring::{hits, pagerank, IterationOptions};
+use graph::shortest_path::{bellman_ford, dijkstra};
+
+#[pyclass]
+#[derive(Default)]
+struct Graph {
+    inner: AdjGraph<i64>,
+}
+
+#[pymethods]
+impl Graph {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    // a[i, j] != 0 becomes an edge i -> j with weight round(a[i, j]); the
+    // array is read in place through a view, not copied into Rust
+    #[staticmethod]
+    fn from_numpy(matrix: PyReadonlyArray2<'_, f64>) -> PyResult<Self> {
+        let a = matrix.as_array();
+        let (rows, cols) = a.dim();
+        if rows != cols {
+            return Err(PyValueError::new_err("adjacency matrix must be square"));
+        }
+        let mut g = AdjGraph::new();
+        for v in 0..rows as i64 {
+            g.add_vertex(&v);
+        }
+        for ((i, j), &x) in a.indexed_iter() {
+            if x == 0.0 {
+                continue;
+            }
+            if !x.is_finite() || x.round().abs() > i32::MAX as f64 {
+                return Err(PyValueError::new_err(format!(
+                    "weight at ({i}, {j}) is not a 32-bit integer"
+                )));
+            }
+            g.add_edge(&(i as i64), &(j as i64), x.round() as i32);
+        }
+        Ok(Self { inner: g })
+    }
+
+    fn add_vertex(&mut self, key: i64) {
+        if !self.inner.contains(&key) {
+            self.inner.add_vertex(&key);
+        }
+    }
+
+    #[pyo3(signature = (source, target, weight = 1, undirected = false))]
+    fn add_edge(&mut self, source: i64, target: i64, weight: i32, undirected: bool) {
+        self.inner.add_edge(&source, &target, weight);
+        if undirected && source != target {
+            self.inner.add_edge(&target, &source, weight);
+        }
+    }
+
+    fn remove_edge(&mut self, source: i64, target: i64) -> Option<i32> {
+        self.inner.remove_edge(&source, &target)
+    }
+
+    fn vertex_count(&self) -> u32 {
+        self.inner.vertex_num()
+    }
+
+    fn edge_count(&self) -> u32 {
+        self.inner.edge_num()
+    }
+
+    fn vertices(&self) -> Vec<i64> {
+        let mut keys = self.inner.vertex_keys();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn neighbors(&self, key: i64) -> PyResult<Vec<(i64, i32)>> {
+        Ok(self.vertex(key)?.neighbors.clone())
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.vertex_num() as usize
+    }
+
+    fn __contains__(&self, key: i64) -> bool {
+        self.inner.contains(&key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Graph(vertices={}, edges={})",
+            self.inner.vertex_num(),
+            self.inner.edge_num()
+        )
+    }
+
+    // distances of every vertex reachable from source
+    fn dijkstra(&self, source: i64) -> PyResult<HashMap<i64, i64>> {
+        self.vertex(source)?;
+        if self.has_negative_weight() {
+            return Err(PyValueError::new_err(
+                "dijkstra needs non-negative weights, use bellman_ford",
+            ));
+        }
+        Ok(dijkstra(&self.inner, &source).dist)
+    }
+
+    fn bellman_ford(&self, source: i64) -> PyResult<HashMap<i64, i64>> {
+        self.vertex(source)?;
+        bellman_ford(&self.inner, &source)
+            .map(|sp| sp.dist)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    // source..=target, None when target is unreachable
+    fn shortest_path(&self, source: i64, target: i64) -> PyResult<Option<Vec<i64>>> {
+        self.vertex(source)?;
+        self.vertex(target)?;
+        let sp = if self.has_negative_weight() {
+            bellman_ford(&self.inner, &source).map_err(|e| PyValueError::new_err(e.to_string()))?
+        } else {
+            dijkstra(&self.inner, &source)
+        };
+        Ok(sp.path_to(&target))
+    }
+
+    #[pyo3(signature = (damping = 0.85, tolerance = 1e-9, max_iterations = 100))]
+    fn pagerank(
+        &self,
+        damping: f64,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> PyResult<HashMap<i64, f64>> {
+        if !(0.0..=1.0).contains(&damping) {
+            return Err(PyValueError::new_err("damping must be in [0, 1]"));
+        }
+        let (csr, keys) = CsrGraph::from_adjlist(&self.inner);
+        let options = IterationOptions {
+            tolerance,
+            max_iterations,
+        };
+        Ok(keys
+            .into_iter()
+            .zip(pagerank(&csr, damping, &options).scores)
+            .collect())
+    }
+
+    // (hubs, authorities)
+    #[pyo3(signature = (tolerance = 1e-9, max_iterations = 100))]
+    fn hits(
+        &self,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> (HashMap<i64, f64>, HashMap<i64, f64>) {
+        let (csr, keys) = CsrGraph::from_adjlist(&self.inner);
+        let options = IterationOptions {
+            tolerance,
+            max_iterations,
+        };
+        let h = hits(&csr, &options);
+        (
+            keys.iter().copied().zip(h.hubs).collect(),
+            keys.into_iter().zip(h.authorities).collect(),
+        )
+    }
+
+    #[pyo3(signature = (seed = 0, max_rounds = 100))]
+    fn label_propagation(&self, seed: u64, max_rounds: usize) -> Vec<Vec<i64>> {
+        let mut communities = label_propagation(&self.inner, max_rounds, SplitMix64::new(seed));
+        for c in communities.iter_mut() {
+            c.sort_unstable();
+        }
+        communities.sort_unstable();
+        communities
+    }
+
+    fn modularity(&self, communities: Vec<Vec<i64>>) -> f64 {
+        modularity(&self.inner, &communities)
+    }
+}
+
+impl Graph {
+    fn vertex(&self, key: i64) -> PyResult<&graph::adjlist::Vertex<i64>> {
+        self.inner
+            .get_vertex(&key)
+            .ok_or_else(|| PyKeyError::new_err(key))
+    }
+
+    fn has_negative_weight(&self) -> bool {
+        self.inner.vertex_keys().iter().any(|k| {
+            self.inner
+                .get_vertex(k)
+                .unwrap()
+                .neighbors
+                .iter()
+                .any(|&(_, wt)| wt < 0)
+        })
+    }
+}
+
+#[pymodule]
+fn graph_algos(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Graph>()?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+# Tests of the Python module, run from this directory with
+# `maturin develop --extras test && pytest`.
+
+import math
+
+import numpy as np
+import pytest
+
+from graph_algos import Graph
+
+
+def two_triangles():
+    # two undirected triangles joined by the edge 2 - 3
+    g = Graph()
+    for a, b in [(0, 1), (1, 2), (0, 2), (3, 4), (4, 5), (3, 5), (2, 3)]:
+        g.add_edge(a, b, undirected=True)
+    return g
+
+
+def test_building():
+    g = Graph()
+    assert len(g) == 0 and g.vertex_count() == 0 and g.edge_count() == 0
+    g.add_vertex(7)
+    g.add_vertex(7)
+    assert g.vertices() == [7]
+    g.add_edge(1, 2, 5)
+    g.add_edge(2, 3, undirected=True)
+    assert g.vertices() == [1, 2, 3, 7]
+    assert 3 in g and 4 not in g
+    assert g.edge_count() == 3
+    assert g.neighbors(1) == [(2, 5)]
+    assert sorted(g.neighbors(3)) == [(2, 1)]
+    assert g.remove_edge(1, 2) == 5
+    assert g.remove_edge(1, 2) is None
+    assert g.edge_count() == 2
+    assert repr(g) == "Graph(vertices=4, edges=2)"
+    with pytest.raises(KeyError):
+        g.neighbors(4)
+
+
+def test_from_numpy():
+    a = np.array([[0.0, 2.0, 0.0], [0.0, 0.0, 3.4], [1.0, 0.0, 0.0]])
+    g = Graph.from_numpy(a)
+    assert g.vertices() == [0, 1, 2]
+    assert g.edge_count() == 3
+    assert g.neighbors(1) == [(2, 3)]
+    assert g.dijkstra(0) == {0: 0, 1: 2, 2: 5}
+    # a strided view is read as is
+    assert Graph.from_numpy(a.T).neighbors(0) == [(2, 1)]
+    with pytest.raises(ValueError):
+        Graph.from_numpy(np.zeros((2, 3)))
+    with pytest.raises(ValueError):
+        Graph.from_numpy(np.array([[0.0, math.inf], [0.0, 0.0]]))
+    with pytest.raises(ValueError):
+        Graph.from_numpy(np.array([[0.0, 1e12], [0.0, 0.0]]))
+
+
+def test_shortest_paths():
+    g = Graph()
+    for a, b, w in [(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 1)]:
+        g.add_edge(a, b, w)
+    g.add_vertex(9)
+    assert g.dijkstra(0) == {0: 0, 1: 3, 2: 1, 3: 4}
+    assert g.bellman_ford(0) == g.dijkstra(0)
+    assert g.shortest_path(0, 3) == [0, 2, 1, 3]
+    assert g.shortest_path(0, 9) is None
+    with pytest.raises(KeyError):
+        g.dijkstra(5)
+    with pytest.raises(KeyError):
+        g.shortest_path(0, 5)
+    g.add_edge(2, 3, -3)
+    with pytest.raises(ValueError):
+        g.dijkstra(0)
+    assert g.bellman_ford(0)[3] == -2
+    assert g.shortest_path(0, 3) == [0, 2, 3]
+    g.add_edge(3, 2, 1)
+    with pytest.raises(ValueError):
+        g.bellman_ford(0)
+
+
+def test_centrality():
+    # a directed cycle ranks every vertex alike
+    cycle = Graph()
+    for v in range(5):
+        cycle.add_edge(v, (v + 1) % 5)
+    ranks = cycle.pagerank()
+    assert sorted(ranks) == list(range(5))
+    assert all(r == pytest.approx(0.2) for r in ranks.values())
+    # a star into 0: the center is the authority, the leaves the hubs
+    star = Graph()
+    for v in range(1, 6):
+        star.add_edge(v, 0)
+    ranks = star.pagerank(damping=0.9)
+    assert sum(ranks.values()) == pytest.approx(1.0)
+    assert max(ranks, key=ranks.get) == 0
+    hubs, authorities = star.hits()
+    assert authorities[0] == pytest.approx(1.0)
+    assert hubs[0] == pytest.approx(0.0)
+    assert all(hubs[v] == pytest.approx(0.2) for v in range(1, 6))
+    with pytest.raises(ValueError):
+        star.pagerank(damping=1.5)
+
+
+def test_communities():
+    g = two_triangles()
+    communities = g.label_propagation(seed=3)
+    assert sorted(v for c in communities for v in c) == list(range(6))
+    assert communities == g.label_propagation(seed=3)
+    split = [[0, 1, 2], [3, 4, 5]]
+    assert g.modularity(split) == pytest.approx(5 / 14)
+    assert g.modularity(split) > g.modularity([list(range(6))])
@@ -0,0 +1,259 @@
+use std::{collections::HashMap, hash::Hash, ops::Deref};
+
+use crate::memory::{hash_map_bytes, vec_bytes, MemoryFootprint};
+use crate::small_vec::SmallVec;
+
+// Storage for the (neighbor, weight) list of a vertex. Graphs use a Vec by
+// default; SmallNeighbors keeps short lists inline instead, which saves an
+// allocation per vertex when most degrees are small.
+pub trait NeighborList<T>: Default + Clone + Deref<Target = [(T, i32)]> {
+    fn push(&mut self, item: (T, i32));
+    fn remove(&mut self, index: usize) -> (T, i32);
+    fn retain<F: FnMut(&(T, i32)) -> bool>(&mut self, keep: F);
+    // bytes allocated outside the list value itself
+    fn heap_bytes(&self) -> usize;
+}
+
+impl<T: Clone> NeighborList<T> for Vec<(T, i32)> {
+    fn push(&mut self, item: (T, i32)) {
+        Vec::push(self, item)
+    }
+    fn remove(&mut self, index: usize) -> (T, i32) {
+        Vec::remove(self, index)
+    }
+    fn retain<F: FnMut(&(T, i32)) -> bool>(&mut self, keep: F) {
+        Vec::retain(self, keep)
+    }
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(self)
+    }
+}
+
+pub type SmallNeighbors<T, const N: usize> = SmallVec<(T, i32), N>;
+
+impl<T: Clone, const N: usize> NeighborList<T> for SmallNeighbors<T, N> {
+    fn push(&mut self, item: (T, i32)) {
+        SmallVec::push(self, item)
+    }
+    fn remove(&mut self, index: usize) -> (T, i32) {
+        SmallVec::remove(self, index)
+    }
+    fn retain<F: FnMut(&(T, i32)) -> bool>(&mut self, keep: F) {
+        SmallVec::retain(self, keep)
+    }
+    fn heap_bytes(&self) -> usize {
+        SmallVec::heap_bytes(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Vertex<T, S = Vec<(T, i32)>> {
+    pub key: T,
+    pub neighbors: S,
+}
+
+impl<T: Clone + PartialEq> Vertex<T> {
+    pub fn new(key: T) -> Self {
+        Self::with_storage(key)
+    }
+}
+
+impl<T: Clone + PartialEq, S: NeighborList<T>> Vertex<T, S> {
+    pub fn with_storage(key: T) -> Self {
+        Self {
+            key,
+            neighbors: S::default(),
+        }
+    }
+
+    pub fn adjacent_key(&self, key: &T) -> bool {
+        for (nbr, _wt) in self.neighbors.iter() {
+            if nbr == key {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn add_neighbor(&mut self, nbr: T, wt: i32) {
+        self.neighbors.push((nbr, wt));
+    }
+
+    pub fn get_neighbors(&self) -> Vec<&T> {
+        let mut nbrs = Vec::new();
+        for (nbr, _wt) in self.neighbors.iter() {
+            nbrs.push(nbr);
+        }
+        nbrs
+    }
+
+    pub fn get_nbr_weight(&self, key: &T) -> &i32 {
+        for (nbr, wt) in self.neighbors.iter() {
+            if nbr == key {
+                return wt;
+            }
+        }
+        &0
+    }
+}
+
+#[derive(Debug)]
+pub struct Graph<T, S = Vec<(T, i32)>> {
+    vertnums: u32,
+    edgenums: u32,
+    vertics: HashMap<T, Vertex<T, S>>,
+}
+
+impl<T: Hash + Eq + PartialEq + Clone, S: NeighborList<T>> Default for Graph<T, S> {
+    fn default() -> Self {
+        Self::with_storage()
+    }
+}
+
+impl<T: Hash + Eq + PartialEq + Clone> Graph<T> {
+    pub fn new() -> Self {
+        Self::with_storage()
+    }
+}
+
+// e.g. Graph::<u32, SmallNeighbors<u32, 4>>::with_storage()
+impl<T: Hash + Eq + PartialEq + Clone, S: NeighborList<T>> Graph<T, S> {
+    pub fn with_storage() -> Self {
+        Self {
+            vertnums: 0,
+            edgenums: 0,
+            vertics: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertnums == 0
+    }
+    pub fn vertex_num(&self) -> u32 {
+        self.vertnums
+    }
+    pub fn edge_num(&self) -> u32 {
+        self.edgenums
+    }
+
+    pub fn contains(&self, key: &T) -> bool {
+        for (nbr, _vertex) in self.vertics.iter() {
+            if nbr == key {
+                return true;
+            }
+        }
+        false
+    }
+
+    // re-adding a key replaces it with a fresh vertex without out-edges,
+    // edges pointing to it from other vertices are kept
+    pub fn add_vertex(&mut self, key: &T) -> Option<Vertex<T, S>> {
+        let vertex = Vertex::with_storage(key.clone());
+        let old_vertex = self.vertics.insert(key.clone(), vertex);
+        match &old_vertex {
+            Some(old) => self.edgenums -= old.neighbors.len() as u32,
+            None => self.vertnums += 1,
+        }
+        old_vertex
+    }
+
+    pub fn get_vertex(&self, key: &T) -> Option<&Vertex<T, S>> {
+        if let Some(ver) = self.vertics.get(key) {
+            Some(ver)
+        } else {
+            None
+        }
+    }
+
+    pub fn vertex_keys(&self) -> Vec<T> {
+        let mut keys = Vec::new();
+        for key in self.vertics.keys() {
+            keys.push(key.clone());
+        }
+        keys
+    }
+
+    // O(V + E): the graph keeps no in-lists, so every other list is
+    // scanned for edges into key. arena_graph::ArenaGraph removes a vertex
+    // in O(degree) where that matters.
+    pub fn remove_vertex(&mut self, key: &T) -> Option<Vertex<T, S>> {
+        let old_vertex = self.vertics.remove(key)?;
+        self.vertnums -= 1;
+        self.edgenums -= old_vertex.neighbors.len() as u32;
+
+        for vt in self.vertics.values_mut() {
+            let before = vt.neighbors.len();
+            vt.neighbors.retain(|(k, _)| k != key);
+            self.edgenums -= (before - vt.neighbors.len()) as u32;
+        }
+        Some(old_vertex)
+    }
+
+    pub fn add_edge(&mut self, from: &T, to: &T, wt: i32) {
+        if !self.contains(from) {
+            self.add_vertex(from);
+        }
+        if !self.contains(to) {
+            self.add_vertex(to);
+        }
+        self.edgenums += 1;
+        self.vertics
+            .get_mut(from)
+            .unwrap()
+            .add_neighbor(to.clone(), wt);
+    }
+
+    // removes one from -> to edge and returns its weight
+    pub fn remove_edge(&mut self, from: &T, to: &T) -> Option<i32> {
+        let vt = self.vertics.get_mut(from)?;
+        let pos = vt.neighbors.iter().position(|(k, _)| k == to)?;
+        self.edgenums -= 1;
+        Some(vt.neighbors.remove(pos).1)
+    }
+
+    // inline neighbor storage is part of the vertex table, only spilled
+    // lists count as neighbor lists
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            vertex_table: hash_map_bytes(&self.vertics),
+            neighbor_lists: self
+                .vertics
+                .values()
+                .map(|v| v.neighbors.heap_bytes())
+                .sum(),
+            indices: 0,
+        }
+    }
+
+    pub fn adjacent(&self, from: &T, to: &T) -> bool {
+        self.vertics.get(from).is_some_and(|vt| vt.adjacent_key(to))
+    }
+
+    // dense 0..n numbering of the vertices, used by the algorithm modules so
+    // they can work on plain vectors instead of hashing keys in inner loops
+    pub(crate) fn indexed(&self) -> Indexed<T> {
+        let keys = self.vertex_keys();
+        let index: HashMap<T, usize> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (k.clone(), i))
+            .collect();
+        let adj = keys
+            .iter()
+            .map(|k| {
+                self.vertics[k]
+                    .neighbors
+                    .iter()
+                    .map(|(nbr, wt)| (index[nbr], *wt))
+                    .collect()
+            })
+            .collect();
+        Indexed { keys, index, adj }
+    }
+}
+
+pub(crate) struct Indexed<T> {
+    pub keys: Vec<T>,
+    pub index: HashMap<T, usize>,
+    pub adj: Vec<Vec<(usize, i32)>>,
+}
@@ -0,0 +1,156 @@
+use crate::trie::{Trie, ROOT};
+
+// Dictionary matcher: a trie of the patterns plus, for every node, a
+// failure link to the longest proper suffix of its string that is also in
+// the trie. Text is scanned once, following failure links on mismatches,
+// so finding all matches is O(text + patterns + matches).
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    // ids of the patterns ending at a node
+    trie: Trie<u8, Vec<usize>>,
+    fail: Vec<usize>,
+    // the nearest node on the failure chain that ends a pattern
+    output: Vec<usize>,
+    lens: Vec<usize>,
+}
+
+// pattern ids are their indices in the pattern list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+const NONE: usize = usize::MAX;
+
+impl AhoCorasick {
+    // empty patterns never match
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = P>) -> Self {
+        let mut trie: Trie<u8, Vec<usize>> = Trie::new();
+        let mut lens = Vec::new();
+        for (id, p) in patterns.into_iter().enumerate() {
+            let p = p.as_ref();
+            lens.push(p.len());
+            if p.is_empty() {
+                continue;
+            }
+            match trie.get_mut(p) {
+                Some(ids) => ids.push(id),
+                None => {
+                    trie.insert(p, vec![id]);
+                }
+            }
+        }
+
+        let n = trie.nodes.len();
+        let mut fail = vec![ROOT; n];
+        let mut output = vec![NONE; n];
+        // bfs, so the failure target of a node is always done before it
+        let mut queue = std::collections::VecDeque::from([ROOT]);
+        while let Some(u) = queue.pop_front() {
+            for &(k, v) in trie.nodes[u].children.iter() {
+                if u != ROOT {
+                    let mut f = fail[u];
+                    fail[v] = loop {
+                        if let Ok(next) = trie.child(f, k) {
+                            break next;
+                        }
+                        if f == ROOT {
+                            break ROOT;
+                        }
+                        f = fail[f];
+                    };
+                }
+                let f = fail[v];
+                output[v] = if trie.nodes[f].value.is_some() {
+                    f
+                } else {
+                    output[f]
+                };
+                queue.push_back(v);
+            }
+        }
+        Self {
+            trie,
+            fail,
+            output,
+            lens,
+        }
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.lens.len()
+    }
+
+    // all matches, overlapping ones included, ordered by end and then from
+    // the longest pattern down. Takes any byte source, e.g.
+    // text.iter().copied() or the bytes of a reader.
+    pub fn find_iter<I: IntoIterator<Item = u8>>(&self, text: I) -> FindIter<'_, I::IntoIter> {
+        FindIter {
+            ac: self,
+            text: text.into_iter(),
+            state: ROOT,
+            pos: 0,
+            pending: NONE,
+            next_id: 0,
+        }
+    }
+
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        self.find_iter(text.iter().copied()).next().is_some()
+    }
+
+    fn step(&self, mut state: usize, b: u8) -> usize {
+        loop {
+            if let Ok(next) = self.trie.child(state, b) {
+                return next;
+            }
+            if state == ROOT {
+                return ROOT;
+            }
+            state = self.fail[state];
+        }
+    }
+}
+
+pub struct FindIter<'a, I> {
+    ac: &'a AhoCorasick,
+    text: I,
+    state: usize,
+    // bytes consumed so far
+    pos: usize,
+    // node whose patterns are being reported, and the next one of them
+    pending: usize,
+    next_id: usize,
+}
+
+impl<I: Iterator<Item = u8>> Iterator for FindIter<'_, I> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        loop {
+            while self.pending != NONE {
+                let ids = self.ac.trie.nodes[self.pending].value.as_ref().unwrap();
+                if let Some(&pattern) = ids.get(self.next_id) {
+                    self.next_id += 1;
+                    return Some(Match {
+                        pattern,
+                        start: self.pos - self.ac.lens[pattern],
+                        end: self.pos,
+                    });
+                }
+                self.pending = self.ac.output[self.pending];
+                self.next_id = 0;
+            }
+            let b = self.text.next()?;
+            self.state = self.ac.step(self.state, b);
+            self.pos += 1;
+            self.pending = if self.ac.trie.nodes[self.state].value.is_some() {
+                self.state
+            } else {
+                self.ac.output[self.state]
+            };
+        }
+    }
+}
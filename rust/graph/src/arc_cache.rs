@@ -0,0 +1,176 @@
+use std::{fmt, hash::Hash};
+
+use crate::cache::Cache;
+use crate::lru_cache::LruCache;
+
+// Adaptive replacement cache (Megiddo and Modha): entries seen once live
+// in the recency list t1 and entries seen again in the frequency list t2,
+// both LRU ordered and holding capacity entries together. The ghost lists
+// b1 and b2 remember the keys lately evicted from each. A put that hits a
+// ghost in b1 means t1 was evicting too early and raises the target size
+// of t1, one in b2 lowers it, so the split between recency and frequency
+// follows the workload. A scan of new keys only churns t1.
+pub struct ArcCache<K, V> {
+    t1: LruCache<K, V>,
+    t2: LruCache<K, V>,
+    b1: LruCache<K, ()>,
+    b2: LruCache<K, ()>,
+    // target size of t1
+    p: usize,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> ArcCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        // the lists never evict on their own, the balancing below does
+        Self {
+            t1: LruCache::new(usize::MAX),
+            t2: LruCache::new(usize::MAX),
+            b1: LruCache::new(usize::MAX),
+            b2: LruCache::new(usize::MAX),
+            p: 0,
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.t1.len() + self.t2.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // where the adaptation has put the size of the recency list
+    pub fn target_recent(&self) -> usize {
+        self.p
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.t1.contains_key(key) || self.t2.contains_key(key)
+    }
+
+    // a hit moves key to the frequency list
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if let Some(value) = self.t1.remove(key) {
+            self.t2.put(key.clone(), value);
+        }
+        self.t2.get(key)
+    }
+
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.t1.peek(key).or_else(|| self.t2.peek(key))
+    }
+
+    // the old value of key, if there was one
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if self.capacity == 0 {
+            return None;
+        }
+        if let Some(old) = self.t1.remove(&key) {
+            self.t2.put(key, value);
+            return Some(old);
+        }
+        if self.t2.contains_key(&key) {
+            return self.t2.put(key, value);
+        }
+        let c = self.capacity;
+        if self.b1.contains_key(&key) {
+            let delta = (self.b2.len() / self.b1.len()).max(1);
+            self.p = (self.p + delta).min(c);
+            self.b1.remove(&key);
+            self.make_room(false);
+            self.t2.put(key, value);
+            return None;
+        }
+        if self.b2.contains_key(&key) {
+            let delta = (self.b1.len() / self.b2.len()).max(1);
+            self.p = self.p.saturating_sub(delta);
+            self.b2.remove(&key);
+            self.make_room(true);
+            self.t2.put(key, value);
+            return None;
+        }
+        // a new key: keep the lists to c entries on the t1 side and 2c in
+        // all, ghosts included
+        if self.t1.len() + self.b1.len() >= c {
+            if self.t1.len() < c {
+                self.b1.pop_lru();
+                self.make_room(false);
+            } else {
+                self.t1.pop_lru();
+            }
+        } else {
+            let total = self.len() + self.b1.len() + self.b2.len();
+            if total >= 2 * c {
+                self.b2.pop_lru();
+            }
+            self.make_room(false);
+        }
+        self.t1.put(key, value);
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.t1.remove(key).or_else(|| self.t2.remove(key))
+    }
+
+    pub fn clear(&mut self) {
+        self.t1.clear();
+        self.t2.clear();
+        self.b1.clear();
+        self.b2.clear();
+        self.p = 0;
+    }
+
+    // If full, evicts one entry to its ghost list, from t1 when it is over
+    // target. A hit in b2 also takes from t1 at exactly the target.
+    fn make_room(&mut self, hit_in_b2: bool) {
+        if self.len() < self.capacity {
+            return;
+        }
+        let from_t1 = !self.t1.is_empty()
+            && (self.t1.len() > self.p || (hit_in_b2 && self.t1.len() == self.p));
+        if from_t1 {
+            let (key, _) = self.t1.pop_lru().unwrap();
+            self.b1.put(key, ());
+        } else if let Some((key, _)) = self.t2.pop_lru() {
+            self.b2.put(key, ());
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for ArcCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        ArcCache::get(self, key)
+    }
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        ArcCache::put(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        ArcCache::remove(self, key)
+    }
+    fn contains_key(&self, key: &K) -> bool {
+        ArcCache::contains_key(self, key)
+    }
+    fn len(&self) -> usize {
+        ArcCache::len(self)
+    }
+    fn capacity(&self) -> usize {
+        ArcCache::capacity(self)
+    }
+}
+
+impl<K: Hash + Eq + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for ArcCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcCache")
+            .field("recent", &self.t1)
+            .field("frequent", &self.t2)
+            .field("target_recent", &self.p)
+            .finish()
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Generational slab: values live in one Vec and are addressed by an index
+// plus the generation of the slot. Removing a value bumps the generation,
+// so ids handed out before the removal stop resolving instead of silently
+// pointing at whatever reuses the slot.
+
+pub struct Id<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+// manual impls, deriving would put the bounds on T
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index, self.generation) == (other.index, other.generation)
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> std::hash::Hash for Id<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (self.index, self.generation).hash(state);
+    }
+}
+
+impl<T> std::fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Id({}v{})", self.index, self.generation)
+    }
+}
+
+impl<T> Id<T> {
+    pub(crate) fn new(index: u32, generation: u32) -> Self {
+        Self {
+            index,
+            generation,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    // the same slot after an append moved it along by offset
+    pub(crate) fn shifted(self, offset: u32) -> Self {
+        Self::new(self.index + offset, self.generation)
+    }
+}
+
+enum Slot<T> {
+    Occupied { generation: u32, value: T },
+    Free { generation: u32, next: Option<u32> },
+}
+
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Option<u32>,
+    len: usize,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: None,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(n),
+            free: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> Id<T> {
+        self.len += 1;
+        if let Some(index) = self.free {
+            let slot = &mut self.slots[index as usize];
+            let Slot::Free { generation, next } = *slot else {
+                unreachable!("free list points at an occupied slot");
+            };
+            self.free = next;
+            *slot = Slot::Occupied { generation, value };
+            return Id::new(index, generation);
+        }
+        let index = u32::try_from(self.slots.len()).expect("arena is full");
+        self.slots.push(Slot::Occupied {
+            generation: 0,
+            value,
+        });
+        Id::new(index, 0)
+    }
+
+    pub fn remove(&mut self, id: Id<T>) -> Option<T> {
+        self.get(id)?;
+        let generation = id.generation.wrapping_add(1);
+        let old = std::mem::replace(
+            &mut self.slots[id.index as usize],
+            Slot::Free {
+                generation,
+                next: self.free,
+            },
+        );
+        // a slot whose generation wrapped is retired, never reused
+        if generation != 0 {
+            self.free = Some(id.index);
+        }
+        self.len -= 1;
+        match old {
+            Slot::Occupied { value, .. } => Some(value),
+            Slot::Free { .. } => unreachable!(),
+        }
+    }
+
+    pub fn contains(&self, id: Id<T>) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn get(&self, id: Id<T>) -> Option<&T> {
+        match self.slots.get(id.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, id: Id<T>) -> Option<&mut T> {
+        match self.slots.get_mut(id.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Id<T>, &T)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| match slot {
+                Slot::Occupied { generation, value } => {
+                    Some((Id::new(i as u32, *generation), value))
+                }
+                Slot::Free { .. } => None,
+            })
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.free = None;
+        self.len = 0;
+    }
+
+    // slot access without a generation check, for structures that keep
+    // raw slot indices to live values internally
+    pub(crate) fn at(&self, index: u32) -> &T {
+        match &self.slots[index as usize] {
+            Slot::Occupied { value, .. } => value,
+            Slot::Free { .. } => panic!("slot {index} is free"),
+        }
+    }
+
+    pub(crate) fn at_mut(&mut self, index: u32) -> &mut T {
+        match &mut self.slots[index as usize] {
+            Slot::Occupied { value, .. } => value,
+            Slot::Free { .. } => panic!("slot {index} is free"),
+        }
+    }
+
+    pub(crate) fn id_at(&self, index: u32) -> Id<T> {
+        match &self.slots[index as usize] {
+            Slot::Occupied { generation, .. } => Id::new(index, *generation),
+            Slot::Free { .. } => panic!("slot {index} is free"),
+        }
+    }
+
+    // Moves the slots of other, free ones too, in after these and returns
+    // the offset they moved by: ids into other resolve here once shifted,
+    // and relink gets every value so that its raw indices can follow.
+    pub(crate) fn append(&mut self, other: Self, mut relink: impl FnMut(&mut T, u32)) -> u32 {
+        let offset = u32::try_from(self.slots.len()).expect("arena is full");
+        assert!(
+            u32::try_from(self.slots.len() + other.slots.len()).is_ok(),
+            "arena is full"
+        );
+        for mut slot in other.slots {
+            match &mut slot {
+                Slot::Occupied { value, .. } => relink(value, offset),
+                Slot::Free { next, .. } => *next = next.map(|n| n + offset),
+            }
+            self.slots.push(slot);
+        }
+        // other's free list goes in front of ours, ending where ours began
+        if let Some(head) = other.free {
+            let mut at = head + offset;
+            while let Slot::Free { next: Some(n), .. } = self.slots[at as usize] {
+                at = n;
+            }
+            if let Slot::Free { next, .. } = &mut self.slots[at as usize] {
+                *next = self.free;
+            }
+            self.free = Some(head + offset);
+        }
+        self.len += other.len;
+        offset
+    }
+}
+
+// Tells which arena an id was handed out by, once ids outlive the arena
+// through appends: each arena gets a tag no other has had, and an append
+// files the tags of the arena taken in under the offset it moved by.
+#[derive(Debug)]
+pub(crate) struct Tags {
+    own: u64,
+    appended: HashMap<u64, u32>,
+}
+
+static NEXT_TAG: AtomicU64 = AtomicU64::new(0);
+
+impl Tags {
+    pub(crate) fn new() -> Self {
+        Self {
+            own: NEXT_TAG.fetch_add(1, Ordering::Relaxed),
+            appended: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn own(&self) -> u64 {
+        self.own
+    }
+
+    // how far ids under tag moved to land here, None for another arena's
+    pub(crate) fn offset(&self, tag: u64) -> Option<u32> {
+        if tag == self.own {
+            return Some(0);
+        }
+        self.appended.get(&tag).copied()
+    }
+
+    pub(crate) fn absorb(&mut self, other: Self, offset: u32) {
+        self.appended.insert(other.own, offset);
+        for (tag, at) in other.appended {
+            self.appended.insert(tag, at + offset);
+        }
+    }
+}
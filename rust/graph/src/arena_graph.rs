@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use crate::arena::{Arena, Id};
+use crate::csr::CsrGraph;
+
+// Directed graph whose vertices and edges live in two generational arenas.
+// Every edge is linked into the out-list of its source and the in-list of
+// its target, so removing an edge is O(1) and removing a vertex is
+// O(degree), with no per-vertex allocations. Ids of removed vertices and
+// edges stop resolving instead of aliasing newer ones.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VertexId {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeId {
+    index: u32,
+    generation: u32,
+}
+
+struct Node<T> {
+    value: T,
+    first_out: Option<u32>,
+    first_in: Option<u32>,
+    out_degree: usize,
+    in_degree: usize,
+}
+
+struct Link {
+    from: u32,
+    to: u32,
+    weight: i32,
+    prev_out: Option<u32>,
+    next_out: Option<u32>,
+    prev_in: Option<u32>,
+    next_in: Option<u32>,
+}
+
+pub struct ArenaGraph<T> {
+    nodes: Arena<Node<T>>,
+    links: Arena<Link>,
+}
+
+impl<T> Default for ArenaGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ArenaGraph<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Arena::new(),
+            links: Arena::new(),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn add_vertex(&mut self, value: T) -> VertexId {
+        vertex_id(self.nodes.insert(Node {
+            value,
+            first_out: None,
+            first_in: None,
+            out_degree: 0,
+            in_degree: 0,
+        }))
+    }
+
+    pub fn contains_vertex(&self, v: VertexId) -> bool {
+        self.nodes.contains(node(v))
+    }
+
+    pub fn vertex(&self, v: VertexId) -> Option<&T> {
+        self.nodes.get(node(v)).map(|n| &n.value)
+    }
+
+    pub fn vertex_mut(&mut self, v: VertexId) -> Option<&mut T> {
+        self.nodes.get_mut(node(v)).map(|n| &mut n.value)
+    }
+
+    pub fn vertices(&self) -> impl Iterator<Item = (VertexId, &T)> + '_ {
+        self.nodes.iter().map(|(id, n)| (vertex_id(id), &n.value))
+    }
+
+    pub fn out_degree(&self, v: VertexId) -> Option<usize> {
+        self.nodes.get(node(v)).map(|n| n.out_degree)
+    }
+
+    pub fn in_degree(&self, v: VertexId) -> Option<usize> {
+        self.nodes.get(node(v)).map(|n| n.in_degree)
+    }
+
+    // drops every edge at v, O(degree)
+    pub fn remove_vertex(&mut self, v: VertexId) -> Option<T> {
+        let mut out = self.nodes.get(node(v))?.first_out;
+        while let Some(e) = out {
+            out = self.links.at(e).next_out;
+            self.unlink(e);
+        }
+        // read after the out-edges are gone, self loops were unlinked there
+        let mut inc = self.nodes.at(v.index).first_in;
+        while let Some(e) = inc {
+            inc = self.links.at(e).next_in;
+            self.unlink(e);
+        }
+        self.nodes.remove(node(v)).map(|n| n.value)
+    }
+
+    // None when either endpoint is not a live vertex
+    pub fn add_edge(&mut self, from: VertexId, to: VertexId, weight: i32) -> Option<EdgeId> {
+        if !self.contains_vertex(from) || !self.contains_vertex(to) {
+            return None;
+        }
+        let next_out = self.nodes.at(from.index).first_out;
+        let next_in = self.nodes.at(to.index).first_in;
+        let id = self.links.insert(Link {
+            from: from.index,
+            to: to.index,
+            weight,
+            prev_out: None,
+            next_out,
+            prev_in: None,
+            next_in,
+        });
+        let e = id.index() as u32;
+        if let Some(n) = next_out {
+            self.links.at_mut(n).prev_out = Some(e);
+        }
+        if let Some(n) = next_in {
+            self.links.at_mut(n).prev_in = Some(e);
+        }
+        let src = self.nodes.at_mut(from.index);
+        src.first_out = Some(e);
+        src.out_degree += 1;
+        let dst = self.nodes.at_mut(to.index);
+        dst.first_in = Some(e);
+        dst.in_degree += 1;
+        Some(EdgeId {
+            index: e,
+            generation: id.generation(),
+        })
+    }
+
+    // returns the weight of the removed edge, O(1)
+    pub fn remove_edge(&mut self, e: EdgeId) -> Option<i32> {
+        self.links.get(link(e))?;
+        Some(self.unlink(e.index))
+    }
+
+    // (from, to, weight)
+    pub fn edge(&self, e: EdgeId) -> Option<(VertexId, VertexId, i32)> {
+        let l = self.links.get(link(e))?;
+        Some((self.id_of(l.from), self.id_of(l.to), l.weight))
+    }
+
+    pub fn set_weight(&mut self, e: EdgeId, weight: i32) -> bool {
+        if let Some(l) = self.links.get_mut(link(e)) {
+            l.weight = weight;
+            true
+        } else {
+            false
+        }
+    }
+
+    // some edge from -> to, O(out-degree of from)
+    pub fn find_edge(&self, from: VertexId, to: VertexId) -> Option<EdgeId> {
+        if !self.contains_vertex(to) {
+            return None;
+        }
+        self.out_edges(from)
+            .find(|&(_, v, _)| v == to)
+            .map(|(e, _, _)| e)
+    }
+
+    // (edge, target, weight) for the out-edges of v, newest first; empty
+    // for a removed vertex
+    pub fn out_edges(&self, v: VertexId) -> impl Iterator<Item = (EdgeId, VertexId, i32)> + '_ {
+        let first = self.nodes.get(node(v)).and_then(|n| n.first_out);
+        self.walk(first, |l| l.next_out)
+            .map(|(e, l)| (e, self.id_of(l.to), l.weight))
+    }
+
+    // (edge, source, weight) for the in-edges of v, newest first
+    pub fn in_edges(&self, v: VertexId) -> impl Iterator<Item = (EdgeId, VertexId, i32)> + '_ {
+        let first = self.nodes.get(node(v)).and_then(|n| n.first_in);
+        self.walk(first, |l| l.next_in)
+            .map(|(e, l)| (e, self.id_of(l.from), l.weight))
+    }
+
+    pub fn neighbors(&self, v: VertexId) -> impl Iterator<Item = VertexId> + '_ {
+        self.out_edges(v).map(|(_, u, _)| u)
+    }
+
+    // snapshot for the CSR algorithms, with the vertex id of every CSR
+    // vertex
+    pub fn to_csr(&self) -> (CsrGraph, Vec<VertexId>) {
+        let ids: Vec<VertexId> = self.vertices().map(|(v, _)| v).collect();
+        let dense: HashMap<u32, usize> =
+            ids.iter().enumerate().map(|(i, v)| (v.index, i)).collect();
+        let edges: Vec<_> = ids
+            .iter()
+            .flat_map(|&v| {
+                self.out_edges(v)
+                    .map(|(_, u, wt)| (dense[&v.index], dense[&u.index], wt))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        (CsrGraph::from_edges(ids.len(), &edges), ids)
+    }
+
+    fn id_of(&self, index: u32) -> VertexId {
+        vertex_id(self.nodes.id_at(index))
+    }
+
+    fn walk<F>(&self, first: Option<u32>, next: F) -> impl Iterator<Item = (EdgeId, &Link)> + '_
+    where
+        F: Fn(&Link) -> Option<u32> + 'static,
+    {
+        let mut cur = first;
+        std::iter::from_fn(move || {
+            let e = cur?;
+            let l = self.links.at(e);
+            cur = next(l);
+            let id = self.links.id_at(e);
+            Some((
+                EdgeId {
+                    index: e,
+                    generation: id.generation(),
+                },
+                l,
+            ))
+        })
+    }
+
+    fn unlink(&mut self, e: u32) -> i32 {
+        let l = self.links.at(e);
+        let (from, to) = (l.from, l.to);
+        let (prev_out, next_out, prev_in, next_in) = (l.prev_out, l.next_out, l.prev_in, l.next_in);
+        match prev_out {
+            Some(p) => self.links.at_mut(p).next_out = next_out,
+            None => self.nodes.at_mut(from).first_out = next_out,
+        }
+        if let Some(n) = next_out {
+            self.links.at_mut(n).prev_out = prev_out;
+        }
+        match prev_in {
+            Some(p) => self.links.at_mut(p).next_in = next_in,
+            None => self.nodes.at_mut(to).first_in = next_in,
+        }
+        if let Some(n) = next_in {
+            self.links.at_mut(n).prev_in = prev_in;
+        }
+        self.nodes.at_mut(from).out_degree -= 1;
+        self.nodes.at_mut(to).in_degree -= 1;
+        let id = self.links.id_at(e);
+        self.links.remove(id).unwrap().weight
+    }
+}
+
+fn node<T>(v: VertexId) -> Id<Node<T>> {
+    Id::new(v.index, v.generation)
+}
+
+fn link(e: EdgeId) -> Id<Link> {
+    Id::new(e.index, e.generation)
+}
+
+fn vertex_id<T>(id: Id<Node<T>>) -> VertexId {
+    VertexId {
+        index: id.index() as u32,
+        generation: id.generation(),
+    }
+}
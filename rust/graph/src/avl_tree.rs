@@ -0,0 +1,375 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+use crate::ordered_map::OrderedMap;
+
+// Ordered map on an AVL tree: the heights of the two subtrees of every
+// node differ by at most one, restored with single or double rotations on
+// the way back up from an insert or remove. Height is at most about
+// 1.44 log2 n. Debug builds assert the balance of every node touched.
+//
+// Nodes also keep their subtree size, which gives kth and rank in
+// O(log n), e.g. for running medians and percentiles.
+pub struct AvlTreeMap<K, V> {
+    root: Link<K, V>,
+}
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    height: u8,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn height<K, V>(link: &Link<K, V>) -> u8 {
+    link.as_ref().map_or(0, |n| n.height)
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |n| n.size)
+}
+
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    fn balance(&self) -> i32 {
+        height(&self.left) as i32 - height(&self.right) as i32
+    }
+}
+
+fn rotate_right<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut pivot = node.left.take().unwrap();
+    node.left = pivot.right.take();
+    node.update();
+    pivot.right = Some(node);
+    pivot.update();
+    pivot
+}
+
+fn rotate_left<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut pivot = node.right.take().unwrap();
+    node.right = pivot.left.take();
+    node.update();
+    pivot.left = Some(node);
+    pivot.update();
+    pivot
+}
+
+// node's subtrees are balanced and differ in height by at most two
+fn rebalance<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    node.update();
+    let balance = node.balance();
+    if balance > 1 {
+        if node.left.as_ref().unwrap().balance() < 0 {
+            node.left = Some(rotate_left(node.left.take().unwrap()));
+        }
+        node = rotate_right(node);
+    } else if balance < -1 {
+        if node.right.as_ref().unwrap().balance() > 0 {
+            node.right = Some(rotate_right(node.right.take().unwrap()));
+        }
+        node = rotate_left(node);
+    }
+    debug_assert!(node.balance().abs() <= 1, "avl node out of balance");
+    debug_assert_eq!(
+        node.height,
+        1 + height(&node.left).max(height(&node.right)),
+        "avl height out of date"
+    );
+    node
+}
+
+fn insert<K: Ord, V>(link: Link<K, V>, key: K, value: V, old: &mut Option<V>) -> Box<Node<K, V>> {
+    let Some(mut node) = link else {
+        return Box::new(Node {
+            key,
+            value,
+            height: 1,
+            size: 1,
+            left: None,
+            right: None,
+        });
+    };
+    match key.cmp(&node.key) {
+        Ordering::Less => node.left = Some(insert(node.left.take(), key, value, old)),
+        Ordering::Greater => node.right = Some(insert(node.right.take(), key, value, old)),
+        Ordering::Equal => {
+            *old = Some(std::mem::replace(&mut node.value, value));
+            return node;
+        }
+    }
+    rebalance(node)
+}
+
+// (rest of the tree, removed minimum)
+fn remove_min<K, V>(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let (rest, min) = remove_min(left);
+            node.left = rest;
+            (Some(rebalance(node)), min)
+        }
+    }
+}
+
+fn remove<K: Borrow<Q>, V, Q: Ord + ?Sized>(
+    link: Link<K, V>,
+    key: &Q,
+    removed: &mut Option<(K, V)>,
+) -> Link<K, V> {
+    let mut node = link?;
+    match key.cmp(node.key.borrow()) {
+        Ordering::Less => node.left = remove(node.left.take(), key, removed),
+        Ordering::Greater => node.right = remove(node.right.take(), key, removed),
+        Ordering::Equal => {
+            let (left, right) = (node.left.take(), node.right.take());
+            *removed = Some((node.key, node.value));
+            let (left, right) = match (left, right) {
+                (Some(left), Some(right)) => (left, right),
+                (left, None) => return left,
+                (None, right) => return right,
+            };
+            let (rest, mut min) = remove_min(right);
+            min.left = Some(left);
+            min.right = rest;
+            return Some(rebalance(min));
+        }
+    }
+    Some(rebalance(node))
+}
+
+impl<K: Ord, V> Default for AvlTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> AvlTreeMap<K, V> {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // number of levels, 0 for an empty map
+    pub fn height(&self) -> usize {
+        height(&self.root) as usize
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut old = None;
+        self.root = Some(insert(self.root.take(), key, value, &mut old));
+        old
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut removed = None;
+        self.root = remove(self.root.take(), key, &mut removed);
+        removed.map(|(_, value)| value)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        None
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => link = &mut node.left,
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => link = &mut node.right,
+            }
+        }
+        None
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    // the entry with k smaller keys
+    pub fn kth(&self, mut k: usize) -> Option<(&K, &V)> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left = size(&node.left);
+            match k.cmp(&left) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    link = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    // number of keys smaller than key, whether or not key is present
+    pub fn rank<Q: Ord + ?Sized>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return rank + size(&node.left),
+                Ordering::Greater => {
+                    rank += size(&node.left) + 1;
+                    link = &node.right;
+                }
+            }
+        }
+        rank
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        // the path to the first key in range, keeping the nodes still to
+        // be visited
+        let mut stack = Vec::new();
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let after_start = match range.start_bound() {
+                Bound::Unbounded => true,
+                Bound::Included(s) => node.key >= *s,
+                Bound::Excluded(s) => node.key > *s,
+            };
+            if after_start {
+                stack.push(&**node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        Range { stack, range }
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for AvlTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for AvlTreeMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        AvlTreeMap::insert(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        AvlTreeMap::remove(self, key)
+    }
+    fn get(&self, key: &K) -> Option<&V> {
+        AvlTreeMap::get(self, key)
+    }
+    fn first(&self) -> Option<(&K, &V)> {
+        AvlTreeMap::first(self)
+    }
+    fn last(&self) -> Option<(&K, &V)> {
+        AvlTreeMap::last(self)
+    }
+    fn len(&self) -> usize {
+        AvlTreeMap::len(self)
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for AvlTreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Range<'a, K, V, R> {
+    stack: Vec<&'a Node<K, V>>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        let inside = match self.range.end_bound() {
+            Bound::Unbounded => true,
+            Bound::Included(e) => node.key <= *e,
+            Bound::Excluded(e) => node.key < *e,
+        };
+        if !inside {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(n) = link {
+            self.stack.push(n);
+            link = &n.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
@@ -0,0 +1,498 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+use crate::ordered_map::OrderedMap;
+
+// B+ tree of minimum degree B: the entries live only in the leaves, which
+// are linked left to right, and the inner nodes hold copies of keys to
+// route searches. Every node but the root has between B - 1 and 2B - 1
+// keys. A range scan finds its first leaf in O(log n) and then just walks
+// the leaf list.
+//
+// Nodes live in one Vec and link to each other by index. Inner key i
+// separates child i, whose keys are smaller, from child i + 1.
+pub struct BPlusTree<K, V, const B: usize = 6> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    root: usize,
+    len: usize,
+}
+
+struct Node<K, V> {
+    keys: Vec<K>,
+    // leaves only
+    vals: Vec<V>,
+    // inner nodes only
+    children: Vec<usize>,
+    // next leaf to the right
+    next: usize,
+}
+
+const NIL: usize = usize::MAX;
+
+impl<K, V> Node<K, V> {
+    fn leaf() -> Self {
+        Self {
+            keys: Vec::new(),
+            vals: Vec::new(),
+            children: Vec::new(),
+            next: NIL,
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> Default for BPlusTree<K, V, B> {
+    fn default() -> Self {
+        assert!(B >= 2, "a b+ tree needs a minimum degree of at least 2");
+        Self {
+            nodes: vec![Some(Node::leaf())],
+            free: Vec::new(),
+            root: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> BPlusTree<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> BPlusTree<K, V, B> {
+    // Builds the tree bottom up from entries with strictly increasing keys
+    // in O(n), packing the nodes as full as the bounds allow.
+    pub fn from_sorted<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+        let (keys, vals): (Vec<K>, Vec<V>) = entries.into_iter().unzip();
+        assert!(
+            keys.windows(2).all(|w| w[0] < w[1]),
+            "bulk load needs strictly increasing keys"
+        );
+        let mut tree = Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: 0,
+            len: keys.len(),
+        };
+
+        // (node, smallest key below it) for the level being built
+        let mut level = Vec::new();
+        let mut keys = keys.into_iter();
+        let mut vals = vals.into_iter();
+        for size in chunk_sizes(tree.len, 2 * B - 1, B - 1) {
+            let leaf = Node {
+                keys: keys.by_ref().take(size).collect(),
+                vals: vals.by_ref().take(size).collect(),
+                ..Node::leaf()
+            };
+            let min = leaf.keys[0].clone();
+            if let Some(&(prev, _)) = level.last() {
+                tree.node_mut(prev).next = tree.nodes.len();
+            }
+            level.push((tree.nodes.len(), min));
+            tree.nodes.push(Some(leaf));
+        }
+        while level.len() > 1 {
+            let mut below = level.into_iter();
+            level = Vec::new();
+            for size in chunk_sizes(below.len(), 2 * B, B) {
+                let group: Vec<(usize, K)> = below.by_ref().take(size).collect();
+                let mut group = group.into_iter();
+                let (first, min) = group.next().unwrap();
+                let (children, keys) = group.unzip::<_, _, Vec<_>, Vec<_>>();
+                let mut node = Node {
+                    keys,
+                    children,
+                    ..Node::leaf()
+                };
+                node.children.insert(0, first);
+                level.push((tree.nodes.len(), min));
+                tree.nodes.push(Some(node));
+            }
+        }
+        match level.first() {
+            Some(&(root, _)) => tree.root = root,
+            None => tree.nodes.push(Some(Node::leaf())),
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // number of levels, a single leaf is height 1
+    pub fn height(&self) -> usize {
+        let mut node = self.node(self.root);
+        let mut height = 1;
+        while let Some(&child) = node.children.first() {
+            node = self.node(child);
+            height += 1;
+        }
+        height
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (leaf, mut path) = self.descend(&key);
+        let node = self.node_mut(leaf);
+        let i = match node.keys.binary_search(&key) {
+            Ok(i) => return Some(std::mem::replace(&mut node.vals[i], value)),
+            Err(i) => i,
+        };
+        node.keys.insert(i, key);
+        node.vals.insert(i, value);
+        self.len += 1;
+        let node = self.node_mut(leaf);
+        if node.keys.len() < 2 * B {
+            return None;
+        }
+
+        // split the leaf, then every inner node that overflows in turn
+        let right = Node {
+            keys: node.keys.split_off(B),
+            vals: node.vals.split_off(B),
+            children: Vec::new(),
+            next: node.next,
+        };
+        let mut sep = right.keys[0].clone();
+        let mut right = self.alloc(right);
+        self.node_mut(leaf).next = right;
+        while let Some((parent, i)) = path.pop() {
+            let node = self.node_mut(parent);
+            node.keys.insert(i, sep);
+            node.children.insert(i + 1, right);
+            if node.keys.len() < 2 * B {
+                return None;
+            }
+            let keys = node.keys.split_off(B + 1);
+            let children = node.children.split_off(B + 1);
+            sep = node.keys.pop().unwrap();
+            right = self.alloc(Node {
+                keys,
+                children,
+                ..Node::leaf()
+            });
+        }
+        let old = self.root;
+        self.root = self.alloc(Node {
+            keys: vec![sep],
+            children: vec![old, right],
+            ..Node::leaf()
+        });
+        None
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let (leaf, mut path) = self.descend(key);
+        let node = self.node_mut(leaf);
+        let i = node.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+        node.keys.remove(i);
+        let value = node.vals.remove(i);
+        self.len -= 1;
+
+        // refill or merge underfull nodes from the leaf up
+        let mut cur = leaf;
+        while let Some((parent, i)) = path.pop() {
+            if self.node(cur).keys.len() >= B - 1 {
+                return Some(value);
+            }
+            let siblings = self.node(parent).children.len();
+            if i > 0 && self.node(self.child(parent, i - 1)).keys.len() >= B {
+                self.borrow_from_left(parent, i);
+                return Some(value);
+            }
+            if i + 1 < siblings && self.node(self.child(parent, i + 1)).keys.len() >= B {
+                self.borrow_from_right(parent, i);
+                return Some(value);
+            }
+            self.merge_children(parent, if i > 0 { i - 1 } else { i });
+            cur = parent;
+        }
+        let root = self.node(self.root);
+        if root.keys.is_empty() && !root.is_leaf() {
+            let old = self.root;
+            self.root = root.children[0];
+            self.release(old);
+        }
+        Some(value)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let node = self.node(self.descend(key).0);
+        let i = node.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+        Some(&node.vals[i])
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let node = self.node_mut(self.descend(key).0);
+        let i = node.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+        Some(&mut node.vals[i])
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut node = self.node(self.root);
+        while let Some(&child) = node.children.first() {
+            node = self.node(child);
+        }
+        Some((node.keys.first()?, node.vals.first()?))
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut node = self.node(self.root);
+        while let Some(&child) = node.children.last() {
+            node = self.node(child);
+        }
+        Some((node.keys.last()?, node.vals.last()?))
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, B, RangeFull> {
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, B, R> {
+        let mut leaf = self.root;
+        while !self.node(leaf).is_leaf() {
+            let node = self.node(leaf);
+            // the leaf that holds or would hold the start key
+            let i = match range.start_bound() {
+                Bound::Unbounded => 0,
+                Bound::Included(s) | Bound::Excluded(s) => node.keys.partition_point(|k| k <= s),
+            };
+            leaf = node.children[i];
+        }
+        let keys = &self.node(leaf).keys;
+        let pos = match range.start_bound() {
+            Bound::Unbounded => 0,
+            Bound::Included(s) => keys.partition_point(|k| k < s),
+            Bound::Excluded(s) => keys.partition_point(|k| k <= s),
+        };
+        Range {
+            tree: self,
+            leaf,
+            pos,
+            range,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn node(&self, i: usize) -> &Node<K, V> {
+        self.nodes[i].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, i: usize) -> &mut Node<K, V> {
+        self.nodes[i].as_mut().unwrap()
+    }
+
+    fn child(&self, parent: usize, i: usize) -> usize {
+        self.node(parent).children[i]
+    }
+
+    // the leaf that holds or would hold key, with the (inner node, child
+    // index) pairs on the way down
+    fn descend<Q: Ord + ?Sized>(&self, key: &Q) -> (usize, Vec<(usize, usize)>)
+    where
+        K: Borrow<Q>,
+    {
+        let mut path = Vec::new();
+        let mut cur = self.root;
+        while !self.node(cur).is_leaf() {
+            let node = self.node(cur);
+            let i = node.keys.partition_point(|k| k.borrow() <= key);
+            path.push((cur, i));
+            cur = node.children[i];
+        }
+        (cur, path)
+    }
+
+    fn borrow_from_left(&mut self, parent: usize, i: usize) {
+        let (l, c) = (self.child(parent, i - 1), self.child(parent, i));
+        let mut left = self.nodes[l].take().unwrap();
+        let mut cur = self.nodes[c].take().unwrap();
+        let key = left.keys.pop().unwrap();
+        let sep = &mut self.node_mut(parent).keys[i - 1];
+        if cur.is_leaf() {
+            cur.vals.insert(0, left.vals.pop().unwrap());
+            *sep = key.clone();
+            cur.keys.insert(0, key);
+        } else {
+            cur.keys.insert(0, std::mem::replace(sep, key));
+            cur.children.insert(0, left.children.pop().unwrap());
+        }
+        self.nodes[l] = Some(left);
+        self.nodes[c] = Some(cur);
+    }
+
+    fn borrow_from_right(&mut self, parent: usize, i: usize) {
+        let (c, r) = (self.child(parent, i), self.child(parent, i + 1));
+        let mut cur = self.nodes[c].take().unwrap();
+        let mut right = self.nodes[r].take().unwrap();
+        let key = right.keys.remove(0);
+        let sep = &mut self.node_mut(parent).keys[i];
+        if cur.is_leaf() {
+            cur.vals.push(right.vals.remove(0));
+            cur.keys.push(key);
+            *sep = right.keys[0].clone();
+        } else {
+            cur.keys.push(std::mem::replace(sep, key));
+            cur.children.push(right.children.remove(0));
+        }
+        self.nodes[c] = Some(cur);
+        self.nodes[r] = Some(right);
+    }
+
+    // child i absorbs child i + 1; separator i moves down into inner nodes
+    // and is dropped for leaves
+    fn merge_children(&mut self, parent: usize, i: usize) {
+        let node = self.node_mut(parent);
+        let sep = node.keys.remove(i);
+        let r = node.children.remove(i + 1);
+        let l = node.children[i];
+        let right = self.nodes[r].take().unwrap();
+        self.free.push(r);
+        let left = self.node_mut(l);
+        if left.is_leaf() {
+            left.next = right.next;
+        } else {
+            left.keys.push(sep);
+        }
+        left.keys.extend(right.keys);
+        left.vals.extend(right.vals);
+        left.children.extend(right.children);
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, i: usize) {
+        self.nodes[i] = None;
+        self.free.push(i);
+    }
+}
+
+// Splits n items into as few chunks of at most max as possible, each with
+// at least min items; a single chunk may be smaller.
+fn chunk_sizes(n: usize, max: usize, min: usize) -> Vec<usize> {
+    let mut sizes = vec![max; n / max];
+    let rest = n - sizes.len() * max;
+    if rest > 0 {
+        sizes.push(rest);
+    }
+    let k = sizes.len();
+    if k >= 2 && sizes[k - 1] < min {
+        let both = sizes[k - 2] + sizes[k - 1];
+        sizes[k - 2] = both - both / 2;
+        sizes[k - 1] = both / 2;
+    }
+    sizes
+}
+
+impl<K: Ord + Clone, V, const B: usize> OrderedMap<K, V> for BPlusTree<K, V, B> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BPlusTree::insert(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BPlusTree::remove(self, key)
+    }
+    fn get(&self, key: &K) -> Option<&V> {
+        BPlusTree::get(self, key)
+    }
+    fn first(&self) -> Option<(&K, &V)> {
+        BPlusTree::first(self)
+    }
+    fn last(&self) -> Option<(&K, &V)> {
+        BPlusTree::last(self)
+    }
+    fn len(&self) -> usize {
+        BPlusTree::len(self)
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: fmt::Debug, const B: usize> fmt::Debug for BPlusTree<K, V, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> FromIterator<(K, V)> for BPlusTree<K, V, B> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::default();
+        for (k, v) in iter {
+            tree.insert(k, v);
+        }
+        tree
+    }
+}
+
+pub struct Range<'a, K, V, const B: usize, R> {
+    tree: &'a BPlusTree<K, V, B>,
+    leaf: usize,
+    pos: usize,
+    range: R,
+}
+
+impl<'a, K: Ord + Clone, V, const B: usize, R: RangeBounds<K>> Iterator for Range<'a, K, V, B, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.leaf != NIL && self.pos == self.tree.node(self.leaf).keys.len() {
+            self.leaf = self.tree.node(self.leaf).next;
+            self.pos = 0;
+        }
+        if self.leaf == NIL {
+            return None;
+        }
+        let node = self.tree.node(self.leaf);
+        let key = &node.keys[self.pos];
+        let inside = match self.range.end_bound() {
+            Bound::Unbounded => true,
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+        };
+        if !inside {
+            self.leaf = NIL;
+            return None;
+        }
+        self.pos += 1;
+        Some((key, &node.vals[self.pos - 1]))
+    }
+}
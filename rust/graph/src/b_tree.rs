@@ -0,0 +1,404 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+use crate::ordered_map::OrderedMap;
+
+// Ordered map on a B-tree of minimum degree B: every node but the root
+// holds between B - 1 and 2B - 1 sorted keys, an inner node one child more
+// than keys, and all leaves are on the same level. A larger B means fewer,
+// wider nodes that are searched in cache rather than followed through
+// pointers; std's BTreeMap uses B = 6.
+//
+// Insert splits full nodes and remove tops up nodes with B - 1 keys on the
+// way down, so neither has to walk back up.
+pub struct BTreeMap<K, V, const B: usize = 6> {
+    root: Node<K, V>,
+    len: usize,
+}
+
+struct Node<K, V> {
+    keys: Vec<K>,
+    vals: Vec<V>,
+    // empty in leaves
+    children: Vec<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(b: usize) -> Self {
+        Self {
+            keys: Vec::with_capacity(2 * b - 1),
+            vals: Vec::with_capacity(2 * b - 1),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn search<Q: Ord + ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.keys.binary_search_by(|k| k.borrow().cmp(key))
+    }
+
+    // the full child i gives its median key to self and its upper half to
+    // a new child i + 1
+    fn split_child(&mut self, i: usize, b: usize) {
+        let child = &mut self.children[i];
+        let mut right = Node::new(b);
+        right.keys.extend(child.keys.drain(b..));
+        right.vals.extend(child.vals.drain(b..));
+        if !child.is_leaf() {
+            right.children = child.children.split_off(b);
+        }
+        let key = child.keys.pop().unwrap();
+        let val = child.vals.pop().unwrap();
+        self.keys.insert(i, key);
+        self.vals.insert(i, val);
+        self.children.insert(i + 1, right);
+    }
+
+    // child i absorbs key i and child i + 1
+    fn merge_children(&mut self, i: usize) {
+        let right = self.children.remove(i + 1);
+        let key = self.keys.remove(i);
+        let val = self.vals.remove(i);
+        let left = &mut self.children[i];
+        left.keys.push(key);
+        left.vals.push(val);
+        left.keys.extend(right.keys);
+        left.vals.extend(right.vals);
+        left.children.extend(right.children);
+    }
+
+    // Gives child i at least b keys before a remove descends into it, by
+    // borrowing from a sibling or merging with one. Returns the index the
+    // child has afterwards.
+    fn fill_child(&mut self, i: usize, b: usize) -> usize {
+        if i > 0 && self.children[i - 1].keys.len() >= b {
+            let (left, right) = self.children.split_at_mut(i);
+            let (left, child) = (&mut left[i - 1], &mut right[0]);
+            let key = std::mem::replace(&mut self.keys[i - 1], left.keys.pop().unwrap());
+            let val = std::mem::replace(&mut self.vals[i - 1], left.vals.pop().unwrap());
+            child.keys.insert(0, key);
+            child.vals.insert(0, val);
+            if let Some(c) = left.children.pop() {
+                child.children.insert(0, c);
+            }
+            i
+        } else if i + 1 < self.children.len() && self.children[i + 1].keys.len() >= b {
+            let (left, right) = self.children.split_at_mut(i + 1);
+            let (child, right) = (&mut left[i], &mut right[0]);
+            let key = std::mem::replace(&mut self.keys[i], right.keys.remove(0));
+            let val = std::mem::replace(&mut self.vals[i], right.vals.remove(0));
+            child.keys.push(key);
+            child.vals.push(val);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+            i
+        } else if i + 1 < self.children.len() {
+            self.merge_children(i);
+            i
+        } else {
+            self.merge_children(i - 1);
+            i - 1
+        }
+    }
+
+    // tops up child i if it has only b - 1 keys, returns its new index
+    fn prepare_child(&mut self, i: usize, b: usize) -> usize {
+        if self.children[i].keys.len() < b {
+            self.fill_child(i, b)
+        } else {
+            i
+        }
+    }
+
+    // self has at least b keys unless it is the root
+    fn remove<Q: Ord + ?Sized>(&mut self, key: &Q, b: usize) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+    {
+        match self.search(key) {
+            Ok(i) if self.is_leaf() => Some((self.keys.remove(i), self.vals.remove(i))),
+            Ok(i) => {
+                // replace the key by its predecessor or successor, or merge
+                // the two children around it and remove from there
+                let (k, v) = if self.children[i].keys.len() >= b {
+                    self.children[i].remove_last(b)
+                } else if self.children[i + 1].keys.len() >= b {
+                    self.children[i + 1].remove_first(b)
+                } else {
+                    self.merge_children(i);
+                    return self.children[i].remove(key, b);
+                };
+                Some((
+                    std::mem::replace(&mut self.keys[i], k),
+                    std::mem::replace(&mut self.vals[i], v),
+                ))
+            }
+            Err(_) if self.is_leaf() => None,
+            Err(i) => {
+                let i = self.prepare_child(i, b);
+                self.children[i].remove(key, b)
+            }
+        }
+    }
+
+    fn remove_first(&mut self, b: usize) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.remove(0), self.vals.remove(0));
+        }
+        let i = self.prepare_child(0, b);
+        self.children[i].remove_first(b)
+    }
+
+    fn remove_last(&mut self, b: usize) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.pop().unwrap(), self.vals.pop().unwrap());
+        }
+        let i = self.prepare_child(self.children.len() - 1, b);
+        self.children[i].remove_last(b)
+    }
+}
+
+impl<K: Ord, V, const B: usize> Default for BTreeMap<K, V, B> {
+    fn default() -> Self {
+        assert!(B >= 2, "a b-tree needs a minimum degree of at least 2");
+        Self {
+            root: Node::new(B),
+            len: 0,
+        }
+    }
+}
+
+impl<K: Ord, V> BTreeMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// other degrees through Default, e.g. BTreeMap::<u64, u64, 32>::default()
+impl<K: Ord, V, const B: usize> BTreeMap<K, V, B> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // number of levels, a single leaf is height 1
+    pub fn height(&self) -> usize {
+        let mut node = &self.root;
+        let mut height = 1;
+        while let Some(child) = node.children.first() {
+            node = child;
+            height += 1;
+        }
+        height
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.root.keys.len() == 2 * B - 1 {
+            let old = std::mem::replace(&mut self.root, Node::new(B));
+            self.root.children.push(old);
+            self.root.split_child(0, B);
+        }
+        let mut node = &mut self.root;
+        loop {
+            let mut i = match node.search(&key) {
+                Ok(i) => return Some(std::mem::replace(&mut node.vals[i], value)),
+                Err(i) => i,
+            };
+            if node.is_leaf() {
+                node.keys.insert(i, key);
+                node.vals.insert(i, value);
+                self.len += 1;
+                return None;
+            }
+            if node.children[i].keys.len() == 2 * B - 1 {
+                node.split_child(i, B);
+                match key.cmp(&node.keys[i]) {
+                    Ordering::Less => {}
+                    Ordering::Equal => return Some(std::mem::replace(&mut node.vals[i], value)),
+                    Ordering::Greater => i += 1,
+                }
+            }
+            node = &mut node.children[i];
+        }
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let removed = self.root.remove(key, B);
+        // a merge may have emptied the root
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            self.root = self.root.children.pop().unwrap();
+        }
+        let (_, value) = removed?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut node = &self.root;
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(&node.vals[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &node.children[i],
+            }
+        }
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut node = &mut self.root;
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(&mut node.vals[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &mut node.children[i],
+            }
+        }
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        Some((node.keys.first()?, node.vals.first()?))
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        Some((node.keys.last()?, node.vals.last()?))
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let mut stack = Vec::new();
+        let mut node = &self.root;
+        loop {
+            let i = match range.start_bound() {
+                Bound::Unbounded => 0,
+                Bound::Included(s) => node.keys.partition_point(|k| k < s),
+                Bound::Excluded(s) => node.keys.partition_point(|k| k <= s),
+            };
+            stack.push((node, i));
+            match node.children.get(i) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        Range { stack, range }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<K: Ord, V, const B: usize> OrderedMap<K, V> for BTreeMap<K, V, B> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+    fn first(&self) -> Option<(&K, &V)> {
+        BTreeMap::first(self)
+    }
+    fn last(&self) -> Option<(&K, &V)> {
+        BTreeMap::last(self)
+    }
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug, const B: usize> fmt::Debug for BTreeMap<K, V, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V, const B: usize> FromIterator<(K, V)> for BTreeMap<K, V, B> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+// A stack of (node, index of the next key to visit in it) from the root
+// down; after key i of an inner node comes the subtree of child i + 1.
+pub struct Range<'a, K, V, R> {
+    stack: Vec<(&'a Node<K, V>, usize)>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = loop {
+            let &mut (node, ref mut i) = self.stack.last_mut()?;
+            if *i < node.keys.len() {
+                *i += 1;
+                break (node, *i - 1);
+            }
+            self.stack.pop();
+        };
+        let key = &node.keys[i];
+        let inside = match self.range.end_bound() {
+            Bound::Unbounded => true,
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+        };
+        if !inside {
+            self.stack.clear();
+            return None;
+        }
+        let mut next = node.children.get(i + 1);
+        while let Some(child) = next {
+            self.stack.push((child, 0));
+            next = child.children.first();
+        }
+        Some((key, &node.vals[i]))
+    }
+}
@@ -0,0 +1,219 @@
+use std::{fmt, iter::FusedIterator, slice};
+
+use crate::priority_queue::{Compare, MinOrder, PriorityQueue};
+
+// Implicit binary heap in a Vec: the children of slot i are 2i+1 and 2i+2
+// and no item is popped before its parent. Unlike std's heap the order is
+// a Compare value, a min-heap by default.
+#[derive(Clone)]
+pub struct BinaryHeap<T, C = MinOrder> {
+    data: Vec<T>,
+    cmp: C,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    pub fn new() -> Self {
+        Self::with_compare(MinOrder)
+    }
+}
+
+impl<T: Ord> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Compare<T>> BinaryHeap<T, C> {
+    pub fn with_compare(cmp: C) -> Self {
+        Self {
+            data: Vec::new(),
+            cmp,
+        }
+    }
+
+    // builds the heap bottom up in O(n)
+    pub fn heapify(mut data: Vec<T>, cmp: C) -> Self {
+        heapify(&mut data, &mut |a, b| cmp.before(a, b));
+        Self { data, cmp }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        let last = self.data.len() - 1;
+        sift_up(&mut self.data, last, &mut |a, b| self.cmp.before(a, b));
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.pop()?;
+        if self.data.is_empty() {
+            return Some(last);
+        }
+        let top = std::mem::replace(&mut self.data[0], last);
+        let end = self.data.len();
+        sift_down(&mut self.data, 0, end, &mut |a, b| self.cmp.before(a, b));
+        Some(top)
+    }
+
+    // pops the top and pushes item in one sift, returns the old top
+    pub fn replace_top(&mut self, item: T) -> Option<T> {
+        if self.data.is_empty() {
+            self.data.push(item);
+            return None;
+        }
+        let top = std::mem::replace(&mut self.data[0], item);
+        let end = self.data.len();
+        sift_down(&mut self.data, 0, end, &mut |a, b| self.cmp.before(a, b));
+        Some(top)
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    // arbitrary order
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    // the items in the order pop would return them, sorted in place
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        sort_heap(&mut self.data, &mut |a, b| self.cmp.before(a, b));
+        self.data.reverse();
+        self.data
+    }
+
+    // pops the items in order as the iterator advances; items it does not
+    // reach stay in the heap
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T, C> {
+        DrainSorted { heap: self }
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for BinaryHeap<T, C> {
+    fn push(&mut self, item: T) {
+        BinaryHeap::push(self, item)
+    }
+    fn pop(&mut self) -> Option<T> {
+        BinaryHeap::pop(self)
+    }
+    fn peek(&self) -> Option<&T> {
+        BinaryHeap::peek(self)
+    }
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for BinaryHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.iter()).finish()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::heapify(iter.into_iter().collect(), MinOrder)
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for BinaryHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+pub struct DrainSorted<'a, T, C: Compare<T>> {
+    heap: &'a mut BinaryHeap<T, C>,
+}
+
+impl<T, C: Compare<T>> Iterator for DrainSorted<'_, T, C> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T, C: Compare<T>> ExactSizeIterator for DrainSorted<'_, T, C> {}
+
+impl<T, C: Compare<T>> FusedIterator for DrainSorted<'_, T, C> {}
+
+// The heap helpers take before(a, b), a goes above b, as a closure, so
+// the sort module's heapsort runs on them too.
+
+pub(crate) fn heapify<T>(data: &mut [T], before: &mut impl FnMut(&T, &T) -> bool) {
+    let n = data.len();
+    for i in (0..n / 2).rev() {
+        sift_down(data, i, n, before);
+    }
+}
+
+// Pops each top to the back of the shrinking heap, leaving data in reverse
+// pop order.
+pub(crate) fn sort_heap<T>(data: &mut [T], before: &mut impl FnMut(&T, &T) -> bool) {
+    let mut end = data.len();
+    while end > 1 {
+        end -= 1;
+        data.swap(0, end);
+        sift_down(data, 0, end, before);
+    }
+}
+
+fn sift_up<T>(data: &mut [T], mut i: usize, before: &mut impl FnMut(&T, &T) -> bool) {
+    while i > 0 {
+        let parent = (i - 1) / 2;
+        if !before(&data[i], &data[parent]) {
+            break;
+        }
+        data.swap(i, parent);
+        i = parent;
+    }
+}
+
+// restores the heap property below i within data[..end]
+pub(crate) fn sift_down<T>(
+    data: &mut [T],
+    mut i: usize,
+    end: usize,
+    before: &mut impl FnMut(&T, &T) -> bool,
+) {
+    loop {
+        let left = 2 * i + 1;
+        if left >= end {
+            break;
+        }
+        let right = left + 1;
+        let child = if right < end && before(&data[right], &data[left]) {
+            right
+        } else {
+            left
+        };
+        if !before(&data[child], &data[i]) {
+            break;
+        }
+        data.swap(i, child);
+        i = child;
+    }
+}
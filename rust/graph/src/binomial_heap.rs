@@ -0,0 +1,170 @@
+use crate::priority_queue::{Compare, MinOrder, PriorityQueue};
+
+// Forest of binomial trees with at most one tree per rank, like the bits
+// of len. merge adds two forests the way binary numbers are added, linking
+// equal rank trees as the carry, so push, pop and merge are all O(log n).
+#[derive(Debug, Clone)]
+pub struct BinomialHeap<T, C = MinOrder> {
+    // trees[k] has rank k, i.e. 2^k items
+    trees: Vec<Option<Tree<T>>>,
+    len: usize,
+    cmp: C,
+}
+
+#[derive(Debug, Clone)]
+struct Tree<T> {
+    item: T,
+    // children[i] has rank i
+    children: Vec<Tree<T>>,
+}
+
+impl<T: Ord> BinomialHeap<T> {
+    pub fn new() -> Self {
+        Self::with_compare(MinOrder)
+    }
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Compare<T>> BinomialHeap<T, C> {
+    pub fn with_compare(cmp: C) -> Self {
+        Self {
+            trees: Vec::new(),
+            len: 0,
+            cmp,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // scans the roots, O(log n)
+    pub fn peek(&self) -> Option<&T> {
+        self.top().map(|k| &self.trees[k].as_ref().unwrap().item)
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.len += 1;
+        self.add(
+            0,
+            Tree {
+                item,
+                children: Vec::new(),
+            },
+        );
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let k = self.top()?;
+        let tree = self.trees[k].take().unwrap();
+        while self.trees.last().is_some_and(|t| t.is_none()) {
+            self.trees.pop();
+        }
+        self.len -= 1;
+        let children = tree.children.into_iter().map(Some).collect();
+        self.add_forest(children);
+        Some(tree.item)
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.len += other.len;
+        self.add_forest(other.trees);
+    }
+
+    pub fn clear(&mut self) {
+        self.trees.clear();
+        self.len = 0;
+    }
+
+    fn top(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (k, tree) in self.trees.iter().enumerate() {
+            if let Some(t) = tree {
+                if best.is_none_or(|b| {
+                    self.cmp
+                        .before(&t.item, &self.trees[b].as_ref().unwrap().item)
+                }) {
+                    best = Some(k);
+                }
+            }
+        }
+        best
+    }
+
+    fn add_forest(&mut self, forest: Vec<Option<Tree<T>>>) {
+        for (rank, tree) in forest.into_iter().enumerate() {
+            if let Some(t) = tree {
+                self.add(rank, t);
+            }
+        }
+    }
+
+    // adds a tree of the given rank, carrying into higher ranks
+    fn add(&mut self, mut rank: usize, mut tree: Tree<T>) {
+        loop {
+            if rank >= self.trees.len() {
+                self.trees.resize_with(rank + 1, || None);
+            }
+            match self.trees[rank].take() {
+                None => {
+                    self.trees[rank] = Some(tree);
+                    return;
+                }
+                Some(other) => {
+                    tree = self.link(tree, other);
+                    rank += 1;
+                }
+            }
+        }
+    }
+
+    fn link(&self, a: Tree<T>, b: Tree<T>) -> Tree<T> {
+        let (mut parent, child) = if self.cmp.before(&b.item, &a.item) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        parent.children.push(child);
+        parent
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for BinomialHeap<T, C> {
+    fn push(&mut self, item: T) {
+        BinomialHeap::push(self, item)
+    }
+    fn pop(&mut self) -> Option<T> {
+        BinomialHeap::pop(self)
+    }
+    fn peek(&self) -> Option<&T> {
+        BinomialHeap::peek(self)
+    }
+    fn len(&self) -> usize {
+        BinomialHeap::len(self)
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for BinomialHeap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinomialHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
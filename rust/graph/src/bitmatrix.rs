@@ -0,0 +1,164 @@
+use crate::csr::CsrGraph;
+use crate::matrix;
+use crate::memory::{vec_bytes, MemoryFootprint};
+
+// Adjacency matrix packed 64 cells to a u64 word, row major, each row
+// padded to whole words. The kernels work a word at a time: whole rows
+// are OR-ed or AND-ed together and popcounted, which the compiler turns
+// into vector instructions where the target has them.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    n: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn new(n: usize) -> Self {
+        let words = n.div_ceil(64);
+        Self {
+            n,
+            words,
+            bits: vec![0; n * words],
+        }
+    }
+
+    pub fn from_matrix(g: &matrix::Graph) -> Self {
+        let mut m = Self::new(g.len());
+        for i in 0..g.len() {
+            for j in g.neighbors(i) {
+                m.set(i, j);
+            }
+        }
+        m
+    }
+
+    pub fn from_csr(g: &CsrGraph) -> Self {
+        let mut m = Self::new(g.vertex_count());
+        for i in 0..g.vertex_count() {
+            for &j in g.neighbors(i) {
+                m.set(i, j);
+            }
+        }
+        m
+    }
+
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            vertex_table: 0,
+            neighbor_lists: vec_bytes(&self.bits),
+            indices: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        assert!(j < self.n);
+        self.row(i)[j / 64] >> (j % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, j: usize) {
+        assert!(j < self.n);
+        self.row_mut(i)[j / 64] |= 1 << (j % 64);
+    }
+
+    pub fn unset(&mut self, i: usize, j: usize) {
+        assert!(j < self.n);
+        self.row_mut(i)[j / 64] &= !(1 << (j % 64));
+    }
+
+    pub fn row(&self, i: usize) -> &[u64] {
+        &self.bits[i * self.words..(i + 1) * self.words]
+    }
+
+    fn row_mut(&mut self, i: usize) -> &mut [u64] {
+        &mut self.bits[i * self.words..(i + 1) * self.words]
+    }
+
+    pub fn degree(&self, i: usize) -> usize {
+        self.row(i).iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn neighbors(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        ones(self.row(i))
+    }
+
+    // vertices adjacent to both a and b
+    pub fn intersection(&self, a: usize, b: usize) -> Vec<usize> {
+        let row: Vec<u64> = and(self.row(a), self.row(b)).collect();
+        ones(&row).collect()
+    }
+
+    pub fn common_neighbors(&self, a: usize, b: usize) -> usize {
+        and(self.row(a), self.row(b))
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+
+    // Warshall over whole rows: once i reaches k, i also reaches
+    // everything k reaches. O(n^3 / 64).
+    pub fn transitive_closure(&self) -> BitMatrix {
+        let mut reach = self.clone();
+        let w = self.words;
+        for k in 0..self.n {
+            let row_k = reach.row(k).to_vec();
+            for i in 0..self.n {
+                if reach.bits[i * w + k / 64] >> (k % 64) & 1 == 1 {
+                    for (x, y) in reach.row_mut(i).iter_mut().zip(row_k.iter()) {
+                        *x |= y;
+                    }
+                }
+            }
+        }
+        reach
+    }
+
+    // triangles of the graph read as undirected (edges in both
+    // directions), self loops ignored: every edge i < j adds the common
+    // neighbors above j
+    pub fn triangle_count(&self) -> u64 {
+        let mut count = 0;
+        for i in 0..self.n {
+            for j in self.neighbors(i).filter(|&j| j > i) {
+                count += count_above(self.row(i), self.row(j), j) as u64;
+            }
+        }
+        count
+    }
+}
+
+fn and<'a>(a: &'a [u64], b: &'a [u64]) -> impl Iterator<Item = u64> + 'a {
+    a.iter().zip(b.iter()).map(|(x, y)| x & y)
+}
+
+// popcount of a & b restricted to the bits above `bit`
+fn count_above(a: &[u64], b: &[u64], bit: usize) -> u32 {
+    let word = bit / 64;
+    let first = (a[word] & b[word]) & (!0u64 << (bit % 64) << 1);
+    first.count_ones()
+        + and(&a[word + 1..], &b[word + 1..])
+            .map(|w| w.count_ones())
+            .sum::<u32>()
+}
+
+fn ones(row: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    row.iter().enumerate().flat_map(|(i, &w)| {
+        let mut w = w;
+        std::iter::from_fn(move || {
+            if w == 0 {
+                return None;
+            }
+            let bit = w.trailing_zeros() as usize;
+            w &= w - 1;
+            Some(i * 64 + bit)
+        })
+    })
+}
@@ -0,0 +1,174 @@
+use std::{f64::consts::LN_2, fmt, hash::Hash, marker::PhantomData};
+
+use crate::hashing::{mix64, seeded_hash};
+
+// Bloom filter: k bits set per item in an array of m, so a lookup can say
+// "definitely absent" or "probably present". The k positions come from
+// double hashing, h1 + i*h2 mod m, out of a single hash of the item. Sized
+// from the expected number of items n and the false positive rate p it
+// takes m = -n ln p / ln^2 2 bits and k = (m / n) ln 2 hashes.
+pub struct BloomFilter<T: ?Sized> {
+    words: Vec<u64>,
+    num_bits: usize,
+    num_hashes: u32,
+    seed: u64,
+    item: PhantomData<fn(&T)>,
+}
+
+// union and intersection need the same bits, hashes and seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncompatibleFilters;
+
+impl fmt::Display for IncompatibleFilters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "filters differ in size, hash count or seed")
+    }
+}
+
+impl std::error::Error for IncompatibleFilters {}
+
+impl<T: Hash + ?Sized> BloomFilter<T> {
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false positive rate must be in (0, 1), got {false_positive_rate}"
+        );
+        let n = expected_items.max(1) as f64;
+        let bits = (-n * false_positive_rate.ln() / (LN_2 * LN_2)).ceil();
+        let hashes = (bits / n * LN_2).round().max(1.0);
+        Self::with_params(bits as usize, hashes as u32, 0)
+    }
+
+    pub fn with_params(num_bits: usize, num_hashes: u32, seed: u64) -> Self {
+        assert!(num_bits >= 1, "a bloom filter needs at least one bit");
+        assert!(num_hashes >= 1, "a bloom filter needs at least one hash");
+        Self {
+            words: vec![0; num_bits.div_ceil(64)],
+            num_bits,
+            num_hashes,
+            seed,
+            item: PhantomData,
+        }
+    }
+
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // true if some bit was still clear, i.e. item was surely new
+    pub fn insert(&mut self, item: &T) -> bool {
+        let mut new = false;
+        for bit in self.positions(item) {
+            let (word, mask) = (bit / 64, 1 << (bit % 64));
+            new |= self.words[word] & mask == 0;
+            self.words[word] |= mask;
+        }
+        new
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.positions(item)
+            .all(|bit| self.words[bit / 64] & 1 << (bit % 64) != 0)
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    // number of distinct items inserted, going by how many bits are set
+    pub fn estimated_len(&self) -> f64 {
+        let m = self.num_bits as f64;
+        let ones = self.count_ones() as f64;
+        if ones >= m {
+            return f64::INFINITY;
+        }
+        -m / self.num_hashes as f64 * (1.0 - ones / m).ln()
+    }
+
+    // chance that an absent item passes contains, at the current fill
+    pub fn false_positive_rate(&self) -> f64 {
+        (self.count_ones() as f64 / self.num_bits as f64).powi(self.num_hashes as i32)
+    }
+
+    // afterwards contains whatever either filter contained
+    pub fn union_with(&mut self, other: &Self) -> Result<(), IncompatibleFilters> {
+        self.check(other)?;
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+        Ok(())
+    }
+
+    // Afterwards contains whatever both contained. It can pass more than
+    // a filter built from the common items alone, never less.
+    pub fn intersect_with(&mut self, other: &Self) -> Result<(), IncompatibleFilters> {
+        self.check(other)?;
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    fn check(&self, other: &Self) -> Result<(), IncompatibleFilters> {
+        if (self.num_bits, self.num_hashes, self.seed)
+            == (other.num_bits, other.num_hashes, other.seed)
+        {
+            Ok(())
+        } else {
+            Err(IncompatibleFilters)
+        }
+    }
+
+    fn positions(&self, item: &T) -> impl Iterator<Item = usize> {
+        let m = self.num_bits as u64;
+        let h1 = seeded_hash(item, self.seed);
+        // step of the double hashing, odd so it is never zero
+        let h2 = mix64(h1) | 1;
+        (0..self.num_hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m) as usize)
+    }
+}
+
+// by hand, a derive would want T: Clone
+impl<T: ?Sized> Clone for BloomFilter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            words: self.words.clone(),
+            item: PhantomData,
+            ..*self
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for BloomFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Hash> Extend<T> for BloomFilter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(&item);
+        }
+    }
+}
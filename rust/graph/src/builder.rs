@@ -0,0 +1,256 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::csr::{write_header, CsrGraph};
+
+// Builds a CSR graph from an edge stream of any length. Edges are
+// buffered up to the run capacity, then sorted and spilled to a run file
+// in a private scratch directory; finishing merges the runs straight into
+// the binary CSR file format. Only the per-vertex degree counts and one
+// run live in memory, so the edge set may be much larger than RAM.
+//
+// Vertices are 0..n where n is one past the largest id seen, or more when
+// raised with min_vertices. Parallel edges are kept.
+
+const DEFAULT_RUN_CAPACITY: usize = 1 << 20;
+// runs merged at once, more runs are first merged into longer runs
+const MERGE_FAN_IN: usize = 64;
+// from u64, to u64, weight i32
+const RECORD_LEN: usize = 20;
+
+type Edge = (usize, usize, i32);
+
+pub struct GraphBuilder {
+    run_capacity: usize,
+    parent: PathBuf,
+    scratch: Option<PathBuf>,
+    buffer: Vec<Edge>,
+    runs: Vec<PathBuf>,
+    degree: Vec<usize>,
+    edges: usize,
+    next_file: usize,
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphBuilder {
+    pub fn new() -> Self {
+        Self {
+            run_capacity: DEFAULT_RUN_CAPACITY,
+            parent: std::env::temp_dir(),
+            scratch: None,
+            buffer: Vec::new(),
+            runs: Vec::new(),
+            degree: Vec::new(),
+            edges: 0,
+            next_file: 0,
+        }
+    }
+
+    // edges held in memory before a run is spilled, at least 1
+    pub fn run_capacity(mut self, edges: usize) -> Self {
+        self.run_capacity = edges.max(1);
+        self
+    }
+
+    // where the scratch directory for run files is created
+    pub fn temp_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.parent = dir.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn min_vertices(mut self, n: usize) -> Self {
+        if self.degree.len() < n {
+            self.degree.resize(n, 0);
+        }
+        self
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.degree.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, wt: i32) -> io::Result<()> {
+        let n = from.max(to) + 1;
+        if self.degree.len() < n {
+            self.degree.resize(n, 0);
+        }
+        self.degree[from] += 1;
+        self.edges += 1;
+        self.buffer.push((from, to, wt));
+        if self.buffer.len() >= self.run_capacity {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    // any edge source, e.g. an iterator or a mpsc::Receiver that is
+    // drained until every sender is dropped
+    pub fn add_edges<I>(&mut self, edges: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = Edge>,
+    {
+        for (from, to, wt) in edges {
+            self.add_edge(from, to, wt)?;
+        }
+        Ok(())
+    }
+
+    // small inputs that never spilled are built in memory, otherwise the
+    // runs are merged into a scratch file which is then loaded
+    pub fn build(mut self) -> io::Result<CsrGraph> {
+        if self.runs.is_empty() {
+            return Ok(CsrGraph::from_edges(self.degree.len(), &self.buffer));
+        }
+        let path = self.scratch_file()?;
+        self.write_into(&path)?;
+        CsrGraph::load(&path)
+    }
+
+    // writes the graph in the CsrGraph::save format, ready for
+    // CsrGraph::map
+    pub fn write_csr<P: AsRef<Path>>(mut self, path: P) -> io::Result<()> {
+        self.write_into(path.as_ref())
+    }
+
+    fn write_into(&mut self, path: &Path) -> io::Result<()> {
+        self.spill()?;
+        while self.runs.len() > MERGE_FAN_IN {
+            let mut merged = Vec::new();
+            let runs = std::mem::take(&mut self.runs);
+            for group in runs.chunks(MERGE_FAN_IN) {
+                let out_path = self.scratch_file()?;
+                let mut out = BufWriter::new(File::create(&out_path)?);
+                merge(group, |e| write_record(&mut out, e))?;
+                out.flush()?;
+                for run in group.iter() {
+                    fs::remove_file(run)?;
+                }
+                merged.push(out_path);
+            }
+            self.runs = merged;
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        write_header(&mut out, self.degree.len(), self.edges)?;
+        let mut offset = 0u64;
+        out.write_all(&offset.to_le_bytes())?;
+        for &d in self.degree.iter() {
+            offset += d as u64;
+            out.write_all(&offset.to_le_bytes())?;
+        }
+        // targets go straight to the output, weights to a side file that
+        // is appended once all targets are written
+        let weights_path = self.scratch_file()?;
+        let mut weights = BufWriter::new(File::create(&weights_path)?);
+        merge(&self.runs, |(_, to, wt)| {
+            out.write_all(&(to as u64).to_le_bytes())?;
+            weights.write_all(&wt.to_le_bytes())
+        })?;
+        weights.flush()?;
+        drop(weights);
+        io::copy(&mut File::open(&weights_path)?, &mut out)?;
+        out.flush()?;
+        fs::remove_file(weights_path)
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.buffer.sort_unstable();
+        let path = self.scratch_file()?;
+        let mut out = BufWriter::new(File::create(&path)?);
+        for &e in self.buffer.iter() {
+            write_record(&mut out, e)?;
+        }
+        out.flush()?;
+        self.buffer.clear();
+        self.runs.push(path);
+        Ok(())
+    }
+
+    // a fresh file name inside the scratch directory, created on first use
+    fn scratch_file(&mut self) -> io::Result<PathBuf> {
+        static BUILDERS: AtomicUsize = AtomicUsize::new(0);
+        let dir = match &self.scratch {
+            Some(dir) => dir.clone(),
+            None => {
+                let id = BUILDERS.fetch_add(1, Ordering::Relaxed);
+                let dir = self
+                    .parent
+                    .join(format!("graph-builder-{}-{id}", process::id()));
+                fs::create_dir_all(&dir)?;
+                self.scratch = Some(dir.clone());
+                dir
+            }
+        };
+        self.next_file += 1;
+        Ok(dir.join(format!("{}.run", self.next_file)))
+    }
+}
+
+impl Drop for GraphBuilder {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.scratch {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn write_record<W: Write>(out: &mut W, (from, to, wt): Edge) -> io::Result<()> {
+    out.write_all(&(from as u64).to_le_bytes())?;
+    out.write_all(&(to as u64).to_le_bytes())?;
+    out.write_all(&wt.to_le_bytes())
+}
+
+fn read_record<R: Read>(input: &mut R) -> io::Result<Option<Edge>> {
+    let mut rec = [0; RECORD_LEN];
+    match input.read_exact(&mut rec) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let word = |at: usize| u64::from_le_bytes(rec[at..at + 8].try_into().unwrap()) as usize;
+    let wt = i32::from_le_bytes(rec[16..].try_into().unwrap());
+    Ok(Some((word(0), word(8), wt)))
+}
+
+// k-way merge of sorted run files, edges are emitted in sorted order
+fn merge<F>(runs: &[PathBuf], mut emit: F) -> io::Result<()>
+where
+    F: FnMut(Edge) -> io::Result<()>,
+{
+    let mut readers = runs
+        .iter()
+        .map(|p| File::open(p).map(BufReader::new))
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut heap = BinaryHeap::with_capacity(readers.len());
+    for (i, r) in readers.iter_mut().enumerate() {
+        if let Some(e) = read_record(r)? {
+            heap.push(Reverse((e, i)));
+        }
+    }
+    while let Some(Reverse((e, i))) = heap.pop() {
+        emit(e)?;
+        if let Some(next) = read_record(&mut readers[i])? {
+            heap.push(Reverse((next, i)));
+        }
+    }
+    Ok(())
+}
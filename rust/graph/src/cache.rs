@@ -0,0 +1,16 @@
+// Common interface of the caches in this crate, so replacement policies can
+// be swapped behind one type and compared on the same trace. get counts as
+// a use for the policy, contains_key does not.
+pub trait Cache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V>;
+    // the old value of key, if there was one
+    fn put(&mut self, key: K, value: V) -> Option<V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn contains_key(&self, key: &K) -> bool;
+    fn len(&self) -> usize;
+    fn capacity(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,142 @@
+// C interface, declared in include/graph.h. A graph handle owns a
+// directed graph over the vertex ids 0..n; every call reports failure
+// through a GraphStatus code instead of panicking across the boundary.
+
+use std::slice;
+
+use crate::adjlist::Graph;
+use crate::shortest_path::dijkstra;
+
+pub struct GraphHandle {
+    graph: Graph<u32>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphStatus {
+    Ok = 0,
+    NullPointer = 1,
+    VertexOutOfRange = 2,
+    NegativeWeight = 3,
+    BufferTooSmall = 4,
+}
+
+// written to the distance buffer for vertices the source cannot reach
+pub const GRAPH_UNREACHABLE: i64 = -1;
+// written to the predecessor buffer for the source and unreachable vertices
+pub const GRAPH_NO_VERTEX: u32 = u32::MAX;
+
+#[no_mangle]
+pub extern "C" fn graph_new(vertex_count: u32) -> *mut GraphHandle {
+    let mut graph = Graph::new();
+    for v in 0..vertex_count {
+        graph.add_vertex(&v);
+    }
+    Box::into_raw(Box::new(GraphHandle { graph }))
+}
+
+/// # Safety
+/// `graph` must be null or a handle from `graph_new` that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn graph_free(graph: *mut GraphHandle) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// # Safety
+/// `graph` must be null or a live handle from `graph_new`.
+#[no_mangle]
+pub unsafe extern "C" fn graph_vertex_count(graph: *const GraphHandle) -> u32 {
+    graph.as_ref().map_or(0, |h| h.graph.vertex_num())
+}
+
+/// # Safety
+/// `graph` must be null or a live handle from `graph_new`.
+#[no_mangle]
+pub unsafe extern "C" fn graph_edge_count(graph: *const GraphHandle) -> u32 {
+    graph.as_ref().map_or(0, |h| h.graph.edge_num())
+}
+
+// appends a vertex and stores its id in *id
+/// # Safety
+/// `graph` must be null or a live handle from `graph_new`, `id` null or
+/// valid for one write.
+#[no_mangle]
+pub unsafe extern "C" fn graph_add_vertex(graph: *mut GraphHandle, id: *mut u32) -> GraphStatus {
+    let (Some(h), Some(id)) = (graph.as_mut(), id.as_mut()) else {
+        return GraphStatus::NullPointer;
+    };
+    *id = h.graph.vertex_num();
+    h.graph.add_vertex(id);
+    GraphStatus::Ok
+}
+
+/// # Safety
+/// `graph` must be null or a live handle from `graph_new`.
+#[no_mangle]
+pub unsafe extern "C" fn graph_add_edge(
+    graph: *mut GraphHandle,
+    from: u32,
+    to: u32,
+    weight: i32,
+) -> GraphStatus {
+    let Some(h) = graph.as_mut() else {
+        return GraphStatus::NullPointer;
+    };
+    let n = h.graph.vertex_num();
+    if from >= n || to >= n {
+        return GraphStatus::VertexOutOfRange;
+    }
+    h.graph.add_edge(&from, &to, weight);
+    GraphStatus::Ok
+}
+
+// Single-source shortest paths into buffers of `len` entries, which must
+// hold at least graph_vertex_count values. `prev` may be null when only
+// distances are needed.
+/// # Safety
+/// `graph` must be null or a live handle from `graph_new`; `dist`, and
+/// `prev` when not null, must be valid for `len` writes.
+#[no_mangle]
+pub unsafe extern "C" fn graph_dijkstra(
+    graph: *const GraphHandle,
+    source: u32,
+    dist: *mut i64,
+    prev: *mut u32,
+    len: usize,
+) -> GraphStatus {
+    let Some(h) = graph.as_ref() else {
+        return GraphStatus::NullPointer;
+    };
+    if dist.is_null() {
+        return GraphStatus::NullPointer;
+    }
+    let n = h.graph.vertex_num();
+    if source >= n {
+        return GraphStatus::VertexOutOfRange;
+    }
+    if len < n as usize {
+        return GraphStatus::BufferTooSmall;
+    }
+    let negative = (0..n).any(|v| {
+        let vt = h.graph.get_vertex(&v).unwrap();
+        vt.neighbors.iter().any(|&(_, wt)| wt < 0)
+    });
+    if negative {
+        return GraphStatus::NegativeWeight;
+    }
+
+    let sp = dijkstra(&h.graph, &source);
+    let dist = slice::from_raw_parts_mut(dist, n as usize);
+    for (v, d) in (0..n).zip(dist.iter_mut()) {
+        *d = sp.dist.get(&v).copied().unwrap_or(GRAPH_UNREACHABLE);
+    }
+    if !prev.is_null() {
+        let prev = slice::from_raw_parts_mut(prev, n as usize);
+        for (v, p) in (0..n).zip(prev.iter_mut()) {
+            *p = sp.prev.get(&v).copied().unwrap_or(GRAPH_NO_VERTEX);
+        }
+    }
+    GraphStatus::Ok
+}
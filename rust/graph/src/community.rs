@@ -0,0 +1,109 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::adjlist::{Graph, Indexed, NeighborList};
+use crate::rng::Rng;
+
+// Communities of an undirected graph stored with both edge directions.
+// Edge weights are connection strengths and should be positive.
+
+// Raghavan-Albert-Kumara label propagation: every vertex starts in its own
+// community and repeatedly adopts the label with the largest total edge
+// weight among its neighbors, visiting vertices in a fresh random order
+// each round. Ties are broken at random but a vertex keeps its label when
+// it is among the best ones, so the process settles.
+pub fn label_propagation<T: Hash + Eq + Clone, S: NeighborList<T>, R: Rng>(
+    graph: &Graph<T, S>,
+    max_rounds: usize,
+    mut rng: R,
+) -> Vec<Vec<T>> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let mut label: Vec<usize> = (0..n).collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    let mut score = vec![0i64; n];
+    let mut touched = Vec::new();
+    let mut best = Vec::new();
+    for _ in 0..max_rounds {
+        rng.shuffle(&mut order);
+        let mut changed = false;
+        for &v in order.iter() {
+            for &(u, wt) in g.adj[v].iter() {
+                if u == v {
+                    continue;
+                }
+                if score[label[u]] == 0 {
+                    touched.push(label[u]);
+                }
+                score[label[u]] += wt as i64;
+            }
+            let top = touched.iter().map(|&l| score[l]).max();
+            best.clear();
+            best.extend(touched.iter().copied().filter(|&l| Some(score[l]) == top));
+            if !best.is_empty() && !best.contains(&label[v]) {
+                label[v] = best[rng.gen_range(best.len())];
+                changed = true;
+            }
+            for l in touched.drain(..) {
+                score[l] = 0;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    groups(&g, &label)
+}
+
+// Newman's modularity of a partition, in [-1/2, 1]; vertices missing from
+// every community are counted as singletons
+pub fn modularity<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+    communities: &[Vec<T>],
+) -> f64 {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let mut label: Vec<usize> = (0..n).map(|v| communities.len() + v).collect();
+    for (c, members) in communities.iter().enumerate() {
+        for key in members.iter() {
+            if let Some(&v) = g.index.get(key) {
+                label[v] = c;
+            }
+        }
+    }
+    let strength: Vec<f64> = g
+        .adj
+        .iter()
+        .map(|nbrs| nbrs.iter().map(|&(_, wt)| wt as f64).sum())
+        .collect();
+    let total: f64 = strength.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let mut inside = HashMap::new();
+    let mut degree = HashMap::new();
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        *degree.entry(label[v]).or_insert(0.0) += strength[v];
+        for &(u, wt) in nbrs.iter() {
+            if label[u] == label[v] {
+                *inside.entry(label[v]).or_insert(0.0) += wt as f64;
+            }
+        }
+    }
+    degree
+        .iter()
+        .map(|(c, d)| inside.get(c).unwrap_or(&0.0) / total - (d / total).powi(2))
+        .sum()
+}
+
+fn groups<T: Clone>(g: &Indexed<T>, label: &[usize]) -> Vec<Vec<T>> {
+    let mut slot = vec![usize::MAX; label.len()];
+    let mut out: Vec<Vec<T>> = Vec::new();
+    for (v, &l) in label.iter().enumerate() {
+        if slot[l] == usize::MAX {
+            slot[l] = out.len();
+            out.push(Vec::new());
+        }
+        out[slot[l]].push(g.keys[v].clone());
+    }
+    out
+}
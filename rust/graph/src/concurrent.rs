@@ -0,0 +1,140 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hash},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        RwLock,
+    },
+};
+
+use crate::adjlist::Graph;
+use crate::parallel::threads;
+
+// Directed graph that many threads can grow and read at once. Vertices
+// are spread over independently locked shards by key hash, so writers
+// only contend when they touch the same shard. No operation holds two
+// shard locks at a time, which rules out lock-order deadlocks; the price
+// is that add_edge is not atomic as a whole, a concurrent reader may see
+// the target vertex before the edge to it.
+
+type Shard<T> = HashMap<T, Vec<(T, i32)>>;
+
+pub struct ConcurrentGraph<T> {
+    shards: Vec<RwLock<Shard<T>>>,
+    hasher: RandomState,
+    vertices: AtomicUsize,
+    edges: AtomicUsize,
+}
+
+impl<T: Hash + Eq + Clone> Default for ConcurrentGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq + Clone> ConcurrentGraph<T> {
+    // a few shards per hardware thread keeps collisions between writers rare
+    pub fn new() -> Self {
+        Self::with_shards(4 * threads())
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| RwLock::default()).collect(),
+            hasher: RandomState::new(),
+            vertices: AtomicUsize::new(0),
+            edges: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.load(Ordering::Relaxed)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertex_count() == 0
+    }
+
+    pub fn contains(&self, key: &T) -> bool {
+        self.shard(key).read().unwrap().contains_key(key)
+    }
+
+    // false when the vertex already existed
+    pub fn add_vertex(&self, key: &T) -> bool {
+        let mut shard = self.shard(key).write().unwrap();
+        if shard.contains_key(key) {
+            return false;
+        }
+        shard.insert(key.clone(), Vec::new());
+        self.vertices.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    // missing endpoints are added first, as in Graph::add_edge
+    pub fn add_edge(&self, from: &T, to: &T, wt: i32) {
+        self.add_vertex(to);
+        let mut shard = self.shard(from).write().unwrap();
+        shard
+            .entry(from.clone())
+            .or_insert_with(|| {
+                self.vertices.fetch_add(1, Ordering::Relaxed);
+                Vec::new()
+            })
+            .push((to.clone(), wt));
+        self.edges.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // snapshot of the out-edges, None for an unknown vertex
+    pub fn neighbors(&self, key: &T) -> Option<Vec<(T, i32)>> {
+        self.shard(key).read().unwrap().get(key).cloned()
+    }
+
+    // runs f on the out-edges under the shard's read lock, without copying
+    pub fn with_neighbors<R>(&self, key: &T, f: impl FnOnce(&[(T, i32)]) -> R) -> Option<R> {
+        self.shard(key).read().unwrap().get(key).map(|nbrs| f(nbrs))
+    }
+
+    pub fn degree(&self, key: &T) -> Option<usize> {
+        self.with_neighbors(key, |nbrs| nbrs.len())
+    }
+
+    pub fn into_graph(self) -> Graph<T> {
+        let mut g = Graph::new();
+        let shards: Vec<Shard<T>> = self
+            .shards
+            .into_iter()
+            .map(|s| s.into_inner().unwrap())
+            .collect();
+        for key in shards.iter().flat_map(|s| s.keys()) {
+            g.add_vertex(key);
+        }
+        for (key, nbrs) in shards.iter().flat_map(|s| s.iter()) {
+            for (nbr, wt) in nbrs.iter() {
+                g.add_edge(key, nbr, *wt);
+            }
+        }
+        g
+    }
+
+    fn shard(&self, key: &T) -> &RwLock<Shard<T>> {
+        let h = self.hasher.hash_one(key);
+        &self.shards[(h % self.shards.len() as u64) as usize]
+    }
+}
+
+impl<T: Hash + Eq + Clone> From<Graph<T>> for ConcurrentGraph<T> {
+    fn from(graph: Graph<T>) -> Self {
+        let g = Self::new();
+        for key in graph.vertex_keys() {
+            g.add_vertex(&key);
+            for (nbr, wt) in graph.get_vertex(&key).unwrap().neighbors.iter() {
+                g.add_edge(&key, nbr, *wt);
+            }
+        }
+        g
+    }
+}
@@ -0,0 +1,168 @@
+use std::{fmt, rc::Rc};
+
+// Persistent singly linked list: consing onto a list makes a new one that
+// shares all of the old as its tail, and every node knows the length from
+// itself down, so head, tail, cons and len are all O(1). Versions with a
+// common tail, as from backtracking or an undo stack, cost one node each.
+pub struct ConsList<T> {
+    head: Option<Rc<Cons<T>>>,
+}
+
+struct Cons<T> {
+    value: T,
+    next: Option<Rc<Cons<T>>>,
+    len: usize,
+}
+
+// by hand, a derive would want T: Clone
+impl<T> Clone for ConsList<T> {
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> Default for ConsList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ConsList<T> {
+    pub fn new() -> Self {
+        Self { head: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |n| n.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // value in front of self
+    pub fn cons(&self, value: T) -> Self {
+        Self {
+            head: Some(Rc::new(Cons {
+                value,
+                next: self.head.clone(),
+                len: self.len() + 1,
+            })),
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|n| &n.value)
+    }
+
+    // everything after the head, None if empty
+    pub fn tail(&self) -> Option<Self> {
+        self.head.as_ref().map(|n| Self {
+            head: n.next.clone(),
+        })
+    }
+
+    pub fn uncons(&self) -> Option<(&T, Self)> {
+        self.head.as_ref().map(|n| {
+            (
+                &n.value,
+                Self {
+                    head: n.next.clone(),
+                },
+            )
+        })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    // true if both are the same version or copies of it
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
+
+    pub fn reverse(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.iter()
+            .fold(Self::new(), |list, value| list.cons(value.clone()))
+    }
+}
+
+// Iteratively, a recursive drop of a long list would overflow the stack.
+// Stops at the first node another list still holds.
+impl<T> Drop for ConsList<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Cons<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.next.map_or(0, |n| n.len);
+        (n, Some(n))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a ConsList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// the list in iteration order, the first item at the head
+impl<T> FromIterator<T> for ConsList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items
+            .into_iter()
+            .rev()
+            .fold(Self::new(), |list, value| list.cons(value))
+    }
+}
+
+impl<T: PartialEq> PartialEq for ConsList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for ConsList<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for ConsList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
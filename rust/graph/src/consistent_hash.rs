@@ -0,0 +1,153 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use crate::hashing::seeded_hash;
+
+// Consistent hashing: nodes and keys hash to points on a ring of u64s, and
+// a key belongs to the first node point at or after its own, wrapping
+// around. Each node takes many points, its virtual nodes, which evens out
+// the arcs between them. Adding or removing a node only moves the keys on
+// the arcs it gains or loses, about 1 / n of them, where rehashing mod n
+// would move nearly all.
+#[derive(Debug, Clone)]
+pub struct ConsistentHashRing<N> {
+    ring: BTreeMap<u64, N>,
+    // virtual node count of every node
+    nodes: HashMap<N, usize>,
+    default_vnodes: usize,
+}
+
+impl<N: Hash + Eq + Clone> ConsistentHashRing<N> {
+    pub fn new(vnodes_per_node: usize) -> Self {
+        assert!(vnodes_per_node >= 1, "a node needs at least one point");
+        Self {
+            ring: BTreeMap::new(),
+            nodes: HashMap::new(),
+            default_vnodes: vnodes_per_node,
+        }
+    }
+
+    // number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.keys()
+    }
+
+    pub fn contains_node(&self, node: &N) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    // false if node was already on the ring
+    pub fn add_node(&mut self, node: N) -> bool {
+        self.add_weighted(node, self.default_vnodes)
+    }
+
+    // With vnodes points instead of the default, for a share of the keys
+    // in proportion.
+    pub fn add_weighted(&mut self, node: N, vnodes: usize) -> bool {
+        assert!(vnodes >= 1, "a node needs at least one point");
+        if self.nodes.contains_key(&node) {
+            return false;
+        }
+        for i in 0..vnodes {
+            // a point already taken stays with its node
+            self.ring
+                .entry(point(&node, i))
+                .or_insert_with(|| node.clone());
+        }
+        self.nodes.insert(node, vnodes);
+        true
+    }
+
+    pub fn remove_node(&mut self, node: &N) -> bool {
+        let Some(vnodes) = self.nodes.remove(node) else {
+            return false;
+        };
+        for i in 0..vnodes {
+            let p = point(node, i);
+            if self.ring.get(&p) == Some(node) {
+                self.ring.remove(&p);
+            }
+        }
+        true
+    }
+
+    // the node key belongs to, None on an empty ring
+    pub fn node_for<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        let h = seeded_hash(key, KEY_SEED);
+        self.ring
+            .range(h..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, n)| n)
+    }
+
+    // Up to count distinct nodes for key, the owner first and then the
+    // following ones around the ring, for placing replicas.
+    pub fn nodes_for<K: Hash + ?Sized>(&self, key: &K, count: usize) -> Vec<&N> {
+        let h = seeded_hash(key, KEY_SEED);
+        let mut found: Vec<&N> = Vec::new();
+        for (_, n) in self.ring.range(h..).chain(self.ring.range(..h)) {
+            if found.len() == count.min(self.nodes.len()) {
+                break;
+            }
+            if !found.contains(&n) {
+                found.push(n);
+            }
+        }
+        found
+    }
+
+    // keys per node
+    pub fn distribution<'a, K, I>(&self, keys: I) -> HashMap<&N, usize>
+    where
+        K: Hash + ?Sized + 'a,
+        I: IntoIterator<Item = &'a K>,
+    {
+        let mut counts = HashMap::new();
+        for key in keys {
+            if let Some(n) = self.node_for(key) {
+                *counts.entry(n).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    // Fraction of keys that belong to a different node on other, say the
+    // ring after a node was added or removed.
+    pub fn moved_fraction<'a, K, I>(&self, other: &Self, keys: I) -> f64
+    where
+        K: Hash + ?Sized + 'a,
+        I: IntoIterator<Item = &'a K>,
+    {
+        let (mut moved, mut total) = (0, 0);
+        for key in keys {
+            total += 1;
+            if self.node_for(key) != other.node_for(key) {
+                moved += 1;
+            }
+        }
+        if total == 0 {
+            0.0
+        } else {
+            moved as f64 / total as f64
+        }
+    }
+}
+
+// keys and node points hash apart
+const KEY_SEED: u64 = 0;
+const NODE_SEED: u64 = 1;
+
+fn point<N: Hash>(node: &N, vnode: usize) -> u64 {
+    seeded_hash(&(node, vnode), NODE_SEED)
+}
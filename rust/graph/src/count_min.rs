@@ -0,0 +1,218 @@
+use std::{
+    cmp::Reverse, collections::HashMap, f64::consts::E, fmt, hash::Hash, marker::PhantomData,
+};
+
+use crate::hashing::{mix64, seeded_hash};
+
+// Count-min sketch: depth rows of width counters, every item adding to one
+// counter per row. A counter collects the item and whatever collides with
+// it, so the smallest of an item's counters is an estimate that is never
+// low, and with width e / epsilon and depth ln(1 / delta) it is high by
+// more than epsilon times the total count only with probability delta.
+// Conservative update raises each counter only as far as the new estimate,
+// which keeps the estimates lower so long as nothing is subtracted.
+pub struct CountMinSketch<T: ?Sized> {
+    counters: Vec<u64>,
+    width: usize,
+    depth: usize,
+    seed: u64,
+    conservative: bool,
+    total: u64,
+    item: PhantomData<fn(&T)>,
+}
+
+// merging needs the same width, depth and seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncompatibleSketches;
+
+impl fmt::Display for IncompatibleSketches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sketches differ in width, depth or seed")
+    }
+}
+
+impl std::error::Error for IncompatibleSketches {}
+
+impl<T: Hash + ?Sized> CountMinSketch<T> {
+    pub fn new(epsilon: f64, delta: f64) -> Self {
+        assert!(epsilon > 0.0, "epsilon must be positive, got {epsilon}");
+        assert!(
+            delta > 0.0 && delta < 1.0,
+            "delta must be in (0, 1), got {delta}"
+        );
+        let width = (E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        Self::with_dims(width, depth, 0)
+    }
+
+    pub fn with_dims(width: usize, depth: usize, seed: u64) -> Self {
+        assert!(
+            width >= 1 && depth >= 1,
+            "sketch needs at least one counter"
+        );
+        Self {
+            counters: vec![0; width * depth],
+            width,
+            depth,
+            seed,
+            conservative: false,
+            total: 0,
+            item: PhantomData,
+        }
+    }
+
+    pub fn conservative(mut self, on: bool) -> Self {
+        self.conservative = on;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    // sum of all counts added
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn increment(&mut self, item: &T) {
+        self.add(item, 1);
+    }
+
+    pub fn add(&mut self, item: &T, count: u64) {
+        self.total += count;
+        if self.conservative {
+            let target = self.estimate(item) + count;
+            for c in self.cells(item) {
+                self.counters[c] = self.counters[c].max(target);
+            }
+        } else {
+            for c in self.cells(item) {
+                self.counters[c] += count;
+            }
+        }
+    }
+
+    // at least the true count of item
+    pub fn estimate(&self, item: &T) -> u64 {
+        self.cells(item).map(|c| self.counters[c]).min().unwrap()
+    }
+
+    // afterwards estimates the combined streams, as if all of other's
+    // counts had been added here
+    pub fn merge(&mut self, other: &Self) -> Result<(), IncompatibleSketches> {
+        if (self.width, self.depth, self.seed) != (other.width, other.depth, other.seed) {
+            return Err(IncompatibleSketches);
+        }
+        for (a, b) in self.counters.iter_mut().zip(&other.counters) {
+            *a += b;
+        }
+        self.total += other.total;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.counters.fill(0);
+        self.total = 0;
+    }
+
+    // counter index of item in every row
+    fn cells(&self, item: &T) -> impl Iterator<Item = usize> {
+        let h1 = seeded_hash(item, self.seed);
+        let h2 = mix64(h1) | 1;
+        let width = self.width;
+        (0..self.depth).map(move |row| {
+            let h = h1.wrapping_add((row as u64).wrapping_mul(h2));
+            row * width + (h % width as u64) as usize
+        })
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for CountMinSketch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountMinSketch")
+            .field("width", &self.width)
+            .field("depth", &self.depth)
+            .field("conservative", &self.conservative)
+            .field("total", &self.total)
+            .finish_non_exhaustive()
+    }
+}
+
+// by hand, a derive would want T: Clone
+impl<T: ?Sized> Clone for CountMinSketch<T> {
+    fn clone(&self) -> Self {
+        Self {
+            counters: self.counters.clone(),
+            item: PhantomData,
+            ..*self
+        }
+    }
+}
+
+// The most frequent items of a stream: a count-min sketch for the counts
+// and up to 2k candidates by estimate, cut back to the best k when full.
+// Items making up more than a 1/k share of the stream stay tracked unless
+// the sketch overestimates the others badly.
+#[derive(Debug, Clone)]
+pub struct HeavyHitters<T: Hash + Eq + Clone> {
+    sketch: CountMinSketch<T>,
+    k: usize,
+    candidates: HashMap<T, u64>,
+}
+
+impl<T: Hash + Eq + Clone> HeavyHitters<T> {
+    pub fn new(k: usize, sketch: CountMinSketch<T>) -> Self {
+        assert!(k >= 1, "heavy hitters needs room for one item");
+        Self {
+            sketch,
+            k,
+            candidates: HashMap::new(),
+        }
+    }
+
+    pub fn sketch(&self) -> &CountMinSketch<T> {
+        &self.sketch
+    }
+
+    pub fn add(&mut self, item: T, count: u64) {
+        self.sketch.add(&item, count);
+        let estimate = self.sketch.estimate(&item);
+        // estimates only grow, so a stale one is a lower bound
+        self.candidates.insert(item, estimate);
+        if self.candidates.len() > 2 * self.k {
+            let floor = self.kth_estimate();
+            self.candidates.retain(|_, &mut e| e >= floor);
+        }
+    }
+
+    // the k items with the highest estimates, highest first
+    pub fn top(&self) -> Vec<(T, u64)> {
+        let mut top: Vec<(T, u64)> = self
+            .candidates
+            .keys()
+            .map(|item| (item.clone(), self.sketch.estimate(item)))
+            .collect();
+        top.sort_unstable_by_key(|&(_, e)| Reverse(e));
+        top.truncate(self.k);
+        top
+    }
+
+    // tracked items estimated at phi times the total or more, highest first
+    pub fn above(&self, phi: f64) -> Vec<(T, u64)> {
+        let threshold = phi * self.sketch.total() as f64;
+        let mut top = self.top();
+        top.retain(|&(_, e)| e as f64 >= threshold);
+        top
+    }
+
+    fn kth_estimate(&self) -> u64 {
+        let mut estimates: Vec<u64> = self.candidates.values().copied().collect();
+        let (_, &mut kth, _) = estimates.select_nth_unstable_by(self.k - 1, |a, b| b.cmp(a));
+        kth
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    collections::{BinaryHeap, HashSet},
+    hash::Hash,
+};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::independent_set::undirected;
+
+#[derive(Debug, Clone)]
+pub struct VertexCover<T> {
+    pub cover: Vec<T>,
+    // size of the maximal matching found; every cover needs one endpoint of
+    // each matched edge, so the optimum is at least this
+    pub lower_bound: usize,
+}
+
+impl<T> VertexCover<T> {
+    // guaranteed upper bound on cover / optimum, never above 2
+    pub fn ratio_bound(&self) -> f64 {
+        if self.lower_bound == 0 {
+            1.0
+        } else {
+            self.cover.len() as f64 / self.lower_bound as f64
+        }
+    }
+}
+
+// both endpoints of a maximal matching
+pub fn vertex_cover_2approx<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> VertexCover<T> {
+    let g = graph.indexed();
+    let adj = undirected(&g.adj);
+    let mut covered = vec![false; adj.len()];
+    let mut cover = Vec::new();
+    let mut matched = 0;
+    for v in 0..adj.len() {
+        if covered[v] {
+            continue;
+        }
+        if let Some(&u) = adj[v].iter().find(|&&u| !covered[u]) {
+            covered[v] = true;
+            cover.push(g.keys[v].clone());
+            if u != v {
+                covered[u] = true;
+                cover.push(g.keys[u].clone());
+            }
+            matched += 1;
+        }
+    }
+    VertexCover {
+        cover,
+        lower_bound: matched,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetCover<E> {
+    // indices into the input sets, in the order they were picked
+    pub sets: Vec<usize>,
+    // universe elements that no set contains
+    pub uncovered: Vec<E>,
+    // greedy is within H(d) of the optimum, d the largest set size
+    pub ratio_bound: f64,
+}
+
+impl<E> SetCover<E> {
+    pub fn lower_bound(&self) -> usize {
+        (self.sets.len() as f64 / self.ratio_bound).ceil() as usize
+    }
+}
+
+// greedy ln(n) approximation: always take the set covering the most
+// still uncovered elements. Gains only shrink, so stale heap entries are
+// re-scored lazily instead of updating every set after each pick.
+pub fn greedy_set_cover<E: Hash + Eq + Clone>(universe: &[E], sets: &[Vec<E>]) -> SetCover<E> {
+    let mut remaining: HashSet<&E> = universe.iter().collect();
+    let mut seen: HashSet<&E> = HashSet::new();
+    let members: Vec<Vec<&E>> = sets
+        .iter()
+        .map(|s| {
+            seen.clear();
+            s.iter()
+                .filter(|e| remaining.contains(e) && seen.insert(e))
+                .collect()
+        })
+        .collect();
+    let largest = members.iter().map(|m| m.len()).max().unwrap_or(0);
+
+    let mut heap: BinaryHeap<(usize, usize)> = members
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (m.len(), i))
+        .collect();
+    let mut chosen = Vec::new();
+    while let Some((gain, i)) = heap.pop() {
+        if gain == 0 || remaining.is_empty() {
+            break;
+        }
+        let now = members[i].iter().filter(|e| remaining.contains(*e)).count();
+        if now < gain {
+            heap.push((now, i));
+            continue;
+        }
+        for e in members[i].iter() {
+            remaining.remove(e);
+        }
+        chosen.push(i);
+    }
+
+    let uncovered = universe
+        .iter()
+        .filter(|e| remaining.contains(e))
+        .cloned()
+        .collect();
+    SetCover {
+        sets: chosen,
+        uncovered,
+        ratio_bound: (1..=largest).map(|k| 1.0 / k as f64).sum::<f64>().max(1.0),
+    }
+}
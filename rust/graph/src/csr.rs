@@ -0,0 +1,321 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    hash::Hash,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::mapped::{Buffer, Mmap};
+use crate::memory::MemoryFootprint;
+use crate::union_find::UnionFind;
+
+// Compressed sparse row form: the out-neighbors of v are
+// targets[offsets[v]..offsets[v + 1]], sorted by target. Vertices are
+// 0..n, built once and then read only. The arrays either live on the
+// heap or point straight into a mapped graph file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsrGraph {
+    offsets: Buffer<usize>,
+    targets: Buffer<usize>,
+    weights: Buffer<i32>,
+}
+
+// Binary graph file, all integers little endian:
+//   magic "CSRGRAPH", version u32, reserved u32, n u64, m u64
+//   offsets (n + 1) x u64, targets m x u64, weights m x i32
+// Every section starts 8-byte aligned, so on 64-bit little endian hosts
+// the sections can be used in place as the CsrGraph arrays.
+const MAGIC: &[u8; 8] = b"CSRGRAPH";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 32;
+
+impl CsrGraph {
+    // edges as (from, to, weight), every endpoint must be below n
+    pub fn from_edges(n: usize, edges: &[(usize, usize, i32)]) -> Self {
+        let mut offsets = vec![0; n + 1];
+        for &(from, to, _) in edges.iter() {
+            assert!(from < n && to < n, "edge ({from}, {to}) out of range");
+            offsets[from + 1] += 1;
+        }
+        for v in 0..n {
+            offsets[v + 1] += offsets[v];
+        }
+        let mut sorted = edges.to_vec();
+        sorted.sort_unstable_by_key(|&(from, to, _)| (from, to));
+        Self {
+            offsets: offsets.into(),
+            targets: sorted
+                .iter()
+                .map(|&(_, to, _)| to)
+                .collect::<Vec<_>>()
+                .into(),
+            weights: sorted
+                .iter()
+                .map(|&(_, _, wt)| wt)
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+
+    // also returns the key of every vertex id
+    pub fn from_adjlist<T: Hash + Eq + Clone, S: NeighborList<T>>(
+        graph: &Graph<T, S>,
+    ) -> (Self, Vec<T>) {
+        let g = graph.indexed();
+        let mut edges = Vec::new();
+        for (v, nbrs) in g.adj.iter().enumerate() {
+            for &(u, wt) in nbrs.iter() {
+                edges.push((v, u, wt));
+            }
+        }
+        (Self::from_edges(g.keys.len(), &edges), g.keys)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        write_header(&mut out, self.vertex_count(), self.edge_count())?;
+        let offsets: &[usize] = if self.offsets.is_empty() {
+            &[0]
+        } else {
+            &self.offsets
+        };
+        for &x in offsets.iter().chain(self.targets.iter()) {
+            out.write_all(&(x as u64).to_le_bytes())?;
+        }
+        for &wt in self.weights.iter() {
+            out.write_all(&wt.to_le_bytes())?;
+        }
+        out.flush()
+    }
+
+    // reads a graph file into memory
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut input = BufReader::new(File::open(&path)?);
+        let mut header = [0; HEADER_LEN];
+        input.read_exact(&mut header)?;
+        let (n, m) = parse_header(&header, File::open(path)?.metadata()?.len())?;
+        let mut offsets = Vec::with_capacity(n + 1);
+        let mut targets = Vec::with_capacity(m);
+        let mut weights = Vec::with_capacity(m);
+        let mut word = [0; 8];
+        for i in 0..n + 1 + m {
+            input.read_exact(&mut word)?;
+            let x = usize::try_from(u64::from_le_bytes(word))
+                .map_err(|_| invalid("index overflows usize"))?;
+            if i <= n {
+                offsets.push(x);
+            } else {
+                targets.push(x);
+            }
+        }
+        let mut word = [0; 4];
+        for _ in 0..m {
+            input.read_exact(&mut word)?;
+            weights.push(i32::from_le_bytes(word));
+        }
+        check_offsets(&offsets, m)?;
+        check_targets(&targets, n)?;
+        Ok(Self {
+            offsets: offsets.into(),
+            targets: targets.into(),
+            weights: weights.into(),
+        })
+    }
+
+    // Maps a graph file without copying it; past one pass over the targets
+    // to check them, pages are read in on demand, so graphs much larger
+    // than memory can be traversed. Hosts that are not 64-bit little endian
+    // fall back to load.
+    /// # Safety
+    /// The file must not be truncated or written to while the graph or any
+    /// clone of it is alive.
+    pub unsafe fn map<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        if cfg!(not(all(
+            target_endian = "little",
+            target_pointer_width = "64"
+        ))) {
+            return Self::load(path);
+        }
+        let file = File::open(path)?;
+        let map = Arc::new(Mmap::map(&file)?);
+        let bytes = map.bytes();
+        if bytes.len() < HEADER_LEN {
+            return Err(invalid("file shorter than the header"));
+        }
+        let (n, m) = parse_header(&bytes[..HEADER_LEN], bytes.len() as u64)?;
+        let targets_at = HEADER_LEN + 8 * (n + 1);
+        let weights_at = targets_at + 8 * m;
+        let graph = Self {
+            offsets: Buffer::mapped(&map, HEADER_LEN, n + 1),
+            targets: Buffer::mapped(&map, targets_at, m),
+            weights: Buffer::mapped(&map, weights_at, m),
+        };
+        check_offsets(&graph.offsets, m)?;
+        check_targets(&graph.targets, n)?;
+        Ok(graph)
+    }
+
+    // mapped graphs only report heap memory, which is none
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            vertex_table: 0,
+            neighbor_lists: self.targets.heap_bytes() + self.weights.heap_bytes(),
+            indices: self.offsets.heap_bytes(),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertex_count() == 0
+    }
+
+    pub fn degree(&self, v: usize) -> usize {
+        self.offsets[v + 1] - self.offsets[v]
+    }
+
+    pub fn neighbors(&self, v: usize) -> &[usize] {
+        &self.targets[self.offsets[v]..self.offsets[v + 1]]
+    }
+
+    pub fn weights(&self, v: usize) -> &[i32] {
+        &self.weights[self.offsets[v]..self.offsets[v + 1]]
+    }
+
+    pub fn edges(&self, v: usize) -> impl Iterator<Item = (usize, i32)> + '_ {
+        self.neighbors(v)
+            .iter()
+            .copied()
+            .zip(self.weights(v).iter().copied())
+    }
+
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.neighbors(from).binary_search(&to).is_ok()
+    }
+
+    // edges reversed, in-neighbors become out-neighbors
+    pub fn transpose(&self) -> Self {
+        let mut edges = Vec::with_capacity(self.edge_count());
+        for v in 0..self.vertex_count() {
+            for (u, wt) in self.edges(v) {
+                edges.push((u, v, wt));
+            }
+        }
+        Self::from_edges(self.vertex_count(), &edges)
+    }
+
+    // hop distance from source, None for unreachable vertices
+    pub fn bfs(&self, source: usize) -> Vec<Option<usize>> {
+        self.multi_source_bfs(&[source]).0
+    }
+
+    // distance to the closest source and which source that is
+    pub fn multi_source_bfs(&self, sources: &[usize]) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+        let n = self.vertex_count();
+        let mut dist = vec![None; n];
+        let mut nearest = vec![None; n];
+        let mut queue = VecDeque::new();
+        for &s in sources.iter() {
+            if dist[s].is_none() {
+                dist[s] = Some(0);
+                nearest[s] = Some(s);
+                queue.push_back(s);
+            }
+        }
+        while let Some(v) = queue.pop_front() {
+            let d = dist[v].unwrap() + 1;
+            for &u in self.neighbors(v) {
+                if dist[u].is_none() {
+                    dist[u] = Some(d);
+                    nearest[u] = nearest[v];
+                    queue.push_back(u);
+                }
+            }
+        }
+        (dist, nearest)
+    }
+
+    // weakly connected components, every vertex is labelled with the
+    // smallest vertex id of its component
+    pub fn connected_components(&self) -> Vec<usize> {
+        let n = self.vertex_count();
+        let mut uf = UnionFind::new(n);
+        for v in 0..n {
+            for &u in self.neighbors(v) {
+                uf.union(v, u);
+            }
+        }
+        let mut smallest = vec![usize::MAX; n];
+        let roots: Vec<usize> = (0..n).map(|v| uf.find(v)).collect();
+        for v in 0..n {
+            smallest[roots[v]] = smallest[roots[v]].min(v);
+        }
+        roots.into_iter().map(|r| smallest[r]).collect()
+    }
+}
+
+pub(crate) fn write_header<W: Write>(out: &mut W, n: usize, m: usize) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&0u32.to_le_bytes())?;
+    out.write_all(&(n as u64).to_le_bytes())?;
+    out.write_all(&(m as u64).to_le_bytes())
+}
+
+// (n, m) after checking the header against the file length
+fn parse_header(header: &[u8], file_len: u64) -> io::Result<(usize, usize)> {
+    if &header[..8] != MAGIC {
+        return Err(invalid("not a CSR graph file"));
+    }
+    let word = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if version != VERSION {
+        return Err(invalid("unsupported CSR graph file version"));
+    }
+    let (n, m) = (word(16), word(24));
+    let expected = n
+        .checked_add(1)
+        .and_then(|x| x.checked_add(m))
+        .and_then(|x| x.checked_mul(8))
+        .and_then(|x| x.checked_add(m.checked_mul(4)?))
+        .and_then(|x| x.checked_add(HEADER_LEN as u64));
+    if expected != Some(file_len) {
+        return Err(invalid("file length does not match the header"));
+    }
+    match (usize::try_from(n), usize::try_from(m)) {
+        (Ok(n), Ok(m)) => Ok((n, m)),
+        _ => Err(invalid("graph too large for this platform")),
+    }
+}
+
+// offsets must start at 0, never decrease and end at m, so every
+// neighbor slice is in bounds; target ids are checked on use
+fn check_offsets(offsets: &[usize], m: usize) -> io::Result<()> {
+    let monotone = offsets.windows(2).all(|w| w[0] <= w[1]);
+    if offsets.first() != Some(&0) || offsets.last() != Some(&m) || !monotone {
+        return Err(invalid("corrupt offset table"));
+    }
+    Ok(())
+}
+
+// every edge ends at one of the n vertices, or traversals would index
+// out of bounds
+fn check_targets(targets: &[usize], n: usize) -> io::Result<()> {
+    if targets.iter().any(|&t| t >= n) {
+        return Err(invalid("edge target out of range"));
+    }
+    Ok(())
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,191 @@
+use std::{fmt, hash::Hash, marker::PhantomData};
+
+use crate::hashing::{mix64, seeded_hash};
+use crate::rng::{Rng, SplitMix64};
+
+// Cuckoo filter: a 16-bit fingerprint per item in one of two buckets of
+// four slots. The second bucket is the first xor a hash of the fingerprint,
+// so either bucket can be found from the other and the fingerprint alone,
+// and a full bucket makes room by kicking a random fingerprint over to its
+// other bucket. Unlike a Bloom filter it can delete, as long as only items
+// that were inserted get removed. False positives run about 8 / 2^16:
+// eight slots checked, each matching by chance one time in 2^16.
+pub struct CuckooFilter<T: ?Sized> {
+    buckets: Vec<[u16; BUCKET_SIZE]>,
+    len: usize,
+    // a fingerprint and its bucket that found no room after MAX_KICKS
+    // moves; while set, the filter takes no more items
+    victim: Option<(usize, u16)>,
+    rng: SplitMix64,
+    item: PhantomData<fn(&T)>,
+}
+
+const BUCKET_SIZE: usize = 4;
+const MAX_KICKS: usize = 500;
+// 0 marks a free slot
+const EMPTY: u16 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CuckooFilterFull;
+
+impl fmt::Display for CuckooFilterFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cuckoo filter is full")
+    }
+}
+
+impl std::error::Error for CuckooFilterFull {}
+
+impl<T: Hash + ?Sized> CuckooFilter<T> {
+    // room for about capacity items, bucket count rounded up to a power
+    // of two for 95% load
+    pub fn new(capacity: usize) -> Self {
+        let buckets = (capacity * 100)
+            .div_ceil(BUCKET_SIZE * 95)
+            .next_power_of_two()
+            .max(2);
+        Self {
+            buckets: vec![[EMPTY; BUCKET_SIZE]; buckets],
+            len: 0,
+            victim: None,
+            rng: SplitMix64::new(0),
+            item: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // slots in all, the filter fills up somewhat before that
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    // Inserting twice stores two fingerprints, and then it takes two
+    // removes to forget the item.
+    pub fn insert(&mut self, item: &T) -> Result<(), CuckooFilterFull> {
+        if self.victim.is_some() {
+            return Err(CuckooFilterFull);
+        }
+        let (i1, fp) = self.locate(item);
+        let i2 = self.alt_index(i1, fp);
+        if self.put(i1, fp) || self.put(i2, fp) {
+            self.len += 1;
+            return Ok(());
+        }
+        let mut i = if self.rng.gen_bool(0.5) { i1 } else { i2 };
+        let mut fp = fp;
+        for _ in 0..MAX_KICKS {
+            let slot = self.rng.gen_range(BUCKET_SIZE);
+            std::mem::swap(&mut fp, &mut self.buckets[i][slot]);
+            i = self.alt_index(i, fp);
+            if self.put(i, fp) {
+                self.len += 1;
+                return Ok(());
+            }
+        }
+        // the item is in, but some fingerprint is left over
+        self.victim = Some((i, fp));
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        let (i1, fp) = self.locate(item);
+        let i2 = self.alt_index(i1, fp);
+        self.buckets[i1].contains(&fp)
+            || self.buckets[i2].contains(&fp)
+            || self
+                .victim
+                .is_some_and(|(i, v)| v == fp && (i == i1 || i == i2))
+    }
+
+    // false if no fingerprint of item was found
+    pub fn remove(&mut self, item: &T) -> bool {
+        let (i1, fp) = self.locate(item);
+        let i2 = self.alt_index(i1, fp);
+        if self
+            .victim
+            .is_some_and(|(i, v)| v == fp && (i == i1 || i == i2))
+        {
+            self.victim = None;
+            self.len -= 1;
+            return true;
+        }
+        for i in [i1, i2] {
+            if let Some(slot) = self.buckets[i].iter_mut().find(|s| **s == fp) {
+                *slot = EMPTY;
+                self.len -= 1;
+                // the freed slot may take the victim back
+                if let Some((vi, vfp)) = self.victim {
+                    let alt = self.alt_index(vi, vfp);
+                    if self.put(vi, vfp) || self.put(alt, vfp) {
+                        self.victim = None;
+                    }
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.fill([EMPTY; BUCKET_SIZE]);
+        self.len = 0;
+        self.victim = None;
+    }
+
+    // (first bucket, fingerprint)
+    fn locate(&self, item: &T) -> (usize, u16) {
+        let h = seeded_hash(item, 0);
+        let mask = self.buckets.len() - 1;
+        let fp = ((h >> 48) as u16).max(1);
+        (h as usize & mask, fp)
+    }
+
+    // its own inverse: alt_index(alt_index(i, fp), fp) == i
+    fn alt_index(&self, i: usize, fp: u16) -> usize {
+        let mask = self.buckets.len() - 1;
+        i ^ (mix64(fp as u64) as usize & mask)
+    }
+
+    fn put(&mut self, i: usize, fp: u16) -> bool {
+        match self.buckets[i].iter_mut().find(|s| **s == EMPTY) {
+            Some(slot) => {
+                *slot = fp;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for CuckooFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CuckooFilter")
+            .field("len", &self.len)
+            .field("buckets", &self.buckets.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// by hand, a derive would want T: Clone
+impl<T: ?Sized> Clone for CuckooFilter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            buckets: self.buckets.clone(),
+            rng: self.rng.clone(),
+            item: PhantomData,
+            ..*self
+        }
+    }
+}
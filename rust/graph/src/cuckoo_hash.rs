@@ -0,0 +1,264 @@
+use std::{borrow::Borrow, fmt, hash::Hash};
+
+use crate::hashing::seeded_hash;
+use crate::rng::{Rng, SplitMix64};
+
+// Cuckoo hashing: two tables with a hash function each, and every key in
+// one of its two slots, so a lookup reads at most two slots. An insert
+// into an occupied slot kicks the resident over to its slot in the other
+// table, which may kick another, and so on. When that runs too long there
+// is most likely a cycle, and the entry left over goes to a small stash,
+// searched linearly; only when the stash is full is the whole map rebuilt
+// with new hash seeds. The tables double when the load would pass one
+// half, below which rebuilds stay rare and inserts take O(1) expected.
+// Keys that collide under every seed tried stay in the stash, which then
+// outgrows its size: slow, but the map never gives up on a key.
+pub struct CuckooHashMap<K, V> {
+    tables: [Vec<Option<(K, V)>>; 2],
+    stash: Vec<(K, V)>,
+    seeds: [u64; 2],
+    len: usize,
+    rehashes: usize,
+    rng: SplitMix64,
+}
+
+const MIN_SLOTS: usize = 8;
+// entries held outside the tables before a failed insert rebuilds
+const STASH: usize = 4;
+// seeds a rebuild tries before leaving what it can't place in the stash
+const MAX_REBUILDS: usize = 8;
+
+impl<K: Hash + Eq, V> Default for CuckooHashMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> CuckooHashMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let slots = capacity.next_power_of_two().max(MIN_SLOTS);
+        let mut rng = SplitMix64::new(0);
+        Self {
+            tables: [empty_table(slots), empty_table(slots)],
+            stash: Vec::new(),
+            seeds: [rng.next_u64(), rng.next_u64()],
+            len: 0,
+            rehashes: 0,
+            rng,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // slots in both tables, without the stash
+    pub fn capacity(&self) -> usize {
+        2 * self.tables[0].len()
+    }
+
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    // rebuilds with new seeds so far, growing or not
+    pub fn rehashes(&self) -> usize {
+        self.rehashes
+    }
+
+    fn slot<Q: Hash + ?Sized>(&self, t: usize, key: &Q) -> usize {
+        seeded_hash(key, self.seeds[t]) as usize & (self.tables[t].len() - 1)
+    }
+
+    // entries that found no slot
+    pub fn stashed(&self) -> usize {
+        self.stash.len()
+    }
+
+    // (table, slot) of key, if it isn't in the stash
+    fn find<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        (0..2).find_map(|t| {
+            let i = self.slot(t, key);
+            match &self.tables[t][i] {
+                Some((k, _)) if k.borrow() == key => Some((t, i)),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.find(key) {
+            Some((t, i)) => self.tables[t][i].as_ref().map(|(_, v)| v),
+            None => self.stash_position(key).map(|i| &self.stash[i].1),
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.find(key) {
+            Some((t, i)) => self.tables[t][i].as_mut().map(|(_, v)| v),
+            None => self.stash_position(key).map(|i| &mut self.stash[i].1),
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some() || self.stash_position(key).is_some()
+    }
+
+    fn stash_position<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.stash.iter().position(|(k, _)| k.borrow() == key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(v) = self.get_mut(&key) {
+            return Some(std::mem::replace(v, value));
+        }
+        if 2 * (self.len + 1) > self.capacity() {
+            let slots = 2 * self.tables[0].len();
+            self.rebuild(slots, Vec::new());
+        }
+        if let Err(homeless) = self.place((key, value)) {
+            if self.stash.len() < STASH {
+                self.stash.push(homeless);
+            } else {
+                self.rebuild(self.tables[0].len(), vec![homeless]);
+            }
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = match self.find(key) {
+            Some((t, i)) => self.tables[t][i].take().map(|(_, v)| v),
+            None => self
+                .stash_position(key)
+                .map(|i| self.stash.swap_remove(i).1),
+        };
+        self.len -= value.is_some() as usize;
+        value
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let tables = self.tables.iter().flatten().flatten();
+        tables.chain(&self.stash).map(|(k, v)| (k, v))
+    }
+
+    pub fn clear(&mut self) {
+        for table in self.tables.iter_mut() {
+            table.iter_mut().for_each(|s| *s = None);
+        }
+        self.stash.clear();
+        self.len = 0;
+    }
+
+    // Kicks entries back and forth until one lands in an empty slot, or
+    // hands back the one left without a slot after too many kicks.
+    fn place(&mut self, mut entry: (K, V)) -> Result<(), (K, V)> {
+        let max_kicks = 8 * (self.tables[0].len().trailing_zeros() as usize + 1);
+        // kick only when both slots are taken
+        let mut t = if self.tables[0][self.slot(0, &entry.0)].is_some() {
+            1
+        } else {
+            0
+        };
+        for _ in 0..max_kicks {
+            let i = self.slot(t, &entry.0);
+            match self.tables[t][i].replace(entry) {
+                None => return Ok(()),
+                Some(kicked) => entry = kicked,
+            }
+            t = 1 - t;
+        }
+        Err(entry)
+    }
+
+    // New seeds and tables of the given size for everything held plus
+    // extra, doubling after a few failed attempts unless the tables are
+    // mostly empty, when more room won't help. What the last attempt can't
+    // place is stashed.
+    fn rebuild(&mut self, mut slots: usize, mut entries: Vec<(K, V)>) {
+        entries.append(&mut self.stash);
+        for attempt in 1..=MAX_REBUILDS {
+            for table in self.tables.iter_mut() {
+                entries.extend(table.drain(..).flatten());
+            }
+            if attempt % 4 == 0 && 2 * entries.len() > slots {
+                slots *= 2;
+            }
+            self.tables = [empty_table(slots), empty_table(slots)];
+            self.seeds = [self.rng.next_u64(), self.rng.next_u64()];
+            self.rehashes += 1;
+            while let Some(entry) = entries.pop() {
+                if let Err(homeless) = self.place(entry) {
+                    if attempt == MAX_REBUILDS {
+                        self.stash.push(homeless);
+                    } else {
+                        entries.push(homeless);
+                        break;
+                    }
+                }
+            }
+            if entries.is_empty() {
+                return;
+            }
+        }
+    }
+}
+
+fn empty_table<K, V>(slots: usize) -> Vec<Option<(K, V)>> {
+    (0..slots).map(|_| None).collect()
+}
+
+impl<K: Hash + Eq + fmt::Debug, V: fmt::Debug> fmt::Debug for CuckooHashMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V> Extend<(K, V)> for CuckooHashMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for CuckooHashMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
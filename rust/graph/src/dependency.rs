@@ -0,0 +1,129 @@
+use std::{collections::HashMap, fmt, hash::Hash};
+
+use crate::adjlist::Graph;
+use crate::topo::topological_levels;
+
+// Orders items so that each comes after everything it depends on, over
+// the topological sort of the graph with an edge from every dependency to
+// its dependent. Items that don't depend on each other keep the order they
+// were added in, so a resolution is reproducible.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError<T> {
+    // each depends on the next, and the last on the first
+    Cycle(Vec<T>),
+    Missing { item: T, dependency: T },
+}
+
+impl<T: fmt::Debug> fmt::Display for ResolveError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::Cycle(items) => {
+                write!(f, "dependency cycle: ")?;
+                for item in items.iter() {
+                    write!(f, "{item:?} depends on ")?;
+                }
+                write!(f, "{:?}", items[0])
+            }
+            ResolveError::Missing { item, dependency } => {
+                write!(
+                    f,
+                    "{item:?} depends on {dependency:?}, which was never added"
+                )
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for ResolveError<T> {}
+
+#[derive(Debug, Clone)]
+pub struct DependencyResolver<T> {
+    items: Vec<T>,
+    index: HashMap<T, usize>,
+    // declared dependencies of each item, checked when resolving
+    dependencies: Vec<Vec<T>>,
+}
+
+impl<T: Hash + Eq + Clone> Default for DependencyResolver<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq + Clone> DependencyResolver<T> {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            index: HashMap::new(),
+            dependencies: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Adds item, or more dependencies to an item already added. The
+    // dependencies need only have been added by the time of resolving.
+    pub fn add<I: IntoIterator<Item = T>>(&mut self, item: T, dependencies: I) {
+        let i = match self.index.get(&item) {
+            Some(&i) => i,
+            None => {
+                self.index.insert(item.clone(), self.items.len());
+                self.items.push(item);
+                self.dependencies.push(Vec::new());
+                self.items.len() - 1
+            }
+        };
+        self.dependencies[i].extend(dependencies);
+    }
+
+    pub fn dependencies(&self, item: &T) -> Option<&[T]> {
+        self.index.get(item).map(|&i| &self.dependencies[i][..])
+    }
+
+    // every item after its dependencies
+    pub fn resolve(&self) -> Result<Vec<T>, ResolveError<T>> {
+        Ok(self.batches()?.concat())
+    }
+
+    // Groups of items that can run in parallel, each depending only on
+    // items of earlier batches, and as early as that allows.
+    pub fn batches(&self) -> Result<Vec<Vec<T>>, ResolveError<T>> {
+        let mut graph: Graph<usize> = Graph::new();
+        // all vertices first, re-adding one would drop its edges
+        for i in 0..self.items.len() {
+            graph.add_vertex(&i);
+        }
+        for (i, dependencies) in self.dependencies.iter().enumerate() {
+            for dependency in dependencies.iter() {
+                let Some(&d) = self.index.get(dependency) else {
+                    return Err(ResolveError::Missing {
+                        item: self.items[i].clone(),
+                        dependency: dependency.clone(),
+                    });
+                };
+                graph.add_edge(&d, &i, 0);
+            }
+        }
+        let item = |&i: &usize| self.items[i].clone();
+        match topological_levels(&graph) {
+            Ok(levels) => Ok(levels
+                .into_iter()
+                .map(|mut batch| {
+                    batch.sort_unstable();
+                    batch.iter().map(item).collect()
+                })
+                .collect()),
+            // the cycle's edges run from dependency to dependent
+            Err(cycle) => Err(ResolveError::Cycle(
+                cycle.vertices.iter().rev().map(item).collect(),
+            )),
+        }
+    }
+}
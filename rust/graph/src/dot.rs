@@ -0,0 +1,49 @@
+use std::{
+    fmt::{Display, Write},
+    hash::Hash,
+};
+
+use crate::adjlist::{Graph, NeighborList};
+
+// Graphviz source for the graph, vertices and edges in key order so the
+// output is stable between runs. Edge weights become edge labels.
+pub fn to_dot<T: Hash + Eq + Clone + Ord + Display, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+    name: &str,
+) -> String {
+    let mut keys = graph.vertex_keys();
+    keys.sort();
+    let mut out = String::new();
+    writeln!(out, "digraph {} {{", quote(name)).unwrap();
+    for key in keys.iter() {
+        writeln!(out, "    {};", quote(&key.to_string())).unwrap();
+    }
+    for key in keys.iter() {
+        let mut nbrs = graph.get_vertex(key).unwrap().neighbors.to_vec();
+        nbrs.sort();
+        for (nbr, wt) in nbrs.iter() {
+            writeln!(
+                out,
+                "    {} -> {} [label=\"{wt}\"];",
+                quote(&key.to_string()),
+                quote(&nbr.to_string())
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn quote(id: &str) -> String {
+    let mut s = String::with_capacity(id.len() + 2);
+    s.push('"');
+    for c in id.chars() {
+        if c == '"' || c == '\\' {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('"');
+    s
+}
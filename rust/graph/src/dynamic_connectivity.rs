@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::union_find::RollbackUnionFind;
+
+// Offline dynamic connectivity: a whole sequence of edge insertions,
+// deletions and queries over the vertices 0..n is answered at once in
+// O(q log q log n). Each edge is alive on an interval of the sequence;
+// the intervals are stored on a segment tree over time, and a depth first
+// walk of the tree unions the edges of a node on the way down and rolls
+// them back on the way up, so every leaf sees exactly the edges alive at
+// that moment.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    // edges are undirected and may be added more than once
+    Add(usize, usize),
+    // removes one copy of the edge, ignored when it is not present
+    Remove(usize, usize),
+    Connected(usize, usize),
+    Components,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    Connected(bool),
+    Components(usize),
+}
+
+// one answer per Connected or Components op, in order
+pub fn offline_connectivity(n: usize, ops: &[Op]) -> Vec<Answer> {
+    let t = ops.len();
+    if t == 0 {
+        return Vec::new();
+    }
+    let mut tree: Vec<Vec<(usize, usize)>> = vec![Vec::new(); 4 * t];
+    let mut open: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (i, op) in ops.iter().enumerate() {
+        match *op {
+            Op::Add(u, v) => {
+                assert!(u < n && v < n, "edge ({u}, {v}) out of range");
+                open.entry((u.min(v), u.max(v))).or_default().push(i);
+            }
+            Op::Remove(u, v) => {
+                let key = (u.min(v), u.max(v));
+                if let Some(start) = open.get_mut(&key).and_then(|s| s.pop()) {
+                    insert(&mut tree, 1, 0, t, start, i, key);
+                }
+            }
+            _ => {}
+        }
+    }
+    for (key, starts) in open {
+        for start in starts {
+            insert(&mut tree, 1, 0, t, start, t, key);
+        }
+    }
+
+    let mut uf = RollbackUnionFind::new(n);
+    let mut answers = Vec::new();
+    // (node, lo, hi, snapshot before the node's unions or None on entry)
+    let mut stack = vec![(1, 0, t, None)];
+    while let Some((node, lo, hi, snapshot)) = stack.pop() {
+        if let Some(s) = snapshot {
+            uf.rollback(s);
+            continue;
+        }
+        stack.push((node, lo, hi, Some(uf.snapshot())));
+        for &(u, v) in tree[node].iter() {
+            uf.union(u, v);
+        }
+        if hi - lo == 1 {
+            match ops[lo] {
+                Op::Connected(u, v) => answers.push(Answer::Connected(uf.connected(u, v))),
+                Op::Components => answers.push(Answer::Components(uf.set_count())),
+                _ => {}
+            }
+            continue;
+        }
+        let mid = (lo + hi) / 2;
+        stack.push((2 * node + 1, mid, hi, None));
+        stack.push((2 * node, lo, mid, None));
+    }
+    answers
+}
+
+// adds edge to the nodes covering times [from, to)
+fn insert(
+    tree: &mut [Vec<(usize, usize)>],
+    node: usize,
+    lo: usize,
+    hi: usize,
+    from: usize,
+    to: usize,
+    edge: (usize, usize),
+) {
+    if to <= lo || hi <= from {
+        return;
+    }
+    if from <= lo && hi <= to {
+        tree[node].push(edge);
+        return;
+    }
+    let mid = (lo + hi) / 2;
+    insert(tree, 2 * node, lo, mid, from, to, edge);
+    insert(tree, 2 * node + 1, mid, hi, from, to, edge);
+}
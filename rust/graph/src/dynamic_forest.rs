@@ -0,0 +1,173 @@
+// Fully dynamic forest over the vertices 0..n: edges are linked and cut at
+// any time and connectivity is answered without recomputing components.
+// Built on a link-cut tree, every operation is amortized O(log n). The
+// forest stays acyclic, linking two vertices that are already connected is
+// refused.
+
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+pub struct DynamicForest {
+    parent: Vec<usize>,
+    child: Vec<[usize; 2]>,
+    flip: Vec<bool>,
+    edges: usize,
+    // scratch for splay
+    path: Vec<usize>,
+}
+
+impl DynamicForest {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: vec![NIL; n],
+            child: vec![[NIL; 2]; n],
+            flip: vec![false; n],
+            edges: 0,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.len() - self.edges
+    }
+
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        u == v || self.find_root(u) == self.find_root(v)
+    }
+
+    // adds the edge u-v, false when u and v are already connected
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+        self.make_root(u);
+        self.parent[u] = v;
+        self.edges += 1;
+        true
+    }
+
+    // removes the edge u-v, false when there is no such edge
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        if !self.has_edge(u, v) {
+            return false;
+        }
+        // has_edge left v at the top with u as its only left child
+        self.child[v][0] = NIL;
+        self.parent[u] = NIL;
+        self.edges -= 1;
+        true
+    }
+
+    pub fn has_edge(&mut self, u: usize, v: usize) -> bool {
+        if u == v || !self.connected(u, v) {
+            return false;
+        }
+        self.make_root(u);
+        self.access(v);
+        self.push(u);
+        self.child[v][0] == u && self.child[u][1] == NIL
+    }
+
+    fn is_root(&self, x: usize) -> bool {
+        let p = self.parent[x];
+        p == NIL || (self.child[p][0] != x && self.child[p][1] != x)
+    }
+
+    fn push(&mut self, x: usize) {
+        if self.flip[x] {
+            self.flip[x] = false;
+            self.child[x].swap(0, 1);
+            for c in self.child[x] {
+                if c != NIL {
+                    self.flip[c] ^= true;
+                }
+            }
+        }
+    }
+
+    fn rotate(&mut self, x: usize) {
+        let p = self.parent[x];
+        let g = self.parent[p];
+        let side = usize::from(self.child[p][1] == x);
+        if !self.is_root(p) {
+            let ps = usize::from(self.child[g][1] == p);
+            self.child[g][ps] = x;
+        }
+        self.parent[x] = g;
+        let inner = self.child[x][side ^ 1];
+        self.child[p][side] = inner;
+        if inner != NIL {
+            self.parent[inner] = p;
+        }
+        self.child[x][side ^ 1] = p;
+        self.parent[p] = x;
+    }
+
+    fn splay(&mut self, x: usize) {
+        // pending flips on the path from the splay root down to x
+        let mut path = std::mem::take(&mut self.path);
+        path.push(x);
+        let mut y = x;
+        while !self.is_root(y) {
+            y = self.parent[y];
+            path.push(y);
+        }
+        while let Some(v) = path.pop() {
+            self.push(v);
+        }
+        self.path = path;
+        while !self.is_root(x) {
+            let p = self.parent[x];
+            if !self.is_root(p) {
+                let g = self.parent[p];
+                let zigzig = (self.child[g][0] == p) == (self.child[p][0] == x);
+                self.rotate(if zigzig { p } else { x });
+            }
+            self.rotate(x);
+        }
+    }
+
+    // makes the root-to-x path preferred and splays x to its top
+    fn access(&mut self, x: usize) {
+        let mut last = NIL;
+        let mut y = x;
+        while y != NIL {
+            self.splay(y);
+            self.child[y][1] = last;
+            last = y;
+            y = self.parent[y];
+        }
+        self.splay(x);
+    }
+
+    fn make_root(&mut self, x: usize) {
+        self.access(x);
+        self.flip[x] ^= true;
+    }
+
+    fn find_root(&mut self, x: usize) -> usize {
+        self.access(x);
+        let mut r = x;
+        loop {
+            self.push(r);
+            match self.child[r][0] {
+                NIL => break,
+                c => r = c,
+            }
+        }
+        self.splay(r);
+        r
+    }
+}
@@ -0,0 +1,513 @@
+use std::{collections::HashSet, ops::ControlFlow};
+
+// Exact cover problem: rows, each a set of columns, to pick so that every
+// primary column is in exactly one picked row and every secondary column in
+// at most one. Rows carry a label, handed back in solutions. Solving runs
+// Knuth's Algorithm X on dancing links, always branching on the primary
+// column with the fewest rows left.
+#[derive(Debug, Clone)]
+pub struct ExactCover<R> {
+    primary: usize,
+    secondary: usize,
+    rows: Vec<(R, Vec<usize>)>,
+}
+
+impl<R> ExactCover<R> {
+    // columns 0..primary, all primary
+    pub fn new(primary: usize) -> Self {
+        Self::with_secondary(primary, 0)
+    }
+
+    // columns 0..primary primary, then secondary more at indices from primary
+    pub fn with_secondary(primary: usize, secondary: usize) -> Self {
+        Self {
+            primary,
+            secondary,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.primary + self.secondary
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn row(&self, i: usize) -> (&R, &[usize]) {
+        let (label, columns) = &self.rows[i];
+        (label, columns)
+    }
+
+    // A row over columns, repeats ignored. The search only branches on
+    // primary columns, so a row with none of them never takes part in a
+    // solution.
+    pub fn add_row(&mut self, label: R, columns: impl IntoIterator<Item = usize>) {
+        let mut columns: Vec<usize> = columns.into_iter().collect();
+        columns.sort_unstable();
+        columns.dedup();
+        if let Some(&c) = columns.last() {
+            assert!(
+                c < self.columns(),
+                "column {c} out of range for {} columns",
+                self.columns()
+            );
+        }
+        self.rows.push((label, columns));
+    }
+
+    // Calls visit with the labels of each solution's rows, until it breaks.
+    // Returns whether it did.
+    pub fn for_each_solution<'a>(
+        &'a self,
+        mut visit: impl FnMut(&[&'a R]) -> ControlFlow<()>,
+    ) -> bool {
+        let mut links = Links::new(self);
+        let mut labels = Vec::new();
+        links.search(|rows| {
+            labels.clear();
+            labels.extend(rows.iter().map(|&r| &self.rows[r].0));
+            visit(&labels)
+        })
+    }
+
+    pub fn first(&self) -> Option<Vec<&R>> {
+        let mut found = None;
+        self.for_each_solution(|rows| {
+            found = Some(rows.to_vec());
+            ControlFlow::Break(())
+        });
+        found
+    }
+
+    // solutions, counting no further than limit
+    pub fn count(&self, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            self.for_each_solution(|_| {
+                count += 1;
+                if count < limit {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                }
+            });
+        }
+        count
+    }
+
+    pub fn all(&self) -> Vec<Vec<&R>> {
+        let mut all = Vec::new();
+        self.for_each_solution(|rows| {
+            all.push(rows.to_vec());
+            ControlFlow::Continue(())
+        });
+        all
+    }
+}
+
+// The dancing links: node 0 is the root, nodes 1..=columns are the column
+// headers, and every 1 of the matrix is a node on two circular lists, its
+// row's (left, right) and its column's (up, down). The root's row list holds
+// the primary headers still to cover. Covering a column unlinks it and every
+// row through it from the other columns; the unlinked nodes keep their own
+// links, so uncovering in reverse order puts everything back.
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    // header of a node's column
+    col: Vec<usize>,
+    // the row of a node, usize::MAX for headers
+    row: Vec<usize>,
+    // rows left in a header's column
+    size: Vec<usize>,
+}
+
+impl Links {
+    fn new<R>(matrix: &ExactCover<R>) -> Self {
+        let headers = matrix.columns() + 1;
+        let nodes = headers + matrix.rows.iter().map(|(_, c)| c.len()).sum::<usize>();
+        let mut links = Self {
+            left: Vec::with_capacity(nodes),
+            right: Vec::with_capacity(nodes),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            col: (0..headers).collect(),
+            row: vec![usize::MAX; headers],
+            size: vec![0; headers],
+        };
+        // the root list runs through the primary headers, the secondary
+        // ones are on lists of their own
+        let primary = matrix.primary;
+        for h in 0..headers {
+            let (l, r) = if h <= primary {
+                ((h + primary) % (primary + 1), (h + 1) % (primary + 1))
+            } else {
+                (h, h)
+            };
+            links.left.push(l);
+            links.right.push(r);
+        }
+        for (r, (_, columns)) in matrix.rows.iter().enumerate() {
+            let first = links.left.len();
+            for (k, &c) in columns.iter().enumerate() {
+                let (h, n) = (c + 1, first + k);
+                links.left.push(if k == 0 {
+                    first + columns.len() - 1
+                } else {
+                    n - 1
+                });
+                links
+                    .right
+                    .push(if k + 1 == columns.len() { first } else { n + 1 });
+                links.up.push(links.up[h]);
+                links.down.push(h);
+                let above = links.up[h];
+                links.down[above] = n;
+                links.up[h] = n;
+                links.col.push(h);
+                links.row.push(r);
+                links.size[h] += 1;
+            }
+        }
+        links
+    }
+
+    fn cover(&mut self, c: usize) {
+        let (l, r) = (self.left[c], self.right[c]);
+        self.right[l] = r;
+        self.left[r] = l;
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = d;
+                self.up[d] = u;
+                self.size[self.col[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = j;
+                self.up[d] = j;
+                self.size[self.col[j]] += 1;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (l, r) = (self.left[c], self.right[c]);
+        self.right[l] = c;
+        self.left[r] = c;
+    }
+
+    // the uncovered primary column with the fewest rows, None if all covered
+    fn choose(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        let mut c = self.right[0];
+        while c != 0 {
+            if best.is_none_or(|b| self.size[c] < self.size[b]) {
+                best = Some(c);
+                if self.size[c] == 0 {
+                    break;
+                }
+            }
+            c = self.right[c];
+        }
+        best
+    }
+
+    // Covers the other columns of the row through node n, or uncovers them
+    // when backing out of it.
+    fn enter(&mut self, n: usize) {
+        let mut j = self.right[n];
+        while j != n {
+            self.cover(self.col[j]);
+            j = self.right[j];
+        }
+    }
+
+    fn leave(&mut self, n: usize) {
+        let mut j = self.left[n];
+        while j != n {
+            self.uncover(self.col[j]);
+            j = self.left[j];
+        }
+    }
+
+    // Algorithm X without recursion: picked[l] is the node of the row tried
+    // at depth l, the column it came from covered. Returns true if visit
+    // broke off the search.
+    fn search(&mut self, mut visit: impl FnMut(&[usize]) -> ControlFlow<()>) -> bool {
+        let mut picked: Vec<usize> = Vec::new();
+        let mut rows: Vec<usize> = Vec::new();
+        // whether to branch deeper, or else move the deepest pick along
+        let mut descend = true;
+        loop {
+            if descend {
+                match self.choose() {
+                    None => {
+                        rows.clear();
+                        rows.extend(picked.iter().map(|&n| self.row[n]));
+                        if visit(&rows).is_break() {
+                            // leave the links as they were, for tidiness
+                            while let Some(n) = picked.pop() {
+                                self.leave(n);
+                                self.uncover(self.col[n]);
+                            }
+                            return true;
+                        }
+                    }
+                    Some(c) => {
+                        self.cover(c);
+                        picked.push(c);
+                    }
+                }
+            }
+            // picked's last node moves down its column: past its row, which
+            // is backed out of, onto the next one, or off the end back to the
+            // header, when the column is uncovered and the search backs up
+            let Some(&n) = picked.last() else {
+                return false;
+            };
+            let c = self.col[n];
+            if n != c {
+                self.leave(n);
+            }
+            let next = self.down[n];
+            if next == c {
+                self.uncover(c);
+                picked.pop();
+                descend = false;
+            } else {
+                *picked.last_mut().unwrap() = next;
+                self.enter(next);
+                descend = true;
+            }
+        }
+    }
+}
+
+// Sudoku of boxes box_rows by box_cols, the grid n by n for n their
+// product, as an exact cover: a row per candidate (row, column, digit), 0
+// for an empty cell, and columns for each cell filled, and each digit once
+// per row, column and box. Clues only get the row of their digit.
+pub fn sudoku_cover(
+    grid: &[Vec<u8>],
+    box_rows: usize,
+    box_cols: usize,
+) -> ExactCover<(usize, usize, u8)> {
+    let n = box_rows * box_cols;
+    assert!(
+        grid.len() == n && grid.iter().all(|row| row.len() == n),
+        "the grid must be {n} by {n}"
+    );
+    assert!(n <= u8::MAX as usize, "digits must fit in a u8");
+    let mut cover = ExactCover::new(4 * n * n);
+    for (r, row) in grid.iter().enumerate() {
+        for (c, &given) in row.iter().enumerate() {
+            assert!(given as usize <= n, "digit {given} at ({r}, {c}) above {n}");
+            let b = r / box_rows * box_rows + c / box_cols;
+            let digits = if given == 0 {
+                1..=n as u8
+            } else {
+                given..=given
+            };
+            for d in digits {
+                let k = d as usize - 1;
+                cover.add_row(
+                    (r, c, d),
+                    [
+                        r * n + c,
+                        n * n + r * n + k,
+                        2 * n * n + c * n + k,
+                        3 * n * n + b * n + k,
+                    ],
+                );
+            }
+        }
+    }
+    cover
+}
+
+// The standard 9 by 9 with 3 by 3 boxes: the grid filled in, None if it
+// has no solution. Conflicting clues have none.
+pub fn solve_sudoku(grid: &[[u8; 9]; 9]) -> Option<[[u8; 9]; 9]> {
+    let rows: Vec<Vec<u8>> = grid.iter().map(|row| row.to_vec()).collect();
+    let cover = sudoku_cover(&rows, 3, 3);
+    let mut out = [[0; 9]; 9];
+    for &(r, c, d) in cover.first()? {
+        out[r][c] = d;
+    }
+    Some(out)
+}
+
+// Polyomino: one or more unit squares, as (row, column) cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polyomino {
+    cells: Vec<(i32, i32)>,
+}
+
+impl Polyomino {
+    pub fn new(cells: impl IntoIterator<Item = (i32, i32)>) -> Self {
+        let cells = normalize(cells.into_iter().collect());
+        assert!(!cells.is_empty(), "a polyomino needs a cell");
+        Self { cells }
+    }
+
+    // From rows of text: any char but ' ' and '.' is a cell.
+    pub fn parse(picture: &str) -> Self {
+        Self::new(picture.lines().enumerate().flat_map(|(r, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|&(_, ch)| ch != ' ' && ch != '.')
+                .map(move |(c, _)| (r as i32, c as i32))
+        }))
+    }
+
+    // the twelve pentominoes, F I L N P T U V W X Y Z
+    pub fn pentominoes() -> Vec<Self> {
+        [
+            ".##\n##.\n.#.",
+            "#####",
+            "####\n#...",
+            "###.\n..##",
+            "###\n##.",
+            "###\n.#.\n.#.",
+            "#.#\n###",
+            "#..\n#..\n###",
+            "#..\n##.\n.##",
+            ".#.\n###\n.#.",
+            "####\n.#..",
+            "##.\n.#.\n.##",
+        ]
+        .into_iter()
+        .map(Self::parse)
+        .collect()
+    }
+
+    pub fn cells(&self) -> &[(i32, i32)] {
+        &self.cells
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // the distinct shapes under rotation, and under reflection too if flips
+    pub fn orientations(&self, flips: bool) -> Vec<Polyomino> {
+        let mut seen = HashSet::new();
+        let mut shapes = Vec::new();
+        for flip in [false, true].into_iter().take(1 + flips as usize) {
+            let mut cells: Vec<(i32, i32)> = self
+                .cells
+                .iter()
+                .map(|&(r, c)| if flip { (r, -c) } else { (r, c) })
+                .collect();
+            for _ in 0..4 {
+                cells = normalize(cells.iter().map(|&(r, c)| (c, -r)).collect());
+                if seen.insert(cells.clone()) {
+                    shapes.push(Polyomino {
+                        cells: cells.clone(),
+                    });
+                }
+            }
+        }
+        shapes
+    }
+}
+
+// sorted, and shifted to touch row 0 and column 0
+fn normalize(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    let top = cells.iter().map(|&(r, _)| r).min().unwrap_or(0);
+    let left = cells.iter().map(|&(_, c)| c).min().unwrap_or(0);
+    for cell in &mut cells {
+        *cell = (cell.0 - top, cell.1 - left);
+    }
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+}
+
+// a piece put down: its index among the pieces, and the board cells it takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub piece: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+// Tiling a board with every piece exactly once, as an exact cover: a row
+// per placement of a piece, in any orientation allowed, over a column per
+// free board cell and one per piece. board[r][c] says whether (r, c) is to
+// be covered.
+pub fn polyomino_cover(
+    board: &[Vec<bool>],
+    pieces: &[Polyomino],
+    flips: bool,
+) -> ExactCover<Placement> {
+    let mut index = vec![vec![usize::MAX; board.first().map_or(0, Vec::len)]; board.len()];
+    let mut free = 0;
+    for (r, row) in board.iter().enumerate() {
+        assert_eq!(row.len(), index[r].len(), "board rows must be equally long");
+        for (c, &open) in row.iter().enumerate() {
+            if open {
+                index[r][c] = free;
+                free += 1;
+            }
+        }
+    }
+    let mut cover = ExactCover::new(free + pieces.len());
+    for (p, piece) in pieces.iter().enumerate() {
+        for shape in piece.orientations(flips) {
+            for (r0, row) in index.iter().enumerate() {
+                for c0 in 0..row.len() {
+                    let cells: Option<Vec<(usize, usize)>> = shape
+                        .cells
+                        .iter()
+                        .map(|&(r, c)| {
+                            let (r, c) = (r0 + r as usize, c0 + c as usize);
+                            let open = index.get(r)?.get(c)? != &usize::MAX;
+                            open.then_some((r, c))
+                        })
+                        .collect();
+                    if let Some(cells) = cells {
+                        let columns: Vec<usize> = cells
+                            .iter()
+                            .map(|&(r, c)| index[r][c])
+                            .chain([free + p])
+                            .collect();
+                        cover.add_row(Placement { piece: p, cells }, columns);
+                    }
+                }
+            }
+        }
+    }
+    cover
+}
+
+// A tiling of the board, with pieces flipped over if flips, None if there
+// is none.
+pub fn tile_polyominoes(
+    board: &[Vec<bool>],
+    pieces: &[Polyomino],
+    flips: bool,
+) -> Option<Vec<Placement>> {
+    let cover = polyomino_cover(board, pieces, flips);
+    let solution = cover.first()?;
+    Some(solution.into_iter().cloned().collect())
+}
@@ -0,0 +1,257 @@
+use std::ops::Range;
+
+use crate::num::Integer;
+
+// Binary indexed tree over n values: tree[i] (1-based) holds the sum of the
+// lowbit(i) values ending at i. Point updates and prefix sums are O(log n)
+// with a single Vec and no recursion.
+#[derive(Debug, Clone)]
+pub struct Fenwick<T> {
+    tree: Vec<T>,
+}
+
+impl<T: Integer> Fenwick<T> {
+    // n zeros
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![T::ZERO; n + 1],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // value i += delta
+    pub fn add(&mut self, i: usize, delta: T) {
+        assert!(
+            i < self.len(),
+            "index {i} out of range for length {}",
+            self.len()
+        );
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += lowbit(i);
+        }
+    }
+
+    // sum of the first end values
+    pub fn prefix(&self, end: usize) -> T {
+        assert!(
+            end <= self.len(),
+            "prefix {end} out of range for length {}",
+            self.len()
+        );
+        let mut sum = T::ZERO;
+        let mut i = end;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= lowbit(i);
+        }
+        sum
+    }
+
+    pub fn range_sum(&self, range: Range<usize>) -> T {
+        assert!(range.start <= range.end, "range {range:?} is reversed");
+        self.prefix(range.end) - self.prefix(range.start)
+    }
+
+    pub fn get(&self, i: usize) -> T {
+        self.range_sum(i..i + 1)
+    }
+
+    pub fn set(&mut self, i: usize, value: T) {
+        let old = self.get(i);
+        self.add(i, value - old);
+    }
+
+    // smallest i with prefix(i + 1) > k, i.e. the position of the k-th unit
+    // (from 0) when the values are counts. Values must be non-negative;
+    // None when the total is at most k. O(log n).
+    pub fn kth(&self, k: T) -> Option<usize> {
+        let n = self.len();
+        let mut pos = 0;
+        let mut rest = k;
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            let next = pos + step;
+            if next <= n && self.tree[next] <= rest {
+                pos = next;
+                rest -= self.tree[next];
+            }
+            step /= 2;
+        }
+        (pos < n).then_some(pos)
+    }
+}
+
+impl<T: Integer> From<Vec<T>> for Fenwick<T> {
+    // O(n): every node passes its sum on to its parent
+    fn from(values: Vec<T>) -> Self {
+        let mut tree = Vec::with_capacity(values.len() + 1);
+        tree.push(T::ZERO);
+        tree.extend(values);
+        for i in 1..tree.len() {
+            let parent = i + lowbit(i);
+            if parent < tree.len() {
+                let v = tree[i];
+                tree[parent] += v;
+            }
+        }
+        Self { tree }
+    }
+}
+
+impl<T: Integer> FromIterator<T> for Fenwick<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+pub(crate) fn lowbit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+// Fenwick tree of Fenwick trees over a rows x cols grid, point updates and
+// rectangle sums in O(log rows * log cols).
+#[derive(Debug, Clone)]
+pub struct Fenwick2D<T> {
+    rows: usize,
+    cols: usize,
+    // (rows + 1) x (cols + 1), row and column 0 unused
+    tree: Vec<T>,
+}
+
+impl<T: Integer> Fenwick2D<T> {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            tree: vec![T::ZERO; (rows + 1) * (cols + 1)],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn add(&mut self, row: usize, col: usize, delta: T) {
+        assert!(
+            row < self.rows && col < self.cols,
+            "cell ({row}, {col}) out of range for {}x{}",
+            self.rows,
+            self.cols
+        );
+        let mut r = row + 1;
+        while r <= self.rows {
+            let mut c = col + 1;
+            while c <= self.cols {
+                self.tree[r * (self.cols + 1) + c] += delta;
+                c += lowbit(c);
+            }
+            r += lowbit(r);
+        }
+    }
+
+    // sum over the first rows x cols cells
+    pub fn prefix(&self, rows: usize, cols: usize) -> T {
+        assert!(
+            rows <= self.rows && cols <= self.cols,
+            "prefix ({rows}, {cols}) out of range for {}x{}",
+            self.rows,
+            self.cols
+        );
+        let mut sum = T::ZERO;
+        let mut r = rows;
+        while r > 0 {
+            let mut c = cols;
+            while c > 0 {
+                sum += self.tree[r * (self.cols + 1) + c];
+                c -= lowbit(c);
+            }
+            r -= lowbit(r);
+        }
+        sum
+    }
+
+    pub fn rect_sum(&self, rows: Range<usize>, cols: Range<usize>) -> T {
+        rect(rows, cols, |r, c| self.prefix(r, c))
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.rect_sum(row..row + 1, col..col + 1)
+    }
+}
+
+// Immutable 2D prefix sums, O(rows * cols) to build and O(1) rectangle sums.
+#[derive(Debug, Clone)]
+pub struct PrefixSum2D<T> {
+    rows: usize,
+    cols: usize,
+    // sums[r * (cols + 1) + c] is the sum over the first r x c cells
+    sums: Vec<T>,
+}
+
+impl<T: Integer> PrefixSum2D<T> {
+    // all rows must have the same length
+    pub fn new(grid: &[Vec<T>]) -> Self {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        let width = cols + 1;
+        let mut sums = vec![T::ZERO; (rows + 1) * width];
+        for (r, row) in grid.iter().enumerate() {
+            assert_eq!(row.len(), cols, "row {r} has a different length");
+            let mut run = T::ZERO;
+            for (c, &v) in row.iter().enumerate() {
+                run += v;
+                sums[(r + 1) * width + c + 1] = sums[r * width + c + 1] + run;
+            }
+        }
+        Self { rows, cols, sums }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn prefix(&self, rows: usize, cols: usize) -> T {
+        assert!(
+            rows <= self.rows && cols <= self.cols,
+            "prefix ({rows}, {cols}) out of range for {}x{}",
+            self.rows,
+            self.cols
+        );
+        self.sums[rows * (self.cols + 1) + cols]
+    }
+
+    pub fn rect_sum(&self, rows: Range<usize>, cols: Range<usize>) -> T {
+        rect(rows, cols, |r, c| self.prefix(r, c))
+    }
+}
+
+// inclusion-exclusion over the four corner prefixes
+fn rect<T: Integer>(
+    rows: Range<usize>,
+    cols: Range<usize>,
+    prefix: impl Fn(usize, usize) -> T,
+) -> T {
+    assert!(
+        rows.start <= rows.end && cols.start <= cols.end,
+        "rectangle {rows:?} x {cols:?} is reversed"
+    );
+    prefix(rows.end, cols.end) - prefix(rows.start, cols.end) - prefix(rows.end, cols.start)
+        + prefix(rows.start, cols.start)
+}
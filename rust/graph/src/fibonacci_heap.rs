@@ -0,0 +1,395 @@
+use std::{error::Error, fmt};
+
+use crate::arena::{Arena, Id, Tags};
+use crate::priority_queue::{Compare, MinOrder, PriorityQueue};
+
+// Lazy collection of heap-ordered trees kept in circular doubly linked
+// lists. push and decrease_key are O(1) amortized; trees are only linked
+// by degree when the minimum is popped, O(log n) amortized. A vertex that
+// loses a second child is cut from its parent as well, which keeps the
+// degrees logarithmic. The nodes live in an arena and link to each other
+// by slot, so a handle is an arena id that stops resolving once its item
+// is popped.
+pub struct FibonacciHeap<T, C = MinOrder> {
+    nodes: Arena<Node<T>>,
+    min: Link,
+    tags: Tags,
+    cmp: C,
+}
+
+type Link = Option<u32>;
+
+struct Node<T> {
+    item: T,
+    parent: Link,
+    // any child, the children form a ring through left and right
+    child: Link,
+    left: u32,
+    right: u32,
+    degree: usize,
+    // lost a child since it became a child itself
+    marked: bool,
+}
+
+// Refers to a pushed item until it is popped, in the heap that returned it
+// or a heap that heap was melded into. Anywhere else it refers to nothing.
+pub struct Handle<T> {
+    tag: u64,
+    id: Id<Node<T>>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.tag, self.id) == (other.tag, other.id)
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({}:{:?})", self.tag, self.id)
+    }
+}
+
+// a decrease_key through a handle whose item is gone, with the item
+// handed back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleHandle<T>(pub T);
+
+impl<T> fmt::Display for StaleHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "handle refers to no item in this heap")
+    }
+}
+
+impl<T: fmt::Debug> Error for StaleHandle<T> {}
+
+impl<T: Ord> FibonacciHeap<T> {
+    pub fn new() -> Self {
+        Self::with_compare(MinOrder)
+    }
+}
+
+impl<T: Ord> Default for FibonacciHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Compare<T>> FibonacciHeap<T, C> {
+    pub fn with_compare(cmp: C) -> Self {
+        Self {
+            nodes: Arena::new(),
+            min: None,
+            tags: Tags::new(),
+            cmp,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.min.map(|m| &self.nodes.at(m).item)
+    }
+
+    pub fn push(&mut self, item: T) -> Handle<T> {
+        let id = self.nodes.insert(Node {
+            item,
+            parent: None,
+            child: None,
+            left: 0,
+            right: 0,
+            degree: 0,
+            marked: false,
+        });
+        let x = id.index() as u32;
+        let node = self.nodes.at_mut(x);
+        node.left = x;
+        node.right = x;
+        self.add_root(x);
+        Handle {
+            tag: self.tags.own(),
+            id,
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let z = self.min?;
+        if let Some(child) = self.nodes.at(z).child {
+            for c in ring(&self.nodes, child) {
+                let node = self.nodes.at_mut(c);
+                node.parent = None;
+                node.marked = false;
+            }
+            splice(&mut self.nodes, z, child);
+        }
+        let (left, rest) = (self.nodes.at(z).left, self.nodes.at(z).right);
+        self.nodes.at_mut(left).right = rest;
+        self.nodes.at_mut(rest).left = left;
+        let node = self.nodes.remove(self.nodes.id_at(z)).unwrap();
+        if rest == z {
+            self.min = None;
+        } else {
+            self.min = Some(rest);
+            self.consolidate();
+        }
+        Some(node.item)
+    }
+
+    // moves every item of other into self, the nodes of the smaller heap
+    // into the arena of the larger, so O(min(n, m)); handles of other stay
+    // valid for self
+    pub fn meld(&mut self, mut other: Self) {
+        if other.len() > self.len() {
+            std::mem::swap(&mut self.nodes, &mut other.nodes);
+            std::mem::swap(&mut self.min, &mut other.min);
+            std::mem::swap(&mut self.tags, &mut other.tags);
+        }
+        let offset = self.nodes.append(other.nodes, |node, offset| {
+            for link in [&mut node.parent, &mut node.child] {
+                *link = link.map(|i| i + offset);
+            }
+            node.left += offset;
+            node.right += offset;
+        });
+        self.tags.absorb(other.tags, offset);
+        if let Some(root) = other.min {
+            self.add_root(root + offset);
+        }
+    }
+
+    // the slot of handle's node, if its item is still in this heap
+    fn slot(&self, handle: Handle<T>) -> Option<u32> {
+        let id = handle.id.shifted(self.tags.offset(handle.tag)?);
+        self.nodes.contains(id).then_some(id.index() as u32)
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.slot(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slot(handle).map(|i| &self.nodes.at(i).item)
+    }
+
+    /// Replaces the item behind `handle` with one that is popped no later,
+    /// or hands it back if that item was popped or `handle` is from a heap
+    /// that was never melded into this one. Panics if `item` would be
+    /// popped after the current one.
+    pub fn decrease_key(&mut self, handle: Handle<T>, item: T) -> Result<(), StaleHandle<T>> {
+        let Some(x) = self.slot(handle) else {
+            return Err(StaleHandle(item));
+        };
+        let node = self.nodes.at_mut(x);
+        assert!(
+            !self.cmp.before(&node.item, &item),
+            "decrease_key with a later item"
+        );
+        node.item = item;
+        let parent = node.parent;
+        if let Some(parent) = parent.filter(|&p| self.before(x, p)) {
+            self.cut(x);
+            let mut y = parent;
+            while let Some(next) = self.nodes.at(y).parent {
+                let node = self.nodes.at_mut(y);
+                if !node.marked {
+                    node.marked = true;
+                    break;
+                }
+                self.cut(y);
+                y = next;
+            }
+        }
+        if self.before(x, self.min.unwrap()) {
+            self.min = Some(x);
+        }
+        Ok(())
+    }
+
+    // handles given out so far refer to nothing afterwards
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.min = None;
+        self.tags = Tags::new();
+    }
+
+    fn before(&self, a: u32, b: u32) -> bool {
+        self.cmp
+            .before(&self.nodes.at(a).item, &self.nodes.at(b).item)
+    }
+
+    // the ring of node joins the roots, node being the least of its ring
+    fn add_root(&mut self, node: u32) {
+        let Some(min) = self.min else {
+            self.min = Some(node);
+            return;
+        };
+        splice(&mut self.nodes, min, node);
+        if self.before(node, min) {
+            self.min = Some(node);
+        }
+    }
+
+    // moves x from the children of its parent to the root ring
+    fn cut(&mut self, x: u32) {
+        let node = self.nodes.at(x);
+        let (parent, left, right) = (node.parent.unwrap(), node.left, node.right);
+        if right == x {
+            self.nodes.at_mut(parent).child = None;
+        } else {
+            self.nodes.at_mut(left).right = right;
+            self.nodes.at_mut(right).left = left;
+            let p = self.nodes.at_mut(parent);
+            if p.child == Some(x) {
+                p.child = Some(right);
+            }
+        }
+        self.nodes.at_mut(parent).degree -= 1;
+        let node = self.nodes.at_mut(x);
+        node.left = x;
+        node.right = x;
+        node.parent = None;
+        node.marked = false;
+        splice(&mut self.nodes, self.min.unwrap(), x);
+    }
+
+    // Links roots of equal degree until all degrees differ, then rebuilds
+    // the root ring and finds the new minimum. Every root leaves the ring
+    // before any linking, so a comparator panic can't leave it half
+    // rewired: the guard rings up whatever it still holds.
+    fn consolidate(&mut self) {
+        let Self {
+            nodes, min, cmp, ..
+        } = self;
+        let pending = ring(nodes, min.take().unwrap());
+        for &r in &pending {
+            let node = nodes.at_mut(r);
+            node.left = r;
+            node.right = r;
+        }
+        let mut roots = Roots {
+            nodes,
+            pending,
+            carry: None,
+            by_degree: Vec::new(),
+            min,
+        };
+        while let Some(x) = roots.pending.pop() {
+            roots.carry = Some(x);
+            let mut d = roots.nodes.at(x).degree;
+            loop {
+                if d >= roots.by_degree.len() {
+                    roots.by_degree.resize(d + 1, None);
+                }
+                let Some(mut y) = roots.by_degree[d] else {
+                    break;
+                };
+                let mut x = roots.carry.unwrap();
+                if cmp.before(&roots.nodes.at(y).item, &roots.nodes.at(x).item) {
+                    std::mem::swap(&mut x, &mut y);
+                }
+                // y becomes a child of x
+                let node = roots.nodes.at_mut(y);
+                node.parent = Some(x);
+                node.marked = false;
+                match roots.nodes.at(x).child {
+                    None => roots.nodes.at_mut(x).child = Some(y),
+                    Some(child) => splice(roots.nodes, child, y),
+                }
+                roots.nodes.at_mut(x).degree += 1;
+                roots.by_degree[d] = None;
+                roots.carry = Some(x);
+                d += 1;
+            }
+            roots.by_degree[d] = roots.carry.take();
+        }
+        drop(roots);
+        let start = self.min.unwrap();
+        for root in ring(&self.nodes, start) {
+            if self.before(root, self.min.unwrap()) {
+                self.min = Some(root);
+            }
+        }
+    }
+}
+
+// The roots of a consolidate in progress, each a ring of its own: those
+// not yet linked, the one being linked and one per degree. Dropping it
+// joins them all into the root ring.
+struct Roots<'a, T> {
+    nodes: &'a mut Arena<Node<T>>,
+    pending: Vec<u32>,
+    carry: Link,
+    by_degree: Vec<Link>,
+    min: &'a mut Link,
+}
+
+impl<T> Drop for Roots<'_, T> {
+    fn drop(&mut self) {
+        let by_degree = self.by_degree.iter().flatten();
+        for &root in self.pending.iter().chain(&self.carry).chain(by_degree) {
+            match *self.min {
+                Some(min) => splice(self.nodes, min, root),
+                None => *self.min = Some(root),
+            }
+        }
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for FibonacciHeap<T, C> {
+    fn push(&mut self, item: T) {
+        FibonacciHeap::push(self, item);
+    }
+    fn pop(&mut self) -> Option<T> {
+        FibonacciHeap::pop(self)
+    }
+    fn peek(&self) -> Option<&T> {
+        FibonacciHeap::peek(self)
+    }
+    fn len(&self) -> usize {
+        FibonacciHeap::len(self)
+    }
+}
+
+impl<T, C> fmt::Debug for FibonacciHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FibonacciHeap")
+            .field("len", &self.nodes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// joins the rings of a and b into one
+fn splice<T>(nodes: &mut Arena<Node<T>>, a: u32, b: u32) {
+    let a_right = nodes.at(a).right;
+    let b_left = nodes.at(b).left;
+    nodes.at_mut(a).right = b;
+    nodes.at_mut(b).left = a;
+    nodes.at_mut(b_left).right = a_right;
+    nodes.at_mut(a_right).left = b_left;
+}
+
+fn ring<T>(nodes: &Arena<Node<T>>, start: u32) -> Vec<u32> {
+    let mut ring = vec![start];
+    let mut cur = nodes.at(start).right;
+    while cur != start {
+        ring.push(cur);
+        cur = nodes.at(cur).right;
+    }
+    ring
+}
@@ -0,0 +1,49 @@
+use crate::rng::Rng;
+
+// Random edge lists as (from, to, weight) over vertices 0..n, for
+// benchmarks and randomized checks. Weights are uniform in 1..=max_weight.
+
+// m directed edges with uniformly random endpoints, self loops and
+// parallel edges included
+pub fn random_edges<R: Rng>(
+    n: usize,
+    m: usize,
+    max_weight: i32,
+    rng: &mut R,
+) -> Vec<(usize, usize, i32)> {
+    (0..m)
+        .map(|_| (rng.gen_range(n), rng.gen_range(n), weight(max_weight, rng)))
+        .collect()
+}
+
+// a random spanning tree plus `extra` random edges, no self loops
+pub fn random_connected<R: Rng>(
+    n: usize,
+    extra: usize,
+    max_weight: i32,
+    rng: &mut R,
+) -> Vec<(usize, usize, i32)> {
+    let mut edges = Vec::with_capacity(n + extra);
+    for v in 1..n {
+        edges.push((rng.gen_range(v), v, weight(max_weight, rng)));
+    }
+    while n > 1 && edges.len() < n - 1 + extra {
+        let (a, b) = (rng.gen_range(n), rng.gen_range(n));
+        if a != b {
+            edges.push((a, b, weight(max_weight, rng)));
+        }
+    }
+    edges
+}
+
+// every edge followed by its reverse with the same weight
+pub fn undirected(edges: &[(usize, usize, i32)]) -> Vec<(usize, usize, i32)> {
+    edges
+        .iter()
+        .flat_map(|&(a, b, wt)| [(a, b, wt), (b, a, wt)])
+        .collect()
+}
+
+fn weight<R: Rng>(max_weight: i32, rng: &mut R) -> i32 {
+    1 + rng.gen_range(max_weight.max(1) as usize) as i32
+}
@@ -0,0 +1,71 @@
+// Axis-aligned boxes and points in R^D shared by the spatial indexes. All
+// comparisons are inclusive, a box contains the points on its boundary.
+
+pub fn dist2<const D: usize>(a: &[f64; D], b: &[f64; D]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect<const D: usize> {
+    pub min: [f64; D],
+    pub max: [f64; D],
+}
+
+impl<const D: usize> Rect<D> {
+    pub fn new(min: [f64; D], max: [f64; D]) -> Self {
+        assert!(
+            (0..D).all(|i| min[i] <= max[i]),
+            "rect corners {min:?} and {max:?} are out of order"
+        );
+        Self { min, max }
+    }
+
+    // degenerate box around a single point
+    pub fn point(p: [f64; D]) -> Self {
+        Self { min: p, max: p }
+    }
+
+    pub fn contains_point(&self, p: &[f64; D]) -> bool {
+        (0..D).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        (0..D).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..D).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    pub fn center(&self) -> [f64; D] {
+        std::array::from_fn(|i| (self.min[i] + self.max[i]) / 2.0)
+    }
+
+    // area in 2d, volume in 3d
+    pub fn volume(&self) -> f64 {
+        (0..D).map(|i| self.max[i] - self.min[i]).product()
+    }
+
+    // smallest box around both
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: std::array::from_fn(|i| self.min[i].min(other.min[i])),
+            max: std::array::from_fn(|i| self.max[i].max(other.max[i])),
+        }
+    }
+
+    // how much the volume grows to take in other
+    pub fn enlargement(&self, other: &Self) -> f64 {
+        self.union(other).volume() - self.volume()
+    }
+
+    // squared distance from p to the nearest point of the box, 0 inside
+    pub fn min_dist2(&self, p: &[f64; D]) -> f64 {
+        (0..D)
+            .map(|i| {
+                let d = (self.min[i] - p[i]).max(p[i] - self.max[i]).max(0.0);
+                d * d
+            })
+            .sum()
+    }
+}
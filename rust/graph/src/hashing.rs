@@ -0,0 +1,20 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+// Seeded hashing for the probabilistic structures. Filters and sketches can
+// only be merged if they hashed alike, so nothing here is randomized per
+// process the way std's RandomState is: DefaultHasher::new has fixed keys,
+// and the seed goes in first.
+
+pub(crate) fn seeded_hash<T: Hash + ?Sized>(item: &T, seed: u64) -> u64 {
+    let mut h = DefaultHasher::new();
+    h.write_u64(seed);
+    item.hash(&mut h);
+    h.finish()
+}
+
+// SplitMix64's finalizer, for deriving more hash values from one
+pub(crate) fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,175 @@
+use std::{f64::consts::LN_2, fmt, hash::Hash, marker::PhantomData};
+
+use crate::hashing::seeded_hash;
+
+// HyperLogLog: 2^p registers, each item's hash picking a register by its
+// top p bits and offering the position of the first 1 in the rest; a
+// register keeps the largest offer. n distinct items push the registers up
+// to about log2(n / 2^p), and the estimate comes out within 1.04 / sqrt(2^p)
+// relative error. The estimator is Ertl's (2017) from the register
+// histogram, which corrects the bias of the classic harmonic mean at small
+// and large counts alike without empirical tables.
+pub struct HyperLogLog<T: ?Sized> {
+    registers: Vec<u8>,
+    precision: u32,
+    seed: u64,
+    item: PhantomData<fn(&T)>,
+}
+
+pub const MIN_PRECISION: u32 = 4;
+pub const MAX_PRECISION: u32 = 18;
+
+// merging needs the same precision and seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncompatibleSketches;
+
+impl fmt::Display for IncompatibleSketches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sketches differ in precision or seed")
+    }
+}
+
+impl std::error::Error for IncompatibleSketches {}
+
+impl<T: Hash + ?Sized> HyperLogLog<T> {
+    pub fn new(precision: u32) -> Self {
+        Self::with_seed(precision, 0)
+    }
+
+    pub fn with_seed(precision: u32, seed: u64) -> Self {
+        assert!(
+            (MIN_PRECISION..=MAX_PRECISION).contains(&precision),
+            "precision must be {MIN_PRECISION} to {MAX_PRECISION}, got {precision}"
+        );
+        Self {
+            registers: vec![0; 1 << precision],
+            precision,
+            seed,
+            item: PhantomData,
+        }
+    }
+
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    // standard error of the estimate relative to the count
+    pub fn relative_error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    // true if a register went up
+    pub fn insert(&mut self, item: &T) -> bool {
+        let h = seeded_hash(item, self.seed);
+        let p = self.precision;
+        let index = (h >> (64 - p)) as usize;
+        // the rest of the bits, q = 64 - p of them, giving 1..=q + 1
+        let rest = h << p;
+        let rank = (rest.leading_zeros().min(64 - p) + 1) as u8;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registers.iter().all(|&r| r == 0)
+    }
+
+    // estimated number of distinct items inserted
+    pub fn estimate(&self) -> f64 {
+        let q = 64 - self.precision as usize;
+        let m = self.registers.len() as f64;
+        let mut counts = vec![0u32; q + 2];
+        for &r in &self.registers {
+            counts[r as usize] += 1;
+        }
+        let mut z = m * tau(1.0 - counts[q + 1] as f64 / m);
+        for k in (1..=q).rev() {
+            z = 0.5 * (z + counts[k] as f64);
+        }
+        z += m * sigma(counts[0] as f64 / m);
+        m * m / (2.0 * LN_2 * z)
+    }
+
+    // afterwards estimates the distinct items of both
+    pub fn merge(&mut self, other: &Self) -> Result<(), IncompatibleSketches> {
+        if (self.precision, self.seed) != (other.precision, other.seed) {
+            return Err(IncompatibleSketches);
+        }
+        for (a, &b) in self.registers.iter_mut().zip(&other.registers) {
+            *a = (*a).max(b);
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.registers.fill(0);
+    }
+}
+
+// sum of x^(2^k) 2^(k-1) for k >= 1, plus x
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let last = z;
+        z += x * y;
+        y += y;
+        if z == last {
+            return z;
+        }
+    }
+}
+
+// (1 - x - sum of (1 - x^(2^-k))^2 2^-k for k >= 1) / 3
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let last = z;
+        y *= 0.5;
+        z -= (1.0 - x) * (1.0 - x) * y;
+        if z == last {
+            return z / 3.0;
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for HyperLogLog<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HyperLogLog")
+            .field("precision", &self.precision)
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+// by hand, a derive would want T: Clone
+impl<T: ?Sized> Clone for HyperLogLog<T> {
+    fn clone(&self) -> Self {
+        Self {
+            registers: self.registers.clone(),
+            item: PhantomData,
+            ..*self
+        }
+    }
+}
+
+impl<T: Hash> Extend<T> for HyperLogLog<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(&item);
+        }
+    }
+}
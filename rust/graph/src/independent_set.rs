@@ -0,0 +1,170 @@
+use std::{cmp::Reverse, collections::BinaryHeap, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::covering::vertex_cover_2approx;
+
+// Edges are read as undirected. A vertex with a self loop conflicts with
+// itself, so it never joins an independent set and is always in a cover.
+
+// graphs up to this many vertices are solved exactly
+pub const EXACT_LIMIT: usize = 40;
+
+// exact for small graphs, greedy min degree heuristic above EXACT_LIMIT
+pub fn maximum_independent_set<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Vec<T> {
+    if graph.vertex_num() as usize <= EXACT_LIMIT {
+        exact_independent_set(graph)
+    } else {
+        greedy_independent_set(graph)
+    }
+}
+
+// exact up to EXACT_LIMIT, above it both endpoints of a maximal matching,
+// at most twice the minimum
+pub fn minimum_vertex_cover<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Vec<T> {
+    if graph.vertex_num() as usize <= EXACT_LIMIT {
+        exact_vertex_cover(graph)
+    } else {
+        vertex_cover_2approx(graph).cover
+    }
+}
+
+// branch and bound over bitmasks, panics above EXACT_LIMIT vertices
+pub fn exact_independent_set<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Vec<T> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    assert!(
+        n <= EXACT_LIMIT,
+        "exact search is limited to {EXACT_LIMIT} vertices"
+    );
+    let adj = undirected(&g.adj);
+    let mut nbr = vec![0u64; n];
+    let mut cand = 0u64;
+    for v in 0..n {
+        for &u in adj[v].iter() {
+            nbr[v] |= 1 << u;
+        }
+        if nbr[v] & (1 << v) == 0 {
+            cand |= 1 << v;
+        }
+    }
+    let mut best = 0u64;
+    branch(&nbr, cand, 0, &mut best);
+    (0..n)
+        .filter(|&v| best & (1 << v) != 0)
+        .map(|v| g.keys[v].clone())
+        .collect()
+}
+
+pub fn exact_vertex_cover<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> Vec<T> {
+    complement(graph, &exact_independent_set(graph))
+}
+
+// repeatedly take a vertex of minimum remaining degree and drop its
+// neighbors; the result is maximal but not necessarily maximum
+pub fn greedy_independent_set<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Vec<T> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let adj = undirected(&g.adj);
+    let mut removed = vec![false; n];
+    let mut degree: Vec<usize> = adj.iter().map(|a| a.len()).collect();
+    let mut heap = BinaryHeap::new();
+    for v in 0..n {
+        if adj[v].contains(&v) {
+            removed[v] = true;
+        } else {
+            heap.push(Reverse((degree[v], v)));
+        }
+    }
+    let mut set = Vec::new();
+    while let Some(Reverse((d, v))) = heap.pop() {
+        if removed[v] || d != degree[v] {
+            continue;
+        }
+        removed[v] = true;
+        set.push(g.keys[v].clone());
+        for &u in adj[v].iter() {
+            if removed[u] {
+                continue;
+            }
+            removed[u] = true;
+            for &w in adj[u].iter() {
+                if !removed[w] {
+                    degree[w] -= 1;
+                    heap.push(Reverse((degree[w], w)));
+                }
+            }
+        }
+    }
+    set
+}
+
+pub fn greedy_vertex_cover<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Vec<T> {
+    complement(graph, &greedy_independent_set(graph))
+}
+
+fn branch(nbr: &[u64], cand: u64, cur: u64, best: &mut u64) {
+    if cand == 0 {
+        if cur.count_ones() > best.count_ones() {
+            *best = cur;
+        }
+        return;
+    }
+    if cur.count_ones() + cand.count_ones() <= best.count_ones() {
+        return;
+    }
+    let (mut low, mut low_deg) = (0, u32::MAX);
+    let (mut high, mut high_deg) = (0, 0);
+    let mut rest = cand;
+    while rest != 0 {
+        let v = rest.trailing_zeros() as usize;
+        rest &= rest - 1;
+        let d = (nbr[v] & cand).count_ones();
+        if d < low_deg {
+            (low, low_deg) = (v, d);
+        }
+        if d >= high_deg {
+            (high, high_deg) = (v, d);
+        }
+    }
+    // some maximum set contains any vertex of degree 0 or 1
+    if low_deg <= 1 {
+        branch(nbr, cand & !nbr[low] & !(1 << low), cur | 1 << low, best);
+        return;
+    }
+    branch(nbr, cand & !nbr[high] & !(1 << high), cur | 1 << high, best);
+    branch(nbr, cand & !(1 << high), cur, best);
+}
+
+pub(crate) fn undirected(adj: &[Vec<(usize, i32)>]) -> Vec<Vec<usize>> {
+    let mut out = vec![Vec::new(); adj.len()];
+    for (v, nbrs) in adj.iter().enumerate() {
+        for &(u, _) in nbrs.iter() {
+            out[v].push(u);
+            out[u].push(v);
+        }
+    }
+    for nbrs in out.iter_mut() {
+        nbrs.sort_unstable();
+        nbrs.dedup();
+    }
+    out
+}
+
+fn complement<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>, set: &[T]) -> Vec<T> {
+    let set: std::collections::HashSet<&T> = set.iter().collect();
+    graph
+        .vertex_keys()
+        .into_iter()
+        .filter(|k| !set.contains(k))
+        .collect()
+}
@@ -0,0 +1,155 @@
+// Min-heap over the items 0..n with a priority per item. The position of
+// every item is tracked, so its priority can be lowered in place instead
+// of pushing a duplicate entry. D is the arity; wider heaps are shallower,
+// which makes decrease_key cheaper and pop a little more expensive.
+
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<K, const D: usize = 4> {
+    // heap of (key, item)
+    heap: Vec<(K, usize)>,
+    // position of each item in heap, usize::MAX when absent
+    pos: Vec<usize>,
+}
+
+const ABSENT: usize = usize::MAX;
+
+impl<K: Ord + Copy, const D: usize> IndexedHeap<K, D> {
+    // items must be below n
+    pub fn new(n: usize) -> Self {
+        assert!(D >= 2, "heap arity must be at least 2");
+        Self {
+            heap: Vec::new(),
+            pos: vec![ABSENT; n],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, item: usize) -> bool {
+        self.pos[item] != ABSENT
+    }
+
+    pub fn key(&self, item: usize) -> Option<K> {
+        match self.pos[item] {
+            ABSENT => None,
+            i => Some(self.heap[i].0),
+        }
+    }
+
+    pub fn peek(&self) -> Option<(usize, K)> {
+        self.heap.first().map(|&(k, item)| (item, k))
+    }
+
+    // the item must not be in the heap yet
+    pub fn push(&mut self, item: usize, key: K) {
+        assert!(!self.contains(item), "item {item} is already in the heap");
+        self.heap.push((key, item));
+        self.pos[item] = self.heap.len() - 1;
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    // lowers the key of an item in the heap; a larger key is ignored
+    pub fn decrease_key(&mut self, item: usize, key: K) {
+        let i = self.pos[item];
+        assert!(i != ABSENT, "item {item} is not in the heap");
+        if key < self.heap[i].0 {
+            self.heap[i].0 = key;
+            self.sift_up(i);
+        }
+    }
+
+    // pushes a new item or lowers the key of a present one, returns
+    // whether the heap changed
+    pub fn push_or_decrease(&mut self, item: usize, key: K) -> bool {
+        match self.key(item) {
+            None => {
+                self.push(item, key);
+                true
+            }
+            Some(old) if key < old => {
+                self.decrease_key(item, key);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<(usize, K)> {
+        let last = self.heap.len().checked_sub(1)?;
+        self.swap(0, last);
+        let (key, item) = self.heap.pop().unwrap();
+        self.pos[item] = ABSENT;
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some((item, key))
+    }
+
+    // removes an item wherever it is
+    pub fn remove(&mut self, item: usize) -> Option<K> {
+        let i = self.pos[item];
+        if i == ABSENT {
+            return None;
+        }
+        let last = self.heap.len() - 1;
+        self.swap(i, last);
+        let (key, _) = self.heap.pop().unwrap();
+        self.pos[item] = ABSENT;
+        if i < self.heap.len() {
+            self.sift_down(i);
+            self.sift_up(i);
+        }
+        Some(key)
+    }
+
+    pub fn clear(&mut self) {
+        for &(_, item) in self.heap.iter() {
+            self.pos[item] = ABSENT;
+        }
+        self.heap.clear();
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.pos[self.heap[a].1] = a;
+        self.pos[self.heap[b].1] = b;
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / D;
+            if self.heap[i].0 >= self.heap[parent].0 {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let first = D * i + 1;
+            if first >= self.heap.len() {
+                break;
+            }
+            let last = (first + D).min(self.heap.len());
+            let mut best = first;
+            for c in first + 1..last {
+                if self.heap[c].0 < self.heap[best].0 {
+                    best = c;
+                }
+            }
+            if self.heap[best].0 >= self.heap[i].0 {
+                break;
+            }
+            self.swap(i, best);
+            i = best;
+        }
+    }
+}
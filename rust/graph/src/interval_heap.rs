@@ -0,0 +1,211 @@
+use std::slice;
+
+// Double-ended priority queue. Slots 2i and 2i+1 form node i and hold the
+// interval [lo, hi] of its subtree: the lo ends are a min-heap, the hi ends
+// a max-heap, and every interval lies inside its parent's. The last node
+// may hold a single item, which counts as both ends.
+#[derive(Debug, Clone)]
+pub struct IntervalHeap<T> {
+    data: Vec<T>,
+}
+
+impl<T: Ord> Default for IntervalHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> IntervalHeap<T> {
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn peek_max(&self) -> Option<&T> {
+        self.data.get(1).or(self.data.first())
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        let i = self.data.len() - 1;
+        if i % 2 == 1 {
+            if self.data[i] < self.data[i - 1] {
+                self.data.swap(i - 1, i);
+                self.sift_up_min(i - 1);
+            } else {
+                self.sift_up_max(i);
+            }
+        } else if i > 0 {
+            let parent = (i / 2 - 1) / 2;
+            if self.data[i] < self.data[2 * parent] {
+                self.sift_up_min(i);
+            } else if self.data[i] > self.data[2 * parent + 1] {
+                self.sift_up_max(i);
+            }
+        }
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let min = self.data.swap_remove(0);
+        if !self.data.is_empty() {
+            self.sift_down_min(0);
+        }
+        Some(min)
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        if self.data.len() <= 2 {
+            return self.data.pop();
+        }
+        let max = self.data.swap_remove(1);
+        self.sift_down_max(1);
+        Some(max)
+    }
+
+    // keeps the capacity smallest items seen: pushes item and returns the
+    // largest item when that leaves more than capacity of them
+    pub fn push_bounded(&mut self, item: T, capacity: usize) -> Option<T> {
+        if self.len() < capacity {
+            self.push(item);
+            return None;
+        }
+        if self.peek_max().is_none_or(|max| item >= *max) {
+            return Some(item);
+        }
+        self.push(item);
+        self.pop_max()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    // arbitrary order
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(item) = self.pop_min() {
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    // i is the lo slot of its node
+    fn sift_up_min(&mut self, mut i: usize) {
+        while i >= 2 {
+            let parent_lo = 2 * ((i / 2 - 1) / 2);
+            if self.data[i] >= self.data[parent_lo] {
+                break;
+            }
+            self.data.swap(i, parent_lo);
+            i = parent_lo;
+        }
+    }
+
+    // i is the hi slot of its node, or the single slot of the last node
+    fn sift_up_max(&mut self, mut i: usize) {
+        while i >= 2 {
+            let parent_hi = 2 * ((i / 2 - 1) / 2) + 1;
+            if self.data[i] <= self.data[parent_hi] {
+                break;
+            }
+            self.data.swap(i, parent_hi);
+            i = parent_hi;
+        }
+    }
+
+    fn sift_down_min(&mut self, mut i: usize) {
+        let n = self.data.len();
+        loop {
+            if i + 1 < n && self.data[i] > self.data[i + 1] {
+                self.data.swap(i, i + 1);
+            }
+            let left = 2 * i + 2;
+            if left >= n {
+                break;
+            }
+            let right = left + 2;
+            let child = if right < n && self.data[right] < self.data[left] {
+                right
+            } else {
+                left
+            };
+            if self.data[child] >= self.data[i] {
+                break;
+            }
+            self.data.swap(i, child);
+            i = child;
+        }
+    }
+
+    fn sift_down_max(&mut self, mut i: usize) {
+        let n = self.data.len();
+        loop {
+            if self.data[i - 1] > self.data[i] {
+                self.data.swap(i - 1, i);
+            }
+            let left = 2 * i;
+            if left >= n {
+                break;
+            }
+            // hi slot of a child node, the lo slot when it holds one item
+            let hi = |lo: usize| if lo + 1 < n { lo + 1 } else { lo };
+            let mut child = hi(left);
+            let right = left + 2;
+            if right < n && self.data[hi(right)] > self.data[child] {
+                child = hi(right);
+            }
+            if self.data[child] <= self.data[i] {
+                break;
+            }
+            self.data.swap(i, child);
+            if child % 2 == 0 {
+                break;
+            }
+            i = child;
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for IntervalHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for IntervalHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
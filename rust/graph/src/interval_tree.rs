@@ -0,0 +1,325 @@
+use std::{cmp::Ordering, fmt, ops::Bound, ops::Range};
+
+// Half-open intervals [start, end) with values, on an AVL tree ordered by
+// (start, end). Every node also keeps the largest end in its subtree, so
+// an overlap query skips the subtrees that end before it starts and stops
+// at the first start past its end: O(log n + k) for k reported intervals.
+// Intervals that are inserted more than once share a node.
+pub struct IntervalTree<T, V> {
+    root: Link<T, V>,
+    len: usize,
+}
+
+type Link<T, V> = Option<Box<Node<T, V>>>;
+
+struct Node<T, V> {
+    range: Range<T>,
+    values: Vec<V>,
+    // largest end in the subtree
+    max: T,
+    height: u8,
+    left: Link<T, V>,
+    right: Link<T, V>,
+}
+
+fn height<T, V>(link: &Link<T, V>) -> u8 {
+    link.as_ref().map_or(0, |n| n.height)
+}
+
+fn order<T: Ord>(a: &Range<T>, b: &Range<T>) -> Ordering {
+    a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+}
+
+impl<T: Ord + Clone, V> Node<T, V> {
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        let mut max = &self.range.end;
+        for child in [&self.left, &self.right].into_iter().flatten() {
+            max = max.max(&child.max);
+        }
+        self.max = max.clone();
+    }
+
+    fn balance(&self) -> i32 {
+        height(&self.left) as i32 - height(&self.right) as i32
+    }
+}
+
+fn rotate_right<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut pivot = node.left.take().unwrap();
+    node.left = pivot.right.take();
+    node.update();
+    pivot.right = Some(node);
+    pivot.update();
+    pivot
+}
+
+fn rotate_left<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut pivot = node.right.take().unwrap();
+    node.right = pivot.left.take();
+    node.update();
+    pivot.left = Some(node);
+    pivot.update();
+    pivot
+}
+
+fn rebalance<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    node.update();
+    let balance = node.balance();
+    if balance > 1 {
+        if node.left.as_ref().unwrap().balance() < 0 {
+            node.left = Some(rotate_left(node.left.take().unwrap()));
+        }
+        node = rotate_right(node);
+    } else if balance < -1 {
+        if node.right.as_ref().unwrap().balance() > 0 {
+            node.right = Some(rotate_right(node.right.take().unwrap()));
+        }
+        node = rotate_left(node);
+    }
+    node
+}
+
+fn insert<T: Ord + Clone, V>(link: Link<T, V>, range: Range<T>, value: V) -> Box<Node<T, V>> {
+    let Some(mut node) = link else {
+        return Box::new(Node {
+            max: range.end.clone(),
+            range,
+            values: vec![value],
+            height: 1,
+            left: None,
+            right: None,
+        });
+    };
+    match order(&range, &node.range) {
+        Ordering::Less => node.left = Some(insert(node.left.take(), range, value)),
+        Ordering::Greater => node.right = Some(insert(node.right.take(), range, value)),
+        Ordering::Equal => {
+            node.values.push(value);
+            return node;
+        }
+    }
+    rebalance(node)
+}
+
+// (rest of the tree, removed minimum)
+fn remove_min<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> (Link<T, V>, Box<Node<T, V>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let (rest, min) = remove_min(left);
+            node.left = rest;
+            (Some(rebalance(node)), min)
+        }
+    }
+}
+
+fn remove<T: Ord + Clone, V>(
+    link: Link<T, V>,
+    range: &Range<T>,
+    removed: &mut Option<V>,
+) -> Link<T, V> {
+    let mut node = link?;
+    match order(range, &node.range) {
+        Ordering::Less => node.left = remove(node.left.take(), range, removed),
+        Ordering::Greater => node.right = remove(node.right.take(), range, removed),
+        Ordering::Equal => {
+            *removed = node.values.pop();
+            if !node.values.is_empty() {
+                return Some(node);
+            }
+            let (left, right) = match (node.left.take(), node.right.take()) {
+                (Some(left), Some(right)) => (left, right),
+                (left, None) => return left,
+                (None, right) => return right,
+            };
+            let (rest, mut min) = remove_min(right);
+            min.left = Some(left);
+            min.right = rest;
+            return Some(rebalance(min));
+        }
+    }
+    Some(rebalance(node))
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    // number of intervals, counting repeats
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        assert!(
+            range.start < range.end,
+            "interval tree needs non-empty intervals"
+        );
+        self.root = Some(insert(self.root.take(), range, value));
+        self.len += 1;
+    }
+
+    // removes the value inserted last for exactly this interval
+    pub fn remove(&mut self, range: &Range<T>) -> Option<V> {
+        let mut removed = None;
+        self.root = remove(self.root.take(), range, &mut removed);
+        let value = removed?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    // intervals that share at least one point with range, by start
+    pub fn overlapping(&self, range: Range<T>) -> Overlaps<'_, T, V> {
+        // an empty range overlaps nothing
+        let root = if range.start < range.end {
+            &self.root
+        } else {
+            &None
+        };
+        Overlaps::new(root, range.start, Bound::Excluded(range.end))
+    }
+
+    // intervals that contain point, by start
+    pub fn containing(&self, point: &T) -> Overlaps<'_, T, V> {
+        Overlaps::new(&self.root, point.clone(), Bound::Included(point.clone()))
+    }
+
+    // all intervals by start
+    pub fn iter(&self) -> Iter<'_, T, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            current: None,
+        };
+        iter.descend(&self.root);
+        iter
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+}
+
+impl<T: Ord + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for IntervalTree<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord + Clone, V> FromIterator<(Range<T>, V)> for IntervalTree<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<T>, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (range, v) in iter {
+            tree.insert(range, v);
+        }
+        tree
+    }
+}
+
+pub struct Iter<'a, T, V> {
+    stack: Vec<&'a Node<T, V>>,
+    // node whose values are being reported, and the next one
+    current: Option<(&'a Node<T, V>, usize)>,
+}
+
+impl<'a, T, V> Iter<'a, T, V> {
+    fn descend(&mut self, mut link: &'a Link<T, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T, V> Iterator for Iter<'a, T, V> {
+    type Item = (&'a Range<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((node, i)) = &mut self.current {
+                if let Some(v) = node.values.get(*i) {
+                    *i += 1;
+                    return Some((&node.range, v));
+                }
+            }
+            let node = self.stack.pop()?;
+            self.descend(&node.right);
+            self.current = Some((node, 0));
+        }
+    }
+}
+
+// An in-order walk that only enters subtrees reaching past lo and ends at
+// the first interval starting after hi.
+pub struct Overlaps<'a, T, V> {
+    stack: Vec<&'a Node<T, V>>,
+    current: Option<(&'a Node<T, V>, usize)>,
+    lo: T,
+    hi: Bound<T>,
+}
+
+impl<'a, T: Ord, V> Overlaps<'a, T, V> {
+    fn new(root: &'a Link<T, V>, lo: T, hi: Bound<T>) -> Self {
+        let mut iter = Self {
+            stack: Vec::new(),
+            current: None,
+            lo,
+            hi,
+        };
+        iter.descend(root);
+        iter
+    }
+
+    fn descend(&mut self, mut link: &'a Link<T, V>) {
+        while let Some(node) = link {
+            if node.max <= self.lo {
+                break;
+            }
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+
+    fn starts_before_end(&self, start: &T) -> bool {
+        match &self.hi {
+            Bound::Included(hi) => start <= hi,
+            Bound::Excluded(hi) => start < hi,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<'a, T: Ord, V> Iterator for Overlaps<'a, T, V> {
+    type Item = (&'a Range<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((node, i)) = &mut self.current {
+                if let Some(v) = node.values.get(*i) {
+                    *i += 1;
+                    return Some((&node.range, v));
+                }
+            }
+            let node = self.stack.pop()?;
+            if !self.starts_before_end(&node.range.start) {
+                self.stack.clear();
+                self.current = None;
+                return None;
+            }
+            self.descend(&node.right);
+            self.current = (node.range.end > self.lo).then_some((node, 0));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::independent_set::undirected;
+
+// Batagelj-Zaversnik peeling in O(n + m): vertices are kept in buckets by
+// current degree and always removed from the lowest non-empty bucket.
+// Edges are read as undirected, self loops are ignored.
+
+// core number of every vertex: the largest k such that the vertex belongs
+// to a subgraph where all degrees are at least k
+pub fn k_cores<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> HashMap<T, usize> {
+    let g = graph.indexed();
+    let (order, core) = peel(&undirected(&g.adj));
+    order
+        .into_iter()
+        .map(|v| (g.keys[v].clone(), core[v]))
+        .collect()
+}
+
+// removal order of the peeling; every vertex has at most `degeneracy`
+// neighbors later in the order
+pub fn degeneracy_ordering<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Vec<T> {
+    let g = graph.indexed();
+    let (order, _) = peel(&undirected(&g.adj));
+    order.into_iter().map(|v| g.keys[v].clone()).collect()
+}
+
+pub fn degeneracy<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> usize {
+    let g = graph.indexed();
+    let (_, core) = peel(&undirected(&g.adj));
+    core.into_iter().max().unwrap_or(0)
+}
+
+// vertices of the k-core, i.e. with core number >= k
+pub fn k_core<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>, k: usize) -> Vec<T> {
+    k_cores(graph)
+        .into_iter()
+        .filter(|&(_, c)| c >= k)
+        .map(|(v, _)| v)
+        .collect()
+}
+
+pub(crate) fn peel(adj: &[Vec<usize>]) -> (Vec<usize>, Vec<usize>) {
+    let n = adj.len();
+    let mut degree: Vec<usize> = (0..n)
+        .map(|v| adj[v].iter().filter(|&&u| u != v).count())
+        .collect();
+    let max_deg = degree.iter().copied().max().unwrap_or(0);
+
+    // vertices sorted by degree, with bucket start offsets
+    let mut start = vec![0; max_deg + 2];
+    for &d in degree.iter() {
+        start[d + 1] += 1;
+    }
+    for d in 1..start.len() {
+        start[d] += start[d - 1];
+    }
+    let mut order = vec![0; n];
+    let mut pos = vec![0; n];
+    let mut fill = start.clone();
+    for v in 0..n {
+        pos[v] = fill[degree[v]];
+        order[pos[v]] = v;
+        fill[degree[v]] += 1;
+    }
+
+    for i in 0..n {
+        let v = order[i];
+        for &u in adj[v].iter() {
+            if u == v || degree[u] <= degree[v] {
+                continue;
+            }
+            // move u to the front of its bucket, then shrink the bucket
+            let du = degree[u];
+            let front = start[du];
+            let w = order[front];
+            if u != w {
+                order.swap(pos[u], front);
+                pos[w] = pos[u];
+                pos[u] = front;
+            }
+            start[du] = front + 1;
+            degree[u] -= 1;
+        }
+    }
+    (order, degree)
+}
@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+
+use crate::binary_heap::BinaryHeap;
+use crate::geometry::dist2;
+
+// K-dimensional tree over points in R^K. Every node splits space at its
+// point along one axis, the axes taking turns by depth. A bulk build splits
+// at the median and gives a tree of depth log2 n; inserted points just
+// become new leaves. Points are identified by their index in insertion
+// order, build order first.
+#[derive(Debug, Clone)]
+pub struct KdTree<const K: usize> {
+    points: Vec<[f64; K]>,
+    // node of point i
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    axis: usize,
+    // points at or below and at or above the split, ties can be on
+    // either side
+    child: [usize; 2],
+}
+
+const NIL: usize = usize::MAX;
+
+impl<const K: usize> Default for KdTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const K: usize> KdTree<K> {
+    pub fn new() -> Self {
+        assert!(K >= 1, "a kd-tree needs at least one dimension");
+        Self {
+            points: Vec::new(),
+            nodes: Vec::new(),
+            root: NIL,
+        }
+    }
+
+    // balanced tree over points, O(n log n)
+    pub fn build(points: Vec<[f64; K]>) -> Self {
+        let mut tree = Self::new();
+        tree.nodes = vec![
+            Node {
+                axis: 0,
+                child: [NIL; 2]
+            };
+            points.len()
+        ];
+        tree.points = points;
+        let mut ids: Vec<usize> = (0..tree.points.len()).collect();
+        tree.root = tree.build_from(&mut ids, 0);
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, id: usize) -> &[f64; K] {
+        &self.points[id]
+    }
+
+    pub fn points(&self) -> &[[f64; K]] {
+        &self.points
+    }
+
+    // adds a leaf for point and returns its id
+    pub fn insert(&mut self, point: [f64; K]) -> usize {
+        let id = self.points.len();
+        self.points.push(point);
+        let mut node = Node {
+            axis: 0,
+            child: [NIL; 2],
+        };
+        if self.root == NIL {
+            self.root = id;
+            self.nodes.push(node);
+            return id;
+        }
+        let mut cur = self.root;
+        loop {
+            let Node { axis, child } = self.nodes[cur];
+            let side = self.side(cur, axis, &point);
+            if child[side] == NIL {
+                node.axis = (axis + 1) % K;
+                self.nodes.push(node);
+                self.nodes[cur].child[side] = id;
+                return id;
+            }
+            cur = child[side];
+        }
+    }
+
+    // closest point to query and its distance
+    pub fn nearest(&self, query: &[f64; K]) -> Option<(usize, f64)> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    // the k closest points, nearest first, with their distances
+    pub fn k_nearest(&self, query: &[f64; K], k: usize) -> Vec<(usize, f64)> {
+        if k == 0 || self.root == NIL {
+            return Vec::new();
+        }
+        // the best k so far by squared distance, farthest on top
+        let mut best =
+            BinaryHeap::with_compare(|a: &(f64, usize), b: &(f64, usize)| b.0.total_cmp(&a.0));
+        // (node, lower bound on the squared distance to its region)
+        let mut stack = vec![(self.root, 0.0)];
+        while let Some((cur, bound)) = stack.pop() {
+            if best.len() == k && bound >= best.peek().unwrap().0 {
+                continue;
+            }
+            let d = dist2(&self.points[cur], query);
+            if best.len() < k {
+                best.push((d, cur));
+            } else if d < best.peek().unwrap().0 {
+                best.replace_top((d, cur));
+            }
+            let Node { axis, child } = self.nodes[cur];
+            let near = self.side(cur, axis, query);
+            let gap = query[axis] - self.points[cur][axis];
+            // the near side is searched first, so it goes on the stack last
+            if child[1 - near] != NIL {
+                stack.push((child[1 - near], bound.max(gap * gap)));
+            }
+            if child[near] != NIL {
+                stack.push((child[near], bound));
+            }
+        }
+        let mut found: Vec<(usize, f64)> = best
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(d, id)| (id, d.sqrt()))
+            .collect();
+        found.reverse();
+        found
+    }
+
+    // points with min[i] <= p[i] <= max[i] on every axis
+    pub fn within_box(&self, min: &[f64; K], max: &[f64; K]) -> Vec<usize> {
+        self.search(
+            |p| (0..K).all(|i| min[i] <= p[i] && p[i] <= max[i]),
+            |axis, split| [min[axis] <= split, max[axis] >= split],
+        )
+    }
+
+    // points at distance at most radius from center
+    pub fn within_radius(&self, center: &[f64; K], radius: f64) -> Vec<usize> {
+        let r2 = radius * radius;
+        self.search(
+            |p| dist2(p, center) <= r2,
+            |axis, split| {
+                [
+                    center[axis] - radius <= split,
+                    center[axis] + radius >= split,
+                ]
+            },
+        )
+    }
+
+    // Ids of the points that pass keep, in no particular order. visit tells
+    // for a split value on an axis whether the lower and the upper side can
+    // hold such points.
+    fn search(
+        &self,
+        keep: impl Fn(&[f64; K]) -> bool,
+        visit: impl Fn(usize, f64) -> [bool; 2],
+    ) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(cur) = stack.pop() {
+            if cur == NIL {
+                continue;
+            }
+            if keep(&self.points[cur]) {
+                found.push(cur);
+            }
+            let Node { axis, child } = self.nodes[cur];
+            let sides = visit(axis, self.points[cur][axis]);
+            for side in 0..2 {
+                if sides[side] {
+                    stack.push(child[side]);
+                }
+            }
+        }
+        found
+    }
+
+    // where point goes below node cur, 0 for the lower side
+    fn side(&self, cur: usize, axis: usize, point: &[f64; K]) -> usize {
+        match point[axis].total_cmp(&self.points[cur][axis]) {
+            Ordering::Less => 0,
+            _ => 1,
+        }
+    }
+
+    fn build_from(&mut self, ids: &mut [usize], depth: usize) -> usize {
+        if ids.is_empty() {
+            return NIL;
+        }
+        let axis = depth % K;
+        let mid = ids.len() / 2;
+        let points = &self.points;
+        ids.select_nth_unstable_by(mid, |&a, &b| points[a][axis].total_cmp(&points[b][axis]));
+        let (lo, rest) = ids.split_at_mut(mid);
+        let (pivot, hi) = rest.split_first_mut().unwrap();
+        let id = *pivot;
+        let left = self.build_from(lo, depth + 1);
+        let right = self.build_from(hi, depth + 1);
+        self.nodes[id] = Node {
+            axis,
+            child: [left, right],
+        };
+        id
+    }
+}
+
+impl<const K: usize> FromIterator<[f64; K]> for KdTree<K> {
+    fn from_iter<I: IntoIterator<Item = [f64; K]>>(iter: I) -> Self {
+        Self::build(iter.into_iter().collect())
+    }
+}
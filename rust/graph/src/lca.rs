@@ -0,0 +1,138 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::segment_tree::Monoid;
+use crate::sparse_table::{IdempotentOp, SparseTable};
+use crate::tree::{check, TreeError};
+use crate::union_find::UnionFind;
+
+// Tarjan's offline lca: one dfs over the tree answers every query, total
+// time is O((n + q) α(n)). The dfs is iterative so deep trees don't
+// overflow the stack.
+pub fn lca_offline<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    tree: &Graph<T, S>,
+    root: &T,
+    queries: &[(T, T)],
+) -> Result<Vec<T>, TreeError> {
+    let g = tree.indexed();
+    check(&g)?;
+    let root = *g.index.get(root).ok_or(TreeError::MissingRoot)?;
+    let n = g.keys.len();
+
+    let mut pending = vec![Vec::new(); n];
+    for (i, (u, v)) in queries.iter().enumerate() {
+        let u = *g.index.get(u).ok_or(TreeError::MissingVertex)?;
+        let v = *g.index.get(v).ok_or(TreeError::MissingVertex)?;
+        pending[u].push((v, i));
+        pending[v].push((u, i));
+    }
+
+    let mut uf = UnionFind::new(n);
+    let mut ancestor: Vec<usize> = (0..n).collect();
+    let mut done = vec![false; n];
+    let mut answer = vec![usize::MAX; queries.len()];
+    let mut visited = vec![false; n];
+    // (vertex, parent, next neighbor to look at)
+    let mut stack = vec![(root, usize::MAX, 0)];
+    visited[root] = true;
+
+    while let Some(&mut (v, parent, ref mut next)) = stack.last_mut() {
+        if let Some(&(u, _)) = g.adj[v].get(*next) {
+            *next += 1;
+            if !visited[u] {
+                visited[u] = true;
+                stack.push((u, v, 0));
+            }
+            continue;
+        }
+        stack.pop();
+        done[v] = true;
+        for &(u, i) in pending[v].iter() {
+            if done[u] {
+                answer[i] = ancestor[uf.find(u)];
+            }
+        }
+        if parent != usize::MAX {
+            uf.union(parent, v);
+            let r = uf.find(parent);
+            ancestor[r] = parent;
+        }
+    }
+
+    Ok(answer.into_iter().map(|v| g.keys[v].clone()).collect())
+}
+
+// Online lca with O(1) queries after O(n log n) preprocessing. The lca of u
+// and v is the shallowest vertex of the euler tour between the first visits
+// of u and v, found with a sparse table over (depth, vertex).
+#[derive(Debug, Clone)]
+pub struct EulerTourLca<T> {
+    keys: Vec<T>,
+    index: HashMap<T, usize>,
+    first: Vec<usize>,
+    table: SparseTable<(usize, usize), Shallowest>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Shallowest;
+
+impl Monoid<(usize, usize)> for Shallowest {
+    fn identity() -> (usize, usize) {
+        (usize::MAX, usize::MAX)
+    }
+    fn combine(a: &(usize, usize), b: &(usize, usize)) -> (usize, usize) {
+        *a.min(b)
+    }
+}
+
+impl IdempotentOp<(usize, usize)> for Shallowest {}
+
+impl<T: Hash + Eq + Clone> EulerTourLca<T> {
+    pub fn new<S: NeighborList<T>>(tree: &Graph<T, S>, root: &T) -> Result<Self, TreeError> {
+        let g = tree.indexed();
+        check(&g)?;
+        let root = *g.index.get(root).ok_or(TreeError::MissingRoot)?;
+        let n = g.keys.len();
+
+        let mut first = vec![usize::MAX; n];
+        let mut tour = Vec::with_capacity(2 * n - 1);
+        // (vertex, depth, next neighbor to look at)
+        let mut stack = vec![(root, 0, 0)];
+        first[root] = 0;
+        tour.push((0, root));
+        while let Some(&mut (v, depth, ref mut next)) = stack.last_mut() {
+            if let Some(&(u, _)) = g.adj[v].get(*next) {
+                *next += 1;
+                if first[u] == usize::MAX {
+                    first[u] = tour.len();
+                    tour.push((depth + 1, u));
+                    stack.push((u, depth + 1, 0));
+                }
+                continue;
+            }
+            stack.pop();
+            if let Some(&(p, pd, _)) = stack.last() {
+                tour.push((pd, p));
+            }
+        }
+
+        Ok(Self {
+            keys: g.keys,
+            index: g.index,
+            first,
+            table: SparseTable::new(tour),
+        })
+    }
+
+    pub fn lca(&self, u: &T, v: &T) -> Result<&T, TreeError> {
+        let u = self.first[*self.index.get(u).ok_or(TreeError::MissingVertex)?];
+        let v = self.first[*self.index.get(v).ok_or(TreeError::MissingVertex)?];
+        let (_, w) = self.table.query(u.min(v)..u.max(v) + 1);
+        Ok(&self.keys[w])
+    }
+
+    pub fn depth(&self, v: &T) -> Result<usize, TreeError> {
+        let i = self.first[*self.index.get(v).ok_or(TreeError::MissingVertex)?];
+        Ok(self.table.query(i..i + 1).0)
+    }
+}
@@ -0,0 +1,271 @@
+use std::{borrow::Borrow, collections::HashMap, fmt, hash::Hash};
+
+use crate::cache::Cache;
+
+// Least frequently used cache: entries sit in one list per use count, most
+// recent use first, and the smallest count in use is tracked. A hit moves
+// its entry from the list for f to the front of the list for f + 1, and
+// eviction takes the back of the list for the smallest count, so ties go
+// to the least recently used. Both are O(1). Entries live in an arena
+// linked by index, and the lists in a map from count to (front, back).
+pub struct LfuCache<K, V> {
+    map: HashMap<K, usize>,
+    entries: Vec<Option<Entry<K, V>>>,
+    free: Vec<usize>,
+    lists: HashMap<u64, (usize, usize)>,
+    // smallest count with a list, or 0 when not known after a remove
+    min_count: u64,
+    capacity: usize,
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    count: u64,
+    prev: usize,
+    next: usize,
+}
+
+const NIL: usize = usize::MAX;
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            map: HashMap::new(),
+            entries: Vec::new(),
+            free: Vec::new(),
+            lists: HashMap::new(),
+            min_count: 0,
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn entry(&self, i: usize) -> &Entry<K, V> {
+        self.entries[i].as_ref().unwrap()
+    }
+
+    fn entry_mut(&mut self, i: usize) -> &mut Entry<K, V> {
+        self.entries[i].as_mut().unwrap()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    // counts a use of key
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = *self.map.get(key)?;
+        self.bump(i);
+        Some(&self.entry(i).value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = *self.map.get(key)?;
+        self.bump(i);
+        Some(&mut self.entry_mut(i).value)
+    }
+
+    // looks without counting a use
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&i| &self.entry(i).value)
+    }
+
+    // uses of key so far, putting it counts as the first
+    pub fn count<Q>(&self, key: &Q) -> Option<u64>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&i| self.entry(i).count)
+    }
+
+    // The old value of key, if there was one; replacing a value counts as
+    // a use. A new key evicts the least frequently used entry when full.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&i) = self.map.get(&key) {
+            self.bump(i);
+            return Some(std::mem::replace(&mut self.entry_mut(i).value, value));
+        }
+        if self.capacity == 0 {
+            return None;
+        }
+        if self.map.len() == self.capacity {
+            self.pop_lfu();
+        }
+        let entry = Entry {
+            key: key.clone(),
+            value,
+            count: 1,
+            prev: NIL,
+            next: NIL,
+        };
+        let i = match self.free.pop() {
+            Some(i) => {
+                self.entries[i] = Some(entry);
+                i
+            }
+            None => {
+                self.entries.push(Some(entry));
+                self.entries.len() - 1
+            }
+        };
+        self.map.insert(key, i);
+        self.push_front(i);
+        self.min_count = 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.map.remove(key)?;
+        let count = self.entry(i).count;
+        let (_, value) = self.release(i);
+        if count == self.min_count && !self.lists.contains_key(&count) {
+            self.min_count = 0;
+        }
+        Some(value)
+    }
+
+    // evicts the least recently used of the least frequently used entries
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        if self.map.is_empty() {
+            return None;
+        }
+        if self.min_count == 0 {
+            // after a remove emptied the smallest list, O(distinct counts)
+            self.min_count = *self.lists.keys().min().unwrap();
+        }
+        let (_, back) = self.lists[&self.min_count];
+        let (key, value) = self.release(back);
+        self.map.remove(&key);
+        if !self.lists.contains_key(&self.min_count) {
+            self.min_count = 0;
+        }
+        Some((key, value))
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.free.clear();
+        self.lists.clear();
+        self.min_count = 0;
+    }
+
+    // unlinks and frees slot i, leaving the map to the caller
+    fn release(&mut self, i: usize) -> (K, V) {
+        self.unlink(i);
+        let entry = self.entries[i].take().unwrap();
+        self.free.push(i);
+        (entry.key, entry.value)
+    }
+
+    fn bump(&mut self, i: usize) {
+        let count = self.entry(i).count;
+        self.unlink(i);
+        if count == self.min_count && !self.lists.contains_key(&count) {
+            self.min_count = count + 1;
+        }
+        self.entry_mut(i).count = count + 1;
+        self.push_front(i);
+    }
+
+    // takes i out of the list for its count, dropping the list if emptied
+    fn unlink(&mut self, i: usize) {
+        let Entry {
+            prev, next, count, ..
+        } = *self.entry(i);
+        let list = self.lists.get_mut(&count).unwrap();
+        if prev == NIL {
+            list.0 = next;
+        }
+        if next == NIL {
+            list.1 = prev;
+        }
+        if list.0 == NIL {
+            self.lists.remove(&count);
+        }
+        if prev != NIL {
+            self.entry_mut(prev).next = next;
+        }
+        if next != NIL {
+            self.entry_mut(next).prev = prev;
+        }
+    }
+
+    fn push_front(&mut self, i: usize) {
+        let count = self.entry(i).count;
+        let list = self.lists.entry(count).or_insert((NIL, NIL));
+        let head = list.0;
+        list.0 = i;
+        if head == NIL {
+            list.1 = i;
+        }
+        let entry = self.entry_mut(i);
+        entry.prev = NIL;
+        entry.next = head;
+        if head != NIL {
+            self.entry_mut(head).prev = i;
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LfuCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        LfuCache::get(self, key)
+    }
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        LfuCache::put(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        LfuCache::remove(self, key)
+    }
+    fn contains_key(&self, key: &K) -> bool {
+        LfuCache::contains_key(self, key)
+    }
+    fn len(&self) -> usize {
+        LfuCache::len(self)
+    }
+    fn capacity(&self) -> usize {
+        LfuCache::capacity(self)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LfuCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().flatten().map(|e| (&e.key, &e.value)))
+            .finish()
+    }
+}
@@ -0,0 +1,97 @@
+pub mod adjlist;
+pub mod aho_corasick;
+pub mod arc_cache;
+pub mod arena;
+pub mod arena_graph;
+pub mod avl_tree;
+pub mod b_plus_tree;
+pub mod b_tree;
+pub mod binary_heap;
+pub mod binomial_heap;
+pub mod bit_vec;
+pub mod bitmatrix;
+pub mod bloom_filter;
+pub mod builder;
+pub mod cache;
+pub mod capi;
+pub mod community;
+pub mod concurrent;
+pub mod cons_list;
+pub mod consistent_hash;
+pub mod count_min;
+pub mod covering;
+pub mod csr;
+pub mod cuckoo_filter;
+pub mod cuckoo_hash;
+pub mod dependency;
+pub mod dot;
+pub mod dynamic_connectivity;
+pub mod dynamic_forest;
+pub mod exact_cover;
+pub mod fenwick;
+pub mod fibonacci_heap;
+pub mod generators;
+pub mod geometry;
+mod hashing;
+pub mod hyperloglog;
+pub mod independent_set;
+pub mod indexed_heap;
+pub mod interval_heap;
+pub mod interval_tree;
+pub mod kcore;
+pub mod kd_tree;
+pub mod lca;
+pub mod lfu_cache;
+pub mod linked_list;
+pub mod lru_cache;
+mod mapped;
+pub mod matrix;
+pub mod memory;
+pub mod merkle;
+pub mod minhash;
+pub mod monotonic;
+pub mod mst;
+pub mod num;
+pub mod ordered_map;
+pub mod pairing_heap;
+pub mod parallel;
+pub mod partition;
+pub mod persistent_segment_tree;
+pub mod persistent_vec;
+pub mod planarity;
+pub mod postman;
+pub mod priority_queue;
+pub mod quadtree;
+pub mod r_tree;
+pub mod radix_tree;
+pub mod range_map;
+pub mod red_black_tree;
+pub mod ring_buffer;
+pub mod rng;
+pub mod robin_hood;
+pub mod rope;
+pub mod scapegoat_tree;
+pub mod scc;
+pub mod scoring;
+pub mod search;
+pub mod segment_tree;
+pub mod sha256;
+pub mod shortest_path;
+pub mod skip_list;
+pub mod small_vec;
+pub mod sort;
+pub mod spanning_tree;
+pub mod sparse_table;
+pub mod splay_tree;
+pub mod strings;
+pub mod suffix_array;
+pub mod suffix_automaton;
+pub mod suffix_tree;
+pub mod topo;
+pub mod treap;
+pub mod tree;
+pub mod trie;
+pub mod union_find;
+pub mod veb_tree;
+pub mod walk;
+pub mod wavelet_tree;
@@ -0,0 +1,488 @@
+use std::fmt;
+
+// Doubly linked list without unsafe or Rc<RefCell>: the nodes live in a
+// slab owned by the list and link to each other by index, so the borrow
+// checker only ever sees one Vec. Removed slots go on a free list for
+// reuse. A cursor walks the list and inserts or removes at its position in
+// O(1), with a ghost position past the back, between it and the front,
+// where it reads nothing. Moving another list's nodes in, on append or a
+// splice, copies them into this slab: O(length of the other list) instead
+// of the O(1) of relinking pointers, the price of owning the storage.
+pub struct DoublyLinkedList<T> {
+    nodes: Vec<Option<Node<T>>>,
+    free: Vec<usize>,
+    head: usize,
+    tail: usize,
+    len: usize,
+}
+
+struct Node<T> {
+    value: T,
+    prev: usize,
+    next: usize,
+}
+
+const NIL: usize = usize::MAX;
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, i: usize) -> &Node<T> {
+        self.nodes[i].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, i: usize) -> &mut Node<T> {
+        self.nodes[i].as_mut().unwrap()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        (self.head != NIL).then(|| &self.node(self.head).value)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        (self.tail != NIL).then(|| &self.node(self.tail).value)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        let i = self.head;
+        (i != NIL).then(|| &mut self.node_mut(i).value)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        let i = self.tail;
+        (i != NIL).then(|| &mut self.node_mut(i).value)
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        self.insert_between(value, NIL, self.head);
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        self.insert_between(value, self.tail, NIL);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        (self.head != NIL).then(|| self.unlink(self.head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        (self.tail != NIL).then(|| self.unlink(self.tail))
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|v| v == value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+
+    // moves every item of other to the back, leaving other empty
+    pub fn append(&mut self, other: &mut Self) {
+        if self.is_empty() {
+            std::mem::swap(self, other);
+            return;
+        }
+        while let Some(value) = other.pop_front() {
+            self.push_back(value);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // a cursor on the front item, or the ghost if empty
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            at: self.head,
+            index: 0,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            at: self.tail,
+            index: self.len.saturating_sub(1),
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            at: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            at: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    // links a new node between prev and next, either NIL at an end
+    fn insert_between(&mut self, value: T, prev: usize, next: usize) -> usize {
+        let node = Node { value, prev, next };
+        let i = match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        if prev == NIL {
+            self.head = i;
+        } else {
+            self.node_mut(prev).next = i;
+        }
+        if next == NIL {
+            self.tail = i;
+        } else {
+            self.node_mut(next).prev = i;
+        }
+        self.len += 1;
+        i
+    }
+
+    fn unlink(&mut self, i: usize) -> T {
+        let Node { value, prev, next } = self.nodes[i].take().unwrap();
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.node_mut(prev).next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.node_mut(next).prev = prev;
+        }
+        self.free.push(i);
+        self.len -= 1;
+        value
+    }
+
+    // next of i, the ghost NIL included
+    fn next_of(&self, i: usize) -> usize {
+        if i == NIL {
+            self.head
+        } else {
+            self.node(i).next
+        }
+    }
+
+    fn prev_of(&self, i: usize) -> usize {
+        if i == NIL {
+            self.tail
+        } else {
+            self.node(i).prev
+        }
+    }
+}
+
+// Read only cursor. index counts from the front; it is len at the ghost.
+pub struct Cursor<'a, T> {
+    list: &'a DoublyLinkedList<T>,
+    at: usize,
+    index: usize,
+}
+
+impl<'a, T> Cursor<'a, T> {
+    // None at the ghost
+    pub fn index(&self) -> Option<usize> {
+        (self.at != NIL).then_some(self.index)
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        (self.at != NIL).then(|| &self.list.node(self.at).value)
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let i = self.list.next_of(self.at);
+        (i != NIL).then(|| &self.list.node(i).value)
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let i = self.list.prev_of(self.at);
+        (i != NIL).then(|| &self.list.node(i).value)
+    }
+
+    // from the back onto the ghost, from the ghost onto the front
+    pub fn move_next(&mut self) {
+        self.index = if self.at == NIL { 0 } else { self.index + 1 };
+        self.at = self.list.next_of(self.at);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.at = self.list.prev_of(self.at);
+        // from the ghost index is len, so this lands on len - 1 at the back
+        self.index = if self.at == NIL {
+            self.list.len
+        } else {
+            self.index - 1
+        };
+    }
+}
+
+pub struct CursorMut<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    at: usize,
+    index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn index(&self) -> Option<usize> {
+        (self.at != NIL).then_some(self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        let i = self.at;
+        (i != NIL).then(|| &mut self.list.node_mut(i).value)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let i = self.list.next_of(self.at);
+        (i != NIL).then(|| &mut self.list.node_mut(i).value)
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let i = self.list.prev_of(self.at);
+        (i != NIL).then(|| &mut self.list.node_mut(i).value)
+    }
+
+    pub fn move_next(&mut self) {
+        self.index = if self.at == NIL { 0 } else { self.index + 1 };
+        self.at = self.list.next_of(self.at);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.at = self.list.prev_of(self.at);
+        // from the ghost index is len, so this lands on len - 1 at the back
+        self.index = if self.at == NIL {
+            self.list.len
+        } else {
+            self.index - 1
+        };
+    }
+
+    // the list the cursor borrows, read only
+    pub fn as_list(&self) -> &DoublyLinkedList<T> {
+        self.list
+    }
+
+    // inserts after the current item, at the front from the ghost
+    pub fn insert_after(&mut self, value: T) {
+        let next = self.list.next_of(self.at);
+        self.list.insert_between(value, self.at, next);
+        self.fix_ghost_index();
+    }
+
+    // inserts before the current item, at the back from the ghost
+    pub fn insert_before(&mut self, value: T) {
+        let prev = self.list.prev_of(self.at);
+        self.list.insert_between(value, prev, self.at);
+        self.index += 1;
+    }
+
+    // removes the current item and moves onto the one after it
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.at == NIL {
+            return None;
+        }
+        let next = self.list.node(self.at).next;
+        let value = self.list.unlink(self.at);
+        self.at = next;
+        self.fix_ghost_index();
+        Some(value)
+    }
+
+    // moves the items of other in after the current item, at the front
+    // from the ghost
+    pub fn splice_after(&mut self, mut other: DoublyLinkedList<T>) {
+        while let Some(value) = other.pop_back() {
+            self.insert_after(value);
+        }
+    }
+
+    // moves the items of other in before the current item, at the back from
+    // the ghost
+    pub fn splice_before(&mut self, mut other: DoublyLinkedList<T>) {
+        while let Some(value) = other.pop_front() {
+            self.insert_before(value);
+        }
+    }
+
+    // Splits off the items after the current one as a new list, the whole
+    // list from the ghost.
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let mut rest = DoublyLinkedList::new();
+        loop {
+            let next = self.list.next_of(self.at);
+            if next == NIL {
+                break;
+            }
+            rest.push_back(self.list.unlink(next));
+        }
+        self.fix_ghost_index();
+        rest
+    }
+
+    fn fix_ghost_index(&mut self) {
+        if self.at == NIL {
+            self.index = self.list.len;
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a DoublyLinkedList<T>,
+    front: usize,
+    back: usize,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.front);
+        self.front = node.next;
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.back);
+        self.back = node.prev;
+        self.len -= 1;
+        Some(&node.value)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<T>(DoublyLinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// compacts the copy into list order
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
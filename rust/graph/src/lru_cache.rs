@@ -0,0 +1,295 @@
+use std::{borrow::Borrow, collections::HashMap, fmt, hash::Hash};
+
+use crate::cache::Cache;
+
+// Least recently used cache: a hash map from key to slot in an arena of
+// entries, and the entries linked by index into a list from most to least
+// recently used. A hit moves its entry to the front and a miss that
+// overflows the capacity drops entries from the back, all O(1). Capacity
+// is a total cost, one per entry unless a cost function says otherwise.
+pub struct LruCache<K, V> {
+    map: HashMap<K, usize>,
+    entries: Vec<Option<Entry<K, V>>>,
+    free: Vec<usize>,
+    // most and least recently used
+    head: usize,
+    tail: usize,
+    cost: fn(&K, &V) -> usize,
+    total_cost: usize,
+    capacity: usize,
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    cost: usize,
+    prev: usize,
+    next: usize,
+}
+
+const NIL: usize = usize::MAX;
+
+fn unit_cost<K, V>(_: &K, _: &V) -> usize {
+    1
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    // holds up to capacity entries
+    pub fn new(capacity: usize) -> Self {
+        Self::with_cost(capacity, unit_cost)
+    }
+
+    // holds entries up to a total cost of capacity; putting an entry that
+    // costs more than that just drops the key
+    pub fn with_cost(capacity: usize, cost: fn(&K, &V) -> usize) -> Self {
+        Self {
+            map: HashMap::new(),
+            entries: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            cost,
+            total_cost: 0,
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // cost of the entries held, len() with the default cost
+    pub fn total_cost(&self) -> usize {
+        self.total_cost
+    }
+
+    fn entry(&self, i: usize) -> &Entry<K, V> {
+        self.entries[i].as_ref().unwrap()
+    }
+
+    fn entry_mut(&mut self, i: usize) -> &mut Entry<K, V> {
+        self.entries[i].as_mut().unwrap()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    // marks key as just used
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = *self.map.get(key)?;
+        self.touch(i);
+        Some(&self.entry(i).value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = *self.map.get(key)?;
+        self.touch(i);
+        Some(&mut self.entry_mut(i).value)
+    }
+
+    // looks without marking key as used
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&i| &self.entry(i).value)
+    }
+
+    // The old value of key, if there was one. Makes room by evicting from
+    // the least recently used end.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        let cost = (self.cost)(&key, &value);
+        if cost > self.capacity {
+            // would only push everything else out, then itself
+            return self.remove(&key);
+        }
+        let old = match self.map.get(&key) {
+            Some(&i) => {
+                self.touch(i);
+                let entry = self.entry_mut(i);
+                let old_cost = std::mem::replace(&mut entry.cost, cost);
+                let old = std::mem::replace(&mut entry.value, value);
+                self.total_cost = self.total_cost - old_cost + cost;
+                Some(old)
+            }
+            None => {
+                let entry = Entry {
+                    key: key.clone(),
+                    value,
+                    cost,
+                    prev: NIL,
+                    next: NIL,
+                };
+                let i = match self.free.pop() {
+                    Some(i) => {
+                        self.entries[i] = Some(entry);
+                        i
+                    }
+                    None => {
+                        self.entries.push(Some(entry));
+                        self.entries.len() - 1
+                    }
+                };
+                self.map.insert(key, i);
+                self.push_front(i);
+                self.total_cost += cost;
+                None
+            }
+        };
+        while self.total_cost > self.capacity {
+            self.pop_lru();
+        }
+        old
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.map.remove(key)?;
+        Some(self.release(i).1)
+    }
+
+    // evicts the least recently used entry
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        if self.tail == NIL {
+            return None;
+        }
+        let (key, value) = self.release(self.tail);
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    // the entry next in line for eviction
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        (self.tail != NIL).then(|| {
+            let e = self.entry(self.tail);
+            (&e.key, &e.value)
+        })
+    }
+
+    // most recently used first
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            cache: self,
+            cur: self.head,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.free.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.total_cost = 0;
+    }
+
+    // unlinks and frees slot i, leaving the map to the caller
+    fn release(&mut self, i: usize) -> (K, V) {
+        self.unlink(i);
+        let entry = self.entries[i].take().unwrap();
+        self.free.push(i);
+        self.total_cost -= entry.cost;
+        (entry.key, entry.value)
+    }
+
+    fn touch(&mut self, i: usize) {
+        if self.head != i {
+            self.unlink(i);
+            self.push_front(i);
+        }
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let Entry { prev, next, .. } = *self.entry(i);
+        match prev {
+            NIL => self.head = next,
+            p => self.entry_mut(p).next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            n => self.entry_mut(n).prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, i: usize) {
+        let head = self.head;
+        let entry = self.entry_mut(i);
+        entry.prev = NIL;
+        entry.next = head;
+        match head {
+            NIL => self.tail = i,
+            h => self.entry_mut(h).prev = i,
+        }
+        self.head = i;
+    }
+}
+
+impl<K: Hash + Eq + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        LruCache::get(self, key)
+    }
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        LruCache::put(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        LruCache::remove(self, key)
+    }
+    fn contains_key(&self, key: &K) -> bool {
+        LruCache::contains_key(self, key)
+    }
+    fn len(&self) -> usize {
+        LruCache::len(self)
+    }
+    fn capacity(&self) -> usize {
+        LruCache::capacity(self)
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    cache: &'a LruCache<K, V>,
+    cur: usize,
+}
+
+impl<'a, K: Hash + Eq + Clone, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == NIL {
+            return None;
+        }
+        let entry = self.cache.entry(self.cur);
+        self.cur = entry.next;
+        Some((&entry.key, &entry.value))
+    }
+}
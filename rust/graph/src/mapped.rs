@@ -0,0 +1,212 @@
+use std::{fmt, fs::File, io, ops::Deref, sync::Arc};
+
+// Read-only views into a file that may be memory mapped. A Buffer is
+// either an owned vector or a typed window into a shared mapping; both
+// deref to a plain slice so callers never see the difference.
+
+pub(crate) enum Buffer<T> {
+    Owned(Vec<T>),
+    Mapped {
+        map: Arc<Mmap>,
+        start: usize,
+        len: usize,
+    },
+}
+
+impl<T> Buffer<T> {
+    // `start` is a byte offset into the mapping, it must be aligned for T
+    // and the window must lie inside the mapping
+    pub(crate) fn mapped(map: &Arc<Mmap>, start: usize, len: usize) -> Self {
+        let end = len
+            .checked_mul(std::mem::size_of::<T>())
+            .and_then(|bytes| bytes.checked_add(start));
+        assert!(end.is_some_and(|end| end <= map.len()));
+        assert_eq!(
+            (map.as_ptr() as usize + start) % std::mem::align_of::<T>(),
+            0
+        );
+        Buffer::Mapped {
+            map: Arc::clone(map),
+            start,
+            len,
+        }
+    }
+}
+
+impl<T> Buffer<T> {
+    // zero for mapped windows, those pages belong to the file
+    pub(crate) fn heap_bytes(&self) -> usize {
+        match self {
+            Buffer::Owned(v) => v.capacity() * std::mem::size_of::<T>(),
+            Buffer::Mapped { .. } => 0,
+        }
+    }
+}
+
+impl<T> Deref for Buffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Buffer::Owned(v) => v,
+            // in bounds and aligned, checked in Buffer::mapped
+            Buffer::Mapped { map, start, len } => unsafe {
+                std::slice::from_raw_parts(map.as_ptr().add(*start) as *const T, *len)
+            },
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Buffer<T> {
+    fn from(v: Vec<T>) -> Self {
+        Buffer::Owned(v)
+    }
+}
+
+impl<T> Default for Buffer<T> {
+    fn default() -> Self {
+        Buffer::Owned(Vec::new())
+    }
+}
+
+// cloning a mapped buffer shares the mapping
+impl<T: Clone> Clone for Buffer<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Buffer::Owned(v) => Buffer::Owned(v.clone()),
+            Buffer::Mapped { map, start, len } => Buffer::Mapped {
+                map: Arc::clone(map),
+                start: *start,
+                len: *len,
+            },
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Buffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for Buffer<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for Buffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+// A private read-only mapping of a whole file, unmapped on drop.
+pub(crate) struct Mmap {
+    ptr: *const u8,
+    len: usize,
+}
+
+// the mapping is never written through, sharing it is like sharing a &[u8]
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{c_int, c_void};
+
+    pub const PROT_READ: c_int = 1;
+    pub const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: i64,
+        ) -> *mut c_void;
+        pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+}
+
+#[cfg(unix)]
+impl Mmap {
+    // Safety: the file must not be truncated or modified while mapped,
+    // the slices handed out would change or fault under the reader
+    pub(crate) unsafe fn map(file: &File) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file too large to map"))?;
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "cannot map an empty file",
+            ));
+        }
+        let ptr = sys::mmap(
+            std::ptr::null_mut(),
+            len,
+            sys::PROT_READ,
+            sys::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        );
+        if ptr as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr: ptr as *const u8,
+            len,
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        unsafe {
+            sys::munmap(self.ptr as *mut _, self.len);
+        }
+    }
+}
+
+// no mmap binding elsewhere, the file is read into an 8-byte aligned heap
+// buffer instead so the rest of the code is the same
+#[cfg(not(unix))]
+impl Mmap {
+    pub(crate) unsafe fn map(file: &File) -> io::Result<Self> {
+        use std::io::Read;
+
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file too large to read"))?;
+        let mut words = vec![0u64; len.div_ceil(8)].into_boxed_slice();
+        let bytes = std::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, len);
+        (&*file).read_exact(bytes)?;
+        let ptr = Box::into_raw(words) as *const u8;
+        Ok(Self { ptr, len })
+    }
+}
+
+#[cfg(not(unix))]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        unsafe {
+            let words =
+                std::ptr::slice_from_raw_parts_mut(self.ptr as *mut u64, self.len.div_ceil(8));
+            drop(Box::from_raw(words));
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::memory::{vec_bytes, MemoryFootprint};
+
+#[derive(Debug)]
+pub struct Vertex<'a> {
+    pub id: usize,
+    pub name: &'a str,
+}
+
+impl<'a> Vertex<'a> {
+    pub fn new(id: usize, name: &'a str) -> Self {
+        Self { id, name }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Edge {
+    edge: bool,
+}
+
+impl Default for Edge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Edge {
+    pub fn new() -> Self {
+        Self { edge: false }
+    }
+
+    pub fn set_edge() -> Self {
+        Edge { edge: true }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.edge
+    }
+}
+
+#[derive(Debug)]
+pub struct Graph {
+    nodes: usize,
+    graph: Vec<Vec<Edge>>,
+}
+
+impl Graph {
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            nodes,
+            graph: vec![vec![Edge::new(); nodes]; nodes],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    // panics if either vertex is beyond the graph
+    pub fn add_edge(&mut self, n1: &Vertex, n2: &Vertex) {
+        let (from, to) = (n1.id, n2.id);
+        assert!(
+            from < self.nodes && to < self.nodes,
+            "edge ({from}, {to}) out of range"
+        );
+        self.graph[from][to] = Edge::set_edge();
+    }
+
+    // one cell per vertex pair, the row vectors count as the index
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            vertex_table: 0,
+            neighbor_lists: self.graph.iter().map(vec_bytes).sum(),
+            indices: vec_bytes(&self.graph),
+        }
+    }
+
+    pub fn adjacent(&self, from: usize, to: usize) -> bool {
+        from < self.nodes && to < self.nodes && self.graph[from][to].is_set()
+    }
+
+    pub fn neighbors(&self, id: usize) -> Vec<usize> {
+        let mut nbrs = Vec::new();
+        for (to, edge) in self.graph[id].iter().enumerate() {
+            if edge.is_set() {
+                nbrs.push(to);
+            }
+        }
+        nbrs
+    }
+
+    // per-cell reference versions of the kernels in bitmatrix
+
+    // Warshall: from reaches to through a path of at least one edge
+    pub fn transitive_closure(&self) -> Graph {
+        let n = self.nodes;
+        let mut reach = self.graph.clone();
+        for k in 0..n {
+            let row_k = reach[k].clone();
+            for row in reach.iter_mut() {
+                if !row[k].is_set() {
+                    continue;
+                }
+                for (cell, via) in row.iter_mut().zip(row_k.iter()) {
+                    if via.is_set() {
+                        *cell = Edge::set_edge();
+                    }
+                }
+            }
+        }
+        Graph {
+            nodes: n,
+            graph: reach,
+        }
+    }
+
+    // triangles of the graph read as undirected (edges in both
+    // directions), self loops ignored
+    pub fn triangle_count(&self) -> u64 {
+        let n = self.nodes;
+        let mut count = 0;
+        for i in 0..n {
+            for j in i + 1..n {
+                if !self.graph[i][j].is_set() {
+                    continue;
+                }
+                for k in j + 1..n {
+                    if self.graph[i][k].is_set() && self.graph[j][k].is_set() {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    pub fn common_neighbors(&self, a: usize, b: usize) -> usize {
+        (0..self.nodes)
+            .filter(|&v| self.graph[a][v].is_set() && self.graph[b][v].is_set())
+            .count()
+    }
+}
@@ -0,0 +1,41 @@
+use std::{collections::HashMap, mem::size_of};
+
+// Heap bytes held by a graph representation, split by purpose. Figures
+// count allocated capacity, not just the used part, and leave out memory
+// owned by the vertex keys themselves (e.g. the bytes of String keys) and
+// file-backed mappings.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryFootprint {
+    // per-vertex records and the table that finds them
+    pub vertex_table: usize,
+    // neighbor ids and edge weights
+    pub neighbor_lists: usize,
+    // offset arrays and other lookup structure
+    pub indices: usize,
+}
+
+impl MemoryFootprint {
+    pub fn total(&self) -> usize {
+        self.vertex_table + self.neighbor_lists + self.indices
+    }
+}
+
+// std's swiss table: a power of two number of buckets at 7/8 load, one
+// control byte per bucket plus a group of trailing control bytes
+pub(crate) fn hash_map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    let cap = map.capacity();
+    if cap == 0 {
+        return 0;
+    }
+    let buckets = if cap < 8 {
+        cap.next_power_of_two().max(4)
+    } else {
+        (cap * 8 / 7).next_power_of_two()
+    };
+    buckets * (size_of::<(K, V)>() + 1) + 16
+}
+
+pub(crate) fn vec_bytes<T>(v: &Vec<T>) -> usize {
+    v.capacity() * size_of::<T>()
+}
@@ -0,0 +1,235 @@
+use std::{fmt, marker::PhantomData};
+
+use crate::hashing::seeded_hash;
+use crate::sha256::Sha256;
+
+// How a Merkle tree hashes. Leaves and inner nodes hash apart, so a node's
+// digest can never pass for a leaf's (the second preimage attack on naive
+// trees).
+pub trait MerkleHasher {
+    type Digest: Clone + Eq + fmt::Debug;
+
+    fn leaf(data: &[u8]) -> Self::Digest;
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+}
+
+// SHA-256 with the RFC 6962 prefixes, 0 for leaves and 1 for nodes.
+pub struct Sha256Hasher;
+
+impl MerkleHasher for Sha256Hasher {
+    type Digest = [u8; 32];
+
+    fn leaf(data: &[u8]) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(&[0]);
+        h.update(data);
+        h.finish()
+    }
+
+    fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(&[1]);
+        h.update(left);
+        h.update(right);
+        h.finish()
+    }
+}
+
+// 64 bit std hashing: catches accidental corruption fast, but is no
+// defense against anyone choosing the data.
+pub struct FastHasher;
+
+impl MerkleHasher for FastHasher {
+    type Digest = u64;
+
+    fn leaf(data: &[u8]) -> u64 {
+        seeded_hash(data, 0)
+    }
+
+    fn node(left: &u64, right: &u64) -> u64 {
+        seeded_hash(&(left, right), 1)
+    }
+}
+
+// Merkle tree over a sequence of byte strings: leaf digests at the bottom
+// and each level above hashing pairs, an odd digest at the end moving up
+// unpaired. All levels are kept, so a proof for a leaf is its siblings on
+// the way up, O(log n) digests, and replacing a leaf rehashes its path.
+pub struct MerkleTree<H: MerkleHasher = Sha256Hasher> {
+    // levels[0] are the leaves, the last level is the root alone
+    levels: Vec<Vec<H::Digest>>,
+}
+
+// which side of the running digest a proof step's sibling is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+// Inclusion proof for the leaf at index of a tree of leaves leaves.
+pub struct Proof<H: MerkleHasher> {
+    pub index: usize,
+    pub leaves: usize,
+    pub path: Vec<(Side, H::Digest)>,
+    hasher: PhantomData<fn() -> H>,
+}
+
+// by hand, a derive would want H: Clone
+impl<H: MerkleHasher> Clone for MerkleTree<H> {
+    fn clone(&self) -> Self {
+        Self {
+            levels: self.levels.clone(),
+        }
+    }
+}
+
+impl<H: MerkleHasher> Clone for Proof<H> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<H: MerkleHasher> fmt::Debug for Proof<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proof")
+            .field("index", &self.index)
+            .field("leaves", &self.leaves)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl<H: MerkleHasher> fmt::Debug for MerkleTree<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MerkleTree")
+            .field("leaves", &self.len())
+            .field("root", &self.root())
+            .finish()
+    }
+}
+
+impl<H: MerkleHasher> MerkleTree<H> {
+    pub fn new<I>(leaves: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        Self::from_leaf_digests(leaves.into_iter().map(|d| H::leaf(d.as_ref())).collect())
+    }
+
+    // over leaves already hashed with H::leaf
+    pub fn from_leaf_digests(digests: Vec<H::Digest>) -> Self {
+        let mut levels = vec![digests];
+        while levels.last().unwrap().len() > 1 {
+            let below = levels.last().unwrap();
+            let level = below
+                .chunks(2)
+                .map(|pair| match pair {
+                    [l, r] => H::node(l, r),
+                    [odd] => odd.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+        }
+        Self { levels }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    // None for no leaves
+    pub fn root(&self) -> Option<&H::Digest> {
+        self.levels.last().unwrap().first()
+    }
+
+    pub fn leaf_digest(&self, index: usize) -> Option<&H::Digest> {
+        self.levels[0].get(index)
+    }
+
+    pub fn proof(&self, index: usize) -> Option<Proof<H>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut path = Vec::with_capacity(self.levels.len() - 1);
+        let mut i = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = i ^ 1;
+            if sibling < level.len() {
+                let side = if sibling < i { Side::Left } else { Side::Right };
+                path.push((side, level[sibling].clone()));
+            }
+            i /= 2;
+        }
+        Some(Proof {
+            index,
+            leaves: self.len(),
+            path,
+            hasher: PhantomData,
+        })
+    }
+
+    // replaces leaf index and rehashes its O(log n) ancestors
+    pub fn update(&mut self, index: usize, data: &[u8]) {
+        let len = self.len();
+        assert!(index < len, "index {index} out of range for length {len}");
+        self.levels[0][index] = H::leaf(data);
+        let mut i = index;
+        for l in 1..self.levels.len() {
+            let below = &self.levels[l - 1];
+            let (left, right) = (i & !1, i | 1);
+            let digest = match below.get(right) {
+                Some(r) => H::node(&below[left], r),
+                None => below[left].clone(),
+            };
+            i /= 2;
+            self.levels[l][i] = digest;
+        }
+    }
+}
+
+impl<H: MerkleHasher> Proof<H> {
+    // the root a tree would have with data as this proof's leaf
+    pub fn root_for(&self, data: &[u8]) -> H::Digest {
+        self.path
+            .iter()
+            .fold(H::leaf(data), |acc, (side, sibling)| match side {
+                Side::Left => H::node(sibling, &acc),
+                Side::Right => H::node(&acc, sibling),
+            })
+    }
+
+    // true if data is the leaf at index of a tree with this root
+    pub fn verify(&self, root: &H::Digest, data: &[u8]) -> bool {
+        self.path.len() == self.expected_len() && self.root_for(data) == *root
+    }
+
+    // Siblings the path needs, the levels where index isn't the odd one out.
+    // Checking it, and the sides against index, stops a proof taken for
+    // one position being passed off for another.
+    fn expected_len(&self) -> usize {
+        let (mut i, mut n, mut steps) = (self.index, self.leaves, 0);
+        let mut sides = self.path.iter().map(|(s, _)| *s);
+        while n > 1 {
+            if i ^ 1 < n {
+                let side = if i % 2 == 1 { Side::Left } else { Side::Right };
+                if sides.next() != Some(side) {
+                    return usize::MAX;
+                }
+                steps += 1;
+            }
+            i /= 2;
+            n = n.div_ceil(2);
+        }
+        steps
+    }
+}
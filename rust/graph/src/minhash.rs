@@ -0,0 +1,210 @@
+use std::{collections::HashMap, hash::Hash, marker::PhantomData};
+
+use crate::hashing::{mix64, seeded_hash};
+use crate::rng::{Rng, SplitMix64};
+
+// MinHash: under a random permutation of the item space, two sets have the
+// same smallest item with probability equal to their Jaccard similarity
+// |A n B| / |A u B|. A signature keeps the minimum under k permutations, a
+// hash of the item mixed with k different salts, and the fraction of equal
+// positions in two signatures estimates the similarity within about
+// 1 / sqrt(k).
+#[derive(Debug, Clone)]
+pub struct MinHash<T: ?Sized> {
+    salts: Vec<u64>,
+    seed: u64,
+    item: PhantomData<fn(&T)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Signature(pub Vec<u64>);
+
+impl<T: Hash + ?Sized> MinHash<T> {
+    pub fn new(num_hashes: usize) -> Self {
+        Self::with_seed(num_hashes, 0)
+    }
+
+    pub fn with_seed(num_hashes: usize, seed: u64) -> Self {
+        assert!(num_hashes >= 1, "minhash needs at least one hash");
+        let mut rng = SplitMix64::new(seed);
+        Self {
+            salts: (0..num_hashes).map(|_| rng.next_u64()).collect(),
+            seed,
+            item: PhantomData,
+        }
+    }
+
+    pub fn num_hashes(&self) -> usize {
+        self.salts.len()
+    }
+
+    // all u64::MAX for the empty set
+    pub fn signature<'a, I>(&self, items: I) -> Signature
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        let mut mins = vec![u64::MAX; self.salts.len()];
+        for item in items {
+            let h = seeded_hash(item, self.seed);
+            for (min, &salt) in mins.iter_mut().zip(&self.salts) {
+                *min = (*min).min(mix64(h ^ salt));
+            }
+        }
+        Signature(mins)
+    }
+}
+
+impl Signature {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // estimated Jaccard similarity, both from the same MinHash
+    pub fn similarity(&self, other: &Signature) -> f64 {
+        assert_eq!(self.len(), other.len(), "signatures differ in length");
+        let equal = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        equal as f64 / self.len() as f64
+    }
+
+    // signature of the union of both sets
+    pub fn union(&self, other: &Signature) -> Signature {
+        assert_eq!(self.len(), other.len(), "signatures differ in length");
+        Signature(
+            self.0
+                .iter()
+                .zip(&other.0)
+                .map(|(&a, &b)| a.min(b))
+                .collect(),
+        )
+    }
+}
+
+// Locality-sensitive hashing over signatures: cut into bands of rows
+// values each, and two signatures become candidates when they agree on all
+// of some band. Sets of similarity s collide with probability
+// 1 - (1 - s^rows)^bands, an S-curve that rises steepest around
+// (1 / bands)^(1 / rows), so near duplicates are found without comparing
+// every pair.
+#[derive(Debug, Clone)]
+pub struct LshIndex<K> {
+    bands: usize,
+    rows: usize,
+    keys: Vec<K>,
+    signatures: Vec<Signature>,
+    // per band, band hash to ids
+    tables: Vec<HashMap<u64, Vec<usize>>>,
+}
+
+impl<K> LshIndex<K> {
+    // takes signatures of bands * rows hashes
+    pub fn new(bands: usize, rows: usize) -> Self {
+        assert!(bands >= 1 && rows >= 1, "lsh needs a band of one row");
+        Self {
+            bands,
+            rows,
+            keys: Vec::new(),
+            signatures: Vec::new(),
+            tables: vec![HashMap::new(); bands],
+        }
+    }
+
+    pub fn bands(&self) -> usize {
+        self.bands
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    // the similarity at which the chance of becoming candidates is steepest
+    pub fn threshold(&self) -> f64 {
+        (1.0 / self.bands as f64).powf(1.0 / self.rows as f64)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn key(&self, id: usize) -> &K {
+        &self.keys[id]
+    }
+
+    pub fn signature(&self, id: usize) -> &Signature {
+        &self.signatures[id]
+    }
+
+    // adds key under signature and returns its id
+    pub fn insert(&mut self, key: K, signature: Signature) -> usize {
+        let id = self.keys.len();
+        let hashes: Vec<u64> = self.band_hashes(&signature).collect();
+        for (table, h) in self.tables.iter_mut().zip(hashes) {
+            table.entry(h).or_default().push(id);
+        }
+        self.keys.push(key);
+        self.signatures.push(signature);
+        id
+    }
+
+    // ids sharing a band with signature, in increasing order
+    pub fn candidates(&self, signature: &Signature) -> Vec<usize> {
+        let mut found: Vec<usize> = self
+            .band_hashes(signature)
+            .enumerate()
+            .filter_map(|(band, h)| self.tables[band].get(&h))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    // candidates estimated at least threshold similar, most similar first
+    pub fn query(&self, signature: &Signature, threshold: f64) -> Vec<(usize, f64)> {
+        let mut found: Vec<(usize, f64)> = self
+            .candidates(signature)
+            .into_iter()
+            .map(|id| (id, self.signatures[id].similarity(signature)))
+            .filter(|&(_, s)| s >= threshold)
+            .collect();
+        found.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        found
+    }
+
+    // every pair of ids sharing a band, (smaller, larger) in increasing
+    // order
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for table in &self.tables {
+            for ids in table.values() {
+                for (i, &a) in ids.iter().enumerate() {
+                    pairs.extend(ids[i + 1..].iter().map(|&b| (a, b)));
+                }
+            }
+        }
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+    }
+
+    fn band_hashes<'a>(&self, signature: &'a Signature) -> impl Iterator<Item = u64> + 'a {
+        assert_eq!(
+            signature.len(),
+            self.bands * self.rows,
+            "signature length must be bands * rows"
+        );
+        signature
+            .0
+            .chunks(self.rows)
+            .map(|rows| seeded_hash(rows, 0))
+    }
+}
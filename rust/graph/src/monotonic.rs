@@ -0,0 +1,223 @@
+use std::ops::Range;
+
+use crate::ring_buffer::RingBuffer;
+
+// Stack kept monotonic by popping, before each push, the items the new one
+// dominates. keep(a, b) says whether a may stay below b: a > b keeps the
+// stack strictly decreasing from the bottom, so the top is always the
+// nearest item greater than everything pushed after it. Each item is
+// pushed and popped once, O(1) amortized per push.
+pub struct MonotonicStack<T, F = fn(&T, &T) -> bool> {
+    items: Vec<T>,
+    keep: F,
+}
+
+fn greater<T: Ord>(a: &T, b: &T) -> bool {
+    a > b
+}
+
+fn less<T: Ord>(a: &T, b: &T) -> bool {
+    a < b
+}
+
+impl<T: Ord> MonotonicStack<T> {
+    // strictly decreasing from the bottom
+    pub fn decreasing() -> Self {
+        Self::by(greater)
+    }
+
+    pub fn increasing() -> Self {
+        Self::by(less)
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> bool> MonotonicStack<T, F> {
+    pub fn by(keep: F) -> Self {
+        Self {
+            items: Vec::new(),
+            keep,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn top(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.push_with(value, |_, _| {});
+    }
+
+    // Hands each item value pops to on_pop, top first, along with the item
+    // left below it: the nearest earlier one it didn't dominate.
+    pub fn push_with(&mut self, value: T, mut on_pop: impl FnMut(T, Option<&T>)) {
+        while let Some(top) = self.items.last() {
+            if (self.keep)(top, &value) {
+                break;
+            }
+            let top = self.items.pop().unwrap();
+            on_pop(top, self.items.last());
+        }
+        self.items.push(value);
+    }
+
+    // bottom to top
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+// Deque kept monotonic from the front the same way, with pushes at the
+// back and expiry at the front. Each item gets the index of its push, so
+// evict_before drops those that slid out of a window; with keep as a > b
+// the front is the maximum of the items pushed since the last eviction
+// point, the sliding window maximum.
+pub struct MonotonicDeque<T, F = fn(&T, &T) -> bool> {
+    items: RingBuffer<(usize, T)>,
+    pushed: usize,
+    keep: F,
+}
+
+impl<T: Ord> MonotonicDeque<T> {
+    // the front is the maximum
+    pub fn max() -> Self {
+        Self::by(greater)
+    }
+
+    pub fn min() -> Self {
+        Self::by(less)
+    }
+}
+
+impl<T, F: Fn(&T, &T) -> bool> MonotonicDeque<T, F> {
+    pub fn by(keep: F) -> Self {
+        Self {
+            items: RingBuffer::new(),
+            pushed: 0,
+            keep,
+        }
+    }
+
+    // items still held, not pushed
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // index the next push gets
+    pub fn next_index(&self) -> usize {
+        self.pushed
+    }
+
+    pub fn push(&mut self, value: T) -> usize {
+        while let Some((_, back)) = self.items.back() {
+            if (self.keep)(back, &value) {
+                break;
+            }
+            self.items.pop_back();
+        }
+        let index = self.pushed;
+        self.pushed += 1;
+        // a growable buffer never refuses
+        self.items.push_back((index, value)).ok();
+        index
+    }
+
+    // the front item and its index
+    pub fn front(&self) -> Option<(usize, &T)> {
+        self.items.front().map(|(i, v)| (*i, v))
+    }
+
+    pub fn pop_front(&mut self) -> Option<(usize, T)> {
+        self.items.pop_front()
+    }
+
+    // drops the items pushed before index
+    pub fn evict_before(&mut self, index: usize) {
+        while self.items.front().is_some_and(|&(i, _)| i < index) {
+            self.items.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, &T)> {
+        self.items.iter().map(|(i, v)| (*i, v))
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+// Maximum of every window of k consecutive values, n - k + 1 of them, in
+// O(n) overall.
+pub fn sliding_window_max<T: Ord + Clone>(values: &[T], k: usize) -> Vec<T> {
+    sliding_window(values, k, MonotonicDeque::max())
+}
+
+pub fn sliding_window_min<T: Ord + Clone>(values: &[T], k: usize) -> Vec<T> {
+    sliding_window(values, k, MonotonicDeque::min())
+}
+
+fn sliding_window<T: Clone>(values: &[T], k: usize, mut deque: MonotonicDeque<T>) -> Vec<T> {
+    assert!(k >= 1, "window must hold at least one value");
+    let mut out = Vec::with_capacity((values.len() + 1).saturating_sub(k));
+    for (i, value) in values.iter().enumerate() {
+        deque.push(value.clone());
+        if i + 1 >= k {
+            deque.evict_before(i + 1 - k);
+            out.push(deque.front().unwrap().1.clone());
+        }
+    }
+    out
+}
+
+// For each value the index of the first later one strictly greater.
+pub fn next_greater<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    let mut next = vec![None; values.len()];
+    // indices waiting for an answer, their values not increasing up the
+    // stack, so a new value answers a run at the top
+    let mut stack = MonotonicStack::by(|&a: &usize, &b: &usize| values[a] >= values[b]);
+    for i in 0..values.len() {
+        stack.push_with(i, |j, _| next[j] = Some(i));
+    }
+    next
+}
+
+// Largest rectangle under a histogram of bars of width 1: its area and the
+// bars it spans, the leftmost such, (0, 0..0) if there are no bars. Each
+// bar popped off an increasing stack is the shortest of the widest span it
+// is the minimum of, between the bar left below it and the one popping it.
+pub fn largest_rectangle(heights: &[u64]) -> (u64, Range<usize>) {
+    // a bar of height 0 past the end pops everything
+    let height = |i: usize| heights.get(i).copied().unwrap_or(0);
+    let mut stack = MonotonicStack::by(|&a: &usize, &b: &usize| height(a) < height(b));
+    let mut best = (0, 0..0);
+    for i in 0..=heights.len() {
+        stack.push_with(i, |top, below| {
+            let left = below.map_or(0, |&l| l + 1);
+            let area = height(top) * (i - left) as u64;
+            if area > best.0 || (area == best.0 && area > 0 && left < best.1.start) {
+                best = (area, left..i);
+            }
+        });
+    }
+    best
+}
@@ -0,0 +1,75 @@
+use std::hash::Hash;
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::indexed_heap::IndexedHeap;
+use crate::union_find::UnionFind;
+
+// Minimum spanning forest of an undirected graph (every edge stored in
+// both directions); one tree per connected component.
+
+#[derive(Debug, Clone)]
+pub struct SpanningForest<T> {
+    pub weight: i64,
+    pub edges: Vec<(T, T, i32)>,
+}
+
+pub fn kruskal<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> SpanningForest<T> {
+    let g = graph.indexed();
+    let mut edges: Vec<(i32, usize, usize)> = Vec::new();
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        for &(u, wt) in nbrs.iter() {
+            if v < u {
+                edges.push((wt, v, u));
+            }
+        }
+    }
+    edges.sort_unstable();
+    let mut uf = UnionFind::new(g.keys.len());
+    let mut forest = SpanningForest {
+        weight: 0,
+        edges: Vec::new(),
+    };
+    for (wt, v, u) in edges {
+        if uf.union(v, u) {
+            forest.weight += wt as i64;
+            forest
+                .edges
+                .push((g.keys[v].clone(), g.keys[u].clone(), wt));
+        }
+    }
+    forest
+}
+
+pub fn prim<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> SpanningForest<T> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let mut in_tree = vec![false; n];
+    // tree vertex each fringe vertex would connect through
+    let mut link = vec![usize::MAX; n];
+    let mut heap: IndexedHeap<i32> = IndexedHeap::new(n);
+    let mut forest = SpanningForest {
+        weight: 0,
+        edges: Vec::new(),
+    };
+    for root in 0..n {
+        if in_tree[root] {
+            continue;
+        }
+        heap.push(root, 0);
+        while let Some((v, wt)) = heap.pop() {
+            in_tree[v] = true;
+            if link[v] != usize::MAX {
+                forest.weight += wt as i64;
+                forest
+                    .edges
+                    .push((g.keys[link[v]].clone(), g.keys[v].clone(), wt));
+            }
+            for &(u, w) in g.adj[v].iter() {
+                if !in_tree[u] && heap.push_or_decrease(u, w) {
+                    link[u] = v;
+                }
+            }
+        }
+    }
+    forest
+}
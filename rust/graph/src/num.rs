@@ -0,0 +1,74 @@
+use std::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Div, Mul, Rem, Sub, SubAssign},
+};
+
+// The integer operations the range query structures need, implemented for
+// all primitive integers.
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    // wraps like an `as` cast
+    fn from_usize(n: usize) -> Self;
+
+    fn abs_value(self) -> Self;
+}
+
+macro_rules! integer {
+    (signed $($t:ty),*) => {$(
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            fn from_usize(n: usize) -> Self {
+                n as $t
+            }
+            fn abs_value(self) -> Self {
+                self.abs()
+            }
+        }
+    )*};
+    (unsigned $($t:ty),*) => {$(
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+            fn from_usize(n: usize) -> Self {
+                n as $t
+            }
+            fn abs_value(self) -> Self {
+                self
+            }
+        }
+    )*};
+}
+
+integer!(signed i8, i16, i32, i64, i128, isize);
+integer!(unsigned u8, u16, u32, u64, u128, usize);
+
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a.abs_value(), b.abs_value());
+    while b != T::ZERO {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
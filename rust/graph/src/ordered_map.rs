@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+// Common interface of the ordered maps in this crate, enough to swap them
+// in benchmarks and property tests. std's BTreeMap implements it too as the
+// baseline to compare against.
+pub trait OrderedMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn get(&self, key: &K) -> Option<&V>;
+    // the smallest and largest entries
+    fn first(&self) -> Option<(&K, &V)>;
+    fn last(&self) -> Option<(&K, &V)>;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for BTreeMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+    fn first(&self) -> Option<(&K, &V)> {
+        self.first_key_value()
+    }
+    fn last(&self) -> Option<(&K, &V)> {
+        self.last_key_value()
+    }
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+}
@@ -0,0 +1,319 @@
+use std::{error::Error, fmt};
+
+use crate::arena::{Arena, Id, Tags};
+use crate::priority_queue::{Compare, MinOrder, PriorityQueue};
+
+// Heap-ordered multiway tree, children kept as a sibling list. push and
+// decrease_key link two trees in O(1); pop pairs up the children of the
+// root left to right and folds the pairs right to left, O(log n)
+// amortized. The nodes live in an arena and link to each other by slot,
+// so a handle is an arena id that stops resolving once its item is popped.
+pub struct PairingHeap<T, C = MinOrder> {
+    nodes: Arena<Node<T>>,
+    root: Link,
+    tags: Tags,
+    cmp: C,
+}
+
+type Link = Option<u32>;
+
+struct Node<T> {
+    item: T,
+    child: Link,
+    next: Link,
+    // left sibling, or the parent for a first child
+    prev: Link,
+}
+
+// Refers to a pushed item until it is popped, in the heap that returned it
+// or a heap that heap was melded into. Anywhere else it refers to nothing.
+pub struct Handle<T> {
+    tag: u64,
+    id: Id<Node<T>>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.tag, self.id) == (other.tag, other.id)
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({}:{:?})", self.tag, self.id)
+    }
+}
+
+// a decrease_key through a handle whose item is gone, with the item
+// handed back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleHandle<T>(pub T);
+
+impl<T> fmt::Display for StaleHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "handle refers to no item in this heap")
+    }
+}
+
+impl<T: fmt::Debug> Error for StaleHandle<T> {}
+
+impl<T: Ord> PairingHeap<T> {
+    pub fn new() -> Self {
+        Self::with_compare(MinOrder)
+    }
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Compare<T>> PairingHeap<T, C> {
+    pub fn with_compare(cmp: C) -> Self {
+        Self {
+            nodes: Arena::new(),
+            root: None,
+            tags: Tags::new(),
+            cmp,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.root.map(|r| &self.nodes.at(r).item)
+    }
+
+    pub fn push(&mut self, item: T) -> Handle<T> {
+        let id = self.nodes.insert(Node {
+            item,
+            child: None,
+            next: None,
+            prev: None,
+        });
+        let node = id.index() as u32;
+        self.root = Some(link(&self.cmp, &mut self.nodes, self.root, node));
+        Handle {
+            tag: self.tags.own(),
+            id,
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root.take()?;
+        let node = self.nodes.remove(self.nodes.id_at(root)).unwrap();
+        merge_pairs(&self.cmp, &mut self.nodes, node.child, &mut self.root);
+        Some(node.item)
+    }
+
+    // moves every item of other into self, the nodes of the smaller heap
+    // into the arena of the larger, so O(min(n, m)); handles of other stay
+    // valid for self
+    pub fn meld(&mut self, mut other: Self) {
+        if other.len() > self.len() {
+            std::mem::swap(&mut self.nodes, &mut other.nodes);
+            std::mem::swap(&mut self.root, &mut other.root);
+            std::mem::swap(&mut self.tags, &mut other.tags);
+        }
+        let offset = self.nodes.append(other.nodes, |node, offset| {
+            for link in [&mut node.child, &mut node.next, &mut node.prev] {
+                *link = link.map(|i| i + offset);
+            }
+        });
+        self.tags.absorb(other.tags, offset);
+        if let Some(r) = other.root {
+            self.root = Some(link(&self.cmp, &mut self.nodes, self.root, r + offset));
+        }
+    }
+
+    // the slot of handle's node, if its item is still in this heap
+    fn slot(&self, handle: Handle<T>) -> Option<u32> {
+        let id = handle.id.shifted(self.tags.offset(handle.tag)?);
+        self.nodes.contains(id).then_some(id.index() as u32)
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.slot(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slot(handle).map(|i| &self.nodes.at(i).item)
+    }
+
+    /// Replaces the item behind `handle` with one that is popped no later,
+    /// or hands it back if that item was popped or `handle` is from a heap
+    /// that was never melded into this one. Panics if `item` would be
+    /// popped after the current one.
+    pub fn decrease_key(&mut self, handle: Handle<T>, item: T) -> Result<(), StaleHandle<T>> {
+        let Some(i) = self.slot(handle) else {
+            return Err(StaleHandle(item));
+        };
+        let node = self.nodes.at_mut(i);
+        assert!(
+            !self.cmp.before(&node.item, &item),
+            "decrease_key with a later item"
+        );
+        node.item = item;
+        if self.root == Some(i) {
+            return Ok(());
+        }
+        // cut the subtree out of its sibling list and link it to the root
+        let (prev, next) = (node.prev.unwrap(), node.next);
+        node.prev = None;
+        node.next = None;
+        let before = self.nodes.at_mut(prev);
+        if before.child == Some(i) {
+            before.child = next;
+        } else {
+            before.next = next;
+        }
+        if let Some(next) = next {
+            self.nodes.at_mut(next).prev = Some(prev);
+        }
+        self.root = Some(link(&self.cmp, &mut self.nodes, self.root, i));
+        Ok(())
+    }
+
+    // handles given out so far refer to nothing afterwards
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.root = None;
+        self.tags = Tags::new();
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue<T> for PairingHeap<T, C> {
+    fn push(&mut self, item: T) {
+        PairingHeap::push(self, item);
+    }
+    fn pop(&mut self) -> Option<T> {
+        PairingHeap::pop(self)
+    }
+    fn peek(&self) -> Option<&T> {
+        PairingHeap::peek(self)
+    }
+    fn len(&self) -> usize {
+        PairingHeap::len(self)
+    }
+}
+
+impl<T, C> fmt::Debug for PairingHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PairingHeap")
+            .field("len", &self.nodes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// a and b are roots without siblings, the later one becomes the first
+// child of the other; the comparison is made before anything is moved
+fn link<T, C: Compare<T>>(cmp: &C, nodes: &mut Arena<Node<T>>, a: Link, b: u32) -> u32 {
+    let Some(a) = a else {
+        return b;
+    };
+    let b_first = cmp.before(&nodes.at(b).item, &nodes.at(a).item);
+    let (parent, child) = if b_first { (b, a) } else { (a, b) };
+    attach(nodes, parent, child);
+    parent
+}
+
+// child, a root, becomes the first child of parent
+fn attach<T>(nodes: &mut Arena<Node<T>>, parent: u32, child: u32) {
+    let first = nodes.at(parent).child;
+    if let Some(first) = first {
+        nodes.at_mut(first).prev = Some(child);
+    }
+    let c = nodes.at_mut(child);
+    c.next = first;
+    c.prev = Some(parent);
+    nodes.at_mut(parent).child = Some(child);
+}
+
+// Links the sibling list from first into one tree, stored in out: the
+// first pass links neighbors pairwise, chaining the results through next
+// in reverse order, the second folds them from the last pair back.
+fn merge_pairs<T, C: Compare<T>>(cmp: &C, nodes: &mut Arena<Node<T>>, first: Link, out: &mut Link) {
+    let mut pending = Pending {
+        nodes,
+        out,
+        root: None,
+        pairs: None,
+        rest: first,
+    };
+    while let Some(a) = pending.rest {
+        let merged = match pending.nodes.at(a).next {
+            Some(b) => {
+                let after = pending.nodes.at(b).next;
+                // a and b stay in rest until the comparison is made
+                let merged = link(cmp, pending.nodes, Some(a), b);
+                pending.rest = after;
+                merged
+            }
+            None => {
+                pending.rest = None;
+                a
+            }
+        };
+        let node = pending.nodes.at_mut(merged);
+        node.prev = None;
+        node.next = pending.pairs;
+        pending.pairs = Some(merged);
+    }
+    while let Some(p) = pending.pairs {
+        let next = pending.nodes.at(p).next;
+        let root = link(cmp, pending.nodes, pending.root, p);
+        pending.nodes.at_mut(root).next = None;
+        pending.root = Some(root);
+        pending.pairs = next;
+    }
+}
+
+// The trees of a merge_pairs in progress. Dropping it stores the root in
+// out; after a comparator panic the pairs and siblings not yet linked are
+// hung under it out of order, so every node stays reachable from the root.
+struct Pending<'a, T> {
+    nodes: &'a mut Arena<Node<T>>,
+    out: &'a mut Link,
+    root: Link,
+    pairs: Link,
+    rest: Link,
+}
+
+impl<T> Drop for Pending<'_, T> {
+    fn drop(&mut self) {
+        let mut top = self.root;
+        for mut list in [self.pairs, self.rest] {
+            while let Some(n) = list {
+                let node = self.nodes.at_mut(n);
+                list = node.next;
+                node.prev = None;
+                node.next = None;
+                top = Some(match top {
+                    Some(t) => {
+                        attach(self.nodes, t, n);
+                        t
+                    }
+                    None => n,
+                });
+            }
+        }
+        *self.out = top;
+    }
+}
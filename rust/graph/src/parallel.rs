@@ -0,0 +1,171 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::csr::CsrGraph;
+
+// Data parallel algorithms over CsrGraph on scoped std threads. Work below
+// SEQUENTIAL_CUTOFF items runs on the calling thread, spawning is not free.
+
+pub const SEQUENTIAL_CUTOFF: usize = 1024;
+
+const UNSEEN: usize = usize::MAX;
+
+pub fn threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// splits items into one chunk per thread and maps every chunk
+pub(crate) fn map_chunks<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &[T]) -> R + Sync,
+{
+    let workers = threads();
+    if items.len() < SEQUENTIAL_CUTOFF || workers == 1 {
+        return vec![f(0, items)];
+    }
+    let size = items.len().div_ceil(workers);
+    thread::scope(|s| {
+        let handles: Vec<_> = items
+            .chunks(size)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let f = &f;
+                s.spawn(move || f(i * size, chunk))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+// out[i] = f(i) for every index, one contiguous block per thread
+pub(crate) fn fill<R, F>(out: &mut [R], f: F)
+where
+    R: Send,
+    F: Fn(usize) -> R + Sync,
+{
+    let workers = threads();
+    if out.len() < SEQUENTIAL_CUTOFF || workers == 1 {
+        for (i, x) in out.iter_mut().enumerate() {
+            *x = f(i);
+        }
+        return;
+    }
+    let size = out.len().div_ceil(workers);
+    thread::scope(|s| {
+        for (c, chunk) in out.chunks_mut(size).enumerate() {
+            let f = &f;
+            s.spawn(move || {
+                for (i, x) in chunk.iter_mut().enumerate() {
+                    *x = f(c * size + i);
+                }
+            });
+        }
+    });
+}
+
+// level synchronous bfs, same result as CsrGraph::bfs
+pub fn par_bfs(graph: &CsrGraph, source: usize) -> Vec<Option<usize>> {
+    par_multi_source_bfs(graph, &[source]).0
+}
+
+// every frontier is split across threads, a vertex is claimed for the next
+// level by whichever thread wins the compare exchange on its distance. Ties
+// between sources at equal distance go to an arbitrary one of them.
+pub fn par_multi_source_bfs(
+    graph: &CsrGraph,
+    sources: &[usize],
+) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+    let n = graph.vertex_count();
+    let dist: Vec<AtomicUsize> = (0..n).map(|_| AtomicUsize::new(UNSEEN)).collect();
+    let nearest: Vec<AtomicUsize> = (0..n).map(|_| AtomicUsize::new(UNSEEN)).collect();
+    let mut frontier = Vec::new();
+    for &s in sources.iter() {
+        if dist[s].swap(0, Ordering::Relaxed) == UNSEEN {
+            nearest[s].store(s, Ordering::Relaxed);
+            frontier.push(s);
+        }
+    }
+    let mut level = 0;
+    while !frontier.is_empty() {
+        level += 1;
+        let next = map_chunks(&frontier, |_, chunk| {
+            let mut local = Vec::new();
+            for &v in chunk {
+                let owner = nearest[v].load(Ordering::Relaxed);
+                for &u in graph.neighbors(v) {
+                    if dist[u].load(Ordering::Relaxed) == UNSEEN
+                        && dist[u]
+                            .compare_exchange(UNSEEN, level, Ordering::Relaxed, Ordering::Relaxed)
+                            .is_ok()
+                    {
+                        nearest[u].store(owner, Ordering::Relaxed);
+                        local.push(u);
+                    }
+                }
+            }
+            local
+        });
+        frontier = next.concat();
+    }
+    let unwrap = |v: Vec<AtomicUsize>| {
+        v.into_iter()
+            .map(|a| Some(a.into_inner()).filter(|&d| d != UNSEEN))
+            .collect()
+    };
+    (unwrap(dist), unwrap(nearest))
+}
+
+// Same labels as CsrGraph::connected_components. Edges are hooked into a
+// lock free union find from all threads at once: roots are only ever linked
+// to a smaller root with a compare exchange, finds halve paths as they go,
+// so the surviving root of every component is its smallest vertex.
+pub fn par_connected_components(graph: &CsrGraph) -> Vec<usize> {
+    let n = graph.vertex_count();
+    let parent: Vec<AtomicUsize> = (0..n).map(AtomicUsize::new).collect();
+    let vertices: Vec<usize> = (0..n).collect();
+    map_chunks(&vertices, |_, chunk| {
+        for &v in chunk {
+            for &u in graph.neighbors(v) {
+                hook(&parent, v, u);
+            }
+        }
+    });
+    let mut labels = vec![0; n];
+    fill(&mut labels, |v| find(&parent, v));
+    labels
+}
+
+fn find(parent: &[AtomicUsize], mut x: usize) -> usize {
+    loop {
+        let p = parent[x].load(Ordering::Acquire);
+        if p == x {
+            return x;
+        }
+        let gp = parent[p].load(Ordering::Acquire);
+        if gp != p {
+            // losing this race only means the path stays a bit longer
+            let _ = parent[x].compare_exchange(p, gp, Ordering::AcqRel, Ordering::Relaxed);
+        }
+        x = p;
+    }
+}
+
+fn hook(parent: &[AtomicUsize], a: usize, b: usize) {
+    loop {
+        let (ra, rb) = (find(parent, a), find(parent, b));
+        if ra == rb {
+            return;
+        }
+        let (hi, lo) = (ra.max(rb), ra.min(rb));
+        if parent[hi]
+            .compare_exchange(hi, lo, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            return;
+        }
+    }
+}
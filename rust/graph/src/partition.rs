@@ -0,0 +1,361 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::indexed_heap::IndexedHeap;
+use crate::rng::{Rng, SplitMix64};
+
+// Balanced k-way partitioning by recursive bisection. Every bisection is
+// multilevel: the graph is coarsened by heavy edge matching until it is
+// small, split there by greedy region growing, and the split is projected
+// back level by level with Kernighan-Lin refinement (the Fiduccia-
+// Mattheyses single vertex move variant) at each level.
+//
+// The graph is read as undirected: u-v has the weight of u -> v, or the
+// larger one when both directions are stored. Weights should be positive,
+// self loops are ignored. Blocks hold the same number of vertices up to a
+// few percent.
+
+#[derive(Debug, Clone)]
+pub struct Partition<T> {
+    pub blocks: Vec<Vec<T>>,
+    // total weight of the edges between different blocks
+    pub cut: i64,
+}
+
+// coarsening stops once a level has at most this many vertices
+const COARSEST: usize = 40;
+// allowed deviation of a side from its target weight, as a fraction of
+// the smaller side's target
+const IMBALANCE: f64 = 0.03;
+const INITIAL_TRIES: usize = 4;
+const MAX_PASSES: usize = 8;
+// moves without a new best state before a refinement pass gives up
+const STALL: usize = 64;
+
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Weighted {
+    vw: Vec<i64>,
+    adj: Vec<Vec<(usize, i64)>>,
+}
+
+// k must be at least 1; with more blocks than vertices some stay empty
+pub fn partition<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+    k: usize,
+) -> Partition<T> {
+    assert!(k >= 1, "need at least one block");
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let mut edges: HashMap<(usize, usize), i64> = HashMap::new();
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        for &(u, wt) in nbrs.iter() {
+            if u != v {
+                let w = edges.entry((v.min(u), v.max(u))).or_insert(wt as i64);
+                *w = (*w).max(wt as i64);
+            }
+        }
+    }
+    let mut adj = vec![Vec::new(); n];
+    for (&(a, b), &w) in edges.iter() {
+        adj[a].push((b, w));
+        adj[b].push((a, w));
+    }
+    let weighted = Weighted {
+        vw: vec![1; n],
+        adj,
+    };
+
+    let mut rng = SplitMix64::new(0x5eed);
+    let mut blocks = Vec::with_capacity(k);
+    split(&weighted, (0..n).collect(), k, &mut rng, &mut blocks);
+
+    let mut block_of = vec![0; n];
+    for (b, members) in blocks.iter().enumerate() {
+        for &v in members.iter() {
+            block_of[v] = b;
+        }
+    }
+    let cut = edges
+        .iter()
+        .filter(|(&(a, b), _)| block_of[a] != block_of[b])
+        .map(|(_, &w)| w)
+        .sum();
+    Partition {
+        blocks: blocks
+            .into_iter()
+            .map(|b| b.into_iter().map(|v| g.keys[v].clone()).collect())
+            .collect(),
+        cut,
+    }
+}
+
+// ids maps the vertices of g to the original vertex ids
+fn split(g: &Weighted, ids: Vec<usize>, k: usize, rng: &mut SplitMix64, out: &mut Vec<Vec<usize>>) {
+    if k == 1 {
+        out.push(ids);
+        return;
+    }
+    let k0 = k / 2;
+    let side = bisect(g, k0 as f64 / k as f64, rng);
+    for s in [false, true] {
+        let (sub, sub_ids) = induced(g, &ids, &side, s);
+        split(&sub, sub_ids, if s { k - k0 } else { k0 }, rng, out);
+    }
+}
+
+fn induced(g: &Weighted, ids: &[usize], side: &[bool], s: bool) -> (Weighted, Vec<usize>) {
+    let mut local = vec![NIL; side.len()];
+    let mut sub_ids = Vec::new();
+    for v in 0..side.len() {
+        if side[v] == s {
+            local[v] = sub_ids.len();
+            sub_ids.push(v);
+        }
+    }
+    let sub = Weighted {
+        vw: sub_ids.iter().map(|&v| g.vw[v]).collect(),
+        adj: sub_ids
+            .iter()
+            .map(|&v| {
+                g.adj[v]
+                    .iter()
+                    .filter(|&&(u, _)| side[u] == s)
+                    .map(|&(u, w)| (local[u], w))
+                    .collect()
+            })
+            .collect(),
+    };
+    (sub, sub_ids.into_iter().map(|v| ids[v]).collect())
+}
+
+// side[v] == false for the part that should get `frac` of the weight
+fn bisect(g: &Weighted, frac: f64, rng: &mut SplitMix64) -> Vec<bool> {
+    let mut levels = vec![g.clone()];
+    let mut maps = Vec::new();
+    while levels.last().unwrap().vw.len() > COARSEST {
+        let fine = levels.last().unwrap();
+        let (coarse, map) = coarsen(fine, rng);
+        if coarse.vw.len() * 10 > fine.vw.len() * 9 {
+            break;
+        }
+        levels.push(coarse);
+        maps.push(map);
+    }
+    let mut side = initial(levels.last().unwrap(), frac, rng);
+    for (fine, map) in levels.iter().rev().skip(1).zip(maps.iter().rev()) {
+        side = map.iter().map(|&c| side[c]).collect();
+        refine(fine, &mut side, frac);
+    }
+    side
+}
+
+// heavy edge matching: every vertex is merged with the unmatched neighbor
+// it shares the heaviest edge with, as long as the merged vertex stays
+// light enough not to dominate a side later
+fn coarsen(g: &Weighted, rng: &mut SplitMix64) -> (Weighted, Vec<usize>) {
+    let n = g.vw.len();
+    let total: i64 = g.vw.iter().sum();
+    let limit = (3 * total / (2 * COARSEST as i64)).max(1);
+    let mut order: Vec<usize> = (0..n).collect();
+    rng.shuffle(&mut order);
+    let mut mate = vec![NIL; n];
+    for &v in order.iter() {
+        if mate[v] != NIL {
+            continue;
+        }
+        mate[v] = v;
+        let mut best: Option<(i64, usize)> = None;
+        for &(u, w) in g.adj[v].iter() {
+            if mate[u] == NIL && g.vw[v] + g.vw[u] <= limit && best.is_none_or(|(bw, _)| w > bw) {
+                best = Some((w, u));
+            }
+        }
+        if let Some((_, u)) = best {
+            mate[v] = u;
+            mate[u] = v;
+        }
+    }
+
+    let mut map = vec![NIL; n];
+    let mut members = Vec::new();
+    for v in 0..n {
+        if map[v] == NIL {
+            map[v] = members.len();
+            map[mate[v]] = members.len();
+            members.push((v, mate[v]));
+        }
+    }
+    let nc = members.len();
+    let mut vw = Vec::with_capacity(nc);
+    let mut adj = Vec::with_capacity(nc);
+    // slot of each coarse neighbor in the row being built
+    let mut slot = vec![NIL; nc];
+    for (c, &(a, b)) in members.iter().enumerate() {
+        vw.push(if a == b { g.vw[a] } else { g.vw[a] + g.vw[b] });
+        let mut row: Vec<(usize, i64)> = Vec::new();
+        let list: &[usize] = if a == b { &[a] } else { &[a, b] };
+        for &v in list.iter() {
+            for &(u, w) in g.adj[v].iter() {
+                let cu = map[u];
+                if cu == c {
+                    continue;
+                }
+                if slot[cu] == NIL {
+                    slot[cu] = row.len();
+                    row.push((cu, 0));
+                }
+                row[slot[cu]].1 += w;
+            }
+        }
+        for &(cu, _) in row.iter() {
+            slot[cu] = NIL;
+        }
+        adj.push(row);
+    }
+    (Weighted { vw, adj }, map)
+}
+
+// greedy region growing from a few random seeds, breadth first, each
+// candidate refined and the smallest cut kept
+fn initial(g: &Weighted, frac: f64, rng: &mut SplitMix64) -> Vec<bool> {
+    let n = g.vw.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let target = target_weight(g, frac);
+    let mut best: Option<(i64, Vec<bool>)> = None;
+    for _ in 0..INITIAL_TRIES {
+        let mut seeds: Vec<usize> = (0..n).collect();
+        rng.shuffle(&mut seeds);
+        let mut seeds = seeds.into_iter();
+        let mut side = vec![true; n];
+        let mut w0 = 0;
+        let mut queue = std::collections::VecDeque::new();
+        let mut queued = vec![false; n];
+        while w0 < target {
+            let v = match queue.pop_front() {
+                Some(v) => v,
+                // the region used up its component, continue elsewhere
+                None => match seeds.find(|&v| !queued[v]) {
+                    Some(v) => {
+                        queued[v] = true;
+                        v
+                    }
+                    None => break,
+                },
+            };
+            side[v] = false;
+            w0 += g.vw[v];
+            for &(u, _) in g.adj[v].iter() {
+                if !queued[u] {
+                    queued[u] = true;
+                    queue.push_back(u);
+                }
+            }
+        }
+        refine(g, &mut side, frac);
+        let cut = cut_of(g, &side);
+        if best.as_ref().is_none_or(|(c, _)| cut < *c) {
+            best = Some((cut, side));
+        }
+    }
+    best.unwrap().1
+}
+
+fn target_weight(g: &Weighted, frac: f64) -> i64 {
+    let total: i64 = g.vw.iter().sum();
+    (total as f64 * frac).round() as i64
+}
+
+fn cut_of(g: &Weighted, side: &[bool]) -> i64 {
+    let mut cut = 0;
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        for &(u, w) in nbrs.iter() {
+            if v < u && side[v] != side[u] {
+                cut += w;
+            }
+        }
+    }
+    cut
+}
+
+fn refine(g: &Weighted, side: &mut [bool], frac: f64) {
+    let total: i64 = g.vw.iter().sum();
+    let max_vw = g.vw.iter().copied().max().unwrap_or(0);
+    let target = target_weight(g, frac);
+    let smaller = target.min(total - target);
+    let tol = ((smaller as f64 * IMBALANCE) as i64).max(max_vw - 1);
+    for _ in 0..MAX_PASSES {
+        if !pass(g, side, target, tol) {
+            break;
+        }
+    }
+}
+
+// One pass: repeatedly move the unlocked vertex with the largest gain
+// (cut reduction) that keeps the sides balanced, lock it, and finally roll
+// back to the best state seen. Returns whether that state is better than
+// the starting one.
+fn pass(g: &Weighted, side: &mut [bool], target: i64, tol: i64) -> bool {
+    let n = g.vw.len();
+    let mut gain = vec![0i64; n];
+    let mut heaps: [IndexedHeap<i64>; 2] = [IndexedHeap::new(n), IndexedHeap::new(n)];
+    for v in 0..n {
+        for &(u, w) in g.adj[v].iter() {
+            gain[v] += if side[u] != side[v] { w } else { -w };
+        }
+        heaps[side[v] as usize].push(v, -gain[v]);
+    }
+    let mut w0: i64 = (0..n).filter(|&v| !side[v]).map(|v| g.vw[v]).sum();
+    let mut cut = cut_of(g, side);
+    let imbalance = |w0: i64| (w0 - target).abs();
+    // unbalanced states always rank below balanced ones
+    let score = |cut: i64, w0: i64| (imbalance(w0) > tol, cut, imbalance(w0));
+
+    let mut best = score(cut, w0);
+    let mut best_len = 0;
+    let mut moves = Vec::new();
+    let mut locked = vec![false; n];
+    while moves.len() - best_len <= STALL {
+        let mut pick: Option<(i64, usize, i64)> = None;
+        for heap in heaps.iter() {
+            let Some((v, neg_gain)) = heap.peek() else {
+                continue;
+            };
+            let moved = if side[v] { w0 + g.vw[v] } else { w0 - g.vw[v] };
+            let balanced = imbalance(moved) <= tol || imbalance(moved) < imbalance(w0);
+            if balanced && pick.is_none_or(|(gn, _, _)| -neg_gain > gn) {
+                pick = Some((-neg_gain, v, moved));
+            }
+        }
+        let Some((gv, v, moved)) = pick else {
+            break;
+        };
+        heaps[side[v] as usize].remove(v);
+        side[v] = !side[v];
+        locked[v] = true;
+        w0 = moved;
+        cut -= gv;
+        moves.push(v);
+        for &(u, w) in g.adj[v].iter() {
+            if locked[u] {
+                continue;
+            }
+            gain[u] += if side[u] == side[v] { -2 * w } else { 2 * w };
+            let heap = &mut heaps[side[u] as usize];
+            heap.remove(u);
+            heap.push(u, -gain[u]);
+        }
+        let now = score(cut, w0);
+        if now < best {
+            best = now;
+            best_len = moves.len();
+        }
+    }
+    for &v in moves[best_len..].iter() {
+        side[v] = !side[v];
+    }
+    best_len > 0
+}
@@ -0,0 +1,243 @@
+use std::{marker::PhantomData, ops::Range};
+
+use crate::segment_tree::{Monoid, Sum};
+
+// Segment tree where update copies the O(log n) nodes on the path to the
+// leaf and returns a new version; old versions stay queryable and share
+// every other node with it.
+#[derive(Debug, Clone)]
+pub struct PersistentSegmentTree<T, Op> {
+    n: usize,
+    nodes: Vec<Node<T>>,
+    roots: Vec<usize>,
+    op: PhantomData<Op>,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    left: usize,
+    right: usize,
+}
+
+// a version of a PersistentSegmentTree, only meaningful for the tree that
+// returned it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version(usize);
+
+impl<T: Clone, Op: Monoid<T>> PersistentSegmentTree<T, Op> {
+    // version 0 holds values
+    pub fn new(values: Vec<T>) -> Self {
+        let mut tree = Self {
+            n: values.len(),
+            nodes: Vec::with_capacity(2 * values.len()),
+            roots: Vec::new(),
+            op: PhantomData,
+        };
+        let root = if values.is_empty() {
+            tree.leaf(Op::identity())
+        } else {
+            tree.build(0, values.len(), &values)
+        };
+        tree.roots.push(root);
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn initial(&self) -> Version {
+        Version(0)
+    }
+
+    pub fn latest(&self) -> Version {
+        Version(self.roots.len() - 1)
+    }
+
+    pub fn version_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    // version with value i set to value, version itself is unchanged
+    pub fn update(&mut self, version: Version, i: usize, value: T) -> Version {
+        assert!(i < self.n, "index {i} out of range for length {}", self.n);
+        let root = self.set(self.roots[version.0], 0, self.n, i, value);
+        self.roots.push(root);
+        self.latest()
+    }
+
+    pub fn get(&self, version: Version, i: usize) -> &T {
+        assert!(i < self.n, "index {i} out of range for length {}", self.n);
+        let (mut node, mut lo, mut hi) = (self.roots[version.0], 0, self.n);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if i < mid {
+                node = self.nodes[node].left;
+                hi = mid;
+            } else {
+                node = self.nodes[node].right;
+                lo = mid;
+            }
+        }
+        &self.nodes[node].value
+    }
+
+    pub fn query(&self, version: Version, range: Range<usize>) -> T {
+        assert!(
+            range.start <= range.end && range.end <= self.n,
+            "range {}..{} out of range for length {}",
+            range.start,
+            range.end,
+            self.n
+        );
+        if range.start == range.end {
+            return Op::identity();
+        }
+        self.query_node(self.roots[version.0], 0, self.n, &range)
+    }
+
+    fn leaf(&mut self, value: T) -> usize {
+        self.nodes.push(Node {
+            value,
+            left: usize::MAX,
+            right: usize::MAX,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn inner(&mut self, left: usize, right: usize) -> usize {
+        let value = Op::combine(&self.nodes[left].value, &self.nodes[right].value);
+        self.nodes.push(Node { value, left, right });
+        self.nodes.len() - 1
+    }
+
+    fn build(&mut self, lo: usize, hi: usize, values: &[T]) -> usize {
+        if hi - lo == 1 {
+            return self.leaf(values[lo].clone());
+        }
+        let mid = (lo + hi) / 2;
+        let left = self.build(lo, mid, values);
+        let right = self.build(mid, hi, values);
+        self.inner(left, right)
+    }
+
+    fn set(&mut self, node: usize, lo: usize, hi: usize, i: usize, value: T) -> usize {
+        if hi - lo == 1 {
+            return self.leaf(value);
+        }
+        let mid = (lo + hi) / 2;
+        let Node { left, right, .. } = self.nodes[node];
+        if i < mid {
+            let left = self.set(left, lo, mid, i, value);
+            self.inner(left, right)
+        } else {
+            let right = self.set(right, mid, hi, i, value);
+            self.inner(left, right)
+        }
+    }
+
+    fn query_node(&self, node: usize, lo: usize, hi: usize, range: &Range<usize>) -> T {
+        if range.start <= lo && hi <= range.end {
+            return self.nodes[node].value.clone();
+        }
+        let mid = (lo + hi) / 2;
+        let Node { left, right, .. } = self.nodes[node];
+        if range.end <= mid {
+            self.query_node(left, lo, mid, range)
+        } else if mid <= range.start {
+            self.query_node(right, mid, hi, range)
+        } else {
+            Op::combine(
+                &self.query_node(left, lo, mid, range),
+                &self.query_node(right, mid, hi, range),
+            )
+        }
+    }
+}
+
+// Order statistics of subarrays. Version i of the tree counts how often
+// each distinct value occurs in values[..i], so the counts of values[l..r]
+// are version r minus version l, and kth walks both versions at once in
+// O(log n).
+#[derive(Debug, Clone)]
+pub struct RangeKth<T> {
+    sorted: Vec<T>,
+    counts: PersistentSegmentTree<usize, Sum>,
+    // prefix[i] counts values[..i]
+    prefix: Vec<Version>,
+}
+
+impl<T: Ord + Clone> RangeKth<T> {
+    pub fn new(values: &[T]) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        let mut counts = PersistentSegmentTree::new(vec![0; sorted.len()]);
+        let mut prefix = vec![counts.initial()];
+        for v in values {
+            let rank = sorted.binary_search(v).unwrap();
+            let last = *prefix.last().unwrap();
+            let count = *counts.get(last, rank);
+            prefix.push(counts.update(last, rank, count + 1));
+        }
+        Self {
+            sorted,
+            counts,
+            prefix,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefix.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // the k-th smallest (from 0) of values[range], None when the range has
+    // at most k values
+    pub fn kth(&self, range: Range<usize>, k: usize) -> Option<&T> {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {}..{} out of range for length {}",
+            range.start,
+            range.end,
+            self.len()
+        );
+        if k >= range.end - range.start {
+            return None;
+        }
+        let nodes = &self.counts.nodes;
+        let mut a = self.counts.roots[self.prefix[range.start].0];
+        let mut b = self.counts.roots[self.prefix[range.end].0];
+        let (mut lo, mut hi, mut k) = (0, self.sorted.len(), k);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            let left = nodes[nodes[b].left].value - nodes[nodes[a].left].value;
+            if k < left {
+                a = nodes[a].left;
+                b = nodes[b].left;
+                hi = mid;
+            } else {
+                k -= left;
+                a = nodes[a].right;
+                b = nodes[b].right;
+                lo = mid;
+            }
+        }
+        Some(&self.sorted[lo])
+    }
+
+    // number of values in values[range] that are less than x
+    pub fn count_less(&self, range: Range<usize>, x: &T) -> usize {
+        let rank = self.sorted.partition_point(|v| v < x);
+        let (from, to) = (self.prefix[range.start], self.prefix[range.end]);
+        self.counts.query(to, 0..rank) - self.counts.query(from, 0..rank)
+    }
+}
@@ -0,0 +1,444 @@
+use std::{fmt, rc::Rc, slice};
+
+// Persistent vector as a relaxed radix balanced tree (Bagwell and Rompf):
+// items sit in leaves of up to B and branches hold up to B children. Every
+// operation copies the O(log n) nodes on its path and shares the rest with
+// the version it came from, so clones are O(1) and old versions stay valid.
+// A branch whose children are all full but the last is indexed by radix,
+// digit by digit; one made irregular by a concatenation keeps the
+// cumulative sizes of its children and is indexed by searching them.
+// Concatenation merges the two trees down the seam where they meet,
+// repacking the leaves and branches along it.
+pub struct PVec<T> {
+    root: Option<Node<T>>,
+    // of the root, leaves are height 0
+    height: u32,
+}
+
+enum Node<T> {
+    Leaf(Rc<Vec<T>>),
+    Branch(Rc<Branch<T>>),
+}
+
+struct Branch<T> {
+    children: Vec<Node<T>>,
+    // sizes[i] is the number of items in children[..=i]
+    sizes: Vec<usize>,
+    // every child but the last holds as many items as its height allows
+    dense: bool,
+}
+
+const BITS: u32 = 5;
+const B: usize = 1 << BITS;
+
+// by hand, a derive would want T: Clone
+impl<T> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Leaf(items) => Node::Leaf(Rc::clone(items)),
+            Node::Branch(branch) => Node::Branch(Rc::clone(branch)),
+        }
+    }
+}
+
+impl<T> Clone for PVec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            height: self.height,
+        }
+    }
+}
+
+// items a full node of height h holds
+fn capacity(h: u32) -> usize {
+    1usize.checked_shl(BITS * (h + 1)).unwrap_or(usize::MAX)
+}
+
+impl<T> Node<T> {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(items) => items.len(),
+            Node::Branch(branch) => *branch.sizes.last().unwrap(),
+        }
+    }
+
+    fn branch(children: Vec<Node<T>>, h: u32) -> Self {
+        let mut sizes = Vec::with_capacity(children.len());
+        let mut total = 0;
+        for child in &children {
+            total += child.len();
+            sizes.push(total);
+        }
+        let full = capacity(h - 1);
+        let dense = children[..children.len() - 1]
+            .iter()
+            .all(|c| c.len() == full);
+        Node::Branch(Rc::new(Branch {
+            children,
+            sizes,
+            dense,
+        }))
+    }
+
+    fn children(&self) -> &[Node<T>] {
+        match self {
+            Node::Leaf(_) => &[],
+            Node::Branch(branch) => &branch.children,
+        }
+    }
+}
+
+impl<T> Branch<T> {
+    // child holding item i of this branch at height h, and i within it
+    fn locate(&self, i: usize, h: u32) -> (usize, usize) {
+        let j = if self.dense {
+            i >> (BITS * h)
+        } else {
+            self.sizes.partition_point(|&s| s <= i)
+        };
+        (j, if j == 0 { i } else { i - self.sizes[j - 1] })
+    }
+}
+
+// Groups nodes of height h - 1 in runs of up to B under new nodes of
+// height h, filling all but the last run when full and evening out the
+// runs otherwise. Even runs keep room on both sides of a split, or every
+// later concatenation at the same place would split it again.
+fn pack<T>(nodes: Vec<Node<T>>, h: u32, full: bool) -> Vec<Node<T>> {
+    let run = run_length(nodes.len(), full);
+    let mut packed = Vec::with_capacity(nodes.len().div_ceil(run));
+    let mut nodes = nodes.into_iter().peekable();
+    while nodes.peek().is_some() {
+        packed.push(Node::branch(nodes.by_ref().take(run).collect(), h));
+    }
+    packed
+}
+
+fn pack_leaves<T>(items: Vec<T>, full: bool) -> Vec<Node<T>> {
+    let run = run_length(items.len(), full);
+    let mut leaves = Vec::with_capacity(items.len().div_ceil(run));
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        leaves.push(Node::Leaf(Rc::new(items.by_ref().take(run).collect())));
+    }
+    leaves
+}
+
+fn run_length(n: usize, full: bool) -> usize {
+    if full || n == 0 {
+        B
+    } else {
+        n.div_ceil(n.div_ceil(B))
+    }
+}
+
+impl<T> Default for PVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PVec<T> {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            height: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, Node::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // levels of branches above the leaves
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        let (mut node, mut h, mut i) = (self.root.as_ref()?, self.height, index);
+        loop {
+            match node {
+                Node::Leaf(items) => return Some(&items[i]),
+                Node::Branch(branch) => {
+                    let (j, rest) = branch.locate(i, h);
+                    node = &branch.children[j];
+                    h -= 1;
+                    i = rest;
+                }
+            }
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.get(self.len().wrapping_sub(1))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: self
+                .root
+                .as_ref()
+                .map(|root| vec![slice::from_ref(root).iter()])
+                .unwrap_or_default(),
+            leaf: [].iter(),
+            remaining: self.len(),
+        }
+    }
+
+    // true if both are the same version or copies of it
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (None, None) => true,
+            (Some(Node::Leaf(a)), Some(Node::Leaf(b))) => Rc::ptr_eq(a, b),
+            (Some(Node::Branch(a)), Some(Node::Branch(b))) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl<T: Clone> PVec<T> {
+    // a version with item index replaced
+    pub fn set(&self, index: usize, value: T) -> Self {
+        let len = self.len();
+        assert!(index < len, "index {index} out of range for length {len}");
+        fn go<T: Clone>(node: &Node<T>, h: u32, i: usize, value: T) -> Node<T> {
+            match node {
+                Node::Leaf(items) => {
+                    let mut items = Vec::clone(items);
+                    items[i] = value;
+                    Node::Leaf(Rc::new(items))
+                }
+                Node::Branch(branch) => {
+                    let (j, rest) = branch.locate(i, h);
+                    let mut children = branch.children.clone();
+                    children[j] = go(&children[j], h - 1, rest, value);
+                    // sizes don't change
+                    Node::Branch(Rc::new(Branch {
+                        children,
+                        sizes: branch.sizes.clone(),
+                        dense: branch.dense,
+                    }))
+                }
+            }
+        }
+        Self {
+            root: Some(go(self.root.as_ref().unwrap(), self.height, index, value)),
+            height: self.height,
+        }
+    }
+
+    pub fn push_back(&self, value: T) -> Self {
+        // Err is a new node of the same height holding only value, for when
+        // node has no room left down its right edge
+        fn go<T: Clone>(node: &Node<T>, h: u32, value: T) -> Result<Node<T>, Node<T>> {
+            match node {
+                Node::Leaf(items) if items.len() < B => {
+                    let mut items = Vec::clone(items);
+                    items.push(value);
+                    Ok(Node::Leaf(Rc::new(items)))
+                }
+                Node::Leaf(_) => Err(Node::Leaf(Rc::new(vec![value]))),
+                Node::Branch(branch) => {
+                    let mut children = branch.children.clone();
+                    match go(children.last().unwrap(), h - 1, value) {
+                        Ok(last) => *children.last_mut().unwrap() = last,
+                        Err(sibling) if children.len() < B => children.push(sibling),
+                        Err(sibling) => return Err(Node::branch(vec![sibling], h)),
+                    }
+                    Ok(Node::branch(children, h))
+                }
+            }
+        }
+        let Some(root) = &self.root else {
+            return Self {
+                root: Some(Node::Leaf(Rc::new(vec![value]))),
+                height: 0,
+            };
+        };
+        match go(root, self.height, value) {
+            Ok(root) => Self {
+                root: Some(root),
+                height: self.height,
+            },
+            Err(sibling) => Self {
+                root: Some(Node::branch(vec![root.clone(), sibling], self.height + 1)),
+                height: self.height + 1,
+            },
+        }
+    }
+
+    // O(log n) as a concatenation
+    pub fn push_front(&self, value: T) -> Self {
+        Self::from_iter([value]).concat(self)
+    }
+
+    // the version without the last item, and that item
+    pub fn pop_back(&self) -> Option<(Self, T)> {
+        // the node left, None if it emptied
+        fn go<T: Clone>(node: &Node<T>, h: u32) -> (Option<Node<T>>, T) {
+            match node {
+                Node::Leaf(items) => {
+                    let mut items = Vec::clone(items);
+                    let value = items.pop().unwrap();
+                    (
+                        (!items.is_empty()).then(|| Node::Leaf(Rc::new(items))),
+                        value,
+                    )
+                }
+                Node::Branch(branch) => {
+                    let mut children = branch.children.clone();
+                    let (last, value) = go(children.last().unwrap(), h - 1);
+                    match last {
+                        Some(last) => *children.last_mut().unwrap() = last,
+                        None => {
+                            children.pop();
+                        }
+                    }
+                    (
+                        (!children.is_empty()).then(|| Node::branch(children, h)),
+                        value,
+                    )
+                }
+            }
+        }
+        let (mut root, value) = go(self.root.as_ref()?, self.height);
+        let mut height = self.height;
+        // drop roots left with one child
+        while let Some(Node::Branch(branch)) = &root {
+            if branch.children.len() > 1 {
+                break;
+            }
+            root = Some(branch.children[0].clone());
+            height -= 1;
+        }
+        Some((Self { root, height }, value))
+    }
+
+    // self followed by other, in O(log n) new nodes
+    pub fn concat(&self, other: &Self) -> Self {
+        let (Some(left), Some(right)) = (&self.root, &other.root) else {
+            return if self.is_empty() { other } else { self }.clone();
+        };
+        let mut nodes = merge(left, self.height, right, other.height);
+        let mut height = self.height.max(other.height);
+        if nodes.len() > 1 {
+            height += 1;
+            nodes = pack(nodes, height, true);
+        }
+        Self {
+            root: nodes.pop(),
+            height,
+        }
+    }
+}
+
+// Merges the right edge of left, of height hl, with the left edge of right
+// into one or two nodes of the larger height.
+fn merge<T: Clone>(left: &Node<T>, hl: u32, right: &Node<T>, hr: u32) -> Vec<Node<T>> {
+    if let (Node::Leaf(a), Node::Leaf(b)) = (left, right) {
+        return pack_leaves(a.iter().chain(b.iter()).cloned().collect(), false);
+    }
+    let h = hl.max(hr);
+    let mut children = Vec::with_capacity(2 * B);
+    if hl > hr {
+        let (last, init) = left.children().split_last().unwrap();
+        children.extend_from_slice(init);
+        children.extend(merge(last, hl - 1, right, hr));
+    } else if hl < hr {
+        let (first, tail) = right.children().split_first().unwrap();
+        children.extend(merge(left, hl, first, hr - 1));
+        children.extend_from_slice(tail);
+    } else {
+        let (last, init) = left.children().split_last().unwrap();
+        let (first, tail) = right.children().split_first().unwrap();
+        children.extend_from_slice(init);
+        children.extend(merge(last, h - 1, first, h - 1));
+        children.extend_from_slice(tail);
+    }
+    pack(children, h, false)
+}
+
+impl<T> FromIterator<T> for PVec<T> {
+    // bottom up, every node full but the last on each level
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut nodes = pack_leaves(iter.into_iter().collect(), true);
+        let mut height = 0;
+        while nodes.len() > 1 {
+            height += 1;
+            nodes = pack(nodes, height, true);
+        }
+        Self {
+            root: nodes.pop(),
+            height,
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<slice::Iter<'a, Node<T>>>,
+    leaf: slice::Iter<'a, T>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(item) = self.leaf.next() {
+                self.remaining -= 1;
+                return Some(item);
+            }
+            let top = self.stack.last_mut()?;
+            match top.next() {
+                Some(Node::Leaf(items)) => self.leaf = items.iter(),
+                Some(Node::Branch(branch)) => self.stack.push(branch.children.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a PVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PVec<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,561 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+
+// Left-right planarity test (Brandes, "The Left-Right Planarity Test").
+// The graph is read as undirected, self loops and parallel edges are
+// ignored since they never affect planarity. All dfs passes are iterative.
+
+#[derive(Debug, Clone)]
+pub struct Embedding<T> {
+    // neighbors of every vertex in clockwise order
+    pub rotation: HashMap<T, Vec<T>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KuratowskiKind {
+    K5,
+    K33,
+}
+
+// a subdivision of K5 or K3,3 contained in the graph
+#[derive(Debug, Clone)]
+pub struct Kuratowski<T> {
+    pub kind: KuratowskiKind,
+    pub edges: Vec<(T, T)>,
+}
+
+pub fn is_planar<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Result<Embedding<T>, Kuratowski<T>> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let mut edges = Vec::new();
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        for &(u, _) in nbrs.iter() {
+            if v != u {
+                edges.push((v.min(u), v.max(u)));
+            }
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+
+    if let Some(rotation) = LrPlanarity::new(n, &edges).run(true) {
+        let rotation = rotation
+            .into_iter()
+            .enumerate()
+            .map(|(v, nbrs)| {
+                let nbrs = nbrs.into_iter().map(|u| g.keys[u].clone()).collect();
+                (g.keys[v].clone(), nbrs)
+            })
+            .collect();
+        return Ok(Embedding { rotation });
+    }
+
+    // drop every edge that is not needed for non-planarity, what stays is
+    // a minimal non-planar subgraph, i.e. a Kuratowski subdivision
+    let mut keep = edges;
+    let mut i = 0;
+    while i < keep.len() {
+        let e = keep.remove(i);
+        if LrPlanarity::new(n, &keep).run(false).is_some() {
+            keep.insert(i, e);
+            i += 1;
+        }
+    }
+    let mut degree = vec![0; n];
+    for &(u, v) in keep.iter() {
+        degree[u] += 1;
+        degree[v] += 1;
+    }
+    let kind = if degree.contains(&4) {
+        KuratowskiKind::K5
+    } else {
+        KuratowskiKind::K33
+    };
+    let edges = keep
+        .into_iter()
+        .map(|(u, v)| (g.keys[u].clone(), g.keys[v].clone()))
+        .collect();
+    Err(Kuratowski { kind, edges })
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Interval {
+    low: Option<usize>,
+    high: Option<usize>,
+}
+
+impl Interval {
+    fn is_empty(&self) -> bool {
+        self.low.is_none() && self.high.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ConflictPair {
+    left: Interval,
+    right: Interval,
+}
+
+impl ConflictPair {
+    fn swap(&mut self) {
+        std::mem::swap(&mut self.left, &mut self.right);
+    }
+}
+
+const NONE: usize = usize::MAX;
+
+// edge ids index `edges`; after orientation edge e goes src[e] -> dst[e]
+struct LrPlanarity {
+    n: usize,
+    adj: Vec<Vec<(usize, usize)>>,
+    src: Vec<usize>,
+    dst: Vec<usize>,
+    oriented: Vec<bool>,
+    out: Vec<Vec<usize>>,
+    height: Vec<usize>,
+    parent_edge: Vec<usize>,
+    lowpt: Vec<usize>,
+    lowpt2: Vec<usize>,
+    nesting_depth: Vec<i64>,
+    lowpt_edge: Vec<usize>,
+    reference: Vec<usize>,
+    side: Vec<i64>,
+    stack_bottom: Vec<usize>,
+    stack: Vec<ConflictPair>,
+    roots: Vec<usize>,
+}
+
+impl LrPlanarity {
+    fn new(n: usize, edges: &[(usize, usize)]) -> Self {
+        let m = edges.len();
+        let mut adj = vec![Vec::new(); n];
+        for (id, &(u, v)) in edges.iter().enumerate() {
+            adj[u].push((v, id));
+            adj[v].push((u, id));
+        }
+        Self {
+            n,
+            adj,
+            src: vec![NONE; m],
+            dst: vec![NONE; m],
+            oriented: vec![false; m],
+            out: vec![Vec::new(); n],
+            height: vec![NONE; n],
+            parent_edge: vec![NONE; n],
+            lowpt: vec![0; m],
+            lowpt2: vec![0; m],
+            nesting_depth: vec![0; m],
+            lowpt_edge: vec![NONE; m],
+            reference: vec![NONE; m],
+            side: vec![1; m],
+            stack_bottom: vec![0; m],
+            stack: Vec::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    fn run(mut self, embed: bool) -> Option<Vec<Vec<usize>>> {
+        let m = self.src.len();
+        if self.n > 2 && m > 3 * self.n - 6 {
+            return None;
+        }
+        for v in 0..self.n {
+            if self.height[v] == NONE {
+                self.height[v] = 0;
+                self.roots.push(v);
+                self.orient(v);
+            }
+        }
+        self.sort_out_edges();
+        for i in 0..self.roots.len() {
+            if !self.test(self.roots[i]) {
+                return None;
+            }
+        }
+        if !embed {
+            return Some(Vec::new());
+        }
+        for e in 0..m {
+            self.nesting_depth[e] *= self.sign(e);
+        }
+        self.sort_out_edges();
+        Some(self.embed())
+    }
+
+    fn sort_out_edges(&mut self) {
+        for v in 0..self.n {
+            let mut out = std::mem::take(&mut self.out[v]);
+            out.sort_by_key(|&e| self.nesting_depth[e]);
+            self.out[v] = out;
+        }
+    }
+
+    fn orient(&mut self, root: usize) {
+        let mut next = vec![0; self.n];
+        // set while the child reached through adj[v][next[v]] is on the stack
+        let mut descended = vec![false; self.n];
+        let mut dfs = vec![root];
+        while let Some(&v) = dfs.last() {
+            if descended[v] {
+                descended[v] = false;
+                let (_, e) = self.adj[v][next[v]];
+                self.after_orient(v, e);
+                next[v] += 1;
+                continue;
+            }
+            let Some(&(w, e)) = self.adj[v].get(next[v]) else {
+                dfs.pop();
+                continue;
+            };
+            if self.oriented[e] {
+                next[v] += 1;
+                continue;
+            }
+            self.oriented[e] = true;
+            self.src[e] = v;
+            self.dst[e] = w;
+            self.out[v].push(e);
+            self.lowpt[e] = self.height[v];
+            self.lowpt2[e] = self.height[v];
+            if self.height[w] == NONE {
+                // tree edge
+                self.parent_edge[w] = e;
+                self.height[w] = self.height[v] + 1;
+                descended[v] = true;
+                dfs.push(w);
+                continue;
+            }
+            // back edge
+            self.lowpt[e] = self.height[w];
+            self.after_orient(v, e);
+            next[v] += 1;
+        }
+    }
+
+    fn after_orient(&mut self, v: usize, e: usize) {
+        self.nesting_depth[e] = 2 * self.lowpt[e] as i64;
+        if self.lowpt2[e] < self.height[v] {
+            // chordal edge
+            self.nesting_depth[e] += 1;
+        }
+        let p = self.parent_edge[v];
+        if p == NONE {
+            return;
+        }
+        if self.lowpt[e] < self.lowpt[p] {
+            self.lowpt2[p] = self.lowpt[p].min(self.lowpt2[e]);
+            self.lowpt[p] = self.lowpt[e];
+        } else if self.lowpt[e] > self.lowpt[p] {
+            self.lowpt2[p] = self.lowpt2[p].min(self.lowpt[e]);
+        } else {
+            self.lowpt2[p] = self.lowpt2[p].min(self.lowpt2[e]);
+        }
+    }
+
+    fn test(&mut self, root: usize) -> bool {
+        let mut next = vec![0; self.n];
+        let mut descended = vec![false; self.n];
+        let mut dfs = vec![root];
+        while let Some(&v) = dfs.last() {
+            if !descended[v] {
+                if let Some(&e) = self.out[v].get(next[v]) {
+                    self.stack_bottom[e] = self.stack.len();
+                    let w = self.dst[e];
+                    if self.parent_edge[w] == e {
+                        descended[v] = true;
+                        dfs.push(w);
+                        continue;
+                    }
+                    self.lowpt_edge[e] = e;
+                    self.stack.push(ConflictPair {
+                        left: Interval::default(),
+                        right: Interval {
+                            low: Some(e),
+                            high: Some(e),
+                        },
+                    });
+                } else {
+                    dfs.pop();
+                    let p = self.parent_edge[v];
+                    if p != NONE {
+                        self.remove_back_edges(p);
+                    }
+                    continue;
+                }
+            }
+            descended[v] = false;
+            let e = self.out[v][next[v]];
+            // integrate the return edges of e
+            if self.lowpt[e] < self.height[v] {
+                let p = self.parent_edge[v];
+                if next[v] == 0 {
+                    self.lowpt_edge[p] = self.lowpt_edge[e];
+                } else if !self.add_constraints(e, p) {
+                    return false;
+                }
+            }
+            next[v] += 1;
+        }
+        true
+    }
+
+    fn conflicting(&self, i: &Interval, b: usize) -> bool {
+        match i.high {
+            Some(h) => self.lowpt[h] > self.lowpt[b],
+            None => false,
+        }
+    }
+
+    fn lowest(&self, p: &ConflictPair) -> usize {
+        match (p.left.low, p.right.low) {
+            (None, Some(r)) => self.lowpt[r],
+            (Some(l), None) => self.lowpt[l],
+            (Some(l), Some(r)) => self.lowpt[l].min(self.lowpt[r]),
+            (None, None) => NONE,
+        }
+    }
+
+    fn add_constraints(&mut self, ei: usize, e: usize) -> bool {
+        let mut p = ConflictPair::default();
+        // merge the return edges of ei into p.right
+        loop {
+            let mut q = self.stack.pop().unwrap();
+            if !q.left.is_empty() {
+                q.swap();
+            }
+            if !q.left.is_empty() {
+                return false;
+            }
+            let q_low = q.right.low.unwrap();
+            if self.lowpt[q_low] > self.lowpt[e] {
+                match p.right.low {
+                    None => p.right = q.right,
+                    Some(low) => self.reference[low] = q.right.high.unwrap_or(NONE),
+                }
+                p.right.low = q.right.low;
+            } else {
+                self.reference[q_low] = self.lowpt_edge[e];
+            }
+            if self.stack.len() == self.stack_bottom[ei] {
+                break;
+            }
+        }
+        // merge the conflicting return edges of the earlier siblings into p.left
+        while let Some(top) = self.stack.last() {
+            if !self.conflicting(&top.left, ei) && !self.conflicting(&top.right, ei) {
+                break;
+            }
+            let mut q = self.stack.pop().unwrap();
+            if self.conflicting(&q.right, ei) {
+                q.swap();
+            }
+            if self.conflicting(&q.right, ei) {
+                return false;
+            }
+            // merge the interval below lowpt(ei) into p.right
+            if let Some(low) = p.right.low {
+                self.reference[low] = q.right.high.unwrap_or(NONE);
+            }
+            if q.right.low.is_some() {
+                p.right.low = q.right.low;
+            }
+            match p.left.low {
+                None => p.left = q.left,
+                Some(low) => self.reference[low] = q.left.high.unwrap_or(NONE),
+            }
+            p.left.low = q.left.low;
+        }
+        if !(p.left.is_empty() && p.right.is_empty()) {
+            self.stack.push(p);
+        }
+        true
+    }
+
+    fn remove_back_edges(&mut self, e: usize) {
+        let u = self.src[e];
+        // drop whole conflict pairs that end at u
+        while let Some(top) = self.stack.last() {
+            if self.lowest(top) != self.height[u] {
+                break;
+            }
+            let p = self.stack.pop().unwrap();
+            if let Some(low) = p.left.low {
+                self.side[low] = -1;
+            }
+        }
+        if let Some(mut p) = self.stack.pop() {
+            // trim the left interval
+            while let Some(h) = p.left.high {
+                if self.dst[h] != u {
+                    break;
+                }
+                p.left.high = some(self.reference[h]);
+            }
+            if p.left.high.is_none() {
+                if let Some(low) = p.left.low {
+                    self.reference[low] = p.right.low.unwrap_or(NONE);
+                    self.side[low] = -1;
+                    p.left.low = None;
+                }
+            }
+            // trim the right interval
+            while let Some(h) = p.right.high {
+                if self.dst[h] != u {
+                    break;
+                }
+                p.right.high = some(self.reference[h]);
+            }
+            if p.right.high.is_none() {
+                if let Some(low) = p.right.low {
+                    self.reference[low] = p.left.low.unwrap_or(NONE);
+                    self.side[low] = -1;
+                    p.right.low = None;
+                }
+            }
+            self.stack.push(p);
+        }
+        // e sits on the side of its highest return edge
+        if self.lowpt[e] < self.height[u] {
+            let top = self.stack.last().unwrap();
+            let (hl, hr) = (top.left.high, top.right.high);
+            self.reference[e] = match (hl, hr) {
+                (Some(l), Some(r)) if self.lowpt[l] > self.lowpt[r] => l,
+                (Some(l), None) => l,
+                (_, r) => r.unwrap_or(NONE),
+            };
+        }
+    }
+
+    fn sign(&mut self, e: usize) -> i64 {
+        let mut chain = vec![e];
+        while self.reference[*chain.last().unwrap()] != NONE {
+            let next = self.reference[*chain.last().unwrap()];
+            chain.push(next);
+        }
+        for i in (0..chain.len() - 1).rev() {
+            self.side[chain[i]] *= self.side[chain[i + 1]];
+            self.reference[chain[i]] = NONE;
+        }
+        self.side[e]
+    }
+
+    fn embed(&mut self) -> Vec<Vec<usize>> {
+        let mut rot = Rotation::new(self.n);
+        for v in 0..self.n {
+            let mut prev = NONE;
+            for i in 0..self.out[v].len() {
+                let w = self.dst[self.out[v][i]];
+                rot.add_cw(v, w, prev);
+                prev = w;
+            }
+        }
+        let mut left_ref = vec![NONE; self.n];
+        let mut right_ref = vec![NONE; self.n];
+        for i in 0..self.roots.len() {
+            let mut next = vec![0; self.n];
+            let mut dfs = vec![self.roots[i]];
+            while let Some(&v) = dfs.last() {
+                let Some(&e) = self.out[v].get(next[v]) else {
+                    dfs.pop();
+                    continue;
+                };
+                next[v] += 1;
+                let w = self.dst[e];
+                if self.parent_edge[w] == e {
+                    rot.add_first(w, v);
+                    left_ref[v] = w;
+                    right_ref[v] = w;
+                    dfs.push(w);
+                } else if self.side[e] == 1 {
+                    rot.add_cw(w, v, right_ref[w]);
+                } else {
+                    rot.add_ccw(w, v, left_ref[w]);
+                    left_ref[w] = v;
+                }
+            }
+        }
+        rot.into_lists()
+    }
+}
+
+fn some(e: usize) -> Option<usize> {
+    if e == NONE {
+        None
+    } else {
+        Some(e)
+    }
+}
+
+// per vertex circular list of neighbors, keyed by half edge (v, w)
+struct Rotation {
+    first: Vec<usize>,
+    cw: HashMap<(usize, usize), usize>,
+    ccw: HashMap<(usize, usize), usize>,
+}
+
+impl Rotation {
+    fn new(n: usize) -> Self {
+        Self {
+            first: vec![NONE; n],
+            cw: HashMap::new(),
+            ccw: HashMap::new(),
+        }
+    }
+
+    // insert w right after `reference` clockwise around v
+    fn add_cw(&mut self, v: usize, w: usize, reference: usize) {
+        if reference == NONE {
+            self.first[v] = w;
+            self.cw.insert((v, w), w);
+            self.ccw.insert((v, w), w);
+            return;
+        }
+        let next = self.cw[&(v, reference)];
+        self.cw.insert((v, reference), w);
+        self.ccw.insert((v, w), reference);
+        self.cw.insert((v, w), next);
+        self.ccw.insert((v, next), w);
+    }
+
+    // insert w right before `reference` clockwise around v
+    fn add_ccw(&mut self, v: usize, w: usize, reference: usize) {
+        if reference == NONE {
+            self.add_cw(v, w, NONE);
+            return;
+        }
+        let prev = self.ccw[&(v, reference)];
+        self.add_cw(v, w, prev);
+        if self.first[v] == reference {
+            self.first[v] = w;
+        }
+    }
+
+    fn add_first(&mut self, v: usize, w: usize) {
+        let first = self.first[v];
+        self.add_ccw(v, w, first);
+    }
+
+    fn into_lists(self) -> Vec<Vec<usize>> {
+        (0..self.first.len())
+            .map(|v| {
+                let mut list = Vec::new();
+                let start = self.first[v];
+                if start == NONE {
+                    return list;
+                }
+                let mut w = start;
+                loop {
+                    list.push(w);
+                    w = self.cw[&(v, w)];
+                    if w == start {
+                        break;
+                    }
+                }
+                list
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,190 @@
+use std::{fmt, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::shortest_path::dijkstra_indexed;
+
+// Route inspection on an undirected weighted graph (every edge stored in
+// both directions). Odd degree vertices are paired up by an exact minimum
+// weight perfect matching over their shortest path distances, the matched
+// paths are duplicated and an Eulerian circuit of the result is returned.
+
+// the matching is a bitmask dp, 2^k states for k odd vertices
+pub const MAX_ODD_VERTICES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostmanError {
+    Asymmetric,
+    NegativeWeight,
+    Disconnected,
+    TooManyOddVertices(usize),
+}
+
+impl fmt::Display for PostmanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PostmanError::Asymmetric => write!(f, "edge without its reverse edge"),
+            PostmanError::NegativeWeight => write!(f, "negative edge weight"),
+            PostmanError::Disconnected => write!(f, "edges are not connected"),
+            PostmanError::TooManyOddVertices(k) => write!(
+                f,
+                "{k} odd degree vertices, at most {MAX_ODD_VERTICES} are supported"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PostmanError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route<T> {
+    pub cost: i64,
+    // closed walk, the first vertex is repeated at the end
+    pub walk: Vec<T>,
+}
+
+pub fn chinese_postman<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Result<Route<T>, PostmanError> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+
+    // undirected edge list, each edge taken from its lower endpoint
+    let mut edges: Vec<(usize, usize, i32)> = Vec::new();
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        for &(u, wt) in nbrs.iter() {
+            if wt < 0 {
+                return Err(PostmanError::NegativeWeight);
+            }
+            let forward = nbrs.iter().filter(|&&e| e == (u, wt)).count();
+            let backward = g.adj[u].iter().filter(|&&e| e == (v, wt)).count();
+            if forward != backward {
+                return Err(PostmanError::Asymmetric);
+            }
+            if v <= u {
+                edges.push((v, u, wt));
+            }
+        }
+    }
+    if edges.is_empty() {
+        return Ok(Route {
+            cost: 0,
+            walk: Vec::new(),
+        });
+    }
+
+    let mut degree = vec![0usize; n];
+    for &(a, b, _) in edges.iter() {
+        degree[a] += 1;
+        degree[b] += 1;
+    }
+    let odd: Vec<usize> = (0..n).filter(|&v| degree[v] % 2 == 1).collect();
+    if odd.len() > MAX_ODD_VERTICES {
+        return Err(PostmanError::TooManyOddVertices(odd.len()));
+    }
+
+    let paths: Vec<_> = odd.iter().map(|&s| dijkstra_indexed(&g.adj, s)).collect();
+    let k = odd.len();
+    let mut dist = vec![vec![0i64; k]; k];
+    for i in 0..k {
+        for j in 0..k {
+            dist[i][j] = paths[i].0[odd[j]].ok_or(PostmanError::Disconnected)?;
+        }
+    }
+
+    // extra copies of edges along the matched shortest paths
+    let mut all = edges.clone();
+    for (i, j) in min_matching(&dist) {
+        let prev = &paths[i].1;
+        let mut cur = odd[j];
+        while cur != odd[i] {
+            let p = prev[cur].unwrap();
+            let wt = g.adj[p]
+                .iter()
+                .filter(|&&(u, _)| u == cur)
+                .map(|&(_, wt)| wt)
+                .min()
+                .unwrap();
+            all.push((p, cur, wt));
+            cur = p;
+        }
+    }
+
+    let cost = all.iter().map(|&(_, _, wt)| wt as i64).sum();
+    let walk = euler_circuit(n, &all).ok_or(PostmanError::Disconnected)?;
+    Ok(Route {
+        cost,
+        walk: walk.into_iter().map(|v| g.keys[v].clone()).collect(),
+    })
+}
+
+// pairs (i, j) of a minimum weight perfect matching, k is even
+fn min_matching(dist: &[Vec<i64>]) -> Vec<(usize, usize)> {
+    let k = dist.len();
+    let full = (1usize << k) - 1;
+    // best[mask] is the cheapest matching of the vertices in mask; the
+    // lowest unmatched vertex is always paired first so each state is
+    // reached by only k transitions
+    let mut best = vec![i64::MAX; 1 << k];
+    let mut choice = vec![(0, 0); 1 << k];
+    best[0] = 0;
+    for mask in 0..full {
+        if best[mask] == i64::MAX {
+            continue;
+        }
+        let i = (!mask).trailing_zeros() as usize;
+        for (j, &d) in dist[i].iter().enumerate().skip(i + 1) {
+            if mask & (1 << j) != 0 {
+                continue;
+            }
+            let next = mask | (1 << i) | (1 << j);
+            let cost = best[mask] + d;
+            if cost < best[next] {
+                best[next] = cost;
+                choice[next] = (i, j);
+            }
+        }
+    }
+    let mut pairs = Vec::new();
+    let mut mask = full;
+    while mask != 0 {
+        let (i, j) = choice[mask];
+        pairs.push((i, j));
+        mask &= !((1 << i) | (1 << j));
+    }
+    pairs
+}
+
+// Hierholzer's algorithm, None if the edges don't form one component
+fn euler_circuit(n: usize, edges: &[(usize, usize, i32)]) -> Option<Vec<usize>> {
+    let mut adj = vec![Vec::new(); n];
+    for (id, &(a, b, _)) in edges.iter().enumerate() {
+        adj[a].push((b, id));
+        if a != b {
+            adj[b].push((a, id));
+        }
+    }
+    let mut used = vec![false; edges.len()];
+    let mut next = vec![0; n];
+    let mut stack = vec![edges[0].0];
+    let mut circuit = Vec::with_capacity(edges.len() + 1);
+    while let Some(&v) = stack.last() {
+        while next[v] < adj[v].len() && used[adj[v][next[v]].1] {
+            next[v] += 1;
+        }
+        match adj[v].get(next[v]) {
+            Some(&(u, id)) => {
+                used[id] = true;
+                stack.push(u);
+            }
+            None => {
+                circuit.push(v);
+                stack.pop();
+            }
+        }
+    }
+    if circuit.len() != edges.len() + 1 {
+        return None;
+    }
+    circuit.reverse();
+    Some(circuit)
+}
@@ -0,0 +1,49 @@
+use std::cmp::Ordering;
+
+// Common interface of the heaps in this crate. Every queue is ordered by a
+// Compare value and pops the item that compares Less than all the others,
+// so MinOrder gives a min-heap and MaxOrder a max-heap.
+pub trait PriorityQueue<T> {
+    fn push(&mut self, item: T);
+    fn pop(&mut self) -> Option<T>;
+    fn peek(&self) -> Option<&T>;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub trait Compare<T> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+
+    // a is popped before b
+    fn before(&self, a: &T, b: &T) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinOrder;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxOrder;
+
+impl<T: Ord> Compare<T> for MinOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T: Ord> Compare<T> for MaxOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+// e.g. BinaryHeap::with_compare(|a: &(i64, u32), b: &(i64, u32)| a.0.cmp(&b.0))
+impl<T, F: Fn(&T, &T) -> Ordering> Compare<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt;
+
+use crate::binary_heap::BinaryHeap;
+use crate::geometry::{dist2, Rect};
+
+// Region tree over a fixed box in R^D: a cell holds up to leaf_capacity
+// points and then splits at its center into 2^D equal cells, four for the
+// quadtree and eight for the octree. Points are identified by their index
+// in insertion order. Cells stop splitting at MAX_DEPTH, so a pile of equal
+// points just fills one leaf.
+#[derive(Debug, Clone)]
+pub struct Orthtree<const D: usize> {
+    points: Vec<[f64; D]>,
+    cells: Vec<Cell<D>>,
+    leaf_capacity: usize,
+}
+
+pub type Quadtree = Orthtree<2>;
+pub type Octree = Orthtree<3>;
+
+#[derive(Debug, Clone)]
+struct Cell<const D: usize> {
+    rect: Rect<D>,
+    depth: usize,
+    // point ids while a leaf
+    items: Vec<usize>,
+    // 2^D cells once split, bit i of the index set for the upper half on
+    // axis i
+    children: Vec<usize>,
+}
+
+const DEFAULT_LEAF_CAPACITY: usize = 8;
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds;
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "point lies outside the tree's bounds")
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+impl<const D: usize> Orthtree<D> {
+    pub fn new(bounds: Rect<D>) -> Self {
+        Self::with_leaf_capacity(bounds, DEFAULT_LEAF_CAPACITY)
+    }
+
+    pub fn with_leaf_capacity(bounds: Rect<D>, leaf_capacity: usize) -> Self {
+        assert!(leaf_capacity >= 1, "leaves need room for a point");
+        Self {
+            points: Vec::new(),
+            cells: vec![Cell {
+                rect: bounds,
+                depth: 0,
+                items: Vec::new(),
+                children: Vec::new(),
+            }],
+            leaf_capacity,
+        }
+    }
+
+    pub fn bounds(&self) -> &Rect<D> {
+        &self.cells[0].rect
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, id: usize) -> &[f64; D] {
+        &self.points[id]
+    }
+
+    pub fn insert(&mut self, point: [f64; D]) -> Result<usize, OutOfBounds> {
+        if !self.bounds().contains_point(&point) {
+            return Err(OutOfBounds);
+        }
+        let id = self.points.len();
+        self.points.push(point);
+        let leaf = self.leaf_of(&point);
+        self.cells[leaf].items.push(id);
+        let cell = &self.cells[leaf];
+        if cell.items.len() > self.leaf_capacity && cell.depth < MAX_DEPTH {
+            self.split(leaf);
+        }
+        Ok(id)
+    }
+
+    // ids of the points equal to point
+    pub fn query_point(&self, point: &[f64; D]) -> Vec<usize> {
+        if !self.bounds().contains_point(point) {
+            return Vec::new();
+        }
+        let leaf = self.leaf_of(point);
+        self.cells[leaf]
+            .items
+            .iter()
+            .copied()
+            .filter(|&id| self.points[id] == *point)
+            .collect()
+    }
+
+    // ids of the points inside rect, in no particular order
+    pub fn query_rect(&self, rect: &Rect<D>) -> Vec<usize> {
+        self.search(|cell| cell.intersects(rect), |p| rect.contains_point(p))
+    }
+
+    // ids of the points at distance at most radius from center
+    pub fn within_radius(&self, center: &[f64; D], radius: f64) -> Vec<usize> {
+        let r2 = radius * radius;
+        self.search(
+            |cell| cell.min_dist2(center) <= r2,
+            |p| dist2(p, center) <= r2,
+        )
+    }
+
+    pub fn nearest(&self, query: &[f64; D]) -> Option<(usize, f64)> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    // the k closest points, nearest first, with their distances
+    pub fn k_nearest(&self, query: &[f64; D], k: usize) -> Vec<(usize, f64)> {
+        // best first over cells and points by (squared) distance; a point
+        // popped is closer than everything still queued
+        let mut queue =
+            BinaryHeap::with_compare(|a: &(f64, bool, usize), b: &(f64, bool, usize)| {
+                a.0.total_cmp(&b.0)
+            });
+        let mut found = Vec::new();
+        queue.push((self.cells[0].rect.min_dist2(query), false, 0));
+        while let Some((d, is_point, i)) = queue.pop() {
+            if found.len() == k {
+                break;
+            }
+            if is_point {
+                found.push((i, d.sqrt()));
+                continue;
+            }
+            let cell = &self.cells[i];
+            for &id in cell.items.iter() {
+                queue.push((dist2(&self.points[id], query), true, id));
+            }
+            for &c in cell.children.iter() {
+                queue.push((self.cells[c].rect.min_dist2(query), false, c));
+            }
+        }
+        found
+    }
+
+    fn search(
+        &self,
+        enter: impl Fn(&Rect<D>) -> bool,
+        keep: impl Fn(&[f64; D]) -> bool,
+    ) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            let cell = &self.cells[i];
+            if !enter(&cell.rect) {
+                continue;
+            }
+            found.extend(cell.items.iter().filter(|&&id| keep(&self.points[id])));
+            stack.extend(cell.children.iter().copied());
+        }
+        found
+    }
+
+    fn leaf_of(&self, point: &[f64; D]) -> usize {
+        let mut i = 0;
+        while !self.cells[i].children.is_empty() {
+            let cell = &self.cells[i];
+            i = cell.children[child_index(&cell.rect, point)];
+        }
+        i
+    }
+
+    fn split(&mut self, leaf: usize) {
+        let Cell { rect, depth, .. } = self.cells[leaf];
+        let center = rect.center();
+        let first = self.cells.len();
+        for index in 0..1usize << D {
+            let upper = |axis: usize| index >> axis & 1 == 1;
+            self.cells.push(Cell {
+                rect: Rect {
+                    min: std::array::from_fn(|a| if upper(a) { center[a] } else { rect.min[a] }),
+                    max: std::array::from_fn(|a| if upper(a) { rect.max[a] } else { center[a] }),
+                },
+                depth: depth + 1,
+                items: Vec::new(),
+                children: Vec::new(),
+            });
+        }
+        let items = std::mem::take(&mut self.cells[leaf].items);
+        self.cells[leaf].children = (first..first + (1 << D)).collect();
+        for id in items {
+            let c = first + child_index(&rect, &self.points[id]);
+            self.cells[c].items.push(id);
+        }
+        // everything may have landed in the same child
+        for c in first..first + (1 << D) {
+            if self.cells[c].items.len() > self.leaf_capacity && depth + 1 < MAX_DEPTH {
+                self.split(c);
+            }
+        }
+    }
+}
+
+fn child_index<const D: usize>(rect: &Rect<D>, point: &[f64; D]) -> usize {
+    let center = rect.center();
+    (0..D)
+        .filter(|&a| point[a] >= center[a])
+        .map(|a| 1 << a)
+        .sum()
+}
@@ -0,0 +1,357 @@
+use std::cmp::Ordering;
+
+use crate::binary_heap::BinaryHeap;
+use crate::geometry::Rect;
+
+// R-tree over boxes in R^D. Every node keeps the bounding box of its
+// entries, at most max_entries of them; an overfull node is split with
+// Guttman's quadratic method, seeded with the two entries that would waste
+// the most volume in one box. Bulk loading packs sort-tile-recursive
+// instead: full nodes that barely overlap. Boxes are identified by their
+// index in insertion order, bulk loaded ones first.
+#[derive(Debug, Clone)]
+pub struct RTree<const D: usize> {
+    rects: Vec<Rect<D>>,
+    nodes: Vec<Node<D>>,
+    root: usize,
+    max_entries: usize,
+    min_entries: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<const D: usize> {
+    rect: Rect<D>,
+    leaf: bool,
+    // box ids in a leaf, nodes otherwise
+    entries: Vec<usize>,
+}
+
+const DEFAULT_MAX_ENTRIES: usize = 16;
+
+impl<const D: usize> Default for RTree<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const D: usize> RTree<D> {
+    pub fn new() -> Self {
+        Self::with_max_entries(DEFAULT_MAX_ENTRIES)
+    }
+
+    // splits leave every node at least 40% full
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        assert!(max_entries >= 2, "r-tree nodes need room for two entries");
+        Self {
+            rects: Vec::new(),
+            nodes: vec![Node {
+                rect: Rect::point([0.0; D]),
+                leaf: true,
+                entries: Vec::new(),
+            }],
+            root: 0,
+            max_entries,
+            min_entries: (max_entries * 2 / 5).max(1),
+        }
+    }
+
+    // sort-tile-recursive packing of rects, O(n log n)
+    pub fn bulk_load(rects: Vec<Rect<D>>) -> Self {
+        let mut tree = Self::new();
+        if rects.is_empty() {
+            return tree;
+        }
+        tree.nodes.clear();
+        tree.rects = rects;
+        let mut level: Vec<usize> = (0..tree.rects.len()).collect();
+        let mut leaf = true;
+        loop {
+            let mut items: Vec<([f64; D], usize)> = level
+                .iter()
+                .map(|&e| (tree.entry_rect(leaf, e).center(), e))
+                .collect();
+            let mut groups = Vec::new();
+            tile(&mut items, 0, tree.max_entries, &mut groups);
+            level = groups
+                .into_iter()
+                .map(|entries| tree.push_node(leaf, entries))
+                .collect();
+            leaf = false;
+            if level.len() == 1 {
+                break;
+            }
+        }
+        tree.root = level[0];
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn rect(&self, id: usize) -> &Rect<D> {
+        &self.rects[id]
+    }
+
+    // levels of nodes, 1 for a lone leaf
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut cur = self.root;
+        while !self.nodes[cur].leaf {
+            cur = self.nodes[cur].entries[0];
+            height += 1;
+        }
+        height
+    }
+
+    // adds rect and returns its id
+    pub fn insert(&mut self, rect: Rect<D>) -> usize {
+        let id = self.rects.len();
+        self.rects.push(rect);
+        let mut path = Vec::new();
+        let mut cur = self.root;
+        while !self.nodes[cur].leaf {
+            path.push(cur);
+            cur = self.choose_child(cur, &rect);
+        }
+        self.nodes[cur].entries.push(id);
+        // refit the boxes on the way up, splitting what overflowed
+        let mut split = self.refit(cur);
+        while let Some(parent) = path.pop() {
+            if let Some(sibling) = split {
+                self.nodes[parent].entries.push(sibling);
+            }
+            split = self.refit(parent);
+        }
+        if let Some(sibling) = split {
+            self.root = self.push_node(false, vec![self.root, sibling]);
+        }
+        id
+    }
+
+    // ids of the boxes that share a point with window, in no particular
+    // order
+    pub fn query(&self, window: &Rect<D>) -> Vec<usize> {
+        self.search(|rect| rect.intersects(window))
+    }
+
+    // ids of the boxes inside window
+    pub fn query_contained(&self, window: &Rect<D>) -> Vec<usize> {
+        let mut found = self.query(window);
+        found.retain(|&id| window.contains(&self.rects[id]));
+        found
+    }
+
+    // ids of the boxes that contain point
+    pub fn query_point(&self, point: &[f64; D]) -> Vec<usize> {
+        self.search(|rect| rect.contains_point(point))
+    }
+
+    // closest box to query and its distance, 0 from inside
+    pub fn nearest(&self, query: &[f64; D]) -> Option<(usize, f64)> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    // the k closest boxes, nearest first, with their distances
+    pub fn k_nearest(&self, query: &[f64; D], k: usize) -> Vec<(usize, f64)> {
+        let mut found = Vec::new();
+        if self.is_empty() {
+            return found;
+        }
+        // best first over nodes and boxes by (squared) distance; a box
+        // popped is closer than everything still queued
+        let mut queue =
+            BinaryHeap::with_compare(|a: &(f64, bool, usize), b: &(f64, bool, usize)| {
+                a.0.total_cmp(&b.0)
+            });
+        queue.push((
+            self.nodes[self.root].rect.min_dist2(query),
+            false,
+            self.root,
+        ));
+        while let Some((d, is_rect, i)) = queue.pop() {
+            if found.len() == k {
+                break;
+            }
+            if is_rect {
+                found.push((i, d.sqrt()));
+                continue;
+            }
+            let node = &self.nodes[i];
+            for &e in node.entries.iter() {
+                let rect = self.entry_rect(node.leaf, e);
+                queue.push((rect.min_dist2(query), node.leaf, e));
+            }
+        }
+        found
+    }
+
+    // boxes of the subtrees that keep accepts, and of the matches
+    fn search(&self, keep: impl Fn(&Rect<D>) -> bool) -> Vec<usize> {
+        let mut found = Vec::new();
+        if self.is_empty() {
+            return found;
+        }
+        let mut stack = vec![self.root];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !keep(&node.rect) {
+                continue;
+            }
+            if node.leaf {
+                found.extend(node.entries.iter().filter(|&&id| keep(&self.rects[id])));
+            } else {
+                stack.extend(node.entries.iter().copied());
+            }
+        }
+        found
+    }
+
+    fn entry_rect(&self, leaf: bool, e: usize) -> &Rect<D> {
+        if leaf {
+            &self.rects[e]
+        } else {
+            &self.nodes[e].rect
+        }
+    }
+
+    fn bounding(&self, leaf: bool, entries: &[usize]) -> Rect<D> {
+        entries
+            .iter()
+            .map(|&e| *self.entry_rect(leaf, e))
+            .reduce(|a, b| a.union(&b))
+            .unwrap()
+    }
+
+    fn push_node(&mut self, leaf: bool, entries: Vec<usize>) -> usize {
+        let rect = self.bounding(leaf, &entries);
+        self.nodes.push(Node {
+            rect,
+            leaf,
+            entries,
+        });
+        self.nodes.len() - 1
+    }
+
+    // the child that grows least to take in rect, ties to the smaller one
+    fn choose_child(&self, node: usize, rect: &Rect<D>) -> usize {
+        let cost = |c: usize| {
+            let r = &self.nodes[c].rect;
+            (r.enlargement(rect), r.volume())
+        };
+        self.nodes[node]
+            .entries
+            .iter()
+            .copied()
+            .min_by(|&a, &b| {
+                let (ga, va) = cost(a);
+                let (gb, vb) = cost(b);
+                ga.total_cmp(&gb).then(va.total_cmp(&vb))
+            })
+            .unwrap()
+    }
+
+    // recomputes the box of node and splits it if it overflowed, returning
+    // the new sibling
+    fn refit(&mut self, node: usize) -> Option<usize> {
+        let leaf = self.nodes[node].leaf;
+        if self.nodes[node].entries.len() <= self.max_entries {
+            self.nodes[node].rect = self.bounding(leaf, &self.nodes[node].entries);
+            return None;
+        }
+        let entries = std::mem::take(&mut self.nodes[node].entries);
+        let (a, b) = self.quadratic_split(leaf, entries);
+        self.nodes[node].rect = self.bounding(leaf, &a);
+        self.nodes[node].entries = a;
+        Some(self.push_node(leaf, b))
+    }
+
+    fn quadratic_split(&self, leaf: bool, mut rest: Vec<usize>) -> (Vec<usize>, Vec<usize>) {
+        let rect = |e: usize| *self.entry_rect(leaf, e);
+        // seeds: the pair whose box wastes the most volume
+        let (mut s, mut t, mut waste) = (0, 1, f64::NEG_INFINITY);
+        for i in 0..rest.len() {
+            for j in i + 1..rest.len() {
+                let (a, b) = (rect(rest[i]), rect(rest[j]));
+                let w = a.union(&b).volume() - a.volume() - b.volume();
+                if w > waste {
+                    (s, t, waste) = (i, j, w);
+                }
+            }
+        }
+        // t > s, so removing t first leaves s in place
+        let seed_b = rest.swap_remove(t);
+        let seed_a = rest.swap_remove(s);
+        let mut groups = [vec![seed_a], vec![seed_b]];
+        let mut boxes = [rect(seed_a), rect(seed_b)];
+        while !rest.is_empty() {
+            // a group that needs everything left to reach the minimum gets it
+            if let Some(g) = (0..2).find(|&g| groups[g].len() + rest.len() <= self.min_entries) {
+                groups[g].append(&mut rest);
+                break;
+            }
+            // next: the entry with the strongest preference for one group
+            let grow = |e: usize| {
+                [
+                    boxes[0].enlargement(&rect(e)),
+                    boxes[1].enlargement(&rect(e)),
+                ]
+            };
+            let (i, _) = rest
+                .iter()
+                .enumerate()
+                .map(|(i, &e)| {
+                    let [d0, d1] = grow(e);
+                    (i, (d0 - d1).abs())
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            let e = rest.swap_remove(i);
+            let [d0, d1] = grow(e);
+            let g = match d0.total_cmp(&d1) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => match boxes[0].volume().total_cmp(&boxes[1].volume()) {
+                    Ordering::Less => 0,
+                    Ordering::Greater => 1,
+                    Ordering::Equal => usize::from(groups[1].len() < groups[0].len()),
+                },
+            };
+            groups[g].push(e);
+            boxes[g] = boxes[g].union(&rect(e));
+        }
+        let [a, b] = groups;
+        (a, b)
+    }
+}
+
+// Cuts items into groups of at most m: sorted by center on axis, split into
+// slabs of whole groups, and each slab tiled on the next axis.
+fn tile<const D: usize>(
+    items: &mut [([f64; D], usize)],
+    axis: usize,
+    m: usize,
+    groups: &mut Vec<Vec<usize>>,
+) {
+    items.sort_unstable_by(|a, b| a.0[axis].total_cmp(&b.0[axis]));
+    if axis + 1 >= D || items.len() <= m {
+        groups.extend(items.chunks(m).map(|c| c.iter().map(|&(_, e)| e).collect()));
+        return;
+    }
+    let leaves = items.len().div_ceil(m);
+    let slabs = (leaves as f64).powf(1.0 / (D - axis) as f64).ceil() as usize;
+    let per_slab = leaves.div_ceil(slabs) * m;
+    for slab in items.chunks_mut(per_slab) {
+        tile(slab, axis + 1, m, groups);
+    }
+}
+
+impl<const D: usize> FromIterator<Rect<D>> for RTree<D> {
+    fn from_iter<I: IntoIterator<Item = Rect<D>>>(iter: I) -> Self {
+        Self::bulk_load(iter.into_iter().collect())
+    }
+}
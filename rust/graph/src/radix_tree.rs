@@ -0,0 +1,335 @@
+use crate::trie::{Trie, ROOT};
+
+// Path-compressed trie: chains of nodes with a single child and no value
+// are merged into one edge labelled with the whole run of symbols, so the
+// tree has at most 2 * len + 1 nodes however long the keys are. Same API
+// as Trie.
+#[derive(Debug, Clone)]
+pub struct RadixTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    // symbols on the edge from the parent, empty only for the root
+    label: Vec<K>,
+    // sorted by the first symbol of the child's label
+    children: Vec<(K, usize)>,
+    value: Option<V>,
+}
+
+impl<K: Ord + Copy, V> Default for RadixTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Copy, V> RadixTree<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                label: Vec::new(),
+                children: Vec::new(),
+                value: None,
+            }],
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    pub fn insert(&mut self, key: &[K], value: V) -> Option<V> {
+        let mut node = ROOT;
+        let mut i = 0;
+        while i < key.len() {
+            let pos = match self.child(node, key[i]) {
+                Ok(pos) => pos,
+                Err(pos) => {
+                    let leaf = self.alloc(key[i..].to_vec(), Some(value));
+                    self.nodes[node].children.insert(pos, (key[i], leaf));
+                    self.len += 1;
+                    return None;
+                }
+            };
+            let child = self.nodes[node].children[pos].1;
+            let common = common_prefix(&self.nodes[child].label, &key[i..]);
+            if common < self.nodes[child].label.len() {
+                // split the edge, the new node takes the common part
+                let rest = self.nodes[child].label.split_off(common);
+                let first = rest[0];
+                let label = std::mem::replace(&mut self.nodes[child].label, rest);
+                let mid = self.alloc(label, None);
+                self.nodes[mid].children.push((first, child));
+                self.nodes[node].children[pos].1 = mid;
+                node = mid;
+            } else {
+                node = child;
+            }
+            i += common;
+        }
+        let old = self.nodes[node].value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn get(&self, key: &[K]) -> Option<&V> {
+        self.find(key).and_then(|n| self.nodes[n].value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, key: &[K]) -> Option<&mut V> {
+        self.find(key).and_then(|n| self.nodes[n].value.as_mut())
+    }
+
+    pub fn contains(&self, key: &[K]) -> bool {
+        self.get(key).is_some()
+    }
+
+    // removes key and merges the edges around it back together
+    pub fn remove(&mut self, key: &[K]) -> Option<V> {
+        let mut parent = None;
+        let mut node = ROOT;
+        let mut i = 0;
+        while i < key.len() {
+            let pos = self.child(node, key[i]).ok()?;
+            let child = self.nodes[node].children[pos].1;
+            if !key[i..].starts_with(&self.nodes[child].label) {
+                return None;
+            }
+            i += self.nodes[child].label.len();
+            parent = Some((node, pos));
+            node = child;
+        }
+        let value = self.nodes[node].value.take()?;
+        self.len -= 1;
+        let Some((parent, pos)) = parent else {
+            return Some(value);
+        };
+        match self.nodes[node].children.len() {
+            0 => {
+                self.nodes[parent].children.remove(pos);
+                self.free.push(node);
+                if parent != ROOT && self.nodes[parent].value.is_none() {
+                    self.merge_child(parent);
+                }
+            }
+            1 => self.merge_child(node),
+            _ => {}
+        }
+        Some(value)
+    }
+
+    pub fn longest_prefix(&self, query: &[K]) -> Option<(usize, &V)> {
+        let mut node = ROOT;
+        let mut i = 0;
+        let mut best = self.nodes[ROOT].value.as_ref().map(|v| (0, v));
+        while i < query.len() {
+            let Ok(pos) = self.child(node, query[i]) else {
+                break;
+            };
+            let child = self.nodes[node].children[pos].1;
+            if !query[i..].starts_with(&self.nodes[child].label) {
+                break;
+            }
+            i += self.nodes[child].label.len();
+            node = child;
+            if let Some(v) = &self.nodes[node].value {
+                best = Some((i, v));
+            }
+        }
+        best
+    }
+
+    pub fn iter_prefix(&self, prefix: &[K]) -> PrefixIter<'_, K, V> {
+        let mut node = ROOT;
+        let mut i = 0;
+        let mut stack = Vec::new();
+        loop {
+            if i == prefix.len() {
+                stack.push((node, i));
+                break;
+            }
+            let Ok(pos) = self.child(node, prefix[i]) else {
+                break;
+            };
+            let child = self.nodes[node].children[pos].1;
+            let label = &self.nodes[child].label;
+            if label.starts_with(&prefix[i..]) {
+                // the prefix ends inside this edge
+                stack.push((child, i));
+                break;
+            }
+            if !prefix[i..].starts_with(label) {
+                break;
+            }
+            i += label.len();
+            node = child;
+        }
+        let key = match stack.first() {
+            Some(&(_, depth)) => prefix[..depth].to_vec(),
+            None => Vec::new(),
+        };
+        PrefixIter {
+            tree: self,
+            key,
+            stack,
+        }
+    }
+
+    pub fn iter(&self) -> PrefixIter<'_, K, V> {
+        self.iter_prefix(&[])
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn child(&self, node: usize, k: K) -> Result<usize, usize> {
+        self.nodes[node]
+            .children
+            .binary_search_by(|&(c, _)| c.cmp(&k))
+    }
+
+    fn find(&self, key: &[K]) -> Option<usize> {
+        let mut node = ROOT;
+        let mut i = 0;
+        while i < key.len() {
+            let pos = self.child(node, key[i]).ok()?;
+            let child = self.nodes[node].children[pos].1;
+            if !key[i..].starts_with(&self.nodes[child].label) {
+                return None;
+            }
+            i += self.nodes[child].label.len();
+            node = child;
+        }
+        Some(node)
+    }
+
+    // node has no value and a single child, which is folded into it
+    fn merge_child(&mut self, node: usize) {
+        if self.nodes[node].children.len() != 1 {
+            return;
+        }
+        let (_, index) = self.nodes[node].children[0];
+        let child = std::mem::replace(
+            &mut self.nodes[index],
+            Node {
+                label: Vec::new(),
+                children: Vec::new(),
+                value: None,
+            },
+        );
+        self.free.push(index);
+        let n = &mut self.nodes[node];
+        n.label.extend(child.label);
+        n.children = child.children;
+        n.value = child.value;
+    }
+
+    fn alloc(&mut self, label: Vec<K>, value: Option<V>) -> usize {
+        let node = Node {
+            label,
+            children: Vec::new(),
+            value,
+        };
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+impl<K: Ord + Copy, V> From<Trie<K, V>> for RadixTree<K, V> {
+    // compresses the single child chains of the trie, O(trie nodes)
+    fn from(mut trie: Trie<K, V>) -> Self {
+        let mut tree = Self::new();
+        tree.len = trie.len();
+        tree.nodes[ROOT].value = trie.nodes[ROOT].value.take();
+        // (trie node, radix parent, symbol into the trie node)
+        let mut stack: Vec<(usize, usize, K)> = trie.nodes[ROOT]
+            .children
+            .iter()
+            .rev()
+            .map(|&(k, c)| (c, ROOT, k))
+            .collect();
+        while let Some((mut t, parent, k)) = stack.pop() {
+            let mut label = vec![k];
+            while trie.nodes[t].value.is_none() && trie.nodes[t].children.len() == 1 {
+                let (k, c) = trie.nodes[t].children[0];
+                label.push(k);
+                t = c;
+            }
+            let value = trie.nodes[t].value.take();
+            let node = tree.alloc(label, value);
+            tree.nodes[parent].children.push((k, node));
+            stack.extend(
+                trie.nodes[t]
+                    .children
+                    .iter()
+                    .rev()
+                    .map(|&(k, c)| (c, node, k)),
+            );
+        }
+        tree
+    }
+}
+
+impl<K: Ord + Copy, V> FromIterator<(Vec<K>, V)> for RadixTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Vec<K>, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (key, value) in iter {
+            tree.insert(&key, value);
+        }
+        tree
+    }
+}
+
+pub struct PrefixIter<'a, K, V> {
+    tree: &'a RadixTree<K, V>,
+    key: Vec<K>,
+    // (node, key length up to the start of its label)
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, K: Ord + Copy, V> Iterator for PrefixIter<'a, K, V> {
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth)) = self.stack.pop() {
+            let n = &self.tree.nodes[node];
+            self.key.truncate(depth);
+            self.key.extend_from_slice(&n.label);
+            for &(_, child) in n.children.iter().rev() {
+                self.stack.push((child, self.key.len()));
+            }
+            if let Some(v) = &n.value {
+                return Some((self.key.clone(), v));
+            }
+        }
+        None
+    }
+}
+
+fn common_prefix<K: PartialEq>(a: &[K], b: &[K]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
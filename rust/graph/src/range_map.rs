@@ -0,0 +1,258 @@
+use std::{collections::BTreeMap, fmt, ops::Range};
+
+// Map from half-open ranges of keys to values, kept as disjoint segments in
+// a BTreeMap from start to (end, value). Inserting a range overwrites what
+// was under it, cutting the segments it partly covers, and merges with the
+// segments on either side that touch or overlap it and hold an equal
+// value, so the segments stay as few as the contents allow. A lookup is
+// the last segment starting at or before the key: O(log n).
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeMap<K, V> {
+    segments: BTreeMap<K, (K, V)>,
+}
+
+impl<K, V> Default for RangeMap<K, V> {
+    fn default() -> Self {
+        Self {
+            segments: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + Eq> RangeMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // number of segments
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+
+    // the segment holding key, with its value
+    pub fn get_key_value(&self, key: &K) -> Option<(Range<K>, &V)> {
+        let (start, (end, value)) = self.segments.range(..=key).next_back()?;
+        (key < end).then(|| (start.clone()..end.clone(), value))
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_key_value(key).is_some()
+    }
+
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        self.remove(range.clone());
+        let Range { mut start, mut end } = range;
+        let before = self.segments.range(..&start).next_back();
+        if let Some((s, (e, v))) = before {
+            if *e == start && *v == value {
+                start = s.clone();
+            }
+        }
+        if let Some((e, v)) = self.segments.get(&end) {
+            if *v == value {
+                let e = e.clone();
+                self.segments.remove(&end);
+                end = e;
+            }
+        }
+        self.segments.insert(start, (end, value));
+    }
+
+    // clears range, cutting the segments at its ends
+    pub fn remove(&mut self, range: Range<K>) {
+        if range.start >= range.end {
+            return;
+        }
+        let before = self.segments.range_mut(..&range.start).next_back();
+        if let Some((_, (end, value))) = before {
+            if *end > range.start {
+                let old_end = std::mem::replace(end, range.start.clone());
+                if old_end > range.end {
+                    let value = value.clone();
+                    self.segments.insert(range.end.clone(), (old_end, value));
+                    return;
+                }
+            }
+        }
+        let inside: Vec<K> = self
+            .segments
+            .range(&range.start..&range.end)
+            .map(|(s, _)| s.clone())
+            .collect();
+        for start in inside {
+            let (end, value) = self.segments.remove(&start).unwrap();
+            if end > range.end {
+                self.segments.insert(range.end.clone(), (end, value));
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Range<K>, &V)> + '_ {
+        self.segments
+            .iter()
+            .map(|(s, (e, v))| (s.clone()..e.clone(), v))
+    }
+
+    // segments meeting range, in order and uncut, none for an empty range
+    pub fn overlapping(&self, range: Range<K>) -> impl Iterator<Item = (Range<K>, &V)> + '_ {
+        let nonempty = range.start < range.end;
+        let before = self
+            .segments
+            .range(..&range.start)
+            .next_back()
+            .filter(|(_, (e, _))| nonempty && *e > range.start);
+        let inside = nonempty
+            .then(|| self.segments.range(range.start.clone()..range.end))
+            .into_iter()
+            .flatten();
+        before
+            .into_iter()
+            .chain(inside)
+            .map(|(s, (e, v))| (s.clone()..e.clone(), v))
+    }
+
+    // the parts of range no segment covers, in order
+    pub fn gaps(&self, range: Range<K>) -> Vec<Range<K>> {
+        let mut gaps = Vec::new();
+        let mut at = range.start.clone();
+        for (segment, _) in self.overlapping(range.clone()) {
+            if segment.start > at {
+                gaps.push(std::mem::replace(&mut at, segment.end)..segment.start);
+            } else if segment.end > at {
+                at = segment.end;
+            }
+        }
+        if at < range.end {
+            gaps.push(at..range.end);
+        }
+        gaps
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: Clone + Eq + fmt::Debug> fmt::Debug for RangeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + Eq> Extend<(Range<K>, V)> for RangeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + Eq> FromIterator<(Range<K>, V)> for RangeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// Set of keys as coalesced disjoint half-open ranges: a RangeMap to ().
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct RangeSet<K> {
+    map: RangeMap<K, ()>,
+}
+
+impl<K: Ord + Clone> RangeSet<K> {
+    pub fn new() -> Self {
+        Self {
+            map: RangeMap::new(),
+        }
+    }
+
+    // number of disjoint ranges
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn insert(&mut self, range: Range<K>) {
+        self.map.insert(range, ());
+    }
+
+    pub fn remove(&mut self, range: Range<K>) {
+        self.map.remove(range);
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    // the range holding key
+    pub fn range_of(&self, key: &K) -> Option<Range<K>> {
+        self.map.get_key_value(key).map(|(r, _)| r)
+    }
+
+    // true if some key of range is in the set
+    pub fn overlaps(&self, range: Range<K>) -> bool {
+        self.map.overlapping(range).next().is_some()
+    }
+
+    // true if every key of range is
+    pub fn covers(&self, range: Range<K>) -> bool {
+        range.start >= range.end
+            || self
+                .range_of(&range.start)
+                .is_some_and(|r| r.end >= range.end)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Range<K>> + '_ {
+        self.map.iter().map(|(r, _)| r)
+    }
+
+    pub fn overlapping(&self, range: Range<K>) -> impl Iterator<Item = Range<K>> + '_ {
+        self.map.overlapping(range).map(|(r, _)| r)
+    }
+
+    pub fn gaps(&self, range: Range<K>) -> Vec<Range<K>> {
+        self.map.gaps(range)
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug> fmt::Debug for RangeSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone> Extend<Range<K>> for RangeSet<K> {
+    fn extend<I: IntoIterator<Item = Range<K>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<K: Ord + Clone> FromIterator<Range<K>> for RangeSet<K> {
+    fn from_iter<I: IntoIterator<Item = Range<K>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
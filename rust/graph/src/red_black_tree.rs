@@ -0,0 +1,446 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+use crate::ordered_map::OrderedMap;
+
+// Ordered map on a red-black tree: no red node has a red child and every
+// root-to-leaf path passes the same number of black nodes, so the height is
+// at most 2 log2(n + 1). Insert and remove repair the colors with at most
+// two and three rotations. Nodes live in one Vec, link to each other by
+// index and keep a parent link for the fix-ups and for iteration.
+#[derive(Clone)]
+pub struct RedBlackTreeMap<K, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    root: usize,
+    len: usize,
+}
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    red: bool,
+    parent: usize,
+    // left and right
+    child: [usize; 2],
+}
+
+const NIL: usize = usize::MAX;
+const LEFT: usize = 0;
+const RIGHT: usize = 1;
+
+impl<K: Ord, V> Default for RedBlackTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> RedBlackTreeMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: NIL,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut parent = NIL;
+        let mut side = LEFT;
+        let mut cur = self.root;
+        while cur != NIL {
+            side = match key.cmp(&self.node(cur).key) {
+                Ordering::Less => LEFT,
+                Ordering::Greater => RIGHT,
+                Ordering::Equal => {
+                    return Some(std::mem::replace(&mut self.node_mut(cur).value, value));
+                }
+            };
+            parent = cur;
+            cur = self.node(cur).child[side];
+        }
+        let z = self.alloc(Node {
+            key,
+            value,
+            red: true,
+            parent,
+            child: [NIL; 2],
+        });
+        if parent == NIL {
+            self.root = z;
+        } else {
+            self.node_mut(parent).child[side] = z;
+        }
+        self.len += 1;
+        self.insert_fixup(z);
+        None
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let z = self.find(key)?;
+        let [left, right] = self.node(z).child;
+        let mut removed_red = self.node(z).red;
+        // x takes the place of the node that leaves the tree, and may be NIL
+        let (x, x_parent);
+        if left == NIL || right == NIL {
+            x = if left == NIL { right } else { left };
+            x_parent = self.node(z).parent;
+            self.transplant(z, x);
+        } else {
+            // the successor of z moves into its place
+            let y = self.min_from(right);
+            removed_red = self.node(y).red;
+            x = self.node(y).child[RIGHT];
+            if self.node(y).parent == z {
+                x_parent = y;
+            } else {
+                x_parent = self.node(y).parent;
+                self.transplant(y, x);
+                self.node_mut(y).child[RIGHT] = right;
+                self.node_mut(right).parent = y;
+            }
+            self.transplant(z, y);
+            self.node_mut(y).child[LEFT] = left;
+            self.node_mut(left).parent = y;
+            let red = self.node(z).red;
+            self.node_mut(y).red = red;
+        }
+        if !removed_red {
+            self.remove_fixup(x, x_parent);
+        }
+        self.len -= 1;
+        self.free.push(z);
+        self.nodes[z].take().map(|n| n.value)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).map(|i| &self.node(i).value)
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let i = self.find(key)?;
+        Some(&mut self.node_mut(i).value)
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        (self.root != NIL).then(|| self.entry(self.min_from(self.root)))
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        if self.root == NIL {
+            return None;
+        }
+        let mut i = self.root;
+        while self.node(i).child[RIGHT] != NIL {
+            i = self.node(i).child[RIGHT];
+        }
+        Some(self.entry(i))
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        // the smallest key not before the start bound
+        let mut cur = self.root;
+        let mut first = NIL;
+        while cur != NIL {
+            let key = &self.node(cur).key;
+            let after_start = match range.start_bound() {
+                Bound::Unbounded => true,
+                Bound::Included(s) => key >= s,
+                Bound::Excluded(s) => key > s,
+            };
+            if after_start {
+                first = cur;
+                cur = self.node(cur).child[LEFT];
+            } else {
+                cur = self.node(cur).child[RIGHT];
+            }
+        }
+        Range {
+            map: self,
+            cur: first,
+            range,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = NIL;
+        self.len = 0;
+    }
+
+    fn node(&self, i: usize) -> &Node<K, V> {
+        self.nodes[i].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, i: usize) -> &mut Node<K, V> {
+        self.nodes[i].as_mut().unwrap()
+    }
+
+    fn entry(&self, i: usize) -> (&K, &V) {
+        let n = self.node(i);
+        (&n.key, &n.value)
+    }
+
+    // NIL leaves are black
+    fn is_red(&self, i: usize) -> bool {
+        i != NIL && self.node(i).red
+    }
+
+    fn set_black(&mut self, i: usize) {
+        if i != NIL {
+            self.node_mut(i).red = false;
+        }
+    }
+
+    fn find<Q: Ord + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        let mut cur = self.root;
+        while cur != NIL {
+            cur = match key.cmp(self.node(cur).key.borrow()) {
+                Ordering::Less => self.node(cur).child[LEFT],
+                Ordering::Equal => return Some(cur),
+                Ordering::Greater => self.node(cur).child[RIGHT],
+            };
+        }
+        None
+    }
+
+    fn min_from(&self, mut i: usize) -> usize {
+        while self.node(i).child[LEFT] != NIL {
+            i = self.node(i).child[LEFT];
+        }
+        i
+    }
+
+    fn successor(&self, i: usize) -> usize {
+        let right = self.node(i).child[RIGHT];
+        if right != NIL {
+            return self.min_from(right);
+        }
+        let mut cur = i;
+        let mut parent = self.node(i).parent;
+        while parent != NIL && self.node(parent).child[RIGHT] == cur {
+            cur = parent;
+            parent = self.node(parent).parent;
+        }
+        parent
+    }
+
+    // which child of its parent i is
+    fn side(&self, parent: usize, i: usize) -> usize {
+        if self.node(parent).child[LEFT] == i {
+            LEFT
+        } else {
+            RIGHT
+        }
+    }
+
+    // puts v where u hangs in the tree
+    fn transplant(&mut self, u: usize, v: usize) {
+        let parent = self.node(u).parent;
+        if parent == NIL {
+            self.root = v;
+        } else {
+            let side = self.side(parent, u);
+            self.node_mut(parent).child[side] = v;
+        }
+        if v != NIL {
+            self.node_mut(v).parent = parent;
+        }
+    }
+
+    // rotate(x, LEFT) lifts the right child of x and moves x down to the left
+    fn rotate(&mut self, x: usize, dir: usize) {
+        let y = self.node(x).child[1 - dir];
+        let inner = self.node(y).child[dir];
+        self.node_mut(x).child[1 - dir] = inner;
+        if inner != NIL {
+            self.node_mut(inner).parent = x;
+        }
+        self.transplant(x, y);
+        self.node_mut(y).child[dir] = x;
+        self.node_mut(x).parent = y;
+    }
+
+    fn insert_fixup(&mut self, mut z: usize) {
+        while self.is_red(self.node(z).parent) {
+            // a red parent is never the root, so the grandparent exists
+            let p = self.node(z).parent;
+            let g = self.node(p).parent;
+            let dir = self.side(g, p);
+            let uncle = self.node(g).child[1 - dir];
+            if self.is_red(uncle) {
+                self.node_mut(p).red = false;
+                self.node_mut(uncle).red = false;
+                self.node_mut(g).red = true;
+                z = g;
+                continue;
+            }
+            if self.side(p, z) != dir {
+                z = p;
+                self.rotate(z, dir);
+            }
+            let p = self.node(z).parent;
+            self.node_mut(p).red = false;
+            self.node_mut(g).red = true;
+            self.rotate(g, 1 - dir);
+        }
+        let root = self.root;
+        self.node_mut(root).red = false;
+    }
+
+    // the subtree at x, under parent, is one black node short
+    fn remove_fixup(&mut self, mut x: usize, mut parent: usize) {
+        while x != self.root && !self.is_red(x) {
+            // the sibling of a short subtree is never NIL
+            let dir = if self.node(parent).child[LEFT] == x {
+                LEFT
+            } else {
+                RIGHT
+            };
+            let mut w = self.node(parent).child[1 - dir];
+            if self.is_red(w) {
+                self.node_mut(w).red = false;
+                self.node_mut(parent).red = true;
+                self.rotate(parent, dir);
+                w = self.node(parent).child[1 - dir];
+            }
+            let [near, far] = [self.node(w).child[dir], self.node(w).child[1 - dir]];
+            if !self.is_red(near) && !self.is_red(far) {
+                self.node_mut(w).red = true;
+                x = parent;
+                parent = self.node(x).parent;
+                continue;
+            }
+            if !self.is_red(far) {
+                self.set_black(near);
+                self.node_mut(w).red = true;
+                self.rotate(w, 1 - dir);
+                w = self.node(parent).child[1 - dir];
+            }
+            let red = self.node(parent).red;
+            self.node_mut(w).red = red;
+            self.node_mut(parent).red = false;
+            let far = self.node(w).child[1 - dir];
+            self.set_black(far);
+            self.rotate(parent, dir);
+            x = self.root;
+        }
+        self.set_black(x);
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for RedBlackTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for RedBlackTreeMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        RedBlackTreeMap::insert(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        RedBlackTreeMap::remove(self, key)
+    }
+    fn get(&self, key: &K) -> Option<&V> {
+        RedBlackTreeMap::get(self, key)
+    }
+    fn first(&self) -> Option<(&K, &V)> {
+        RedBlackTreeMap::first(self)
+    }
+    fn last(&self) -> Option<(&K, &V)> {
+        RedBlackTreeMap::last(self)
+    }
+    fn len(&self) -> usize {
+        RedBlackTreeMap::len(self)
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for RedBlackTreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Range<'a, K, V, R> {
+    map: &'a RedBlackTreeMap<K, V>,
+    cur: usize,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == NIL {
+            return None;
+        }
+        let (key, value) = self.map.entry(self.cur);
+        let inside = match self.range.end_bound() {
+            Bound::Unbounded => true,
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+        };
+        if !inside {
+            self.cur = NIL;
+            return None;
+        }
+        self.cur = self.map.successor(self.cur);
+        Some((key, value))
+    }
+}
@@ -0,0 +1,414 @@
+use std::{
+    error::Error,
+    fmt,
+    mem::MaybeUninit,
+    ops::{Index, IndexMut},
+    slice,
+};
+
+// Circular buffer with pushes and pops at both ends in O(1). The items are
+// a window of len slots starting at head that may wrap past the end of the
+// buffer. A growable buffer doubles when full, moving the items to the
+// front of the new one; a bounded one refuses pushes when full, or drops
+// the item at the other end on an overwriting push, which makes it a fixed
+// size history.
+pub struct RingBuffer<T> {
+    // buf[(head + i) % capacity] is initialized for i < len
+    buf: Box<[MaybeUninit<T>]>,
+    head: usize,
+    len: usize,
+    growable: bool,
+}
+
+// a push into a full bounded buffer, with the item handed back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferFull<T>(pub T);
+
+impl<T> fmt::Display for BufferFull<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ring buffer is full")
+    }
+}
+
+impl<T: fmt::Debug> Error for BufferFull<T> {}
+
+const MIN_GROWTH: usize = 4;
+
+impl<T> RingBuffer<T> {
+    // growable
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    // growable, with room for capacity items before the first growth
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: uninit(capacity),
+            head: 0,
+            len: 0,
+            growable: true,
+        }
+    }
+
+    // never holds more than capacity items
+    pub fn bounded(capacity: usize) -> Self {
+        let mut buffer = Self::with_capacity(capacity);
+        buffer.growable = false;
+        buffer
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        !self.growable
+    }
+
+    // a growable buffer is full only until the next push
+    pub fn is_full(&self) -> bool {
+        self.len == self.buf.len()
+    }
+
+    // slot of the item at index i < capacity
+    fn slot(&self, i: usize) -> usize {
+        let j = self.head + i;
+        if j >= self.buf.len() {
+            j - self.buf.len()
+        } else {
+            j
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(unsafe { self.buf[self.slot(index)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let s = self.slot(index);
+        Some(unsafe { self.buf[s].assume_init_mut() })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    pub fn push_back(&mut self, value: T) -> Result<(), BufferFull<T>> {
+        if !self.make_room() {
+            return Err(BufferFull(value));
+        }
+        let s = self.slot(self.len);
+        self.buf[s].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn push_front(&mut self, value: T) -> Result<(), BufferFull<T>> {
+        if !self.make_room() {
+            return Err(BufferFull(value));
+        }
+        self.head = self.slot(self.buf.len() - 1);
+        self.buf[self.head].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    // Pushes even into a full bounded buffer, popping the front item to
+    // make room and returning it. Never fails, a bounded buffer of
+    // capacity 0 hands the value straight back.
+    pub fn push_back_overwrite(&mut self, value: T) -> Option<T> {
+        if self.growable || !self.is_full() {
+            self.push_back(value).ok();
+            return None;
+        }
+        if self.buf.is_empty() {
+            return Some(value);
+        }
+        let old = self.pop_front();
+        self.push_back(value).ok();
+        old
+    }
+
+    // the mirror of push_back_overwrite, popping the back item
+    pub fn push_front_overwrite(&mut self, value: T) -> Option<T> {
+        if self.growable || !self.is_full() {
+            self.push_front(value).ok();
+            return None;
+        }
+        if self.buf.is_empty() {
+            return Some(value);
+        }
+        let old = self.pop_back();
+        self.push_front(value).ok();
+        old
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = self.slot(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let s = self.slot(self.len);
+        Some(unsafe { self.buf[s].assume_init_read() })
+    }
+
+    // The items as two slices, in order: the run from head to the end of
+    // the buffer and the wrapped part, which is empty if nothing wraps.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let first = self.len.min(self.buf.len() - self.head);
+        unsafe {
+            let p = self.buf.as_ptr() as *const T;
+            (
+                slice::from_raw_parts(p.add(self.head), first),
+                slice::from_raw_parts(p, self.len - first),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let first = self.len.min(self.buf.len() - self.head);
+        // the two ranges, head..head + first and 0..len - first, don't
+        // overlap
+        unsafe {
+            let p = self.buf.as_mut_ptr() as *mut T;
+            (
+                slice::from_raw_parts_mut(p.add(self.head), first),
+                slice::from_raw_parts_mut(p, self.len - first),
+            )
+        }
+    }
+
+    // Rotates the items to the start of the buffer if they wrap, O(capacity)
+    // then and free after, and returns them as one slice.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len > self.buf.len() {
+            self.buf.rotate_left(self.head);
+            self.head = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (a, b) = self.as_slices();
+        Iter {
+            a: a.iter(),
+            b: b.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> {
+        let (a, b) = self.as_mut_slices();
+        a.iter_mut().chain(b.iter_mut())
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop_back();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.head = 0;
+    }
+
+    // false if full and bounded
+    fn make_room(&mut self) -> bool {
+        if !self.is_full() {
+            return true;
+        }
+        if !self.growable {
+            return false;
+        }
+        let mut buf = uninit((2 * self.buf.len()).max(MIN_GROWTH));
+        for (i, slot) in buf.iter_mut().take(self.len).enumerate() {
+            // each item is moved out exactly once, the old buffer drops as
+            // plain memory
+            let s = self.slot(i);
+            slot.write(unsafe { self.buf[s].assume_init_read() });
+        }
+        self.buf = buf;
+        self.head = 0;
+        true
+    }
+}
+
+fn uninit<T>(n: usize) -> Box<[MaybeUninit<T>]> {
+    (0..n).map(|_| MaybeUninit::uninit()).collect()
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for RingBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for RingBuffer<T> {
+    fn clone(&self) -> Self {
+        let mut copy = Self::with_capacity(self.capacity());
+        copy.growable = self.growable;
+        for item in self.iter() {
+            copy.push_back(item.clone()).ok();
+        }
+        copy
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RingBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for RingBuffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for RingBuffer<T> {}
+
+impl<T> Index<usize> for RingBuffer<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {index} out of range for length {len}"))
+    }
+}
+
+impl<T> IndexMut<usize> for RingBuffer<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index {index} out of range for length {len}"))
+    }
+}
+
+// pushes at the back, panicking if a bounded buffer fills
+impl<T> Extend<T> for RingBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            if self.push_back(item).is_err() {
+                panic!("ring buffer is full");
+            }
+        }
+    }
+}
+
+impl<T> FromIterator<T> for RingBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut buffer = Self::new();
+        buffer.extend(iter);
+        buffer
+    }
+}
+
+pub struct Iter<'a, T> {
+    a: slice::Iter<'a, T>,
+    b: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.a.next().or_else(|| self.b.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.a.len() + self.b.len();
+        (n, Some(n))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.b.next_back().or_else(|| self.a.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<T>(RingBuffer<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for RingBuffer<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
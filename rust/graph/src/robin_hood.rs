@@ -0,0 +1,316 @@
+use std::{
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hash},
+};
+
+// Open addressing hash map with Robin Hood linear probing. An entry's
+// probe distance is how far it sits past its home slot; an insert that
+// meets an entry closer to home than itself takes that slot and carries
+// the displaced entry on. Distances stay even, so lookups can stop at the
+// first entry closer to home than the probe, and a remove shifts the run
+// after it back one slot instead of leaving a tombstone. The table doubles
+// when the load would pass max_load_factor; slots are a power of two and
+// keep each entry's hash so growing doesn't rehash keys.
+#[derive(Clone)]
+pub struct RobinHoodMap<K, V, S = RandomState> {
+    slots: Vec<Option<Slot<K, V>>>,
+    len: usize,
+    max_load_factor: f64,
+    hasher: S,
+}
+
+#[derive(Clone)]
+struct Slot<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+const MIN_SLOTS: usize = 8;
+const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.875;
+
+// Probe distances of the entries: histogram[d] entries sit d slots past
+// their home.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeStats {
+    pub len: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub histogram: Vec<usize>,
+}
+
+impl<K: Hash + Eq, V> RobinHoodMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V> Default for RobinHoodMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> RobinHoodMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    // room for capacity entries before growing
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let mut map = Self {
+            slots: Vec::new(),
+            len: 0,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            hasher,
+        };
+        map.resize(map.slots_for(capacity));
+        map
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // number of slots, used or not
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.slots.len() as f64
+    }
+
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    // grows right away if the load is already past the new limit
+    pub fn set_max_load_factor(&mut self, max_load_factor: f64) {
+        assert!(
+            max_load_factor > 0.0 && max_load_factor < 1.0,
+            "max load factor must be in (0, 1), got {max_load_factor}"
+        );
+        self.max_load_factor = max_load_factor;
+        let slots = self.slots_for(self.len);
+        if slots > self.slots.len() {
+            self.resize(slots);
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(key)?;
+        self.slots[i].as_ref().map(|s| &s.value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(key)?;
+        self.slots[i].as_mut().map(|s| &mut s.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hasher.hash_one(&key);
+        if let Some(i) = self.find_hashed(hash, &key) {
+            let slot = self.slots[i].as_mut().unwrap();
+            return Some(std::mem::replace(&mut slot.value, value));
+        }
+        if self.len + 1 > self.max_len() {
+            self.resize(self.slots.len() * 2);
+        }
+        let home = hash as usize & (self.slots.len() - 1);
+        self.place(home, Slot { hash, key, value });
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut i = self.find(key)?;
+        let removed = self.slots[i].take().unwrap();
+        // shift back the entries after it that are away from home
+        let mask = self.slots.len() - 1;
+        loop {
+            let next = (i + 1) & mask;
+            match &self.slots[next] {
+                Some(slot) if self.distance(next, slot.hash) > 0 => {
+                    self.slots[i] = self.slots[next].take();
+                    i = next;
+                }
+                _ => break,
+            }
+        }
+        self.len -= 1;
+        Some(removed.value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.slots.iter().flatten().map(|s| (&s.key, &s.value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|s| *s = None);
+        self.len = 0;
+    }
+
+    pub fn probe_stats(&self) -> ProbeStats {
+        let mut histogram = Vec::new();
+        for (i, slot) in self.slots.iter().enumerate() {
+            if let Some(slot) = slot {
+                let d = self.distance(i, slot.hash);
+                if d >= histogram.len() {
+                    histogram.resize(d + 1, 0);
+                }
+                histogram[d] += 1;
+            }
+        }
+        let total: usize = histogram.iter().enumerate().map(|(d, &c)| d * c).sum();
+        ProbeStats {
+            len: self.len,
+            max: histogram.len().saturating_sub(1),
+            mean: if self.len == 0 {
+                0.0
+            } else {
+                total as f64 / self.len as f64
+            },
+            histogram,
+        }
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_hashed(self.hasher.hash_one(key), key)
+    }
+
+    fn find_hashed<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let mask = self.slots.len() - 1;
+        let mut i = hash as usize & mask;
+        let mut dist = 0;
+        loop {
+            let slot = self.slots[i].as_ref()?;
+            if self.distance(i, slot.hash) < dist {
+                return None;
+            }
+            if slot.hash == hash && slot.key.borrow() == key {
+                return Some(i);
+            }
+            i = (i + 1) & mask;
+            dist += 1;
+        }
+    }
+
+    // puts slot, whose key is absent, at its home i or past it, moving on
+    // the entries it displaces
+    fn place(&mut self, mut i: usize, slot: Slot<K, V>) {
+        let mask = self.slots.len() - 1;
+        let mut carried = slot;
+        let mut dist = 0;
+        loop {
+            match &mut self.slots[i] {
+                None => {
+                    self.slots[i] = Some(carried);
+                    return;
+                }
+                Some(other) => {
+                    let other_dist = (i.wrapping_sub(other.hash as usize)) & mask;
+                    if other_dist < dist {
+                        std::mem::swap(other, &mut carried);
+                        dist = other_dist;
+                    }
+                }
+            }
+            i = (i + 1) & mask;
+            dist += 1;
+        }
+    }
+
+    // how far slot i is past the home of hash
+    fn distance(&self, i: usize, hash: u64) -> usize {
+        i.wrapping_sub(hash as usize) & (self.slots.len() - 1)
+    }
+
+    fn max_len(&self) -> usize {
+        (self.slots.len() as f64 * self.max_load_factor) as usize
+    }
+
+    fn slots_for(&self, len: usize) -> usize {
+        ((len as f64 / self.max_load_factor).ceil() as usize + 1)
+            .next_power_of_two()
+            .max(MIN_SLOTS)
+    }
+
+    fn resize(&mut self, slots: usize) {
+        let old = std::mem::replace(&mut self.slots, (0..slots).map(|_| None).collect());
+        let mask = slots - 1;
+        for slot in old.into_iter().flatten() {
+            self.place(slot.hash as usize & mask, slot);
+        }
+    }
+}
+
+impl<K: Hash + Eq + fmt::Debug, V: fmt::Debug, S: BuildHasher> fmt::Debug
+    for RobinHoodMap<K, V, S>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for RobinHoodMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for RobinHoodMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
@@ -0,0 +1,472 @@
+use std::{fmt, ops::Range};
+
+use crate::rng::{Rng, SplitMix64};
+
+// Rope: a string as a sequence of chunks of up to MAX_CHUNK bytes kept in
+// an implicit treap, each node knowing the bytes and chars in its subtree.
+// Positions resolve by descending on those counts, by char or by byte, and
+// insert, remove, split and append all come down to O(log n) expected
+// splits and merges, cutting a chunk in two where a position falls inside
+// it. Text inserted at the end of a chunk with room is appended to it, so
+// typing one char at a time doesn't leave a chunk per char.
+#[derive(Clone)]
+pub struct Rope {
+    root: Link,
+    rng: SplitMix64,
+}
+
+type Link = Option<Box<Node>>;
+
+#[derive(Clone)]
+struct Node {
+    chunk: String,
+    // chars in chunk
+    chunk_chars: usize,
+    priority: u64,
+    bytes: usize,
+    chars: usize,
+    left: Link,
+    right: Link,
+}
+
+const MAX_CHUNK: usize = 1024;
+
+#[derive(Clone, Copy)]
+enum Metric {
+    Bytes,
+    Chars,
+}
+
+impl Node {
+    fn new(chunk: String, priority: u64) -> Box<Self> {
+        let chunk_chars = chunk.chars().count();
+        Box::new(Self {
+            bytes: chunk.len(),
+            chars: chunk_chars,
+            chunk,
+            chunk_chars,
+            priority,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.bytes = self.chunk.len() + bytes(&self.left) + bytes(&self.right);
+        self.chars = self.chunk_chars + chars(&self.left) + chars(&self.right);
+    }
+
+    fn own(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Bytes => self.chunk.len(),
+            Metric::Chars => self.chunk_chars,
+        }
+    }
+}
+
+fn bytes(link: &Link) -> usize {
+    link.as_ref().map_or(0, |n| n.bytes)
+}
+
+fn chars(link: &Link) -> usize {
+    link.as_ref().map_or(0, |n| n.chars)
+}
+
+fn measure(link: &Link, metric: Metric) -> usize {
+    match metric {
+        Metric::Bytes => bytes(link),
+        Metric::Chars => chars(link),
+    }
+}
+
+// byte offset in s of its char k
+fn char_to_byte_in(s: &str, k: usize) -> usize {
+    s.char_indices().nth(k).map_or(s.len(), |(i, _)| i)
+}
+
+fn merge(a: Link, b: Link) -> Link {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority >= b.priority {
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+// (the first k bytes or chars, the rest), cutting the chunk k falls in
+fn split(link: Link, k: usize, metric: Metric, rng: &mut SplitMix64) -> (Link, Link) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let left = measure(&node.left, metric);
+    let own = node.own(metric);
+    if k <= left {
+        let (l, r) = split(node.left.take(), k, metric, rng);
+        node.left = r;
+        node.update();
+        (l, Some(node))
+    } else if k >= left + own {
+        let (l, r) = split(node.right.take(), k - left - own, metric, rng);
+        node.right = l;
+        node.update();
+        (Some(node), r)
+    } else {
+        let cut = match metric {
+            Metric::Bytes => k - left,
+            Metric::Chars => char_to_byte_in(&node.chunk, k - left),
+        };
+        let tail = Node::new(node.chunk.split_off(cut), rng.next_u64());
+        node.chunk_chars -= tail.chunk_chars;
+        let right = merge(Some(tail), node.right.take());
+        node.update();
+        (Some(node), right)
+    }
+}
+
+// appends text to the last chunk if it fits
+fn push_to_last(link: &mut Link, text: &str) -> bool {
+    let Some(node) = link else {
+        return false;
+    };
+    let pushed = if node.right.is_some() {
+        push_to_last(&mut node.right, text)
+    } else if node.chunk.len() + text.len() <= MAX_CHUNK {
+        node.chunk.push_str(text);
+        node.chunk_chars += text.chars().count();
+        true
+    } else {
+        false
+    };
+    if pushed {
+        node.update();
+    }
+    pushed
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rope {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            rng: SplitMix64::new(0x2093),
+        }
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        bytes(&self.root)
+    }
+
+    pub fn len_chars(&self) -> usize {
+        chars(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // chunks of text in order, none empty
+    fn build(&mut self, mut text: &str) -> Link {
+        let mut link = None;
+        while !text.is_empty() {
+            let mut cut = text.len().min(MAX_CHUNK);
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            let (chunk, rest) = text.split_at(cut);
+            link = merge(link, Some(Node::new(chunk.to_owned(), self.rng.next_u64())));
+            text = rest;
+        }
+        link
+    }
+
+    // checked before splitting, a panic halfway through would lose text
+    fn check_boundary(&self, k: usize, metric: Metric) {
+        if let Metric::Bytes = metric {
+            assert!(
+                self.is_char_boundary(k),
+                "byte index {k} is not a char boundary"
+            );
+        }
+    }
+
+    fn insert_by(&mut self, k: usize, text: &str, metric: Metric) {
+        let len = measure(&self.root, metric);
+        assert!(k <= len, "index {k} out of range for length {len}");
+        self.check_boundary(k, metric);
+        if text.is_empty() {
+            return;
+        }
+        let (mut left, right) = split(self.root.take(), k, metric, &mut self.rng);
+        if !push_to_last(&mut left, text) {
+            let middle = self.build(text);
+            left = merge(left, middle);
+        }
+        self.root = merge(left, right);
+    }
+
+    // inserts text before char char_idx
+    pub fn insert(&mut self, char_idx: usize, text: &str) {
+        self.insert_by(char_idx, text, Metric::Chars);
+    }
+
+    // panics unless byte_idx is on a char boundary
+    pub fn insert_at_byte(&mut self, byte_idx: usize, text: &str) {
+        self.insert_by(byte_idx, text, Metric::Bytes);
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.insert(self.len_chars(), text);
+    }
+
+    fn remove_by(&mut self, range: Range<usize>, metric: Metric) {
+        let len = measure(&self.root, metric);
+        assert!(
+            range.start <= range.end && range.end <= len,
+            "range {range:?} out of range for length {len}"
+        );
+        self.check_boundary(range.start, metric);
+        self.check_boundary(range.end, metric);
+        let (left, rest) = split(self.root.take(), range.start, metric, &mut self.rng);
+        let (_, right) = split(rest, range.end - range.start, metric, &mut self.rng);
+        self.root = merge(left, right);
+    }
+
+    // removes the chars in range
+    pub fn remove(&mut self, char_range: Range<usize>) {
+        self.remove_by(char_range, Metric::Chars);
+    }
+
+    pub fn remove_bytes(&mut self, byte_range: Range<usize>) {
+        self.remove_by(byte_range, Metric::Bytes);
+    }
+
+    // keeps the first char_idx chars and returns the rest
+    pub fn split_off(&mut self, char_idx: usize) -> Rope {
+        let len = self.len_chars();
+        assert!(
+            char_idx <= len,
+            "index {char_idx} out of range for length {len}"
+        );
+        let (left, right) = split(self.root.take(), char_idx, Metric::Chars, &mut self.rng);
+        self.root = left;
+        Rope {
+            root: right,
+            rng: SplitMix64::new(self.rng.next_u64()),
+        }
+    }
+
+    pub fn append(&mut self, other: Rope) {
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    // chunk holding position k by metric, and k within it
+    fn locate(&self, mut k: usize, metric: Metric) -> Option<(&Node, usize)> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left = measure(&node.left, metric);
+            if k < left {
+                link = &node.left;
+            } else if k < left + node.own(metric) {
+                return Some((node, k - left));
+            } else {
+                k -= left + node.own(metric);
+                link = &node.right;
+            }
+        }
+        None
+    }
+
+    // true at 0 and len_bytes too, as for str
+    pub fn is_char_boundary(&self, byte_idx: usize) -> bool {
+        match self.locate(byte_idx, Metric::Bytes) {
+            Some((node, k)) => node.chunk.is_char_boundary(k),
+            None => byte_idx == self.len_bytes(),
+        }
+    }
+
+    pub fn char(&self, char_idx: usize) -> Option<char> {
+        let (node, k) = self.locate(char_idx, Metric::Chars)?;
+        node.chunk.chars().nth(k)
+    }
+
+    pub fn byte(&self, byte_idx: usize) -> Option<u8> {
+        let (node, k) = self.locate(byte_idx, Metric::Bytes)?;
+        Some(node.chunk.as_bytes()[k])
+    }
+
+    // Byte offset of char char_idx, len_bytes for len_chars. Both
+    // conversions descend once, summing the other count on the way.
+    pub fn char_to_byte(&self, char_idx: usize) -> usize {
+        let len = self.len_chars();
+        assert!(
+            char_idx <= len,
+            "index {char_idx} out of range for length {len}"
+        );
+        let (mut k, mut link, mut offset) = (char_idx, &self.root, 0);
+        while let Some(node) = link {
+            let left = chars(&node.left);
+            if k < left {
+                link = &node.left;
+            } else if k < left + node.chunk_chars {
+                return offset + bytes(&node.left) + char_to_byte_in(&node.chunk, k - left);
+            } else {
+                k -= left + node.chunk_chars;
+                offset += bytes(&node.left) + node.chunk.len();
+                link = &node.right;
+            }
+        }
+        offset
+    }
+
+    // index of the char containing byte byte_idx, len_chars for len_bytes
+    pub fn byte_to_char(&self, byte_idx: usize) -> usize {
+        let len = self.len_bytes();
+        assert!(
+            byte_idx <= len,
+            "index {byte_idx} out of range for length {len}"
+        );
+        let (mut k, mut link, mut offset) = (byte_idx, &self.root, 0);
+        while let Some(node) = link {
+            let left = bytes(&node.left);
+            if k < left {
+                link = &node.left;
+            } else if k < left + node.chunk.len() {
+                let within = node
+                    .chunk
+                    .char_indices()
+                    .take_while(|&(i, _)| i <= k - left)
+                    .count()
+                    - 1;
+                return offset + chars(&node.left) + within;
+            } else {
+                k -= left + node.chunk.len();
+                offset += chars(&node.left) + node.chunk_chars;
+                link = &node.right;
+            }
+        }
+        offset
+    }
+
+    pub fn chunks(&self) -> Chunks<'_> {
+        let mut chunks = Chunks { stack: Vec::new() };
+        chunks.descend(&self.root);
+        chunks
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.chunks().flat_map(str::bytes)
+    }
+
+    // the chars in range as a String
+    pub fn slice(&self, char_range: Range<usize>) -> String {
+        let len = self.len_chars();
+        assert!(
+            char_range.start <= char_range.end && char_range.end <= len,
+            "range {char_range:?} out of range for length {len}"
+        );
+        let start = self.char_to_byte(char_range.start);
+        let end = self.char_to_byte(char_range.end);
+        let mut out = String::with_capacity(end - start);
+        let mut offset = 0;
+        for chunk in self.chunks() {
+            let (lo, hi) = (offset, offset + chunk.len());
+            offset = hi;
+            if hi <= start {
+                continue;
+            }
+            if lo >= end {
+                break;
+            }
+            out.push_str(&chunk[start.max(lo) - lo..end.min(hi) - lo]);
+        }
+        out
+    }
+}
+
+// in-order over the chunks with an explicit stack
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Chunks<'a> {
+    fn descend(&mut self, mut link: &'a Link) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let node = self.stack.pop()?;
+        self.descend(&node.right);
+        Some(&node.chunk)
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        let mut rope = Self::new();
+        rope.root = rope.build(text);
+        rope
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Self) -> bool {
+        self.len_bytes() == other.len_bytes() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len_bytes() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
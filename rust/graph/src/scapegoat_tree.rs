@@ -0,0 +1,413 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+use crate::ordered_map::OrderedMap;
+
+// Ordered map on a scapegoat tree: nodes carry nothing but the entry and
+// two child links. An insert that lands deeper than log_{1/α} of the size
+// rebuilds the subtree of the highest ancestor whose child holds more than
+// α of its nodes into a perfectly balanced one, and removes rebuild the
+// whole tree once a 1 - α fraction of it is gone. Lookups are O(log n)
+// worst case, updates O(log n) amortized.
+#[derive(Clone)]
+pub struct ScapegoatTree<K, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    root: usize,
+    len: usize,
+    // largest len since the last full rebuild
+    max_len: usize,
+    alpha: f64,
+}
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // left and right
+    child: [usize; 2],
+}
+
+const NIL: usize = usize::MAX;
+const DEFAULT_ALPHA: f64 = 2.0 / 3.0;
+
+impl<K: Ord, V> Default for ScapegoatTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> ScapegoatTree<K, V> {
+    pub fn new() -> Self {
+        Self::with_alpha(DEFAULT_ALPHA)
+    }
+
+    // alpha in (0.5, 1) trades lookup depth, smaller is shallower, against
+    // the rebuild work
+    pub fn with_alpha(alpha: f64) -> Self {
+        assert!(
+            alpha > 0.5 && alpha < 1.0,
+            "alpha must be between 0.5 and 1, got {alpha}"
+        );
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: NIL,
+            len: 0,
+            max_len: 0,
+            alpha,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // number of levels, 0 for an empty map
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut level = vec![self.root];
+        while level.iter().any(|&i| i != NIL) {
+            height += 1;
+            level = level
+                .into_iter()
+                .filter(|&i| i != NIL)
+                .flat_map(|i| self.node(i).child)
+                .collect();
+        }
+        height
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        // (node, direction taken) from the root down
+        let mut path = Vec::new();
+        let mut cur = self.root;
+        while cur != NIL {
+            let dir = match key.cmp(&self.node(cur).key) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => {
+                    return Some(std::mem::replace(&mut self.node_mut(cur).value, value));
+                }
+            };
+            path.push((cur, dir));
+            cur = self.node(cur).child[dir];
+        }
+        let z = self.alloc(Node {
+            key,
+            value,
+            child: [NIL; 2],
+        });
+        self.link(path.last().copied(), z);
+        self.len += 1;
+        self.max_len = self.max_len.max(self.len);
+
+        if path.len() as f64 > (self.max_len as f64).ln() / (1.0 / self.alpha).ln() {
+            // walk back up until a child is too heavy for its parent
+            let mut size = 1;
+            for j in (0..path.len()).rev() {
+                let (p, dir) = path[j];
+                let parent_size = 1 + size + self.size(self.node(p).child[1 - dir]);
+                if size as f64 > self.alpha * parent_size as f64 {
+                    let sub = self.rebuild(p);
+                    self.link(j.checked_sub(1).map(|k| path[k]), sub);
+                    break;
+                }
+                size = parent_size;
+            }
+        }
+        None
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut parent = None;
+        let mut z = self.root;
+        while z != NIL {
+            let dir = match key.cmp(self.node(z).key.borrow()) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => break,
+            };
+            parent = Some((z, dir));
+            z = self.node(z).child[dir];
+        }
+        if z == NIL {
+            return None;
+        }
+
+        let [left, right] = self.node(z).child;
+        let replacement = if left == NIL {
+            right
+        } else if right == NIL {
+            left
+        } else {
+            // the successor takes the place of z
+            let mut succ_parent = z;
+            let mut succ = right;
+            while self.node(succ).child[0] != NIL {
+                succ_parent = succ;
+                succ = self.node(succ).child[0];
+            }
+            if succ_parent != z {
+                let succ_right = self.node(succ).child[1];
+                self.node_mut(succ_parent).child[0] = succ_right;
+                self.node_mut(succ).child[1] = right;
+            }
+            self.node_mut(succ).child[0] = left;
+            succ
+        };
+        self.link(parent, replacement);
+        let node = self.nodes[z].take().unwrap();
+        self.free.push(z);
+        self.len -= 1;
+
+        if (self.len as f64) < self.alpha * self.max_len as f64 {
+            self.root = self.rebuild(self.root);
+            self.max_len = self.len;
+        }
+        Some(node.value)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).map(|i| &self.node(i).value)
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let i = self.find(key)?;
+        Some(&mut self.node_mut(i).value)
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.end(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.end(1)
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        // the path to the first key in range, keeping the nodes still to
+        // be visited
+        let mut stack = Vec::new();
+        let mut cur = self.root;
+        while cur != NIL {
+            let key = &self.node(cur).key;
+            let after_start = match range.start_bound() {
+                Bound::Unbounded => true,
+                Bound::Included(s) => key >= s,
+                Bound::Excluded(s) => key > s,
+            };
+            if after_start {
+                stack.push(cur);
+                cur = self.node(cur).child[0];
+            } else {
+                cur = self.node(cur).child[1];
+            }
+        }
+        Range {
+            map: self,
+            stack,
+            range,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = NIL;
+        self.len = 0;
+        self.max_len = 0;
+    }
+
+    fn node(&self, i: usize) -> &Node<K, V> {
+        self.nodes[i].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, i: usize) -> &mut Node<K, V> {
+        self.nodes[i].as_mut().unwrap()
+    }
+
+    fn find<Q: Ord + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        let mut cur = self.root;
+        while cur != NIL {
+            cur = match key.cmp(self.node(cur).key.borrow()) {
+                Ordering::Less => self.node(cur).child[0],
+                Ordering::Equal => return Some(cur),
+                Ordering::Greater => self.node(cur).child[1],
+            };
+        }
+        None
+    }
+
+    fn end(&self, dir: usize) -> Option<(&K, &V)> {
+        let mut cur = self.root;
+        if cur == NIL {
+            return None;
+        }
+        while self.node(cur).child[dir] != NIL {
+            cur = self.node(cur).child[dir];
+        }
+        let node = self.node(cur);
+        Some((&node.key, &node.value))
+    }
+
+    // hangs child below (parent, direction), or makes it the root
+    fn link(&mut self, parent: Option<(usize, usize)>, child: usize) {
+        match parent {
+            Some((p, dir)) => self.node_mut(p).child[dir] = child,
+            None => self.root = child,
+        }
+    }
+
+    // there is no size field, so this walks the subtree
+    fn size(&self, root: usize) -> usize {
+        let mut count = 0;
+        let mut stack = vec![root];
+        while let Some(i) = stack.pop() {
+            if i != NIL {
+                count += 1;
+                stack.extend(self.node(i).child);
+            }
+        }
+        count
+    }
+
+    // relinks the subtree at root into a perfectly balanced one, returns
+    // its new root
+    fn rebuild(&mut self, root: usize) -> usize {
+        let mut order = Vec::new();
+        let mut stack = Vec::new();
+        let mut cur = root;
+        while cur != NIL || !stack.is_empty() {
+            while cur != NIL {
+                stack.push(cur);
+                cur = self.node(cur).child[0];
+            }
+            let i = stack.pop().unwrap();
+            order.push(i);
+            cur = self.node(i).child[1];
+        }
+        self.build(&order)
+    }
+
+    fn build(&mut self, order: &[usize]) -> usize {
+        if order.is_empty() {
+            return NIL;
+        }
+        let mid = order.len() / 2;
+        let left = self.build(&order[..mid]);
+        let right = self.build(&order[mid + 1..]);
+        self.node_mut(order[mid]).child = [left, right];
+        order[mid]
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for ScapegoatTree<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        ScapegoatTree::insert(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        ScapegoatTree::remove(self, key)
+    }
+    fn get(&self, key: &K) -> Option<&V> {
+        ScapegoatTree::get(self, key)
+    }
+    fn first(&self) -> Option<(&K, &V)> {
+        ScapegoatTree::first(self)
+    }
+    fn last(&self) -> Option<(&K, &V)> {
+        ScapegoatTree::last(self)
+    }
+    fn len(&self) -> usize {
+        ScapegoatTree::len(self)
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for ScapegoatTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for ScapegoatTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Range<'a, K, V, R> {
+    map: &'a ScapegoatTree<K, V>,
+    stack: Vec<usize>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.map.node(self.stack.pop()?);
+        let inside = match self.range.end_bound() {
+            Bound::Unbounded => true,
+            Bound::Included(end) => node.key <= *end,
+            Bound::Excluded(end) => node.key < *end,
+        };
+        if !inside {
+            self.stack.clear();
+            return None;
+        }
+        let mut cur = node.child[1];
+        while cur != NIL {
+            self.stack.push(cur);
+            cur = self.map.node(cur).child[0];
+        }
+        Some((&node.key, &node.value))
+    }
+}
@@ -0,0 +1,118 @@
+use std::hash::Hash;
+
+use crate::adjlist::{Graph, NeighborList};
+
+// Strongly connected components, both iterative. Components come out in
+// reverse topological order of the condensation for Tarjan and in
+// topological order for Kosaraju.
+
+const NONE: usize = usize::MAX;
+
+pub fn tarjan_scc<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> Vec<Vec<T>> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let mut index = vec![NONE; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut counter = 0;
+    let mut components = Vec::new();
+    for root in 0..n {
+        if index[root] != NONE {
+            continue;
+        }
+        let mut calls = vec![(root, 0)];
+        index[root] = counter;
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&mut (v, ref mut next)) = calls.last_mut() {
+            if let Some(&(u, _)) = g.adj[v].get(*next) {
+                *next += 1;
+                if index[u] == NONE {
+                    index[u] = counter;
+                    low[u] = counter;
+                    counter += 1;
+                    stack.push(u);
+                    on_stack[u] = true;
+                    calls.push((u, 0));
+                } else if on_stack[u] {
+                    low[v] = low[v].min(index[u]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(p, _)) = calls.last() {
+                low[p] = low[p].min(low[v]);
+            }
+            if low[v] == index[v] {
+                let mut component = Vec::new();
+                loop {
+                    let w = stack.pop().unwrap();
+                    on_stack[w] = false;
+                    component.push(g.keys[w].clone());
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+pub fn kosaraju_scc<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> Vec<Vec<T>> {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    let mut order = Vec::with_capacity(n);
+    let mut seen = vec![false; n];
+    for root in 0..n {
+        if seen[root] {
+            continue;
+        }
+        seen[root] = true;
+        let mut calls = vec![(root, 0)];
+        while let Some(&mut (v, ref mut next)) = calls.last_mut() {
+            if let Some(&(u, _)) = g.adj[v].get(*next) {
+                *next += 1;
+                if !seen[u] {
+                    seen[u] = true;
+                    calls.push((u, 0));
+                }
+                continue;
+            }
+            calls.pop();
+            order.push(v);
+        }
+    }
+
+    let mut reverse = vec![Vec::new(); n];
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        for &(u, _) in nbrs.iter() {
+            reverse[u].push(v);
+        }
+    }
+    let mut assigned = vec![false; n];
+    let mut components = Vec::new();
+    for &root in order.iter().rev() {
+        if assigned[root] {
+            continue;
+        }
+        assigned[root] = true;
+        let mut component = Vec::new();
+        let mut todo = vec![root];
+        while let Some(v) = todo.pop() {
+            component.push(g.keys[v].clone());
+            for &u in reverse[v].iter() {
+                if !assigned[u] {
+                    assigned[u] = true;
+                    todo.push(u);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
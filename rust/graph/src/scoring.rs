@@ -0,0 +1,181 @@
+use crate::csr::CsrGraph;
+use crate::parallel::{fill, map_chunks};
+
+// Iterative vertex scoring. A step reads the previous scores and writes
+// the next ones, usually through par_update so every vertex is computed on
+// some thread; the engine swaps buffers and stops once the L1 change
+// between two iterations drops below the tolerance.
+
+#[derive(Debug, Clone, Copy)]
+pub struct IterationOptions {
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for IterationOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-9,
+            max_iterations: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Iteration {
+    pub scores: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+pub fn iterate_until_converged<F>(
+    init: Vec<f64>,
+    options: &IterationOptions,
+    mut step: F,
+) -> Iteration
+where
+    F: FnMut(&[f64], &mut [f64]),
+{
+    let mut cur = init;
+    let mut next = vec![0.0; cur.len()];
+    for i in 1..=options.max_iterations {
+        step(&cur, &mut next);
+        let delta = l1_distance(&cur, &next);
+        std::mem::swap(&mut cur, &mut next);
+        if delta < options.tolerance {
+            return Iteration {
+                scores: cur,
+                iterations: i,
+                converged: true,
+            };
+        }
+    }
+    Iteration {
+        scores: cur,
+        iterations: options.max_iterations,
+        converged: false,
+    }
+}
+
+// out[v] = f(v) for every vertex, in parallel
+pub fn par_update<F>(out: &mut [f64], f: F)
+where
+    F: Fn(usize) -> f64 + Sync,
+{
+    fill(out, f);
+}
+
+pub fn par_sum(values: &[f64]) -> f64 {
+    map_chunks(values, |_, chunk| chunk.iter().sum::<f64>())
+        .into_iter()
+        .sum()
+}
+
+// power iteration with uniform teleport; the rank of dangling vertices is
+// spread evenly over all vertices so the scores always sum to 1
+pub fn pagerank(graph: &CsrGraph, damping: f64, options: &IterationOptions) -> Iteration {
+    let n = graph.vertex_count();
+    if n == 0 {
+        return Iteration {
+            scores: Vec::new(),
+            iterations: 0,
+            converged: true,
+        };
+    }
+    let incoming = graph.transpose();
+    let inv_degree: Vec<f64> = (0..n)
+        .map(|v| match graph.degree(v) {
+            0 => 0.0,
+            d => 1.0 / d as f64,
+        })
+        .collect();
+    let base = (1.0 - damping) / n as f64;
+    iterate_until_converged(vec![1.0 / n as f64; n], options, |old, new| {
+        let dangling: f64 = map_chunks(old, |offset, chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| inv_degree[offset + i] == 0.0)
+                .map(|(_, r)| r)
+                .sum::<f64>()
+        })
+        .into_iter()
+        .sum();
+        let spread = base + damping * dangling / n as f64;
+        par_update(new, |v| {
+            let inflow: f64 = incoming
+                .neighbors(v)
+                .iter()
+                .map(|&u| old[u] * inv_degree[u])
+                .sum();
+            spread + damping * inflow
+        });
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct Hits {
+    pub hubs: Vec<f64>,
+    pub authorities: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+// Kleinberg's hubs and authorities, both normalized to sum 1. The engine
+// state is hubs followed by authorities; one step updates authorities from
+// the old hubs and then hubs from the new authorities.
+pub fn hits(graph: &CsrGraph, options: &IterationOptions) -> Hits {
+    let n = graph.vertex_count();
+    let incoming = graph.transpose();
+    let mut auth = vec![0.0; n];
+    let init = vec![if n == 0 { 0.0 } else { 1.0 / n as f64 }; 2 * n];
+    let result = iterate_until_converged(init, options, |old, new| {
+        let hubs = &old[..n];
+        par_update(&mut auth, |v| {
+            incoming
+                .neighbors(v)
+                .iter()
+                .map(|&u| hubs[u])
+                .fold(0.0, |acc, x| acc + x)
+        });
+        normalize(&mut auth);
+        let (new_hubs, new_auth) = new.split_at_mut(n);
+        new_auth.copy_from_slice(&auth);
+        par_update(new_hubs, |v| {
+            graph
+                .neighbors(v)
+                .iter()
+                .map(|&u| auth[u])
+                .fold(0.0, |acc, x| acc + x)
+        });
+        normalize(new_hubs);
+    });
+    let mut scores = result.scores;
+    let authorities = scores.split_off(n);
+    Hits {
+        hubs: scores,
+        authorities,
+        iterations: result.iterations,
+        converged: result.converged,
+    }
+}
+
+fn normalize(values: &mut [f64]) {
+    let total = par_sum(values);
+    if total > 0.0 {
+        for x in values.iter_mut() {
+            *x /= total;
+        }
+    }
+}
+
+fn l1_distance(a: &[f64], b: &[f64]) -> f64 {
+    let diffs: Vec<f64> = map_chunks(a, |offset, chunk| {
+        chunk
+            .iter()
+            .zip(b[offset..].iter())
+            .map(|(x, y)| (x - y).abs())
+            .sum()
+    });
+    diffs.into_iter().sum()
+}
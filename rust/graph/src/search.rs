@@ -0,0 +1,310 @@
+use std::{cmp::Ordering, ops::Range};
+
+use crate::num::Integer;
+
+// Searches: the bounds of a key in a sorted slice, and the point a
+// monotone predicate flips at, over integers or, by bisection, floats;
+// interpolation and exponential search of sorted data; ternary search for
+// the minimum of a unimodal function.
+
+// The first x in lo..hi for which pred is false, or hi if there is none.
+// pred must hold for a prefix of the range and fail for the rest; hi - lo
+// must fit in T.
+pub fn partition_point<T: Integer, F: FnMut(T) -> bool>(mut lo: T, hi: T, mut pred: F) -> T {
+    let two = T::ONE + T::ONE;
+    let mut len = if hi > lo { hi - lo } else { T::ZERO };
+    while len > T::ZERO {
+        let half = len / two;
+        let mid = lo + half;
+        if pred(mid) {
+            lo = mid + T::ONE;
+            len -= half + T::ONE;
+        } else {
+            len = half;
+        }
+    }
+    lo
+}
+
+// The first index of v at which pred is false, for pred true on a prefix.
+pub fn slice_partition_point<T, F: FnMut(&T) -> bool>(v: &[T], mut pred: F) -> usize {
+    partition_point(0, v.len(), |i| pred(&v[i]))
+}
+
+// The first index of a sorted slice whose item is not less than x, where x
+// would be inserted before any equal items.
+pub fn lower_bound<T: Ord>(v: &[T], x: &T) -> usize {
+    lower_bound_by(v, |y| y.cmp(x))
+}
+
+// f orders an item against the target, as for slice::binary_search_by
+pub fn lower_bound_by<T, F: FnMut(&T) -> Ordering>(v: &[T], mut f: F) -> usize {
+    slice_partition_point(v, |y| f(y) == Ordering::Less)
+}
+
+pub fn lower_bound_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &[T], key: &K, mut f: F) -> usize {
+    lower_bound_by(v, |y| f(y).cmp(key))
+}
+
+// The first index whose item is greater than x, after any equal items.
+pub fn upper_bound<T: Ord>(v: &[T], x: &T) -> usize {
+    upper_bound_by(v, |y| y.cmp(x))
+}
+
+pub fn upper_bound_by<T, F: FnMut(&T) -> Ordering>(v: &[T], mut f: F) -> usize {
+    slice_partition_point(v, |y| f(y) != Ordering::Greater)
+}
+
+pub fn upper_bound_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &[T], key: &K, mut f: F) -> usize {
+    upper_bound_by(v, |y| f(y).cmp(key))
+}
+
+// the indices of the items equal to x, empty where x would go if none
+pub fn equal_range<T: Ord>(v: &[T], x: &T) -> Range<usize> {
+    let lo = lower_bound(v, x);
+    lo..lo + upper_bound(&v[lo..], x)
+}
+
+// Bisection over floats, configured: it stops once the bracket is no wider
+// than the tolerance, after the iteration limit, or when no float is left
+// strictly between its ends, whichever is first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bisection {
+    tolerance: f64,
+    max_iterations: u32,
+}
+
+impl Default for Bisection {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.0,
+            max_iterations: u32::MAX,
+        }
+    }
+}
+
+impl Bisection {
+    // Bisects down to adjacent floats, without an iteration limit: that
+    // takes at most about 2100 halvings, from the widest finite bracket.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // absolute width of the final bracket
+    pub fn tolerance(mut self, eps: f64) -> Self {
+        self.tolerance = eps.max(0.0);
+        self
+    }
+
+    pub fn max_iterations(mut self, n: u32) -> Self {
+        self.max_iterations = n;
+        self
+    }
+
+    // Where pred flips from true to false in [lo, hi], for pred true up to
+    // some point and false after: the final bracket's midpoint.
+    pub fn partition_point<F: FnMut(f64) -> bool>(&self, lo: f64, hi: f64, mut pred: F) -> f64 {
+        self.narrow(lo, hi, |x| Some(pred(x)))
+    }
+
+    // A zero of a continuous f in [lo, hi], where f(lo) and f(hi) differ in
+    // sign; None if they don't or either is NaN.
+    pub fn root<F: FnMut(f64) -> f64>(&self, lo: f64, hi: f64, mut f: F) -> Option<f64> {
+        let (lo, hi) = (lo.min(hi), lo.max(hi));
+        let (flo, fhi) = (f(lo), f(hi));
+        if flo == 0.0 {
+            return Some(lo);
+        }
+        if fhi == 0.0 {
+            return Some(hi);
+        }
+        if flo.is_nan() || fhi.is_nan() || (flo < 0.0) == (fhi < 0.0) {
+            return None;
+        }
+        // lo's side of the root is where f has lo's sign
+        Some(self.narrow(lo, hi, |x| {
+            let y = f(x);
+            (y != 0.0).then_some((y < 0.0) == (flo < 0.0))
+        }))
+    }
+
+    // halves [lo, hi] towards where side turns false, or stops at an x
+    // side has no answer for
+    fn narrow(&self, lo: f64, hi: f64, mut side: impl FnMut(f64) -> Option<bool>) -> f64 {
+        let (mut lo, mut hi) = (lo.min(hi), lo.max(hi));
+        for _ in 0..self.max_iterations {
+            let mid = lo.midpoint(hi);
+            if half_width(lo, hi) <= self.tolerance / 2.0 || mid <= lo || mid >= hi {
+                break;
+            }
+            match side(mid) {
+                Some(true) => lo = mid,
+                Some(false) => hi = mid,
+                None => return mid,
+            }
+        }
+        lo.midpoint(hi)
+    }
+}
+
+// (hi - lo) / 2, finite even where hi - lo overflows
+fn half_width(lo: f64, hi: f64) -> f64 {
+    hi / 2.0 - lo / 2.0
+}
+
+// bisection with the defaults
+pub fn bisect<F: FnMut(f64) -> bool>(lo: f64, hi: f64, pred: F) -> f64 {
+    Bisection::new().partition_point(lo, hi, pred)
+}
+
+// Interpolation search of a sorted slice of integers: probes where x would
+// be if the keys were spread evenly between the ends of the range, which
+// on uniformly distributed keys takes O(log log n) probes. A probe that
+// doesn't halve the range is followed by a bisection, so skewed keys cost
+// at most twice binary search's O(log n). Ok with the index of an item
+// equal to x, or Err with where x would be inserted, as binary_search.
+pub fn interpolation_search<T: Ord + Copy + Into<i128>>(v: &[T], x: T) -> Result<usize, usize> {
+    let target: i128 = x.into();
+    let (mut lo, mut hi) = (0, v.len());
+    let mut bisect = false;
+    while lo < hi {
+        let (first, last) = (v[lo], v[hi - 1]);
+        if x < first {
+            return Err(lo);
+        }
+        if x > last {
+            return Err(hi);
+        }
+        let (a, b) = (first.into(), last.into());
+        let mid = if bisect || a == b {
+            lo + (hi - lo) / 2
+        } else {
+            let at = (target - a) as f64 / (b - a) as f64 * (hi - 1 - lo) as f64;
+            (lo + at as usize).min(hi - 1)
+        };
+        let before = hi - lo;
+        match v[mid].cmp(&x) {
+            Ordering::Less => lo = mid + 1,
+            Ordering::Greater => hi = mid,
+            Ordering::Equal => return Ok(mid),
+        }
+        bisect = !bisect && 2 * (hi - lo) > before;
+    }
+    Err(lo)
+}
+
+// Exponential search of a sorted slice: doubles a bound from the front
+// until it passes x, then binary searches the last step, O(log i) for x
+// found at or inserted before index i. Results as binary_search.
+pub fn exponential_search<T: Ord>(v: &[T], x: &T) -> Result<usize, usize> {
+    exponential_search_by(v, |y| y.cmp(x))
+}
+
+pub fn exponential_search_by<T, F: FnMut(&T) -> Ordering>(
+    v: &[T],
+    mut f: F,
+) -> Result<usize, usize> {
+    let i = exponential_partition_point(0, |i| i < v.len() && f(&v[i]) == Ordering::Less)
+        .expect("the predicate fails at v.len()");
+    match v.get(i) {
+        Some(y) if f(y) == Ordering::Equal => Ok(i),
+        _ => Err(i),
+    }
+}
+
+// The first x from lo on for which pred is false, for pred true on a
+// prefix of lo..: found without an upper bound, by probing lo + 1, lo + 3,
+// lo + 7, ... until pred fails and bisecting the last step, O(log d) for
+// an answer d past lo. This searches sorted sequences of unknown length,
+// streams read up to the probe, or monotone functions. None if pred holds
+// all the way to T::MAX.
+pub fn exponential_partition_point<T: Integer, F: FnMut(T) -> bool>(
+    lo: T,
+    mut pred: F,
+) -> Option<T> {
+    if !pred(lo) {
+        return Some(lo);
+    }
+    let two = T::ONE + T::ONE;
+    // pred holds at good and fails at bad
+    let (mut good, mut step) = (lo, T::ONE);
+    let bad = loop {
+        if good >= T::MAX - step {
+            if pred(T::MAX) {
+                return None;
+            }
+            break T::MAX;
+        }
+        let probe = good + step;
+        if !pred(probe) {
+            break probe;
+        }
+        good = probe;
+        if step <= T::MAX / two {
+            step = step * two;
+        }
+    };
+    Some(partition_point(good + T::ONE, bad, pred))
+}
+
+// Ternary search for the minimum of f over lo..=hi, for f strictly
+// decreasing and then strictly increasing there (either part may be
+// empty): each round compares f at the thirds and drops the third that
+// can't hold the minimum, O(log n) evaluations. Maximize by minimizing
+// Reverse(f(x)) or -f(x). hi - lo must fit in T.
+pub fn ternary_search_min<T, K, F>(mut lo: T, mut hi: T, mut f: F) -> T
+where
+    T: Integer,
+    K: PartialOrd,
+    F: FnMut(T) -> K,
+{
+    assert!(lo <= hi, "empty range {lo:?}..={hi:?}");
+    let three = T::ONE + T::ONE + T::ONE;
+    while hi - lo >= three {
+        let third = (hi - lo) / three;
+        let (m1, m2) = (lo + third, hi - third);
+        if f(m1) < f(m2) {
+            hi = m2 - T::ONE;
+        } else {
+            lo = m1 + T::ONE;
+        }
+    }
+    // two or three left
+    let mut best = (lo, f(lo));
+    while best.0 < hi {
+        let x = best.0 + T::ONE;
+        let y = f(x);
+        if y < best.1 {
+            best = (x, y);
+        } else {
+            break;
+        }
+    }
+    best.0
+}
+
+// The same over floats, narrowing [lo, hi] by thirds until it is no wider
+// than the tolerance or stops shrinking, and returning its midpoint.
+pub fn ternary_search_min_f64<F: FnMut(f64) -> f64>(
+    lo: f64,
+    hi: f64,
+    tolerance: f64,
+    mut f: F,
+) -> f64 {
+    let (mut lo, mut hi) = (lo.min(hi), lo.max(hi));
+    while half_width(lo, hi) > tolerance / 2.0 {
+        // finite, as half_width
+        let third = hi / 3.0 - lo / 3.0;
+        let (m1, m2) = (lo + third, hi - third);
+        let (was_lo, was_hi) = (lo, hi);
+        if f(m1) < f(m2) {
+            hi = m2;
+        } else {
+            lo = m1;
+        }
+        if (lo, hi) == (was_lo, was_hi) {
+            break;
+        }
+    }
+    lo.midpoint(hi)
+}
@@ -0,0 +1,428 @@
+use std::{marker::PhantomData, ops::Range};
+
+use crate::num::{gcd, Integer};
+
+// An associative operation with an identity. combine does not have to be
+// commutative, queries combine the values left to right.
+pub trait Monoid<T> {
+    fn identity() -> T;
+    fn combine(a: &T, b: &T) -> T;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sum;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Min;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Max;
+
+// gcd of the absolute values, 0 for an empty range
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gcd;
+
+impl<T: Integer> Monoid<T> for Sum {
+    fn identity() -> T {
+        T::ZERO
+    }
+    fn combine(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+impl<T: Integer> Monoid<T> for Min {
+    fn identity() -> T {
+        T::MAX
+    }
+    fn combine(a: &T, b: &T) -> T {
+        *a.min(b)
+    }
+}
+
+impl<T: Integer> Monoid<T> for Max {
+    fn identity() -> T {
+        T::MIN
+    }
+    fn combine(a: &T, b: &T) -> T {
+        *a.max(b)
+    }
+}
+
+impl<T: Integer> Monoid<T> for Gcd {
+    fn identity() -> T {
+        T::ZERO
+    }
+    fn combine(a: &T, b: &T) -> T {
+        gcd(*a, *b)
+    }
+}
+
+// Bottom-up segment tree over n values: leaves at n..2n, node i combines
+// 2i and 2i+1. update and query are O(log n).
+// e.g. SegmentTree::<i64, Min>::from(vec![5, 2, 7]).query(1..3) == 2
+#[derive(Debug, Clone)]
+pub struct SegmentTree<T, Op> {
+    n: usize,
+    tree: Vec<T>,
+    op: PhantomData<Op>,
+}
+
+impl<T: Clone, Op: Monoid<T>> SegmentTree<T, Op> {
+    // n identity values
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            tree: vec![Op::identity(); 2 * n],
+            op: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn get(&self, i: usize) -> &T {
+        assert!(i < self.n, "index {i} out of range for length {}", self.n);
+        &self.tree[self.n + i]
+    }
+
+    pub fn update(&mut self, i: usize, value: T) {
+        assert!(i < self.n, "index {i} out of range for length {}", self.n);
+        let mut i = i + self.n;
+        self.tree[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = Op::combine(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+
+    // combination of the values in range, the identity when it is empty
+    pub fn query(&self, range: Range<usize>) -> T {
+        let Range { start, end } = range;
+        assert!(
+            start <= end && end <= self.n,
+            "range {start}..{end} out of range for length {}",
+            self.n
+        );
+        let mut left = Op::identity();
+        let mut right = Op::identity();
+        let (mut l, mut r) = (start + self.n, end + self.n);
+        while l < r {
+            if l % 2 == 1 {
+                left = Op::combine(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                right = Op::combine(&self.tree[r], &right);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        Op::combine(&left, &right)
+    }
+
+    pub fn all(&self) -> T {
+        self.query(0..self.n)
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>> From<Vec<T>> for SegmentTree<T, Op> {
+    // O(n)
+    fn from(values: Vec<T>) -> Self {
+        let n = values.len();
+        let mut tree = Vec::with_capacity(2 * n);
+        tree.resize(n, Op::identity());
+        tree.extend(values);
+        for i in (1..n).rev() {
+            tree[i] = Op::combine(&tree[2 * i], &tree[2 * i + 1]);
+        }
+        Self {
+            n,
+            tree,
+            op: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>> FromIterator<T> for SegmentTree<T, Op> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+// Range updates for a lazy segment tree: a tag is an update waiting to be
+// pushed down to the children of a node. no_tag must leave values alone.
+pub trait LazyOp<T>: Monoid<T> {
+    type Tag: Clone;
+
+    fn no_tag() -> Self::Tag;
+    // value of a range of len items after tag is applied to each of them
+    fn apply(tag: &Self::Tag, value: &T, len: usize) -> T;
+    // the tag that applies older and then newer
+    fn compose(newer: &Self::Tag, older: &Self::Tag) -> Self::Tag;
+}
+
+// range add, range sum
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AddSum;
+
+// range add, range min
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AddMin;
+
+// range assign, range sum
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AssignSum;
+
+// range assign, range min
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AssignMin;
+
+macro_rules! monoid_as {
+    ($($lazy:ty => $base:ty),*) => {$(
+        impl<T: Integer> Monoid<T> for $lazy {
+            fn identity() -> T {
+                <$base as Monoid<T>>::identity()
+            }
+            fn combine(a: &T, b: &T) -> T {
+                <$base as Monoid<T>>::combine(a, b)
+            }
+        }
+    )*};
+}
+
+monoid_as!(AddSum => Sum, AddMin => Min, AssignSum => Sum, AssignMin => Min);
+
+impl<T: Integer> LazyOp<T> for AddSum {
+    type Tag = T;
+    fn no_tag() -> T {
+        T::ZERO
+    }
+    fn apply(tag: &T, value: &T, len: usize) -> T {
+        *value + *tag * T::from_usize(len)
+    }
+    fn compose(newer: &T, older: &T) -> T {
+        *newer + *older
+    }
+}
+
+impl<T: Integer> LazyOp<T> for AddMin {
+    type Tag = T;
+    fn no_tag() -> T {
+        T::ZERO
+    }
+    fn apply(tag: &T, value: &T, _len: usize) -> T {
+        *value + *tag
+    }
+    fn compose(newer: &T, older: &T) -> T {
+        *newer + *older
+    }
+}
+
+impl<T: Integer> LazyOp<T> for AssignSum {
+    type Tag = Option<T>;
+    fn no_tag() -> Option<T> {
+        None
+    }
+    fn apply(tag: &Option<T>, value: &T, len: usize) -> T {
+        tag.map_or(*value, |v| v * T::from_usize(len))
+    }
+    fn compose(newer: &Option<T>, older: &Option<T>) -> Option<T> {
+        newer.or(*older)
+    }
+}
+
+impl<T: Integer> LazyOp<T> for AssignMin {
+    type Tag = Option<T>;
+    fn no_tag() -> Option<T> {
+        None
+    }
+    fn apply(tag: &Option<T>, value: &T, _len: usize) -> T {
+        tag.unwrap_or(*value)
+    }
+    fn compose(newer: &Option<T>, older: &Option<T>) -> Option<T> {
+        newer.or(*older)
+    }
+}
+
+// Top-down segment tree with lazy tags, update and query are O(log n).
+// Nodes only cover non-empty ranges, so the identity is never updated.
+// e.g. LazySegmentTree::<i64, AddSum>::from(vec![0; 8]).update(2..5, 3)
+pub struct LazySegmentTree<T, Op: LazyOp<T>> {
+    n: usize,
+    tree: Vec<T>,
+    tags: Vec<Op::Tag>,
+}
+
+impl<T: Clone, Op: LazyOp<T>> Clone for LazySegmentTree<T, Op> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            tree: self.tree.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+}
+
+impl<T: Clone + std::fmt::Debug, Op: LazyOp<T>> std::fmt::Debug for LazySegmentTree<T, Op> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazySegmentTree")
+            .field("len", &self.n)
+            .field("all", &self.all())
+            .finish()
+    }
+}
+
+impl<T: Clone, Op: LazyOp<T>> LazySegmentTree<T, Op> {
+    pub fn filled(n: usize, value: T) -> Self {
+        Self::from(vec![value; n])
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn get(&self, i: usize) -> T {
+        assert!(i < self.n, "index {i} out of range for length {}", self.n);
+        self.query(i..i + 1)
+    }
+
+    // applies tag to every value in range
+    pub fn update(&mut self, range: Range<usize>, tag: Op::Tag) {
+        self.check(&range);
+        if range.start < range.end {
+            self.update_node(1, 0, self.n, &range, &tag);
+        }
+    }
+
+    // combination of the values in range, the identity when it is empty.
+    // Pending tags are applied on the way down without being pushed, so
+    // queries only need &self.
+    pub fn query(&self, range: Range<usize>) -> T {
+        self.check(&range);
+        if range.start == range.end {
+            return Op::identity();
+        }
+        self.query_node(1, 0, self.n, &range, Op::no_tag())
+    }
+
+    pub fn all(&self) -> T {
+        if self.n == 0 {
+            Op::identity()
+        } else {
+            self.tree[1].clone()
+        }
+    }
+
+    fn check(&self, range: &Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.n,
+            "range {}..{} out of range for length {}",
+            range.start,
+            range.end,
+            self.n
+        );
+    }
+
+    fn build(&mut self, node: usize, lo: usize, hi: usize, values: &[T]) {
+        if hi - lo == 1 {
+            self.tree[node] = values[lo].clone();
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.build(2 * node, lo, mid, values);
+        self.build(2 * node + 1, mid, hi, values);
+        self.tree[node] = Op::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    fn apply_node(&mut self, node: usize, len: usize, tag: &Op::Tag) {
+        self.tree[node] = Op::apply(tag, &self.tree[node], len);
+        self.tags[node] = Op::compose(tag, &self.tags[node]);
+    }
+
+    fn push(&mut self, node: usize, lo: usize, hi: usize) {
+        let tag = std::mem::replace(&mut self.tags[node], Op::no_tag());
+        let mid = (lo + hi) / 2;
+        self.apply_node(2 * node, mid - lo, &tag);
+        self.apply_node(2 * node + 1, hi - mid, &tag);
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        range: &Range<usize>,
+        tag: &Op::Tag,
+    ) {
+        if range.end <= lo || hi <= range.start {
+            return;
+        }
+        if range.start <= lo && hi <= range.end {
+            self.apply_node(node, hi - lo, tag);
+            return;
+        }
+        self.push(node, lo, hi);
+        let mid = (lo + hi) / 2;
+        self.update_node(2 * node, lo, mid, range, tag);
+        self.update_node(2 * node + 1, mid, hi, range, tag);
+        self.tree[node] = Op::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    // pending is the composition of the tags above node
+    fn query_node(
+        &self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        range: &Range<usize>,
+        pending: Op::Tag,
+    ) -> T {
+        if range.start <= lo && hi <= range.end {
+            return Op::apply(&pending, &self.tree[node], hi - lo);
+        }
+        let pending = Op::compose(&pending, &self.tags[node]);
+        let mid = (lo + hi) / 2;
+        if range.end <= mid {
+            self.query_node(2 * node, lo, mid, range, pending)
+        } else if mid <= range.start {
+            self.query_node(2 * node + 1, mid, hi, range, pending)
+        } else {
+            let left = self.query_node(2 * node, lo, mid, range, pending.clone());
+            let right = self.query_node(2 * node + 1, mid, hi, range, pending);
+            Op::combine(&left, &right)
+        }
+    }
+}
+
+impl<T: Clone, Op: LazyOp<T>> From<Vec<T>> for LazySegmentTree<T, Op> {
+    fn from(values: Vec<T>) -> Self {
+        let n = values.len();
+        let size = if n == 0 { 0 } else { 4 * n };
+        let mut tree = Self {
+            n,
+            tree: vec![Op::identity(); size],
+            tags: vec![Op::no_tag(); size],
+        };
+        if n > 0 {
+            tree.build(1, 0, n, &values);
+        }
+        tree
+    }
+}
+
+impl<T: Clone, Op: LazyOp<T>> FromIterator<T> for LazySegmentTree<T, Op> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
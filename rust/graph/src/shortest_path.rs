@@ -0,0 +1,187 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::indexed_heap::IndexedHeap;
+use crate::priority_queue::PriorityQueue;
+
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<T> {
+    pub source: T,
+    pub dist: HashMap<T, i64>,
+    pub prev: HashMap<T, T>,
+}
+
+impl<T: Hash + Eq + Clone> ShortestPaths<T> {
+    // source..=target, None when target is unreachable
+    pub fn path_to(&self, target: &T) -> Option<Vec<T>> {
+        if !self.dist.contains_key(target) {
+            return None;
+        }
+        let mut path = vec![target.clone()];
+        let mut cur = target;
+        while let Some(p) = self.prev.get(cur) {
+            path.push(p.clone());
+            cur = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// edge weights must be non-negative
+pub fn dijkstra<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+    source: &T,
+) -> ShortestPaths<T> {
+    let mut sp = ShortestPaths {
+        source: source.clone(),
+        dist: HashMap::new(),
+        prev: HashMap::new(),
+    };
+    let g = graph.indexed();
+    let Some(&s) = g.index.get(source) else {
+        return sp;
+    };
+    let (dist, prev) = dijkstra_indexed(&g.adj, s);
+    for v in 0..g.keys.len() {
+        if let Some(d) = dist[v] {
+            sp.dist.insert(g.keys[v].clone(), d);
+        }
+        if let Some(p) = prev[v] {
+            sp.prev.insert(g.keys[v].clone(), g.keys[p].clone());
+        }
+    }
+    sp
+}
+
+// dijkstra on any min-ordered queue of (distance, vertex): instead of
+// decreasing keys every improvement is pushed and stale entries are skipped
+// when popped. Meant for comparing the heaps, dijkstra is faster.
+pub fn dijkstra_with<T, S, Q>(graph: &Graph<T, S>, source: &T, mut queue: Q) -> ShortestPaths<T>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+    Q: PriorityQueue<(i64, usize)>,
+{
+    let mut sp = ShortestPaths {
+        source: source.clone(),
+        dist: HashMap::new(),
+        prev: HashMap::new(),
+    };
+    let g = graph.indexed();
+    let Some(&s) = g.index.get(source) else {
+        return sp;
+    };
+    let n = g.keys.len();
+    let mut dist: Vec<Option<i64>> = vec![None; n];
+    let mut prev = vec![None; n];
+    dist[s] = Some(0);
+    queue.push((0, s));
+    while let Some((d, v)) = queue.pop() {
+        if dist[v].is_some_and(|best| d > best) {
+            continue;
+        }
+        for &(u, wt) in g.adj[v].iter() {
+            let nd = d + wt as i64;
+            if dist[u].is_none_or(|best| nd < best) {
+                dist[u] = Some(nd);
+                prev[u] = Some(v);
+                queue.push((nd, u));
+            }
+        }
+    }
+    for v in 0..n {
+        if let Some(d) = dist[v] {
+            sp.dist.insert(g.keys[v].clone(), d);
+        }
+        if let Some(p) = prev[v] {
+            sp.prev.insert(g.keys[v].clone(), g.keys[p].clone());
+        }
+    }
+    sp
+}
+
+pub(crate) fn dijkstra_indexed(
+    adj: &[Vec<(usize, i32)>],
+    source: usize,
+) -> (Vec<Option<i64>>, Vec<Option<usize>>) {
+    let mut dist = vec![None; adj.len()];
+    let mut prev = vec![None; adj.len()];
+    let mut heap: IndexedHeap<i64> = IndexedHeap::new(adj.len());
+    dist[source] = Some(0);
+    heap.push(source, 0);
+    while let Some((v, d)) = heap.pop() {
+        for &(u, wt) in adj[v].iter() {
+            let nd = d + wt as i64;
+            if dist[u].is_none_or(|best| nd < best) {
+                dist[u] = Some(nd);
+                prev[u] = Some(v);
+                heap.push_or_decrease(u, nd);
+            }
+        }
+    }
+    (dist, prev)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegativeCycle;
+
+impl std::fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "negative cycle reachable from the source")
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+// handles negative weights, O(n m)
+pub fn bellman_ford<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+    source: &T,
+) -> Result<ShortestPaths<T>, NegativeCycle> {
+    let mut sp = ShortestPaths {
+        source: source.clone(),
+        dist: HashMap::new(),
+        prev: HashMap::new(),
+    };
+    let g = graph.indexed();
+    let Some(&s) = g.index.get(source) else {
+        return Ok(sp);
+    };
+    let n = g.keys.len();
+    let mut dist: Vec<Option<i64>> = vec![None; n];
+    let mut prev = vec![None; n];
+    dist[s] = Some(0);
+    for round in 0..n {
+        let mut changed = false;
+        for v in 0..n {
+            let Some(d) = dist[v] else {
+                continue;
+            };
+            for &(u, wt) in g.adj[v].iter() {
+                let nd = d + wt as i64;
+                if dist[u].is_none_or(|best| nd < best) {
+                    dist[u] = Some(nd);
+                    prev[u] = Some(v);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+        // still relaxing after n - 1 full rounds
+        if round == n - 1 {
+            return Err(NegativeCycle);
+        }
+    }
+    for v in 0..n {
+        if let Some(d) = dist[v] {
+            sp.dist.insert(g.keys[v].clone(), d);
+        }
+        if let Some(p) = prev[v] {
+            sp.prev.insert(g.keys[v].clone(), g.keys[p].clone());
+        }
+    }
+    Ok(sp)
+}
@@ -0,0 +1,403 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    ops::{Bound, RangeBounds, RangeFull},
+};
+
+use crate::ordered_map::OrderedMap;
+use crate::rng::{Rng, SplitMix64};
+
+// Ordered map on a skip list: every entry is linked into level 0 and, with
+// probability 1/2 per level, into the levels above it, so searches skip
+// ahead on the sparse upper levels. O(log n) expected per operation. Nodes
+// live in one Vec and link to each other by index.
+#[derive(Clone)]
+pub struct SkipListMap<K, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    // first node on each level
+    head: Vec<usize>,
+    // number of levels in use
+    level: usize,
+    len: usize,
+    rng: SplitMix64,
+}
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Vec<usize>,
+}
+
+const NIL: usize = usize::MAX;
+const DEFAULT_MAX_LEVEL: usize = 32;
+
+impl<K: Ord, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_config(DEFAULT_MAX_LEVEL, 0x5eed)
+    }
+
+    // max_level bounds the tower height, about log2 of the expected size is
+    // enough; seed fixes the tower heights for reproducible layouts
+    pub fn with_config(max_level: usize, seed: u64) -> Self {
+        assert!(max_level >= 1, "a skip list needs at least one level");
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: vec![NIL; max_level],
+            level: 0,
+            len: 0,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let preds = self.predecessors(&key);
+        let found = self.next(preds[0], 0);
+        if found != NIL && self.node(found).key == key {
+            return Some(std::mem::replace(
+                &mut self.nodes[found].as_mut().unwrap().value,
+                value,
+            ));
+        }
+        let height = self.random_height();
+        let next = (0..height).map(|l| self.next(preds[l], l)).collect();
+        let node = self.alloc(Node { key, value, next });
+        for (l, &pred) in preds.iter().enumerate().take(height) {
+            self.set_next(pred, l, node);
+        }
+        self.level = self.level.max(height);
+        self.len += 1;
+        None
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).map(|i| &self.node(i).value)
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let i = self.find(key)?;
+        Some(&mut self.nodes[i].as_mut().unwrap().value)
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let preds = self.predecessors(key);
+        let target = self.next(preds[0], 0);
+        if target == NIL || self.node(target).key.borrow() != key {
+            return None;
+        }
+        let node = self.nodes[target].take().unwrap();
+        for (l, &next) in node.next.iter().enumerate() {
+            self.set_next(preds[l], l, next);
+        }
+        while self.level > 0 && self.head[self.level - 1] == NIL {
+            self.level -= 1;
+        }
+        self.free.push(target);
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.entry(self.head[0])
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut cur = NIL;
+        for l in (0..self.level).rev() {
+            while self.next(cur, l) != NIL {
+                cur = self.next(cur, l);
+            }
+        }
+        self.entry(cur)
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+
+    // entries with keys in range, in key order
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let start = match range.start_bound() {
+            Bound::Unbounded => self.head[0],
+            Bound::Included(k) => self.lower_bound(|x| x < k),
+            Bound::Excluded(k) => self.lower_bound(|x| x <= k),
+        };
+        Range {
+            map: self,
+            cur: start,
+            range,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let max_level = self.head.len();
+        self.nodes.clear();
+        self.free.clear();
+        self.head = vec![NIL; max_level];
+        self.level = 0;
+        self.len = 0;
+    }
+
+    fn node(&self, i: usize) -> &Node<K, V> {
+        self.nodes[i].as_ref().unwrap()
+    }
+
+    fn entry(&self, i: usize) -> Option<(&K, &V)> {
+        (i != NIL).then(|| {
+            let n = self.node(i);
+            (&n.key, &n.value)
+        })
+    }
+
+    // successor of pred on level l, pred NIL is the head
+    fn next(&self, pred: usize, l: usize) -> usize {
+        if pred == NIL {
+            self.head[l]
+        } else {
+            self.node(pred).next[l]
+        }
+    }
+
+    fn set_next(&mut self, pred: usize, l: usize, to: usize) {
+        if pred == NIL {
+            self.head[l] = to;
+        } else {
+            self.nodes[pred].as_mut().unwrap().next[l] = to;
+        }
+    }
+
+    // the last node before key on every level, NIL for the head
+    fn predecessors<Q: Ord + ?Sized>(&self, key: &Q) -> Vec<usize>
+    where
+        K: Borrow<Q>,
+    {
+        let mut preds = vec![NIL; self.head.len()];
+        let mut cur = NIL;
+        for l in (0..self.level).rev() {
+            loop {
+                let next = self.next(cur, l);
+                if next == NIL || self.node(next).key.borrow().cmp(key) != Ordering::Less {
+                    break;
+                }
+                cur = next;
+            }
+            preds[l] = cur;
+        }
+        preds
+    }
+
+    // first node whose key does not satisfy before
+    fn lower_bound(&self, before: impl Fn(&K) -> bool) -> usize {
+        let mut cur = NIL;
+        for l in (0..self.level).rev() {
+            loop {
+                let next = self.next(cur, l);
+                if next == NIL || !before(&self.node(next).key) {
+                    break;
+                }
+                cur = next;
+            }
+        }
+        self.next(cur, 0)
+    }
+
+    fn find<Q: Ord + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        let i = self.lower_bound(|k| k.borrow() < key);
+        (i != NIL && self.node(i).key.borrow() == key).then_some(i)
+    }
+
+    fn random_height(&mut self) -> usize {
+        (1 + self.rng.next_u64().trailing_ones() as usize).min(self.head.len())
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SkipListMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for SkipListMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        SkipListMap::insert(self, key, value)
+    }
+    fn remove(&mut self, key: &K) -> Option<V> {
+        SkipListMap::remove(self, key)
+    }
+    fn get(&self, key: &K) -> Option<&V> {
+        SkipListMap::get(self, key)
+    }
+    fn first(&self) -> Option<(&K, &V)> {
+        SkipListMap::first(self)
+    }
+    fn last(&self) -> Option<(&K, &V)> {
+        SkipListMap::last(self)
+    }
+    fn len(&self) -> usize {
+        SkipListMap::len(self)
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipListMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Range<'a, K, V, R> {
+    map: &'a SkipListMap<K, V>,
+    cur: usize,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == NIL {
+            return None;
+        }
+        let node = self.map.node(self.cur);
+        let inside = match self.range.end_bound() {
+            Bound::Unbounded => true,
+            Bound::Included(end) => node.key <= *end,
+            Bound::Excluded(end) => node.key < *end,
+        };
+        if !inside {
+            self.cur = NIL;
+            return None;
+        }
+        self.cur = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+// Ordered set on top of SkipListMap.
+#[derive(Clone)]
+pub struct SkipListSet<K> {
+    map: SkipListMap<K, ()>,
+}
+
+impl<K: Ord> Default for SkipListSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> SkipListSet<K> {
+    pub fn new() -> Self {
+        Self {
+            map: SkipListMap::new(),
+        }
+    }
+
+    pub fn with_config(max_level: usize, seed: u64) -> Self {
+        Self {
+            map: SkipListMap::with_config(max_level, seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // returns whether key was new
+    pub fn insert(&mut self, key: K) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    pub fn contains<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &K> + '_ {
+        self.map.iter().map(|(k, _)| k)
+    }
+
+    pub fn range<'a, R: RangeBounds<K> + 'a>(&'a self, range: R) -> impl Iterator<Item = &'a K> {
+        self.map.range(range).map(|(k, _)| k)
+    }
+}
+
+impl<K: Ord + fmt::Debug> fmt::Debug for SkipListSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord> FromIterator<K> for SkipListSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for k in iter {
+            set.insert(k);
+        }
+        set
+    }
+}
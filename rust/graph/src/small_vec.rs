@@ -0,0 +1,237 @@
+use std::{
+    fmt,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+    ptr, slice,
+};
+
+// A vector that keeps up to N items inline and moves them to the heap once
+// it grows past that. Used as neighbor storage for graphs where most
+// vertices have a handful of edges, which then need no allocation at all.
+
+pub struct SmallVec<A, const N: usize> {
+    data: Data<A, N>,
+}
+
+enum Data<A, const N: usize> {
+    // buf[..len] is initialized
+    Inline {
+        len: usize,
+        buf: [MaybeUninit<A>; N],
+    },
+    Heap(Vec<A>),
+}
+
+impl<A, const N: usize> SmallVec<A, N> {
+    pub fn new() -> Self {
+        Self {
+            data: Data::Inline {
+                len: 0,
+                buf: [const { MaybeUninit::uninit() }; N],
+            },
+        }
+    }
+
+    pub fn inline_capacity(&self) -> usize {
+        N
+    }
+
+    // true once the items live on the heap
+    pub fn spilled(&self) -> bool {
+        matches!(self.data, Data::Heap(_))
+    }
+
+    // allocated bytes, zero while the items are inline
+    pub fn heap_bytes(&self) -> usize {
+        match &self.data {
+            Data::Inline { .. } => 0,
+            Data::Heap(v) => v.capacity() * std::mem::size_of::<A>(),
+        }
+    }
+
+    pub fn push(&mut self, item: A) {
+        match &mut self.data {
+            Data::Inline { len, buf } if *len < N => {
+                buf[*len].write(item);
+                *len += 1;
+            }
+            Data::Inline { len, buf } => {
+                let mut heap = Vec::with_capacity(2 * N.max(1));
+                let n = std::mem::take(len);
+                for slot in buf[..n].iter() {
+                    // each item is moved out exactly once, len is already 0
+                    heap.push(unsafe { slot.assume_init_read() });
+                }
+                heap.push(item);
+                self.data = Data::Heap(heap);
+            }
+            Data::Heap(v) => v.push(item),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<A> {
+        match &mut self.data {
+            Data::Inline { len: 0, .. } => None,
+            Data::Inline { len, buf } => {
+                *len -= 1;
+                Some(unsafe { buf[*len].assume_init_read() })
+            }
+            Data::Heap(v) => v.pop(),
+        }
+    }
+
+    // removes and returns the item at index, shifting the tail left
+    pub fn remove(&mut self, index: usize) -> A {
+        match &mut self.data {
+            Data::Inline { len, buf } => {
+                assert!(index < *len, "index {index} out of range for length {len}");
+                unsafe {
+                    let p = buf.as_mut_ptr().add(index);
+                    let item = (*p).assume_init_read();
+                    ptr::copy(p.add(1), p, *len - index - 1);
+                    *len -= 1;
+                    item
+                }
+            }
+            Data::Heap(v) => v.remove(index),
+        }
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        match &mut self.data {
+            Data::Inline { len, buf } => {
+                if new_len < *len {
+                    let old = std::mem::replace(len, new_len);
+                    for slot in buf[new_len..old].iter_mut() {
+                        unsafe { slot.assume_init_drop() };
+                    }
+                }
+            }
+            Data::Heap(v) => v.truncate(new_len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn retain<F: FnMut(&A) -> bool>(&mut self, mut keep: F) {
+        match &mut self.data {
+            Data::Inline { len, buf } => {
+                // len is 0 while items are moved so a panic in keep only
+                // leaks the items instead of dropping any twice
+                let n = std::mem::take(len);
+                let mut kept = 0;
+                for i in 0..n {
+                    unsafe {
+                        if keep(buf[i].assume_init_ref()) {
+                            if i != kept {
+                                let item = buf[i].assume_init_read();
+                                buf[kept].write(item);
+                            }
+                            kept += 1;
+                        } else {
+                            buf[i].assume_init_drop();
+                        }
+                    }
+                }
+                *len = kept;
+            }
+            Data::Heap(v) => v.retain(keep),
+        }
+    }
+
+    pub fn into_vec(mut self) -> Vec<A> {
+        match &mut self.data {
+            Data::Inline { len, buf } => {
+                let n = std::mem::take(len);
+                buf[..n]
+                    .iter()
+                    .map(|slot| unsafe { slot.assume_init_read() })
+                    .collect()
+            }
+            Data::Heap(v) => std::mem::take(v),
+        }
+    }
+}
+
+impl<A, const N: usize> Drop for SmallVec<A, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<A, const N: usize> Deref for SmallVec<A, N> {
+    type Target = [A];
+
+    fn deref(&self) -> &[A] {
+        match &self.data {
+            Data::Inline { len, buf } => unsafe {
+                slice::from_raw_parts(buf.as_ptr() as *const A, *len)
+            },
+            Data::Heap(v) => v,
+        }
+    }
+}
+
+impl<A, const N: usize> DerefMut for SmallVec<A, N> {
+    fn deref_mut(&mut self) -> &mut [A] {
+        match &mut self.data {
+            Data::Inline { len, buf } => unsafe {
+                slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut A, *len)
+            },
+            Data::Heap(v) => v,
+        }
+    }
+}
+
+impl<A, const N: usize> Default for SmallVec<A, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Clone, const N: usize> Clone for SmallVec<A, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<A: fmt::Debug, const N: usize> fmt::Debug for SmallVec<A, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<A: PartialEq, const N: usize> PartialEq for SmallVec<A, N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<A: Eq, const N: usize> Eq for SmallVec<A, N> {}
+
+impl<A, const N: usize> Extend<A> for SmallVec<A, N> {
+    fn extend<I: IntoIterator<Item = A>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<A, const N: usize> FromIterator<A> for SmallVec<A, N> {
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<'a, A, const N: usize> IntoIterator for &'a SmallVec<A, N> {
+    type Item = &'a A;
+    type IntoIter = slice::Iter<'a, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,75 @@
+// Sorting algorithms over slices, one submodule each, generic over Ord
+// with _by and _by_key variants like the std sorts, and an external sort
+// for record streams too large for memory.
+
+use std::cmp::Ordering;
+
+mod counting;
+mod external;
+mod heap;
+mod intro;
+mod merge;
+mod network;
+mod par;
+mod quick;
+mod radix;
+mod select;
+mod tim;
+
+pub use counting::{bucket_sort, bucket_sort_by_key, counting_sort, counting_sort_by_key};
+pub use external::{external_sort, Codec, ExternalSorter, LeBytes, Lines, SortedRecords};
+pub use heap::{heapsort, heapsort_by, heapsort_by_key};
+pub use intro::{introsort, introsort_by, introsort_by_key};
+pub use merge::{
+    merge_sort, merge_sort_bottom_up, merge_sort_bottom_up_by, merge_sort_bottom_up_by_key,
+    merge_sort_by, merge_sort_by_key, MergeSorter,
+};
+pub use network::SortingNetwork;
+pub use par::{
+    par_merge_sort, par_merge_sort_by, par_merge_sort_by_key, par_quicksort, par_quicksort_by,
+    par_quicksort_by_key, ParSort,
+};
+pub use quick::{quicksort, quicksort_by, quicksort_by_key, Pivot, Quicksort};
+pub use radix::{msd_radix_sort, msd_radix_sort_by_key, radix_sort, radix_sort_by_key, RadixKey};
+pub use select::{
+    kth_smallest, kth_smallest_by, kth_smallest_by_key, kth_smallest_deterministic,
+    kth_smallest_deterministic_by, kth_smallest_deterministic_by_key, partial_sort,
+    partial_sort_by, partial_sort_by_key, top_k, top_k_by, top_k_by_key,
+};
+pub use tim::{tim_sort, tim_sort_by, tim_sort_by_key};
+
+pub fn insertion_sort<T: Ord>(v: &mut [T]) {
+    insertion_sort_by(v, T::cmp);
+}
+
+// Stable, O(n^2) but the fastest for the short slices the others end on.
+pub fn insertion_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    for i in 1..v.len() {
+        let mut j = i;
+        while j > 0 && compare(&v[j], &v[j - 1]) == Ordering::Less {
+            v.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+pub fn insertion_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    insertion_sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+// Rearranges v so that position i holds the item that was at the i-th
+// index order yields, swapping along the cycles of the permutation.
+fn permute<T>(v: &mut [T], order: impl IntoIterator<Item = usize>) {
+    // to[i] is where the item now at i goes
+    let mut to = vec![0; v.len()];
+    for (dest, from) in order.into_iter().enumerate() {
+        to[from] = dest;
+    }
+    for i in 0..v.len() {
+        while to[i] != i {
+            let j = to[i];
+            v.swap(i, j);
+            to.swap(i, j);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::{insertion_sort_by, merge_sort_by, permute};
+
+// Counting sort of integers from a small range: counts each value between
+// the least and the greatest and writes them back in order. O(n + range)
+// time and O(range) space, so only for ranges not much wider than n.
+pub fn counting_sort<T: Copy + Into<i64>>(v: &mut [T]) {
+    let Some((min, max)) = bounds(v.iter().map(|&x| x.into())) else {
+        return;
+    };
+    // a sample of each value, to write back
+    let mut seen: Vec<(usize, Option<T>)> = vec![(0, None); width(min, max)];
+    for &x in v.iter() {
+        let slot = &mut seen[(x.into() - min) as usize];
+        slot.0 += 1;
+        slot.1 = Some(x);
+    }
+    let mut out = v.iter_mut();
+    for (count, sample) in seen {
+        if let Some(x) = sample {
+            for slot in out.by_ref().take(count) {
+                *slot = x;
+            }
+        }
+    }
+}
+
+// Stable counting sort on integer keys from a small range. Keys are taken
+// once; their counts give every item its place, and the items are then
+// permuted there. O(n + range).
+pub fn counting_sort_by_key<T, F: FnMut(&T) -> i64>(v: &mut [T], key: F) {
+    let keys: Vec<i64> = v.iter().map(key).collect();
+    let Some((min, max)) = bounds(keys.iter().copied()) else {
+        return;
+    };
+    let mut starts = vec![0; width(min, max) + 1];
+    for &k in &keys {
+        starts[(k - min) as usize + 1] += 1;
+    }
+    for i in 1..starts.len() {
+        starts[i] += starts[i - 1];
+    }
+    // order[p] is the old index of the item going to position p
+    let mut order = vec![0; keys.len()];
+    for (i, &k) in keys.iter().enumerate() {
+        let start = &mut starts[(k - min) as usize];
+        order[*start] = i;
+        *start += 1;
+    }
+    permute(v, order);
+}
+
+fn bounds(keys: impl Iterator<Item = i64>) -> Option<(i64, i64)> {
+    keys.fold(None, |acc, k| match acc {
+        None => Some((k, k)),
+        Some((lo, hi)) => Some((lo.min(k), hi.max(k))),
+    })
+}
+
+fn width(min: i64, max: i64) -> usize {
+    let width = max.abs_diff(min).saturating_add(1);
+    usize::try_from(width).expect("key range too wide to count")
+}
+
+// Bucket sort of floats spread evenly over their range: n buckets over
+// [min, max], each item dropped into its own with a stable counting pass,
+// then each bucket insertion sorted. Expected O(n) for uniform input, O(n^2)
+// at worst when the values bunch up. Orders by total_cmp, so -0.0 comes
+// before 0.0; infinities or NaNs, which leave no range to split, fall back
+// to a mergesort. Stable.
+pub fn bucket_sort<T: Copy + Into<f64>>(v: &mut [T]) {
+    buckets(v, |&x| x.into());
+}
+
+pub fn bucket_sort_by_key<T, F: FnMut(&T) -> f64>(v: &mut [T], key: F) {
+    let mut keyed: Vec<(f64, usize)> = v.iter().map(key).zip(0..).collect();
+    buckets(&mut keyed, |&(k, _)| k);
+    permute(v, keyed.into_iter().map(|(_, from)| from));
+}
+
+fn buckets<T: Copy>(v: &mut [T], key: impl Fn(&T) -> f64) {
+    let n = v.len();
+    if n < 2 {
+        return;
+    }
+    let (min, max) = v
+        .iter()
+        .map(&key)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), k| {
+            (lo.min(k), hi.max(k))
+        });
+    let span = max - min;
+    if !(span.is_finite() && span > 0.0) || v.iter().any(|x| key(x).is_nan()) {
+        merge_sort_by(v, |a, b| key(a).total_cmp(&key(b)));
+        return;
+    }
+    let bucket = |x: &T| (((key(x) - min) / span * n as f64) as usize).min(n - 1);
+    let mut starts = vec![0; n + 1];
+    for x in v.iter() {
+        starts[bucket(x) + 1] += 1;
+    }
+    for i in 1..=n {
+        starts[i] += starts[i - 1];
+    }
+    let mut next = starts.clone();
+    let from = v.to_vec();
+    for x in from {
+        let b = bucket(&x);
+        v[next[b]] = x;
+        next[b] += 1;
+    }
+    for w in starts.windows(2) {
+        insertion_sort_by(&mut v[w[0]..w[1]], |a, b| key(a).total_cmp(&key(b)));
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    cmp::Ordering,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    mem,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{self, AtomicUsize},
+    vec,
+};
+
+use super::merge::merge_sort_by;
+use crate::binary_heap::BinaryHeap;
+use crate::priority_queue::{Compare, MinOrder};
+
+// External mergesort for record streams larger than memory, as the graph
+// builder sorts its edges: records are buffered up to the run capacity,
+// then sorted and spilled to a run file in a private scratch directory,
+// and finishing k-way merges the runs through a BinaryHeap of their heads,
+// first into longer runs while there are more than the fan-in. Only one
+// run's records and a buffer per open run are in memory. Ties go to the
+// earlier run, so the sort is stable.
+//
+// How records are written to and read back from run files is a Codec:
+// Lines for text, LeBytes for integers; serde users implement it over
+// their format of choice.
+
+const DEFAULT_RUN_CAPACITY: usize = 1 << 20;
+const DEFAULT_FAN_IN: usize = 64;
+
+pub trait Codec<T> {
+    fn write<W: Write>(&self, out: &mut W, record: &T) -> io::Result<()>;
+
+    // None at the end of the input
+    fn read<R: BufRead>(&self, input: &mut R) -> io::Result<Option<T>>;
+}
+
+// newline terminated lines; a line holding a newline can't be written
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lines;
+
+impl Codec<String> for Lines {
+    fn write<W: Write>(&self, out: &mut W, line: &String) -> io::Result<()> {
+        if line.contains('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "line holds a newline",
+            ));
+        }
+        out.write_all(line.as_bytes())?;
+        out.write_all(b"\n")
+    }
+
+    fn read<R: BufRead>(&self, input: &mut R) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.ends_with('\n') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+}
+
+// integers, little endian at their full width
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeBytes;
+
+macro_rules! le_bytes {
+    ($($t:ty),*) => {$(
+        impl Codec<$t> for LeBytes {
+            fn write<W: Write>(&self, out: &mut W, record: &$t) -> io::Result<()> {
+                out.write_all(&record.to_le_bytes())
+            }
+
+            fn read<R: BufRead>(&self, input: &mut R) -> io::Result<Option<$t>> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                match input.read_exact(&mut bytes) {
+                    Ok(()) => Ok(Some(<$t>::from_le_bytes(bytes))),
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+                    Err(e) => Err(e),
+                }
+            }
+        }
+    )*};
+}
+
+le_bytes!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+pub struct ExternalSorter<T, C, O = MinOrder> {
+    codec: C,
+    cmp: O,
+    run_capacity: usize,
+    fan_in: usize,
+    scratch: Scratch,
+    buffer: Vec<T>,
+    runs: Vec<PathBuf>,
+    len: usize,
+}
+
+impl<T: Ord, C: Codec<T>> ExternalSorter<T, C> {
+    pub fn new(codec: C) -> Self {
+        Self::with_compare(codec, MinOrder)
+    }
+}
+
+impl<T, C: Codec<T>, O: Compare<T> + Clone> ExternalSorter<T, C, O> {
+    pub fn with_compare(codec: C, cmp: O) -> Self {
+        Self {
+            codec,
+            cmp,
+            run_capacity: DEFAULT_RUN_CAPACITY,
+            fan_in: DEFAULT_FAN_IN,
+            scratch: Scratch::new(std::env::temp_dir()),
+            buffer: Vec::new(),
+            runs: Vec::new(),
+            len: 0,
+        }
+    }
+
+    // records held in memory before a run is spilled, at least 1
+    pub fn run_capacity(mut self, records: usize) -> Self {
+        self.run_capacity = records.max(1);
+        self
+    }
+
+    // run files open at once in a merge, at least 2
+    pub fn fan_in(mut self, runs: usize) -> Self {
+        self.fan_in = runs.max(2);
+        self
+    }
+
+    // where the scratch directory for run files is created
+    pub fn temp_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.scratch.parent = dir.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, record: T) -> io::Result<()> {
+        self.len += 1;
+        self.buffer.push(record);
+        if self.buffer.len() >= self.run_capacity {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    pub fn push_all<I: IntoIterator<Item = T>>(&mut self, records: I) -> io::Result<()> {
+        for record in records {
+            self.push(record)?;
+        }
+        Ok(())
+    }
+
+    // Inputs that never spilled are sorted in memory. Otherwise the runs
+    // are merged down to at most the fan-in, and the last merge happens as
+    // the returned iterator is read.
+    pub fn finish(mut self) -> io::Result<SortedRecords<T, C, O>> {
+        if self.runs.is_empty() {
+            self.sort_buffer();
+            return Ok(SortedRecords {
+                source: Source::Memory(mem::take(&mut self.buffer).into_iter()),
+                codec: self.codec,
+                failed: false,
+                _scratch: self.scratch,
+            });
+        }
+        self.spill()?;
+        while self.runs.len() > self.fan_in {
+            let mut merged = Vec::new();
+            let runs = mem::take(&mut self.runs);
+            for group in runs.chunks(self.fan_in) {
+                let path = self.scratch.file()?;
+                let mut out = BufWriter::new(File::create(&path)?);
+                let mut merge = Merge::open(group, &self.codec, self.cmp.clone())?;
+                while let Some(record) = merge.next(&self.codec)? {
+                    self.codec.write(&mut out, &record)?;
+                }
+                out.flush()?;
+                for run in group.iter() {
+                    fs::remove_file(run)?;
+                }
+                merged.push(path);
+            }
+            self.runs = merged;
+        }
+        let merge = Merge::open(&self.runs, &self.codec, self.cmp.clone())?;
+        Ok(SortedRecords {
+            source: Source::Runs(merge),
+            codec: self.codec,
+            failed: false,
+            _scratch: self.scratch,
+        })
+    }
+
+    fn sort_buffer(&mut self) {
+        let cmp = &self.cmp;
+        merge_sort_by(&mut self.buffer, |a, b| cmp.compare(a, b));
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.sort_buffer();
+        let path = self.scratch.file()?;
+        let mut out = BufWriter::new(File::create(&path)?);
+        for record in self.buffer.iter() {
+            self.codec.write(&mut out, record)?;
+        }
+        out.flush()?;
+        self.buffer.clear();
+        self.runs.push(path);
+        Ok(())
+    }
+}
+
+// Sorts records into run files under the system temp directory, runs of
+// DEFAULT_RUN_CAPACITY records, and returns them in order.
+pub fn external_sort<T, C, I>(records: I, codec: C) -> io::Result<SortedRecords<T, C>>
+where
+    T: Ord,
+    C: Codec<T>,
+    I: IntoIterator<Item = T>,
+{
+    let mut sorter = ExternalSorter::new(codec);
+    sorter.push_all(records)?;
+    sorter.finish()
+}
+
+// The sorted records, read off the final merge; the scratch directory is
+// removed when it is dropped. Iteration ends after the first error.
+pub struct SortedRecords<T, C, O = MinOrder> {
+    source: Source<T, O>,
+    codec: C,
+    failed: bool,
+    _scratch: Scratch,
+}
+
+enum Source<T, O> {
+    Memory(vec::IntoIter<T>),
+    Runs(Merge<T, O>),
+}
+
+impl<T, C: Codec<T>, O: Compare<T>> Iterator for SortedRecords<T, C, O> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        if self.failed {
+            return None;
+        }
+        match &mut self.source {
+            Source::Memory(records) => records.next().map(Ok),
+            Source::Runs(merge) => {
+                let next = merge.next(&self.codec);
+                self.failed = next.is_err();
+                next.transpose()
+            }
+        }
+    }
+}
+
+// the head record of every run not yet exhausted, with its run
+struct Merge<T, O> {
+    heads: BinaryHeap<(T, usize), Heads<O>>,
+    readers: Vec<BufReader<File>>,
+}
+
+impl<T, O: Compare<T>> Merge<T, O> {
+    fn open<C: Codec<T>>(runs: &[PathBuf], codec: &C, cmp: O) -> io::Result<Self> {
+        let mut readers = runs
+            .iter()
+            .map(|p| File::open(p).map(BufReader::new))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut heads = BinaryHeap::with_compare(Heads(cmp));
+        for (i, r) in readers.iter_mut().enumerate() {
+            if let Some(record) = codec.read(r)? {
+                heads.push((record, i));
+            }
+        }
+        Ok(Self { heads, readers })
+    }
+
+    fn next<C: Codec<T>>(&mut self, codec: &C) -> io::Result<Option<T>> {
+        let Some(&(_, i)) = self.heads.peek() else {
+            return Ok(None);
+        };
+        let top = match codec.read(&mut self.readers[i])? {
+            Some(record) => self.heads.replace_top((record, i)),
+            None => self.heads.pop(),
+        };
+        Ok(top.map(|(record, _)| record))
+    }
+}
+
+// records in order, equal ones by run
+struct Heads<O>(O);
+
+impl<T, O: Compare<T>> Compare<(T, usize)> for Heads<O> {
+    fn compare(&self, a: &(T, usize), b: &(T, usize)) -> Ordering {
+        self.0.compare(&a.0, &b.0).then(a.1.cmp(&b.1))
+    }
+}
+
+// a private directory for run files, created on first use and removed
+// with everything in it on drop
+struct Scratch {
+    parent: PathBuf,
+    dir: Option<PathBuf>,
+    next_file: usize,
+}
+
+impl Scratch {
+    fn new(parent: PathBuf) -> Self {
+        Self {
+            parent,
+            dir: None,
+            next_file: 0,
+        }
+    }
+
+    fn file(&mut self) -> io::Result<PathBuf> {
+        static SORTS: AtomicUsize = AtomicUsize::new(0);
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => {
+                let id = SORTS.fetch_add(1, atomic::Ordering::Relaxed);
+                let dir = self
+                    .parent
+                    .join(format!("external-sort-{}-{id}", process::id()));
+                fs::create_dir_all(&dir)?;
+                self.dir = Some(dir.clone());
+                dir
+            }
+        };
+        self.next_file += 1;
+        Ok(dir.join(format!("{}.run", self.next_file)))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.dir {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
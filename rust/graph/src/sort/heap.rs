@@ -0,0 +1,20 @@
+use std::cmp::Ordering;
+
+use crate::binary_heap::{heapify, sort_heap};
+
+// In-place heapsort on the BinaryHeap's sift-down: heapify the slice into
+// a max-heap, then swap each maximum to the back. O(n log n) at worst,
+// O(1) extra space, not stable.
+pub fn heapsort<T: Ord>(v: &mut [T]) {
+    heapsort_by(v, T::cmp);
+}
+
+pub fn heapsort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    let mut above = |a: &T, b: &T| compare(a, b) == Ordering::Greater;
+    heapify(v, &mut above);
+    sort_heap(v, &mut above);
+}
+
+pub fn heapsort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    heapsort_by(v, |a, b| key(a).cmp(&key(b)));
+}
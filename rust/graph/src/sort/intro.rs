@@ -0,0 +1,53 @@
+use std::{cmp::Ordering, mem};
+
+use super::heap::heapsort_by;
+use super::insertion_sort_by;
+use super::quick::{median_of_three, partition2};
+
+// slices this short are insertion sorted
+const CUTOFF: usize = 16;
+
+// Introsort: quicksort with a median-of-three pivot and Hoare's partition,
+// which gives up on any slice still being partitioned 2 log2 n levels
+// down and heapsorts it instead, so a bad run of pivots costs at most
+// O(n log n) and never the quadratic worst case. Short slices are left to
+// insertion sort. Unstable, O(log n) stack.
+pub fn introsort<T: Ord>(v: &mut [T]) {
+    introsort_by(v, T::cmp);
+}
+
+pub fn introsort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    let depth = 2 * (usize::BITS - v.len().leading_zeros());
+    intro(v, depth, &mut compare);
+}
+
+pub fn introsort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    introsort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+fn intro<T, F: FnMut(&T, &T) -> Ordering>(mut v: &mut [T], mut depth: u32, compare: &mut F) {
+    loop {
+        if v.len() <= CUTOFF {
+            insertion_sort_by(v, &mut *compare);
+            return;
+        }
+        if depth == 0 {
+            heapsort_by(v, &mut *compare);
+            return;
+        }
+        depth -= 1;
+        let p = median_of_three(v, compare);
+        v.swap(0, p);
+        let p = partition2(v, compare);
+        let (left, rest) = mem::take(&mut v).split_at_mut(p);
+        let right = &mut rest[1..];
+        // the shorter side recursively, the longer in the loop
+        if left.len() < right.len() {
+            intro(left, depth, compare);
+            v = right;
+        } else {
+            intro(right, depth, compare);
+            v = left;
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{cmp::Ordering, mem, ptr};
+
+use super::insertion_sort_by;
+
+// runs this short are insertion sorted before merging
+const RUN: usize = 16;
+
+// Stable mergesort with a scratch buffer kept between calls, so sorting
+// many slices allocates only when one is longer than any before. Merging
+// copies the shorter run out to the buffer and merges back into the slice,
+// so the buffer needs half the slice. Both the recursive top-down order
+// and the iterative bottom-up one are here; they compare the same way and
+// only differ in which runs they merge when.
+pub struct MergeSorter<T> {
+    // capacity only, never holds an initialized item between merges
+    buf: Vec<T>,
+}
+
+impl<T> Default for MergeSorter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MergeSorter<T> {
+    pub fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    // buffer space, in items
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    pub fn sort(&mut self, v: &mut [T])
+    where
+        T: Ord,
+    {
+        self.sort_by(v, T::cmp);
+    }
+
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, v: &mut [T], mut compare: F) {
+        self.reserve(v.len());
+        let buf = self.buf.as_mut_ptr();
+        top_down(v, buf, &mut compare);
+    }
+
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, v: &mut [T], mut key: F) {
+        self.sort_by(v, |a, b| key(a).cmp(&key(b)));
+    }
+
+    pub fn sort_bottom_up(&mut self, v: &mut [T])
+    where
+        T: Ord,
+    {
+        self.sort_bottom_up_by(v, T::cmp);
+    }
+
+    pub fn sort_bottom_up_by<F: FnMut(&T, &T) -> Ordering>(&mut self, v: &mut [T], mut compare: F) {
+        self.reserve(v.len());
+        let buf = self.buf.as_mut_ptr();
+        bottom_up(v, buf, &mut compare);
+    }
+
+    pub fn sort_bottom_up_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, v: &mut [T], mut key: F) {
+        self.sort_bottom_up_by(v, |a, b| key(a).cmp(&key(b)));
+    }
+
+    fn reserve(&mut self, len: usize) {
+        // len is 0, so this makes room for len / 2 items
+        self.buf.reserve(len / 2);
+    }
+}
+
+fn top_down<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], buf: *mut T, compare: &mut F) {
+    if v.len() <= RUN {
+        insertion_sort_by(v, &mut *compare);
+        return;
+    }
+    let mid = v.len() / 2;
+    top_down(&mut v[..mid], buf, compare);
+    top_down(&mut v[mid..], buf, compare);
+    // the buffer was reserved for half of the whole slice
+    unsafe { merge(v, mid, buf, compare) };
+}
+
+fn bottom_up<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], buf: *mut T, compare: &mut F) {
+    for run in v.chunks_mut(RUN) {
+        insertion_sort_by(run, &mut *compare);
+    }
+    let mut width = RUN;
+    while width < v.len() {
+        for pair in v.chunks_mut(2 * width) {
+            if pair.len() > width {
+                // the shorter run of a pair is at most half of the slice
+                unsafe { merge(pair, width, buf, compare) };
+            }
+        }
+        width *= 2;
+    }
+}
+
+// Merges the sorted runs v[..mid] and v[mid..]. The shorter one is copied
+// out and merged back, front to back for the left run and back to front
+// for the right; ties go to the left run's item, which keeps the sort
+// stable.
+//
+// Safety: buf must have room for the shorter run, and nothing else may
+// use it.
+pub(super) unsafe fn merge<T, F: FnMut(&T, &T) -> Ordering>(
+    v: &mut [T],
+    mid: usize,
+    buf: *mut T,
+    compare: &mut F,
+) {
+    let len = v.len();
+    if mid == 0 || mid == len || mem::size_of::<T>() == 0 {
+        return;
+    }
+    let v_ptr = v.as_mut_ptr();
+    // already in order across the seam
+    if compare(&*v_ptr.add(mid), &*v_ptr.add(mid - 1)) != Ordering::Less {
+        return;
+    }
+    // hole.start..hole.end are the buffered items not yet merged, and
+    // hole.dest where they go; if compare panics, dropping the hole puts
+    // them back, so the slice holds each item once when the unwinding
+    // reaches it
+    if mid <= len - mid {
+        ptr::copy_nonoverlapping(v_ptr, buf, mid);
+        let mut hole = Hole {
+            start: buf,
+            end: buf.add(mid),
+            dest: v_ptr,
+        };
+        let mut right = v_ptr.add(mid);
+        let end = v_ptr.add(len);
+        // dest trails right by the buffered items
+        while hole.start < hole.end && right < end {
+            if compare(&*right, &*hole.start) == Ordering::Less {
+                ptr::copy_nonoverlapping(right, hole.dest, 1);
+                right = right.add(1);
+            } else {
+                ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                hole.start = hole.start.add(1);
+            }
+            hole.dest = hole.dest.add(1);
+        }
+    } else {
+        ptr::copy_nonoverlapping(v_ptr.add(mid), buf, len - mid);
+        let mut hole = Hole {
+            start: buf,
+            end: buf.add(len - mid),
+            dest: v_ptr.add(mid),
+        };
+        // the gap, from dest, runs up to out
+        let mut out = v_ptr.add(len);
+        while v_ptr < hole.dest && hole.start < hole.end {
+            out = out.sub(1);
+            let left = hole.dest.sub(1);
+            let right = hole.end.sub(1);
+            if compare(&*right, &*left) == Ordering::Less {
+                ptr::copy_nonoverlapping(left, out, 1);
+                hole.dest = left;
+            } else {
+                ptr::copy_nonoverlapping(right, out, 1);
+                hole.end = right;
+            }
+        }
+    }
+    // drops the hole, moving the buffered items left into the gap
+}
+
+// items buffered during a merge, start..end, and the gap at dest they fill
+pub(super) struct Hole<T> {
+    pub(super) start: *mut T,
+    pub(super) end: *mut T,
+    pub(super) dest: *mut T,
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        // start..end and the gap at dest are the same length and disjoint
+        unsafe {
+            let left = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, left);
+        }
+    }
+}
+
+// Recursive, top-down: sorts each half and merges them.
+pub fn merge_sort<T: Ord>(v: &mut [T]) {
+    MergeSorter::new().sort(v);
+}
+
+pub fn merge_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: F) {
+    MergeSorter::new().sort_by(v, compare);
+}
+
+pub fn merge_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], key: F) {
+    MergeSorter::new().sort_by_key(v, key);
+}
+
+// Iterative, bottom-up: sorts short runs, then merges neighbouring runs in
+// passes of doubling width.
+pub fn merge_sort_bottom_up<T: Ord>(v: &mut [T]) {
+    MergeSorter::new().sort_bottom_up(v);
+}
+
+pub fn merge_sort_bottom_up_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: F) {
+    MergeSorter::new().sort_bottom_up_by(v, compare);
+}
+
+pub fn merge_sort_bottom_up_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], key: F) {
+    MergeSorter::new().sort_bottom_up_by_key(v, key);
+}
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+
+// bit k of LOW_BITS[w] is bit w of k
+const LOW_BITS: [u64; 6] = [
+    0xaaaa_aaaa_aaaa_aaaa,
+    0xcccc_cccc_cccc_cccc,
+    0xf0f0_f0f0_f0f0_f0f0,
+    0xff00_ff00_ff00_ff00,
+    0xffff_0000_ffff_0000,
+    0xffff_ffff_0000_0000,
+];
+
+// A sorting network on a fixed number of inputs: a list of comparators
+// (i, j), i < j, each leaving the lesser of v[i] and v[j] at i. Which two
+// wires are compared never depends on the data, so the comparators of a
+// layer can all run at once, in hardware or SIMD lanes: depth, not length,
+// is a network's parallel time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortingNetwork {
+    inputs: usize,
+    comparators: Vec<(usize, usize)>,
+}
+
+impl SortingNetwork {
+    // panics unless i < j < inputs for every comparator
+    pub fn new(inputs: usize, comparators: Vec<(usize, usize)>) -> Self {
+        for &(i, j) in comparators.iter() {
+            assert!(
+                i < j && j < inputs,
+                "comparator ({i}, {j}) on {inputs} inputs"
+            );
+        }
+        Self {
+            inputs,
+            comparators,
+        }
+    }
+
+    // Batcher's odd-even mergesort, merging sorted halves by merging
+    // their odd and even positions and fixing up neighbours: O(n log^2 n)
+    // comparators, depth log n (log n + 1) / 2 for n a power of two.
+    // Other n leave out the comparators that would touch wires past the
+    // end.
+    pub fn batcher_odd_even(n: usize) -> Self {
+        let mut comparators = Vec::new();
+        let mut p = 1;
+        while p < n {
+            let mut k = p;
+            while k >= 1 {
+                for j in (k % p..n.saturating_sub(k)).step_by(2 * k) {
+                    for i in 0..k.min(n - j - k) {
+                        // both in the same pair of blocks being merged
+                        if (i + j) / (2 * p) == (i + j + k) / (2 * p) {
+                            comparators.push((i + j, i + j + k));
+                        }
+                    }
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+        Self::new(n, comparators)
+    }
+
+    // Bitonic sort, every comparator ascending: each block is merged by
+    // comparing its halves mirrored, which makes two bitonic halves, then
+    // half-cleaning those down to single wires. Same depth as Batcher's and
+    // a few more comparators, but the most regular wiring. Built for the
+    // next power of two with the comparators past n left out, as if those
+    // wires held keys greater than any other.
+    pub fn bitonic(n: usize) -> Self {
+        let size = n.next_power_of_two();
+        let mut comparators = Vec::new();
+        let mut block = 2;
+        while block <= size {
+            let mut j = block / 2;
+            while j >= 1 {
+                for i in 0..n {
+                    // mirrored in the first step of a block, j apart after
+                    let l = if j == block / 2 {
+                        i ^ (block - 1)
+                    } else {
+                        i ^ j
+                    };
+                    if i < l && l < n {
+                        comparators.push((i, l));
+                    }
+                }
+                j /= 2;
+            }
+            block *= 2;
+        }
+        Self::new(n, comparators)
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn comparators(&self) -> &[(usize, usize)] {
+        &self.comparators
+    }
+
+    pub fn len(&self) -> usize {
+        self.comparators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.comparators.is_empty()
+    }
+
+    // Comparators grouped into parallel steps, each as early as the last
+    // comparator on either of its wires allows, in their order within.
+    pub fn layers(&self) -> Vec<Vec<(usize, usize)>> {
+        // the layer after the last one to use each wire
+        let mut free = vec![0; self.inputs];
+        let mut layers: Vec<Vec<(usize, usize)>> = Vec::new();
+        for &(i, j) in self.comparators.iter() {
+            let layer = free[i].max(free[j]);
+            if layer == layers.len() {
+                layers.push(Vec::new());
+            }
+            layers[layer].push((i, j));
+            free[i] = layer + 1;
+            free[j] = layer + 1;
+        }
+        layers
+    }
+
+    pub fn depth(&self) -> usize {
+        self.layers().len()
+    }
+
+    // Runs the network on v; panics unless v has exactly inputs items.
+    pub fn apply<T: Ord>(&self, v: &mut [T]) {
+        self.apply_by(v, T::cmp);
+    }
+
+    pub fn apply_by<T, F: FnMut(&T, &T) -> Ordering>(&self, v: &mut [T], mut compare: F) {
+        assert_eq!(v.len(), self.inputs, "network and slice lengths differ");
+        for &(i, j) in self.comparators.iter() {
+            if compare(&v[j], &v[i]) == Ordering::Less {
+                v.swap(i, j);
+            }
+        }
+    }
+
+    // By the zero-one principle a network sorts every input if it sorts
+    // every one of 0s and 1s, so this runs all 2^n of those, 64 at a time:
+    // wire w is a word holding bit w of 64 inputs, and a comparator is an
+    // and and an or. Practical up to 30 inputs or so; panics at 64.
+    pub fn is_sorting(&self) -> bool {
+        self.zero_one_counterexample().is_none()
+    }
+
+    // an input of 0s and 1s the network leaves unsorted, if there is one
+    pub fn zero_one_counterexample(&self) -> Option<Vec<u8>> {
+        let n = self.inputs;
+        assert!(n < 64, "2^{n} zero-one inputs");
+        let inputs: u64 = 1 << n;
+        let mut wires = vec![0u64; n];
+        for first in (0..inputs).step_by(64) {
+            // the low six bits count through the word, the rest are first's
+            for (w, word) in wires.iter_mut().enumerate() {
+                *word = match LOW_BITS.get(w) {
+                    Some(&bits) => bits,
+                    None if first >> w & 1 == 1 => u64::MAX,
+                    None => 0,
+                };
+            }
+            for &(i, j) in self.comparators.iter() {
+                (wires[i], wires[j]) = (wires[i] & wires[j], wires[i] | wires[j]);
+            }
+            // the inputs, among these 64, left with a 1 before a 0
+            let live = if inputs - first < 64 {
+                (1 << (inputs - first)) - 1
+            } else {
+                u64::MAX
+            };
+            let unsorted = wires.windows(2).fold(0, |acc, w| acc | (w[0] & !w[1])) & live;
+            if unsorted != 0 {
+                let input = first + unsorted.trailing_zeros() as u64;
+                return Some((0..n).map(|w| (input >> w & 1) as u8).collect());
+            }
+        }
+        None
+    }
+}
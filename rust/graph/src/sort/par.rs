@@ -0,0 +1,185 @@
+use std::{cmp::Ordering, mem, thread};
+
+use super::intro::introsort_by;
+use super::merge::{merge, MergeSorter};
+use super::quick::{median_of_three, partition2};
+use crate::parallel::threads;
+
+// Parallel mergesort and quicksort on scoped std threads, like the graph
+// algorithms in parallel.rs. Both split the work into about as many tasks
+// as threads, a thread per task, and sort each sequentially once it is
+// no longer than the cutoff or there are tasks enough. The mergesort is
+// stable: halves are sorted in parallel and merged on the caller's
+// thread, so its last merge is one pass over the whole slice. The
+// quicksort partitions sequentially and hands one side to a new thread;
+// its sequential sort is introsort, so it stays O(n log n) at worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParSort {
+    threads: usize,
+    cutoff: usize,
+}
+
+impl Default for ParSort {
+    fn default() -> Self {
+        Self {
+            threads: threads(),
+            cutoff: 1 << 13,
+        }
+    }
+}
+
+impl ParSort {
+    // one thread per core, slices of 8192 or fewer sorted sequentially
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 1 sorts on the calling thread only
+    pub fn threads(mut self, n: usize) -> Self {
+        self.threads = n.max(1);
+        self
+    }
+
+    pub fn cutoff(mut self, len: usize) -> Self {
+        self.cutoff = len.max(1);
+        self
+    }
+
+    pub fn merge_sort<T: Ord + Send>(&self, v: &mut [T]) {
+        self.merge_sort_by(v, T::cmp);
+    }
+
+    pub fn merge_sort_by<T, F>(&self, v: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        par_merge(v, self.cutoff, self.splits(1), &compare);
+    }
+
+    pub fn merge_sort_by_key<T, K, F>(&self, v: &mut [T], key: F)
+    where
+        T: Send,
+        K: Ord,
+        F: Fn(&T) -> K + Sync,
+    {
+        self.merge_sort_by(v, |a, b| key(a).cmp(&key(b)));
+    }
+
+    pub fn quicksort<T: Ord + Send>(&self, v: &mut [T]) {
+        self.quicksort_by(v, T::cmp);
+    }
+
+    pub fn quicksort_by<T, F>(&self, v: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        // partitions are uneven, so twice the tasks to even out the load
+        par_quick(v, self.cutoff, self.splits(2), &compare);
+    }
+
+    pub fn quicksort_by_key<T, K, F>(&self, v: &mut [T], key: F)
+    where
+        T: Send,
+        K: Ord,
+        F: Fn(&T) -> K + Sync,
+    {
+        self.quicksort_by(v, |a, b| key(a).cmp(&key(b)));
+    }
+
+    // levels of splitting for at least per_thread tasks per thread
+    fn splits(&self, per_thread: usize) -> u32 {
+        if self.threads == 1 {
+            return 0;
+        }
+        (self.threads * per_thread)
+            .next_power_of_two()
+            .trailing_zeros()
+    }
+}
+
+fn par_merge<T, F>(v: &mut [T], cutoff: usize, splits: u32, compare: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if splits == 0 || v.len() <= cutoff {
+        MergeSorter::new().sort_by(v, compare);
+        return;
+    }
+    let mid = v.len() / 2;
+    let (left, right) = v.split_at_mut(mid);
+    thread::scope(|s| {
+        s.spawn(|| par_merge(left, cutoff, splits - 1, compare));
+        par_merge(right, cutoff, splits - 1, compare);
+    });
+    let mut buf: Vec<T> = Vec::with_capacity(mid.min(v.len() - mid));
+    // the buffer has room for the shorter half and is this merge's alone
+    unsafe { merge(v, mid, buf.as_mut_ptr(), &mut |a, b| compare(a, b)) };
+}
+
+fn par_quick<T, F>(mut v: &mut [T], cutoff: usize, mut splits: u32, compare: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    thread::scope(|s| loop {
+        if splits == 0 || v.len() <= cutoff {
+            introsort_by(v, compare);
+            return;
+        }
+        splits -= 1;
+        let mut compare_mut = |a: &T, b: &T| compare(a, b);
+        let p = median_of_three(v, &mut compare_mut);
+        v.swap(0, p);
+        let p = partition2(v, &mut compare_mut);
+        let (left, rest) = mem::take(&mut v).split_at_mut(p);
+        // the left side on a new thread, the right on this one
+        s.spawn(move || par_quick(left, cutoff, splits, compare));
+        v = &mut rest[1..];
+    });
+}
+
+// with ParSort's defaults
+pub fn par_merge_sort<T: Ord + Send>(v: &mut [T]) {
+    ParSort::new().merge_sort(v);
+}
+
+pub fn par_merge_sort_by<T, F>(v: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    ParSort::new().merge_sort_by(v, compare);
+}
+
+pub fn par_merge_sort_by_key<T, K, F>(v: &mut [T], key: F)
+where
+    T: Send,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    ParSort::new().merge_sort_by_key(v, key);
+}
+
+pub fn par_quicksort<T: Ord + Send>(v: &mut [T]) {
+    ParSort::new().quicksort(v);
+}
+
+pub fn par_quicksort_by<T, F>(v: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    ParSort::new().quicksort_by(v, compare);
+}
+
+pub fn par_quicksort_by_key<T, K, F>(v: &mut [T], key: F)
+where
+    T: Send,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    ParSort::new().quicksort_by_key(v, key);
+}
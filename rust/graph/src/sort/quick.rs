@@ -0,0 +1,205 @@
+use std::{cmp::Ordering, mem};
+
+use super::insertion_sort_by;
+use crate::rng::{Rng, SplitMix64};
+
+// how quicksort picks the element it partitions around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pivot {
+    // quadratic on sorted input, there for teaching
+    First,
+    // of the elements a quarter, half and three quarters of the way in;
+    // sampling the ends instead goes quadratic on sorted input, whose
+    // three-way partitions leave the least key of a side at its end
+    MedianOfThree,
+    // uniformly, from a SplitMix64 seeded with this
+    Random(u64),
+}
+
+// Quicksort, configured: the pivot rule, whether to partition three ways
+// (less, equal, greater, so runs of equal keys are done with at once) or
+// two (Hoare's, equal keys spread over both sides), and the length at or
+// below which a slice is left to insertion sort. Recursing into the shorter
+// side and looping on the longer keeps the stack O(log n). Not stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quicksort {
+    pivot: Pivot,
+    three_way: bool,
+    cutoff: usize,
+}
+
+impl Default for Quicksort {
+    fn default() -> Self {
+        Self {
+            pivot: Pivot::MedianOfThree,
+            three_way: true,
+            cutoff: 16,
+        }
+    }
+}
+
+impl Quicksort {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pivot(mut self, pivot: Pivot) -> Self {
+        self.pivot = pivot;
+        self
+    }
+
+    pub fn three_way(mut self, on: bool) -> Self {
+        self.three_way = on;
+        self
+    }
+
+    // 0 partitions all the way down
+    pub fn cutoff(mut self, len: usize) -> Self {
+        self.cutoff = len;
+        self
+    }
+
+    pub fn sort<T: Ord>(&self, v: &mut [T]) {
+        self.sort_by(v, T::cmp);
+    }
+
+    pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(&self, v: &mut [T], mut compare: F) {
+        let seed = match self.pivot {
+            Pivot::Random(seed) => seed,
+            _ => 0,
+        };
+        let mut rng = SplitMix64::new(seed);
+        self.quick(v, &mut rng, &mut compare);
+    }
+
+    pub fn sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(&self, v: &mut [T], mut key: F) {
+        self.sort_by(v, |a, b| key(a).cmp(&key(b)));
+    }
+
+    fn quick<T, F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        mut v: &mut [T],
+        rng: &mut SplitMix64,
+        compare: &mut F,
+    ) {
+        loop {
+            if v.len() <= self.cutoff.max(1) {
+                insertion_sort_by(v, &mut *compare);
+                return;
+            }
+            let p = self.choose(v, rng, compare);
+            v.swap(0, p);
+            // v[..lo] and v[hi..] are what's left to sort
+            let (lo, hi) = if self.three_way {
+                partition3(v, compare)
+            } else {
+                let p = partition2(v, compare);
+                (p, p + 1)
+            };
+            let (left, rest) = mem::take(&mut v).split_at_mut(lo);
+            let right = &mut rest[hi - lo..];
+            if left.len() < right.len() {
+                self.quick(left, rng, compare);
+                v = right;
+            } else {
+                self.quick(right, rng, compare);
+                v = left;
+            }
+        }
+    }
+
+    fn choose<T, F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        v: &[T],
+        rng: &mut SplitMix64,
+        compare: &mut F,
+    ) -> usize {
+        match self.pivot {
+            Pivot::First => 0,
+            Pivot::Random(_) => rng.gen_range(v.len()),
+            Pivot::MedianOfThree => median_of_three(v, compare),
+        }
+    }
+}
+
+// index of the median of the items a quarter, half and three quarters in
+pub(super) fn median_of_three<T, F: FnMut(&T, &T) -> Ordering>(v: &[T], compare: &mut F) -> usize {
+    let (a, b, c) = (v.len() / 4, v.len() / 2, v.len() * 3 / 4);
+    let mut less = |i: usize, j: usize| compare(&v[i], &v[j]) == Ordering::Less;
+    if less(a, b) {
+        if less(b, c) {
+            b
+        } else if less(a, c) {
+            c
+        } else {
+            a
+        }
+    } else if less(a, c) {
+        a
+    } else if less(b, c) {
+        c
+    } else {
+        b
+    }
+}
+
+// Hoare's partition around v[0]: both scans stop on keys equal to the
+// pivot, so all-equal input still splits in half. Returns where the pivot
+// lands, nothing after it less and nothing before it greater.
+pub(super) fn partition2<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut F) -> usize {
+    let (mut i, mut j) = (1, v.len() - 1);
+    loop {
+        while i <= j && compare(&v[i], &v[0]) == Ordering::Less {
+            i += 1;
+        }
+        while i <= j && compare(&v[j], &v[0]) == Ordering::Greater {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        v.swap(i, j);
+        i += 1;
+        j -= 1;
+    }
+    v.swap(0, j);
+    j
+}
+
+// Dijkstra's three-way partition around v[0]: returns (lo, hi) with v[..lo]
+// less than the pivot, v[lo..hi] equal and v[hi..] greater. v[lo] is always
+// an equal key, so it stands in for the pivot as that moves.
+pub(super) fn partition3<T, F: FnMut(&T, &T) -> Ordering>(
+    v: &mut [T],
+    compare: &mut F,
+) -> (usize, usize) {
+    let (mut lt, mut i, mut gt) = (0, 1, v.len());
+    while i < gt {
+        match compare(&v[i], &v[lt]) {
+            Ordering::Less => {
+                v.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                v.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (lt, gt)
+}
+
+// with the default configuration
+pub fn quicksort<T: Ord>(v: &mut [T]) {
+    Quicksort::new().sort(v);
+}
+
+pub fn quicksort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: F) {
+    Quicksort::new().sort_by(v, compare);
+}
+
+pub fn quicksort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], key: F) {
+    Quicksort::new().sort_by_key(v, key);
+}
@@ -0,0 +1,196 @@
+use std::cmp::Reverse;
+
+use super::{insertion_sort_by, permute};
+
+// A fixed-width key radix sort can sort by: BYTES bytes, byte 0 the least
+// significant, ordered so that comparing them from the top agrees with the
+// key's own order. Implement it to sort custom types with radix_sort.
+pub trait RadixKey {
+    const BYTES: usize;
+
+    fn radix_byte(&self, i: usize) -> u8;
+}
+
+macro_rules! radix_unsigned {
+    ($($t:ty),*) => {$(
+        impl RadixKey for $t {
+            const BYTES: usize = std::mem::size_of::<$t>();
+
+            fn radix_byte(&self, i: usize) -> u8 {
+                (*self >> (8 * i)) as u8
+            }
+        }
+    )*};
+}
+
+// signed keys with the sign bit flipped sort as unsigned ones
+macro_rules! radix_signed {
+    ($($t:ty as $u:ty),*) => {$(
+        impl RadixKey for $t {
+            const BYTES: usize = std::mem::size_of::<$t>();
+
+            fn radix_byte(&self, i: usize) -> u8 {
+                ((*self as $u ^ 1 << (<$u>::BITS - 1)) >> (8 * i)) as u8
+            }
+        }
+    )*};
+}
+
+radix_unsigned!(u8, u16, u32, u64, u128, usize);
+radix_signed!(
+    i8 as u8,
+    i16 as u16,
+    i32 as u32,
+    i64 as u64,
+    i128 as u128,
+    isize as usize
+);
+
+impl RadixKey for bool {
+    const BYTES: usize = 1;
+
+    fn radix_byte(&self, _: usize) -> u8 {
+        *self as u8
+    }
+}
+
+impl RadixKey for char {
+    const BYTES: usize = 3;
+
+    fn radix_byte(&self, i: usize) -> u8 {
+        (*self as u32).radix_byte(i)
+    }
+}
+
+// by the first, then the second
+impl<A: RadixKey, B: RadixKey> RadixKey for (A, B) {
+    const BYTES: usize = A::BYTES + B::BYTES;
+
+    fn radix_byte(&self, i: usize) -> u8 {
+        if i < B::BYTES {
+            self.1.radix_byte(i)
+        } else {
+            self.0.radix_byte(i - B::BYTES)
+        }
+    }
+}
+
+impl<K: RadixKey> RadixKey for Reverse<K> {
+    const BYTES: usize = K::BYTES;
+
+    fn radix_byte(&self, i: usize) -> u8 {
+        !self.0.radix_byte(i)
+    }
+}
+
+// LSD radix sort: one stable counting pass per key byte, least significant
+// first, between the slice and a buffer as long. Byte counts for every pass
+// come from one scan up front, and passes where every key has the same byte
+// are skipped. O(n BYTES), stable.
+pub fn radix_sort<T: RadixKey + Copy>(v: &mut [T]) {
+    lsd(v, |&x| x);
+}
+
+// Keys are taken once, sorted with their positions, and the slice then
+// permuted into place, so T needn't be Copy.
+pub fn radix_sort_by_key<T, K: RadixKey + Copy, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    let mut keyed: Vec<(K, usize)> = v.iter().map(&mut key).zip(0..).collect();
+    lsd(&mut keyed, |&(k, _)| k);
+    permute(v, keyed.into_iter().map(|(_, from)| from));
+}
+
+fn lsd<T: Copy, K: RadixKey>(v: &mut [T], key: impl Fn(&T) -> K) {
+    let n = v.len();
+    if n < 2 {
+        return;
+    }
+    let mut counts = vec![[0usize; 256]; K::BYTES];
+    for x in v.iter() {
+        let k = key(x);
+        for (i, count) in counts.iter_mut().enumerate() {
+            count[k.radix_byte(i) as usize] += 1;
+        }
+    }
+    let mut buf = v.to_vec();
+    let mut in_buf = false;
+    for (i, count) in counts.iter().enumerate() {
+        if count.contains(&n) {
+            continue;
+        }
+        let mut offsets = [0; 256];
+        let mut sum = 0;
+        for (offset, &c) in offsets.iter_mut().zip(count) {
+            *offset = sum;
+            sum += c;
+        }
+        let mut scatter = |from: &[T], to: &mut [T]| {
+            for x in from {
+                let b = key(x).radix_byte(i) as usize;
+                to[offsets[b]] = *x;
+                offsets[b] += 1;
+            }
+        };
+        if in_buf {
+            scatter(&buf, v);
+        } else {
+            scatter(v, &mut buf);
+        }
+        in_buf = !in_buf;
+    }
+    if in_buf {
+        v.copy_from_slice(&buf);
+    }
+}
+
+// buckets this small are insertion sorted on the rest of their keys
+const MSD_CUTOFF: usize = 32;
+
+// MSD radix sort of byte strings, in place as an American flag sort: count
+// the strings of a range by their byte at the current depth, with strings
+// ending there first, swap each into its bucket, and carry on one byte
+// deeper in every bucket, from a work list rather than the call stack so
+// long shared prefixes can't overflow it. Not stable.
+pub fn msd_radix_sort<T: AsRef<[u8]>>(v: &mut [T]) {
+    msd_radix_sort_by_key(v, |s| s.as_ref());
+}
+
+pub fn msd_radix_sort_by_key<T, F: Fn(&T) -> &[u8]>(v: &mut [T], key: F) {
+    let mut work = vec![(0, v.len(), 0)];
+    while let Some((lo, hi, depth)) = work.pop() {
+        let part = &mut v[lo..hi];
+        // every key in part has the same first depth bytes
+        if part.len() <= MSD_CUTOFF {
+            insertion_sort_by(part, |a, b| key(a)[depth..].cmp(&key(b)[depth..]));
+            continue;
+        }
+        let bucket = |x: &T| key(x).get(depth).map_or(0, |&b| b as usize + 1);
+        let mut counts = [0usize; 257];
+        for x in part.iter() {
+            counts[bucket(x)] += 1;
+        }
+        let (mut next, mut end) = ([0; 257], [0; 257]);
+        let mut sum = 0;
+        for b in 0..257 {
+            next[b] = sum;
+            sum += counts[b];
+            end[b] = sum;
+        }
+        for b in 0..257 {
+            while next[b] < end[b] {
+                let home = bucket(&part[next[b]]);
+                if home == b {
+                    next[b] += 1;
+                } else {
+                    part.swap(next[b], next[home]);
+                    next[home] += 1;
+                }
+            }
+        }
+        // the strings that ended are equal, the rest go one byte deeper
+        for b in 1..257 {
+            if counts[b] > 1 {
+                work.push((lo + end[b] - counts[b], lo + end[b], depth + 1));
+            }
+        }
+    }
+}
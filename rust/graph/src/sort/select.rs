@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+
+use super::insertion_sort_by;
+use super::intro::introsort_by;
+use super::quick::partition3;
+use crate::binary_heap::BinaryHeap;
+use crate::rng::{Rng, SplitMix64};
+
+// slices this short are insertion sorted instead of partitioned
+const CUTOFF: usize = 16;
+
+// Quickselect: rearranges v so that v[k] is the item a sort would put
+// there, none after it less and none before it greater, and returns it.
+// Each round partitions three ways around a pseudorandom pivot and goes on
+// only into the side holding k, O(n) expected. The pivots come from a
+// fixed seed, so runs are reproducible. Panics if k is out of bounds.
+pub fn kth_smallest<T: Ord>(v: &mut [T], k: usize) -> &T {
+    kth_smallest_by(v, k, T::cmp)
+}
+
+pub fn kth_smallest_by<T, F: FnMut(&T, &T) -> Ordering>(
+    v: &mut [T],
+    k: usize,
+    mut compare: F,
+) -> &T {
+    let mut rng = SplitMix64::new(0x5e1ec7);
+    select(v, k, &mut Strategy::Random(&mut rng), &mut compare);
+    &v[k]
+}
+
+pub fn kth_smallest_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], k: usize, mut key: F) -> &T {
+    kth_smallest_by(v, k, |a, b| key(a).cmp(&key(b)))
+}
+
+// The same, with the median of medians of five as every pivot: at least
+// 3/10 of a slice is left behind by each round, so O(n) at worst, for
+// about twice the comparisons of the random pivots.
+pub fn kth_smallest_deterministic<T: Ord>(v: &mut [T], k: usize) -> &T {
+    kth_smallest_deterministic_by(v, k, T::cmp)
+}
+
+pub fn kth_smallest_deterministic_by<T, F: FnMut(&T, &T) -> Ordering>(
+    v: &mut [T],
+    k: usize,
+    mut compare: F,
+) -> &T {
+    select(v, k, &mut Strategy::MedianOfMedians, &mut compare);
+    &v[k]
+}
+
+pub fn kth_smallest_deterministic_by_key<T, K: Ord, F: FnMut(&T) -> K>(
+    v: &mut [T],
+    k: usize,
+    mut key: F,
+) -> &T {
+    kth_smallest_deterministic_by(v, k, |a, b| key(a).cmp(&key(b)))
+}
+
+enum Strategy<'a> {
+    Random(&'a mut SplitMix64),
+    MedianOfMedians,
+}
+
+fn select<T, F: FnMut(&T, &T) -> Ordering>(
+    mut v: &mut [T],
+    mut k: usize,
+    strategy: &mut Strategy,
+    compare: &mut F,
+) {
+    assert!(
+        k < v.len(),
+        "k is {k} but the slice holds {} items",
+        v.len()
+    );
+    loop {
+        if v.len() <= CUTOFF {
+            insertion_sort_by(v, &mut *compare);
+            return;
+        }
+        let p = match strategy {
+            Strategy::Random(rng) => rng.gen_range(v.len()),
+            Strategy::MedianOfMedians => median_of_medians(v, compare),
+        };
+        v.swap(0, p);
+        let (lo, hi) = partition3(v, compare);
+        if k < lo {
+            v = &mut v[..lo];
+        } else if k >= hi {
+            v = &mut v[hi..];
+            k -= hi;
+        } else {
+            return;
+        }
+    }
+}
+
+// Moves the median of every group of five to the front, in group order,
+// and selects the median of those; returns its index.
+fn median_of_medians<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut F) -> usize {
+    let groups = v.len().div_ceil(5);
+    for g in 0..groups {
+        let (start, end) = (5 * g, (5 * g + 5).min(v.len()));
+        insertion_sort_by(&mut v[start..end], &mut *compare);
+        // slot g belongs to a group already done with
+        v.swap(g, (start + end) / 2);
+    }
+    let mid = groups / 2;
+    select(
+        &mut v[..groups],
+        mid,
+        &mut Strategy::MedianOfMedians,
+        compare,
+    );
+    mid
+}
+
+// Sorts the k smallest items into v[..k], the rest left after them in no
+// particular order: a selection, then introsort of the prefix, O(n + k log
+// k). Not stable.
+pub fn partial_sort<T: Ord>(v: &mut [T], k: usize) {
+    partial_sort_by(v, k, T::cmp);
+}
+
+pub fn partial_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], k: usize, mut compare: F) {
+    if k >= v.len() {
+        introsort_by(v, compare);
+        return;
+    }
+    if k == 0 {
+        return;
+    }
+    kth_smallest_by(v, k - 1, &mut compare);
+    introsort_by(&mut v[..k - 1], compare);
+}
+
+pub fn partial_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], k: usize, mut key: F) {
+    partial_sort_by(v, k, |a, b| key(a).cmp(&key(b)));
+}
+
+// The k largest items of a stream, largest first, kept in a min-heap of k
+// whose top is the one to beat: O(n log k) time, O(k) space.
+pub fn top_k<T: Ord, I: IntoIterator<Item = T>>(items: I, k: usize) -> Vec<T> {
+    top_k_by(items, k, T::cmp)
+}
+
+pub fn top_k_by<T, I, F>(items: I, k: usize, compare: F) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut heap = BinaryHeap::with_compare(&compare);
+    for item in items {
+        if heap.len() < k {
+            heap.push(item);
+        } else if heap
+            .peek()
+            .is_some_and(|top| compare(&item, top) == Ordering::Greater)
+        {
+            heap.replace_top(item);
+        }
+    }
+    let mut top = heap.into_sorted_vec();
+    top.reverse();
+    top
+}
+
+pub fn top_k_by_key<T, K, I, F>(items: I, k: usize, key: F) -> Vec<T>
+where
+    K: Ord,
+    I: IntoIterator<Item = T>,
+    F: Fn(&T) -> K,
+{
+    top_k_by(items, k, |a, b| key(a).cmp(&key(b)))
+}
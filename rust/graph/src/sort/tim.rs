@@ -0,0 +1,334 @@
+use std::{cmp::Ordering, ptr, slice};
+
+use super::merge::Hole;
+
+// wins in a row for one run before a merge starts galloping
+const MIN_GALLOP: usize = 7;
+
+// Timsort-style adaptive mergesort. The slice is cut into its natural runs,
+// ascending or strictly descending (reversed in place), short runs padded
+// to a minimum length with insertion sort. Runs go on a stack whose
+// lengths are kept growing faster than Fibonacci downwards, merging the
+// top ones when they'd break that, so merges stay balanced and the stack
+// O(log n) deep. Merges first skip what is already in place at either end,
+// then gallop, exponential then binary searches for how far one run's
+// items go before the other's next, whenever one run keeps winning.
+// Sorted or reverse sorted input is O(n), few runs O(n log runs), and the
+// worst case O(n log n). Stable.
+pub fn tim_sort<T: Ord>(v: &mut [T]) {
+    tim_sort_by(v, T::cmp);
+}
+
+pub fn tim_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    let mut less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    let n = v.len();
+    if n < 2 {
+        return;
+    }
+    let min_run = min_run(n);
+    let mut merger = Merger {
+        buf: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+    // (start, len) of each pending run, bottom first
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0;
+    while start < n {
+        let rest = &mut v[start..];
+        let mut len = run_length(rest, &mut less);
+        if len < min_run {
+            let forced = min_run.min(rest.len());
+            binary_insertion_sort(&mut rest[..forced], len, &mut less);
+            len = forced;
+        }
+        runs.push((start, len));
+        start += len;
+        collapse(v, &mut runs, &mut merger, &mut less, false);
+    }
+    collapse(v, &mut runs, &mut merger, &mut less, true);
+}
+
+pub fn tim_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    tim_sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+// n for n below 64, otherwise between 32 and 64 such that n / min_run is
+// a power of two or just under, so the final merges are balanced
+fn min_run(mut n: usize) -> usize {
+    let mut odd = 0;
+    while n >= 64 {
+        odd |= n & 1;
+        n >>= 1;
+    }
+    n + odd
+}
+
+// length of the run at the front of v, a strictly descending one reversed
+fn run_length<T>(v: &mut [T], less: &mut impl FnMut(&T, &T) -> bool) -> usize {
+    if v.len() < 2 {
+        return v.len();
+    }
+    let mut end = 2;
+    if less(&v[1], &v[0]) {
+        // strictly, so reversing can't reorder equal items
+        while end < v.len() && less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+        v[..end].reverse();
+    } else {
+        while end < v.len() && !less(&v[end], &v[end - 1]) {
+            end += 1;
+        }
+    }
+    end
+}
+
+// Extends the sorted prefix v[..sorted] to all of v, finding each item's
+// place by binary search, after any equal ones: O(n log n) comparisons
+// where plain insertion sort makes O(n^2) on a random run.
+fn binary_insertion_sort<T>(v: &mut [T], sorted: usize, less: &mut impl FnMut(&T, &T) -> bool) {
+    for i in sorted.max(1)..v.len() {
+        let (prefix, rest) = v.split_at(i);
+        let at = prefix.partition_point(|x| !less(&rest[0], x));
+        v[at..=i].rotate_right(1);
+    }
+}
+
+// Merges runs until the lengths from the top, A B C D with A topmost,
+// satisfy B > A, C > B + A and D > C + B, or down to one run if all. The
+// fourth run in the test is the fix to Timsort's original invariant, which
+// could break lower in the stack.
+fn collapse<T>(
+    v: &mut [T],
+    runs: &mut Vec<(usize, usize)>,
+    merger: &mut Merger<T>,
+    less: &mut impl FnMut(&T, &T) -> bool,
+    all: bool,
+) {
+    while runs.len() > 1 {
+        let n = runs.len();
+        let len = |i: usize| runs[i].1;
+        let at = if (n >= 3 && len(n - 3) <= len(n - 2) + len(n - 1))
+            || (n >= 4 && len(n - 4) <= len(n - 3) + len(n - 2))
+        {
+            // merge the middle run with its shorter neighbour
+            if len(n - 3) < len(n - 1) {
+                n - 3
+            } else {
+                n - 2
+            }
+        } else if all || len(n - 2) <= len(n - 1) {
+            n - 2
+        } else {
+            break;
+        };
+        let ((start, left), (_, right)) = (runs[at], runs[at + 1]);
+        merger.merge(&mut v[start..start + left + right], left, less);
+        runs[at].1 = left + right;
+        runs.remove(at + 1);
+    }
+}
+
+struct Merger<T> {
+    // capacity only, grown to the shorter run of each merge
+    buf: Vec<T>,
+    // adapts: lower while galloping pays, higher when it doesn't
+    min_gallop: usize,
+}
+
+impl<T> Merger<T> {
+    fn merge(&mut self, v: &mut [T], mid: usize, less: &mut impl FnMut(&T, &T) -> bool) {
+        // left items no greater than the right run's first, and right items
+        // no less than the left run's last, are already in place
+        let (left, right) = v.split_at(mid);
+        let skip = gallop_front(left, |x| !less(&right[0], x));
+        let keep = gallop_back(right, |x| !less(x, &left[mid - 1]));
+        let end = v.len() - keep;
+        let v = &mut v[skip..end];
+        let mid = mid - skip;
+        if mid == 0 || mid == v.len() {
+            return;
+        }
+        let shorter = mid.min(v.len() - mid);
+        self.buf.reserve(shorter);
+        let buf = self.buf.as_mut_ptr();
+        // the buffer holds the shorter run, and nothing else uses it
+        unsafe {
+            if mid == shorter {
+                self.merge_lo(v, mid, buf, less);
+            } else {
+                self.merge_hi(v, mid, buf, less);
+            }
+        }
+    }
+
+    // The left run is buffered and merged front to back. As in merge.rs,
+    // the hole refills the gap with what's left of the buffer, on panic or
+    // when done.
+    unsafe fn merge_lo(
+        &mut self,
+        v: &mut [T],
+        mid: usize,
+        buf: *mut T,
+        less: &mut impl FnMut(&T, &T) -> bool,
+    ) {
+        let v_ptr = v.as_mut_ptr();
+        ptr::copy_nonoverlapping(v_ptr, buf, mid);
+        let mut hole = Hole {
+            start: buf,
+            end: buf.add(mid),
+            dest: v_ptr,
+        };
+        let mut right = v_ptr.add(mid);
+        let end = v_ptr.add(v.len());
+        let live =
+            |from: *mut T, to: *mut T| slice::from_raw_parts(from, to.offset_from(from) as usize);
+        'merge: loop {
+            // one item at a time, counting wins in a row
+            let (mut left_wins, mut right_wins) = (0, 0);
+            loop {
+                if hole.start == hole.end || right == end {
+                    break 'merge;
+                }
+                if less(&*right, &*hole.start) {
+                    ptr::copy_nonoverlapping(right, hole.dest, 1);
+                    right = right.add(1);
+                    (left_wins, right_wins) = (0, right_wins + 1);
+                } else {
+                    ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                    hole.start = hole.start.add(1);
+                    (left_wins, right_wins) = (left_wins + 1, 0);
+                }
+                hole.dest = hole.dest.add(1);
+                if left_wins.max(right_wins) >= self.min_gallop {
+                    break;
+                }
+            }
+            // galloping, while either run wins big
+            loop {
+                let k = gallop_front(live(hole.start, hole.end), |x| !less(&*right, x));
+                ptr::copy_nonoverlapping(hole.start, hole.dest, k);
+                hole.start = hole.start.add(k);
+                hole.dest = hole.dest.add(k);
+                if hole.start == hole.end {
+                    break 'merge;
+                }
+                let k2 = gallop_front(live(right, end), |x| less(x, &*hole.start));
+                // the gap is shorter than the buffer's rest, the copy may overlap
+                ptr::copy(right, hole.dest, k2);
+                right = right.add(k2);
+                hole.dest = hole.dest.add(k2);
+                if right == end {
+                    break 'merge;
+                }
+                self.min_gallop = self.min_gallop.saturating_sub(1).max(1);
+                if k < MIN_GALLOP && k2 < MIN_GALLOP {
+                    self.min_gallop += 2;
+                    break;
+                }
+            }
+        }
+    }
+
+    // The right run is buffered and merged back to front, the mirror image.
+    unsafe fn merge_hi(
+        &mut self,
+        v: &mut [T],
+        mid: usize,
+        buf: *mut T,
+        less: &mut impl FnMut(&T, &T) -> bool,
+    ) {
+        let v_ptr = v.as_mut_ptr();
+        ptr::copy_nonoverlapping(v_ptr.add(mid), buf, v.len() - mid);
+        // hole.dest is the end of the unmerged left items, the gap from it
+        // runs up to out
+        let mut hole = Hole {
+            start: buf,
+            end: buf.add(v.len() - mid),
+            dest: v_ptr.add(mid),
+        };
+        let mut out = v_ptr.add(v.len());
+        let live =
+            |from: *mut T, to: *mut T| slice::from_raw_parts(from, to.offset_from(from) as usize);
+        'merge: loop {
+            let (mut left_wins, mut right_wins) = (0, 0);
+            loop {
+                if hole.dest == v_ptr || hole.start == hole.end {
+                    break 'merge;
+                }
+                let (left, right) = (hole.dest.sub(1), hole.end.sub(1));
+                out = out.sub(1);
+                if less(&*right, &*left) {
+                    ptr::copy_nonoverlapping(left, out, 1);
+                    hole.dest = left;
+                    (left_wins, right_wins) = (left_wins + 1, 0);
+                } else {
+                    ptr::copy_nonoverlapping(right, out, 1);
+                    hole.end = right;
+                    (left_wins, right_wins) = (0, right_wins + 1);
+                }
+                if left_wins.max(right_wins) >= self.min_gallop {
+                    break;
+                }
+            }
+            loop {
+                // left items greater than the last buffered one go last
+                let right_last = hole.end.sub(1);
+                let k = gallop_back(live(v_ptr, hole.dest), |x| less(&*right_last, x));
+                out = out.sub(k);
+                hole.dest = hole.dest.sub(k);
+                ptr::copy(hole.dest, out, k);
+                if hole.dest == v_ptr {
+                    break 'merge;
+                }
+                let left_last = hole.dest.sub(1);
+                let k2 = gallop_back(live(hole.start, hole.end), |x| !less(x, &*left_last));
+                out = out.sub(k2);
+                hole.end = hole.end.sub(k2);
+                ptr::copy_nonoverlapping(hole.end, out, k2);
+                if hole.start == hole.end {
+                    break 'merge;
+                }
+                self.min_gallop = self.min_gallop.saturating_sub(1).max(1);
+                if k < MIN_GALLOP && k2 < MIN_GALLOP {
+                    self.min_gallop += 2;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// How many items from the front of run satisfy pred, which holds for a
+// prefix of it: probes 1, 3, 7, ... in then binary searches the last step,
+// O(log k) for an answer of k.
+fn gallop_front<T>(run: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let mut lo = 0;
+    let mut step = 1;
+    while lo < run.len() && pred(&run[lo]) {
+        lo += step;
+        step *= 2;
+    }
+    // everything before the last probe that held passes, lo itself fails or
+    // is past the end
+    let from = lo.saturating_sub(step / 2).min(run.len());
+    let to = lo.min(run.len());
+    from + run[from..to].partition_point(pred)
+}
+
+// how many items from the back of run satisfy pred, which holds for a
+// suffix of it
+fn gallop_back<T>(run: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let n = run.len();
+    let mut k = 0;
+    let mut step = 1;
+    while k < n && pred(&run[n - 1 - k]) {
+        k += step;
+        step *= 2;
+    }
+    let from = k.saturating_sub(step / 2).min(n);
+    let to = k.min(n);
+    // the last step skipped over n - to..n - from, pred fails then holds
+    let skipped = &run[n - to..n - from];
+    from + skipped.len() - skipped.partition_point(|x| !pred(x))
+}
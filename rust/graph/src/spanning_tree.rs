@@ -0,0 +1,267 @@
+use std::hash::Hash;
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::rng::Rng;
+use crate::tree::bfs;
+
+// Undirected multigraphs only: every edge stored in both directions.
+// Parallel edges count as distinct, self loops are ignored.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpanningTreeCount {
+    Exact(u128),
+    // the exact determinant overflowed, this is a floating point estimate
+    Approx(f64),
+}
+
+impl SpanningTreeCount {
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            SpanningTreeCount::Exact(c) => c as f64,
+            SpanningTreeCount::Approx(c) => c,
+        }
+    }
+}
+
+// exact counting is O(k n^3) for k primes, above this size only the
+// floating point determinant is computed
+pub const EXACT_LIMIT: usize = 100;
+
+// Kirchhoff's matrix tree theorem: the count is any cofactor of the
+// Laplacian. For small graphs the determinant is taken modulo enough 62 bit
+// primes to cover the Hadamard bound and rebuilt with the chinese remainder
+// theorem; if the result fits in a u128 it is exact.
+pub fn count_spanning_trees<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> SpanningTreeCount {
+    let g = graph.indexed();
+    let n = g.keys.len();
+    if n == 0 {
+        return SpanningTreeCount::Exact(0);
+    }
+    // laplacian with the last row and column removed
+    let m = n - 1;
+    let mut lap = vec![vec![0i64; m]; m];
+    for (v, nbrs) in g.adj.iter().enumerate().take(m) {
+        for &(u, _) in nbrs.iter() {
+            if u != v {
+                lap[v][v] += 1;
+                if u < m {
+                    lap[v][u] -= 1;
+                }
+            }
+        }
+    }
+    if n <= EXACT_LIMIT {
+        if let Some(count) = exact_det(&lap) {
+            return SpanningTreeCount::Exact(count);
+        }
+    }
+    let lap = lap
+        .into_iter()
+        .map(|row| row.into_iter().map(|x| x as f64).collect())
+        .collect();
+    SpanningTreeCount::Approx(gauss(lap).max(0.0).round())
+}
+
+// Wilson's algorithm: loop erased random walks towards the growing tree
+// give a spanning tree drawn uniformly from all of them. None if the graph
+// is empty or disconnected, or has an edge without its reverse, where a
+// walk could reach a vertex with no way out.
+pub fn random_spanning_tree<T, S, R>(graph: &Graph<T, S>, rng: &mut R) -> Option<Vec<(T, T)>>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+    R: Rng,
+{
+    let g = graph.indexed();
+    let n = g.keys.len();
+    if n == 0 || bfs(&g.adj, 0).0.len() != n {
+        return None;
+    }
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        if nbrs
+            .iter()
+            .any(|&(u, _)| u != v && !g.adj[u].iter().any(|&(w, _)| w == v))
+        {
+            return None;
+        }
+    }
+    let adj: Vec<Vec<usize>> = g
+        .adj
+        .iter()
+        .enumerate()
+        .map(|(v, nbrs)| nbrs.iter().map(|&(u, _)| u).filter(|&u| u != v).collect())
+        .collect();
+    let mut in_tree = vec![false; n];
+    let mut next = vec![usize::MAX; n];
+    in_tree[rng.gen_range(n)] = true;
+    for start in 0..n {
+        // the walk overwrites next[], which erases the loops implicitly
+        let mut v = start;
+        while !in_tree[v] {
+            next[v] = adj[v][rng.gen_range(adj[v].len())];
+            v = next[v];
+        }
+        let mut v = start;
+        while !in_tree[v] {
+            in_tree[v] = true;
+            v = next[v];
+        }
+    }
+    Some(
+        (0..n)
+            .filter(|&v| next[v] != usize::MAX)
+            .map(|v| (g.keys[v].clone(), g.keys[next[v]].clone()))
+            .collect(),
+    )
+}
+
+// determinant of a non-negative definite integer matrix, None if it
+// does not fit in a u128
+fn exact_det(a: &[Vec<i64>]) -> Option<u128> {
+    // Hadamard: |det| <= product of the row norms
+    let bits: f64 = a
+        .iter()
+        .map(|row| {
+            0.5 * row
+                .iter()
+                .map(|&x| (x as f64) * (x as f64))
+                .sum::<f64>()
+                .log2()
+        })
+        .sum();
+    let mut primes = Vec::new();
+    let mut p = 1u64 << 62;
+    while (primes.len() as f64) * 61.0 < bits.max(0.0) + 2.0 {
+        p -= 1;
+        if is_prime(p) {
+            primes.push(p);
+        }
+    }
+    let residues: Vec<u64> = primes.iter().map(|&p| det_mod(a, p)).collect();
+
+    // Garner's mixed radix form, x = c0 + c1 p0 + c2 p0 p1 + ...
+    let mut c = vec![0u64; primes.len()];
+    for i in 0..primes.len() {
+        let pi = primes[i];
+        let mut t = residues[i];
+        for j in 0..i {
+            let diff = (t + pi - c[j] % pi) % pi;
+            t = mul_mod(diff, pow_mod(primes[j] % pi, pi - 2, pi), pi);
+        }
+        c[i] = t;
+    }
+    let mut x = 0u128;
+    for i in (0..primes.len()).rev() {
+        x = x
+            .checked_mul(primes[i] as u128)?
+            .checked_add(c[i] as u128)?;
+    }
+    Some(x)
+}
+
+fn det_mod(a: &[Vec<i64>], p: u64) -> u64 {
+    let n = a.len();
+    let mut m: Vec<Vec<u64>> = a
+        .iter()
+        .map(|row| row.iter().map(|&x| x.rem_euclid(p as i64) as u64).collect())
+        .collect();
+    let mut det = 1u64;
+    for k in 0..n {
+        let Some(piv) = (k..n).find(|&i| m[i][k] != 0) else {
+            return 0;
+        };
+        if piv != k {
+            m.swap(k, piv);
+            det = p - det;
+        }
+        det = mul_mod(det, m[k][k], p);
+        let inv = pow_mod(m[k][k], p - 2, p);
+        let (top, rest) = m.split_at_mut(k + 1);
+        let pivot = &top[k];
+        for row in rest.iter_mut() {
+            let f = mul_mod(row[k], inv, p);
+            if f == 0 {
+                continue;
+            }
+            for (x, &y) in row[k..].iter_mut().zip(pivot[k..].iter()) {
+                *x = (*x + p - mul_mod(f, y, p)) % p;
+            }
+        }
+    }
+    det % p
+}
+
+fn mul_mod(a: u64, b: u64, p: u64) -> u64 {
+    (a as u128 * b as u128 % p as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, p: u64) -> u64 {
+    let mut acc = 1;
+    base %= p;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul_mod(acc, base, p);
+        }
+        base = mul_mod(base, base, p);
+        exp >>= 1;
+    }
+    acc
+}
+
+// deterministic Miller-Rabin for 64 bit integers
+fn is_prime(n: u64) -> bool {
+    if n < 2 || n.is_multiple_of(2) {
+        return n == 2;
+    }
+    let (mut d, mut s) = (n - 1, 0);
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    'witness: for a in [2u64, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if a.is_multiple_of(n) {
+            continue;
+        }
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+fn gauss(mut a: Vec<Vec<f64>>) -> f64 {
+    let n = a.len();
+    let mut det = 1.0;
+    for k in 0..n {
+        let p = (k..n)
+            .max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))
+            .unwrap();
+        if a[p][k] == 0.0 {
+            return 0.0;
+        }
+        if p != k {
+            a.swap(k, p);
+            det = -det;
+        }
+        det *= a[k][k];
+        let (top, rest) = a.split_at_mut(k + 1);
+        let pivot = &top[k];
+        for row in rest.iter_mut() {
+            let f = row[k] / pivot[k];
+            for (x, &p) in row[k..].iter_mut().zip(pivot[k..].iter()) {
+                *x -= f * p;
+            }
+        }
+    }
+    det
+}
@@ -0,0 +1,69 @@
+use std::{marker::PhantomData, ops::Range};
+
+use crate::num::Integer;
+use crate::segment_tree::{Gcd, Max, Min, Monoid};
+
+// Monoids with combine(a, a) == a. Overlapping parts of a range then do no
+// harm, so any range is covered by two precomputed power of two blocks.
+pub trait IdempotentOp<T>: Monoid<T> {}
+
+impl<T: Integer> IdempotentOp<T> for Min {}
+impl<T: Integer> IdempotentOp<T> for Max {}
+impl<T: Integer> IdempotentOp<T> for Gcd {}
+
+// Static range queries: levels[k][i] combines values[i..i + 2^k]. O(n log n)
+// to build, O(1) per query.
+#[derive(Debug, Clone)]
+pub struct SparseTable<T, Op> {
+    levels: Vec<Vec<T>>,
+    op: PhantomData<Op>,
+}
+
+impl<T: Clone, Op: IdempotentOp<T>> SparseTable<T, Op> {
+    pub fn new(values: Vec<T>) -> Self {
+        let mut levels = vec![values];
+        let mut width = 1;
+        while 2 * width <= levels[0].len() {
+            let prev = levels.last().unwrap();
+            let next = (0..prev.len() - width)
+                .map(|i| Op::combine(&prev[i], &prev[i + width]))
+                .collect();
+            levels.push(next);
+            width *= 2;
+        }
+        Self {
+            levels,
+            op: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // combination of the values in range, the identity when it is empty
+    pub fn query(&self, range: Range<usize>) -> T {
+        let Range { start, end } = range;
+        assert!(
+            start <= end && end <= self.len(),
+            "range {start}..{end} out of range for length {}",
+            self.len()
+        );
+        if start == end {
+            return Op::identity();
+        }
+        let k = (end - start).ilog2() as usize;
+        let level = &self.levels[k];
+        Op::combine(&level[start], &level[end - (1 << k)])
+    }
+}
+
+impl<T: Clone, Op: IdempotentOp<T>> FromIterator<T> for SparseTable<T, Op> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
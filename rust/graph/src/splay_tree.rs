@@ -0,0 +1,319 @@
+use std::{borrow::Borrow, cmp::Ordering, fmt};
+
+// Self-adjusting ordered map: every access splays the node it finds to the
+// root with top-down rotations, so the operations take O(log n) amortized
+// and keys used often or recently stay near the top. There is no balance
+// information beyond the subtree sizes, which keep len and split O(1) extra.
+pub struct SplayTree<K, V> {
+    root: Link<K, V>,
+}
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |n| n.size)
+}
+
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+// Brings the node where the search for target ends to the root. target
+// tells whether the wanted key is Less, Equal or Greater than a node key.
+// The nodes passed on the way are split off into a left tree of smaller
+// and a right tree of larger keys, which become the new root's subtrees.
+fn splay<K, V>(mut t: Box<Node<K, V>>, target: impl Fn(&K) -> Ordering) -> Box<Node<K, V>> {
+    // pieces of the left tree, each hangs as the right child of the one
+    // before it; the right tree the other way around
+    let mut left: Vec<Box<Node<K, V>>> = Vec::new();
+    let mut right: Vec<Box<Node<K, V>>> = Vec::new();
+    loop {
+        match target(&t.key) {
+            Ordering::Less => {
+                let Some(mut l) = t.left.take() else { break };
+                if target(&l.key) == Ordering::Less {
+                    // zig-zig, rotate right first
+                    t.left = l.right.take();
+                    t.update();
+                    l.right = Some(t);
+                    t = l;
+                    let Some(next) = t.left.take() else { break };
+                    right.push(t);
+                    t = next;
+                } else {
+                    right.push(t);
+                    t = l;
+                }
+            }
+            Ordering::Greater => {
+                let Some(mut r) = t.right.take() else { break };
+                if target(&r.key) == Ordering::Greater {
+                    t.right = r.left.take();
+                    t.update();
+                    r.left = Some(t);
+                    t = r;
+                    let Some(next) = t.right.take() else { break };
+                    left.push(t);
+                    t = next;
+                } else {
+                    left.push(t);
+                    t = r;
+                }
+            }
+            Ordering::Equal => break,
+        }
+    }
+    let mut acc = t.left.take();
+    for mut piece in left.into_iter().rev() {
+        piece.right = acc;
+        piece.update();
+        acc = Some(piece);
+    }
+    t.left = acc;
+    let mut acc = t.right.take();
+    for mut piece in right.into_iter().rev() {
+        piece.left = acc;
+        piece.update();
+        acc = Some(piece);
+    }
+    t.right = acc;
+    t.update();
+    t
+}
+
+impl<K: Ord, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> SplayTree<K, V> {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // the key at the root, the most recently accessed one
+    pub fn root_key(&self) -> Option<&K> {
+        self.root.as_ref().map(|n| &n.key)
+    }
+
+    pub fn find<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.find_mut(key).map(|v| &*v)
+    }
+
+    pub fn find_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let root = self.splay_at(key)?;
+        (root.key.borrow() == key).then_some(&mut root.value)
+    }
+
+    pub fn contains<Q: Ord + ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let Some(mut root) = self.root.take().map(|t| splay(t, |k| key.cmp(k))) else {
+            self.root = Some(Box::new(Node {
+                key,
+                value,
+                size: 1,
+                left: None,
+                right: None,
+            }));
+            return None;
+        };
+        let (left, right) = match key.cmp(&root.key) {
+            Ordering::Equal => {
+                let old = std::mem::replace(&mut root.value, value);
+                self.root = Some(root);
+                return Some(old);
+            }
+            Ordering::Less => {
+                let left = root.left.take();
+                root.update();
+                (left, Some(root))
+            }
+            Ordering::Greater => {
+                let right = root.right.take();
+                root.update();
+                (Some(root), right)
+            }
+        };
+        let mut node = Box::new(Node {
+            key,
+            value,
+            size: 0,
+            left,
+            right,
+        });
+        node.update();
+        self.root = Some(node);
+        None
+    }
+
+    pub fn delete<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        if self.splay_at(key)?.key.borrow() != key {
+            return None;
+        }
+        let mut root = self.root.take().unwrap();
+        let mut rest = Self {
+            root: root.left.take(),
+        };
+        rest.join(Self {
+            root: root.right.take(),
+        });
+        *self = rest;
+        Some(root.value)
+    }
+
+    // Smallest and largest entries. Both splay, so a following join or
+    // split near the ends is cheap.
+    pub fn first(&mut self) -> Option<(&K, &V)> {
+        let root = self.splay_end(Ordering::Less)?;
+        Some((&root.key, &root.value))
+    }
+
+    pub fn last(&mut self) -> Option<(&K, &V)> {
+        let root = self.splay_end(Ordering::Greater)?;
+        Some((&root.key, &root.value))
+    }
+
+    // moves the keys >= key into the returned tree
+    pub fn split<Q: Ord + ?Sized>(&mut self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+    {
+        let Some(mut root) = self.root.take().map(|t| splay(t, |k| key.cmp(k.borrow()))) else {
+            return Self::new();
+        };
+        if root.key.borrow() < key {
+            let right = root.right.take();
+            root.update();
+            self.root = Some(root);
+            Self { root: right }
+        } else {
+            self.root = root.left.take();
+            root.update();
+            Self { root: Some(root) }
+        }
+    }
+
+    // moves all keys of other into self, every key of other must be greater
+    // than the keys of self
+    pub fn join(&mut self, mut other: Self) {
+        if other.is_empty() {
+            return;
+        }
+        let Some(root) = self.splay_end(Ordering::Greater) else {
+            *self = other;
+            return;
+        };
+        let (min, _) = other.first().unwrap();
+        assert!(root.key < *min, "joined splay tree overlaps this one");
+        root.right = other.root.take();
+        root.update();
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.descend(&self.root);
+        iter
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn splay_at<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut Box<Node<K, V>>>
+    where
+        K: Borrow<Q>,
+    {
+        let root = splay(self.root.take()?, |k| key.cmp(k.borrow()));
+        Some(self.root.insert(root))
+    }
+
+    fn splay_end(&mut self, side: Ordering) -> Option<&mut Box<Node<K, V>>> {
+        let root = splay(self.root.take()?, |_| side);
+        Some(self.root.insert(root))
+    }
+}
+
+// a long path, which a splay tree may well have, would overflow the stack
+// with the recursive drop of the boxes
+impl<K, V> Drop for SplayTree<K, V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<K, V>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SplayTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SplayTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (k, v) in iter {
+            tree.insert(k, v);
+        }
+        tree
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn descend(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.descend(&node.right);
+        Some((&node.key, &node.value))
+    }
+}
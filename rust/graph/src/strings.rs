@@ -0,0 +1,45 @@
+// Exact string matching over bytes, one algorithm per submodule, and the
+// prefix functions behind it. Text and patterns are byte slices, so a &str
+// is searched by its as_bytes() and positions are byte offsets; an empty
+// pattern never matches, as in aho_corasick.
+
+mod boyer_moore;
+mod kmp;
+mod prefix;
+mod rolling_hash;
+
+pub use boyer_moore::{BoyerMoore, Horspool};
+pub use kmp::{failure_function, find_all, FindAll, Kmp, KmpStream, StreamMatches};
+pub use prefix::{
+    distinct_prefixes_in, periods, prefix_function, prefix_matches, prefix_occurrences,
+    primitive_root, smallest_period, z_array, z_find_all,
+};
+pub use rolling_hash::{Moduli, RabinKarp, RollingHash, UnequalLengths};
+
+// A single pattern matcher, preprocessed once and run over any number of
+// texts.
+pub trait Matcher {
+    fn pattern(&self) -> &[u8];
+
+    // the first match starting at or after from
+    fn find_from(&self, text: &[u8], from: usize) -> Option<usize>;
+
+    fn find(&self, text: &[u8]) -> Option<usize> {
+        self.find_from(text, 0)
+    }
+
+    // start of every match, overlapping ones included
+    fn matches(&self, text: &[u8]) -> Vec<usize> {
+        let mut starts = Vec::new();
+        let mut from = 0;
+        while let Some(at) = self.find_from(text, from) {
+            starts.push(at);
+            from = at + 1;
+        }
+        starts
+    }
+
+    fn count(&self, text: &[u8]) -> usize {
+        self.matches(text).len()
+    }
+}
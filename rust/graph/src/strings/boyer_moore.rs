@@ -0,0 +1,136 @@
+use super::Matcher;
+
+// Boyer-Moore: the window is compared right to left, and a mismatch moves
+// it by the larger of two shifts. The bad character rule lines the
+// mismatched text byte up with its last occurrence in the pattern; the
+// good suffix rule lines the matched suffix up with its previous
+// occurrence in the pattern preceded by a different byte, or with the
+// longest prefix that is a suffix of it. Sublinear on long patterns over
+// large alphabets, often reading only n / m bytes; O(nm) at worst when
+// every match of a periodic pattern is wanted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoyerMoore {
+    pattern: Vec<u8>,
+    // 1 + the last index of each byte in the pattern, 0 if absent
+    last: [usize; 256],
+    // good_suffix[j], for pattern[j..] matched and pattern[j - 1] not
+    good_suffix: Vec<usize>,
+}
+
+impl BoyerMoore {
+    pub fn new<P: AsRef<[u8]>>(pattern: P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        let m = pattern.len();
+        let mut last = [0; 256];
+        for (i, &b) in pattern.iter().enumerate() {
+            last[b as usize] = i + 1;
+        }
+        // border[i]: where the widest border of pattern[i..] starts
+        let mut border = vec![0; m + 1];
+        let mut good_suffix = vec![0; m + 1];
+        let (mut i, mut j) = (m, m + 1);
+        border[i] = j;
+        while i > 0 {
+            while j <= m && pattern[i - 1] != pattern[j - 1] {
+                // pattern[j..] recurs at i, preceded by a different byte
+                if good_suffix[j] == 0 {
+                    good_suffix[j] = j - i;
+                }
+                j = border[j];
+            }
+            i -= 1;
+            j -= 1;
+            border[i] = j;
+        }
+        // the rest shift to the widest border of the whole pattern that fits
+        j = border[0];
+        for (i, shift) in good_suffix.iter_mut().enumerate() {
+            if *shift == 0 {
+                *shift = j;
+            }
+            if i == j {
+                j = border[j];
+            }
+        }
+        Self {
+            pattern,
+            last,
+            good_suffix,
+        }
+    }
+}
+
+impl Matcher for BoyerMoore {
+    fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    fn find_from(&self, text: &[u8], from: usize) -> Option<usize> {
+        let m = self.pattern.len();
+        if m == 0 {
+            return None;
+        }
+        let mut s = from;
+        while s + m <= text.len() {
+            // unmatched bytes left, right to left
+            let mut j = m;
+            while j > 0 && self.pattern[j - 1] == text[s + j - 1] {
+                j -= 1;
+            }
+            if j == 0 {
+                return Some(s);
+            }
+            let bad = text[s + j - 1] as usize;
+            // past the last occurrence, or one step if that lies to the right
+            let bad_shift = j.saturating_sub(self.last[bad]).max(1);
+            s += bad_shift.max(self.good_suffix[j]);
+        }
+        None
+    }
+}
+
+// Horspool's simplification: only a bad character rule, by the text byte
+// under the window's last position, whatever mismatched. One table and one
+// lookup per window, usually as fast as Boyer-Moore on natural text, with
+// the same O(nm) worst case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Horspool {
+    pattern: Vec<u8>,
+    // how far the last byte of the pattern is from each byte's last
+    // occurrence before it, m if none
+    shift: [usize; 256],
+}
+
+impl Horspool {
+    pub fn new<P: AsRef<[u8]>>(pattern: P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        let m = pattern.len();
+        let mut shift = [m; 256];
+        for (i, &b) in pattern.iter().enumerate().take(m.saturating_sub(1)) {
+            shift[b as usize] = m - 1 - i;
+        }
+        Self { pattern, shift }
+    }
+}
+
+impl Matcher for Horspool {
+    fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    fn find_from(&self, text: &[u8], from: usize) -> Option<usize> {
+        let m = self.pattern.len();
+        if m == 0 {
+            return None;
+        }
+        let mut s = from;
+        while s + m <= text.len() {
+            let window = &text[s..s + m];
+            if window[m - 1] == self.pattern[m - 1] && *window == self.pattern[..] {
+                return Some(s);
+            }
+            s += self.shift[window[m - 1] as usize];
+        }
+        None
+    }
+}
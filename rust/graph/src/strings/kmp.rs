@@ -0,0 +1,209 @@
+use std::borrow::Cow;
+
+use super::Matcher;
+
+// Knuth-Morris-Pratt: after a mismatch the pattern slides to its longest
+// border, the longest proper prefix of the part matched so far that is
+// also a suffix of it, read from the failure function, so the text is
+// never scanned backwards. O(pattern) to build, O(text) to search, and
+// the state between bytes is one length, which is what lets a stream be
+// searched a chunk at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kmp {
+    pattern: Vec<u8>,
+    fail: Vec<usize>,
+}
+
+// fail[i] is the length of the longest proper border of pattern[..=i]
+pub fn failure_function(pattern: &[u8]) -> Vec<usize> {
+    let mut fail = vec![0; pattern.len()];
+    let mut k = 0;
+    for i in 1..pattern.len() {
+        while k > 0 && pattern[i] != pattern[k] {
+            k = fail[k - 1];
+        }
+        if pattern[i] == pattern[k] {
+            k += 1;
+        }
+        fail[i] = k;
+    }
+    fail
+}
+
+impl Kmp {
+    pub fn new<P: AsRef<[u8]>>(pattern: P) -> Self {
+        let pattern = pattern.as_ref().to_vec();
+        let fail = failure_function(&pattern);
+        Self { pattern, fail }
+    }
+
+    pub fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    pub fn failure(&self) -> &[usize] {
+        &self.fail
+    }
+
+    // start of every match, overlapping ones included, left to right
+    pub fn find_all<'a>(&'a self, text: &'a [u8]) -> FindAll<'a> {
+        FindAll {
+            kmp: Cow::Borrowed(self),
+            text,
+            at: 0,
+            matched: 0,
+        }
+    }
+
+    pub fn find(&self, text: &[u8]) -> Option<usize> {
+        self.find_all(text).next()
+    }
+
+    pub fn stream(&self) -> KmpStream<'_> {
+        KmpStream {
+            kmp: self,
+            matched: 0,
+            offset: 0,
+        }
+    }
+
+    // how much of the pattern is matched after byte b, with matched before
+    fn step(&self, mut matched: usize, b: u8) -> usize {
+        if matched == self.pattern.len() {
+            matched = self.fail[matched - 1];
+        }
+        while matched > 0 && self.pattern[matched] != b {
+            matched = self.fail[matched - 1];
+        }
+        if self.pattern[matched] == b {
+            matched + 1
+        } else {
+            0
+        }
+    }
+}
+
+// one pass over the text, not a restart per match
+impl Matcher for Kmp {
+    fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    fn find_from(&self, text: &[u8], from: usize) -> Option<usize> {
+        let mut matches = self.find_all(text);
+        matches.at = from.min(text.len());
+        matches.next()
+    }
+
+    fn matches(&self, text: &[u8]) -> Vec<usize> {
+        self.find_all(text).collect()
+    }
+
+    fn count(&self, text: &[u8]) -> usize {
+        self.find_all(text).count()
+    }
+}
+
+// the matches of pattern in text, building the failure function first
+pub fn find_all<'a>(text: &'a [u8], pattern: &[u8]) -> FindAll<'a> {
+    FindAll {
+        kmp: Cow::Owned(Kmp::new(pattern)),
+        text,
+        at: 0,
+        matched: 0,
+    }
+}
+
+pub struct FindAll<'a> {
+    kmp: Cow<'a, Kmp>,
+    text: &'a [u8],
+    // next byte of text to read
+    at: usize,
+    matched: usize,
+}
+
+impl Iterator for FindAll<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let len = self.kmp.pattern.len();
+        if len == 0 {
+            return None;
+        }
+        while self.at < self.text.len() {
+            self.matched = self.kmp.step(self.matched, self.text[self.at]);
+            self.at += 1;
+            if self.matched == len {
+                return Some(self.at - len);
+            }
+        }
+        None
+    }
+}
+
+// Searches text arriving in chunks: matches may straddle chunks, and are
+// reported at their offsets in the whole stream.
+#[derive(Debug, Clone)]
+pub struct KmpStream<'a> {
+    kmp: &'a Kmp,
+    matched: usize,
+    // bytes consumed so far
+    offset: usize,
+}
+
+impl<'a> KmpStream<'a> {
+    // The matches ending in chunk, as the iterator is advanced; whatever
+    // of the chunk it has not read when dropped is consumed then.
+    pub fn feed<'s>(&'s mut self, chunk: &'s [u8]) -> StreamMatches<'s, 'a> {
+        StreamMatches {
+            stream: self,
+            chunk,
+            at: 0,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    // forgets the bytes seen, as a fresh stream
+    pub fn reset(&mut self) {
+        self.matched = 0;
+        self.offset = 0;
+    }
+}
+
+pub struct StreamMatches<'s, 'a> {
+    stream: &'s mut KmpStream<'a>,
+    chunk: &'s [u8],
+    at: usize,
+}
+
+impl Iterator for StreamMatches<'_, '_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let stream = &mut *self.stream;
+        let len = stream.kmp.pattern.len();
+        if len == 0 {
+            stream.offset += self.chunk.len() - self.at;
+            self.at = self.chunk.len();
+            return None;
+        }
+        while self.at < self.chunk.len() {
+            stream.matched = stream.kmp.step(stream.matched, self.chunk[self.at]);
+            self.at += 1;
+            stream.offset += 1;
+            if stream.matched == len {
+                return Some(stream.offset - len);
+            }
+        }
+        None
+    }
+}
+
+impl Drop for StreamMatches<'_, '_> {
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
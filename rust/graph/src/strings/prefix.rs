@@ -0,0 +1,128 @@
+use super::kmp::failure_function;
+
+// The Z-array and prefix function of a string, and what they answer: the
+// Z-array compares every suffix with the whole string, the prefix function
+// every prefix with its own suffixes, both in O(n).
+
+// z[i] is the length of the longest common prefix of s and s[i..], with
+// z[0] = s.len(). Kept is the rightmost segment known to equal a prefix,
+// inside which a position starts from what its mirror in the prefix
+// already matched.
+pub fn z_array(s: &[u8]) -> Vec<usize> {
+    let n = s.len();
+    let mut z = vec![0; n];
+    if n == 0 {
+        return z;
+    }
+    z[0] = n;
+    // s[l..r] == s[..r - l]
+    let (mut l, mut r) = (0, 0);
+    for i in 1..n {
+        let mut k = if i < r { z[i - l].min(r - i) } else { 0 };
+        while i + k < n && s[k] == s[i + k] {
+            k += 1;
+        }
+        z[i] = k;
+        if i + k > r {
+            (l, r) = (i, i + k);
+        }
+    }
+    z
+}
+
+// pi[i] is the length of the longest proper border of s[..=i]: KMP's
+// failure function, under its usual name.
+pub fn prefix_function(s: &[u8]) -> Vec<usize> {
+    failure_function(s)
+}
+
+// For every position of text, how long a prefix of pattern starts there,
+// by the Z-array of the pattern run along the text: O(text + pattern).
+pub fn prefix_matches(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    let z = z_array(pattern);
+    let (n, m) = (text.len(), pattern.len());
+    let mut lcp = vec![0; n];
+    // text[l..r] == pattern[..r - l]
+    let (mut l, mut r) = (0, 0);
+    for i in 0..n {
+        let mut k = if i < r { z[i - l].min(r - i) } else { 0 };
+        while k < m && i + k < n && pattern[k] == text[i + k] {
+            k += 1;
+        }
+        lcp[i] = k;
+        if i + k > r {
+            (l, r) = (i, i + k);
+        }
+    }
+    lcp
+}
+
+// start of every match, overlapping ones included
+pub fn z_find_all(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let lcp = prefix_matches(text, pattern);
+    (0..text.len())
+        .filter(|&i| lcp[i] == pattern.len())
+        .collect()
+}
+
+// counts[l - 1] is how many times pattern[..l] occurs in text, for every
+// non-empty prefix
+pub fn prefix_occurrences(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    let mut counts = vec![0; pattern.len() + 1];
+    for k in prefix_matches(text, pattern) {
+        counts[k] += 1;
+    }
+    // a match of length k is one of every shorter prefix too
+    for l in (1..pattern.len()).rev() {
+        counts[l] += counts[l + 1];
+    }
+    counts.remove(0);
+    counts
+}
+
+// how many distinct non-empty prefixes of pattern occur in text: as every
+// prefix of one that does occurs too, the longest match
+pub fn distinct_prefixes_in(text: &[u8], pattern: &[u8]) -> usize {
+    prefix_matches(text, pattern).into_iter().max().unwrap_or(0)
+}
+
+// Every p with s[i] == s[i + p] throughout, ascending and ending with
+// s.len() itself: one per border, p = len - border.
+pub fn periods(s: &[u8]) -> Vec<usize> {
+    let n = s.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let pi = prefix_function(s);
+    let mut periods = Vec::new();
+    let mut border = pi[n - 1];
+    while border > 0 {
+        periods.push(n - border);
+        border = pi[border - 1];
+    }
+    periods.push(n);
+    periods
+}
+
+// 0 for the empty string
+pub fn smallest_period(s: &[u8]) -> usize {
+    match s.len() {
+        0 => 0,
+        n => n - prefix_function(s)[n - 1],
+    }
+}
+
+// The shortest r with s = r^k, r = s if s is no repetition. If any period
+// divides the length the smallest one does (Fine and Wilf), so that is the
+// one to try.
+pub fn primitive_root(s: &[u8]) -> &[u8] {
+    let p = smallest_period(s);
+    if p > 0 && s.len().is_multiple_of(p) {
+        &s[..p]
+    } else {
+        s
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, fmt, ops::ControlFlow, ops::Range};
+
+use crate::aho_corasick::Match;
+use crate::hashing::mix64;
+use crate::search::partition_point;
+
+// the Mersenne prime 2^61 - 1, and a second, smaller one for double hashing
+const MODULI: [u64; 2] = [(1 << 61) - 1, (1 << 31) - 1];
+const DEFAULT_SEED: u64 = 0x2011;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moduli {
+    // mod 2^61 - 1
+    Single,
+    // mod 2^61 - 1 and 2^31 - 1, for a collision chance around 2^-92 per
+    // comparison of unequal strings
+    Double,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Modulus {
+    m: u64,
+    base: u64,
+}
+
+impl Modulus {
+    fn mul(self, a: u64, b: u64) -> u64 {
+        (a as u128 * b as u128 % self.m as u128) as u64
+    }
+}
+
+// Polynomial hashes of every prefix of a text, h(s) = sum of (s[i] + 1)
+// base^(len - 1 - i) mod m, from which the hash of any substring is O(1):
+// h(text[l..r]) = h(text[..r]) - h(text[..l]) base^(r - l). Equal
+// substrings hash alike, unequal ones of the same length collide with
+// chance about len / m for a random base, so equal hashes are a very
+// likely but unproven equality. The base comes from the seed, and every
+// RollingHash with the same seed and moduli hashes alike.
+#[derive(Debug, Clone)]
+pub struct RollingHash {
+    moduli: Vec<Modulus>,
+    // per modulus, the hash of text[..i] and base^i
+    prefix: Vec<Vec<u64>>,
+    powers: Vec<Vec<u64>>,
+}
+
+impl RollingHash {
+    pub fn new(text: &[u8]) -> Self {
+        Self::with_seed(text, Moduli::Single, DEFAULT_SEED)
+    }
+
+    pub fn double(text: &[u8]) -> Self {
+        Self::with_seed(text, Moduli::Double, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(text: &[u8], moduli: Moduli, seed: u64) -> Self {
+        let count = match moduli {
+            Moduli::Single => 1,
+            Moduli::Double => 2,
+        };
+        let moduli: Vec<Modulus> = MODULI[..count]
+            .iter()
+            .map(|&m| Modulus {
+                m,
+                // above any byte, below m
+                base: 257 + mix64(seed ^ m) % (m - 257),
+            })
+            .collect();
+        let mut prefix = Vec::new();
+        let mut powers = Vec::new();
+        for &md in moduli.iter() {
+            let (mut h, mut p) = (vec![0; text.len() + 1], vec![1; text.len() + 1]);
+            for (i, &b) in text.iter().enumerate() {
+                h[i + 1] = (md.mul(h[i], md.base) + b as u64 + 1) % md.m;
+                p[i + 1] = md.mul(p[i], md.base);
+            }
+            prefix.push(h);
+            powers.push(p);
+        }
+        Self {
+            moduli,
+            prefix,
+            powers,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefix[0].len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // the hash of text[range], both moduli's packed in one u128
+    pub fn hash(&self, range: Range<usize>) -> u128 {
+        let Range { start, end } = range;
+        assert!(
+            start <= end && end <= self.len(),
+            "{start}..{end} out of text"
+        );
+        let mut packed = 0;
+        for (k, &md) in self.moduli.iter().enumerate() {
+            let shifted = md.mul(self.prefix[k][start], self.powers[k][end - start]);
+            let h = (self.prefix[k][end] + md.m - shifted) % md.m;
+            packed = packed << 64 | h as u128;
+        }
+        packed
+    }
+
+    // whether text[a] == text[b], up to hash collisions
+    pub fn equal(&self, a: Range<usize>, b: Range<usize>) -> bool {
+        a.len() == b.len() && self.hash(a) == self.hash(b)
+    }
+
+    // Length of the longest common prefix of text[i..] and text[j..], by
+    // binary search on the length with a hash comparison each step:
+    // O(log n).
+    pub fn lcp(&self, i: usize, j: usize) -> usize {
+        let most = self.len() - i.max(j);
+        partition_point(1, most + 1, |l| self.equal(i..i + l, j..j + l)) - 1
+    }
+}
+
+// Rabin-Karp search for any of a set of patterns of one length: each
+// window of the text is hashed in O(1) and looked up among the patterns'
+// hashes, and only a hit is compared byte by byte, so collisions cost
+// time but never a false match. O(text + patterns) expected.
+#[derive(Debug, Clone)]
+pub struct RabinKarp {
+    patterns: Vec<Vec<u8>>,
+    len: usize,
+    // pattern ids by hash
+    table: HashMap<u128, Vec<usize>>,
+}
+
+// a pattern not as long as the first one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnequalLengths {
+    pub pattern: usize,
+    pub len: usize,
+    pub expected: usize,
+}
+
+impl fmt::Display for UnequalLengths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pattern {} is {} bytes long, the first is {}",
+            self.pattern, self.len, self.expected
+        )
+    }
+}
+
+impl std::error::Error for UnequalLengths {}
+
+impl RabinKarp {
+    // pattern ids are their indices; empty patterns never match
+    pub fn new<P: AsRef<[u8]>>(
+        patterns: impl IntoIterator<Item = P>,
+    ) -> Result<Self, UnequalLengths> {
+        let patterns: Vec<Vec<u8>> = patterns.into_iter().map(|p| p.as_ref().to_vec()).collect();
+        let len = patterns.first().map_or(0, Vec::len);
+        let mut table: HashMap<u128, Vec<usize>> = HashMap::new();
+        for (id, p) in patterns.iter().enumerate() {
+            if p.len() != len {
+                return Err(UnequalLengths {
+                    pattern: id,
+                    len: p.len(),
+                    expected: len,
+                });
+            }
+            table
+                .entry(RollingHash::new(p).hash(0..len))
+                .or_default()
+                .push(id);
+        }
+        Ok(Self {
+            patterns,
+            len,
+            table,
+        })
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    // every match, by start and then pattern id
+    pub fn find_all(&self, text: &[u8]) -> Vec<Match> {
+        let mut matches = Vec::new();
+        self.search(text, |m| {
+            matches.push(m);
+            ControlFlow::Continue(())
+        });
+        matches
+    }
+
+    pub fn find(&self, text: &[u8]) -> Option<Match> {
+        let mut first = None;
+        self.search(text, |m| {
+            first = Some(m);
+            ControlFlow::Break(())
+        });
+        first
+    }
+
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        self.find(text).is_some()
+    }
+
+    fn search(&self, text: &[u8], mut visit: impl FnMut(Match) -> ControlFlow<()>) {
+        if self.len == 0 || text.len() < self.len {
+            return;
+        }
+        let hashes = RollingHash::new(text);
+        for start in 0..=text.len() - self.len {
+            let end = start + self.len;
+            let Some(ids) = self.table.get(&hashes.hash(start..end)) else {
+                continue;
+            };
+            for &pattern in ids.iter() {
+                if self.patterns[pattern] == text[start..end] {
+                    let found = Match {
+                        pattern,
+                        start,
+                        end,
+                    };
+                    if visit(found).is_break() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::ops::Range;
+
+// Suffix array of text: the start positions of all suffixes in sorted
+// order. Built with SA-IS in O(n).
+pub fn suffix_array(text: &[u8]) -> Vec<usize> {
+    let s: Vec<usize> = text.iter().map(|&b| b as usize).collect();
+    sa_is(&s, 255)
+}
+
+// Kasai's algorithm, O(n): lcp[i] is the length of the longest common
+// prefix of the suffixes sa[i - 1] and sa[i], lcp[0] is 0.
+pub fn lcp_array(text: &[u8], sa: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    let mut rank = vec![0; n];
+    for (i, &p) in sa.iter().enumerate() {
+        rank[p] = i;
+    }
+    let mut lcp = vec![0; n];
+    let mut h = 0;
+    for p in 0..n {
+        if rank[p] == 0 {
+            h = 0;
+            continue;
+        }
+        let q = sa[rank[p] - 1];
+        while p + h < n && q + h < n && text[p + h] == text[q + h] {
+            h += 1;
+        }
+        lcp[rank[p]] = h;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+// A text with its suffix and lcp arrays, for substring queries.
+#[derive(Debug, Clone)]
+pub struct SuffixArray {
+    text: Vec<u8>,
+    sa: Vec<usize>,
+    lcp: Vec<usize>,
+}
+
+impl SuffixArray {
+    pub fn new(text: &[u8]) -> Self {
+        let sa = suffix_array(text);
+        let lcp = lcp_array(text, &sa);
+        Self {
+            text: text.to_vec(),
+            sa,
+            lcp,
+        }
+    }
+
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    pub fn sa(&self) -> &[usize] {
+        &self.sa
+    }
+
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    // the block of sa whose suffixes start with pattern, O(m log n)
+    pub fn find_range(&self, pattern: &[u8]) -> Range<usize> {
+        let suffix = |p: usize| &self.text[p..];
+        let start = self.sa.partition_point(|&p| suffix(p) < pattern);
+        let end = start + self.sa[start..].partition_point(|&p| suffix(p).starts_with(pattern));
+        start..end
+    }
+
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        !self.find_range(pattern).is_empty()
+    }
+
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.find_range(pattern).len()
+    }
+
+    // start positions of pattern in the text, ascending
+    pub fn positions(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions = self.sa[self.find_range(pattern)].to_vec();
+        positions.sort_unstable();
+        positions
+    }
+
+    // a longest substring occurring at least twice, as its start and length
+    pub fn longest_repeat(&self) -> Option<(usize, usize)> {
+        let (i, &len) = self.lcp.iter().enumerate().max_by_key(|&(_, &l)| l)?;
+        (len > 0).then(|| (self.sa[i], len))
+    }
+
+    // number of distinct non-empty substrings
+    pub fn distinct_substrings(&self) -> usize {
+        let n = self.text.len();
+        n * (n + 1) / 2 - self.lcp.iter().sum::<usize>()
+    }
+}
+
+// SA-IS over the alphabet 0..=upper: suffixes are classified S or L, the
+// LMS substrings are sorted by induced sorting, named, and if the names are
+// not unique the reduced string is sorted recursively.
+fn sa_is(s: &[usize], upper: usize) -> Vec<usize> {
+    let n = s.len();
+    match n {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+    // ls[i]: suffix i is S-type, smaller than suffix i + 1
+    let mut ls = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if s[i] == s[i + 1] {
+            ls[i + 1]
+        } else {
+            s[i] < s[i + 1]
+        };
+    }
+    // bucket starts: sum_l for L-type, sum_s for S-type suffixes
+    let mut sum_l = vec![0; upper + 1];
+    let mut sum_s = vec![0; upper + 1];
+    for i in 0..n {
+        if !ls[i] {
+            sum_s[s[i]] += 1;
+        } else {
+            sum_l[s[i] + 1] += 1;
+        }
+    }
+    for i in 0..=upper {
+        sum_s[i] += sum_l[i];
+        if i < upper {
+            sum_l[i + 1] += sum_s[i];
+        }
+    }
+
+    let is_lms = |i: usize| i > 0 && !ls[i - 1] && ls[i];
+    let mut lms_index = vec![usize::MAX; n];
+    let lms: Vec<usize> = (1..n).filter(|&i| is_lms(i)).collect();
+    for (k, &i) in lms.iter().enumerate() {
+        lms_index[i] = k;
+    }
+    let m = lms.len();
+
+    let mut sa = vec![0; n];
+    induce(s, &ls, &sum_l, &sum_s, &lms, &mut sa);
+    if m > 0 {
+        let mut sorted_lms: Vec<usize> = sa
+            .iter()
+            .copied()
+            .filter(|&v| v != usize::MAX && lms_index[v] != usize::MAX)
+            .collect();
+        let end_of = |i: usize| lms.get(lms_index[i] + 1).copied().unwrap_or(n);
+        let mut reduced = vec![0; m];
+        let mut names = 0;
+        reduced[lms_index[sorted_lms[0]]] = 0;
+        for w in 1..m {
+            let (mut l, mut r) = (sorted_lms[w - 1], sorted_lms[w]);
+            let (end_l, end_r) = (end_of(l), end_of(r));
+            let mut same = end_l - l == end_r - r;
+            if same {
+                while l < end_l && s[l] == s[r] {
+                    l += 1;
+                    r += 1;
+                }
+                if l == n || r == n || s[l] != s[r] {
+                    same = false;
+                }
+            }
+            if !same {
+                names += 1;
+            }
+            reduced[lms_index[sorted_lms[w]]] = names;
+        }
+        let reduced_sa = sa_is(&reduced, names);
+        for (slot, &r) in sorted_lms.iter_mut().zip(reduced_sa.iter()) {
+            *slot = lms[r];
+        }
+        induce(s, &ls, &sum_l, &sum_s, &sorted_lms, &mut sa);
+    }
+    sa
+}
+
+// places the lms suffixes in order at the ends of their buckets, then
+// induces the L-type suffixes left to right and the S-type right to left
+fn induce(
+    s: &[usize],
+    ls: &[bool],
+    sum_l: &[usize],
+    sum_s: &[usize],
+    lms: &[usize],
+    sa: &mut [usize],
+) {
+    let n = s.len();
+    sa.fill(usize::MAX);
+    let mut buf = sum_s.to_vec();
+    for &d in lms {
+        sa[buf[s[d]]] = d;
+        buf[s[d]] += 1;
+    }
+    buf.copy_from_slice(sum_l);
+    sa[buf[s[n - 1]]] = n - 1;
+    buf[s[n - 1]] += 1;
+    for i in 0..n {
+        let v = sa[i];
+        if v != usize::MAX && v >= 1 && !ls[v - 1] {
+            sa[buf[s[v - 1]]] = v - 1;
+            buf[s[v - 1]] += 1;
+        }
+    }
+    buf.copy_from_slice(sum_l);
+    for i in (0..n).rev() {
+        let v = sa[i];
+        if v != usize::MAX && v >= 1 && ls[v - 1] {
+            buf[s[v - 1] + 1] -= 1;
+            sa[buf[s[v - 1] + 1]] = v - 1;
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::ops::Range;
+
+// Smallest automaton accepting exactly the substrings of a text, built
+// online in O(n log σ). Every state is a class of substrings with the same
+// set of end positions; link points to the class of their longest suffix
+// outside it.
+#[derive(Debug, Clone)]
+pub struct SuffixAutomaton {
+    states: Vec<State>,
+    // occurrences of the substrings of each state
+    counts: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    // length of the longest substring in the state
+    len: usize,
+    link: usize,
+    // sorted by byte
+    next: Vec<(u8, usize)>,
+}
+
+const NONE: usize = usize::MAX;
+
+impl SuffixAutomaton {
+    pub fn new(text: &[u8]) -> Self {
+        let mut states = vec![State {
+            len: 0,
+            link: NONE,
+            next: Vec::new(),
+        }];
+        let mut counts = vec![0];
+        let mut last = 0;
+        for &c in text {
+            let cur = states.len();
+            states.push(State {
+                len: states[last].len + 1,
+                link: 0,
+                next: Vec::new(),
+            });
+            counts.push(1);
+            let mut p = last;
+            while p != NONE && step(&states, p, c).is_none() {
+                set(&mut states, p, c, cur);
+                p = states[p].link;
+            }
+            if p != NONE {
+                let q = step(&states, p, c).unwrap();
+                if states[p].len + 1 == states[q].len {
+                    states[cur].link = q;
+                } else {
+                    let clone = states.len();
+                    states.push(State {
+                        len: states[p].len + 1,
+                        link: states[q].link,
+                        next: states[q].next.clone(),
+                    });
+                    counts.push(0);
+                    while p != NONE && step(&states, p, c) == Some(q) {
+                        set(&mut states, p, c, clone);
+                        p = states[p].link;
+                    }
+                    states[q].link = clone;
+                    states[cur].link = clone;
+                }
+            }
+            last = cur;
+        }
+
+        // end positions flow up the suffix links, longest states first
+        let mut by_len = vec![0; text.len() + 1];
+        for s in states.iter() {
+            by_len[s.len] += 1;
+        }
+        for i in 1..by_len.len() {
+            by_len[i] += by_len[i - 1];
+        }
+        let mut order = vec![0; states.len()];
+        for (v, s) in states.iter().enumerate().rev() {
+            by_len[s.len] -= 1;
+            order[by_len[s.len]] = v;
+        }
+        for &v in order.iter().rev() {
+            if states[v].link != NONE {
+                counts[states[v].link] += counts[v];
+            }
+        }
+        Self { states, counts }
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        self.walk(pattern).is_some()
+    }
+
+    // occurrences of pattern in the text, possibly overlapping
+    pub fn occurrences(&self, pattern: &[u8]) -> usize {
+        if pattern.is_empty() {
+            return self.states.iter().map(|s| s.len).max().unwrap_or(0) + 1;
+        }
+        self.walk(pattern).map_or(0, |v| self.counts[v])
+    }
+
+    // number of distinct non-empty substrings
+    pub fn distinct_substrings(&self) -> usize {
+        self.states
+            .iter()
+            .skip(1)
+            .map(|s| s.len - self.states[s.link].len)
+            .sum()
+    }
+
+    // the first longest substring of other that is also a substring of the
+    // text, as a range of other
+    pub fn longest_common_substring(&self, other: &[u8]) -> Range<usize> {
+        let (mut v, mut len) = (0, 0);
+        let mut best = 0..0;
+        for (i, &c) in other.iter().enumerate() {
+            while v != 0 && step(&self.states, v, c).is_none() {
+                v = self.states[v].link;
+                len = self.states[v].len;
+            }
+            if let Some(u) = step(&self.states, v, c) {
+                v = u;
+                len += 1;
+            }
+            if len > best.len() {
+                best = i + 1 - len..i + 1;
+            }
+        }
+        best
+    }
+
+    fn walk(&self, pattern: &[u8]) -> Option<usize> {
+        pattern.iter().try_fold(0, |v, &c| step(&self.states, v, c))
+    }
+}
+
+pub fn longest_common_substring<'a>(a: &[u8], b: &'a [u8]) -> &'a [u8] {
+    &b[SuffixAutomaton::new(a).longest_common_substring(b)]
+}
+
+fn step(states: &[State], v: usize, c: u8) -> Option<usize> {
+    let next = &states[v].next;
+    next.binary_search_by_key(&c, |&(b, _)| b)
+        .ok()
+        .map(|i| next[i].1)
+}
+
+fn set(states: &mut [State], v: usize, c: u8, to: usize) {
+    let next = &mut states[v].next;
+    match next.binary_search_by_key(&c, |&(b, _)| b) {
+        Ok(i) => next[i].1 = to,
+        Err(i) => next.insert(i, (c, to)),
+    }
+}
@@ -0,0 +1,311 @@
+use std::ops::Range;
+
+// Suffix tree of a byte string, built online with Ukkonen's algorithm in
+// O(n log σ). The text is extended with a terminator that matches no byte,
+// so every suffix ends at its own leaf. Edges store ranges of the text
+// instead of copies of it.
+#[derive(Debug, Clone)]
+pub struct SuffixTree {
+    // the text followed by TERMINATOR
+    text: Vec<u16>,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    // the edge from the parent is text[start..end]
+    start: usize,
+    end: usize,
+    link: usize,
+    // sorted by the first symbol of the child's edge
+    children: Vec<(u16, usize)>,
+    // length of the string spelled from the root to the node
+    depth: usize,
+    // start of a suffix in the subtree, for leaves the suffix itself
+    suffix: usize,
+}
+
+// a substring that occurs count times, at start among other places
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeat {
+    pub start: usize,
+    pub len: usize,
+    pub count: usize,
+}
+
+const TERMINATOR: u16 = 256;
+const ROOT: usize = 0;
+// end of a leaf edge while the tree is built: the current end of the text
+const OPEN: usize = usize::MAX;
+
+impl SuffixTree {
+    pub fn new(text: &[u8]) -> Self {
+        let mut tree = Self {
+            text: text.iter().map(|&b| b as u16).collect(),
+            nodes: vec![Node {
+                start: 0,
+                end: 0,
+                link: ROOT,
+                children: Vec::new(),
+                depth: 0,
+                suffix: 0,
+            }],
+        };
+        tree.text.push(TERMINATOR);
+        tree.build();
+        tree.annotate();
+        tree
+    }
+
+    // length of the text without the terminator
+    pub fn len(&self) -> usize {
+        self.text.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        self.locate(pattern).is_some()
+    }
+
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.locate(pattern).map_or(0, |v| self.leaves(v).count())
+    }
+
+    // start positions of pattern, ascending
+    pub fn positions(&self, pattern: &[u8]) -> Vec<usize> {
+        let Some(v) = self.locate(pattern) else {
+            return Vec::new();
+        };
+        let mut positions: Vec<usize> = self
+            .leaves(v)
+            .map(|leaf| self.nodes[leaf].suffix)
+            .filter(|&s| s < self.len())
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    // a longest substring occurring at least twice: the deepest inner node
+    pub fn longest_repeat(&self) -> Option<Range<usize>> {
+        self.nodes
+            .iter()
+            .skip(1)
+            .filter(|n| !n.children.is_empty())
+            .max_by_key(|n| n.depth)
+            .map(|n| n.suffix..n.suffix + n.depth)
+    }
+
+    // repeats that can be extended neither to the left nor to the right
+    // without losing an occurrence, at least min_len long. Right maximal
+    // repeats are the inner nodes; an inner node is also left maximal when
+    // its occurrences are preceded by different bytes or one starts the
+    // text.
+    pub fn maximal_repeats(&self, min_len: usize) -> Vec<Repeat> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Left {
+            Byte(u16),
+            Diverse,
+        }
+        let order = self.preorder();
+        let mut left = vec![Left::Diverse; self.nodes.len()];
+        let mut count = vec![0; self.nodes.len()];
+        let mut repeats = Vec::new();
+        for &v in order.iter().rev() {
+            let node = &self.nodes[v];
+            if node.children.is_empty() {
+                count[v] = usize::from(node.suffix < self.len());
+                left[v] = match node.suffix {
+                    0 => Left::Diverse,
+                    s => Left::Byte(self.text[s - 1]),
+                };
+                continue;
+            }
+            let first = left[node.children[0].1];
+            left[v] = first;
+            for &(_, c) in node.children.iter() {
+                count[v] += count[c];
+                if left[c] != first {
+                    left[v] = Left::Diverse;
+                }
+            }
+            if v != ROOT && left[v] == Left::Diverse && node.depth >= min_len.max(1) {
+                repeats.push(Repeat {
+                    start: node.suffix,
+                    len: node.depth,
+                    count: count[v],
+                });
+            }
+        }
+        repeats.sort_by_key(|r| (r.start, r.len));
+        repeats
+    }
+
+    fn build(&mut self) {
+        let (mut active_node, mut active_edge, mut active_len) = (ROOT, 0, 0);
+        let mut remainder = 0;
+        for i in 0..self.text.len() {
+            remainder += 1;
+            let mut last_inner: Option<usize> = None;
+            while remainder > 0 {
+                if active_len == 0 {
+                    active_edge = i;
+                }
+                let c = self.text[active_edge];
+                match self.child(active_node, c) {
+                    None => {
+                        let leaf = self.add_node(i, OPEN);
+                        self.set_child(active_node, c, leaf);
+                        if let Some(inner) = last_inner.take() {
+                            self.nodes[inner].link = active_node;
+                        }
+                    }
+                    Some(next) => {
+                        let edge_len = self.nodes[next].end.min(i + 1) - self.nodes[next].start;
+                        if active_len >= edge_len {
+                            // walk down
+                            active_edge += edge_len;
+                            active_len -= edge_len;
+                            active_node = next;
+                            continue;
+                        }
+                        if self.text[self.nodes[next].start + active_len] == self.text[i] {
+                            // already in the tree, the rest of this phase is implicit
+                            if let Some(inner) = last_inner.take() {
+                                if active_node != ROOT {
+                                    self.nodes[inner].link = active_node;
+                                }
+                            }
+                            active_len += 1;
+                            break;
+                        }
+                        let start = self.nodes[next].start;
+                        let split = self.add_node(start, start + active_len);
+                        self.set_child(active_node, c, split);
+                        let leaf = self.add_node(i, OPEN);
+                        self.set_child(split, self.text[i], leaf);
+                        self.nodes[next].start += active_len;
+                        let rest = self.text[self.nodes[next].start];
+                        self.set_child(split, rest, next);
+                        if let Some(inner) = last_inner {
+                            self.nodes[inner].link = split;
+                        }
+                        last_inner = Some(split);
+                    }
+                }
+                remainder -= 1;
+                if active_node == ROOT && active_len > 0 {
+                    active_len -= 1;
+                    active_edge = i + 1 - remainder;
+                } else if active_node != ROOT {
+                    active_node = self.nodes[active_node].link;
+                }
+            }
+        }
+        let end = self.text.len();
+        for node in self.nodes.iter_mut() {
+            if node.end == OPEN {
+                node.end = end;
+            }
+        }
+    }
+
+    // fills in depth and suffix
+    fn annotate(&mut self) {
+        let order = self.preorder();
+        for &v in order.iter() {
+            let depth = self.nodes[v].depth;
+            for k in 0..self.nodes[v].children.len() {
+                let c = self.nodes[v].children[k].1;
+                let node = &mut self.nodes[c];
+                node.depth = depth + node.end - node.start;
+            }
+        }
+        let n = self.text.len();
+        for &v in order.iter().rev() {
+            let node = &self.nodes[v];
+            self.nodes[v].suffix = match node.children.first() {
+                None => n - node.depth,
+                Some(&(_, c)) => self.nodes[c].suffix,
+            };
+        }
+    }
+
+    fn preorder(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![ROOT];
+        while let Some(v) = stack.pop() {
+            order.push(v);
+            stack.extend(self.nodes[v].children.iter().rev().map(|&(_, c)| c));
+        }
+        order
+    }
+
+    fn leaves(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = vec![v];
+        std::iter::from_fn(move || {
+            while let Some(v) = stack.pop() {
+                let children = &self.nodes[v].children;
+                if children.is_empty() {
+                    return Some(v);
+                }
+                stack.extend(children.iter().map(|&(_, c)| c));
+            }
+            None
+        })
+    }
+
+    // the highest node whose string starts with pattern
+    fn locate(&self, pattern: &[u8]) -> Option<usize> {
+        let mut v = ROOT;
+        let mut i = 0;
+        while i < pattern.len() {
+            v = self.child(v, pattern[i] as u16)?;
+            let node = &self.nodes[v];
+            for &symbol in self.text[node.start..node.end].iter() {
+                if i == pattern.len() {
+                    break;
+                }
+                if symbol != pattern[i] as u16 {
+                    return None;
+                }
+                i += 1;
+            }
+        }
+        Some(v)
+    }
+
+    fn child(&self, v: usize, c: u16) -> Option<usize> {
+        let children = &self.nodes[v].children;
+        children
+            .binary_search_by_key(&c, |&(s, _)| s)
+            .ok()
+            .map(|i| children[i].1)
+    }
+
+    fn set_child(&mut self, v: usize, c: u16, child: usize) {
+        let children = &mut self.nodes[v].children;
+        match children.binary_search_by_key(&c, |&(s, _)| s) {
+            Ok(i) => children[i].1 = child,
+            Err(i) => children.insert(i, (c, child)),
+        }
+    }
+
+    fn add_node(&mut self, start: usize, end: usize) -> usize {
+        self.nodes.push(Node {
+            start,
+            end,
+            link: ROOT,
+            children: Vec::new(),
+            depth: 0,
+            suffix: 0,
+        });
+        self.nodes.len() - 1
+    }
+}
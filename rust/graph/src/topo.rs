@@ -0,0 +1,114 @@
+use std::{fmt, hash::Hash};
+
+use crate::adjlist::{Graph, NeighborList};
+
+// Topological order of a directed graph, every edge from an earlier vertex
+// to a later one, by Kahn's algorithm: repeatedly take the vertices no
+// remaining edge points to. Taking all of them at once gives levels, each
+// vertex in the level after the longest path to it. If vertices are left
+// over they lie on or after a cycle, and one is returned.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<T> {
+    // each with an edge to the next, and the last to the first
+    pub vertices: Vec<T>,
+}
+
+impl<T: fmt::Debug> fmt::Display for Cycle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle: ")?;
+        for v in self.vertices.iter() {
+            write!(f, "{v:?} -> ")?;
+        }
+        match self.vertices.first() {
+            Some(first) => write!(f, "{first:?}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for Cycle<T> {}
+
+pub fn topological_sort<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Result<Vec<T>, Cycle<T>> {
+    Ok(topological_levels(graph)?.concat())
+}
+
+// the sources first, then each vertex one level after its latest
+// predecessor; vertices within a level share no path
+pub fn topological_levels<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Result<Vec<Vec<T>>, Cycle<T>> {
+    let g = graph.indexed();
+    let key = |v: &usize| g.keys[*v].clone();
+    match levels_indexed(&g.adj) {
+        Ok(levels) => Ok(levels.iter().map(|l| l.iter().map(key).collect()).collect()),
+        Err(cycle) => Err(Cycle {
+            vertices: cycle.iter().map(key).collect(),
+        }),
+    }
+}
+
+// levels of 0..adj.len(), each in increasing order, or a cycle
+pub(crate) fn levels_indexed(adj: &[Vec<(usize, i32)>]) -> Result<Vec<Vec<usize>>, Vec<usize>> {
+    let n = adj.len();
+    let mut in_degree = vec![0; n];
+    for edges in adj.iter() {
+        for &(u, _) in edges.iter() {
+            in_degree[u] += 1;
+        }
+    }
+    let mut level: Vec<usize> = (0..n).filter(|&v| in_degree[v] == 0).collect();
+    let mut levels = Vec::new();
+    let mut placed = 0;
+    while !level.is_empty() {
+        let mut next = Vec::new();
+        for &v in level.iter() {
+            for &(u, _) in adj[v].iter() {
+                in_degree[u] -= 1;
+                if in_degree[u] == 0 {
+                    next.push(u);
+                }
+            }
+        }
+        next.sort_unstable();
+        placed += level.len();
+        levels.push(std::mem::replace(&mut level, next));
+    }
+    if placed < n {
+        return Err(find_cycle(adj, &in_degree));
+    }
+    Ok(levels)
+}
+
+// Every vertex Kahn's algorithm left has an edge into it from another one
+// left, so walking those edges backwards must come round to a vertex seen
+// before.
+fn find_cycle(adj: &[Vec<(usize, i32)>], in_degree: &[usize]) -> Vec<usize> {
+    let left = |v: usize| in_degree[v] > 0;
+    let mut pred = vec![usize::MAX; adj.len()];
+    for (v, edges) in adj.iter().enumerate() {
+        for &(u, _) in edges.iter() {
+            if left(v) && left(u) {
+                pred[u] = v;
+            }
+        }
+    }
+    let start = (0..adj.len()).find(|&v| left(v)).unwrap();
+    let mut seen = vec![false; adj.len()];
+    let mut v = start;
+    while !seen[v] {
+        seen[v] = true;
+        v = pred[v];
+    }
+    // v is on the cycle, follow it round once more
+    let mut cycle = vec![v];
+    let mut u = pred[v];
+    while u != v {
+        cycle.push(u);
+        u = pred[u];
+    }
+    cycle.reverse();
+    cycle
+}
@@ -0,0 +1,424 @@
+use std::{borrow::Borrow, cmp::Ordering, fmt};
+
+use crate::rng::{Rng, SplitMix64};
+
+// Binary search tree that is also a max-heap on random priorities, which
+// keeps it balanced in expectation: O(log n) expected depth. Everything is
+// built from split and merge. Subtree sizes give k-th element and rank.
+pub struct Treap<K> {
+    root: Link<K>,
+    rng: SplitMix64,
+}
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    item: T,
+    priority: u64,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn new(item: T, priority: u64) -> Box<Self> {
+        Box::new(Self {
+            item,
+            priority,
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |n| n.size)
+}
+
+// all items of a come before those of b
+fn merge<T>(a: Link<T>, b: Link<T>) -> Link<T> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority >= b.priority {
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+// (items going left, the rest), go_left must be true for a prefix of the
+// in-order sequence
+fn split_by<T>(link: Link<T>, go_left: &impl Fn(&T) -> bool) -> (Link<T>, Link<T>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    if go_left(&node.item) {
+        let (l, r) = split_by(node.right.take(), go_left);
+        node.right = l;
+        node.update();
+        (Some(node), r)
+    } else {
+        let (l, r) = split_by(node.left.take(), go_left);
+        node.left = r;
+        node.update();
+        (l, Some(node))
+    }
+}
+
+// (the first k items, the rest)
+fn split_at<T>(link: Link<T>, k: usize) -> (Link<T>, Link<T>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let left = size(&node.left);
+    if k <= left {
+        let (l, r) = split_at(node.left.take(), k);
+        node.left = r;
+        node.update();
+        (l, Some(node))
+    } else {
+        let (l, r) = split_at(node.right.take(), k - left - 1);
+        node.right = l;
+        node.update();
+        (Some(node), r)
+    }
+}
+
+fn nth<T>(mut link: &Link<T>, mut k: usize) -> Option<&T> {
+    while let Some(node) = link {
+        let left = size(&node.left);
+        match k.cmp(&left) {
+            Ordering::Less => link = &node.left,
+            Ordering::Equal => return Some(&node.item),
+            Ordering::Greater => {
+                k -= left + 1;
+                link = &node.right;
+            }
+        }
+    }
+    None
+}
+
+fn nth_mut<T>(mut link: &mut Link<T>, mut k: usize) -> Option<&mut T> {
+    while let Some(node) = link {
+        let left = size(&node.left);
+        match k.cmp(&left) {
+            Ordering::Less => link = &mut node.left,
+            Ordering::Equal => return Some(&mut node.item),
+            Ordering::Greater => {
+                k -= left + 1;
+                link = &mut node.right;
+            }
+        }
+    }
+    None
+}
+
+// in-order iterator with an explicit stack
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    left: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn new(root: &'a Link<T>) -> Self {
+        let mut iter = Self {
+            stack: Vec::new(),
+            left: size(root),
+        };
+        iter.descend(root);
+        iter
+    }
+
+    fn descend(&mut self, mut link: &'a Link<T>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.descend(&node.right);
+        self.left -= 1;
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<K: Ord> Default for Treap<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> Treap<K> {
+    pub fn new() -> Self {
+        Self::with_seed(0x7ea9)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // returns false when key was already present
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.contains(&key) {
+            return false;
+        }
+        let (l, r) = split_by(self.root.take(), &|k: &K| *k < key);
+        let node = Node::new(key, self.rng.next_u64());
+        self.root = merge(merge(l, Some(node)), r);
+        true
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        let (l, rest) = split_by(self.root.take(), &|k: &K| k.borrow() < key);
+        let (mid, r) = split_by(rest, &|k: &K| k.borrow() <= key);
+        self.root = merge(l, r);
+        mid.is_some()
+    }
+
+    pub fn contains<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.item.borrow()) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return true,
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        false
+    }
+
+    // the k-th smallest key, from 0
+    pub fn kth(&self, k: usize) -> Option<&K> {
+        nth(&self.root, k)
+    }
+
+    // number of keys less than key
+    pub fn rank<Q: Ord + ?Sized>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if node.item.borrow() < key {
+                rank += size(&node.left) + 1;
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+        rank
+    }
+
+    pub fn first(&self) -> Option<&K> {
+        self.kth(0)
+    }
+
+    pub fn last(&self) -> Option<&K> {
+        self.len().checked_sub(1).and_then(|k| self.kth(k))
+    }
+
+    // moves the keys >= key into the returned treap
+    pub fn split_off<Q: Ord + ?Sized>(&mut self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+    {
+        let (l, r) = split_by(self.root.take(), &|k: &K| k.borrow() < key);
+        self.root = l;
+        Self {
+            root: r,
+            rng: SplitMix64::new(self.rng.next_u64()),
+        }
+    }
+
+    // moves all keys of other into self, every key of other must be greater
+    // than the keys of self. O(log n) expected.
+    pub fn merge(&mut self, other: Self) {
+        if let (Some(a), Some(b)) = (self.last(), other.first()) {
+            assert!(a < b, "merged treap overlaps this one");
+        }
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter::new(&self.root)
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+}
+
+impl<K: Ord + fmt::Debug> fmt::Debug for Treap<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord> FromIterator<K> for Treap<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut treap = Self::new();
+        for k in iter {
+            treap.insert(k);
+        }
+        treap
+    }
+}
+
+// Treap keyed by position instead of value: a sequence with O(log n)
+// expected insert, remove, split and concatenation anywhere.
+pub struct ImplicitTreap<T> {
+    root: Link<T>,
+    rng: SplitMix64,
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ImplicitTreap<T> {
+    pub fn new() -> Self {
+        Self::with_seed(0x7ea9)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        nth(&self.root, index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        nth_mut(&mut self.root, index)
+    }
+
+    // panics if index > len
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(
+            index <= self.len(),
+            "insertion index {index} out of range for length {}",
+            self.len()
+        );
+        let (l, r) = split_at(self.root.take(), index);
+        let node = Node::new(value, self.rng.next_u64());
+        self.root = merge(merge(l, Some(node)), r);
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.insert(self.len(), value);
+    }
+
+    // panics if index >= len
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(
+            index < self.len(),
+            "removal index {index} out of range for length {}",
+            self.len()
+        );
+        let (l, rest) = split_at(self.root.take(), index);
+        let (mid, r) = split_at(rest, 1);
+        self.root = merge(l, r);
+        mid.unwrap().item
+    }
+
+    // moves the items from index on into the returned sequence
+    pub fn split_off(&mut self, index: usize) -> Self {
+        assert!(
+            index <= self.len(),
+            "split index {index} out of range for length {}",
+            self.len()
+        );
+        let (l, r) = split_at(self.root.take(), index);
+        self.root = l;
+        Self {
+            root: r,
+            rng: SplitMix64::new(self.rng.next_u64()),
+        }
+    }
+
+    // concatenates other to the end of self
+    pub fn append(&mut self, other: Self) {
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.root)
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ImplicitTreap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for ImplicitTreap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut seq = Self::new();
+        for v in iter {
+            seq.push(v);
+        }
+        seq
+    }
+}
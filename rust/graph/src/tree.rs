@@ -0,0 +1,182 @@
+use std::{collections::HashMap, fmt, hash::Hash};
+
+use crate::adjlist::{Graph, Indexed, NeighborList};
+
+// A tree is stored as an undirected graph: every edge a-b is present in
+// both neighbor lists. Weights are ignored, all distances are edge counts.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeError {
+    Empty,
+    Asymmetric,
+    Disconnected,
+    Cycle,
+    MissingRoot,
+    MissingVertex,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::Empty => write!(f, "graph has no vertices"),
+            TreeError::Asymmetric => write!(f, "edge without its reverse edge"),
+            TreeError::Disconnected => write!(f, "graph is not connected"),
+            TreeError::Cycle => write!(f, "graph contains a cycle"),
+            TreeError::MissingRoot => write!(f, "root is not a vertex of the graph"),
+            TreeError::MissingVertex => write!(f, "query vertex is not in the graph"),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diameter<T> {
+    pub length: usize,
+    pub path: Vec<T>,
+}
+
+pub fn is_tree<T: Hash + Eq + Clone, S: NeighborList<T>>(graph: &Graph<T, S>) -> bool {
+    validate(graph).is_ok()
+}
+
+pub fn validate<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    graph: &Graph<T, S>,
+) -> Result<(), TreeError> {
+    check(&graph.indexed())
+}
+
+pub fn diameter<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    tree: &Graph<T, S>,
+) -> Result<Diameter<T>, TreeError> {
+    let g = tree.indexed();
+    check(&g)?;
+    let (a, _) = farthest(&g.adj, 0);
+    let (b, parent) = farthest(&g.adj, a);
+    let path = path_to(&parent, b);
+    Ok(Diameter {
+        length: path.len() - 1,
+        path: path.into_iter().map(|v| g.keys[v].clone()).collect(),
+    })
+}
+
+// one center when the diameter has an even number of edges, two otherwise
+pub fn center<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    tree: &Graph<T, S>,
+) -> Result<Vec<T>, TreeError> {
+    let d = diameter(tree)?;
+    let mid = d.length / 2;
+    let mut centers = vec![d.path[mid].clone()];
+    if d.length % 2 == 1 {
+        centers.push(d.path[mid + 1].clone());
+    }
+    Ok(centers)
+}
+
+pub fn subtree_sizes<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    tree: &Graph<T, S>,
+    root: &T,
+) -> Result<HashMap<T, usize>, TreeError> {
+    let g = tree.indexed();
+    check(&g)?;
+    let root = *g.index.get(root).ok_or(TreeError::MissingRoot)?;
+    let sizes = sizes_from(&g.adj, root).1;
+    Ok(g.keys.into_iter().zip(sizes).collect())
+}
+
+// vertices whose removal leaves components of at most n/2 vertices,
+// there are one or two of them
+pub fn centroid<T: Hash + Eq + Clone, S: NeighborList<T>>(
+    tree: &Graph<T, S>,
+) -> Result<Vec<T>, TreeError> {
+    let g = tree.indexed();
+    check(&g)?;
+    let n = g.keys.len();
+    let (parent, size) = sizes_from(&g.adj, 0);
+    let mut centroids = Vec::new();
+    for v in 0..n {
+        let mut largest = n - size[v];
+        for &(u, _) in g.adj[v].iter() {
+            if parent[u] == Some(v) {
+                largest = largest.max(size[u]);
+            }
+        }
+        if largest <= n / 2 {
+            centroids.push(g.keys[v].clone());
+        }
+    }
+    Ok(centroids)
+}
+
+pub(crate) fn check<T>(g: &Indexed<T>) -> Result<(), TreeError> {
+    let n = g.keys.len();
+    if n == 0 {
+        return Err(TreeError::Empty);
+    }
+    let mut edges = 0;
+    for (v, nbrs) in g.adj.iter().enumerate() {
+        for &(u, _) in nbrs.iter() {
+            if !g.adj[u].iter().any(|&(w, _)| w == v) {
+                return Err(TreeError::Asymmetric);
+            }
+        }
+        edges += nbrs.len();
+    }
+    if bfs(&g.adj, 0).0.len() != n {
+        return Err(TreeError::Disconnected);
+    }
+    if edges != 2 * (n - 1) {
+        return Err(TreeError::Cycle);
+    }
+    Ok(())
+}
+
+// bfs from start, returns the visiting order and the bfs parents
+pub(crate) fn bfs(adj: &[Vec<(usize, i32)>], start: usize) -> (Vec<usize>, Vec<Option<usize>>) {
+    let mut parent = vec![None; adj.len()];
+    let mut seen = vec![false; adj.len()];
+    let mut order = vec![start];
+    seen[start] = true;
+    let mut i = 0;
+    while i < order.len() {
+        let v = order[i];
+        for &(u, _) in adj[v].iter() {
+            if !seen[u] {
+                seen[u] = true;
+                parent[u] = Some(v);
+                order.push(u);
+            }
+        }
+        i += 1;
+    }
+    (order, parent)
+}
+
+fn farthest(adj: &[Vec<(usize, i32)>], start: usize) -> (usize, Vec<Option<usize>>) {
+    let (order, parent) = bfs(adj, start);
+    (*order.last().unwrap(), parent)
+}
+
+fn path_to(parent: &[Option<usize>], end: usize) -> Vec<usize> {
+    let mut path = vec![end];
+    let mut cur = end;
+    while let Some(p) = parent[cur] {
+        path.push(p);
+        cur = p;
+    }
+    path
+}
+
+pub(crate) fn sizes_from(
+    adj: &[Vec<(usize, i32)>],
+    root: usize,
+) -> (Vec<Option<usize>>, Vec<usize>) {
+    let (order, parent) = bfs(adj, root);
+    let mut size = vec![1; adj.len()];
+    for &v in order.iter().rev() {
+        if let Some(p) = parent[v] {
+            size[p] += size[v];
+        }
+    }
+    (parent, size)
+}
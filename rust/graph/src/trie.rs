@@ -0,0 +1,216 @@
+// Prefix tree over sequences of symbols, e.g. Trie<u8, V> for byte strings
+// or Trie<char, V> for text. Nodes live in one Vec, children are kept
+// sorted by symbol so lookups binary search them and iteration is in key
+// order.
+#[derive(Debug, Clone)]
+pub struct Trie<K, V> {
+    pub(crate) nodes: Vec<Node<K, V>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Node<K, V> {
+    pub(crate) children: Vec<(K, usize)>,
+    pub(crate) value: Option<V>,
+}
+
+pub(crate) const ROOT: usize = 0;
+
+impl<K: Ord + Copy, V> Default for Trie<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Copy, V> Trie<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                children: Vec::new(),
+                value: None,
+            }],
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    // number of keys
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    // returns the old value of key
+    pub fn insert(&mut self, key: &[K], value: V) -> Option<V> {
+        let mut node = ROOT;
+        for &k in key {
+            node = match self.child(node, k) {
+                Ok(child) => child,
+                Err(pos) => {
+                    let child = self.alloc();
+                    self.nodes[node].children.insert(pos, (k, child));
+                    child
+                }
+            };
+        }
+        let old = self.nodes[node].value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn get(&self, key: &[K]) -> Option<&V> {
+        self.find(key).and_then(|n| self.nodes[n].value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, key: &[K]) -> Option<&mut V> {
+        self.find(key).and_then(|n| self.nodes[n].value.as_mut())
+    }
+
+    pub fn contains(&self, key: &[K]) -> bool {
+        self.get(key).is_some()
+    }
+
+    // removes key and the nodes that no longer lead to any key
+    pub fn remove(&mut self, key: &[K]) -> Option<V> {
+        let mut path = Vec::with_capacity(key.len());
+        let mut node = ROOT;
+        for &k in key {
+            let child = self.child(node, k).ok()?;
+            path.push(node);
+            node = child;
+        }
+        let value = self.nodes[node].value.take()?;
+        self.len -= 1;
+        while let Some(parent) = path.pop() {
+            let n = &self.nodes[node];
+            if n.value.is_some() || !n.children.is_empty() {
+                break;
+            }
+            let pos = self.nodes[parent]
+                .children
+                .iter()
+                .position(|&(_, c)| c == node)
+                .unwrap();
+            self.nodes[parent].children.remove(pos);
+            self.free.push(node);
+            node = parent;
+        }
+        Some(value)
+    }
+
+    // the longest key that is a prefix of query, as (length, value)
+    pub fn longest_prefix(&self, query: &[K]) -> Option<(usize, &V)> {
+        let mut node = ROOT;
+        let mut best = self.nodes[ROOT].value.as_ref().map(|v| (0, v));
+        for (i, &k) in query.iter().enumerate() {
+            let Ok(child) = self.child(node, k) else {
+                break;
+            };
+            node = child;
+            if let Some(v) = &self.nodes[node].value {
+                best = Some((i + 1, v));
+            }
+        }
+        best
+    }
+
+    // keys starting with prefix and their values, in key order
+    pub fn iter_prefix(&self, prefix: &[K]) -> PrefixIter<'_, K, V> {
+        let stack = match self.find(prefix) {
+            Some(node) => vec![(node, prefix.len(), None)],
+            None => Vec::new(),
+        };
+        PrefixIter {
+            trie: self,
+            key: prefix.to_vec(),
+            stack,
+        }
+    }
+
+    pub fn iter(&self) -> PrefixIter<'_, K, V> {
+        self.iter_prefix(&[])
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub(crate) fn child(&self, node: usize, k: K) -> Result<usize, usize> {
+        let children = &self.nodes[node].children;
+        children
+            .binary_search_by(|&(c, _)| c.cmp(&k))
+            .map(|i| children[i].1)
+    }
+
+    fn find(&self, key: &[K]) -> Option<usize> {
+        let mut node = ROOT;
+        for &k in key {
+            node = self.child(node, k).ok()?;
+        }
+        Some(node)
+    }
+
+    fn alloc(&mut self) -> usize {
+        let node = Node {
+            children: Vec::new(),
+            value: None,
+        };
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+impl<K: Ord + Copy, V> FromIterator<(Vec<K>, V)> for Trie<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Vec<K>, V)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        for (key, value) in iter {
+            trie.insert(&key, value);
+        }
+        trie
+    }
+}
+
+pub struct PrefixIter<'a, K, V> {
+    trie: &'a Trie<K, V>,
+    // key of the node on top of the stack, up to its parent
+    key: Vec<K>,
+    // (node, key length of its parent, symbol leading to it)
+    stack: Vec<(usize, usize, Option<K>)>,
+}
+
+impl<'a, K: Ord + Copy, V> Iterator for PrefixIter<'a, K, V> {
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth, symbol)) = self.stack.pop() {
+            self.key.truncate(depth);
+            self.key.extend(symbol);
+            let n = &self.trie.nodes[node];
+            for &(k, child) in n.children.iter().rev() {
+                self.stack.push((child, self.key.len(), Some(k)));
+            }
+            if let Some(v) = &n.value {
+                return Some((self.key.clone(), v));
+            }
+        }
+        None
+    }
+}
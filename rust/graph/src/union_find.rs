@@ -0,0 +1,264 @@
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    sets: usize,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            sets: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    // adds a new singleton element and returns it
+    pub fn push(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.rank.push(0);
+        self.sets += 1;
+        x
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // path compression
+        let mut cur = x;
+        while self.parent[cur] != root {
+            let next = self.parent[cur];
+            self.parent[cur] = root;
+            cur = next;
+        }
+        root
+    }
+
+    // returns false when x and y were already in the same set
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let (rx, ry) = (self.find(x), self.find(y));
+        if rx == ry {
+            return false;
+        }
+        match self.rank[rx].cmp(&self.rank[ry]) {
+            std::cmp::Ordering::Less => self.parent[rx] = ry,
+            std::cmp::Ordering::Greater => self.parent[ry] = rx,
+            std::cmp::Ordering::Equal => {
+                self.parent[ry] = rx;
+                self.rank[rx] += 1;
+            }
+        }
+        self.sets -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    // the sets, each listed in increasing order, sets ordered by their
+    // smallest element
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut slot = vec![usize::MAX; self.len()];
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.sets);
+        for x in 0..self.len() {
+            let root = self.find(x);
+            if slot[root] == usize::MAX {
+                slot[root] = groups.len();
+                groups.push(Vec::new());
+            }
+            groups[slot[root]].push(x);
+        }
+        groups
+    }
+}
+
+// Union-find over arbitrary hashable keys, numbered densely as they are
+// first seen and backed by UnionFind.
+#[derive(Debug, Clone)]
+pub struct KeyedUnionFind<K> {
+    keys: Vec<K>,
+    index: HashMap<K, usize>,
+    sets: UnionFind,
+}
+
+impl<K: Hash + Eq + Clone> Default for KeyedUnionFind<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone> KeyedUnionFind<K> {
+    pub fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            index: HashMap::new(),
+            sets: UnionFind::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn set_count(&self) -> usize {
+        self.sets.set_count()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    // adds key as a singleton set, false when it was already present
+    pub fn insert(&mut self, key: &K) -> bool {
+        if self.contains(key) {
+            return false;
+        }
+        self.id(key);
+        true
+    }
+
+    // representative of the key's set, None for an unknown key
+    pub fn find(&mut self, key: &K) -> Option<&K> {
+        let &i = self.index.get(key)?;
+        let root = self.sets.find(i);
+        Some(&self.keys[root])
+    }
+
+    // unknown keys are inserted first; false when x and y were already in
+    // the same set
+    pub fn union(&mut self, x: &K, y: &K) -> bool {
+        let (a, b) = (self.id(x), self.id(y));
+        self.sets.union(a, b)
+    }
+
+    pub fn connected(&mut self, x: &K, y: &K) -> bool {
+        match (self.index.get(x), self.index.get(y)) {
+            (Some(&a), Some(&b)) => self.sets.connected(a, b),
+            _ => false,
+        }
+    }
+
+    // sets in order of first insertion, keys within a set likewise
+    pub fn groups(&mut self) -> Vec<Vec<K>> {
+        self.sets
+            .groups()
+            .into_iter()
+            .map(|g| g.into_iter().map(|i| self.keys[i].clone()).collect())
+            .collect()
+    }
+
+    fn id(&mut self, key: &K) -> usize {
+        if let Some(&i) = self.index.get(key) {
+            return i;
+        }
+        let i = self.keys.len();
+        self.keys.push(key.clone());
+        self.index.insert(key.clone(), i);
+        self.sets.push();
+        i
+    }
+}
+
+// Union by size without path compression, so every union can be undone.
+// find is O(log n); snapshot returns a point in the union history and
+// rollback undoes every union that came after it.
+#[derive(Debug, Clone)]
+pub struct RollbackUnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    sets: usize,
+    // the root that was attached below another, one entry per union
+    history: Vec<usize>,
+}
+
+impl RollbackUnionFind {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            sets: n,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    pub fn set_size(&self, x: usize) -> usize {
+        self.size[self.find(x)]
+    }
+
+    pub fn find(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    // returns false when x and y were already in the same set, no history
+    // entry is recorded then
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let (mut rx, mut ry) = (self.find(x), self.find(y));
+        if rx == ry {
+            return false;
+        }
+        if self.size[rx] < self.size[ry] {
+            std::mem::swap(&mut rx, &mut ry);
+        }
+        self.parent[ry] = rx;
+        self.size[rx] += self.size[ry];
+        self.sets -= 1;
+        self.history.push(ry);
+        true
+    }
+
+    pub fn connected(&self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            let child = self.history.pop().unwrap();
+            let root = self.parent[child];
+            self.size[root] -= self.size[child];
+            self.parent[child] = child;
+            self.sets += 1;
+        }
+    }
+}
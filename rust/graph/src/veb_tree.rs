@@ -0,0 +1,274 @@
+use std::{collections::HashMap, fmt};
+
+// van Emde Boas tree over the integers [0, 2^bits). A node over b bits
+// splits a key into its high and low halves, keeps one cluster per high
+// half in use plus a summary tree of those high halves, and stores its
+// minimum outside the clusters. Every operation then recurses into only one
+// tree of half the bits, O(log log U) steps; clusters live in hash maps so
+// space stays O(n) however wide the universe.
+pub struct VebTree {
+    bits: u32,
+    len: usize,
+    root: Node,
+}
+
+#[derive(Default)]
+struct Node {
+    // (min, max), the min not being repeated in any cluster
+    bounds: Option<(u64, u64)>,
+    summary: Option<Box<Node>>,
+    clusters: HashMap<u64, Node>,
+}
+
+// low half of the bits
+fn low_bits(bits: u32) -> u32 {
+    bits / 2
+}
+
+fn split(x: u64, bits: u32) -> (u64, u64) {
+    let lb = low_bits(bits);
+    (x >> lb, x & ((1 << lb) - 1))
+}
+
+fn join(high: u64, low: u64, bits: u32) -> u64 {
+    high << low_bits(bits) | low
+}
+
+impl Node {
+    fn contains(&self, bits: u32, x: u64) -> bool {
+        let Some((min, max)) = self.bounds else {
+            return false;
+        };
+        if x == min || x == max {
+            return true;
+        }
+        if bits == 1 {
+            return false;
+        }
+        let (h, l) = split(x, bits);
+        self.clusters
+            .get(&h)
+            .is_some_and(|c| c.contains(low_bits(bits), l))
+    }
+
+    // x must be absent
+    fn insert(&mut self, bits: u32, mut x: u64) {
+        let Some((min, max)) = &mut self.bounds else {
+            self.bounds = Some((x, x));
+            return;
+        };
+        if x < *min {
+            std::mem::swap(&mut x, min);
+        }
+        if x > *max {
+            *max = x;
+        }
+        if bits == 1 {
+            return;
+        }
+        let (h, l) = split(x, bits);
+        let cluster = self.clusters.entry(h).or_default();
+        if cluster.bounds.is_none() {
+            // the summary only sees a cluster come into use
+            self.summary
+                .get_or_insert_with(Default::default)
+                .insert(bits - low_bits(bits), h);
+        }
+        cluster.insert(low_bits(bits), l);
+    }
+
+    // x must be present
+    fn remove(&mut self, bits: u32, mut x: u64) {
+        let (min, max) = self.bounds.unwrap();
+        if min == max {
+            self.bounds = None;
+            return;
+        }
+        if bits == 1 {
+            let other = 1 - x;
+            self.bounds = Some((other, other));
+            return;
+        }
+        let high_bits = bits - low_bits(bits);
+        let summary = self.summary.as_mut().unwrap();
+        let mut min = min;
+        if x == min {
+            // pull the next key out of its cluster to be the new min
+            let first = summary.bounds.unwrap().0;
+            x = join(first, self.clusters[&first].bounds.unwrap().0, bits);
+            min = x;
+        }
+        let (h, l) = split(x, bits);
+        let cluster = self.clusters.get_mut(&h).unwrap();
+        cluster.remove(low_bits(bits), l);
+        let mut max = max;
+        match cluster.bounds {
+            Some((_, cluster_max)) => {
+                if x == max {
+                    max = join(h, cluster_max, bits);
+                }
+            }
+            None => {
+                self.clusters.remove(&h);
+                summary.remove(high_bits, h);
+                if x == max {
+                    max = match summary.bounds {
+                        None => min,
+                        Some((_, last)) => join(last, self.clusters[&last].bounds.unwrap().1, bits),
+                    };
+                }
+            }
+        }
+        self.bounds = Some((min, max));
+    }
+
+    // smallest key above x
+    fn successor(&self, bits: u32, x: u64) -> Option<u64> {
+        let (min, max) = self.bounds?;
+        if x < min {
+            return Some(min);
+        }
+        if x >= max {
+            return None;
+        }
+        if bits == 1 {
+            return Some(max);
+        }
+        let (h, l) = split(x, bits);
+        if let Some(c) = self.clusters.get(&h) {
+            if l < c.bounds.unwrap().1 {
+                return Some(join(h, c.successor(low_bits(bits), l)?, bits));
+            }
+        }
+        let next = self.summary.as_ref()?.successor(bits - low_bits(bits), h)?;
+        Some(join(next, self.clusters[&next].bounds.unwrap().0, bits))
+    }
+
+    // largest key below x
+    fn predecessor(&self, bits: u32, x: u64) -> Option<u64> {
+        let (min, max) = self.bounds?;
+        if x > max {
+            return Some(max);
+        }
+        if x <= min {
+            return None;
+        }
+        if bits == 1 {
+            return Some(min);
+        }
+        let (h, l) = split(x, bits);
+        if let Some(c) = self.clusters.get(&h) {
+            if l > c.bounds.unwrap().0 {
+                return Some(join(h, c.predecessor(low_bits(bits), l)?, bits));
+            }
+        }
+        // the min is in no cluster, so it is the fallback
+        let prev = self
+            .summary
+            .as_ref()
+            .and_then(|s| s.predecessor(bits - low_bits(bits), h));
+        match prev {
+            Some(prev) => Some(join(prev, self.clusters[&prev].bounds.unwrap().1, bits)),
+            None => Some(min),
+        }
+    }
+}
+
+impl VebTree {
+    // keys in [0, 2^bits), for bits from 1 to 64
+    pub fn new(bits: u32) -> Self {
+        assert!(
+            (1..=64).contains(&bits),
+            "universe must be 1 to 64 bits wide, got {bits}"
+        );
+        Self {
+            bits,
+            len: 0,
+            root: Node::default(),
+        }
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn in_universe(&self, x: u64) -> bool {
+        self.bits == 64 || x >> self.bits == 0
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        self.in_universe(x) && self.root.contains(self.bits, x)
+    }
+
+    // false if x was already present
+    pub fn insert(&mut self, x: u64) -> bool {
+        assert!(
+            self.in_universe(x),
+            "key {x} is outside the {}-bit universe",
+            self.bits
+        );
+        if self.root.contains(self.bits, x) {
+            return false;
+        }
+        self.root.insert(self.bits, x);
+        self.len += 1;
+        true
+    }
+
+    pub fn remove(&mut self, x: u64) -> bool {
+        if !self.contains(x) {
+            return false;
+        }
+        self.root.remove(self.bits, x);
+        self.len -= 1;
+        true
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.root.bounds.map(|(min, _)| min)
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.root.bounds.map(|(_, max)| max)
+    }
+
+    // smallest key greater than x
+    pub fn successor(&self, x: u64) -> Option<u64> {
+        if !self.in_universe(x) {
+            return None;
+        }
+        self.root.successor(self.bits, x)
+    }
+
+    // largest key less than x
+    pub fn predecessor(&self, x: u64) -> Option<u64> {
+        if !self.in_universe(x) {
+            return self.max();
+        }
+        self.root.predecessor(self.bits, x)
+    }
+
+    // keys in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        std::iter::successors(self.min(), |&x| self.successor(x))
+    }
+
+    pub fn clear(&mut self) {
+        self.root = Node::default();
+        self.len = 0;
+    }
+}
+
+impl fmt::Debug for VebTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
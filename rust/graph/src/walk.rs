@@ -0,0 +1,184 @@
+use std::hash::Hash;
+
+use crate::adjlist::{Graph, NeighborList};
+use crate::rng::Rng;
+
+// All walks return the visited vertices including the start, so a walk of
+// `steps` steps has steps + 1 entries. A walk stops early at a vertex
+// without outgoing edges (or without positive weight edges for the
+// weighted walks). An unknown start gives an empty walk.
+
+pub fn random_walk<T, S, R>(graph: &Graph<T, S>, start: &T, steps: usize, rng: &mut R) -> Vec<T>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+    R: Rng,
+{
+    walk(graph, start, steps, |_, nbrs| {
+        if nbrs.is_empty() {
+            None
+        } else {
+            Some(rng.gen_range(nbrs.len()))
+        }
+    })
+}
+
+// next vertex is chosen with probability proportional to the edge weight
+pub fn weighted_random_walk<T, S, R>(
+    graph: &Graph<T, S>,
+    start: &T,
+    steps: usize,
+    rng: &mut R,
+) -> Vec<T>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+    R: Rng,
+{
+    walk(graph, start, steps, |_, nbrs| {
+        pick_weighted(nbrs.iter().map(|(_, wt)| weight(*wt)), rng)
+    })
+}
+
+// transition probabilities of the weighted walk out of `key`,
+// non-positive weights get probability zero
+pub fn transition_probabilities<T, S>(graph: &Graph<T, S>, key: &T) -> Vec<(T, f64)>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+{
+    let Some(vertex) = graph.get_vertex(key) else {
+        return Vec::new();
+    };
+    let total: f64 = vertex.neighbors.iter().map(|(_, wt)| weight(*wt)).sum();
+    vertex
+        .neighbors
+        .iter()
+        .map(|(nbr, wt)| {
+            let p = if total > 0.0 {
+                weight(*wt) / total
+            } else {
+                0.0
+            };
+            (nbr.clone(), p)
+        })
+        .collect()
+}
+
+// node2vec second order walk. Coming from `prev` to `cur`, a neighbor x of
+// cur gets its edge weight multiplied by 1/p if x == prev, by 1 if x is a
+// neighbor of prev and by 1/q otherwise. Small p keeps the walk local, small
+// q pushes it outwards.
+pub fn node2vec_walk<T, S, R>(
+    graph: &Graph<T, S>,
+    start: &T,
+    steps: usize,
+    p: f64,
+    q: f64,
+    rng: &mut R,
+) -> Vec<T>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+    R: Rng,
+{
+    walk(graph, start, steps, |prev, nbrs| match prev {
+        None => pick_weighted(nbrs.iter().map(|(_, wt)| weight(*wt)), rng),
+        Some(prev) => {
+            let prev_vertex = graph.get_vertex(prev).unwrap();
+            let bias = nbrs.iter().map(|(x, wt)| {
+                let factor = if x == prev {
+                    1.0 / p
+                } else if prev_vertex.adjacent_key(x) {
+                    1.0
+                } else {
+                    1.0 / q
+                };
+                weight(*wt) * factor
+            });
+            pick_weighted(bias, rng)
+        }
+    })
+}
+
+// up to k distinct neighbors of `key`, uniformly without replacement
+pub fn sample_neighbors<T, S, R>(graph: &Graph<T, S>, key: &T, k: usize, rng: &mut R) -> Vec<T>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+    R: Rng,
+{
+    let Some(vertex) = graph.get_vertex(key) else {
+        return Vec::new();
+    };
+    // reservoir sampling
+    let mut sample: Vec<T> = Vec::with_capacity(k);
+    for (i, (nbr, _)) in vertex.neighbors.iter().enumerate() {
+        if i < k {
+            sample.push(nbr.clone());
+        } else {
+            let j = rng.gen_range(i + 1);
+            if j < k {
+                sample[j] = nbr.clone();
+            }
+        }
+    }
+    sample
+}
+
+fn walk<T, S, F>(graph: &Graph<T, S>, start: &T, steps: usize, mut choose: F) -> Vec<T>
+where
+    T: Hash + Eq + Clone,
+    S: NeighborList<T>,
+    F: FnMut(Option<&T>, &[(T, i32)]) -> Option<usize>,
+{
+    if !graph.contains(start) {
+        return Vec::new();
+    }
+    let mut path = Vec::with_capacity(steps + 1);
+    path.push(start.clone());
+    let mut prev: Option<T> = None;
+    for _ in 0..steps {
+        let cur = path.last().unwrap();
+        let nbrs = &graph.get_vertex(cur).unwrap().neighbors;
+        let Some(i) = choose(prev.as_ref(), nbrs) else {
+            break;
+        };
+        prev = Some(cur.clone());
+        path.push(nbrs[i].0.clone());
+    }
+    path
+}
+
+fn weight(wt: i32) -> f64 {
+    if wt > 0 {
+        wt as f64
+    } else {
+        0.0
+    }
+}
+
+fn pick_weighted<I, R>(weights: I, rng: &mut R) -> Option<usize>
+where
+    I: Iterator<Item = f64> + Clone,
+    R: Rng,
+{
+    let total: f64 = weights.clone().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut target = rng.next_f64() * total;
+    let mut last = None;
+    for (i, w) in weights.enumerate() {
+        if w <= 0.0 {
+            continue;
+        }
+        if target < w {
+            return Some(i);
+        }
+        target -= w;
+        last = Some(i);
+    }
+    // rounding left target just above the total
+    last
+}
@@ -0,0 +1,244 @@
+use std::ops::Range;
+
+use crate::bit_vec::{BitVec, RankSelect};
+
+// Wavelet tree over a sequence of integers, stored level by level without
+// pointers. The root splits the values on their top bit, zeros left and
+// ones right, keeping order, and each child splits its part on the next
+// bit. A level holds the bits of all its nodes side by side in one
+// rank/select vector; a node is a range of it, and ranks at the range ends
+// give the ranges of its children, so every query walks one node per
+// level: O(log sigma) for values below sigma.
+#[derive(Clone, Debug)]
+pub struct WaveletTree {
+    levels: Vec<RankSelect>,
+    len: usize,
+}
+
+impl WaveletTree {
+    pub fn new(values: &[u64]) -> Self {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let depth = (64 - max.leading_zeros()).max(1);
+        let mut levels = Vec::with_capacity(depth as usize);
+        let mut current = values.to_vec();
+        for l in 0..depth {
+            let shift = depth - 1 - l;
+            let bits: BitVec = current.iter().map(|v| v >> shift & 1 == 1).collect();
+            // nodes are the runs sharing the bits above shift: their zeros
+            // go first, then their ones
+            let mut next = Vec::with_capacity(current.len());
+            let mut start = 0;
+            while start < current.len() {
+                let run = prefix(current[start], shift);
+                let mut end = start;
+                while end < current.len() && prefix(current[end], shift) == run {
+                    end += 1;
+                }
+                let node = &current[start..end];
+                next.extend(node.iter().filter(|&&v| v >> shift & 1 == 0));
+                next.extend(node.iter().filter(|&&v| v >> shift & 1 == 1));
+                start = end;
+            }
+            levels.push(RankSelect::new(bits));
+            current = next;
+        }
+        Self {
+            levels,
+            len: values.len(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // every value is below 2^depth
+    pub fn depth(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    fn check(&self, range: &Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "range {range:?} out of range for length {}",
+            self.len
+        );
+    }
+
+    pub fn get(&self, i: usize) -> u64 {
+        assert!(
+            i < self.len,
+            "index {i} out of range for length {}",
+            self.len
+        );
+        let (mut node, mut i, mut value) = (Node::root(self.len), i, 0);
+        for level in &self.levels {
+            let bit = level.get(i);
+            value = value << 1 | bit as u64;
+            i = node.child_pos(level, bit, i);
+            node = node.child(level, bit);
+        }
+        value
+    }
+
+    // occurrences of value in [..i]
+    pub fn rank(&self, value: u64, i: usize) -> usize {
+        assert!(
+            i <= self.len,
+            "index {i} out of range for length {}",
+            self.len
+        );
+        if !self.fits(value) {
+            return 0;
+        }
+        let (mut node, mut i) = (Node::root(self.len), i);
+        for (l, level) in self.levels.iter().enumerate() {
+            let bit = self.bit(value, l);
+            i = node.child_pos(level, bit, i);
+            node = node.child(level, bit);
+        }
+        i - node.start
+    }
+
+    // position of the occurrence of value of rank k, counting from 0
+    pub fn select(&self, value: u64, k: usize) -> Option<usize> {
+        if !self.fits(value) {
+            return None;
+        }
+        let mut path = Vec::with_capacity(self.levels.len());
+        let mut node = Node::root(self.len);
+        for (l, level) in self.levels.iter().enumerate() {
+            path.push(node);
+            node = node.child(level, self.bit(value, l));
+        }
+        if k >= node.end - node.start {
+            return None;
+        }
+        // back up from the leaf, mapping the position to each parent
+        let mut pos = node.start + k;
+        for (l, level) in self.levels.iter().enumerate().rev() {
+            let parent = path[l];
+            let bit = self.bit(value, l);
+            let child = parent.child(level, bit);
+            let within = pos - child.start;
+            pos = level.select(bit, level.rank(bit, parent.start) + within)?;
+        }
+        Some(pos)
+    }
+
+    // the value of rank k in range when sorted, counting from 0
+    pub fn kth_smallest(&self, range: Range<usize>, k: usize) -> Option<u64> {
+        self.check(&range);
+        if k >= range.len() {
+            return None;
+        }
+        let (mut node, mut lo, mut hi, mut k, mut value) =
+            (Node::root(self.len), range.start, range.end, k, 0);
+        for level in &self.levels {
+            let zeros = level.rank0(hi) - level.rank0(lo);
+            let bit = k >= zeros;
+            if bit {
+                k -= zeros;
+            }
+            value = value << 1 | bit as u64;
+            lo = node.child_pos(level, bit, lo);
+            hi = node.child_pos(level, bit, hi);
+            node = node.child(level, bit);
+        }
+        Some(value)
+    }
+
+    pub fn kth_largest(&self, range: Range<usize>, k: usize) -> Option<u64> {
+        let n = range.len();
+        if k >= n {
+            self.check(&range);
+            return None;
+        }
+        self.kth_smallest(range, n - 1 - k)
+    }
+
+    // values below x in range
+    pub fn count_less(&self, range: Range<usize>, x: u64) -> usize {
+        self.check(&range);
+        if !self.fits(x) {
+            return range.len();
+        }
+        let (mut node, mut lo, mut hi, mut count) =
+            (Node::root(self.len), range.start, range.end, 0);
+        for (l, level) in self.levels.iter().enumerate() {
+            let bit = self.bit(x, l);
+            if bit {
+                count += level.rank0(hi) - level.rank0(lo);
+            }
+            lo = node.child_pos(level, bit, lo);
+            hi = node.child_pos(level, bit, hi);
+            node = node.child(level, bit);
+        }
+        count
+    }
+
+    // values in range that fall in values
+    pub fn range_count(&self, range: Range<usize>, values: Range<u64>) -> usize {
+        if values.start >= values.end {
+            self.check(&range);
+            return 0;
+        }
+        self.count_less(range.clone(), values.end) - self.count_less(range, values.start)
+    }
+
+    fn fits(&self, value: u64) -> bool {
+        self.levels.len() == 64 || value >> self.levels.len() == 0
+    }
+
+    // bit of value looked at on level l
+    fn bit(&self, value: u64, l: usize) -> bool {
+        value >> (self.levels.len() - 1 - l) & 1 == 1
+    }
+}
+
+fn prefix(value: u64, shift: u32) -> u64 {
+    value.checked_shr(shift + 1).unwrap_or(0)
+}
+
+// a node's range of its level
+#[derive(Clone, Copy)]
+struct Node {
+    start: usize,
+    end: usize,
+}
+
+impl Node {
+    fn root(len: usize) -> Self {
+        Self { start: 0, end: len }
+    }
+
+    // zeros in the node, which make up its left child
+    fn zeros(&self, level: &RankSelect) -> usize {
+        level.rank0(self.end) - level.rank0(self.start)
+    }
+
+    fn child(&self, level: &RankSelect, bit: bool) -> Self {
+        let zeros = self.zeros(level);
+        if bit {
+            Self {
+                start: self.start + zeros,
+                end: self.end,
+            }
+        } else {
+            Self {
+                start: self.start,
+                end: self.start + zeros,
+            }
+        }
+    }
+
+    // where position i of the node, or its end, lands in the child for bit
+    fn child_pos(&self, level: &RankSelect, bit: bool, i: usize) -> usize {
+        let child = self.child(level, bit);
+        child.start + level.rank(bit, i) - level.rank(bit, self.start)
+    }
+}
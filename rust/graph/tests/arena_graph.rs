@@ -0,0 +1,189 @@
+// ArenaGraph against a model that keeps every id ever handed out, live or
+// not: removed vertices and edges must stop resolving, and the out-lists
+// and in-lists must hold the live edges, newest first, at every step.
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use graph::arena_graph::{ArenaGraph, EdgeId, VertexId};
+use graph::rng::{Rng, SplitMix64};
+
+use common::cases;
+
+// (from, to, weight) by model vertex
+type Edge = (usize, usize, i32);
+
+// ids by the order they were handed out, None once removed
+#[derive(Default)]
+struct Model {
+    vertices: Vec<(VertexId, Option<u32>)>,
+    edges: Vec<(EdgeId, Option<Edge>)>,
+}
+
+impl Model {
+    fn live_edges(&self) -> impl Iterator<Item = (usize, Edge)> + '_ {
+        self.edges
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(_, e))| e.map(|e| (i, e)))
+    }
+
+    fn remove_vertex(&mut self, v: usize) -> Option<u32> {
+        let value = self.vertices[v].1.take()?;
+        for (_, edge) in self.edges.iter_mut() {
+            if edge.is_some_and(|(a, b, _)| a == v || b == v) {
+                *edge = None;
+            }
+        }
+        Some(value)
+    }
+}
+
+fn check(g: &ArenaGraph<u32>, model: &Model) {
+    let live = model.vertices.iter().filter(|v| v.1.is_some()).count();
+    assert_eq!(g.vertex_count(), live);
+    assert_eq!(g.edge_count(), model.live_edges().count());
+    assert_eq!(g.is_empty(), live == 0);
+    let listed: BTreeMap<usize, u32> = g
+        .vertices()
+        .map(|(id, &value)| {
+            let at = model.vertices.iter().position(|v| v.0 == id).unwrap();
+            (at, value)
+        })
+        .collect();
+    let expected: BTreeMap<usize, u32> = model
+        .vertices
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| v.1.map(|value| (i, value)))
+        .collect();
+    assert_eq!(listed, expected);
+    for (i, &(id, value)) in model.vertices.iter().enumerate() {
+        assert_eq!(g.contains_vertex(id), value.is_some());
+        assert_eq!(g.vertex(id).copied(), value);
+        let mut out: Vec<(EdgeId, VertexId, i32)> = Vec::new();
+        let mut inc: Vec<(EdgeId, VertexId, i32)> = Vec::new();
+        for (e, (a, b, w)) in model.live_edges() {
+            let id = model.edges[e].0;
+            if a == i {
+                out.push((id, model.vertices[b].0, w));
+            }
+            if b == i {
+                inc.push((id, model.vertices[a].0, w));
+            }
+        }
+        out.reverse();
+        inc.reverse();
+        assert_eq!(g.out_edges(id).collect::<Vec<_>>(), out);
+        assert_eq!(g.in_edges(id).collect::<Vec<_>>(), inc);
+        assert!(g.neighbors(id).eq(out.iter().map(|e| e.1)));
+        let degrees = value.map(|_| (out.len(), inc.len()));
+        assert_eq!(g.out_degree(id).zip(g.in_degree(id)), degrees);
+    }
+    for &(id, edge) in model.edges.iter() {
+        let expected = edge.map(|(a, b, w)| (model.vertices[a].0, model.vertices[b].0, w));
+        assert_eq!(g.edge(id), expected);
+    }
+}
+
+#[test]
+fn arena_graph_matches_model() {
+    cases(124, 200, |rng| {
+        let mut g = ArenaGraph::new();
+        let mut model = Model::default();
+        for step in 0..rng.gen_range(400) {
+            let some_vertex = |rng: &mut SplitMix64, m: &Model| rng.gen_range(m.vertices.len());
+            match rng.gen_range(10) {
+                0..=2 => {
+                    let value = rng.next_u64() as u32;
+                    model.vertices.push((g.add_vertex(value), Some(value)));
+                }
+                3 if !model.vertices.is_empty() => {
+                    let v = some_vertex(rng, &model);
+                    let id = model.vertices[v].0;
+                    assert_eq!(g.remove_vertex(id), model.remove_vertex(v));
+                }
+                4..=6 if !model.vertices.is_empty() => {
+                    let (a, b) = (some_vertex(rng, &model), some_vertex(rng, &model));
+                    let b = if rng.gen_bool(0.1) { a } else { b };
+                    let w = rng.gen_range(100) as i32;
+                    let (va, vb) = (model.vertices[a], model.vertices[b]);
+                    let added = g.add_edge(va.0, vb.0, w);
+                    assert_eq!(added.is_some(), va.1.is_some() && vb.1.is_some());
+                    if let Some(e) = added {
+                        model.edges.push((e, Some((a, b, w))));
+                    }
+                }
+                7 if !model.edges.is_empty() => {
+                    let e = rng.gen_range(model.edges.len());
+                    let id = model.edges[e].0;
+                    let expected = model.edges[e].1.take().map(|(_, _, w)| w);
+                    assert_eq!(g.remove_edge(id), expected);
+                }
+                8 if !model.edges.is_empty() => {
+                    let e = rng.gen_range(model.edges.len());
+                    let w = rng.gen_range(100) as i32;
+                    let (id, edge) = &mut model.edges[e];
+                    assert_eq!(g.set_weight(*id, w), edge.is_some());
+                    if let Some(edge) = edge {
+                        edge.2 = w;
+                    }
+                }
+                _ if !model.vertices.is_empty() => {
+                    let (a, b) = (some_vertex(rng, &model), some_vertex(rng, &model));
+                    let (va, vb) = (model.vertices[a].0, model.vertices[b].0);
+                    let any = model.live_edges().any(|(_, e)| (e.0, e.1) == (a, b));
+                    let found = g.find_edge(va, vb);
+                    assert_eq!(found.is_some(), any);
+                    if let Some(e) = found {
+                        assert!(g.edge(e).is_some_and(|(x, y, _)| (x, y) == (va, vb)));
+                    }
+                }
+                _ => {}
+            }
+            if step % 10 == 0 {
+                check(&g, &model);
+            }
+        }
+        check(&g, &model);
+        // the snapshot holds the same edges, by dense index
+        let (csr, ids) = g.to_csr();
+        assert_eq!(csr.vertex_count(), ids.len());
+        let dense = |v: VertexId| ids.iter().position(|&u| u == v).unwrap();
+        let mut snapshot: Vec<(usize, usize, i32)> = (0..ids.len())
+            .flat_map(|v| csr.edges(v).map(move |(u, w)| (v, u, w)))
+            .collect();
+        let mut expected: Vec<(usize, usize, i32)> = model
+            .live_edges()
+            .map(|(_, (a, b, w))| {
+                let (va, vb) = (model.vertices[a].0, model.vertices[b].0);
+                (dense(va), dense(vb), w)
+            })
+            .collect();
+        snapshot.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(snapshot, expected);
+    });
+}
+
+// a slot freed and taken again must not answer to the old id
+#[test]
+fn arena_graph_stale_ids_stay_dead() {
+    let mut g = ArenaGraph::new();
+    let a = g.add_vertex(1);
+    let b = g.add_vertex(2);
+    let e = g.add_edge(a, b, 5).unwrap();
+    assert_eq!(g.remove_vertex(a), Some(1));
+    assert_eq!(g.edge(e), None);
+    assert_eq!(g.in_degree(b), Some(0));
+    let c = g.add_vertex(3);
+    let f = g.add_edge(c, b, 7).unwrap();
+    assert_ne!(a, c);
+    assert_eq!(g.vertex(a), None);
+    assert_eq!(g.add_edge(a, b, 1), None);
+    assert_eq!(g.remove_vertex(a), None);
+    assert_eq!(g.remove_edge(e), None);
+    assert!(!g.set_weight(e, 0));
+    assert_eq!(g.edge(f), Some((c, b, 7)));
+}
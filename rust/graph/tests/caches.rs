@@ -0,0 +1,272 @@
+// The caches against BTreeMap models on random traces over few keys: each
+// model entry keeps its value with the tick of its last use, and evicts
+// the smallest by its policy.
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use graph::arc_cache::ArcCache;
+use graph::cache::Cache;
+use graph::lfu_cache::LfuCache;
+use graph::lru_cache::LruCache;
+use graph::rng::{Rng, SplitMix64};
+
+use common::cases;
+
+// key to (value, cost, last use) under a total cost
+struct LruModel {
+    entries: BTreeMap<usize, (u64, usize, u64)>,
+    capacity: usize,
+    tick: u64,
+}
+
+impl LruModel {
+    fn total(&self) -> usize {
+        self.entries.values().map(|e| e.1).sum()
+    }
+
+    fn get(&mut self, key: usize) -> Option<u64> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(&key)?;
+        entry.2 = self.tick;
+        Some(entry.0)
+    }
+
+    fn put(&mut self, key: usize, value: u64, cost: usize) -> Option<u64> {
+        self.tick += 1;
+        if cost > self.capacity {
+            return self.entries.remove(&key).map(|e| e.0);
+        }
+        let old = self.entries.insert(key, (value, cost, self.tick));
+        while self.total() > self.capacity {
+            let lru = *self.lru().unwrap().0;
+            self.entries.remove(&lru);
+        }
+        old.map(|e| e.0)
+    }
+
+    fn lru(&self) -> Option<(&usize, &(u64, usize, u64))> {
+        self.entries.iter().min_by_key(|(_, e)| e.2)
+    }
+
+    // most recently used first
+    fn order(&self) -> Vec<(usize, u64)> {
+        let mut order: Vec<_> = self.entries.iter().collect();
+        order.sort_by_key(|(_, e)| std::cmp::Reverse(e.2));
+        order.into_iter().map(|(&k, e)| (k, e.0)).collect()
+    }
+}
+
+fn lru_trace(
+    cache: &mut LruCache<usize, u64>,
+    model: &mut LruModel,
+    cost: fn(&usize, &u64) -> usize,
+    rng: &mut SplitMix64,
+) {
+    let keys = 1 + rng.gen_range(3 * model.capacity + 2);
+    for _ in 0..rng.gen_range(1000) {
+        let k = rng.gen_range(keys);
+        match rng.gen_range(10) {
+            0..=3 => assert_eq!(cache.get(&k).copied(), model.get(k)),
+            4..=7 => {
+                let v = rng.next_u64() % 8;
+                assert_eq!(cache.put(k, v), model.put(k, v, cost(&k, &v)));
+            }
+            8 => assert_eq!(cache.remove(&k), model.entries.remove(&k).map(|e| e.0)),
+            _ => assert_eq!(cache.peek(&k), model.entries.get(&k).map(|e| &e.0)),
+        }
+        assert_eq!(cache.len(), model.entries.len());
+        assert_eq!(cache.total_cost(), model.total());
+        assert_eq!(
+            cache.peek_lru().map(|(&k, &v)| (k, v)),
+            model.lru().map(|(&k, e)| (k, e.0))
+        );
+    }
+    let order: Vec<_> = cache.iter().map(|(&k, &v)| (k, v)).collect();
+    assert_eq!(order, model.order());
+}
+
+#[test]
+fn lru_matches_model() {
+    cases(169, 300, |rng| {
+        let capacity = rng.gen_range(20);
+        let mut cache = LruCache::new(capacity);
+        let mut model = LruModel {
+            entries: BTreeMap::new(),
+            capacity,
+            tick: 0,
+        };
+        lru_trace(&mut cache, &mut model, |_, _| 1, rng);
+        while let Some((k, v)) = cache.pop_lru() {
+            let lru = *model.lru().unwrap().0;
+            assert_eq!((k, v), (lru, model.entries.remove(&lru).unwrap().0));
+        }
+        assert!(model.entries.is_empty());
+    });
+}
+
+#[test]
+fn lru_with_costs_matches_model() {
+    cases(1690, 300, |rng| {
+        let capacity = rng.gen_range(40);
+        let cost: fn(&usize, &u64) -> usize = |&k, &v| k % 3 + v as usize;
+        let mut cache = LruCache::with_cost(capacity, cost);
+        let mut model = LruModel {
+            entries: BTreeMap::new(),
+            capacity,
+            tick: 0,
+        };
+        lru_trace(&mut cache, &mut model, cost, rng);
+        cache.clear();
+        assert!(cache.is_empty() && cache.total_cost() == 0);
+    });
+}
+
+// through the trait, as generic code sees it
+#[test]
+fn lru_through_the_cache_trait() {
+    let mut cache: Box<dyn Cache<usize, u64>> = Box::new(LruCache::new(2));
+    assert_eq!(cache.put(1, 10), None);
+    assert_eq!(cache.put(2, 20), None);
+    assert_eq!(cache.get(&1), Some(&10));
+    assert_eq!(cache.put(3, 30), None);
+    assert!(!cache.contains_key(&2));
+    assert!(cache.contains_key(&1) && cache.contains_key(&3));
+    assert_eq!((cache.len(), cache.capacity()), (2, 2));
+}
+
+// key to (value, uses, last use), evicting the fewest uses, then the oldest
+struct LfuModel {
+    entries: BTreeMap<usize, (u64, u64, u64)>,
+    capacity: usize,
+    tick: u64,
+}
+
+impl LfuModel {
+    fn touch(&mut self, key: usize) -> Option<&mut (u64, u64, u64)> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(&key)?;
+        entry.1 += 1;
+        entry.2 = self.tick;
+        Some(entry)
+    }
+
+    fn put(&mut self, key: usize, value: u64) -> Option<u64> {
+        if let Some(entry) = self.touch(key) {
+            return Some(std::mem::replace(&mut entry.0, value));
+        }
+        if self.capacity == 0 {
+            return None;
+        }
+        if self.entries.len() == self.capacity {
+            self.pop();
+        }
+        self.entries.insert(key, (value, 1, self.tick));
+        None
+    }
+
+    fn pop(&mut self) -> Option<(usize, u64)> {
+        let (&key, _) = self.entries.iter().min_by_key(|(_, e)| (e.1, e.2))?;
+        Some((key, self.entries.remove(&key).unwrap().0))
+    }
+}
+
+#[test]
+fn lfu_matches_model() {
+    cases(170, 300, |rng| {
+        let capacity = rng.gen_range(20);
+        let mut cache = LfuCache::new(capacity);
+        let mut model = LfuModel {
+            entries: BTreeMap::new(),
+            capacity,
+            tick: 0,
+        };
+        let keys = 1 + rng.gen_range(3 * capacity + 2);
+        for _ in 0..rng.gen_range(1000) {
+            let k = rng.gen_range(keys);
+            match rng.gen_range(10) {
+                0..=3 => assert_eq!(cache.get(&k).copied(), model.touch(k).map(|e| e.0)),
+                4..=7 => {
+                    let v = rng.next_u64();
+                    assert_eq!(cache.put(k, v), model.put(k, v));
+                }
+                8 => assert_eq!(cache.remove(&k), model.entries.remove(&k).map(|e| e.0)),
+                _ => assert_eq!(cache.pop_lfu(), model.pop()),
+            }
+            assert_eq!(cache.len(), model.entries.len());
+            assert_eq!(cache.count(&k), model.entries.get(&k).map(|e| e.1));
+            assert_eq!(cache.peek(&k), model.entries.get(&k).map(|e| &e.0));
+        }
+        while let Some(popped) = cache.pop_lfu() {
+            assert_eq!(Some(popped), model.pop());
+        }
+        assert!(model.entries.is_empty());
+    });
+}
+
+// ARC's choice of victim is its own, so the model only holds the last
+// value put; whatever the cache still has must agree with it.
+#[test]
+fn arc_agrees_with_last_values_put() {
+    cases(171, 300, |rng| {
+        let capacity = rng.gen_range(20);
+        let mut cache = ArcCache::new(capacity);
+        let mut model = BTreeMap::new();
+        let keys = 1 + rng.gen_range(3 * capacity + 2);
+        for _ in 0..rng.gen_range(2000) {
+            let k = rng.gen_range(keys);
+            let held = cache.contains_key(&k);
+            match rng.gen_range(10) {
+                0..=3 => {
+                    let got = cache.get(&k).copied();
+                    assert_eq!(got.is_some(), held);
+                    assert!(got.is_none() || got == model.get(&k).copied());
+                }
+                4..=8 => {
+                    let v = rng.next_u64();
+                    let old = model.insert(k, v);
+                    assert_eq!(cache.put(k, v), old.filter(|_| held));
+                    assert_eq!(cache.contains_key(&k), capacity > 0);
+                }
+                _ => {
+                    let removed = cache.remove(&k);
+                    assert_eq!(removed, model.remove(&k).filter(|_| held));
+                    assert!(!cache.contains_key(&k));
+                }
+            }
+            assert!(cache.len() <= capacity);
+            assert!(cache.target_recent() <= capacity);
+            assert_eq!(cache.peek(&k).is_some(), cache.contains_key(&k));
+        }
+        // as many new keys as it holds fill it up
+        let len = cache.len();
+        for k in keys..keys + capacity {
+            cache.put(k, 0);
+        }
+        assert!(cache.len() >= len && cache.len() == capacity);
+        cache.clear();
+        assert!(cache.is_empty() && cache.target_recent() == 0);
+    });
+}
+
+// keys used twice sit in the frequency list, where a scan of new keys
+// doesn't reach them, though it flushes an LRU cache of the same size
+#[test]
+fn arc_keeps_frequent_keys_through_a_scan() {
+    let mut arc = ArcCache::new(100);
+    let mut lru = LruCache::new(100);
+    for k in 0..50 {
+        arc.put(k, k);
+        lru.put(k, k);
+        arc.get(&k);
+        lru.get(&k);
+    }
+    for k in 1000..2000 {
+        arc.put(k, k);
+        lru.put(k, k);
+    }
+    assert!((0..50).all(|k| arc.get(&k) == Some(&k)));
+    assert!((0..50).all(|k| !lru.contains_key(&k)));
+    assert_eq!(arc.len(), 100);
+}
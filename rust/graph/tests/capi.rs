@@ -0,0 +1,127 @@
+// include/graph.h is written by hand, so it is checked against what the
+// library really exports: the graph_ functions nm finds in libgraph.a,
+// and a C program that includes the header, links the archive and calls
+// every function. Both are skipped on a machine without the tool.
+
+use std::{collections::BTreeSet, env, fs, path::PathBuf, process::Command};
+
+use graph::capi::{GraphStatus, GRAPH_NO_VERTEX, GRAPH_UNREACHABLE};
+
+const HEADER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/include/graph.h");
+
+// the archive cargo built next to this test, target/<profile>/libgraph.a
+fn static_lib() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let lib = exe.parent().unwrap().parent().unwrap().join("libgraph.a");
+    assert!(lib.exists(), "no {}", lib.display());
+    lib
+}
+
+fn have(tool: &str) -> bool {
+    let found = Command::new(tool).arg("--version").output();
+    let found = found.is_ok_and(|out| out.status.success());
+    if !found {
+        eprintln!("skipped, {tool} is not installed");
+    }
+    found
+}
+
+// the names of the functions the header declares
+fn declared() -> BTreeSet<String> {
+    let header = fs::read_to_string(HEADER).unwrap();
+    let mut names = BTreeSet::new();
+    for (i, _) in header.match_indices("graph_") {
+        let rest = &header[i..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        if rest[end..].starts_with('(') {
+            names.insert(word.to_string());
+        }
+    }
+    names
+}
+
+#[test]
+fn header_declares_every_exported_function() {
+    if !have("nm") {
+        return;
+    }
+    let out = Command::new("nm")
+        .args(["-g", "--defined-only"])
+        .arg(static_lib())
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let exported: BTreeSet<String> = String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(
+            |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [_, "T", name] if name.starts_with("graph_") => Some(name.to_string()),
+                _ => None,
+            },
+        )
+        .collect();
+    assert!(!exported.is_empty());
+    assert_eq!(declared(), exported);
+}
+
+#[test]
+fn c_program_runs_against_static_lib() {
+    if !have("cc") {
+        return;
+    }
+    let expected = [
+        ("UNREACHABLE", GRAPH_UNREACHABLE.to_string()),
+        ("NO_VERTEX", GRAPH_NO_VERTEX.to_string()),
+        ("STATUS_OK", (GraphStatus::Ok as i32).to_string()),
+        (
+            "STATUS_NULL_POINTER",
+            (GraphStatus::NullPointer as i32).to_string(),
+        ),
+        (
+            "STATUS_VERTEX_OUT_OF_RANGE",
+            (GraphStatus::VertexOutOfRange as i32).to_string(),
+        ),
+        (
+            "STATUS_NEGATIVE_WEIGHT",
+            (GraphStatus::NegativeWeight as i32).to_string(),
+        ),
+        (
+            "STATUS_BUFFER_TOO_SMALL",
+            (GraphStatus::BufferTooSmall as i32).to_string(),
+        ),
+    ];
+    let dir = env!("CARGO_MANIFEST_DIR");
+    let exe = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("capi_smoke");
+    let out = Command::new("cc")
+        .args(["-std=c11", "-Wall", "-Wextra", "-Werror"])
+        .arg(format!("-I{dir}/include"))
+        .args(
+            expected
+                .iter()
+                .map(|(name, v)| format!("-DEXPECT_{name}={v}")),
+        )
+        .arg(format!("{dir}/tests/capi/smoke.c"))
+        .arg(static_lib())
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&exe)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let run = Command::new(&exe).output().unwrap();
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+}
@@ -0,0 +1,67 @@
+/* Drives every function of include/graph.h once, linked against
+   libgraph.a. tests/capi.rs passes the values the Rust side uses as
+   EXPECT_ defines; the first surprise exits non-zero. */
+
+#include <stdio.h>
+
+#include "graph.h"
+
+_Static_assert(GRAPH_UNREACHABLE == EXPECT_UNREACHABLE, "GRAPH_UNREACHABLE");
+_Static_assert(GRAPH_NO_VERTEX == EXPECT_NO_VERTEX, "GRAPH_NO_VERTEX");
+_Static_assert(GRAPH_STATUS_OK == EXPECT_STATUS_OK, "GRAPH_STATUS_OK");
+_Static_assert(GRAPH_STATUS_NULL_POINTER == EXPECT_STATUS_NULL_POINTER,
+               "GRAPH_STATUS_NULL_POINTER");
+_Static_assert(GRAPH_STATUS_VERTEX_OUT_OF_RANGE == EXPECT_STATUS_VERTEX_OUT_OF_RANGE,
+               "GRAPH_STATUS_VERTEX_OUT_OF_RANGE");
+_Static_assert(GRAPH_STATUS_NEGATIVE_WEIGHT == EXPECT_STATUS_NEGATIVE_WEIGHT,
+               "GRAPH_STATUS_NEGATIVE_WEIGHT");
+_Static_assert(GRAPH_STATUS_BUFFER_TOO_SMALL == EXPECT_STATUS_BUFFER_TOO_SMALL,
+               "GRAPH_STATUS_BUFFER_TOO_SMALL");
+
+#define CHECK(cond)                                                  \
+  do {                                                               \
+    if (!(cond)) {                                                   \
+      fprintf(stderr, "%s:%d: failed: %s\n", __FILE__, __LINE__, #cond); \
+      return 1;                                                      \
+    }                                                                \
+  } while (0)
+
+int main(void) {
+  graph_t *g = graph_new(3);
+  CHECK(g != NULL);
+  CHECK(graph_vertex_count(g) == 3);
+
+  uint32_t id = 0;
+  CHECK(graph_add_vertex(g, &id) == GRAPH_STATUS_OK);
+  CHECK(id == 3 && graph_vertex_count(g) == 4);
+  CHECK(graph_add_vertex(g, NULL) == GRAPH_STATUS_NULL_POINTER);
+
+  CHECK(graph_add_edge(g, 0, 1, 4) == GRAPH_STATUS_OK);
+  CHECK(graph_add_edge(g, 1, 2, 1) == GRAPH_STATUS_OK);
+  CHECK(graph_add_edge(g, 0, 2, 7) == GRAPH_STATUS_OK);
+  CHECK(graph_add_edge(g, 0, 9, 1) == GRAPH_STATUS_VERTEX_OUT_OF_RANGE);
+  CHECK(graph_edge_count(g) == 3);
+
+  /* 3 is never reached */
+  int64_t dist[4];
+  uint32_t prev[4];
+  CHECK(graph_dijkstra(g, 0, dist, prev, 3) == GRAPH_STATUS_BUFFER_TOO_SMALL);
+  CHECK(graph_dijkstra(g, 4, dist, prev, 4) == GRAPH_STATUS_VERTEX_OUT_OF_RANGE);
+  CHECK(graph_dijkstra(g, 0, NULL, prev, 4) == GRAPH_STATUS_NULL_POINTER);
+  CHECK(graph_dijkstra(g, 0, dist, prev, 4) == GRAPH_STATUS_OK);
+  CHECK(dist[0] == 0 && dist[1] == 4 && dist[2] == 5 && dist[3] == GRAPH_UNREACHABLE);
+  CHECK(prev[0] == GRAPH_NO_VERTEX && prev[1] == 0 && prev[2] == 1);
+  CHECK(prev[3] == GRAPH_NO_VERTEX);
+  CHECK(graph_dijkstra(g, 1, dist, NULL, 4) == GRAPH_STATUS_OK);
+  CHECK(dist[0] == GRAPH_UNREACHABLE && dist[2] == 1);
+
+  CHECK(graph_add_edge(g, 2, 3, -1) == GRAPH_STATUS_OK);
+  CHECK(graph_dijkstra(g, 0, dist, prev, 4) == GRAPH_STATUS_NEGATIVE_WEIGHT);
+
+  CHECK(graph_vertex_count(NULL) == 0 && graph_edge_count(NULL) == 0);
+  CHECK(graph_add_edge(NULL, 0, 0, 0) == GRAPH_STATUS_NULL_POINTER);
+  CHECK(graph_dijkstra(NULL, 0, dist, prev, 4) == GRAPH_STATUS_NULL_POINTER);
+  graph_free(g);
+  graph_free(NULL);
+  return 0;
+}
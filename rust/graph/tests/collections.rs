@@ -0,0 +1,1022 @@
+// The collections against plain models: Vecs, Strings, std's sets, a map
+// of every key or a scan of the input. Every version of the persistent ones is checked
+// again after later versions are made from it.
+
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ops::Range;
+use std::rc::Rc;
+
+use graph::bit_vec::{BitVec, RankSelect};
+use graph::cons_list::ConsList;
+use graph::linked_list::DoublyLinkedList;
+use graph::monotonic::{
+    largest_rectangle, next_greater, sliding_window_max, sliding_window_min, MonotonicDeque,
+    MonotonicStack,
+};
+use graph::persistent_vec::PVec;
+use graph::range_map::{RangeMap, RangeSet};
+use graph::ring_buffer::{BufferFull, RingBuffer};
+use graph::rng::{Rng, SplitMix64};
+use graph::rope::Rope;
+use graph::veb_tree::VebTree;
+use graph::wavelet_tree::WaveletTree;
+
+use common::cases;
+
+fn check_pvec(v: &PVec<u64>, model: &[u64]) {
+    assert_eq!(v.len(), model.len());
+    assert_eq!(v.iter().len(), model.len());
+    assert!(v.iter().eq(model.iter()));
+    assert_eq!(v.first(), model.first());
+    assert_eq!(v.last(), model.last());
+    assert_eq!(v.get(model.len()), None);
+}
+
+#[test]
+fn pvec_versions_match_vecs() {
+    cases(177, 100, |rng| {
+        let mut versions = vec![(PVec::new(), Vec::new())];
+        for _ in 0..rng.gen_range(300) {
+            let (v, mut model) = versions[rng.gen_range(versions.len())].clone();
+            let x = rng.next_u64();
+            let (w, other) = &versions[rng.gen_range(versions.len())];
+            let next = match rng.gen_range(6) {
+                0 if !model.is_empty() => {
+                    let i = rng.gen_range(model.len());
+                    assert_eq!(v.get(i), Some(&model[i]));
+                    model[i] = x;
+                    (v.set(i, x), model)
+                }
+                1 => {
+                    model.insert(0, x);
+                    (v.push_front(x), model)
+                }
+                2 => {
+                    let popped = model.pop();
+                    let (v, last) = v
+                        .pop_back()
+                        .map_or((PVec::new(), None), |(v, x)| (v, Some(x)));
+                    assert_eq!(last, popped);
+                    (v, model)
+                }
+                // kept from doubling the sizes over and over
+                3 if model.len() + other.len() < 20_000 => {
+                    (v.concat(w), [model.as_slice(), other].concat())
+                }
+                4 => {
+                    let model: Vec<u64> =
+                        (0..rng.gen_range(2000)).map(|_| rng.next_u64()).collect();
+                    (model.iter().copied().collect(), model)
+                }
+                _ => {
+                    model.push(x);
+                    (v.push_back(x), model)
+                }
+            };
+            check_pvec(&next.0, &next.1);
+            versions.push(next);
+        }
+        // nothing made later disturbed an older version
+        for (v, model) in versions.iter() {
+            check_pvec(v, model);
+            let i = rng.gen_range(model.len() + 1);
+            assert_eq!(v.get(i), model.get(i));
+            assert!(v.clone().ptr_eq(v));
+        }
+    });
+}
+
+#[test]
+fn cons_list_versions_match_vecs() {
+    cases(1770, 100, |rng| {
+        // models head first
+        let mut versions = vec![(ConsList::new(), Vec::new())];
+        for _ in 0..rng.gen_range(300) {
+            let (list, model): (ConsList<u64>, Vec<u64>) =
+                versions[rng.gen_range(versions.len())].clone();
+            let next = match rng.gen_range(4) {
+                0 => {
+                    let tail = list.tail();
+                    assert_eq!(tail.is_none(), model.is_empty());
+                    match list.uncons() {
+                        Some((&head, rest)) => {
+                            assert_eq!(head, model[0]);
+                            assert_eq!(Some(&rest), tail.as_ref());
+                            (rest, model[1..].to_vec())
+                        }
+                        None => (list, model),
+                    }
+                }
+                1 => {
+                    let model: Vec<u64> = model.into_iter().rev().collect();
+                    (list.reverse(), model)
+                }
+                2 => {
+                    let model: Vec<u64> = (0..rng.gen_range(50)).map(|_| rng.next_u64()).collect();
+                    (model.iter().copied().collect(), model)
+                }
+                _ => {
+                    let x = rng.next_u64();
+                    (list.cons(x), [&[x], model.as_slice()].concat())
+                }
+            };
+            versions.push(next);
+        }
+        for (list, model) in versions.iter() {
+            assert_eq!(list.len(), model.len());
+            assert_eq!(list.head(), model.first());
+            assert!(list.iter().eq(model.iter()));
+            assert_eq!(list, &model.iter().copied().collect::<ConsList<u64>>());
+        }
+    });
+}
+
+// dropping a long list, and versions of it, must not recurse
+#[test]
+fn long_cons_lists_drop() {
+    let mut list = ConsList::new();
+    for i in 0..1_000_000 {
+        list = list.cons(i);
+    }
+    let shorter = list.tail().unwrap().tail().unwrap();
+    drop(list);
+    assert_eq!(shorter.len(), 999_998);
+    assert_eq!(shorter.head(), Some(&999_997));
+}
+
+// one to four bytes a char
+const ALPHABET: [char; 8] = ['a', 'z', ' ', '\n', 'é', 'ß', '中', '🦀'];
+
+fn text(max_chars: usize, rng: &mut SplitMix64) -> String {
+    (0..rng.gen_range(max_chars + 1))
+        .map(|_| ALPHABET[rng.gen_range(ALPHABET.len())])
+        .collect()
+}
+
+fn byte_of(s: &str, char_idx: usize) -> usize {
+    s.char_indices().nth(char_idx).map_or(s.len(), |(b, _)| b)
+}
+
+// index of the char holding byte_idx, the char count past the end
+fn char_of(s: &str, byte_idx: usize) -> usize {
+    s.char_indices().take_while(|&(b, _)| b <= byte_idx).count() - usize::from(byte_idx < s.len())
+}
+
+fn check_rope(rope: &Rope, model: &str, rng: &mut SplitMix64) {
+    assert_eq!(rope, model);
+    assert_eq!(rope.len_bytes(), model.len());
+    assert_eq!(rope.len_chars(), model.chars().count());
+    assert!(rope.chunks().all(|c| !c.is_empty()));
+    assert_eq!(rope.chunks().collect::<String>(), model);
+    assert!(rope.chars().eq(model.chars()));
+    assert!(rope.bytes().eq(model.bytes()));
+    for _ in 0..5 {
+        let b = rng.gen_range(model.len() + 1);
+        assert_eq!(rope.is_char_boundary(b), model.is_char_boundary(b));
+        assert_eq!(rope.byte(b), model.as_bytes().get(b).copied());
+        assert_eq!(rope.byte_to_char(b), char_of(model, b));
+        let c = rng.gen_range(rope.len_chars() + 1);
+        assert_eq!(rope.char(c), model.chars().nth(c));
+        assert_eq!(rope.char_to_byte(c), byte_of(model, c));
+        let end = c + rng.gen_range(rope.len_chars() - c + 1);
+        assert_eq!(
+            rope.slice(c..end),
+            model[byte_of(model, c)..byte_of(model, end)]
+        );
+    }
+}
+
+#[test]
+fn rope_matches_string() {
+    cases(178, 200, |rng| {
+        let mut model = text(rng.gen_range(3000), rng);
+        let mut rope = Rope::from(model.as_str());
+        for _ in 0..rng.gen_range(200) {
+            let chars = rope.len_chars();
+            let (c, d) = (rng.gen_range(chars + 1), rng.gen_range(chars + 1));
+            let (c, d) = (c.min(d), c.max(d));
+            match rng.gen_range(7) {
+                0 => {
+                    let t = text(20, rng);
+                    rope.insert(c, &t);
+                    model.insert_str(byte_of(&model, c), &t);
+                }
+                1 => {
+                    let t = text(500, rng);
+                    let b = byte_of(&model, c);
+                    rope.insert_at_byte(b, &t);
+                    model.insert_str(b, &t);
+                }
+                2 => {
+                    let t = text(5, rng);
+                    rope.push_str(&t);
+                    model.push_str(&t);
+                }
+                3 => {
+                    rope.remove(c..d);
+                    model.replace_range(byte_of(&model, c)..byte_of(&model, d), "");
+                }
+                4 => {
+                    let (b, e) = (byte_of(&model, c), byte_of(&model, d));
+                    rope.remove_bytes(b..e);
+                    model.replace_range(b..e, "");
+                }
+                5 => {
+                    let rest = rope.split_off(c);
+                    let tail = model.split_off(byte_of(&model, c));
+                    check_rope(&rest, &tail, rng);
+                    check_rope(&rope, &model, rng);
+                    rope.append(rest);
+                    model.push_str(&tail);
+                }
+                _ => {
+                    // typed one char at a time
+                    for ch in text(30, rng).chars() {
+                        let at = rng.gen_range(rope.len_chars() + 1);
+                        rope.insert(at, ch.encode_utf8(&mut [0; 4]));
+                        model.insert(byte_of(&model, at), ch);
+                    }
+                }
+            }
+            check_rope(&rope, &model, rng);
+        }
+        assert_eq!(rope.to_string(), model);
+    });
+}
+
+#[test]
+fn bit_vec_matches_vec_of_bools() {
+    cases(179, 200, |rng| {
+        let mut bits = BitVec::zeros(rng.gen_range(300));
+        let mut model = vec![false; bits.len()];
+        for _ in 0..rng.gen_range(1000) {
+            let bit = rng.gen_bool(0.5);
+            if model.is_empty() || rng.gen_bool(0.3) {
+                bits.push(bit);
+                model.push(bit);
+            } else {
+                let i = rng.gen_range(model.len());
+                bits.set(i, bit);
+                model[i] = bit;
+            }
+        }
+        assert_eq!(bits.len(), model.len());
+        assert!(bits.iter().eq(model.iter().copied()));
+        assert!((0..model.len()).all(|i| bits.get(i) == model[i]));
+        assert_eq!(bits.count_ones(), model.iter().filter(|&&b| b).count());
+        assert_eq!(bits.words().len(), model.len().div_ceil(64));
+        if !model.len().is_multiple_of(64) {
+            assert_eq!(bits.words().last().unwrap() >> (model.len() % 64), 0);
+        }
+    });
+}
+
+// rank and select of every position against counting, at densities from
+// none to all and across superblock and sample boundaries
+#[test]
+fn rank_select_match_counting() {
+    cases(1790, 200, |rng| {
+        let density = [0.0, 0.001, 0.05, 0.5, 0.95, 1.0][rng.gen_range(6)];
+        let len = if rng.gen_bool(0.2) {
+            512 * rng.gen_range(8)
+        } else {
+            rng.gen_range(20_000)
+        };
+        let model: Vec<bool> = (0..len).map(|_| rng.gen_bool(density)).collect();
+        let index: RankSelect = model.iter().copied().collect();
+        assert_eq!(index.len(), len);
+        let mut ones = 0;
+        let mut positions = [Vec::new(), Vec::new()];
+        for (i, &bit) in model.iter().enumerate() {
+            assert_eq!(index.rank1(i), ones);
+            assert_eq!(index.rank0(i), i - ones);
+            assert_eq!(index.get(i), bit);
+            ones += usize::from(bit);
+            positions[usize::from(bit)].push(i);
+        }
+        assert_eq!(index.rank1(len), ones);
+        assert_eq!(
+            (index.count_ones(), index.count_zeros()),
+            (ones, len - ones)
+        );
+        for bit in [false, true] {
+            let at = &positions[usize::from(bit)];
+            for (k, &i) in at.iter().enumerate() {
+                assert_eq!(index.select(bit, k), Some(i));
+                assert_eq!(index.rank(bit, i), k);
+            }
+            assert_eq!(index.select(bit, at.len()), None);
+        }
+    });
+}
+
+// every query against scanning the slice, with alphabets from one value
+// to the whole of u64
+#[test]
+fn wavelet_tree_matches_scanning() {
+    cases(180, 200, |rng| {
+        let sigma = [1, 2, 5, 64, 1000, u64::MAX][rng.gen_range(6)];
+        let len = rng.gen_range(500);
+        let values: Vec<u64> = (0..len)
+            .map(|_| match sigma {
+                u64::MAX => rng.next_u64() >> rng.gen_range(64),
+                _ => rng.next_u64() % sigma,
+            })
+            .collect();
+        let tree = WaveletTree::new(&values);
+        assert_eq!(tree.len(), len);
+        assert!(values
+            .iter()
+            .all(|&v| tree.depth() == 64 || v >> tree.depth() == 0));
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(tree.get(i), v);
+        }
+        let some = |rng: &mut SplitMix64| match rng.gen_range(3) {
+            0 if len > 0 => values[rng.gen_range(len)],
+            1 => rng.next_u64() % sigma.saturating_add(1),
+            _ => rng.next_u64(),
+        };
+        for _ in 0..50 {
+            let x = some(rng);
+            let i = rng.gen_range(len + 1);
+            let positions: Vec<usize> = (0..len).filter(|&j| values[j] == x).collect();
+            assert_eq!(
+                tree.rank(x, i),
+                positions.iter().filter(|&&j| j < i).count()
+            );
+            let k = rng.gen_range(positions.len() + 1);
+            assert_eq!(tree.select(x, k), positions.get(k).copied());
+
+            let (a, b) = (rng.gen_range(len + 1), rng.gen_range(len + 1));
+            let range = a.min(b)..a.max(b);
+            let mut sorted = values[range.clone()].to_vec();
+            sorted.sort_unstable();
+            let k = rng.gen_range(sorted.len() + 1);
+            assert_eq!(tree.kth_smallest(range.clone(), k), sorted.get(k).copied());
+            let largest = sorted.iter().rev().nth(k).copied();
+            assert_eq!(tree.kth_largest(range.clone(), k), largest);
+            assert_eq!(
+                tree.count_less(range.clone(), x),
+                sorted.iter().filter(|&&v| v < x).count()
+            );
+            let y = some(rng);
+            let in_range = sorted.iter().filter(|&&v| x <= v && v < y).count();
+            assert_eq!(tree.range_count(range, x..y), in_range);
+        }
+    });
+}
+
+// keys 0..KEYS, few enough to model point by point
+const KEYS: u32 = 100;
+
+fn some_range(rng: &mut SplitMix64) -> Range<u32> {
+    let (a, b) = (
+        rng.gen_range(KEYS as usize + 1),
+        rng.gen_range(KEYS as usize + 1),
+    );
+    a.min(b) as u32..a.max(b) as u32
+}
+
+// maximal runs of consecutive keys holding equal values
+fn runs<V: Copy + Eq>(points: &BTreeMap<u32, V>) -> Vec<(Range<u32>, V)> {
+    let mut runs: Vec<(Range<u32>, V)> = Vec::new();
+    for (&k, &v) in points {
+        match runs.last_mut() {
+            Some((r, w)) if r.end == k && *w == v => r.end += 1,
+            _ => runs.push((k..k + 1, v)),
+        }
+    }
+    runs
+}
+
+// sharing a key, so never an empty range
+fn meets(a: &Range<u32>, b: &Range<u32>) -> bool {
+    a.start.max(b.start) < a.end.min(b.end)
+}
+
+fn gaps_of<V>(points: &BTreeMap<u32, V>, range: Range<u32>) -> Vec<Range<u32>> {
+    let free: BTreeMap<u32, ()> = range
+        .filter(|k| !points.contains_key(k))
+        .map(|k| (k, ()))
+        .collect();
+    runs(&free).into_iter().map(|(r, _)| r).collect()
+}
+
+// segments against a map of every key, which coalesced must be its runs
+#[test]
+fn range_map_matches_points() {
+    cases(181, 300, |rng| {
+        let mut map = RangeMap::new();
+        let mut points = BTreeMap::new();
+        for _ in 0..rng.gen_range(100) {
+            let range = some_range(rng);
+            if rng.gen_bool(0.7) {
+                let v = rng.gen_range(3);
+                map.insert(range.clone(), v);
+                points.extend(range.map(|k| (k, v)));
+            } else {
+                map.remove(range.clone());
+                points.retain(|k, _| !range.contains(k));
+            }
+            let segments: Vec<_> = map.iter().map(|(r, &v)| (r, v)).collect();
+            assert_eq!(segments, runs(&points));
+            assert_eq!(map.len(), segments.len());
+        }
+        for k in 0..=KEYS {
+            assert_eq!(map.get(&k), points.get(&k));
+            let run = runs(&points).into_iter().find(|(r, _)| r.contains(&k));
+            let found = map.get_key_value(&k).map(|(r, &v)| (r, v));
+            assert_eq!(found, run);
+        }
+        for _ in 0..20 {
+            let range = some_range(rng);
+            let overlapping: Vec<_> = map
+                .overlapping(range.clone())
+                .map(|(r, &v)| (r, v))
+                .collect();
+            let expected: Vec<_> = runs(&points)
+                .into_iter()
+                .filter(|(r, _)| meets(r, &range))
+                .collect();
+            assert_eq!(overlapping, expected);
+            assert_eq!(map.gaps(range.clone()), gaps_of(&points, range));
+        }
+    });
+}
+
+#[test]
+fn range_set_matches_points() {
+    cases(1810, 300, |rng| {
+        let mut set = RangeSet::new();
+        let mut points = BTreeMap::new();
+        for _ in 0..rng.gen_range(100) {
+            let range = some_range(rng);
+            if rng.gen_bool(0.6) {
+                set.insert(range.clone());
+                points.extend(range.map(|k| (k, ())));
+            } else {
+                set.remove(range.clone());
+                points.retain(|k, _| !range.contains(k));
+            }
+        }
+        let ranges: Vec<_> = runs(&points).into_iter().map(|(r, _)| r).collect();
+        assert_eq!(set.iter().collect::<Vec<_>>(), ranges);
+        assert_eq!(set.len(), ranges.len());
+        for k in 0..=KEYS {
+            assert_eq!(set.contains(&k), points.contains_key(&k));
+            assert_eq!(
+                set.range_of(&k),
+                ranges.iter().find(|r| r.contains(&k)).cloned()
+            );
+        }
+        for _ in 0..20 {
+            let range = some_range(rng);
+            let inside = range.clone().filter(|k| points.contains_key(k)).count();
+            assert_eq!(set.overlaps(range.clone()), inside > 0);
+            assert_eq!(set.covers(range.clone()), inside == range.len());
+            let overlapping: Vec<_> = ranges
+                .iter()
+                .filter(|r| meets(r, &range))
+                .cloned()
+                .collect();
+            assert_eq!(
+                set.overlapping(range.clone()).collect::<Vec<_>>(),
+                overlapping
+            );
+            assert_eq!(set.gaps(range.clone()), gaps_of(&points, range));
+        }
+    });
+}
+
+// a decreasing stack is what survives popping everything not greater
+#[test]
+fn monotonic_stack_matches_popping_a_vec() {
+    cases(182, 200, |rng| {
+        let mut stack = MonotonicStack::decreasing();
+        let mut model: Vec<u64> = Vec::new();
+        for _ in 0..rng.gen_range(300) {
+            let x = rng.next_u64() % 20;
+            let mut popped = Vec::new();
+            stack.push_with(x, |top, below| popped.push((top, below.copied())));
+            let mut expected = Vec::new();
+            while model.last().is_some_and(|&top| top <= x) {
+                let top = model.pop().unwrap();
+                expected.push((top, model.last().copied()));
+            }
+            model.push(x);
+            assert_eq!(popped, expected);
+            assert!(stack.iter().eq(model.iter()));
+            assert_eq!(stack.top(), model.last());
+        }
+    });
+}
+
+// The front of a max deque is the largest item pushed since the eviction
+// point, at the last index it was pushed, and it holds exactly the items
+// nothing later is at least as large as.
+#[test]
+fn monotonic_deque_matches_window_scan() {
+    cases(1820, 200, |rng| {
+        let mut deque = MonotonicDeque::max();
+        let mut pushed = Vec::new();
+        let mut from = 0;
+        for _ in 0..rng.gen_range(300) {
+            if rng.gen_bool(0.7) {
+                let x = rng.next_u64() % 20;
+                assert_eq!(deque.push(x), pushed.len());
+                pushed.push(x);
+            } else {
+                from = (from + rng.gen_range(4)).min(pushed.len());
+                deque.evict_before(from);
+            }
+            let window = &pushed[from..];
+            let held: Vec<(usize, u64)> = (from..pushed.len())
+                .filter(|&i| pushed[i + 1..].iter().all(|&later| later < pushed[i]))
+                .map(|i| (i, pushed[i]))
+                .collect();
+            assert_eq!(deque.iter().map(|(i, &x)| (i, x)).collect::<Vec<_>>(), held);
+            assert_eq!(deque.len(), held.len());
+            assert_eq!(deque.next_index(), pushed.len());
+            let max = window.iter().max();
+            assert_eq!(deque.front().map(|(_, x)| x), max);
+        }
+    });
+}
+
+fn some_values(rng: &mut SplitMix64) -> Vec<u64> {
+    let top = 1 + rng.gen_range(30) as u64;
+    (0..rng.gen_range(100))
+        .map(|_| rng.next_u64() % top)
+        .collect()
+}
+
+#[test]
+fn window_helpers_match_scanning() {
+    cases(18200, 300, |rng| {
+        let values = some_values(rng);
+        let k = 1 + rng.gen_range(values.len() + 2);
+        let windows = values.windows(k);
+        assert_eq!(
+            sliding_window_max(&values, k),
+            windows
+                .clone()
+                .map(|w| *w.iter().max().unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sliding_window_min(&values, k),
+            windows
+                .map(|w| *w.iter().min().unwrap())
+                .collect::<Vec<_>>()
+        );
+        let next: Vec<Option<usize>> = (0..values.len())
+            .map(|i| (i + 1..values.len()).find(|&j| values[j] > values[i]))
+            .collect();
+        assert_eq!(next_greater(&values), next);
+    });
+}
+
+// the best area over every span, and the leftmost span reaching it
+#[test]
+fn largest_rectangle_matches_every_span() {
+    cases(182000, 300, |rng| {
+        let heights = some_values(rng);
+        let area = |span: &Range<usize>| {
+            heights[span.clone()]
+                .iter()
+                .min()
+                .map_or(0, |&h| h * span.len() as u64)
+        };
+        let n = heights.len();
+        let best = (0..n)
+            .flat_map(|l| (l + 1..=n).map(move |r| l..r))
+            .map(|s| area(&s))
+            .max()
+            .unwrap_or(0);
+        let (found, span) = largest_rectangle(&heights);
+        assert_eq!(found, best);
+        if best == 0 {
+            assert_eq!(span, 0..0);
+        } else {
+            assert_eq!(area(&span), best);
+            let leftmost = (0..n).find(|&l| (l + 1..=n).any(|r| area(&(l..r)) == best));
+            assert_eq!(Some(span.start), leftmost);
+        }
+    });
+}
+
+// keys bunched around a few centres, so clusters fill and empty, with the
+// ends of the universe among them
+fn some_key(max: u64, centres: &[u64], rng: &mut SplitMix64) -> u64 {
+    match rng.gen_range(10) {
+        0 => 0,
+        1 => max,
+        2 => rng.next_u64() & max,
+        _ => {
+            let centre = centres[rng.gen_range(centres.len())];
+            centre
+                .wrapping_add(rng.gen_range(64) as u64)
+                .wrapping_sub(32)
+                & max
+        }
+    }
+}
+
+// every width from one bit to the whole of u64, queries out of the
+// universe included
+#[test]
+fn veb_tree_matches_btree_set() {
+    cases(163, 300, |rng| {
+        let bits = 1 + rng.gen_range(64) as u32;
+        let max = u64::MAX >> (64 - bits);
+        let centres: Vec<u64> = (0..1 + rng.gen_range(4))
+            .map(|_| rng.next_u64() & max)
+            .collect();
+        let mut tree = VebTree::new(bits);
+        let mut model = BTreeSet::new();
+        assert_eq!(tree.bits(), bits);
+        for _ in 0..rng.gen_range(400) {
+            let x = some_key(max, &centres, rng);
+            match rng.gen_range(10) {
+                0..=3 => assert_eq!(tree.insert(x), model.insert(x)),
+                4 | 5 => assert_eq!(tree.remove(x), model.remove(&x)),
+                6 => assert_eq!(tree.contains(x), model.contains(&x)),
+                _ => {
+                    assert_eq!(
+                        tree.successor(x),
+                        model.range(x..).find(|&&k| k > x).copied()
+                    );
+                    assert_eq!(tree.predecessor(x), model.range(..x).next_back().copied());
+                }
+            }
+            assert_eq!(tree.len(), model.len());
+            assert_eq!(tree.is_empty(), model.is_empty());
+            assert_eq!(tree.min(), model.first().copied());
+            assert_eq!(tree.max(), model.last().copied());
+        }
+        assert!(tree.iter().eq(model.iter().copied()));
+        if bits < 64 {
+            let out = max + 1 + (rng.next_u64() >> bits);
+            assert!(!tree.contains(out) && !tree.remove(out));
+            assert_eq!(tree.successor(out), None);
+            assert_eq!(tree.predecessor(out), model.last().copied());
+        }
+        tree.clear();
+        assert!(tree.is_empty() && tree.iter().next().is_none());
+    });
+}
+
+#[test]
+#[should_panic(expected = "outside the 3-bit universe")]
+fn veb_tree_rejects_keys_out_of_universe() {
+    VebTree::new(3).insert(8);
+}
+
+fn check_ring(buffer: &RingBuffer<u64>, model: &VecDeque<u64>) {
+    assert_eq!(buffer.len(), model.len());
+    assert_eq!(buffer.is_empty(), model.is_empty());
+    assert_eq!(buffer.is_full(), buffer.len() == buffer.capacity());
+    assert!(buffer.len() <= buffer.capacity());
+    assert_eq!(buffer.front(), model.front());
+    assert_eq!(buffer.back(), model.back());
+    assert!(buffer.iter().eq(model.iter()));
+    assert!(buffer.iter().rev().eq(model.iter().rev()));
+    assert_eq!(buffer.iter().len(), model.len());
+    let (a, b) = buffer.as_slices();
+    assert!(a.iter().chain(b).eq(model.iter()));
+    // only a run that reaches the end of the buffer wraps
+    assert!(b.is_empty() || !a.is_empty());
+}
+
+// growable and bounded, small enough that the window wraps all the time
+#[test]
+fn ring_buffer_matches_vec_deque() {
+    cases(175, 300, |rng| {
+        let bound = rng.gen_bool(0.5).then(|| rng.gen_range(9));
+        let mut buffer = match bound {
+            Some(capacity) => RingBuffer::bounded(capacity),
+            None if rng.gen_bool(0.5) => RingBuffer::with_capacity(rng.gen_range(9)),
+            None => RingBuffer::new(),
+        };
+        assert_eq!(buffer.is_bounded(), bound.is_some());
+        let capacity = buffer.capacity();
+        let mut model = VecDeque::new();
+        for _ in 0..rng.gen_range(500) {
+            let v = rng.next_u64();
+            let full = bound.is_some_and(|c| model.len() == c);
+            match rng.gen_range(14) {
+                0 | 1 => {
+                    let pushed = buffer.push_back(v);
+                    assert_eq!(pushed, if full { Err(BufferFull(v)) } else { Ok(()) });
+                    if !full {
+                        model.push_back(v);
+                    }
+                }
+                2 | 3 => {
+                    let pushed = buffer.push_front(v);
+                    assert_eq!(pushed, if full { Err(BufferFull(v)) } else { Ok(()) });
+                    if !full {
+                        model.push_front(v);
+                    }
+                }
+                4 => {
+                    model.push_back(v);
+                    let over = if full { model.pop_front() } else { None };
+                    assert_eq!(buffer.push_back_overwrite(v), over);
+                }
+                5 => {
+                    model.push_front(v);
+                    let over = if full { model.pop_back() } else { None };
+                    assert_eq!(buffer.push_front_overwrite(v), over);
+                }
+                6 => assert_eq!(buffer.pop_front(), model.pop_front()),
+                7 => assert_eq!(buffer.pop_back(), model.pop_back()),
+                8 => {
+                    let i = rng.gen_range(model.len() + 1);
+                    assert_eq!(buffer.get(i), model.get(i));
+                    if let Some(x) = buffer.get_mut(i) {
+                        *x = v;
+                        model[i] = v;
+                        assert_eq!(buffer[i], v);
+                    }
+                }
+                9 => {
+                    if let (Some(x), Some(y)) = (buffer.front_mut(), model.front_mut()) {
+                        *x ^= v;
+                        *y ^= v;
+                    }
+                    if let (Some(x), Some(y)) = (buffer.back_mut(), model.back_mut()) {
+                        *x = x.wrapping_add(v);
+                        *y = y.wrapping_add(v);
+                    }
+                }
+                10 => {
+                    assert!(buffer.make_contiguous().iter().eq(model.iter()));
+                    assert!(buffer.as_slices().1.is_empty());
+                }
+                11 => {
+                    let len = rng.gen_range(model.len() + 2);
+                    buffer.truncate(len);
+                    model.truncate(len);
+                }
+                12 => {
+                    buffer.iter_mut().for_each(|x| *x = x.rotate_left(1));
+                    model.iter_mut().for_each(|x| *x = x.rotate_left(1));
+                }
+                // extending can only panic once bounded
+                _ if bound.is_none() => {
+                    let more: Vec<u64> = (0..rng.gen_range(10)).map(|_| rng.next_u64()).collect();
+                    buffer.extend(more.iter().copied());
+                    model.extend(more);
+                }
+                _ => {}
+            }
+            check_ring(&buffer, &model);
+            if bound.is_some() {
+                assert_eq!(buffer.capacity(), capacity);
+            }
+        }
+        let copy = buffer.clone();
+        assert_eq!(copy, buffer);
+        assert_eq!(
+            (copy.is_bounded(), copy.capacity()),
+            (buffer.is_bounded(), buffer.capacity())
+        );
+        let mut back = copy.clone().into_iter();
+        assert_eq!(back.len(), model.len());
+        assert_eq!(back.next_back(), model.back().copied());
+        assert!(copy.into_iter().eq(model.iter().copied()));
+        let collected: RingBuffer<u64> = model.iter().copied().collect();
+        assert_eq!(collected, buffer);
+        buffer.clear();
+        check_ring(&buffer, &VecDeque::new());
+    });
+}
+
+// every item dropped exactly once, by pops, overwrites, truncation, a
+// partly drained iterator and the buffer itself
+#[test]
+fn ring_buffer_drops_each_item_once() {
+    cases(1750, 100, |rng| {
+        let token = Rc::new(());
+        let mut buffer = if rng.gen_bool(0.5) {
+            RingBuffer::bounded(rng.gen_range(9))
+        } else {
+            RingBuffer::new()
+        };
+        let mut held = 0;
+        for _ in 0..rng.gen_range(300) {
+            match rng.gen_range(6) {
+                0 | 1 => held += buffer.push_back(token.clone()).is_ok() as usize,
+                2 => {
+                    buffer.push_front_overwrite(token.clone());
+                    held = buffer.len();
+                }
+                3 => held -= buffer.pop_front().is_some() as usize,
+                4 => {
+                    buffer.truncate(rng.gen_range(held + 1));
+                    held = buffer.len();
+                }
+                _ => {
+                    buffer.make_contiguous();
+                }
+            }
+            assert_eq!(Rc::strong_count(&token), 1 + held);
+        }
+        let mut rest = buffer.clone().into_iter();
+        rest.next();
+        drop(rest);
+        assert_eq!(Rc::strong_count(&token), 1 + held);
+        drop(buffer);
+        assert_eq!(Rc::strong_count(&token), 1);
+    });
+}
+
+fn check_list(list: &DoublyLinkedList<u64>, model: &[u64]) {
+    assert_eq!(list.len(), model.len());
+    assert_eq!(list.is_empty(), model.is_empty());
+    assert_eq!(list.front(), model.first());
+    assert_eq!(list.back(), model.last());
+    assert!(list.iter().eq(model.iter()));
+    assert!(list.iter().rev().eq(model.iter().rev()));
+    assert_eq!(list.iter().len(), model.len());
+    // a read only cursor walks round through the ghost both ways
+    let mut cursor = list.cursor_front();
+    for i in (0..model.len()).chain([model.len()]) {
+        assert_eq!(cursor.index(), (i < model.len()).then_some(i));
+        assert_eq!(cursor.current(), model.get(i));
+        cursor.move_next();
+    }
+    assert_eq!(cursor.current(), model.first());
+    let mut cursor = list.cursor_back();
+    for i in (0..model.len()).rev() {
+        assert_eq!(cursor.current(), model.get(i));
+        cursor.move_prev();
+    }
+    assert_eq!(cursor.index(), None);
+}
+
+fn some_list(rng: &mut SplitMix64) -> Vec<u64> {
+    (0..rng.gen_range(6)).map(|_| rng.next_u64()).collect()
+}
+
+// A mutable cursor against a position in a Vec, len standing for the
+// ghost.
+fn cursor_trace(list: &mut DoublyLinkedList<u64>, model: &mut Vec<u64>, rng: &mut SplitMix64) {
+    let (mut at, mut cursor) = if rng.gen_bool(0.5) {
+        (0, list.cursor_front_mut())
+    } else {
+        (model.len().saturating_sub(1), list.cursor_back_mut())
+    };
+    for _ in 0..rng.gen_range(40) {
+        let n = model.len();
+        let ghost = at == n;
+        let v = rng.next_u64();
+        match rng.gen_range(12) {
+            0 | 1 => {
+                cursor.move_next();
+                at = if ghost { 0 } else { at + 1 };
+            }
+            2 | 3 => {
+                cursor.move_prev();
+                at = match at {
+                    _ if ghost => n.saturating_sub(1),
+                    0 => n,
+                    _ => at - 1,
+                };
+            }
+            4 => {
+                cursor.insert_after(v);
+                if ghost {
+                    model.insert(0, v);
+                    at += 1;
+                } else {
+                    model.insert(at + 1, v);
+                }
+            }
+            5 => {
+                cursor.insert_before(v);
+                model.insert(at, v);
+                at += 1;
+            }
+            6 | 7 => {
+                let removed = (!ghost).then(|| model.remove(at));
+                assert_eq!(cursor.remove_current(), removed);
+            }
+            8 => {
+                let other = some_list(rng);
+                cursor.splice_after(other.iter().copied().collect());
+                let to = if ghost { 0 } else { at + 1 };
+                model.splice(to..to, other.iter().copied());
+                if ghost {
+                    at = model.len();
+                }
+            }
+            9 => {
+                let other = some_list(rng);
+                cursor.splice_before(other.iter().copied().collect());
+                model.splice(at..at, other.iter().copied());
+                at += other.len();
+            }
+            10 => {
+                let from = if ghost { 0 } else { at + 1 };
+                let tail = model.split_off(from);
+                assert!(cursor.split_after().iter().eq(tail.iter()));
+                at = model.len().min(at);
+            }
+            _ => {
+                if let Some(x) = cursor.current() {
+                    *x = v;
+                    model[at] = v;
+                }
+            }
+        }
+        let n = model.len();
+        assert_eq!(cursor.index(), (at < n).then_some(at));
+        assert_eq!(cursor.current().copied(), model.get(at).copied());
+        let next = if at == n {
+            model.first()
+        } else {
+            model.get(at + 1)
+        };
+        assert_eq!(cursor.peek_next().copied(), next.copied());
+        let prev = if at == n {
+            model.last()
+        } else {
+            at.checked_sub(1).map(|i| &model[i])
+        };
+        assert_eq!(cursor.peek_prev().copied(), prev.copied());
+        assert_eq!(cursor.as_list().len(), n);
+    }
+}
+
+// list operations and cursor runs, with the list cloned, collected and
+// appended to along the way
+#[test]
+fn linked_list_matches_vec() {
+    cases(176, 300, |rng| {
+        let mut model = some_list(rng);
+        let mut list: DoublyLinkedList<u64> = model.iter().copied().collect();
+        for _ in 0..rng.gen_range(100) {
+            let v = rng.next_u64();
+            match rng.gen_range(10) {
+                0 => {
+                    list.push_front(v);
+                    model.insert(0, v);
+                }
+                1 => {
+                    list.push_back(v);
+                    model.push(v);
+                }
+                2 => assert_eq!(
+                    list.pop_front(),
+                    (!model.is_empty()).then(|| model.remove(0))
+                ),
+                3 => assert_eq!(list.pop_back(), model.pop()),
+                4 => {
+                    if let (Some(x), Some(y)) = (list.front_mut(), model.first_mut()) {
+                        *x = v;
+                        *y = v;
+                    }
+                    if let (Some(x), Some(y)) = (list.back_mut(), model.last_mut()) {
+                        *x ^= v;
+                        *y ^= v;
+                    }
+                }
+                5 => {
+                    let mut other: DoublyLinkedList<u64> = some_list(rng).into_iter().collect();
+                    model.extend(other.iter().copied());
+                    list.append(&mut other);
+                    assert!(other.is_empty());
+                }
+                6 => {
+                    let x = model
+                        .get(rng.gen_range(model.len() + 1))
+                        .copied()
+                        .unwrap_or(v);
+                    assert_eq!(list.contains(&x), model.contains(&x));
+                }
+                7 => {
+                    // the copy is compacted, and must behave the same
+                    list = list.clone();
+                }
+                _ => cursor_trace(&mut list, &mut model, rng),
+            }
+            check_list(&list, &model);
+        }
+        let mut copy = list.clone();
+        assert_eq!(copy, list);
+        copy.extend([1, 2]);
+        assert_ne!(copy, list);
+        assert!(list
+            .clone()
+            .into_iter()
+            .rev()
+            .eq(model.iter().rev().copied()));
+        assert!(list.into_iter().eq(model.into_iter()));
+        copy.clear();
+        check_list(&copy, &[]);
+    });
+}
@@ -0,0 +1,17 @@
+// Shared by the seeded test files: runs a property once per seed and
+// reports the seed that failed, so it can be replayed with
+// `cases(seed, 1, ..)`.
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use graph::rng::SplitMix64;
+
+pub fn cases(first_seed: u64, count: u64, property: impl Fn(&mut SplitMix64)) {
+    for seed in first_seed..first_seed + count {
+        let mut rng = SplitMix64::new(seed);
+        if let Err(e) = catch_unwind(AssertUnwindSafe(|| property(&mut rng))) {
+            eprintln!("property failed for seed {seed}");
+            resume_unwind(e);
+        }
+    }
+}
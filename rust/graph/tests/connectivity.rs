@@ -0,0 +1,296 @@
+// The connectivity structures against a breadth first search over the
+// edges a plain Vec holds at that moment, on few vertices so that links,
+// cuts and unions keep meeting.
+
+mod common;
+
+use std::collections::VecDeque;
+
+use graph::dynamic_connectivity::{offline_connectivity, Answer, Op};
+use graph::dynamic_forest::DynamicForest;
+use graph::rng::{Rng, SplitMix64};
+use graph::union_find::{KeyedUnionFind, RollbackUnionFind, UnionFind};
+
+use common::cases;
+
+// every vertex labelled by the smallest one it can reach
+fn components(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut adj = vec![Vec::new(); n];
+    for &(u, v) in edges {
+        adj[u].push(v);
+        adj[v].push(u);
+    }
+    let mut label = vec![usize::MAX; n];
+    for s in 0..n {
+        if label[s] != usize::MAX {
+            continue;
+        }
+        label[s] = s;
+        let mut queue = VecDeque::from([s]);
+        while let Some(u) = queue.pop_front() {
+            for &v in &adj[u] {
+                if label[v] == usize::MAX {
+                    label[v] = s;
+                    queue.push_back(v);
+                }
+            }
+        }
+    }
+    label
+}
+
+fn count(label: &[usize]) -> usize {
+    label.iter().enumerate().filter(|&(v, &l)| v == l).count()
+}
+
+fn some_pair(n: usize, rng: &mut SplitMix64) -> (usize, usize) {
+    (rng.gen_range(n), rng.gen_range(n))
+}
+
+#[test]
+fn dynamic_forest_matches_search() {
+    cases(127, 300, |rng| {
+        let n = 1 + rng.gen_range(40);
+        let mut forest = DynamicForest::new(n);
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        assert_eq!(forest.len(), n);
+        for _ in 0..rng.gen_range(500) {
+            let (u, v) = some_pair(n, rng);
+            let label = components(n, &edges);
+            let has = edges.iter().any(|&e| e == (u, v) || e == (v, u));
+            match rng.gen_range(10) {
+                0..=3 => {
+                    let linked = forest.link(u, v);
+                    assert_eq!(linked, label[u] != label[v], "link {u}-{v}");
+                    if linked {
+                        edges.push((u, v));
+                    }
+                }
+                // mostly the edges there are, or cuts would seldom land
+                4..=6 if !edges.is_empty() => {
+                    let (a, b) = edges.swap_remove(rng.gen_range(edges.len()));
+                    let (a, b) = if rng.gen_bool(0.5) { (a, b) } else { (b, a) };
+                    assert!(forest.cut(a, b), "cut {a}-{b}");
+                }
+                7 => {
+                    assert_eq!(forest.cut(u, v), has);
+                    edges.retain(|&e| e != (u, v) && e != (v, u));
+                }
+                8 => assert_eq!(forest.has_edge(u, v), has),
+                _ => assert_eq!(forest.connected(u, v), label[u] == label[v]),
+            }
+            assert_eq!(forest.edge_count(), edges.len());
+            assert_eq!(forest.component_count(), count(&components(n, &edges)));
+        }
+        let label = components(n, &edges);
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(forest.connected(u, v), label[u] == label[v]);
+            }
+        }
+    });
+}
+
+// a path cut and relinked at random places, long enough that splaying
+// has real depth to undo
+#[test]
+fn dynamic_forest_on_long_paths() {
+    cases(1270, 20, |rng| {
+        let n = 2000;
+        let mut forest = DynamicForest::new(n);
+        for v in 1..n {
+            assert!(forest.link(v - 1, v));
+        }
+        for _ in 0..200 {
+            let v = 1 + rng.gen_range(n - 1);
+            assert!(forest.cut(v - 1, v));
+            let (a, b) = some_pair(n, rng);
+            let apart = (a < v) != (b < v);
+            assert_eq!(forest.connected(a, b), !apart);
+            assert!(forest.link(v, v - 1));
+            assert!(forest.connected(a, b));
+        }
+        assert_eq!((forest.edge_count(), forest.component_count()), (n - 1, 1));
+    });
+}
+
+// the sets of a labelling, each in increasing order, by smallest element
+fn groups(label: &[usize]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (v, &l) in label.iter().enumerate() {
+        match groups.iter_mut().find(|g| g[0] == l) {
+            Some(g) => g.push(v),
+            None => groups.push(vec![v]),
+        }
+    }
+    groups
+}
+
+#[test]
+fn union_find_matches_search() {
+    cases(131, 300, |rng| {
+        let mut n = rng.gen_range(50);
+        let mut sets = UnionFind::new(n);
+        let mut edges = Vec::new();
+        for _ in 0..rng.gen_range(300) {
+            if n == 0 || rng.gen_bool(0.05) {
+                assert_eq!(sets.push(), n);
+                n += 1;
+                continue;
+            }
+            let (u, v) = some_pair(n, rng);
+            let label = components(n, &edges);
+            if rng.gen_bool(0.5) {
+                assert_eq!(sets.union(u, v), label[u] != label[v]);
+                edges.push((u, v));
+            } else {
+                assert_eq!(sets.connected(u, v), label[u] == label[v]);
+                assert_eq!(sets.find(u) == sets.find(v), label[u] == label[v]);
+            }
+            assert_eq!(sets.len(), n);
+            assert_eq!(sets.set_count(), count(&components(n, &edges)));
+        }
+        assert_eq!(sets.groups(), groups(&components(n, &edges)));
+    });
+}
+
+// keys numbered as first seen, so the groups follow that order too
+#[test]
+fn keyed_union_find_matches_search() {
+    cases(1310, 300, |rng| {
+        let mut sets = KeyedUnionFind::new();
+        let mut keys: Vec<String> = Vec::new();
+        let mut edges = Vec::new();
+        let id = |keys: &mut Vec<String>, key: &String| match keys.iter().position(|k| k == key) {
+            Some(i) => i,
+            None => {
+                keys.push(key.clone());
+                keys.len() - 1
+            }
+        };
+        for _ in 0..rng.gen_range(300) {
+            let (a, b) = (
+                format!("k{}", rng.gen_range(40)),
+                format!("k{}", rng.gen_range(40)),
+            );
+            match rng.gen_range(4) {
+                0 => {
+                    assert_eq!(sets.insert(&a), !keys.contains(&a));
+                    id(&mut keys, &a);
+                }
+                1 | 2 => {
+                    let (i, j) = (id(&mut keys, &a), id(&mut keys, &b));
+                    let label = components(keys.len(), &edges);
+                    assert_eq!(sets.union(&a, &b), label[i] != label[j]);
+                    edges.push((i, j));
+                }
+                _ => {
+                    let known = |k: &String| keys.iter().position(|x| x == k);
+                    let label = components(keys.len(), &edges);
+                    let expected = match (known(&a), known(&b)) {
+                        (Some(i), Some(j)) => label[i] == label[j],
+                        _ => false,
+                    };
+                    assert_eq!(sets.connected(&a, &b), expected);
+                    let root = sets.find(&a).cloned();
+                    assert_eq!(root.is_some(), known(&a).is_some());
+                    if let (Some(root), Some(i)) = (root, known(&a)) {
+                        assert_eq!(label[known(&root).unwrap()], label[i]);
+                    }
+                }
+            }
+            assert_eq!(sets.len(), keys.len());
+            assert!(keys.iter().all(|k| sets.contains(k)));
+            assert_eq!(sets.set_count(), count(&components(keys.len(), &edges)));
+        }
+        let expected: Vec<Vec<String>> = groups(&components(keys.len(), &edges))
+            .into_iter()
+            .map(|g| g.into_iter().map(|i| keys[i].clone()).collect())
+            .collect();
+        assert_eq!(sets.groups(), expected);
+    });
+}
+
+// a rollback drops the unions made since its snapshot, failed ones
+// included, which left no history
+#[test]
+fn rollback_union_find_matches_search() {
+    cases(132, 300, |rng| {
+        let n = 1 + rng.gen_range(40);
+        let mut sets = RollbackUnionFind::new(n);
+        let mut edges = Vec::new();
+        // (snapshot, edges made by then)
+        let mut snapshots = vec![(sets.snapshot(), 0)];
+        for _ in 0..rng.gen_range(400) {
+            let (u, v) = some_pair(n, rng);
+            let label = components(n, &edges);
+            match rng.gen_range(10) {
+                0..=4 => {
+                    assert_eq!(sets.union(u, v), label[u] != label[v]);
+                    edges.push((u, v));
+                }
+                5 => snapshots.push((sets.snapshot(), edges.len())),
+                6 => {
+                    let at = rng.gen_range(snapshots.len());
+                    let (snapshot, made) = snapshots[at];
+                    sets.rollback(snapshot);
+                    edges.truncate(made);
+                    snapshots.truncate(at + 1);
+                }
+                _ => {
+                    assert_eq!(sets.connected(u, v), label[u] == label[v]);
+                    let size = label.iter().filter(|&&l| l == label[u]).count();
+                    assert_eq!(sets.set_size(u), size);
+                }
+            }
+            assert_eq!(sets.set_count(), count(&components(n, &edges)));
+        }
+        sets.rollback(0);
+        assert_eq!(sets.set_count(), n);
+    });
+}
+
+// replayed in order on a multiset of edges, every copy counted
+#[test]
+fn offline_connectivity_matches_search() {
+    cases(1320, 300, |rng| {
+        let n = 1 + rng.gen_range(30);
+        let mut ops = Vec::new();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut expected = Vec::new();
+        for _ in 0..rng.gen_range(400) {
+            let (u, v) = some_pair(n, rng);
+            match rng.gen_range(10) {
+                0..=3 => {
+                    ops.push(Op::Add(u, v));
+                    edges.push((u, v));
+                }
+                4 | 5 if !edges.is_empty() => {
+                    // a copy there is, named either way round
+                    let (a, b) = edges[rng.gen_range(edges.len())];
+                    let (a, b) = if rng.gen_bool(0.5) { (a, b) } else { (b, a) };
+                    ops.push(Op::Remove(a, b));
+                    let at = edges.iter().position(|&e| e == (a, b) || e == (b, a));
+                    edges.swap_remove(at.unwrap());
+                }
+                6 => {
+                    // often one that isn't there, to be ignored
+                    ops.push(Op::Remove(u, v));
+                    if let Some(at) = edges.iter().position(|&e| e == (u, v) || e == (v, u)) {
+                        edges.swap_remove(at);
+                    }
+                }
+                7 => {
+                    ops.push(Op::Components);
+                    expected.push(Answer::Components(count(&components(n, &edges))));
+                }
+                _ => {
+                    ops.push(Op::Connected(u, v));
+                    let label = components(n, &edges);
+                    expected.push(Answer::Connected(label[u] == label[v]));
+                }
+            }
+        }
+        assert_eq!(offline_connectivity(n, &ops), expected);
+    });
+}
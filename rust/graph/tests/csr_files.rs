@@ -0,0 +1,59 @@
+// Graph files round trip through save, load and map, and files whose
+// edges point past the last vertex are rejected at load time.
+
+use std::{fs, io, path::PathBuf};
+
+use graph::csr::CsrGraph;
+use graph::rng::{Rng, SplitMix64};
+
+const HEADER_LEN: usize = 32;
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("csr-files-{}-{name}", std::process::id()))
+}
+
+fn random_graph(rng: &mut SplitMix64) -> CsrGraph {
+    let n = 1 + rng.gen_range(200);
+    let edges: Vec<_> = (0..rng.gen_range(4 * n))
+        .map(|_| (rng.gen_range(n), rng.gen_range(n), rng.next_u64() as i32))
+        .collect();
+    CsrGraph::from_edges(n, &edges)
+}
+
+#[test]
+fn save_load_and_map_round_trip() {
+    let mut rng = SplitMix64::new(121);
+    let path = temp_file("round-trip");
+    for _ in 0..50 {
+        let g = random_graph(&mut rng);
+        g.save(&path).unwrap();
+        assert_eq!(CsrGraph::load(&path).unwrap(), g);
+        // nothing writes the file while the map is alive
+        assert_eq!(unsafe { CsrGraph::map(&path) }.unwrap(), g);
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn out_of_range_targets_are_rejected() {
+    let mut rng = SplitMix64::new(1210);
+    let path = temp_file("bad-target");
+    for _ in 0..50 {
+        let g = random_graph(&mut rng);
+        let (n, m) = (g.vertex_count(), g.edge_count());
+        if m == 0 {
+            continue;
+        }
+        g.save(&path).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        let at = HEADER_LEN + 8 * (n + 1) + 8 * rng.gen_range(m);
+        let target = (n + rng.gen_range(3)) as u64;
+        bytes[at..at + 8].copy_from_slice(&target.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        let err = CsrGraph::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = unsafe { CsrGraph::map(&path) }.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,215 @@
+// Exact cover against counts known from the literature, n-queens, 4 by 4
+// sudoku and the 5 by 12 pentomino rectangle, and against trying every
+// subset of the rows of small random matrices.
+
+mod common;
+
+use std::collections::BTreeSet;
+
+use graph::exact_cover::{
+    polyomino_cover, solve_sudoku, sudoku_cover, tile_polyominoes, ExactCover, Placement, Polyomino,
+};
+use graph::rng::Rng;
+
+use common::cases;
+
+// a queen per row and column, the diagonals at most once
+fn queens(n: usize) -> ExactCover<(usize, usize)> {
+    let mut cover = ExactCover::with_secondary(2 * n, 2 * (2 * n - 1));
+    for r in 0..n {
+        for c in 0..n {
+            let diagonal = 2 * n + r + c;
+            let anti = 2 * n + (2 * n - 1) + (r + n - 1 - c);
+            cover.add_row((r, c), [r, n + c, diagonal, anti]);
+        }
+    }
+    cover
+}
+
+#[test]
+fn n_queens_counts() {
+    let known = [1, 0, 0, 2, 10, 4, 40, 92];
+    for (n, &count) in (1..).zip(known.iter()) {
+        let cover = queens(n);
+        assert_eq!(cover.count(usize::MAX), count, "{n} queens");
+        for solution in cover.all() {
+            assert_eq!(solution.len(), n);
+            for (i, &&(r, c)) in solution.iter().enumerate() {
+                for &&(s, d) in &solution[..i] {
+                    assert!(r != s && c != d && r + d != s + c && r + c != s + d);
+                }
+            }
+        }
+    }
+}
+
+fn valid_sudoku(grid: &[Vec<u8>], box_rows: usize, box_cols: usize) -> bool {
+    let n = box_rows * box_cols;
+    let full: BTreeSet<u8> = (1..=n as u8).collect();
+    let rows = (0..n).all(|r| grid[r].iter().copied().collect::<BTreeSet<_>>() == full);
+    let cols = (0..n).all(|c| (0..n).map(|r| grid[r][c]).collect::<BTreeSet<_>>() == full);
+    let boxes = (0..n).all(|b| {
+        let (r0, c0) = (b / box_rows * box_rows, b % box_rows * box_cols);
+        let cells = (0..n).map(|i| grid[r0 + i / box_cols][c0 + i % box_cols]);
+        cells.collect::<BTreeSet<_>>() == full
+    });
+    rows && cols && boxes
+}
+
+#[test]
+fn sudoku_4_by_4_has_288_grids() {
+    let cover = sudoku_cover(&vec![vec![0; 4]; 4], 2, 2);
+    let all = cover.all();
+    assert_eq!(all.len(), 288);
+    let grids: BTreeSet<Vec<Vec<u8>>> = all
+        .iter()
+        .map(|solution| {
+            let mut grid = vec![vec![0; 4]; 4];
+            for &&(r, c, d) in solution {
+                grid[r][c] = d;
+            }
+            assert!(valid_sudoku(&grid, 2, 2), "{grid:?}");
+            grid
+        })
+        .collect();
+    assert_eq!(grids.len(), 288);
+    // a clue cuts the grids by the digit's share, a second clash to none
+    let mut clued = vec![vec![0; 4]; 4];
+    clued[0][0] = 1;
+    assert_eq!(sudoku_cover(&clued, 2, 2).count(usize::MAX), 72);
+    clued[0][3] = 1;
+    assert_eq!(sudoku_cover(&clued, 2, 2).count(usize::MAX), 0);
+}
+
+#[test]
+fn sudoku_9_by_9_is_filled_in() {
+    let puzzle = [
+        [5, 3, 0, 0, 7, 0, 0, 0, 0],
+        [6, 0, 0, 1, 9, 5, 0, 0, 0],
+        [0, 9, 8, 0, 0, 0, 0, 6, 0],
+        [8, 0, 0, 0, 6, 0, 0, 0, 3],
+        [4, 0, 0, 8, 0, 3, 0, 0, 1],
+        [7, 0, 0, 0, 2, 0, 0, 0, 6],
+        [0, 6, 0, 0, 0, 0, 2, 8, 0],
+        [0, 0, 0, 4, 1, 9, 0, 0, 5],
+        [0, 0, 0, 0, 8, 0, 0, 7, 9],
+    ];
+    let solved = solve_sudoku(&puzzle).unwrap();
+    let grid: Vec<Vec<u8>> = solved.iter().map(|row| row.to_vec()).collect();
+    assert!(valid_sudoku(&grid, 3, 3));
+    for r in 0..9 {
+        for c in 0..9 {
+            assert!(puzzle[r][c] == 0 || puzzle[r][c] == solved[r][c]);
+        }
+    }
+    let rows: Vec<Vec<u8>> = puzzle.iter().map(|row| row.to_vec()).collect();
+    assert_eq!(sudoku_cover(&rows, 3, 3).count(2), 1);
+    let mut clash = puzzle;
+    clash[0][2] = 5;
+    assert_eq!(solve_sudoku(&clash), None);
+}
+
+// every open cell covered once, every piece used once, in its own shape
+fn check_tiling(board: &[Vec<bool>], pieces: &[Polyomino], tiling: &[Placement]) {
+    let mut covered = vec![vec![false; board[0].len()]; board.len()];
+    let used: BTreeSet<usize> = tiling.iter().map(|p| p.piece).collect();
+    assert_eq!(used.len(), pieces.len());
+    for placement in tiling {
+        let shape = Polyomino::new(placement.cells.iter().map(|&(r, c)| (r as i32, c as i32)));
+        assert!(pieces[placement.piece].orientations(true).contains(&shape));
+        for &(r, c) in &placement.cells {
+            assert!(board[r][c] && !covered[r][c]);
+            covered[r][c] = true;
+        }
+    }
+    assert_eq!(covered, board);
+}
+
+fn count_tilings(rows: usize, cols: usize) -> usize {
+    let board = vec![vec![true; cols]; rows];
+    let pieces = Polyomino::pentominoes();
+    let tiling = tile_polyominoes(&board, &pieces, true).unwrap();
+    check_tiling(&board, &pieces, &tiling);
+    polyomino_cover(&board, &pieces, true).count(usize::MAX)
+}
+
+// the two tilings of 3 by 20, each in the rectangle's four symmetries
+#[test]
+fn pentominoes_tile_3_by_20_8_ways() {
+    let pieces = Polyomino::pentominoes();
+    assert_eq!(pieces.len(), 12);
+    assert!(pieces.iter().all(|p| p.len() == 5));
+    assert_eq!(count_tilings(3, 20), 8);
+}
+
+// 1010 up to symmetry; about a minute unoptimised, so left to --release
+#[test]
+#[cfg_attr(debug_assertions, ignore = "slow unoptimised, run with --release")]
+fn pentominoes_tile_5_by_12_4040_ways() {
+    assert_eq!(count_tilings(5, 12), 4040);
+}
+
+// Counts from every subset of the rows, each solution checked to cover
+// as it should and none found twice.
+#[test]
+fn random_covers_match_every_subset() {
+    cases(184, 300, |rng| {
+        let (primary, secondary) = (rng.gen_range(7), rng.gen_range(3));
+        let columns = primary + secondary;
+        let mut cover = ExactCover::with_secondary(primary, secondary);
+        let mut rows: Vec<u32> = Vec::new();
+        for i in 0..rng.gen_range(13) {
+            // repeats among the columns, which count once
+            let picked: Vec<usize> = (0..rng.gen_range(4))
+                .map(|_| rng.gen_range(columns.max(1)))
+                .filter(|_| columns > 0)
+                .collect();
+            rows.push(picked.iter().fold(0, |m, &c| m | 1 << c));
+            cover.add_row(i as u32, picked);
+        }
+        assert_eq!((cover.rows(), cover.columns()), (rows.len(), columns));
+        // rows without a primary column never join a solution
+        let takes_part = |&m: &u32| m & ((1 << primary) - 1) != 0;
+        let mut expected = BTreeSet::new();
+        for subset in 0u32..1 << rows.len() {
+            let picked: Vec<u32> = (0..rows.len() as u32)
+                .filter(|&i| subset >> i & 1 == 1)
+                .collect();
+            let masks: Vec<u32> = picked.iter().map(|&i| rows[i as usize]).collect();
+            let union = masks.iter().fold(0, |a, &m| a | m);
+            let disjoint = masks.iter().map(|m| m.count_ones()).sum::<u32>() == union.count_ones();
+            if disjoint
+                && union & ((1 << primary) - 1) == (1 << primary) - 1
+                && masks.iter().all(takes_part)
+            {
+                expected.insert(picked);
+            }
+        }
+        let found: Vec<Vec<u32>> = cover
+            .all()
+            .into_iter()
+            .map(|solution| {
+                let mut ids: Vec<u32> = solution.into_iter().copied().collect();
+                ids.sort_unstable();
+                ids
+            })
+            .collect();
+        assert_eq!(found.len(), expected.len(), "{rows:?}");
+        assert_eq!(found.into_iter().collect::<BTreeSet<_>>(), expected);
+        let limit = rng.gen_range(4);
+        assert_eq!(cover.count(limit), expected.len().min(limit));
+        let first = cover.first();
+        assert_eq!(first.is_some(), !expected.is_empty());
+        if let Some(first) = first {
+            let mut ids: Vec<u32> = first.into_iter().copied().collect();
+            ids.sort_unstable();
+            assert!(expected.contains(&ids));
+        }
+        for (i, &mask) in rows.iter().enumerate() {
+            let (&label, cols) = cover.row(i);
+            assert_eq!(label, i as u32);
+            assert_eq!(cols.iter().fold(0, |m, &c| m | 1 << c), mask);
+            assert!(cols.windows(2).all(|w| w[0] < w[1]));
+        }
+    });
+}
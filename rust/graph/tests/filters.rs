@@ -0,0 +1,404 @@
+// The probabilistic filters and sketches against exact models in a BTreeSet
+// or HashMap: they may err in their documented direction only, no false
+// negatives and no underestimates, and stay near their error bounds.
+
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use graph::bloom_filter::BloomFilter;
+use graph::count_min::{CountMinSketch, HeavyHitters};
+use graph::cuckoo_filter::CuckooFilter;
+use graph::hyperloglog::HyperLogLog;
+use graph::minhash::{LshIndex, MinHash, Signature};
+use graph::rng::{Rng, SplitMix64, Zipf};
+
+use common::cases;
+
+// ordered, so a case replays from its seed alone
+fn distinct(n: usize, rng: &mut SplitMix64) -> BTreeSet<u64> {
+    let mut set = BTreeSet::new();
+    while set.len() < n {
+        set.insert(rng.next_u64());
+    }
+    set
+}
+
+#[test]
+fn bloom_has_no_false_negatives() {
+    cases(164, 100, |rng| {
+        let n = 1 + rng.gen_range(2000);
+        let p = [0.1, 0.01, 0.001][rng.gen_range(3)];
+        let mut filter = BloomFilter::new(n, p);
+        let items = distinct(n, rng);
+        for x in items.iter() {
+            filter.insert(x);
+        }
+        assert!(items.iter().all(|x| filter.contains(x)));
+        // nothing inserted is surely new
+        assert!(items.iter().all(|x| !filter.insert(x)));
+        let (bits, hashes, seed) = (filter.num_bits(), filter.num_hashes(), filter.seed());
+        let mut other = BloomFilter::with_params(bits, hashes, seed);
+        let shared: Vec<u64> = items
+            .iter()
+            .copied()
+            .filter(|_| rng.gen_bool(0.5))
+            .collect();
+        let fresh = distinct(rng.gen_range(n), rng);
+        for x in shared.iter().chain(&fresh) {
+            other.insert(x);
+        }
+        let mut union = filter.clone();
+        union.union_with(&other).unwrap();
+        assert!(items.iter().chain(&fresh).all(|x| union.contains(x)));
+        let mut both = filter.clone();
+        both.intersect_with(&other).unwrap();
+        assert!(shared.iter().all(|x| both.contains(x)));
+        let stranger = BloomFilter::with_params(bits, hashes, seed + 1);
+        assert!(filter.union_with(&stranger).is_err());
+        filter.clear();
+        assert!(filter.is_empty());
+        assert!(!items.iter().any(|x| filter.contains(x)));
+    });
+}
+
+#[test]
+fn bloom_false_positives_near_target() {
+    let mut rng = SplitMix64::new(1640);
+    let n = 10_000;
+    for p in [0.05, 0.01] {
+        let mut filter = BloomFilter::new(n, p);
+        let items = distinct(n, &mut rng);
+        filter.extend(items.iter().copied());
+        assert!((filter.estimated_len() - n as f64).abs() < 0.05 * n as f64);
+        let probes = 200_000;
+        let passed = (0..probes)
+            .map(|_| rng.next_u64())
+            .filter(|x| !items.contains(x) && filter.contains(x))
+            .count();
+        let rate = passed as f64 / probes as f64;
+        assert!(rate < 1.5 * p, "false positive rate {rate} for {p}");
+    }
+}
+
+// A multiset model, removes only of items inserted. An item's two
+// buckets hold eight fingerprints, so none goes in more than twice. Pool
+// and model are ordered, the removes at the end too, so a failing case
+// replays from its seed.
+#[test]
+fn cuckoo_filter_has_no_false_negatives() {
+    cases(165, 100, |rng| {
+        let capacity = 1 + rng.gen_range(2000);
+        let mut filter = CuckooFilter::new(capacity);
+        let pool: Vec<u64> = distinct(1 + rng.gen_range(2 * capacity), rng)
+            .into_iter()
+            .collect();
+        let mut model: BTreeMap<u64, usize> = BTreeMap::new();
+        for _ in 0..3 * capacity {
+            let x = pool[rng.gen_range(pool.len())];
+            let copies = model.get(&x).copied().unwrap_or(0);
+            if rng.gen_bool(0.6) && copies < 2 && filter.len() < capacity * 9 / 10 {
+                filter.insert(&x).unwrap();
+                *model.entry(x).or_default() += 1;
+            } else if let Some(count) = model.get_mut(&x) {
+                assert!(filter.remove(&x));
+                *count -= 1;
+                if *count == 0 {
+                    model.remove(&x);
+                }
+            }
+            assert_eq!(filter.len(), model.values().sum::<usize>());
+        }
+        assert!(model.keys().all(|x| filter.contains(x)));
+        for (x, count) in std::mem::take(&mut model) {
+            for _ in 0..count {
+                assert!(filter.remove(&x));
+            }
+        }
+        assert!(filter.is_empty());
+        assert!(!pool.iter().any(|x| filter.contains(x)));
+    });
+}
+
+#[test]
+fn cuckoo_filter_fills_and_reports_full() {
+    let mut rng = SplitMix64::new(1650);
+    let mut filter = CuckooFilter::new(10_000);
+    let mut inserted = Vec::new();
+    for x in distinct(2 * filter.capacity(), &mut rng) {
+        if filter.insert(&x).is_err() {
+            break;
+        }
+        inserted.push(x);
+    }
+    assert!(
+        filter.load_factor() > 0.9,
+        "full at {}",
+        filter.load_factor()
+    );
+    assert!(inserted.iter().all(|x| filter.contains(x)));
+    let passed = (0..100_000)
+        .map(|_| rng.next_u64())
+        .filter(|x| filter.contains(x))
+        .count();
+    assert!(
+        passed < 100_000 * 16 / (1 << 16),
+        "{passed} false positives"
+    );
+}
+
+// A skewed stream counted exactly on the side: no estimate is low,
+// conservative ones are no higher, and few are off by more than epsilon.
+#[test]
+fn count_min_never_underestimates() {
+    cases(166, 40, |rng| {
+        let (epsilon, delta) = (0.001 + rng.next_f64() / 50.0, 0.01);
+        let mut plain = CountMinSketch::new(epsilon, delta);
+        let mut conservative = CountMinSketch::new(epsilon, delta).conservative(true);
+        let zipf = Zipf::new(1 + rng.gen_range(5000), rng.next_f64() * 1.5);
+        let mut model: HashMap<usize, u64> = HashMap::new();
+        for _ in 0..rng.gen_range(20_000) {
+            let x = zipf.sample(rng);
+            let count = 1 + rng.gen_range(3) as u64;
+            plain.add(&x, count);
+            conservative.add(&x, count);
+            *model.entry(x).or_default() += count;
+        }
+        let total: u64 = model.values().sum();
+        assert_eq!(plain.total(), total);
+        assert_eq!(conservative.total(), total);
+        let mut far = 0;
+        for (x, &count) in model.iter() {
+            let (p, c) = (plain.estimate(x), conservative.estimate(x));
+            assert!(count <= c && c <= p, "{x}: true {count}, {c} and {p}");
+            if (p - count) as f64 > epsilon * total as f64 {
+                far += 1;
+            }
+        }
+        assert!(
+            far as f64 <= 2.0 * delta * model.len() as f64 + 2.0,
+            "{far} far off"
+        );
+        plain.clear();
+        assert_eq!(plain.total(), 0);
+        assert!(model.keys().all(|x| plain.estimate(x) == 0));
+    });
+}
+
+#[test]
+fn count_min_merge_adds_streams() {
+    cases(1660, 40, |rng| {
+        let (width, depth, seed) = (1 + rng.gen_range(200), 1 + rng.gen_range(6), rng.next_u64());
+        let mut both = CountMinSketch::with_dims(width, depth, seed);
+        let mut halves = [
+            CountMinSketch::with_dims(width, depth, seed),
+            CountMinSketch::with_dims(width, depth, seed),
+        ];
+        for _ in 0..rng.gen_range(2000) {
+            let x = rng.gen_range(1000);
+            both.increment(&x);
+            halves[rng.gen_range(2)].increment(&x);
+        }
+        let [mut merged, other] = halves;
+        merged.merge(&other).unwrap();
+        assert_eq!(merged.total(), both.total());
+        assert!((0..1000).all(|x| merged.estimate(&x) == both.estimate(&x)));
+        let mut stranger = CountMinSketch::with_dims(width, depth, seed ^ 1);
+        assert!(stranger.merge(&both).is_err());
+        let mut taller = CountMinSketch::with_dims(width, depth + 1, seed);
+        assert!(taller.merge(&both).is_err());
+    });
+}
+
+#[test]
+fn heavy_hitters_keep_the_frequent_items() {
+    cases(16600, 20, |rng| {
+        let k = 1 + rng.gen_range(10);
+        let mut hitters = HeavyHitters::new(k, CountMinSketch::new(0.001, 0.01));
+        let zipf = Zipf::new(10_000, 1.0 + rng.next_f64());
+        let mut model: HashMap<usize, u64> = HashMap::new();
+        for _ in 0..20_000 {
+            let x = zipf.sample(rng);
+            hitters.add(x, 1);
+            *model.entry(x).or_default() += 1;
+        }
+        let top = hitters.top();
+        assert!(top.len() <= k);
+        assert!(top.windows(2).all(|w| w[0].1 >= w[1].1));
+        // every item over a 1/k share is found, with an estimate not low
+        let share = 20_000 / k as u64;
+        for (x, &count) in model.iter().filter(|&(_, &c)| c > share) {
+            let found = top.iter().find(|(y, _)| y == x);
+            assert!(
+                found.is_some_and(|&(_, e)| e >= count),
+                "lost {x} at {count}"
+            );
+        }
+        let above = hitters.above(0.05);
+        assert!(above.iter().all(|&(_, e)| e >= 1000));
+        assert!(above.iter().all(|a| top.contains(a)));
+    });
+}
+
+// Distinct counts against a BTreeSet, repeats included in the stream. The
+// estimate's tail is heavier with fewer registers, so the bound widens at
+// low precision: 13 standard errors with 16 registers, 6 with 1024.
+#[test]
+fn hyperloglog_counts_near_a_set() {
+    cases(167, 60, |rng| {
+        let precision = 4 + rng.gen_range(11) as u32;
+        let mut hll = HyperLogLog::with_seed(precision, rng.next_u64());
+        let items: Vec<u64> = distinct(rng.gen_range(100_000), rng).into_iter().collect();
+        let mut model = BTreeSet::new();
+        for _ in 0..items.len() * 3 / 2 {
+            let x = items[rng.gen_range(items.len())];
+            let raised = hll.insert(&x);
+            if !model.insert(x) {
+                assert!(!raised, "a repeat of {x} raised a register");
+            }
+        }
+        let (n, estimate) = (model.len() as f64, hll.estimate());
+        let sigmas = 5.0 + 32.0 / f64::from(1 << precision).sqrt();
+        assert_eq!(hll.is_empty(), model.is_empty());
+        assert!(
+            (estimate - n).abs() <= sigmas * hll.relative_error() * n + 1.0,
+            "{estimate} for {n} at precision {precision}"
+        );
+        hll.clear();
+        assert!(hll.is_empty());
+        assert_eq!(hll.estimate(), 0.0);
+    });
+}
+
+#[test]
+fn hyperloglog_merge_is_the_union() {
+    cases(1670, 40, |rng| {
+        let (precision, seed) = (4 + rng.gen_range(11) as u32, rng.next_u64());
+        let mut both = HyperLogLog::with_seed(precision, seed);
+        let mut left = HyperLogLog::with_seed(precision, seed);
+        let mut right = HyperLogLog::with_seed(precision, seed);
+        for _ in 0..rng.gen_range(20_000) {
+            let x = rng.gen_range(30_000);
+            both.insert(&x);
+            if rng.gen_bool(0.5) {
+                left.insert(&x);
+            } else {
+                right.extend([x]);
+            }
+        }
+        left.merge(&right).unwrap();
+        assert_eq!(left.estimate(), both.estimate());
+        let mut stranger = HyperLogLog::with_seed(precision, seed ^ 1);
+        assert!(stranger.merge(&both).is_err());
+        let mut finer = HyperLogLog::with_seed(precision + 1, seed);
+        assert!(finer.merge(&both).is_err());
+    });
+}
+
+fn jaccard(a: &BTreeSet<u64>, b: &BTreeSet<u64>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+// b shares about overlap of its items with a
+fn overlapping(a: &BTreeSet<u64>, overlap: f64, rng: &mut SplitMix64) -> BTreeSet<u64> {
+    let mut b: BTreeSet<u64> = a
+        .iter()
+        .copied()
+        .filter(|_| rng.gen_bool(overlap))
+        .collect();
+    let fresh = a.len() - b.len();
+    b.extend(distinct(fresh, rng));
+    b
+}
+
+#[test]
+fn minhash_estimates_jaccard() {
+    cases(168, 100, |rng| {
+        let minhash = MinHash::with_seed(256, rng.next_u64());
+        let a = distinct(1 + rng.gen_range(500), rng);
+        let b = overlapping(&a, rng.next_f64(), rng);
+        let (sa, sb) = (minhash.signature(&a), minhash.signature(&b));
+        let (estimate, exact) = (sa.similarity(&sb), jaccard(&a, &b));
+        // four standard deviations, sqrt(J (1 - J) / k) at most 1 / 32
+        assert!((estimate - exact).abs() <= 0.125, "{estimate} for {exact}");
+        let union: BTreeSet<u64> = a.union(&b).copied().collect();
+        assert_eq!(sa.union(&sb), minhash.signature(&union));
+        assert_eq!(sa.similarity(&sa), 1.0);
+    });
+    let empty = MinHash::<u64>::new(16).signature([]);
+    assert!(empty.0.iter().all(|&h| h == u64::MAX));
+}
+
+// candidates are exactly the ids agreeing with a query on a whole band
+#[test]
+fn lsh_candidates_match_band_comparison() {
+    cases(1680, 20, |rng| {
+        let (bands, rows) = (1 + rng.gen_range(20), 1 + rng.gen_range(6));
+        let minhash = MinHash::with_seed(bands * rows, rng.next_u64());
+        let mut index = LshIndex::new(bands, rows);
+        let mut sets = vec![distinct(1 + rng.gen_range(100), rng)];
+        for _ in 0..rng.gen_range(60) {
+            let base = &sets[rng.gen_range(sets.len())];
+            let next = if rng.gen_bool(0.5) {
+                overlapping(base, 0.8 + rng.next_f64() / 5.0, rng)
+            } else {
+                distinct(1 + rng.gen_range(100), rng)
+            };
+            sets.push(next);
+        }
+        let signatures: Vec<Signature> = sets.iter().map(|s| minhash.signature(s)).collect();
+        for (i, signature) in signatures.iter().enumerate() {
+            assert_eq!(index.insert(i, signature.clone()), i);
+        }
+        let shares_band = |a: &Signature, b: &Signature| {
+            a.0.chunks(rows).zip(b.0.chunks(rows)).any(|(x, y)| x == y)
+        };
+        let mut pairs = Vec::new();
+        for (i, a) in signatures.iter().enumerate() {
+            let naive: Vec<usize> = (0..signatures.len())
+                .filter(|&j| shares_band(a, &signatures[j]))
+                .collect();
+            assert_eq!(index.candidates(a), naive);
+            pairs.extend(naive.into_iter().filter(|&j| j > i).map(|j| (i, j)));
+            let found = index.query(a, 0.5);
+            assert!(found.windows(2).all(|w| w[0].1 >= w[1].1));
+            assert!(found
+                .iter()
+                .all(|&(j, s)| s >= 0.5 && s == a.similarity(&signatures[j])));
+        }
+        assert_eq!(index.candidate_pairs(), pairs);
+    });
+}
+
+#[test]
+fn lsh_finds_near_duplicates() {
+    let mut rng = SplitMix64::new(16800);
+    let minhash = MinHash::with_seed(100, 7);
+    let mut index = LshIndex::new(20, 5);
+    let originals: Vec<BTreeSet<u64>> = (0..200).map(|_| distinct(200, &mut rng)).collect();
+    for (i, set) in originals.iter().enumerate() {
+        index.insert(i, minhash.signature(set));
+    }
+    let mut found = 0;
+    for (i, set) in originals.iter().enumerate() {
+        let copy = overlapping(set, 0.95, &mut rng);
+        if index.candidates(&minhash.signature(&copy)).contains(&i) {
+            found += 1;
+        }
+    }
+    // similarity about 0.9 collides with probability 1 - (1 - 0.9^5)^20
+    assert!(found >= 198, "{found} of 200 near duplicates found");
+    // and unrelated sets of 200 almost never do
+    let strays = (0..200)
+        .filter(|_| {
+            !index
+                .candidates(&minhash.signature(&distinct(200, &mut rng)))
+                .is_empty()
+        })
+        .count();
+    assert!(strays <= 2, "{strays} unrelated sets matched");
+}
@@ -0,0 +1,24 @@
+mod mutations;
+
+use graph::rng::{Rng, SplitMix64};
+
+#[test]
+fn random_mutation_sequences() {
+    let mut rng = SplitMix64::new(115);
+    for _ in 0..2000 {
+        let len = rng.gen_range(256);
+        let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+        mutations::run(&data);
+    }
+}
+
+#[test]
+fn remove_vertex_with_parallel_and_self_edges() {
+    // add 1-2 twice, self loop on 1, remove vertex 1
+    mutations::run(&[1, 1, 2, 5, 1, 1, 2, 6, 1, 1, 1, 7, 2, 1]);
+}
+
+#[test]
+fn remove_missing_vertex() {
+    mutations::run(&[2, 3, 0, 4, 2, 4, 2, 4]);
+}
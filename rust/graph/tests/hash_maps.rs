@@ -0,0 +1,228 @@
+// The hash maps against std's maps, on random operation sequences over
+// few keys, and on keys that every seed hashes alike. The consistent hash
+// ring is checked on which keys may move when its nodes change, and how
+// many do.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+use graph::consistent_hash::ConsistentHashRing;
+use graph::cuckoo_hash::CuckooHashMap;
+use graph::rng::{Rng, SplitMix64};
+use graph::robin_hood::RobinHoodMap;
+
+#[test]
+fn cuckoo_matches_btree_map() {
+    let mut rng = SplitMix64::new(173);
+    for _ in 0..200 {
+        let keys = 1 + rng.gen_range(500);
+        let mut map = CuckooHashMap::new();
+        let mut model = BTreeMap::new();
+        for _ in 0..rng.gen_range(2000) {
+            let k = rng.gen_range(keys);
+            if rng.gen_bool(0.7) {
+                let v = rng.next_u64();
+                assert_eq!(map.insert(k, v), model.insert(k, v));
+            } else {
+                assert_eq!(map.remove(&k), model.remove(&k));
+            }
+            assert_eq!(map.len(), model.len());
+            assert_eq!(map.get(&k), model.get(&k));
+        }
+        let mut entries: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        entries.sort();
+        assert_eq!(entries, model.into_iter().collect::<Vec<_>>());
+    }
+}
+
+// compares by value, hashes to nothing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Clash(u32);
+
+impl Hash for Clash {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+#[test]
+fn cuckoo_stashes_keys_no_seed_separates() {
+    let mut map = CuckooHashMap::new();
+    for k in 0..100 {
+        assert_eq!(map.insert(Clash(k), k), None);
+    }
+    assert_eq!(map.len(), 100);
+    // two slots take two keys, the rest have to wait in the stash
+    assert_eq!(map.stashed(), 98);
+    // and the tables grow with the keys, not with every failed rebuild
+    assert!(map.capacity() <= 8 * 100);
+    for k in 0..100 {
+        assert_eq!(map.get(&Clash(k)), Some(&k));
+    }
+    for k in (0..100).step_by(2) {
+        assert_eq!(map.remove(&Clash(k)), Some(k));
+    }
+    assert_eq!(map.len(), 50);
+    assert!((0..100).all(|k| map.contains_key(&Clash(k)) == (k % 2 == 1)));
+}
+
+// every entry counted once at its distance, and the load kept in bounds
+fn check_probes<K: Hash + Eq, V, S: std::hash::BuildHasher>(map: &RobinHoodMap<K, V, S>) {
+    let stats = map.probe_stats();
+    assert_eq!(stats.len, map.len());
+    assert_eq!(stats.histogram.iter().sum::<usize>(), map.len());
+    assert_eq!(stats.max, stats.histogram.len().saturating_sub(1));
+    assert!(map.len() < map.capacity());
+    assert!(map.load_factor() <= map.max_load_factor());
+}
+
+// a fixed hasher, so a failing seed fails again
+#[test]
+fn robin_hood_matches_hash_map() {
+    let mut rng = SplitMix64::new(172);
+    for _ in 0..200 {
+        let keys = 1 + rng.gen_range(500);
+        let hasher = BuildHasherDefault::<DefaultHasher>::default();
+        let mut map = RobinHoodMap::with_capacity_and_hasher(rng.gen_range(100), hasher);
+        if rng.gen_bool(0.3) {
+            map.set_max_load_factor(0.3 + rng.next_f64() * 0.69);
+        }
+        let mut model = HashMap::new();
+        for step in 0..rng.gen_range(2000) {
+            let k = rng.gen_range(keys);
+            match rng.gen_range(10) {
+                0..=4 => {
+                    let v = rng.next_u64();
+                    assert_eq!(map.insert(k, v), model.insert(k, v));
+                }
+                5..=7 => assert_eq!(map.remove(&k), model.remove(&k)),
+                8 => {
+                    if let Some(v) = map.get_mut(&k) {
+                        *v = v.wrapping_add(1);
+                    }
+                    if let Some(v) = model.get_mut(&k) {
+                        *v = v.wrapping_add(1);
+                    }
+                }
+                _ => assert_eq!(map.contains_key(&k), model.contains_key(&k)),
+            }
+            assert_eq!(map.len(), model.len());
+            assert_eq!(map.is_empty(), model.is_empty());
+            assert_eq!(map.get(&k), model.get(&k));
+            if step % 100 == 0 {
+                check_probes(&map);
+                // a lower limit grows the table at once
+                if rng.gen_bool(0.1) {
+                    map.set_max_load_factor(0.2 + rng.next_f64() * 0.7);
+                    check_probes(&map);
+                }
+            }
+        }
+        check_probes(&map);
+        let entries: HashMap<usize, u64> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(entries, model);
+        assert_eq!(map.keys().count(), model.len());
+        let mut values: Vec<u64> = map.values().copied().collect();
+        let mut expected: Vec<u64> = model.values().copied().collect();
+        values.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(values, expected);
+        let capacity = map.capacity();
+        map.clear();
+        assert!(map.is_empty() && map.iter().next().is_none());
+        assert_eq!(map.capacity(), capacity);
+    }
+}
+
+// one home for every key, so every insert and remove shifts the whole run
+#[test]
+fn robin_hood_keeps_one_long_run() {
+    let mut map = RobinHoodMap::new();
+    for k in 0..200 {
+        assert_eq!(map.insert(Clash(k), k), None);
+    }
+    let stats = map.probe_stats();
+    assert_eq!(stats.histogram, vec![1; 200]);
+    for k in (0..200).step_by(3) {
+        assert_eq!(map.remove(&Clash(k)), Some(k));
+    }
+    // backward shifting leaves no gaps in the run
+    let stats = map.probe_stats();
+    assert_eq!(stats.histogram, vec![1; map.len()]);
+    assert!((0..200).all(|k| map.get(&Clash(k)) == (k % 3 != 0).then_some(&k)));
+}
+
+// Only the keys of a node removed move, and only to a node added; about
+// 1 / n of them either way. The owner is the first of the replicas.
+#[test]
+fn consistent_hash_moves_only_the_changed_arcs() {
+    let mut rng = SplitMix64::new(174);
+    let keys: Vec<u64> = (0..2000).map(|_| rng.next_u64()).collect();
+    for _ in 0..15 {
+        let mut ring = ConsistentHashRing::new(50 + rng.gen_range(150));
+        let mut next = 0u32;
+        for _ in 0..1 + rng.gen_range(12) {
+            assert!(ring.add_node(next));
+            next += 1;
+        }
+        assert!(!ring.add_node(0));
+        for _ in 0..10 {
+            let before = ring.clone();
+            let n = ring.len();
+            let changed = if n > 1 && rng.gen_bool(0.5) {
+                let mut nodes: Vec<u32> = ring.nodes().copied().collect();
+                nodes.sort_unstable();
+                let gone = nodes[rng.gen_range(n)];
+                assert!(ring.remove_node(&gone));
+                assert!(!ring.remove_node(&gone) && !ring.contains_node(&gone));
+                for k in keys.iter() {
+                    let (was, is) = (before.node_for(k), ring.node_for(k));
+                    assert!(was == is || was == Some(&gone), "{k}");
+                }
+                n
+            } else {
+                assert!(ring.add_node(next));
+                for k in keys.iter() {
+                    let (was, is) = (before.node_for(k), ring.node_for(k));
+                    assert!(was == is || is == Some(&next), "{k}");
+                }
+                next += 1;
+                n + 1
+            };
+            let moved = before.moved_fraction(&ring, keys.iter());
+            let share = 1.0 / changed as f64;
+            assert!(
+                moved > share / 3.0 && moved < share * 3.0,
+                "{moved} of 1/{changed}"
+            );
+            let counts = ring.distribution(keys.iter());
+            assert_eq!(counts.values().sum::<usize>(), keys.len());
+            for k in keys.iter().take(50) {
+                let count = rng.gen_range(ring.len() + 2);
+                let replicas = ring.nodes_for(k, count);
+                assert_eq!(replicas.len(), count.min(ring.len()));
+                assert_eq!(
+                    replicas.first().copied(),
+                    ring.node_for(k).filter(|_| count > 0)
+                );
+                assert!((1..replicas.len()).all(|i| !replicas[..i].contains(&replicas[i])));
+            }
+        }
+    }
+}
+
+// twice the points, about twice the keys
+#[test]
+fn consistent_hash_weights_shares_by_points() {
+    let mut rng = SplitMix64::new(1740);
+    let keys: Vec<u64> = (0..20000).map(|_| rng.next_u64()).collect();
+    let mut ring = ConsistentHashRing::new(200);
+    assert!(ring.is_empty() && ring.node_for(&1u64).is_none());
+    for node in 0..4 {
+        assert!(ring.add_node(node));
+    }
+    assert!(ring.add_weighted(4, 800));
+    let counts = ring.distribution(keys.iter());
+    let light = (0..4).map(|n| counts[&n]).sum::<usize>() as f64 / 4.0;
+    let ratio = counts[&4] as f64 / light;
+    assert!(ratio > 2.5 && ratio < 6.0, "{ratio}");
+}
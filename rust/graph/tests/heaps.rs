@@ -0,0 +1,482 @@
+// Stress tests for the mergeable heaps: every operation sequence is
+// replayed on a BinaryHeap and the pop order has to match. Items are
+// (key, id) pairs with unique ids, so the order is fully determined. The
+// indexed heap is checked against the key of every item instead, and the
+// double-ended one against a sorted Vec.
+
+mod common;
+
+use std::{
+    cell::Cell,
+    cmp::Ordering,
+    panic::{catch_unwind, AssertUnwindSafe},
+    rc::Rc,
+};
+
+use graph::binary_heap::BinaryHeap;
+use graph::binomial_heap::BinomialHeap;
+use graph::fibonacci_heap::{FibonacciHeap, Handle};
+use graph::indexed_heap::IndexedHeap;
+use graph::interval_heap::IntervalHeap;
+use graph::pairing_heap::{PairingHeap, StaleHandle};
+use graph::priority_queue::{Compare, MaxOrder, PriorityQueue};
+use graph::rng::{Rng, SplitMix64};
+
+use common::cases;
+
+// pushes and pops through the trait, so any queue can be checked
+fn same_pops<Q: PriorityQueue<(u32, usize)>>(mut queue: Q, rng: &mut SplitMix64) {
+    let mut expected = BinaryHeap::new();
+    for id in 0..rng.gen_range(500) {
+        if rng.gen_bool(0.6) {
+            let item = (rng.gen_range(50) as u32, id);
+            queue.push(item);
+            expected.push(item);
+        } else {
+            assert_eq!(queue.pop(), expected.pop());
+        }
+        assert_eq!(queue.len(), expected.len());
+        assert_eq!(queue.peek(), expected.peek());
+    }
+    assert_eq!(
+        std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>(),
+        expected.into_sorted_vec()
+    );
+}
+
+#[test]
+fn fibonacci_pops_like_binary_heap() {
+    cases(0, 200, |rng| same_pops(FibonacciHeap::new(), rng));
+}
+
+#[test]
+fn fibonacci_decrease_key_matches_binary_heap() {
+    cases(1000, 200, |rng| {
+        let mut heap = FibonacciHeap::new();
+        let mut handles: Vec<Option<Handle<(i64, usize)>>> = Vec::new();
+        // current key per id, the binary heap skips outdated entries
+        let mut keys: Vec<i64> = Vec::new();
+        let mut expected = BinaryHeap::new();
+        for _ in 0..rng.gen_range(800) {
+            match rng.gen_range(3) {
+                0 => {
+                    let id = handles.len();
+                    let key = rng.gen_range(1000) as i64;
+                    handles.push(Some(heap.push((key, id))));
+                    keys.push(key);
+                    expected.push((key, id));
+                }
+                1 => {
+                    let live: Vec<usize> = (0..handles.len())
+                        .filter(|&id| handles[id].is_some())
+                        .collect();
+                    if live.is_empty() {
+                        continue;
+                    }
+                    let id = live[rng.gen_range(live.len())];
+                    keys[id] -= rng.gen_range(300) as i64;
+                    let handle = handles[id].unwrap();
+                    assert_eq!(heap.decrease_key(handle, (keys[id], id)), Ok(()));
+                    assert_eq!(heap.get(handle), Some(&(keys[id], id)));
+                    expected.push((keys[id], id));
+                }
+                _ => {
+                    while expected.peek().is_some_and(|&(k, id)| k != keys[id]) {
+                        expected.pop();
+                    }
+                    let popped = heap.pop();
+                    assert_eq!(popped, expected.pop());
+                    if let Some((_, id)) = popped {
+                        let handle = handles[id].take().unwrap();
+                        assert!(!heap.contains(handle));
+                        let item = (i64::MIN, id);
+                        assert_eq!(heap.decrease_key(handle, item).unwrap_err().0, item);
+                        // a later push of the same key must not match it
+                        keys[id] = i64::MAX;
+                    }
+                }
+            }
+        }
+        let live = handles.iter().filter(|h| h.is_some()).count();
+        assert_eq!(heap.len(), live);
+    });
+}
+
+#[test]
+fn fibonacci_meld_keeps_handles() {
+    cases(2000, 100, |rng| {
+        let mut a = FibonacciHeap::new();
+        let mut b = FibonacciHeap::new();
+        let mut handles = Vec::new();
+        let mut items = Vec::new();
+        for id in 0..rng.gen_range(200) {
+            let item = (rng.gen_range(100) as i64, id);
+            let heap = if rng.gen_bool(0.5) { &mut a } else { &mut b };
+            handles.push(heap.push(item));
+            items.push(item);
+            if rng.gen_bool(0.2) {
+                // pop and push back to get some multi level trees
+                let top = heap.pop().unwrap();
+                handles[top.1] = heap.push(top);
+            }
+        }
+        let mut stranger = FibonacciHeap::new();
+        let elsewhere = stranger.push((0, usize::MAX));
+        a.meld(b);
+        assert_eq!(a.get(elsewhere), None);
+        for (id, item) in items.iter_mut().enumerate() {
+            assert_eq!(a.get(handles[id]), Some(&*item));
+            if rng.gen_bool(0.3) {
+                item.0 -= rng.gen_range(100) as i64;
+                assert_eq!(a.decrease_key(handles[id], *item), Ok(()));
+            }
+        }
+        items.sort();
+        assert_eq!(std::iter::from_fn(|| a.pop()).collect::<Vec<_>>(), items);
+    });
+}
+
+#[test]
+fn fibonacci_max_order() {
+    cases(3000, 50, |rng| {
+        let mut heap = FibonacciHeap::with_compare(MaxOrder);
+        let mut items: Vec<u32> = (0..rng.gen_range(300))
+            .map(|_| rng.gen_range(1000) as u32)
+            .collect();
+        for &x in items.iter() {
+            heap.push(x);
+        }
+        items.sort_by(|a, b| b.cmp(a));
+        assert_eq!(std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), items);
+    });
+}
+
+#[test]
+fn binomial_pops_like_binary_heap() {
+    cases(4000, 200, |rng| same_pops(BinomialHeap::new(), rng));
+}
+
+#[test]
+fn binomial_merge() {
+    cases(5000, 100, |rng| {
+        let mut heaps: Vec<BinomialHeap<(u32, usize)>> = Vec::new();
+        let mut items = Vec::new();
+        for _ in 0..1 + rng.gen_range(8) {
+            let start = items.len();
+            for _ in 0..rng.gen_range(100) {
+                items.push((rng.gen_range(100) as u32, items.len()));
+            }
+            let heap: BinomialHeap<_> = items[start..].iter().copied().collect();
+            heaps.push(heap);
+        }
+        let mut merged = heaps.pop().unwrap();
+        for heap in heaps {
+            merged.merge(heap);
+        }
+        assert_eq!(merged.len(), items.len());
+        items.sort();
+        assert_eq!(
+            std::iter::from_fn(|| merged.pop()).collect::<Vec<_>>(),
+            items
+        );
+    });
+}
+
+// Compares by key, and once lit panics when the count runs out. Items hold
+// a token, so a node freed twice or leaked shows in its count.
+type Tracked = (u32, Rc<()>);
+
+struct Fuse(Rc<Cell<Option<usize>>>);
+
+impl Compare<Tracked> for Fuse {
+    fn compare(&self, a: &Tracked, b: &Tracked) -> Ordering {
+        if let Some(left) = self.0.get() {
+            assert!(left > 0, "comparator fuse burnt");
+            self.0.set(Some(left - 1));
+        }
+        a.0.cmp(&b.0)
+    }
+}
+
+// pops with the fuse lit until one panics, then checks the heap still
+// holds and frees every other item exactly once
+fn survives_panicking_pop<Q: PriorityQueue<Tracked>>(new: fn(Fuse) -> Q, rng: &mut SplitMix64) {
+    let token = Rc::new(());
+    let fuse = Rc::new(Cell::new(None));
+    let mut heap = new(Fuse(fuse.clone()));
+    let n = 1 + rng.gen_range(300);
+    for _ in 0..n {
+        heap.push((rng.gen_range(1000) as u32, token.clone()));
+    }
+    // some pops first, for deeper trees
+    for _ in 0..rng.gen_range(n) {
+        heap.pop();
+    }
+    fuse.set(Some(rng.gen_range(20)));
+    while !heap.is_empty() {
+        let len = heap.len();
+        let popped = catch_unwind(AssertUnwindSafe(|| heap.pop()));
+        assert_eq!(heap.len(), len - 1);
+        if popped.is_err() {
+            break;
+        }
+    }
+    fuse.set(None);
+    let left = heap.len();
+    assert_eq!(std::iter::from_fn(|| heap.pop()).count(), left);
+    for _ in 0..rng.gen_range(50) {
+        heap.push((rng.gen_range(1000) as u32, token.clone()));
+    }
+    drop(heap);
+    assert_eq!(Rc::strong_count(&token), 1);
+}
+
+#[test]
+fn pairing_pops_like_binary_heap() {
+    cases(6000, 200, |rng| same_pops(PairingHeap::new(), rng));
+}
+
+// popped items leave their handles stale, refused without a change
+#[test]
+fn pairing_decrease_key_matches_binary_heap() {
+    cases(6100, 200, |rng| {
+        let mut heap = PairingHeap::new();
+        let mut handles = Vec::new();
+        // current key per id, None once popped; the binary heap skips
+        // outdated entries
+        let mut keys: Vec<Option<i64>> = Vec::new();
+        let mut expected = BinaryHeap::new();
+        for _ in 0..rng.gen_range(800) {
+            match rng.gen_range(3) {
+                0 => {
+                    let id = handles.len();
+                    let key = rng.gen_range(1000) as i64;
+                    handles.push(heap.push((key, id)));
+                    keys.push(Some(key));
+                    expected.push((key, id));
+                }
+                1 if !handles.is_empty() => {
+                    let id = rng.gen_range(handles.len());
+                    let handle = handles[id];
+                    let Some(key) = keys[id] else {
+                        assert!(!heap.contains(handle));
+                        assert_eq!(heap.get(handle), None);
+                        let item = (-1, id);
+                        assert_eq!(heap.decrease_key(handle, item).unwrap_err().0, item);
+                        continue;
+                    };
+                    assert_eq!(heap.get(handle), Some(&(key, id)));
+                    let key = key - rng.gen_range(300) as i64;
+                    assert_eq!(heap.decrease_key(handle, (key, id)), Ok(()));
+                    keys[id] = Some(key);
+                    expected.push((key, id));
+                }
+                _ => {
+                    while expected.peek().is_some_and(|&(k, id)| keys[id] != Some(k)) {
+                        expected.pop();
+                    }
+                    let popped = heap.pop();
+                    assert_eq!(popped, expected.pop());
+                    if let Some((_, id)) = popped {
+                        keys[id] = None;
+                    }
+                }
+            }
+        }
+        assert_eq!(heap.len(), keys.iter().flatten().count());
+        heap.clear();
+        assert!(handles.iter().all(|&h| !heap.contains(h)));
+    });
+}
+
+// handles of either side work after the meld, whichever is larger, and
+// those of items popped before it stay stale; a handle of a heap never
+// melded in is refused
+#[test]
+fn pairing_meld_keeps_handles() {
+    cases(6200, 100, |rng| {
+        let mut a = PairingHeap::new();
+        let mut b = PairingHeap::new();
+        let mut handles = Vec::new();
+        // None once popped
+        let mut items = Vec::new();
+        let share = rng.next_f64();
+        for id in 0..rng.gen_range(200) {
+            let item = (rng.gen_range(100) as i64, id);
+            let heap = if rng.gen_bool(share) { &mut a } else { &mut b };
+            handles.push(heap.push(item));
+            items.push(Some(item));
+            if rng.gen_bool(0.2) {
+                // multi level trees, and free slots for the meld to carry
+                let top = heap.pop().unwrap();
+                items[top.1] = None;
+            }
+        }
+        let mut stranger = PairingHeap::new();
+        let elsewhere = stranger.push((0, usize::MAX));
+        a.meld(b);
+        assert_eq!(a.len(), items.iter().flatten().count());
+        assert_eq!(
+            a.decrease_key(elsewhere, (-1, 0)),
+            Err(StaleHandle((-1, 0)))
+        );
+        for (id, item) in items.iter_mut().enumerate() {
+            assert_eq!(a.get(handles[id]), item.as_ref());
+            if let Some(item) = item.as_mut().filter(|_| rng.gen_bool(0.3)) {
+                item.0 -= rng.gen_range(100) as i64;
+                assert_eq!(a.decrease_key(handles[id], *item), Ok(()));
+            }
+        }
+        // pushes after the meld take the free slots, old handles keep off
+        for id in items.len()..items.len() + 40 {
+            let item = (rng.gen_range(100) as i64, id);
+            handles.push(a.push(item));
+            items.push(Some(item));
+        }
+        for (&handle, item) in handles.iter().zip(&items) {
+            assert_eq!(a.get(handle), item.as_ref());
+        }
+        let mut items: Vec<_> = items.into_iter().flatten().collect();
+        items.sort();
+        assert_eq!(std::iter::from_fn(|| a.pop()).collect::<Vec<_>>(), items);
+    });
+}
+
+#[test]
+fn pairing_survives_panicking_compare() {
+    cases(7000, 200, |rng| {
+        survives_panicking_pop(PairingHeap::with_compare, rng)
+    });
+}
+
+#[test]
+fn fibonacci_survives_panicking_compare() {
+    cases(8000, 200, |rng| {
+        survives_panicking_pop(FibonacciHeap::with_compare, rng)
+    });
+}
+
+// an item holding the smallest key; ties go either way
+fn is_least(top: Option<(usize, u32)>, keys: &[Option<u32>]) -> bool {
+    let least = keys.iter().flatten().min().copied();
+    top.map(|(_, k)| k) == least && top.is_none_or(|(item, k)| keys[item] == Some(k))
+}
+
+fn indexed_matches_keys<const D: usize>(rng: &mut SplitMix64) {
+    let n = 1 + rng.gen_range(60);
+    let mut heap = IndexedHeap::<u32, D>::new(n);
+    let mut keys: Vec<Option<u32>> = vec![None; n];
+    for _ in 0..rng.gen_range(600) {
+        let item = rng.gen_range(n);
+        let key = rng.gen_range(100) as u32;
+        match rng.gen_range(8) {
+            0 | 1 if keys[item].is_none() => {
+                heap.push(item, key);
+                keys[item] = Some(key);
+            }
+            // larger keys are ignored
+            2 | 3 if keys[item].is_some() => {
+                heap.decrease_key(item, key);
+                keys[item] = keys[item].min(Some(key));
+            }
+            4 => {
+                let lowered = keys[item].is_none_or(|old| key < old);
+                assert_eq!(heap.push_or_decrease(item, key), lowered);
+                if lowered {
+                    keys[item] = Some(key);
+                }
+            }
+            5 => assert_eq!(heap.remove(item), keys[item].take()),
+            6 => {
+                let popped = heap.pop();
+                assert!(is_least(popped, &keys), "{popped:?}");
+                if let Some((item, _)) = popped {
+                    keys[item] = None;
+                }
+            }
+            _ => {
+                assert_eq!(heap.contains(item), keys[item].is_some());
+                assert_eq!(heap.key(item), keys[item]);
+            }
+        }
+        assert_eq!(heap.len(), keys.iter().flatten().count());
+        assert_eq!(heap.is_empty(), keys.iter().all(Option::is_none));
+        assert!(is_least(heap.peek(), &keys), "{:?}", heap.peek());
+    }
+    heap.clear();
+    assert!(heap.is_empty() && (0..n).all(|item| !heap.contains(item)));
+    // reusable after a clear
+    heap.push(n - 1, 0);
+    assert_eq!(heap.pop(), Some((n - 1, 0)));
+}
+
+#[test]
+fn indexed_matches_keys_at_every_arity() {
+    cases(125, 200, |rng| {
+        indexed_matches_keys::<2>(rng);
+        indexed_matches_keys::<3>(rng);
+        indexed_matches_keys::<4>(rng);
+        indexed_matches_keys::<8>(rng);
+    });
+}
+
+#[test]
+#[should_panic(expected = "already in the heap")]
+fn indexed_rejects_a_second_push() {
+    let mut heap = IndexedHeap::<u32>::new(3);
+    heap.push(1, 5);
+    heap.push(1, 4);
+}
+
+// few distinct values, so both ends often hold equal items
+#[test]
+fn interval_heap_matches_sorted_vec() {
+    cases(137, 300, |rng| {
+        let distinct = 1 + rng.gen_range(50);
+        let mut model: Vec<u32> = (0..rng.gen_range(50))
+            .map(|_| rng.gen_range(distinct) as u32)
+            .collect();
+        let mut heap: IntervalHeap<u32> = model.iter().copied().collect();
+        model.sort_unstable();
+        for _ in 0..rng.gen_range(500) {
+            let x = rng.gen_range(distinct) as u32;
+            match rng.gen_range(7) {
+                0 | 1 => {
+                    heap.push(x);
+                    model.insert(model.partition_point(|&y| y <= x), x);
+                }
+                2 => assert_eq!(heap.pop_min(), (!model.is_empty()).then(|| model.remove(0))),
+                3 => assert_eq!(heap.pop_max(), model.pop()),
+                4 => {
+                    let capacity = rng.gen_range(model.len() + 3);
+                    model.insert(model.partition_point(|&y| y <= x), x);
+                    let over = (model.len() > capacity).then(|| model.pop().unwrap());
+                    assert_eq!(heap.push_bounded(x, capacity), over);
+                }
+                5 => {
+                    let more: Vec<u32> = (0..rng.gen_range(10))
+                        .map(|_| rng.gen_range(distinct) as u32)
+                        .collect();
+                    heap.extend(more.iter().copied());
+                    model.extend(more);
+                    model.sort_unstable();
+                }
+                _ => {
+                    let mut items: Vec<u32> = heap.iter().copied().collect();
+                    items.sort_unstable();
+                    assert_eq!(items, model);
+                }
+            }
+            assert_eq!(heap.len(), model.len());
+            assert_eq!(heap.is_empty(), model.is_empty());
+            assert_eq!(heap.peek_min(), model.first());
+            assert_eq!(heap.peek_max(), model.last());
+        }
+        let mut items = heap.clone().into_vec();
+        items.sort_unstable();
+        assert_eq!(items, model);
+        assert_eq!(heap.clone().into_sorted_vec(), model);
+        heap.clear();
+        assert!(heap.is_empty() && heap.peek_min().is_none());
+    });
+}
@@ -0,0 +1,88 @@
+// Merkle proofs for every leaf of every tree size up to N, and the ways a
+// proof must fail: other data, another index, a tampered sibling.
+
+mod common;
+
+use graph::merkle::{FastHasher, MerkleHasher, MerkleTree, Sha256Hasher};
+use graph::rng::{Rng, SplitMix64};
+
+use common::cases;
+
+const N: usize = 40;
+
+fn random_leaves(n: usize, rng: &mut SplitMix64) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| {
+            // the index up front keeps every leaf distinct
+            let mut leaf = i.to_le_bytes().to_vec();
+            leaf.extend((0..rng.gen_range(20)).map(|_| rng.next_u64() as u8));
+            leaf
+        })
+        .collect()
+}
+
+fn proofs_verify<H: MerkleHasher>(rng: &mut SplitMix64) {
+    let empty = MerkleTree::<H>::new(Vec::<Vec<u8>>::new());
+    assert!(empty.is_empty());
+    assert_eq!(empty.root(), None);
+    assert!(empty.proof(0).is_none());
+    for n in 1..=N {
+        let leaves = random_leaves(n, rng);
+        let tree = MerkleTree::<H>::new(&leaves);
+        assert_eq!(tree.len(), n);
+        let root = tree.root().unwrap().clone();
+        assert!(tree.proof(n).is_none());
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(i).unwrap();
+            assert!(proof.verify(&root, leaf), "leaf {i} of {n}");
+            assert_eq!(proof.root_for(leaf), root);
+            // other data
+            let mut tampered = leaf.clone();
+            let at = rng.gen_range(tampered.len());
+            tampered[at] ^= 1 << rng.gen_range(8);
+            assert!(!proof.verify(&root, &tampered));
+            tampered = leaf.clone();
+            tampered.push(0);
+            assert!(!proof.verify(&root, &tampered));
+            // a tampered sibling
+            if !proof.path.is_empty() {
+                let mut forged = proof.clone();
+                let step = rng.gen_range(forged.path.len());
+                forged.path[step].1 = H::leaf(b"forged");
+                assert!(!forged.verify(&root, leaf));
+            }
+            // reused at every other index, with either leaf's data
+            for (j, other) in leaves.iter().enumerate().filter(|&(j, _)| j != i) {
+                let mut moved = proof.clone();
+                moved.index = j;
+                assert!(!moved.verify(&root, leaf), "proof for {i} passed at {j}");
+                assert!(!moved.verify(&root, other), "proof for {i} passed at {j}");
+            }
+        }
+    }
+}
+
+#[test]
+fn every_proof_verifies_and_misuse_fails() {
+    cases(183, 3, proofs_verify::<Sha256Hasher>);
+    cases(1830, 3, proofs_verify::<FastHasher>);
+}
+
+#[test]
+fn update_matches_rebuilding() {
+    cases(18300, 50, |rng| {
+        let n = 1 + rng.gen_range(N);
+        let mut leaves = random_leaves(n, rng);
+        let mut tree = MerkleTree::<Sha256Hasher>::new(&leaves);
+        for _ in 0..2 * n {
+            let i = rng.gen_range(n);
+            leaves[i] = random_leaves(1, rng).pop().unwrap();
+            tree.update(i, &leaves[i]);
+            let rebuilt = MerkleTree::<Sha256Hasher>::new(&leaves);
+            assert_eq!(tree.root(), rebuilt.root());
+            assert_eq!(tree.leaf_digest(i), rebuilt.leaf_digest(i));
+            let proof = tree.proof(rng.gen_range(n)).unwrap();
+            assert!(proof.verify(rebuilt.root().unwrap(), &leaves[proof.index]));
+        }
+    });
+}
@@ -0,0 +1,129 @@
+// Shared by tests/graph_mutations.rs and the cargo-fuzz target in fuzz/:
+// decodes bytes into graph operations, applies them to an undirected
+// Graph<u8> (every edge added and removed in both directions) together with
+// a plain edge list model, and checks the invariants after every step.
+
+use graph::adjlist::Graph;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    AddVertex(u8),
+    AddEdge(u8, u8, i32),
+    RemoveVertex(u8),
+    RemoveEdge(u8, u8),
+}
+
+// few distinct keys so operations actually collide
+const KEYS: u8 = 16;
+
+pub fn decode(data: &[u8]) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let mut bytes = data.iter().copied();
+    while let Some(tag) = bytes.next() {
+        let a = bytes.next().unwrap_or(0) % KEYS;
+        let op = match tag % 4 {
+            0 => Op::AddVertex(a),
+            1 => {
+                let b = bytes.next().unwrap_or(0) % KEYS;
+                let wt = bytes.next().unwrap_or(0) as i32;
+                Op::AddEdge(a, b, wt)
+            }
+            2 => Op::RemoveVertex(a),
+            _ => Op::RemoveEdge(a, bytes.next().unwrap_or(0) % KEYS),
+        };
+        ops.push(op);
+    }
+    ops
+}
+
+pub fn run(data: &[u8]) {
+    let mut g = Graph::new();
+    let mut vertices: Vec<u8> = Vec::new();
+    let mut edges: Vec<(u8, u8, i32)> = Vec::new();
+    for op in decode(data) {
+        match op {
+            Op::AddVertex(a) => {
+                g.add_vertex(&a);
+                if vertices.contains(&a) {
+                    // re-adding drops the out-edges of a
+                    edges.retain(|&(from, _, _)| from != a);
+                } else {
+                    vertices.push(a);
+                }
+                // keep the model undirected again
+                let dangling: Vec<_> = edges.iter().filter(|e| e.1 == a).copied().collect();
+                for (from, to, _) in dangling {
+                    g.remove_edge(&from, &to);
+                    let pos = edges
+                        .iter()
+                        .position(|&(f, t, _)| (f, t) == (from, to))
+                        .unwrap();
+                    edges.remove(pos);
+                }
+            }
+            Op::AddEdge(a, b, wt) => {
+                g.add_edge(&a, &b, wt);
+                g.add_edge(&b, &a, wt);
+                for k in [a, b] {
+                    if !vertices.contains(&k) {
+                        vertices.push(k);
+                    }
+                }
+                edges.push((a, b, wt));
+                edges.push((b, a, wt));
+            }
+            Op::RemoveVertex(a) => {
+                let removed = g.remove_vertex(&a);
+                assert_eq!(removed.is_some(), vertices.contains(&a));
+                vertices.retain(|&k| k != a);
+                edges.retain(|&(from, to, _)| from != a && to != a);
+            }
+            Op::RemoveEdge(a, b) => {
+                let wt = g.remove_edge(&a, &b);
+                let pos = edges.iter().position(|&(f, t, _)| (f, t) == (a, b));
+                assert_eq!(wt, pos.map(|i| edges[i].2));
+                if let Some(i) = pos {
+                    edges.remove(i);
+                    assert_eq!(g.remove_edge(&b, &a), wt);
+                    let j = edges
+                        .iter()
+                        .position(|&(f, t, w)| (f, t, w) == (b, a, wt.unwrap()))
+                        .unwrap();
+                    edges.remove(j);
+                }
+            }
+        }
+        check(&g, &vertices, &edges);
+    }
+}
+
+fn check(g: &Graph<u8>, vertices: &[u8], edges: &[(u8, u8, i32)]) {
+    assert_eq!(g.vertex_num() as usize, vertices.len());
+    assert_eq!(g.is_empty(), vertices.is_empty());
+    let mut keys = g.vertex_keys();
+    keys.sort_unstable();
+    let mut expect = vertices.to_vec();
+    expect.sort_unstable();
+    assert_eq!(keys, expect);
+
+    let mut stored = Vec::new();
+    for &v in keys.iter() {
+        for &(nbr, wt) in g.get_vertex(&v).unwrap().neighbors.iter() {
+            // no dangling neighbors
+            assert!(g.contains(&nbr), "{v} -> {nbr} points to a removed vertex");
+            stored.push((v, nbr, wt));
+        }
+    }
+    assert_eq!(g.edge_num() as usize, stored.len());
+
+    // symmetry
+    let mut forward = stored.clone();
+    let mut backward: Vec<_> = stored.iter().map(|&(a, b, wt)| (b, a, wt)).collect();
+    forward.sort_unstable();
+    backward.sort_unstable();
+    assert_eq!(forward, backward);
+
+    let mut model = edges.to_vec();
+    model.sort_unstable();
+    assert_eq!(forward, model);
+}
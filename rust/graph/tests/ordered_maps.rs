@@ -0,0 +1,361 @@
+// Every OrderedMap against std's BTreeMap, on random operation sequences
+// over few keys, with in-order scans and ranges of every bound kind taken
+// between rounds. The treaps and the splay tree go through their own
+// methods, against std's collections and a Vec.
+
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+
+use graph::avl_tree::AvlTreeMap;
+use graph::b_plus_tree::BPlusTree;
+use graph::b_tree;
+use graph::ordered_map::OrderedMap;
+use graph::red_black_tree::RedBlackTreeMap;
+use graph::rng::{Rng, SplitMix64};
+use graph::scapegoat_tree::ScapegoatTree;
+use graph::skip_list::SkipListMap;
+use graph::splay_tree::SplayTree;
+use graph::treap::{ImplicitTreap, Treap};
+
+use common::cases;
+
+// through the trait only, as the benchmarks drive the maps
+fn trace<M: OrderedMap<u32, u64>>(
+    map: &mut M,
+    model: &mut BTreeMap<u32, u64>,
+    keys: usize,
+    rng: &mut SplitMix64,
+) {
+    for _ in 0..rng.gen_range(400) {
+        let k = rng.gen_range(keys) as u32;
+        match rng.gen_range(10) {
+            0..=4 => {
+                let v = rng.next_u64();
+                assert_eq!(map.insert(k, v), model.insert(k, v));
+            }
+            5..=7 => assert_eq!(map.remove(&k), model.remove(&k)),
+            8 => assert_eq!(map.get(&k), model.get(&k)),
+            _ => assert_eq!(map.contains_key(&k), model.contains_key(&k)),
+        }
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.is_empty(), model.is_empty());
+        assert_eq!(map.first(), model.first_key_value());
+        assert_eq!(map.last(), model.last_key_value());
+    }
+}
+
+// any range BTreeMap accepts, its ends often just outside the keys
+fn some_range(keys: usize, rng: &mut SplitMix64) -> (Bound<u32>, Bound<u32>) {
+    let bound = |rng: &mut SplitMix64| {
+        let at = rng.gen_range(keys + 2) as u32;
+        match rng.gen_range(3) {
+            0 => Bound::Included(at),
+            1 => Bound::Excluded(at),
+            _ => Bound::Unbounded,
+        }
+    };
+    let (start, end) = (bound(rng), bound(rng));
+    let value = |b: Bound<u32>| match b {
+        Bound::Included(x) | Bound::Excluded(x) => Some(x),
+        Bound::Unbounded => None,
+    };
+    match (value(start), value(end)) {
+        (Some(s), Some(e)) if s > e => (end, start),
+        (Some(s), Some(e)) if s == e => (Bound::Included(s), end),
+        _ => (start, end),
+    }
+}
+
+// rounds of trace, then the scans the trait doesn't cover
+macro_rules! matches_btree_map {
+    ($name:ident, $seed:expr, $new:expr) => {
+        #[test]
+        fn $name() {
+            cases($seed, 150, |rng| {
+                let mut map = $new;
+                let mut model = BTreeMap::new();
+                let keys = 1 + rng.gen_range(300);
+                for _ in 0..1 + rng.gen_range(5) {
+                    trace(&mut map, &mut model, keys, rng);
+                    assert!(map.iter().eq(model.iter()));
+                    for _ in 0..20 {
+                        let range = some_range(keys, rng);
+                        assert!(map.range(range).eq(model.range(range)), "{range:?}");
+                    }
+                    let k = rng.gen_range(keys) as u32;
+                    if let Some(v) = map.get_mut(&k) {
+                        *v = v.wrapping_add(1);
+                    }
+                    if let Some(v) = model.get_mut(&k) {
+                        *v = v.wrapping_add(1);
+                    }
+                    assert_eq!(OrderedMap::get(&map, &k), model.get(&k));
+                }
+                map.clear();
+                assert!(map.is_empty() && map.iter().next().is_none());
+            });
+        }
+    };
+}
+
+matches_btree_map!(avl_matches_btree_map, 152, AvlTreeMap::new());
+matches_btree_map!(red_black_matches_btree_map, 153, RedBlackTreeMap::new());
+matches_btree_map!(
+    b_tree_2_matches_btree_map,
+    155,
+    b_tree::BTreeMap::<_, _, 2>::default()
+);
+matches_btree_map!(b_tree_6_matches_btree_map, 1550, b_tree::BTreeMap::new());
+matches_btree_map!(
+    b_plus_tree_2_matches_btree_map,
+    156,
+    BPlusTree::<_, _, 2>::default()
+);
+matches_btree_map!(b_plus_tree_6_matches_btree_map, 1560, BPlusTree::new());
+matches_btree_map!(scapegoat_matches_btree_map, 157, ScapegoatTree::new());
+matches_btree_map!(
+    scapegoat_loose_matches_btree_map,
+    1570,
+    ScapegoatTree::with_alpha(0.9)
+);
+matches_btree_map!(skip_list_matches_btree_map, 150, SkipListMap::new());
+matches_btree_map!(
+    skip_list_one_level_matches_btree_map,
+    1500,
+    SkipListMap::with_config(1, 7)
+);
+
+// kth and rank against positions in the model's order
+#[test]
+fn avl_kth_and_rank_match_sorted_order() {
+    cases(158, 200, |rng| {
+        let mut map = AvlTreeMap::new();
+        let mut model = BTreeMap::new();
+        let keys = 1 + rng.gen_range(300);
+        trace(&mut map, &mut model, keys, rng);
+        let sorted: Vec<(&u32, &u64)> = model.iter().collect();
+        for (k, &entry) in sorted.iter().enumerate() {
+            assert_eq!(map.kth(k), Some(entry));
+            assert_eq!(map.rank(entry.0), k);
+        }
+        assert_eq!(map.kth(sorted.len()), None);
+        for k in 0..keys as u32 + 2 {
+            assert_eq!(map.rank(&k), model.range(..k).count());
+        }
+        // balanced: at most about 1.44 log2(n + 2)
+        let bound = 1.45 * ((model.len() + 2) as f64).log2();
+        let height = map.height();
+        assert!(height as f64 <= bound, "{height} for {}", model.len());
+    });
+}
+
+// bulk loaded, then mutated like any other
+#[test]
+fn b_plus_tree_from_sorted_matches_btree_map() {
+    cases(1561, 200, |rng| {
+        let keys = 1 + rng.gen_range(1000);
+        let mut model: BTreeMap<u32, u64> = (0..rng.gen_range(keys))
+            .map(|_| (rng.gen_range(keys) as u32, rng.next_u64()))
+            .collect();
+        let mut map = BPlusTree::<_, _, 3>::from_sorted(model.iter().map(|(&k, &v)| (k, v)));
+        assert_eq!(map.len(), model.len());
+        assert!(map.iter().eq(model.iter()));
+        for _ in 0..20 {
+            let range = some_range(keys, rng);
+            assert!(map.range(range).eq(model.range(range)), "{range:?}");
+        }
+        trace(&mut map, &mut model, keys, rng);
+        assert!(map.iter().eq(model.iter()));
+    });
+}
+
+#[test]
+#[should_panic(expected = "strictly increasing")]
+fn b_plus_tree_from_sorted_rejects_unsorted_keys() {
+    BPlusTree::<u32, u32>::from_sorted([(1, 0), (1, 0)]);
+}
+
+// split at a random key and merged back now and then, halves checked
+#[test]
+fn treap_matches_btree_set() {
+    cases(151, 200, |rng| {
+        let keys = 1 + rng.gen_range(300);
+        let mut treap = if rng.gen_bool(0.5) {
+            Treap::new()
+        } else {
+            Treap::with_seed(rng.next_u64())
+        };
+        let mut model = BTreeSet::new();
+        for _ in 0..rng.gen_range(600) {
+            let k = rng.gen_range(keys + 2) as u32;
+            match rng.gen_range(10) {
+                0..=3 => assert_eq!(treap.insert(k), model.insert(k)),
+                4 | 5 => assert_eq!(treap.remove(&k), model.remove(&k)),
+                6 => assert_eq!(treap.contains(&k), model.contains(&k)),
+                7 => {
+                    let i = rng.gen_range(model.len() + 1);
+                    assert_eq!(treap.kth(i), model.iter().nth(i));
+                    assert_eq!(treap.rank(&k), model.range(..k).count());
+                }
+                8 => {
+                    let upper = treap.split_off(&k);
+                    let model_upper = model.split_off(&k);
+                    assert!(treap.iter().eq(model.iter()));
+                    assert!(upper.iter().eq(model_upper.iter()));
+                    treap.merge(upper);
+                    model.extend(model_upper);
+                }
+                _ => assert!(treap.iter().eq(model.iter())),
+            }
+            assert_eq!(treap.len(), model.len());
+            assert_eq!(treap.is_empty(), model.is_empty());
+            assert_eq!(treap.first(), model.first());
+            assert_eq!(treap.last(), model.last());
+        }
+        assert_eq!(treap.iter().len(), model.len());
+        let rebuilt: Treap<u32> = model.iter().rev().copied().collect();
+        assert!(rebuilt.iter().eq(model.iter()));
+        treap.clear();
+        assert!(treap.is_empty() && treap.first().is_none());
+    });
+}
+
+#[test]
+#[should_panic(expected = "overlaps")]
+fn treap_merge_rejects_overlapping_keys() {
+    let mut a: Treap<u32> = [1, 5].into_iter().collect();
+    a.merge([3].into_iter().collect());
+}
+
+#[test]
+fn implicit_treap_matches_vec() {
+    cases(1510, 200, |rng| {
+        let mut model: Vec<u64> = (0..rng.gen_range(50)).map(|_| rng.next_u64()).collect();
+        let mut seq: ImplicitTreap<u64> = model.iter().copied().collect();
+        for _ in 0..rng.gen_range(500) {
+            let n = model.len();
+            match rng.gen_range(8) {
+                0 | 1 => {
+                    let (i, v) = (rng.gen_range(n + 1), rng.next_u64());
+                    seq.insert(i, v);
+                    model.insert(i, v);
+                }
+                2 => {
+                    let v = rng.next_u64();
+                    seq.push(v);
+                    model.push(v);
+                }
+                3 if n > 0 => {
+                    let i = rng.gen_range(n);
+                    assert_eq!(seq.remove(i), model.remove(i));
+                }
+                4 => {
+                    let i = rng.gen_range(n + 1);
+                    if let Some(v) = seq.get_mut(i) {
+                        *v = v.wrapping_add(1);
+                    }
+                    if let Some(v) = model.get_mut(i) {
+                        *v = v.wrapping_add(1);
+                    }
+                }
+                5 => {
+                    // a cut and rejoin, sometimes the other way round
+                    let i = rng.gen_range(n + 1);
+                    let mut tail = seq.split_off(i);
+                    let model_tail = model.split_off(i);
+                    assert!(tail.iter().eq(model_tail.iter()));
+                    if rng.gen_bool(0.5) {
+                        seq.append(tail);
+                        model.extend(model_tail);
+                    } else {
+                        tail.append(seq);
+                        seq = tail;
+                        model.splice(0..0, model_tail);
+                    }
+                }
+                _ => {
+                    let i = rng.gen_range(n + 1);
+                    assert_eq!(seq.get(i), model.get(i));
+                }
+            }
+            assert_eq!(seq.len(), model.len());
+            assert_eq!(seq.is_empty(), model.is_empty());
+        }
+        assert!(seq.iter().eq(model.iter()));
+        seq.clear();
+        assert!(seq.is_empty() && seq.get(0).is_none());
+    });
+}
+
+// finding a key that is there, or inserting one, leaves it at the root
+#[test]
+fn splay_tree_matches_btree_map() {
+    cases(154, 200, |rng| {
+        let keys = 1 + rng.gen_range(300);
+        let mut tree = SplayTree::new();
+        let mut model = BTreeMap::new();
+        for _ in 0..rng.gen_range(600) {
+            let k = rng.gen_range(keys + 2) as u32;
+            match rng.gen_range(10) {
+                0..=3 => {
+                    let v = rng.next_u64();
+                    assert_eq!(tree.insert(k, v), model.insert(k, v));
+                    assert_eq!(tree.root_key(), Some(&k));
+                }
+                4 | 5 => assert_eq!(tree.delete(&k), model.remove(&k)),
+                6 => {
+                    assert_eq!(tree.find(&k), model.get(&k));
+                    if model.contains_key(&k) {
+                        assert_eq!(tree.root_key(), Some(&k));
+                    }
+                }
+                7 => {
+                    if let Some(v) = tree.find_mut(&k) {
+                        *v = v.wrapping_add(1);
+                    }
+                    if let Some(v) = model.get_mut(&k) {
+                        *v = v.wrapping_add(1);
+                    }
+                    assert_eq!(tree.contains(&k), model.contains_key(&k));
+                }
+                8 => {
+                    let mut upper = tree.split(&k);
+                    let model_upper = model.split_off(&k);
+                    assert!(tree.iter().eq(model.iter()));
+                    assert!(upper.iter().eq(model_upper.iter()));
+                    assert_eq!(upper.first(), model_upper.first_key_value());
+                    tree.join(upper);
+                    model.extend(model_upper);
+                }
+                _ => assert!(tree.iter().eq(model.iter())),
+            }
+            assert_eq!(tree.len(), model.len());
+            assert_eq!(tree.is_empty(), model.is_empty());
+            assert_eq!(tree.first(), model.first_key_value());
+            assert_eq!(tree.last(), model.last_key_value());
+        }
+        let rebuilt: SplayTree<u32, u64> = model.iter().map(|(&k, &v)| (k, v)).collect();
+        assert!(rebuilt.iter().eq(model.iter()));
+        tree.clear();
+        assert!(tree.is_empty() && tree.iter().next().is_none());
+    });
+}
+
+// sorted inserts leave a bare path, which must still drop and be searched
+#[test]
+fn splay_tree_survives_a_long_path() {
+    let n = 200_000;
+    let mut tree: SplayTree<u32, ()> = (0..n).map(|k| (k, ())).collect();
+    assert_eq!(tree.find(&0), Some(&()));
+    assert_eq!(tree.last(), Some((&(n - 1), &())));
+    assert_eq!(tree.iter().count(), n as usize);
+}
+
+#[test]
+#[should_panic(expected = "overlaps")]
+fn splay_tree_join_rejects_overlapping_keys() {
+    let mut a: SplayTree<u32, ()> = [(1, ()), (5, ())].into_iter().collect();
+    a.join([(3, ())].into_iter().collect());
+}
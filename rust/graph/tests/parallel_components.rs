@@ -0,0 +1,40 @@
+use graph::csr::CsrGraph;
+use graph::parallel::par_connected_components;
+use graph::rng::{Rng, SplitMix64};
+
+fn random_graph(rng: &mut SplitMix64, n: usize, m: usize) -> CsrGraph {
+    let edges: Vec<_> = (0..m)
+        .map(|_| (rng.gen_range(n), rng.gen_range(n), 1))
+        .collect();
+    CsrGraph::from_edges(n, &edges)
+}
+
+#[test]
+fn matches_sequential_on_random_graphs() {
+    let mut rng = SplitMix64::new(113);
+    for _ in 0..50 {
+        let n = 1 + rng.gen_range(5000);
+        let m = rng.gen_range(2 * n);
+        let g = random_graph(&mut rng, n, m);
+        assert_eq!(par_connected_components(&g), g.connected_components());
+    }
+}
+
+#[test]
+fn matches_sequential_on_large_sparse_graph() {
+    let mut rng = SplitMix64::new(7);
+    let g = random_graph(&mut rng, 200_000, 150_000);
+    assert_eq!(par_connected_components(&g), g.connected_components());
+}
+
+#[test]
+fn labels_are_smallest_vertex() {
+    let g = CsrGraph::from_edges(6, &[(5, 3, 1), (3, 4, 1), (1, 2, 1)]);
+    assert_eq!(par_connected_components(&g), vec![0, 1, 1, 3, 3, 3]);
+}
+
+#[test]
+fn empty_graph() {
+    let g = CsrGraph::from_edges(0, &[]);
+    assert!(par_connected_components(&g).is_empty());
+}
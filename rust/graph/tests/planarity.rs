@@ -0,0 +1,354 @@
+// Planarity on known families: embeddings are checked against Euler's
+// formula per component, and Kuratowski witnesses by smoothing their
+// degree two vertices away and comparing what's left with K5 or K3,3.
+
+mod common;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use graph::adjlist::Graph;
+use graph::planarity::{is_planar, Embedding, KuratowskiKind};
+use graph::rng::{Rng, SplitMix64};
+
+use common::cases;
+
+fn build(n: usize, edges: &[(usize, usize)]) -> Graph<usize> {
+    let mut g = Graph::new();
+    for v in 0..n {
+        g.add_vertex(&v);
+    }
+    for &(a, b) in edges {
+        g.add_edge(&a, &b, 1);
+        g.add_edge(&b, &a, 1);
+    }
+    g
+}
+
+fn simple(edges: &[(usize, usize)]) -> BTreeSet<(usize, usize)> {
+    edges
+        .iter()
+        .filter(|(a, b)| a != b)
+        .map(|&(a, b)| (a.min(b), a.max(b)))
+        .collect()
+}
+
+fn complete(n: usize) -> Vec<(usize, usize)> {
+    (0..n)
+        .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+        .collect()
+}
+
+fn k33() -> Vec<(usize, usize)> {
+    (0..3).flat_map(|a| (3..6).map(move |b| (a, b))).collect()
+}
+
+fn grid(rows: usize, cols: usize) -> (usize, Vec<(usize, usize)>) {
+    let mut edges = Vec::new();
+    for r in 0..rows {
+        for c in 0..cols {
+            let v = r * cols + c;
+            if c + 1 < cols {
+                edges.push((v, v + 1));
+            }
+            if r + 1 < rows {
+                edges.push((v, v + cols));
+            }
+        }
+    }
+    (rows * cols, edges)
+}
+
+// hub 0 and a rim cycle 1..=n
+fn wheel(n: usize) -> (usize, Vec<(usize, usize)>) {
+    let mut edges = Vec::new();
+    for i in 1..=n {
+        edges.push((0, i));
+        edges.push((i, i % n + 1));
+    }
+    (n + 1, edges)
+}
+
+// Stacked triangulation: every new vertex goes into a random face and is
+// joined to its three corners, 3n - 6 edges.
+fn maximal_planar(n: usize, rng: &mut SplitMix64) -> Vec<(usize, usize)> {
+    let mut edges = vec![(0, 1), (1, 2), (0, 2)];
+    let mut faces = vec![(0, 1, 2), (0, 1, 2)];
+    for v in 3..n {
+        let (a, b, c) = faces.swap_remove(rng.gen_range(faces.len()));
+        edges.extend([(a, v), (b, v), (c, v)]);
+        faces.extend([(a, b, v), (b, c, v), (a, c, v)]);
+    }
+    edges
+}
+
+// each edge replaced by a path through up to two new vertices
+fn subdivide(
+    n: usize,
+    edges: &[(usize, usize)],
+    rng: &mut SplitMix64,
+) -> (usize, Vec<(usize, usize)>) {
+    let mut next = n;
+    let mut out = Vec::new();
+    for &(a, b) in edges {
+        let mut prev = a;
+        for _ in 0..rng.gen_range(3) {
+            out.push((prev, next));
+            prev = next;
+            next += 1;
+        }
+        out.push((prev, b));
+    }
+    (next, out)
+}
+
+fn relabel(n: usize, edges: &mut [(usize, usize)], rng: &mut SplitMix64) {
+    let mut perm: Vec<usize> = (0..n).collect();
+    rng.shuffle(&mut perm);
+    for (a, b) in edges.iter_mut() {
+        (*a, *b) = (perm[*a], perm[*b]);
+    }
+}
+
+// Every vertex lists its distinct neighbors once, and tracing the faces
+// of the rotation system gives V - E + F = 2 in every component, an
+// isolated vertex counting as one face.
+fn check_embedding(n: usize, edges: &[(usize, usize)], embedding: &Embedding<usize>) {
+    let edges = simple(edges);
+    let mut adj = vec![BTreeSet::new(); n];
+    for &(a, b) in edges.iter() {
+        adj[a].insert(b);
+        adj[b].insert(a);
+    }
+    assert_eq!(embedding.rotation.len(), n);
+    let mut position: HashMap<(usize, usize), usize> = HashMap::new();
+    for (v, nbrs) in adj.iter().enumerate() {
+        let rotation = &embedding.rotation[&v];
+        assert_eq!(rotation.len(), nbrs.len(), "rotation of {v}");
+        assert_eq!(&rotation.iter().copied().collect::<BTreeSet<_>>(), nbrs);
+        for (i, &w) in rotation.iter().enumerate() {
+            position.insert((v, w), i);
+        }
+    }
+    // a face continues from dart (v, w) with the dart out of w after v
+    let mut seen = HashSet::new();
+    let mut faces = vec![0; n];
+    for (&(v, w), _) in position.iter() {
+        if !seen.insert((v, w)) {
+            continue;
+        }
+        let (mut a, mut b) = (v, w);
+        loop {
+            let around = &embedding.rotation[&b];
+            let c = around[(position[&(b, a)] + 1) % around.len()];
+            (a, b) = (b, c);
+            if !seen.insert((a, b)) {
+                break;
+            }
+        }
+        assert_eq!((a, b), (v, w), "faces don't close up");
+        faces[v] += 1;
+    }
+    // sum the counts over components
+    let mut component = vec![usize::MAX; n];
+    let mut totals = Vec::new();
+    for s in 0..n {
+        if component[s] != usize::MAX {
+            continue;
+        }
+        let id = totals.len();
+        let (mut vertices, mut degrees, mut face_count) = (0, 0, 0);
+        let mut stack = vec![s];
+        component[s] = id;
+        while let Some(v) = stack.pop() {
+            vertices += 1;
+            degrees += adj[v].len();
+            face_count += faces[v];
+            for &w in adj[v].iter() {
+                if component[w] == usize::MAX {
+                    component[w] = id;
+                    stack.push(w);
+                }
+            }
+        }
+        totals.push((vertices, degrees / 2, face_count.max(1)));
+    }
+    for (v, e, f) in totals {
+        assert_eq!(v as i64 - e as i64 + f as i64, 2, "V {v} E {e} F {f}");
+    }
+}
+
+// The witness is a subgraph whose degree two vertices, smoothed away,
+// leave exactly K5 or K3,3 as its kind says.
+fn check_witness(edges: &[(usize, usize)], kind: KuratowskiKind, witness: &[(usize, usize)]) {
+    let graph = simple(edges);
+    let witness = simple(witness);
+    assert!(witness.is_subset(&graph), "witness edges not in the graph");
+    let mut adj: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &(a, b) in witness.iter() {
+        adj.entry(a).or_default().push(b);
+        adj.entry(b).or_default().push(a);
+    }
+    let branch_degree = match kind {
+        KuratowskiKind::K5 => 4,
+        KuratowskiKind::K33 => 3,
+    };
+    let branch: Vec<usize> = adj
+        .iter()
+        .filter(|(_, nbrs)| nbrs.len() != 2)
+        .map(|(&v, nbrs)| {
+            assert_eq!(nbrs.len(), branch_degree, "degree of {v}");
+            v
+        })
+        .collect();
+    // follow every path out of a branch vertex to the next
+    let mut paths = BTreeSet::new();
+    let mut walked = 0;
+    for &s in branch.iter() {
+        for &first in adj[&s].iter() {
+            let (mut prev, mut cur) = (s, first);
+            let mut len = 1;
+            while adj[&cur].len() == 2 {
+                let next = adj[&cur].iter().copied().find(|&x| x != prev).unwrap();
+                (prev, cur) = (cur, next);
+                len += 1;
+            }
+            assert_ne!(s, cur, "path from {s} back to itself");
+            walked += len;
+            if s < cur {
+                assert!(paths.insert((s, cur)), "two paths {s} to {cur}");
+            }
+        }
+    }
+    // each path walked from both ends, none left over as a lone cycle
+    assert_eq!(walked, 2 * witness.len());
+    match kind {
+        KuratowskiKind::K5 => {
+            assert_eq!(branch.len(), 5);
+            assert_eq!(paths.len(), 10);
+        }
+        KuratowskiKind::K33 => {
+            assert_eq!(branch.len(), 6);
+            assert_eq!(paths.len(), 9);
+            // two sides of three, every path across
+            let mut side = HashMap::new();
+            side.insert(branch[0], 0);
+            for _ in 0..2 {
+                for &(a, b) in paths.iter() {
+                    if let Some(&s) = side.get(&a) {
+                        side.insert(b, 1 - s);
+                    } else if let Some(&s) = side.get(&b) {
+                        side.insert(a, 1 - s);
+                    }
+                }
+            }
+            assert!(paths.iter().all(|(a, b)| side[a] != side[b]));
+        }
+    }
+}
+
+fn planar(n: usize, edges: &[(usize, usize)]) {
+    match is_planar(&build(n, edges)) {
+        Ok(embedding) => check_embedding(n, edges, &embedding),
+        Err(k) => panic!("planar graph reported non-planar: {:?}", k.edges),
+    }
+}
+
+fn non_planar(n: usize, edges: &[(usize, usize)]) {
+    match is_planar(&build(n, edges)) {
+        Ok(_) => panic!("non-planar graph reported planar"),
+        Err(k) => check_witness(edges, k.kind, &k.edges),
+    }
+}
+
+#[test]
+fn kuratowski_graphs() {
+    non_planar(5, &complete(5));
+    non_planar(6, &k33());
+    match is_planar(&build(5, &complete(5))) {
+        Err(k) => assert_eq!(k.kind, KuratowskiKind::K5),
+        Ok(_) => unreachable!(),
+    }
+    match is_planar(&build(6, &k33())) {
+        Err(k) => assert_eq!(k.kind, KuratowskiKind::K33),
+        Ok(_) => unreachable!(),
+    }
+    // K5 and K3,3 less an edge are planar
+    planar(5, &complete(5)[1..]);
+    planar(6, &k33()[1..]);
+    // Petersen, which holds a K3,3 subdivision
+    let mut petersen = Vec::new();
+    for i in 0..5 {
+        petersen.extend([(i, (i + 1) % 5), (i, i + 5), (i + 5, (i + 2) % 5 + 5)]);
+    }
+    non_planar(10, &petersen);
+    non_planar(7, &complete(7));
+}
+
+#[test]
+fn subdivisions_with_extra_edges_stay_non_planar() {
+    cases(105, 200, |rng| {
+        let base = if rng.gen_bool(0.5) {
+            complete(5)
+        } else {
+            k33()
+        };
+        let (mut n, mut edges) = subdivide(6, &base, rng);
+        for _ in 0..rng.gen_range(6) {
+            edges.push((rng.gen_range(n), n));
+            n += 1;
+        }
+        for _ in 0..rng.gen_range(10) {
+            edges.push((rng.gen_range(n), rng.gen_range(n)));
+        }
+        relabel(n, &mut edges, rng);
+        non_planar(n, &edges);
+    });
+}
+
+#[test]
+fn planar_families() {
+    for (rows, cols) in [(1, 1), (1, 7), (2, 2), (5, 8), (12, 12)] {
+        let (n, edges) = grid(rows, cols);
+        planar(n, &edges);
+    }
+    for rim in 3..20 {
+        let (n, edges) = wheel(rim);
+        planar(n, &edges);
+    }
+    cases(1050, 100, |rng| {
+        let n = 3 + rng.gen_range(60);
+        let mut edges = maximal_planar(n, rng);
+        relabel(n, &mut edges, rng);
+        planar(n, &edges);
+        // random subgraphs of it, and duplicated edges, stay planar
+        let mut some: Vec<_> = edges
+            .iter()
+            .copied()
+            .filter(|_| rng.gen_bool(0.7))
+            .collect();
+        some.extend(edges.iter().copied().filter(|_| rng.gen_bool(0.1)));
+        planar(n, &some);
+        // one more edge breaks 3n - 6
+        let present = simple(&edges);
+        if let Some(&extra) = complete(n).iter().find(|e| !present.contains(e)) {
+            edges.push(extra);
+            non_planar(n, &edges);
+        }
+    });
+}
+
+#[test]
+fn disconnected_and_empty_graphs() {
+    planar(0, &[]);
+    planar(4, &[]);
+    // a grid, a wheel, a triangle with a self loop and isolated vertices
+    let (g, mut edges) = grid(4, 4);
+    let (w, spokes) = wheel(6);
+    edges.extend(spokes.iter().map(|&(a, b)| (a + g, b + g)));
+    let t = g + w;
+    edges.extend([(t, t + 1), (t + 1, t + 2), (t, t + 2), (t, t)]);
+    planar(t + 5, &edges);
+    // a K5 next to planar parts is still found
+    edges.extend(complete(5).iter().map(|&(a, b)| (a + t + 5, b + t + 5)));
+    non_planar(t + 10, &edges);
+}
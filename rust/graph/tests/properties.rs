@@ -0,0 +1,279 @@
+// Randomized properties of the algorithm modules. Every case runs with its
+// own seed; a failing case reports the seed so it can be replayed with
+// `cases(seed, 1, ..)` from tests/common.
+
+mod common;
+
+use std::collections::HashMap;
+
+use graph::adjlist::Graph;
+use graph::csr::CsrGraph;
+use graph::generators::{random_connected, random_edges, undirected};
+use graph::lca::{lca_offline, EulerTourLca};
+use graph::mst::{kruskal, prim};
+use graph::parallel::{par_bfs, par_connected_components};
+use graph::postman::chinese_postman;
+use graph::rng::Rng;
+use graph::scc::{kosaraju_scc, tarjan_scc};
+use graph::shortest_path::{bellman_ford, dijkstra};
+use graph::spanning_tree::{count_spanning_trees, random_spanning_tree, SpanningTreeCount};
+use graph::tree::diameter;
+
+use common::cases;
+
+fn build(n: usize, edges: &[(usize, usize, i32)]) -> Graph<usize> {
+    let mut g = Graph::new();
+    for v in 0..n {
+        g.add_vertex(&v);
+    }
+    for &(a, b, wt) in edges {
+        g.add_edge(&a, &b, wt);
+    }
+    g
+}
+
+fn bfs_all(n: usize, edges: &[(usize, usize, i32)]) -> Vec<Vec<Option<usize>>> {
+    let csr = CsrGraph::from_edges(n, edges);
+    (0..n).map(|s| csr.bfs(s)).collect()
+}
+
+#[test]
+fn dijkstra_matches_bellman_ford() {
+    cases(0, 300, |rng| {
+        let n = 1 + rng.gen_range(40);
+        let m = rng.gen_range(4 * n);
+        let g = build(n, &random_edges(n, m, 50, rng));
+        let source = rng.gen_range(n);
+        let d = dijkstra(&g, &source);
+        let b = bellman_ford(&g, &source).unwrap();
+        assert_eq!(d.dist, b.dist);
+        for (v, &dist) in d.dist.iter() {
+            let path = d.path_to(v).unwrap();
+            let total: i64 = path
+                .windows(2)
+                .map(|w| *g.get_vertex(&w[0]).unwrap().get_nbr_weight(&w[1]) as i64)
+                .sum();
+            assert!(total >= dist, "path cheaper than the reported distance");
+        }
+    });
+}
+
+#[test]
+fn bellman_ford_finds_negative_cycles() {
+    cases(1000, 100, |rng| {
+        let n = 2 + rng.gen_range(20);
+        let mut edges = random_edges(n, 2 * n, 50, rng);
+        // a reachable cycle 0 -> a -> 0 of negative total weight
+        let a = 1 + rng.gen_range(n - 1);
+        edges.push((0, a, 1));
+        edges.push((a, 0, -2));
+        assert!(bellman_ford(&build(n, &edges), &0).is_err());
+    });
+}
+
+#[test]
+fn prim_and_kruskal_agree() {
+    cases(2000, 300, |rng| {
+        let n = 1 + rng.gen_range(60);
+        let edges = undirected(&random_edges(n, 2 * n, 30, rng));
+        let g = build(n, &edges);
+        let (k, p) = (kruskal(&g), prim(&g));
+        assert_eq!(k.weight, p.weight);
+        let csr = CsrGraph::from_edges(n, &edges);
+        let mut roots = csr.connected_components();
+        roots.sort_unstable();
+        roots.dedup();
+        assert_eq!(k.edges.len(), n - roots.len());
+        assert_eq!(p.edges.len(), n - roots.len());
+    });
+}
+
+fn normalized(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    for c in components.iter_mut() {
+        c.sort_unstable();
+    }
+    components.sort();
+    components
+}
+
+#[test]
+fn tarjan_and_kosaraju_agree() {
+    cases(3000, 300, |rng| {
+        let n = 1 + rng.gen_range(50);
+        let m = rng.gen_range(3 * n);
+        let edges = random_edges(n, m, 1, rng);
+        let g = build(n, &edges);
+        let t = normalized(tarjan_scc(&g));
+        assert_eq!(t, normalized(kosaraju_scc(&g)));
+        // same component iff mutually reachable
+        let reach = bfs_all(n, &edges);
+        let mut comp = vec![0; n];
+        for (i, c) in t.iter().enumerate() {
+            for &v in c {
+                comp[v] = i;
+            }
+        }
+        for a in 0..n {
+            for b in 0..n {
+                let mutual = reach[a][b].is_some() && reach[b][a].is_some();
+                assert_eq!(mutual, comp[a] == comp[b]);
+            }
+        }
+    });
+}
+
+#[test]
+fn csr_round_trips_adjlist() {
+    cases(4000, 200, |rng| {
+        let n = 1 + rng.gen_range(50);
+        let edges = random_edges(n, 3 * n, 100, rng);
+        let (csr, keys) = CsrGraph::from_adjlist(&build(n, &edges));
+        let mut back: Vec<_> = (0..csr.vertex_count())
+            .flat_map(|v| {
+                csr.edges(v)
+                    .map(move |(u, wt)| (v, u, wt))
+                    .collect::<Vec<_>>()
+            })
+            .map(|(v, u, wt)| (keys[v], keys[u], wt))
+            .collect();
+        let mut expect = edges.clone();
+        back.sort_unstable();
+        expect.sort_unstable();
+        assert_eq!(back, expect);
+    });
+}
+
+#[test]
+fn parallel_traversals_match_sequential() {
+    cases(5000, 100, |rng| {
+        let n = 1 + rng.gen_range(3000);
+        let csr = CsrGraph::from_edges(n, &random_edges(n, 2 * n, 1, rng));
+        let s = rng.gen_range(n);
+        assert_eq!(par_bfs(&csr, s), csr.bfs(s));
+        assert_eq!(par_connected_components(&csr), csr.connected_components());
+    });
+}
+
+#[test]
+fn tree_diameter_is_longest_shortest_path() {
+    cases(6000, 200, |rng| {
+        let n = 1 + rng.gen_range(60);
+        let edges = undirected(&random_connected(n, 0, 1, rng));
+        let d = diameter(&build(n, &edges)).unwrap();
+        let longest = bfs_all(n, &edges)
+            .into_iter()
+            .flatten()
+            .map(|d| d.unwrap())
+            .max()
+            .unwrap();
+        assert_eq!(d.length, longest);
+        assert_eq!(d.path.len(), longest + 1);
+    });
+}
+
+#[test]
+fn lca_matches_parent_walk() {
+    cases(7000, 200, |rng| {
+        let n = 1 + rng.gen_range(80);
+        let tree = random_connected(n, 0, 1, rng);
+        // random_connected links every v > 0 to a smaller parent
+        let mut parent = vec![usize::MAX; n];
+        for &(p, v, _) in tree.iter() {
+            parent[v] = p;
+        }
+        let ancestors = |mut v: usize| {
+            let mut path = vec![v];
+            while parent[v] != usize::MAX {
+                v = parent[v];
+                path.push(v);
+            }
+            path
+        };
+        let queries: Vec<(usize, usize)> = (0..50)
+            .map(|_| (rng.gen_range(n), rng.gen_range(n)))
+            .collect();
+        let g = build(n, &undirected(&tree));
+        let answers = lca_offline(&g, &0, &queries).unwrap();
+        let online = EulerTourLca::new(&g, &0).unwrap();
+        for (&(u, v), &lca) in queries.iter().zip(answers.iter()) {
+            let above_u = ancestors(u);
+            let expect = ancestors(v)
+                .into_iter()
+                .find(|a| above_u.contains(a))
+                .unwrap();
+            assert_eq!(lca, expect);
+            assert_eq!(*online.lca(&u, &v).unwrap(), expect);
+            assert_eq!(online.depth(&u).unwrap(), above_u.len() - 1);
+        }
+    });
+}
+
+#[test]
+fn spanning_tree_count_matches_enumeration() {
+    cases(8000, 150, |rng| {
+        let n = 1 + rng.gen_range(6);
+        let simple = random_edges(n, rng.gen_range(10), 1, rng);
+        let simple: Vec<_> = simple.into_iter().filter(|&(a, b, _)| a != b).collect();
+        let g = build(n, &undirected(&simple));
+        let mut trees = 0u128;
+        for mask in 0u32..1 << simple.len() {
+            if mask.count_ones() as usize + 1 != n {
+                continue;
+            }
+            let chosen: Vec<_> = (0..simple.len())
+                .filter(|&i| mask & (1 << i) != 0)
+                .map(|i| simple[i])
+                .collect();
+            let mut comps = CsrGraph::from_edges(n, &chosen).connected_components();
+            comps.dedup();
+            if comps.iter().all(|&c| c == 0) {
+                trees += 1;
+            }
+        }
+        assert_eq!(count_spanning_trees(&g), SpanningTreeCount::Exact(trees));
+        if let Some(sample) = random_spanning_tree(&g, rng) {
+            assert_eq!(sample.len() + 1, n);
+            let ids: Vec<_> = sample.iter().map(|&(a, b)| (a, b, 1)).collect();
+            let labels = CsrGraph::from_edges(n, &ids).connected_components();
+            assert!(labels.iter().all(|&c| c == 0));
+        }
+    });
+}
+
+// one edge without its reverse, which a walk could follow to a dead end
+#[test]
+fn random_spanning_tree_rejects_directed_edges() {
+    let g = build(2, &[(0, 1, 1)]);
+    cases(8100, 50, |rng| {
+        assert_eq!(random_spanning_tree(&g, rng), None)
+    });
+    cases(8200, 150, |rng| {
+        let n = 2 + rng.gen_range(8);
+        let base = random_connected(n, rng.gen_range(2 * n), 1, rng);
+        let mut edges = undirected(&base);
+        let (a, b) = (rng.gen_range(n), rng.gen_range(n));
+        if a != b && !edges.iter().any(|&(x, y, _)| (x, y) == (b, a)) {
+            edges.push((a, b, 1));
+            assert_eq!(random_spanning_tree(&build(n, &edges), rng), None);
+        }
+    });
+}
+
+#[test]
+fn postman_route_covers_every_edge() {
+    cases(9000, 150, |rng| {
+        let n = 2 + rng.gen_range(12);
+        let base = random_connected(n, rng.gen_range(2 * n), 20, rng);
+        let route = chinese_postman(&build(n, &undirected(&base))).unwrap();
+        let total: i64 = base.iter().map(|&(_, _, wt)| wt as i64).sum();
+        assert!(route.cost >= total);
+        assert_eq!(route.walk.first(), route.walk.last());
+        let mut walked: HashMap<(usize, usize), usize> = HashMap::new();
+        for w in route.walk.windows(2) {
+            *walked.entry((w[0].min(w[1]), w[0].max(w[1]))).or_default() += 1;
+        }
+        for &(a, b, _) in base.iter() {
+            assert!(walked.contains_key(&(a.min(b), a.max(b))));
+        }
+    });
+}
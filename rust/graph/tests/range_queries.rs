@@ -0,0 +1,329 @@
+// The range query structures against scans of a plain Vec, on random
+// updates and every kind of range, empty ones and the whole array
+// included.
+
+mod common;
+
+use std::ops::Range;
+
+use graph::fenwick::{Fenwick, Fenwick2D, PrefixSum2D};
+use graph::num::gcd;
+use graph::persistent_segment_tree::{PersistentSegmentTree, RangeKth};
+use graph::rng::{Rng, SplitMix64};
+use graph::segment_tree::{
+    AddMin, AddSum, AssignMin, AssignSum, Gcd, LazyOp, LazySegmentTree, Max, Min, Monoid,
+    SegmentTree, Sum,
+};
+use graph::sparse_table::{IdempotentOp, SparseTable};
+
+use common::cases;
+
+fn values(n: usize, rng: &mut SplitMix64) -> Vec<i64> {
+    (0..n).map(|_| rng.gen_range(100) as i64 - 50).collect()
+}
+
+fn some_range(n: usize, rng: &mut SplitMix64) -> Range<usize> {
+    let (a, b) = (rng.gen_range(n + 1), rng.gen_range(n + 1));
+    a.min(b)..a.max(b)
+}
+
+fn sum(s: &[i64]) -> i64 {
+    s.iter().sum()
+}
+
+fn min(s: &[i64]) -> i64 {
+    s.iter().copied().min().unwrap_or(i64::MAX)
+}
+
+fn max(s: &[i64]) -> i64 {
+    s.iter().copied().max().unwrap_or(i64::MIN)
+}
+
+fn gcd_of(s: &[i64]) -> i64 {
+    s.iter().fold(0, |g, &x| gcd(g, x))
+}
+
+// not commutative, so a query that combines out of order shows
+struct Concat;
+
+impl Monoid<Vec<u8>> for Concat {
+    fn identity() -> Vec<u8> {
+        Vec::new()
+    }
+    fn combine(a: &Vec<u8>, b: &Vec<u8>) -> Vec<u8> {
+        [&a[..], &b[..]].concat()
+    }
+}
+
+fn point_updates<Op: Monoid<i64>>(fold: fn(&[i64]) -> i64, rng: &mut SplitMix64) {
+    let n = rng.gen_range(100);
+    let mut model = values(n, rng);
+    let mut tree = SegmentTree::<i64, Op>::from(model.clone());
+    assert_eq!(tree.len(), n);
+    for _ in 0..200 {
+        if n > 0 && rng.gen_bool(0.5) {
+            let (i, value) = (rng.gen_range(n), rng.gen_range(100) as i64 - 50);
+            tree.update(i, value);
+            model[i] = value;
+            assert_eq!(*tree.get(i), value);
+        }
+        let range = some_range(n, rng);
+        assert_eq!(tree.query(range.clone()), fold(&model[range]));
+    }
+    assert_eq!(tree.all(), fold(&model));
+}
+
+#[test]
+fn segment_tree_matches_slice_scans() {
+    cases(138, 200, |rng| {
+        point_updates::<Sum>(sum, rng);
+        point_updates::<Min>(min, rng);
+        point_updates::<Max>(max, rng);
+        point_updates::<Gcd>(gcd_of, rng);
+    });
+}
+
+#[test]
+fn segment_tree_combines_left_to_right() {
+    cases(1380, 200, |rng| {
+        let n = rng.gen_range(50);
+        let mut model: Vec<Vec<u8>> = (0..n).map(|i| vec![i as u8]).collect();
+        let mut tree: SegmentTree<Vec<u8>, Concat> = model.iter().cloned().collect();
+        for _ in 0..100 {
+            if n > 0 && rng.gen_bool(0.3) {
+                let i = rng.gen_range(n);
+                let piece = vec![b'a' + rng.gen_range(26) as u8; rng.gen_range(3)];
+                tree.update(i, piece.clone());
+                model[i] = piece;
+            }
+            let range = some_range(n, rng);
+            assert_eq!(tree.query(range.clone()), model[range].concat());
+        }
+        // a tree built empty holds identities until updated
+        let empty = SegmentTree::<Vec<u8>, Concat>::new(n);
+        assert!(empty.all().is_empty());
+    });
+}
+
+fn range_updates<Op: LazyOp<i64>>(
+    fold: fn(&[i64]) -> i64,
+    tag: fn(&mut SplitMix64) -> Op::Tag,
+    apply: fn(&Op::Tag, &mut i64),
+    rng: &mut SplitMix64,
+) {
+    let n = rng.gen_range(100);
+    let mut model = values(n, rng);
+    let mut tree = if rng.gen_bool(0.2) {
+        model = vec![7; n];
+        LazySegmentTree::<i64, Op>::filled(n, 7)
+    } else {
+        LazySegmentTree::<i64, Op>::from(model.clone())
+    };
+    let mut before = (tree.clone(), model.clone());
+    for step in 0..200 {
+        let range = some_range(n, rng);
+        if rng.gen_bool(0.5) {
+            let t = tag(rng);
+            tree.update(range.clone(), t.clone());
+            model[range].iter_mut().for_each(|x| apply(&t, x));
+        } else {
+            assert_eq!(tree.query(range.clone()), fold(&model[range]));
+        }
+        if n > 0 && step % 20 == 0 {
+            let i = rng.gen_range(n);
+            assert_eq!(tree.get(i), model[i]);
+            before = (tree.clone(), model.clone());
+        }
+    }
+    assert_eq!(tree.all(), fold(&model));
+    // a clone taken earlier kept its own tags
+    let (old, old_model) = before;
+    assert!((0..n).all(|i| old.get(i) == old_model[i]));
+}
+
+fn add_tag(rng: &mut SplitMix64) -> i64 {
+    rng.gen_range(20) as i64 - 10
+}
+
+fn assign_tag(rng: &mut SplitMix64) -> Option<i64> {
+    rng.gen_bool(0.8).then(|| rng.gen_range(100) as i64 - 50)
+}
+
+fn add(tag: &i64, x: &mut i64) {
+    *x += tag;
+}
+
+fn assign(tag: &Option<i64>, x: &mut i64) {
+    if let Some(v) = tag {
+        *x = *v;
+    }
+}
+
+#[test]
+fn lazy_segment_tree_matches_slice_scans() {
+    cases(139, 200, |rng| {
+        range_updates::<AddSum>(sum, add_tag, add, rng);
+        range_updates::<AddMin>(min, add_tag, add, rng);
+        range_updates::<AssignSum>(sum, assign_tag, assign, rng);
+        range_updates::<AssignMin>(min, assign_tag, assign, rng);
+    });
+}
+
+fn persistent<Op: Monoid<i64>>(fold: fn(&[i64]) -> i64, rng: &mut SplitMix64) {
+    let n = 1 + rng.gen_range(60);
+    let mut models = vec![values(n, rng)];
+    let mut tree = PersistentSegmentTree::<i64, Op>::new(models[0].clone());
+    let mut versions = vec![tree.initial()];
+    for _ in 0..100 {
+        // from any version, not just the latest
+        let from = rng.gen_range(versions.len());
+        let (i, value) = (rng.gen_range(n), rng.gen_range(100) as i64 - 50);
+        versions.push(tree.update(versions[from], i, value));
+        let mut model = models[from].clone();
+        model[i] = value;
+        models.push(model);
+        assert_eq!(tree.latest(), *versions.last().unwrap());
+    }
+    assert_eq!(tree.version_count(), versions.len());
+    for (&version, model) in versions.iter().zip(&models) {
+        let i = rng.gen_range(n);
+        assert_eq!(*tree.get(version, i), model[i]);
+        for _ in 0..5 {
+            let range = some_range(n, rng);
+            assert_eq!(tree.query(version, range.clone()), fold(&model[range]));
+        }
+    }
+}
+
+#[test]
+fn persistent_segment_tree_keeps_every_version() {
+    cases(140, 200, |rng| {
+        persistent::<Sum>(sum, rng);
+        persistent::<Min>(min, rng);
+    });
+}
+
+#[test]
+fn range_kth_matches_sorted_slices() {
+    cases(1400, 200, |rng| {
+        let n = rng.gen_range(80);
+        let distinct = 1 + rng.gen_range(20);
+        let values: Vec<u32> = (0..n).map(|_| rng.gen_range(distinct) as u32).collect();
+        let kth = RangeKth::new(&values);
+        assert_eq!(kth.len(), n);
+        for _ in 0..50 {
+            let range = some_range(n, rng);
+            let mut sorted = values[range.clone()].to_vec();
+            sorted.sort_unstable();
+            for k in 0..=sorted.len() {
+                assert_eq!(kth.kth(range.clone(), k), sorted.get(k));
+            }
+            let x = rng.gen_range(distinct + 2) as u32;
+            let less = sorted.iter().filter(|&&v| v < x).count();
+            assert_eq!(kth.count_less(range, &x), less);
+        }
+    });
+}
+
+#[test]
+fn fenwick_matches_prefix_scans() {
+    cases(141, 300, |rng| {
+        let n = rng.gen_range(100);
+        // counts, so kth has positions to find
+        let mut model: Vec<i64> = (0..n).map(|_| rng.gen_range(4) as i64).collect();
+        let mut tree = if rng.gen_bool(0.5) {
+            Fenwick::from(model.clone())
+        } else {
+            let mut tree = Fenwick::new(n);
+            for (i, &x) in model.iter().enumerate() {
+                tree.add(i, x);
+            }
+            tree
+        };
+        for _ in 0..100 {
+            if n > 0 && rng.gen_bool(0.5) {
+                let i = rng.gen_range(n);
+                if rng.gen_bool(0.5) {
+                    let delta = rng.gen_range(3) as i64;
+                    tree.add(i, delta);
+                    model[i] += delta;
+                } else {
+                    let value = rng.gen_range(4) as i64;
+                    tree.set(i, value);
+                    model[i] = value;
+                }
+                assert_eq!(tree.get(i), model[i]);
+            }
+            let end = rng.gen_range(n + 1);
+            assert_eq!(tree.prefix(end), sum(&model[..end]));
+            let range = some_range(n, rng);
+            assert_eq!(tree.range_sum(range.clone()), sum(&model[range]));
+        }
+        let total = sum(&model);
+        for k in 0..=total {
+            let expected = (0..n).find(|&i| sum(&model[..=i]) > k);
+            assert_eq!(tree.kth(k), expected, "k = {k}");
+        }
+    });
+}
+
+#[test]
+fn fenwick_2d_matches_grid_scans() {
+    cases(142, 200, |rng| {
+        let (rows, cols) = (rng.gen_range(12), rng.gen_range(12));
+        let mut grid: Vec<Vec<i64>> = (0..rows).map(|_| values(cols, rng)).collect();
+        let mut tree = Fenwick2D::new(rows, cols);
+        for (r, row) in grid.iter().enumerate() {
+            for (c, &x) in row.iter().enumerate() {
+                tree.add(r, c, x);
+            }
+        }
+        assert_eq!((tree.rows(), tree.cols()), (rows, cols));
+        let rect = |grid: &[Vec<i64>], rs: Range<usize>, cs: Range<usize>| -> i64 {
+            grid[rs].iter().map(|row| sum(&row[cs.clone()])).sum()
+        };
+        for _ in 0..100 {
+            if rows > 0 && cols > 0 && rng.gen_bool(0.5) {
+                let (r, c) = (rng.gen_range(rows), rng.gen_range(cols));
+                let delta = rng.gen_range(20) as i64 - 10;
+                tree.add(r, c, delta);
+                grid[r][c] += delta;
+                assert_eq!(tree.get(r, c), grid[r][c]);
+            }
+            let (rs, cs) = (some_range(rows, rng), some_range(cols, rng));
+            assert_eq!(tree.rect_sum(rs.clone(), cs.clone()), rect(&grid, rs, cs));
+        }
+        // without a row there is no width to read off
+        if rows == 0 {
+            return;
+        }
+        let prefix = PrefixSum2D::new(&grid);
+        assert_eq!((prefix.rows(), prefix.cols()), (rows, cols));
+        for _ in 0..50 {
+            let (rs, cs) = (some_range(rows, rng), some_range(cols, rng));
+            assert_eq!(prefix.rect_sum(rs.clone(), cs.clone()), rect(&grid, rs, cs));
+            let (r, c) = (rng.gen_range(rows + 1), rng.gen_range(cols + 1));
+            assert_eq!(prefix.prefix(r, c), tree.prefix(r, c));
+        }
+    });
+}
+
+fn sparse<Op: IdempotentOp<i64>>(fold: fn(&[i64]) -> i64, rng: &mut SplitMix64) {
+    let n = rng.gen_range(200);
+    let model = values(n, rng);
+    let table = SparseTable::<i64, Op>::new(model.clone());
+    assert_eq!(table.len(), n);
+    for _ in 0..200 {
+        let range = some_range(n, rng);
+        assert_eq!(table.query(range.clone()), fold(&model[range]));
+    }
+}
+
+#[test]
+fn sparse_table_matches_slice_scans() {
+    cases(143, 200, |rng| {
+        sparse::<Min>(min, rng);
+        sparse::<Max>(max, rng);
+        sparse::<Gcd>(gcd_of, rng);
+    });
+}
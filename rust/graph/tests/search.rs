@@ -0,0 +1,186 @@
+// Float searches on brackets wider than f64::MAX, where hi - lo overflows,
+// and the integer and slice searches against std's partition_point and
+// binary_search, on sorted data evenly spread, skewed and full of repeats.
+
+mod common;
+
+use std::cmp::Reverse;
+
+use graph::rng::{Rng, SplitMix64};
+use graph::search::{
+    bisect, equal_range, exponential_partition_point, exponential_search, exponential_search_by,
+    interpolation_search, lower_bound, lower_bound_by, lower_bound_by_key, partition_point,
+    slice_partition_point, ternary_search_min, ternary_search_min_f64, upper_bound, upper_bound_by,
+    upper_bound_by_key, Bisection,
+};
+
+use common::cases;
+
+#[test]
+fn bisection_on_brackets_wider_than_f64_max() {
+    for (lo, hi) in [(-1e308, 1e308), (f64::MIN, f64::MAX), (1e308, -1e308)] {
+        assert_eq!(bisect(lo, hi, |x| x < 0.0), 0.0);
+        let x = bisect(lo, hi, |x| x < 1e300);
+        assert!(x.is_finite() && (x - 1e300).abs() <= 1e300 * f64::EPSILON);
+        let root = Bisection::new().root(lo, hi, |x| x - 12.5).unwrap();
+        assert_eq!(root, 12.5);
+        let coarse = Bisection::new().tolerance(1.0).root(lo, hi, |x| x + 3.0);
+        assert!((coarse.unwrap() + 3.0).abs() <= 1.0);
+    }
+}
+
+#[test]
+fn ternary_search_on_brackets_wider_than_f64_max() {
+    for (lo, hi) in [(-1e308, 1e308), (f64::MIN, f64::MAX)] {
+        let x = ternary_search_min_f64(lo, hi, 0.0, |x| (x - 7.0).abs());
+        assert!((x - 7.0).abs() <= 1e-9, "{x}");
+        let far = ternary_search_min_f64(lo, hi, 1e290, |x| (x - 1e300).abs());
+        assert!(far.is_finite() && (far - 1e300).abs() <= 1e290);
+    }
+}
+
+// sorted keys, evenly spread, bunched towards one end, repeated, or out at
+// the ends of i64 where differences need i128
+fn sorted_keys(rng: &mut SplitMix64) -> Vec<i64> {
+    let len = rng.gen_range(500);
+    let mut v: Vec<i64> = match rng.gen_range(4) {
+        0 => (0..len).map(|_| rng.gen_range(10_000) as i64).collect(),
+        1 => (0..len)
+            .map(|_| (rng.gen_range(1000) as i64).pow(3))
+            .collect(),
+        2 => (0..len).map(|_| rng.gen_range(5) as i64).collect(),
+        _ => (0..len).map(|_| rng.next_u64() as i64).collect(),
+    };
+    if rng.gen_bool(0.2) {
+        v.extend([i64::MIN, i64::MAX]);
+    }
+    v.sort();
+    v
+}
+
+fn targets(v: &[i64], rng: &mut SplitMix64) -> Vec<i64> {
+    let mut targets = vec![i64::MIN, i64::MAX, 0, -1];
+    for _ in 0..30 {
+        targets.push(match v.len() {
+            0 => rng.next_u64() as i64,
+            n => v[rng.gen_range(n)].saturating_add(rng.gen_range(3) as i64 - 1),
+        });
+    }
+    targets
+}
+
+// Ok must point at an equal item, Err where std would insert
+fn check_search(v: &[i64], x: i64, found: Result<usize, usize>) {
+    let at = v.partition_point(|&y| y < x);
+    match found {
+        Ok(i) => assert_eq!(v[i], x),
+        Err(i) => {
+            assert_eq!(i, at);
+            assert!(v.binary_search(&x).is_err());
+        }
+    }
+}
+
+#[test]
+fn slice_searches_match_std() {
+    cases(195, 300, |rng| {
+        let v = sorted_keys(rng);
+        for x in targets(&v, rng) {
+            let lower = v.partition_point(|&y| y < x);
+            let upper = v.partition_point(|&y| y <= x);
+            assert_eq!(lower_bound(&v, &x), lower);
+            assert_eq!(lower_bound_by(&v, |y| y.cmp(&x)), lower);
+            assert_eq!(
+                lower_bound_by_key(&v, &i128::from(x), |&y| i128::from(y)),
+                lower
+            );
+            assert_eq!(upper_bound(&v, &x), upper);
+            assert_eq!(upper_bound_by(&v, |y| y.cmp(&x)), upper);
+            assert_eq!(
+                upper_bound_by_key(&v, &i128::from(x), |&y| i128::from(y)),
+                upper
+            );
+            assert_eq!(equal_range(&v, &x), lower..upper);
+            assert_eq!(slice_partition_point(&v, |&y| y < x), lower);
+            check_search(&v, x, interpolation_search(&v, x));
+            // the first of equal items, as it walks from the front
+            let first = if lower < upper { Ok(lower) } else { Err(lower) };
+            assert_eq!(exponential_search(&v, &x), first);
+            assert_eq!(exponential_search_by(&v, |y| y.cmp(&x)), first);
+        }
+    });
+}
+
+// every start and threshold over u8 and i8, so the probes run into MAX
+#[test]
+fn exponential_partition_point_up_to_max() {
+    for lo in 0..=u8::MAX {
+        for t in 0..=256u16 {
+            let expected = (t <= 255).then(|| (t as u8).max(lo));
+            let found = exponential_partition_point(lo, |x| u16::from(x) < t);
+            assert_eq!(found, expected, "lo {lo}, t {t}");
+            if lo as u16 <= t && t <= 255 {
+                assert_eq!(partition_point(lo, u8::MAX, |x| u16::from(x) < t), t as u8);
+            }
+        }
+    }
+    for lo in i8::MIN..=i8::MAX {
+        for t in i8::MIN as i16..=i8::MAX as i16 + 1 {
+            let expected = (t <= 127).then(|| (t as i8).max(lo));
+            assert_eq!(
+                exponential_partition_point(lo, |x| i16::from(x) < t),
+                expected
+            );
+        }
+    }
+    cases(196, 200, |rng| {
+        let lo = u64::MAX - rng.gen_range(1000) as u64 - (rng.next_u64() >> rng.gen_range(64));
+        let t = lo.saturating_add(rng.next_u64() >> rng.gen_range(64));
+        let found = exponential_partition_point(lo, |x| x < t);
+        assert_eq!(found, Some(t));
+        assert_eq!(
+            exponential_partition_point(lo, |x| x <= t),
+            t.checked_add(1)
+        );
+        let lo = i64::MAX - rng.gen_range(100) as i64;
+        let t = lo.saturating_add(rng.gen_range(200) as i64);
+        assert_eq!(
+            exponential_partition_point(lo, |x| x <= t),
+            t.checked_add(1)
+        );
+    });
+}
+
+// strictly down then strictly up, either side possibly empty, tabulated
+// over lo..=hi
+fn unimodal(len: usize, rng: &mut SplitMix64) -> (Vec<i64>, usize) {
+    let bottom = rng.gen_range(len);
+    let mut table = vec![0; len];
+    for i in (0..bottom).rev() {
+        table[i] = table[i + 1] + 1 + rng.gen_range(5) as i64;
+    }
+    for i in bottom + 1..len {
+        table[i] = table[i - 1] + 1 + rng.gen_range(5) as i64;
+    }
+    (table, bottom)
+}
+
+#[test]
+fn ternary_search_min_finds_the_bottom() {
+    cases(1960, 500, |rng| {
+        let len = 1 + rng.gen_range(200);
+        let (table, bottom) = unimodal(len, rng);
+        let lo = rng.gen_range(1000) as i64 - 500;
+        let hi = lo + len as i64 - 1;
+        let at = |x: i64| table[(x - lo) as usize];
+        assert_eq!(ternary_search_min(lo, hi, at), lo + bottom as i64);
+        // maximized through Reverse
+        let peak = ternary_search_min(lo, hi, |x| Reverse(-at(x)));
+        assert_eq!(peak, lo + bottom as i64);
+    });
+    // the whole of u8, the bottom anywhere in it
+    for m in [0, 1, 127, 254, 255] {
+        let found = ternary_search_min(0u8, u8::MAX, |x| (i32::from(x) - m).abs());
+        assert_eq!(i32::from(found), m);
+    }
+}
@@ -0,0 +1,507 @@
+// Stability tests for the mergesorts: records are (key, id) with few
+// distinct keys and ids in input order, so a stable sort by key is the one
+// order std's stable sort gives. Every other sort and selection is checked
+// against std's sort too, on inputs shaped like the cases they treat
+// specially.
+
+mod common;
+
+use std::{
+    cmp::{Ordering, Reverse},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use graph::priority_queue::Compare;
+use graph::rng::{Rng, SplitMix64};
+use graph::sort::{
+    bucket_sort, bucket_sort_by_key, counting_sort, counting_sort_by_key, external_sort, heapsort,
+    heapsort_by, heapsort_by_key, insertion_sort, introsort, introsort_by, introsort_by_key,
+    kth_smallest, kth_smallest_by, kth_smallest_deterministic, kth_smallest_deterministic_by_key,
+    merge_sort, merge_sort_bottom_up, merge_sort_bottom_up_by, merge_sort_bottom_up_by_key,
+    merge_sort_by, merge_sort_by_key, msd_radix_sort, par_merge_sort, par_merge_sort_by_key,
+    par_quicksort, par_quicksort_by, partial_sort, partial_sort_by_key, quicksort, quicksort_by,
+    quicksort_by_key, radix_sort, radix_sort_by_key, tim_sort, tim_sort_by, tim_sort_by_key, top_k,
+    top_k_by, top_k_by_key, ExternalSorter, LeBytes, Lines, MergeSorter, ParSort, Pivot, Quicksort,
+    SortingNetwork,
+};
+
+use common::cases;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Record {
+    key: u32,
+    id: usize,
+}
+
+// lengths around the run and merge boundaries, then some long ones
+fn records(rng: &mut SplitMix64) -> Vec<Record> {
+    let len = match rng.gen_range(3) {
+        0 => rng.gen_range(40),
+        1 => rng.gen_range(300),
+        _ => rng.gen_range(5000),
+    };
+    let few = rng.gen_bool(0.5);
+    let keys = 1 + rng.gen_range(if few { 4 } else { 1000 });
+    let mut records: Vec<Record> = (0..len)
+        .map(|id| Record {
+            key: rng.gen_range(keys) as u32,
+            id,
+        })
+        .collect();
+    if rng.gen_bool(0.2) {
+        // mostly ordered already
+        records.sort_by_key(|r| r.key);
+        for i in 0..len / 10 {
+            records.swap(i, rng.gen_range(len));
+        }
+        for (id, r) in records.iter_mut().enumerate() {
+            r.id = id;
+        }
+    }
+    records
+}
+
+fn stable(records: &[Record]) -> Vec<Record> {
+    let mut expected = records.to_vec();
+    expected.sort_by_key(|r| r.key);
+    expected
+}
+
+#[test]
+fn mergesorts_are_stable() {
+    cases(0, 300, |rng| {
+        let input = records(rng);
+        let expected = stable(&input);
+        let sorts: [fn(&mut Vec<Record>); 4] = [
+            |v| merge_sort_by_key(v, |r| r.key),
+            |v| merge_sort_by(v, |a, b| a.key.cmp(&b.key)),
+            |v| merge_sort_bottom_up_by_key(v, |r| r.key),
+            |v| merge_sort_bottom_up_by(v, |a, b| a.key.cmp(&b.key)),
+        ];
+        for sort in sorts {
+            let mut v = input.clone();
+            sort(&mut v);
+            assert_eq!(v, expected);
+        }
+    });
+}
+
+#[test]
+fn descending_keys_stay_stable() {
+    cases(1000, 100, |rng| {
+        let input = records(rng);
+        let mut expected = input.clone();
+        expected.sort_by_key(|r| Reverse(r.key));
+        let mut v = input.clone();
+        merge_sort_by(&mut v, |a, b| b.key.cmp(&a.key));
+        assert_eq!(v, expected);
+        let mut v = input;
+        merge_sort_bottom_up_by(&mut v, |a, b| b.key.cmp(&a.key));
+        assert_eq!(v, expected);
+    });
+}
+
+#[test]
+fn sorter_reuses_its_buffer() {
+    let mut sorter = MergeSorter::new();
+    let mut rng = SplitMix64::new(7);
+    let mut v: Vec<Record> = (0..10_000)
+        .map(|id| Record {
+            key: rng.next_u64() as u32,
+            id,
+        })
+        .collect();
+    sorter.sort_by_key(&mut v, |r| r.key);
+    assert!(v.windows(2).all(|w| w[0].key <= w[1].key));
+    let capacity = sorter.capacity();
+    assert!(capacity >= 5_000);
+    for len in [0, 1, 17, 999, 10_000] {
+        let input: Vec<Record> = (0..len)
+            .map(|id| Record {
+                key: rng.gen_range(10) as u32,
+                id,
+            })
+            .collect();
+        let expected = stable(&input);
+        let mut v = input.clone();
+        sorter.sort_by_key(&mut v, |r| r.key);
+        assert_eq!(v, expected);
+        let mut v = input;
+        sorter.sort_bottom_up_by_key(&mut v, |r| r.key);
+        assert_eq!(v, expected);
+    }
+    assert_eq!(sorter.capacity(), capacity);
+}
+
+#[test]
+fn whole_records_sort_like_std() {
+    cases(2000, 100, |rng| {
+        let input: Vec<(u32, String)> = (0..rng.gen_range(500))
+            .map(|i| (rng.gen_range(20) as u32, format!("item {i}")))
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+        let mut v = input.clone();
+        merge_sort(&mut v);
+        assert_eq!(v, expected);
+        let mut v = input;
+        merge_sort_bottom_up(&mut v);
+        assert_eq!(v, expected);
+    });
+}
+
+// a comparison that panics midway leaves every item in the slice once
+#[test]
+fn panicking_compare_loses_nothing() {
+    cases(3000, 50, |rng| {
+        let input: Vec<String> = (0..1 + rng.gen_range(400))
+            .map(|i| format!("{:04}", (i * 7919) % 1000))
+            .collect();
+        let limit = rng.gen_range(input.len() * 4);
+        for bottom_up in [false, true] {
+            let mut v = input.clone();
+            let mut compared = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                let compare = |a: &String, b: &String| {
+                    compared += 1;
+                    assert!(compared < limit, "enough");
+                    a.cmp(b)
+                };
+                if bottom_up {
+                    merge_sort_bottom_up_by(&mut v, compare);
+                } else {
+                    merge_sort_by(&mut v, compare);
+                }
+            }));
+            let mut got = v;
+            got.sort();
+            let mut all = input.clone();
+            all.sort();
+            assert_eq!(got, all, "panicked: {}", result.is_err());
+        }
+    });
+}
+
+// the generated networks pass the zero-one check and sort real keys, and
+// one with a comparator taken out is caught
+#[test]
+fn sorting_networks_sort() {
+    let mut rng = SplitMix64::new(4000);
+    for n in 0..=16 {
+        for network in [
+            SortingNetwork::batcher_odd_even(n),
+            SortingNetwork::bitonic(n),
+        ] {
+            assert!(network.is_sorting(), "{n} inputs");
+            for _ in 0..20 {
+                let mut v: Vec<u32> = (0..n).map(|_| rng.gen_range(5) as u32).collect();
+                let mut expected = v.clone();
+                expected.sort();
+                network.apply(&mut v);
+                assert_eq!(v, expected);
+            }
+        }
+    }
+    let network = SortingNetwork::batcher_odd_even(16);
+    assert_eq!((network.len(), network.depth()), (63, 10));
+    let mut comparators = network.comparators().to_vec();
+    comparators.remove(30);
+    let broken = SortingNetwork::new(16, comparators);
+    let mut input = broken.zero_one_counterexample().expect("a counterexample");
+    broken.apply(&mut input);
+    assert!(input.windows(2).any(|w| w[0] > w[1]));
+}
+
+// small integers in the shapes the sorts special-case: random, few
+// distinct, sorted, reversed, organ pipe and constant
+fn shaped(rng: &mut SplitMix64) -> Vec<i64> {
+    let len = match rng.gen_range(3) {
+        0 => rng.gen_range(40),
+        1 => rng.gen_range(300),
+        _ => rng.gen_range(3000),
+    };
+    let spread = [1, 4, 1000][rng.gen_range(3)];
+    let mut v: Vec<i64> = (0..len)
+        .map(|_| rng.gen_range(spread) as i64 - spread as i64 / 2)
+        .collect();
+    match rng.gen_range(6) {
+        0 => v.sort(),
+        1 => v.sort_by_key(|&x| Reverse(x)),
+        2 => {
+            v.sort();
+            let back: Vec<i64> = v.iter().rev().step_by(2).copied().collect();
+            v = v.iter().step_by(2).copied().chain(back).collect();
+        }
+        3 => v.iter_mut().for_each(|x| *x = 7),
+        _ => {}
+    }
+    v
+}
+
+type Sort<'a, T> = dyn Fn(&mut Vec<T>) + 'a;
+
+#[test]
+fn every_sort_matches_std() {
+    let par = ParSort::new().threads(4).cutoff(64);
+    let sorts: Vec<(&str, Box<Sort<i64>>)> = vec![
+        ("insertion", Box::new(|v| insertion_sort(v))),
+        ("quick", Box::new(|v| quicksort(v))),
+        ("intro", Box::new(|v| introsort(v))),
+        ("heap", Box::new(|v| heapsort(v))),
+        ("tim", Box::new(|v| tim_sort(v))),
+        ("merge", Box::new(|v| merge_sort(v))),
+        ("radix", Box::new(|v| radix_sort(v))),
+        ("counting", Box::new(|v| counting_sort(v))),
+        ("par_merge", Box::new(|v| par_merge_sort(v))),
+        ("par_quick", Box::new(|v| par_quicksort(v))),
+        ("par_merge_split", Box::new(move |v| par.merge_sort(v))),
+        ("par_quick_split", Box::new(move |v| par.quicksort(v))),
+    ];
+    cases(185, 300, |rng| {
+        let input = shaped(rng);
+        let mut expected = input.clone();
+        expected.sort();
+        for (name, sort) in &sorts {
+            if *name == "insertion" && input.len() > 300 {
+                continue;
+            }
+            let mut v = input.clone();
+            sort(&mut v);
+            assert_eq!(v, expected, "{name}");
+        }
+        // the comparator and key variants, descending
+        let mut descending = expected.clone();
+        descending.reverse();
+        let by: [fn(&mut [i64]); 5] = [
+            |v| quicksort_by(v, |a, b| b.cmp(a)),
+            |v| introsort_by(v, |a, b| b.cmp(a)),
+            |v| heapsort_by(v, |a, b| b.cmp(a)),
+            |v| tim_sort_by(v, |a, b| b.cmp(a)),
+            |v| par_quicksort_by(v, |a, b| b.cmp(a)),
+        ];
+        let by_key: [fn(&mut [i64]); 6] = [
+            |v| quicksort_by_key(v, |&x| Reverse(x)),
+            |v| introsort_by_key(v, |&x| Reverse(x)),
+            |v| heapsort_by_key(v, |&x| Reverse(x)),
+            |v| radix_sort_by_key(v, |&x| Reverse(x)),
+            |v| counting_sort_by_key(v, |&x| -x),
+            |v| par_merge_sort_by_key(v, |&x| Reverse(x)),
+        ];
+        for (i, sort) in by.iter().chain(&by_key).enumerate() {
+            let mut v = input.clone();
+            sort(&mut v);
+            assert_eq!(v, descending, "variant {i}");
+        }
+    });
+}
+
+// full width keys, where every radix pass does work
+#[test]
+fn radix_sorts_match_std_on_wide_keys() {
+    cases(188, 200, |rng| {
+        let len = rng.gen_range(2000);
+        let input: Vec<u64> = (0..len)
+            .map(|_| rng.next_u64() >> rng.gen_range(64))
+            .collect();
+        let mut expected = input.clone();
+        expected.sort();
+        let mut v = input.clone();
+        radix_sort(&mut v);
+        assert_eq!(v, expected);
+        let mut signed: Vec<i32> = input.iter().map(|&x| x as i32).collect();
+        let mut expected_signed = signed.clone();
+        expected_signed.sort();
+        radix_sort(&mut signed);
+        assert_eq!(signed, expected_signed);
+        // strings sharing long prefixes, some ending inside others
+        let mut words: Vec<String> = input
+            .iter()
+            .map(|&x| format!("{:x}", x).repeat(1 + (x % 3) as usize))
+            .map(|w| w[..w.len() - (w.len() / 3)].to_string())
+            .collect();
+        let mut expected_words = words.clone();
+        expected_words.sort();
+        msd_radix_sort(&mut words);
+        assert_eq!(words, expected_words);
+    });
+}
+
+#[test]
+fn bucket_sort_matches_total_order() {
+    cases(189, 200, |rng| {
+        let len = rng.gen_range(1000);
+        let mut input: Vec<f64> = match rng.gen_range(3) {
+            0 => (0..len).map(|_| rng.next_f64()).collect(),
+            // bunched up
+            1 => (0..len)
+                .map(|_| rng.next_f64().powi(20) * 1e6 - 3.0)
+                .collect(),
+            _ => (0..len).map(|_| rng.gen_range(5) as f64 - 2.0).collect(),
+        };
+        if len > 0 && rng.gen_bool(0.2) {
+            let specials = [f64::INFINITY, f64::NEG_INFINITY, -0.0, 0.0, f64::NAN];
+            input[rng.gen_range(len)] = specials[rng.gen_range(specials.len())];
+        }
+        let mut expected = input.clone();
+        expected.sort_by(f64::total_cmp);
+        let mut v = input;
+        bucket_sort(&mut v);
+        let bits = |v: &[f64]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&v), bits(&expected));
+    });
+}
+
+#[test]
+fn key_sorts_are_stable() {
+    let par = ParSort::new().threads(4).cutoff(32);
+    cases(190, 300, |rng| {
+        let input = records(rng);
+        let expected = stable(&input);
+        let sorts: [(&str, &Sort<Record>); 7] = [
+            ("tim", &|v| tim_sort_by_key(v, |r| r.key)),
+            ("tim_by", &|v| tim_sort_by(v, |a, b| a.key.cmp(&b.key))),
+            ("radix", &|v| radix_sort_by_key(v, |r| r.key)),
+            ("counting", &|v| counting_sort_by_key(v, |r| r.key as i64)),
+            ("bucket", &|v| bucket_sort_by_key(v, |r| r.key as f64)),
+            ("par_merge", &|v| par_merge_sort_by_key(v, |r| r.key)),
+            ("par_merge_split", &|v| par.merge_sort_by_key(v, |r| r.key)),
+        ];
+        for (name, sort) in sorts {
+            let mut v = input.clone();
+            sort(&mut v);
+            assert_eq!(v, expected, "{name}");
+        }
+    });
+}
+
+// every pivot rule, both partitions and cutoffs down to none at all
+#[test]
+fn quicksort_configurations_match_std() {
+    let pivots = [Pivot::First, Pivot::MedianOfThree, Pivot::Random(185)];
+    cases(1850, 60, |rng| {
+        let input = shaped(rng);
+        let mut expected = input.clone();
+        expected.sort();
+        for pivot in pivots {
+            for three_way in [false, true] {
+                for cutoff in [0, 1, 16, 64] {
+                    let sorter = Quicksort::new()
+                        .pivot(pivot)
+                        .three_way(three_way)
+                        .cutoff(cutoff);
+                    let mut v = input.clone();
+                    sorter.sort(&mut v);
+                    assert_eq!(v, expected, "{sorter:?}");
+                    let mut records: Vec<(i64, usize)> =
+                        input.iter().enumerate().map(|(i, &x)| (x, i)).collect();
+                    sorter.sort_by_key(&mut records, |r| r.0);
+                    assert!(records.iter().map(|r| r.0).eq(expected.iter().copied()));
+                }
+            }
+        }
+    });
+}
+
+// compares the key field only, so ties keep their input order
+#[derive(Clone)]
+struct ByKey;
+
+impl Compare<String> for ByKey {
+    fn compare(&self, a: &String, b: &String) -> Ordering {
+        a[..4].cmp(&b[..4])
+    }
+}
+
+// runs of a few records merged a few at a time, so the merges go in
+// rounds, against std's stable sort
+#[test]
+fn external_sort_matches_std_with_small_runs() {
+    cases(193, 60, |rng| {
+        let len = rng.gen_range(400);
+        let (run_capacity, fan_in) = (1 + rng.gen_range(8), 2 + rng.gen_range(3));
+        let input: Vec<u32> = (0..len).map(|_| rng.next_u64() as u32 % 50).collect();
+        let mut sorter = ExternalSorter::new(LeBytes)
+            .run_capacity(run_capacity)
+            .fan_in(fan_in);
+        sorter.push_all(input.iter().copied()).unwrap();
+        assert_eq!(sorter.len(), len);
+        let sorted: Vec<u32> = sorter.finish().unwrap().map(Result::unwrap).collect();
+        let mut expected = input.clone();
+        expected.sort();
+        assert_eq!(sorted, expected);
+
+        let lines: Vec<String> = input
+            .iter()
+            .enumerate()
+            .map(|(id, &key)| format!("{key:04} {id}"))
+            .collect();
+        let mut sorter = ExternalSorter::with_compare(Lines, ByKey)
+            .run_capacity(run_capacity)
+            .fan_in(fan_in);
+        sorter.push_all(lines.iter().cloned()).unwrap();
+        let sorted: Vec<String> = sorter.finish().unwrap().map(Result::unwrap).collect();
+        let mut expected = lines;
+        expected.sort_by(|a, b| ByKey.compare(a, b));
+        assert_eq!(sorted, expected);
+    });
+    let all: Vec<String> = external_sort(["b", "c", "a"].map(String::from), Lines)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(all, ["a", "b", "c"]);
+}
+
+#[test]
+fn selections_match_sorted_order() {
+    cases(194, 300, |rng| {
+        let input = shaped(rng);
+        let mut sorted = input.clone();
+        sorted.sort();
+        let n = input.len();
+        if n > 0 {
+            for _ in 0..10 {
+                let k = rng.gen_range(n);
+                let selects: [fn(&mut [i64], usize) -> i64; 4] = [
+                    |v, k| *kth_smallest(v, k),
+                    |v, k| *kth_smallest_deterministic(v, k),
+                    |v, k| *kth_smallest_by(v, k, |a, b| b.cmp(a)),
+                    |v, k| *kth_smallest_deterministic_by_key(v, k, |&x| Reverse(x)),
+                ];
+                for (i, select) in selects.iter().enumerate() {
+                    let mut v = input.clone();
+                    let got = select(&mut v, k);
+                    // the last two select by descending order
+                    let (at, before_ok): (i64, fn(i64, i64) -> bool) = if i < 2 {
+                        (sorted[k], |x, y| x <= y)
+                    } else {
+                        (sorted[n - 1 - k], |x, y| x >= y)
+                    };
+                    assert_eq!(got, at, "select {i}");
+                    assert_eq!(v[k], at);
+                    assert!(v[..k].iter().all(|&x| before_ok(x, at)));
+                    assert!(v[k + 1..].iter().all(|&x| before_ok(at, x)));
+                }
+            }
+        }
+        let k = rng.gen_range(n + 1);
+        let mut v = input.clone();
+        partial_sort(&mut v, k);
+        assert_eq!(v[..k], sorted[..k]);
+        v.sort();
+        assert_eq!(v, sorted);
+        let mut v = input.clone();
+        partial_sort_by_key(&mut v, k, |&x| Reverse(x));
+        assert!(v[..k].iter().eq(sorted.iter().rev().take(k)));
+        let largest: Vec<i64> = sorted.iter().rev().take(k).copied().collect();
+        assert_eq!(top_k(input.iter().copied(), k), largest);
+        let smallest: Vec<i64> = sorted.iter().take(k).copied().collect();
+        assert_eq!(
+            top_k_by(input.iter().copied(), k, |a, b| b.cmp(a)),
+            smallest
+        );
+        assert_eq!(
+            top_k_by_key(input.iter().copied(), k, |&x| Reverse(x)),
+            smallest
+        );
+    });
+}
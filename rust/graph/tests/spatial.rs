@@ -0,0 +1,324 @@
+// The interval and spatial indexes against scanning everything they hold,
+// on small integer grids where ties, repeats and hits on a boundary are
+// common.
+
+mod common;
+
+use std::ops::Range;
+
+use graph::geometry::Rect;
+use graph::interval_tree::IntervalTree;
+use graph::kd_tree::KdTree;
+use graph::quadtree::{Orthtree, OutOfBounds};
+use graph::r_tree::RTree;
+use graph::rng::{Rng, SplitMix64};
+
+use common::cases;
+
+fn some_interval(span: u32, rng: &mut SplitMix64) -> Range<u32> {
+    let start = rng.gen_range(span as usize) as u32;
+    start..start + 1 + rng.gen_range(span as usize / 4 + 1) as u32
+}
+
+// by start and end, equal intervals in the order they went in
+fn by_start<'a>(intervals: impl Iterator<Item = &'a (Range<u32>, u32)>) -> Vec<(Range<u32>, u32)> {
+    let mut sorted: Vec<(Range<u32>, u32)> = intervals.cloned().collect();
+    sorted.sort_by_key(|(r, _)| (r.start, r.end));
+    sorted
+}
+
+fn listed<'a>(found: impl Iterator<Item = (&'a Range<u32>, &'a u32)>) -> Vec<(Range<u32>, u32)> {
+    found.map(|(r, &v)| (r.clone(), v)).collect()
+}
+
+// intervals in insertion order, which breaks ties between equal ones
+#[test]
+fn interval_tree_matches_scan() {
+    cases(159, 300, |rng| {
+        let span = 1 + rng.gen_range(100) as u32;
+        let mut tree = IntervalTree::new();
+        let mut model: Vec<(Range<u32>, u32)> = Vec::new();
+        for step in 0..rng.gen_range(400) as u32 {
+            match rng.gen_range(10) {
+                0..=4 => {
+                    let range = some_interval(span, rng);
+                    tree.insert(range.clone(), step);
+                    model.push((range, step));
+                }
+                5 | 6 => {
+                    // often one that is there
+                    let range = match model.len() {
+                        0 => some_interval(span, rng),
+                        n if rng.gen_bool(0.7) => model[rng.gen_range(n)].0.clone(),
+                        _ => some_interval(span, rng),
+                    };
+                    let last = model.iter().rposition(|(r, _)| *r == range);
+                    assert_eq!(tree.remove(&range), last.map(|i| model.remove(i).1));
+                }
+                7 | 8 => {
+                    let (a, b) = (
+                        rng.gen_range(span as usize + 2),
+                        rng.gen_range(span as usize + 2),
+                    );
+                    let query = a.min(b) as u32..a.max(b) as u32;
+                    // nothing for an empty query
+                    let meets = |r: &Range<u32>| r.start < query.end && query.start < r.end;
+                    let expected =
+                        by_start(model.iter().filter(|(r, _)| !query.is_empty() && meets(r)));
+                    assert_eq!(listed(tree.overlapping(query)), expected);
+                }
+                _ => {
+                    let point = rng.gen_range(span as usize + 2) as u32;
+                    let expected = by_start(model.iter().filter(|(r, _)| r.contains(&point)));
+                    assert_eq!(listed(tree.containing(&point)), expected);
+                }
+            }
+            assert_eq!(tree.len(), model.len());
+            assert_eq!(tree.is_empty(), model.is_empty());
+        }
+        assert_eq!(listed(tree.iter()), by_start(model.iter()));
+        let rebuilt: IntervalTree<u32, u32> = model.iter().cloned().collect();
+        assert!(rebuilt.iter().eq(tree.iter()));
+        tree.clear();
+        assert!(tree.is_empty() && tree.iter().next().is_none());
+    });
+}
+
+// a point of the grid 0..=side on every axis
+fn some_point<const D: usize>(side: usize, rng: &mut SplitMix64) -> [f64; D] {
+    std::array::from_fn(|_| rng.gen_range(side + 1) as f64)
+}
+
+// half way between grid points too, so queries miss as well as tie
+fn some_query<const D: usize>(side: usize, rng: &mut SplitMix64) -> [f64; D] {
+    std::array::from_fn(|_| rng.gen_range(2 * side + 5) as f64 / 2.0 - 1.0)
+}
+
+fn some_box<const D: usize>(side: usize, rng: &mut SplitMix64) -> ([f64; D], [f64; D]) {
+    let (a, b): ([f64; D], [f64; D]) = (some_query(side, rng), some_query(side, rng));
+    (
+        std::array::from_fn(|i| a[i].min(b[i])),
+        std::array::from_fn(|i| a[i].max(b[i])),
+    )
+}
+
+fn in_box<const D: usize>(p: &[f64; D], min: &[f64; D], max: &[f64; D]) -> bool {
+    (0..D).all(|i| min[i] <= p[i] && p[i] <= max[i])
+}
+
+fn squared<const D: usize>(a: &[f64; D], b: &[f64; D]) -> f64 {
+    (0..D).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+fn sorted(mut ids: Vec<usize>) -> Vec<usize> {
+    ids.sort_unstable();
+    ids
+}
+
+// A k nearest answer against the distance of every id: the k closest,
+// nearest first, each with its own distance. Which of several at the same
+// distance make the cut is up to the index.
+fn check_nearest(found: &[(usize, f64)], distance: &[f64], k: usize) {
+    assert_eq!(found.len(), k.min(distance.len()));
+    let ids = sorted(found.iter().map(|&(id, _)| id).collect());
+    assert!(ids.windows(2).all(|w| w[0] < w[1]), "{found:?}");
+    for &(id, d) in found {
+        assert_eq!(d, distance[id], "{id}");
+    }
+    assert!(found.windows(2).all(|w| w[0].1 <= w[1].1), "{found:?}");
+    if let Some(&(_, farthest)) = found.last() {
+        let mut left_out = (0..distance.len()).filter(|id| ids.binary_search(id).is_err());
+        assert!(left_out.all(|id| distance[id] >= farthest));
+    }
+}
+
+fn kd_tree_matches_scan<const K: usize>(rng: &mut SplitMix64) {
+    let side = 1 + rng.gen_range(20);
+    let mut model: Vec<[f64; K]> = (0..rng.gen_range(200))
+        .map(|_| some_point(side, rng))
+        .collect();
+    let mut tree = match rng.gen_range(3) {
+        0 => KdTree::build(model.clone()),
+        1 => model.iter().copied().collect(),
+        _ => {
+            let mut tree = KdTree::new();
+            for &p in model.iter() {
+                assert_eq!(tree.insert(p), tree.len() - 1);
+            }
+            tree
+        }
+    };
+    for _ in 0..100 {
+        if rng.gen_bool(0.3) {
+            let p = some_point(side, rng);
+            assert_eq!(tree.insert(p), model.len());
+            model.push(p);
+        }
+        assert_eq!(tree.len(), model.len());
+        let (min, max) = some_box(side, rng);
+        let expected: Vec<usize> = (0..model.len())
+            .filter(|&i| in_box(&model[i], &min, &max))
+            .collect();
+        assert_eq!(sorted(tree.within_box(&min, &max)), expected);
+        let center = some_query(side, rng);
+        let radius = rng.gen_range(2 * side) as f64 / 2.0;
+        let expected: Vec<usize> = (0..model.len())
+            .filter(|&i| squared(&model[i], &center) <= radius * radius)
+            .collect();
+        assert_eq!(sorted(tree.within_radius(&center, radius)), expected);
+        let distance: Vec<f64> = model.iter().map(|p| squared(p, &center).sqrt()).collect();
+        let k = rng.gen_range(12);
+        check_nearest(&tree.k_nearest(&center, k), &distance, k);
+        let nearest = tree.nearest(&center);
+        check_nearest(nearest.as_slice(), &distance, 1);
+    }
+    assert!(tree.points().iter().eq(model.iter()));
+    assert!((0..model.len()).all(|i| *tree.point(i) == model[i]));
+}
+
+#[test]
+fn kd_tree_2_matches_scan() {
+    cases(160, 200, kd_tree_matches_scan::<2>);
+}
+
+#[test]
+fn kd_tree_3_matches_scan() {
+    cases(1600, 200, kd_tree_matches_scan::<3>);
+}
+
+// leaves of one point split the most, and piles of equal points stop
+// splitting only at the depth limit
+fn orthtree_matches_scan<const D: usize>(rng: &mut SplitMix64) {
+    let side = 1 + rng.gen_range(20);
+    let bounds = Rect::new([0.0; D], [side as f64; D]);
+    let mut tree = match rng.gen_range(5) {
+        0 => Orthtree::new(bounds),
+        c => Orthtree::with_leaf_capacity(bounds, c),
+    };
+    assert_eq!(*tree.bounds(), bounds);
+    let mut model: Vec<[f64; D]> = Vec::new();
+    for _ in 0..rng.gen_range(300) {
+        let mut p = some_point(side, rng);
+        if rng.gen_bool(0.1) {
+            // just past an edge
+            let axis = rng.gen_range(D);
+            p[axis] = if rng.gen_bool(0.5) {
+                -0.5
+            } else {
+                side as f64 + 0.5
+            };
+            assert_eq!(tree.insert(p), Err(OutOfBounds));
+        } else {
+            assert_eq!(tree.insert(p), Ok(model.len()));
+            model.push(p);
+        }
+        assert_eq!(tree.len(), model.len());
+        assert_eq!(tree.is_empty(), model.is_empty());
+        if rng.gen_bool(0.7) {
+            continue;
+        }
+        let q = if rng.gen_bool(0.5) {
+            some_point(side, rng)
+        } else {
+            some_query(side, rng)
+        };
+        let expected: Vec<usize> = (0..model.len()).filter(|&i| model[i] == q).collect();
+        assert_eq!(sorted(tree.query_point(&q)), expected);
+        let (min, max) = some_box(side, rng);
+        let expected: Vec<usize> = (0..model.len())
+            .filter(|&i| in_box(&model[i], &min, &max))
+            .collect();
+        assert_eq!(sorted(tree.query_rect(&Rect::new(min, max))), expected);
+        let radius = rng.gen_range(2 * side) as f64 / 2.0;
+        let expected: Vec<usize> = (0..model.len())
+            .filter(|&i| squared(&model[i], &q) <= radius * radius)
+            .collect();
+        assert_eq!(sorted(tree.within_radius(&q, radius)), expected);
+        let distance: Vec<f64> = model.iter().map(|p| squared(p, &q).sqrt()).collect();
+        let k = rng.gen_range(12);
+        check_nearest(&tree.k_nearest(&q, k), &distance, k);
+        check_nearest(tree.nearest(&q).as_slice(), &distance, 1);
+    }
+    assert!((0..model.len()).all(|i| *tree.point(i) == model[i]));
+}
+
+#[test]
+fn quadtree_matches_scan() {
+    cases(161, 200, orthtree_matches_scan::<2>);
+}
+
+#[test]
+fn octree_matches_scan() {
+    cases(1610, 200, orthtree_matches_scan::<3>);
+}
+
+// flat and point boxes among them, which touch as often as they overlap
+fn some_rect<const D: usize>(side: usize, rng: &mut SplitMix64) -> Rect<D> {
+    let min: [f64; D] = some_point(side, rng);
+    let max = std::array::from_fn(|i| min[i] + rng.gen_range(side / 3 + 1) as f64);
+    Rect::new(min, max)
+}
+
+fn r_tree_matches_scan<const D: usize>(rng: &mut SplitMix64) {
+    let side = 1 + rng.gen_range(30);
+    let mut model: Vec<Rect<D>> = (0..rng.gen_range(200))
+        .map(|_| some_rect(side, rng))
+        .collect();
+    let mut tree = match rng.gen_range(4) {
+        0 => RTree::bulk_load(model.clone()),
+        1 => model.iter().copied().collect(),
+        m => {
+            let mut tree = if m == 2 {
+                RTree::new()
+            } else {
+                RTree::with_max_entries(2 + rng.gen_range(5))
+            };
+            for (i, &r) in model.iter().enumerate() {
+                assert_eq!(tree.insert(r), i);
+            }
+            tree
+        }
+    };
+    for _ in 0..100 {
+        if rng.gen_bool(0.3) {
+            let r = some_rect(side, rng);
+            assert_eq!(tree.insert(r), model.len());
+            model.push(r);
+        }
+        assert_eq!(tree.len(), model.len());
+        assert_eq!(tree.is_empty(), model.is_empty());
+        let (min, max) = some_box(side, rng);
+        let window = Rect::new(min, max);
+        let meets = |r: &Rect<D>| (0..D).all(|a| r.min[a] <= max[a] && min[a] <= r.max[a]);
+        let expected: Vec<usize> = (0..model.len()).filter(|&i| meets(&model[i])).collect();
+        assert_eq!(sorted(tree.query(&window)), expected);
+        let expected: Vec<usize> = (0..model.len())
+            .filter(|&i| in_box(&model[i].min, &min, &max) && in_box(&model[i].max, &min, &max))
+            .collect();
+        assert_eq!(sorted(tree.query_contained(&window)), expected);
+        let q: [f64; D] = some_query(side, rng);
+        let expected: Vec<usize> = (0..model.len())
+            .filter(|&i| in_box(&q, &model[i].min, &model[i].max))
+            .collect();
+        assert_eq!(sorted(tree.query_point(&q)), expected);
+        // to the closest point of each box, by clamping q into it
+        let distance: Vec<f64> = model
+            .iter()
+            .map(|r| squared(&q, &std::array::from_fn(|a| q[a].clamp(r.min[a], r.max[a]))).sqrt())
+            .collect();
+        let k = rng.gen_range(12);
+        check_nearest(&tree.k_nearest(&q, k), &distance, k);
+        check_nearest(tree.nearest(&q).as_slice(), &distance, 1);
+    }
+    assert!((0..model.len()).all(|i| *tree.rect(i) == model[i]));
+}
+
+#[test]
+fn r_tree_2_matches_scan() {
+    cases(162, 200, r_tree_matches_scan::<2>);
+}
+
+#[test]
+fn r_tree_3_matches_scan() {
+    cases(1620, 200, r_tree_matches_scan::<3>);
+}
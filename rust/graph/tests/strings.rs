@@ -0,0 +1,564 @@
+// The string matchers against naive search, on texts over small alphabets
+// where matches overlap and patterns are often cut from the text itself,
+// and the string indexes against naive substring scans and BTreeMap.
+
+mod common;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use graph::aho_corasick::{AhoCorasick, Match};
+use graph::radix_tree::RadixTree;
+use graph::rng::{Rng, SplitMix64};
+use graph::strings::{
+    distinct_prefixes_in, failure_function, find_all, periods, prefix_function, prefix_matches,
+    prefix_occurrences, primitive_root, smallest_period, z_array, z_find_all, BoyerMoore, Horspool,
+    Kmp, Matcher, Moduli, RabinKarp, RollingHash,
+};
+use graph::suffix_array::{lcp_array, suffix_array, SuffixArray};
+use graph::suffix_automaton::{longest_common_substring, SuffixAutomaton};
+use graph::suffix_tree::SuffixTree;
+use graph::trie::Trie;
+
+use common::cases;
+
+fn random_bytes(len: usize, alphabet: u8, rng: &mut SplitMix64) -> Vec<u8> {
+    (0..len)
+        .map(|_| b'a' + rng.gen_range(alphabet as usize) as u8)
+        .collect()
+}
+
+// a text and a pattern, the pattern often a piece of the text or periodic
+fn text_and_pattern(rng: &mut SplitMix64) -> (Vec<u8>, Vec<u8>) {
+    let alphabet = [1, 2, 3, 26][rng.gen_range(4)];
+    let text = random_bytes(rng.gen_range(300), alphabet, rng);
+    let len = rng.gen_range(12);
+    let pattern = match rng.gen_range(3) {
+        0 if text.len() >= len => {
+            let at = rng.gen_range(text.len() - len + 1);
+            text[at..at + len].to_vec()
+        }
+        1 => {
+            let unit = random_bytes(1 + rng.gen_range(3), alphabet, rng);
+            unit.iter().copied().cycle().take(len).collect()
+        }
+        _ => random_bytes(len, alphabet, rng),
+    };
+    (text, pattern)
+}
+
+// every start, overlapping ones included; none for an empty pattern
+fn naive_matches(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    if pattern.is_empty() || pattern.len() > text.len() {
+        return Vec::new();
+    }
+    (0..=text.len() - pattern.len())
+        .filter(|&i| text[i..].starts_with(pattern))
+        .collect()
+}
+
+fn naive_border(s: &[u8]) -> usize {
+    (0..s.len())
+        .rev()
+        .find(|&l| s[..l] == s[s.len() - l..])
+        .unwrap()
+}
+
+#[test]
+fn kmp_matches_naive_search() {
+    cases(199, 500, |rng| {
+        let (text, pattern) = text_and_pattern(rng);
+        let expected = naive_matches(&text, &pattern);
+        let kmp = Kmp::new(&pattern);
+        assert_eq!(kmp.pattern(), &pattern[..]);
+        let borders: Vec<usize> = (1..=pattern.len())
+            .map(|i| naive_border(&pattern[..i]))
+            .collect();
+        assert_eq!(kmp.failure(), &borders[..]);
+        assert_eq!(failure_function(&pattern), borders);
+        assert_eq!(kmp.find_all(&text).collect::<Vec<_>>(), expected);
+        assert_eq!(find_all(&text, &pattern).collect::<Vec<_>>(), expected);
+        assert_eq!(kmp.find(&text), expected.first().copied());
+    });
+}
+
+// the same matches whichever way the text is cut into chunks
+#[test]
+fn kmp_stream_matches_whole_text() {
+    cases(1990, 500, |rng| {
+        let (text, pattern) = text_and_pattern(rng);
+        let kmp = Kmp::new(&pattern);
+        let mut stream = kmp.stream();
+        let mut found = Vec::new();
+        let mut rest = &text[..];
+        while !rest.is_empty() {
+            let (chunk, after) = rest.split_at(rng.gen_range(rest.len().min(20) + 1));
+            found.extend(stream.feed(chunk));
+            rest = after;
+            assert_eq!(stream.offset(), text.len() - rest.len());
+        }
+        assert_eq!(found, naive_matches(&text, &pattern));
+        stream.reset();
+        assert_eq!(stream.offset(), 0);
+        let again: Vec<usize> = stream.feed(&text).collect();
+        assert_eq!(again, found);
+    });
+}
+
+fn lcp(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+// powers of a short unit, some cut off or with a byte changed, so the
+// strings have periods besides their length
+fn repetitive(rng: &mut SplitMix64) -> Vec<u8> {
+    let unit = random_bytes(1 + rng.gen_range(4), 2, rng);
+    let mut s: Vec<u8> = unit
+        .iter()
+        .copied()
+        .cycle()
+        .take(rng.gen_range(40))
+        .collect();
+    if !s.is_empty() && rng.gen_bool(0.3) {
+        let at = rng.gen_range(s.len());
+        s[at] = b'z';
+    }
+    s
+}
+
+#[test]
+fn prefix_functions_match_naive() {
+    cases(200, 500, |rng| {
+        let (text, pattern) = text_and_pattern(rng);
+        let s = if rng.gen_bool(0.5) {
+            repetitive(rng)
+        } else {
+            pattern.clone()
+        };
+        let z: Vec<usize> = (0..s.len()).map(|i| lcp(&s, &s[i..])).collect();
+        assert_eq!(z_array(&s), z);
+        let borders: Vec<usize> = (1..=s.len()).map(|i| naive_border(&s[..i])).collect();
+        assert_eq!(prefix_function(&s), borders);
+
+        let along: Vec<usize> = (0..text.len()).map(|i| lcp(&text[i..], &pattern)).collect();
+        assert_eq!(prefix_matches(&text, &pattern), along);
+        assert_eq!(z_find_all(&text, &pattern), naive_matches(&text, &pattern));
+        let occurrences: Vec<usize> = (1..=pattern.len())
+            .map(|l| naive_matches(&text, &pattern[..l]).len())
+            .collect();
+        assert_eq!(prefix_occurrences(&text, &pattern), occurrences);
+        let distinct = occurrences.iter().filter(|&&c| c > 0).count();
+        assert_eq!(distinct_prefixes_in(&text, &pattern), distinct);
+    });
+}
+
+#[test]
+fn periods_match_naive() {
+    cases(2000, 500, |rng| {
+        let s = repetitive(rng);
+        let n = s.len();
+        let expected: Vec<usize> = (1..=n)
+            .filter(|&p| (0..n - p).all(|i| s[i] == s[i + p]))
+            .collect();
+        assert_eq!(periods(&s), expected);
+        assert_eq!(smallest_period(&s), expected.first().copied().unwrap_or(0));
+        let root = (1..=n)
+            .find(|&r| n.is_multiple_of(r) && s.chunks(r).all(|c| c == &s[..r]))
+            .map_or(&s[..0], |r| &s[..r]);
+        assert_eq!(primitive_root(&s), root);
+    });
+}
+
+// substring equality and common prefixes by hash, against comparing bytes
+#[test]
+fn rolling_hash_matches_comparison() {
+    cases(201, 300, |rng| {
+        let text = if rng.gen_bool(0.5) {
+            repetitive(rng)
+        } else {
+            text_and_pattern(rng).0
+        };
+        let n = text.len();
+        let moduli = if rng.gen_bool(0.5) {
+            Moduli::Single
+        } else {
+            Moduli::Double
+        };
+        let seed = rng.next_u64();
+        let hashes = RollingHash::with_seed(&text, moduli, seed);
+        assert_eq!(hashes.len(), n);
+        for _ in 0..50 {
+            let (i, j) = (rng.gen_range(n + 1), rng.gen_range(n + 1));
+            let l = rng.gen_range(n - i.max(j) + 1);
+            assert_eq!(
+                hashes.equal(i..i + l, j..j + l),
+                text[i..i + l] == text[j..j + l]
+            );
+            assert_eq!(hashes.lcp(i, j), lcp(&text[i..], &text[j..]));
+            // the same bytes hash alike in another text under the seed
+            let piece = RollingHash::with_seed(&text[i..i + l], moduli, seed);
+            assert_eq!(piece.hash(0..l), hashes.hash(i..i + l));
+        }
+    });
+}
+
+#[test]
+fn rabin_karp_matches_naive_search() {
+    cases(2010, 300, |rng| {
+        let (text, first) = text_and_pattern(rng);
+        let mut patterns = vec![first];
+        for _ in 0..rng.gen_range(6) {
+            let len = patterns[0].len();
+            let next = if text.len() >= len && rng.gen_bool(0.7) {
+                let at = rng.gen_range(text.len() - len + 1);
+                text[at..at + len].to_vec()
+            } else {
+                random_bytes(len, 3, rng)
+            };
+            patterns.push(next);
+        }
+        let rk = RabinKarp::new(&patterns).unwrap();
+        assert_eq!(rk.pattern_count(), patterns.len());
+        let len = patterns[0].len();
+        let mut expected: Vec<Match> = patterns
+            .iter()
+            .enumerate()
+            .flat_map(|(id, p)| {
+                naive_matches(&text, p).into_iter().map(move |start| Match {
+                    pattern: id,
+                    start,
+                    end: start + len,
+                })
+            })
+            .collect();
+        expected.sort_by_key(|m| (m.start, m.pattern));
+        assert_eq!(rk.find_all(&text), expected);
+        assert_eq!(rk.find(&text), expected.first().copied());
+        assert_eq!(rk.is_match(&text), !expected.is_empty());
+        patterns.push(vec![b'a'; len + 1]);
+        assert!(RabinKarp::new(&patterns).is_err());
+    });
+}
+
+fn matchers(pattern: &[u8]) -> Vec<(&'static str, Box<dyn Matcher>)> {
+    vec![
+        ("boyer-moore", Box::new(BoyerMoore::new(pattern))),
+        ("horspool", Box::new(Horspool::new(pattern))),
+        ("kmp", Box::new(Kmp::new(pattern))),
+    ]
+}
+
+// every Matcher, from every starting point, the full byte range included
+#[test]
+fn matchers_agree_with_naive_search() {
+    cases(202, 500, |rng| {
+        let (mut text, mut pattern) = text_and_pattern(rng);
+        if rng.gen_bool(0.2) {
+            // bytes past ASCII, in the shift tables' upper half
+            for b in text.iter_mut().chain(pattern.iter_mut()) {
+                *b = b.wrapping_mul(37) ^ 0x80;
+            }
+        }
+        let expected = naive_matches(&text, &pattern);
+        for (name, matcher) in matchers(&pattern) {
+            assert_eq!(matcher.pattern(), &pattern[..], "{name}");
+            assert_eq!(matcher.matches(&text), expected, "{name}");
+            assert_eq!(matcher.count(&text), expected.len(), "{name}");
+            assert_eq!(matcher.find(&text), expected.first().copied(), "{name}");
+            for from in 0..=text.len() + 1 {
+                let next = expected.iter().copied().find(|&i| i >= from);
+                assert_eq!(matcher.find_from(&text, from), next, "{name} from {from}");
+            }
+        }
+    });
+}
+
+// a text over a small alphabet, or a repetitive one, and patterns drawn
+// from it or made up, never empty
+fn text_and_patterns(rng: &mut SplitMix64) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let text = if rng.gen_bool(0.3) {
+        repetitive(rng)
+    } else {
+        let alphabet = [1, 2, 3, 26][rng.gen_range(4)];
+        random_bytes(rng.gen_range(80), alphabet, rng)
+    };
+    let patterns = (0..20)
+        .map(|_| {
+            let len = 1 + rng.gen_range(6);
+            if text.len() >= len && rng.gen_bool(0.6) {
+                let at = rng.gen_range(text.len() - len + 1);
+                text[at..at + len].to_vec()
+            } else {
+                random_bytes(len, 3, rng)
+            }
+        })
+        .collect();
+    (text, patterns)
+}
+
+fn distinct_substrings(text: &[u8]) -> BTreeSet<&[u8]> {
+    (0..text.len())
+        .flat_map(|i| (i + 1..=text.len()).map(move |j| &text[i..j]))
+        .collect()
+}
+
+// the length of a longest substring that occurs at least twice
+fn longest_repeat_len(text: &[u8]) -> usize {
+    (1..text.len())
+        .rev()
+        .find(|&len| {
+            let mut seen = BTreeSet::new();
+            text.windows(len).any(|w| !seen.insert(w))
+        })
+        .unwrap_or(0)
+}
+
+#[test]
+fn suffix_array_matches_naive_search() {
+    cases(146, 300, |rng| {
+        let (text, patterns) = text_and_patterns(rng);
+        let sa = SuffixArray::new(&text);
+        assert_eq!(sa.text(), &text[..]);
+        let mut sorted: Vec<usize> = (0..text.len()).collect();
+        sorted.sort_by_key(|&i| &text[i..]);
+        assert_eq!(sa.sa(), &sorted[..]);
+        assert_eq!(suffix_array(&text), sorted);
+        let lcps: Vec<usize> = (0..text.len())
+            .map(|i| {
+                if i == 0 {
+                    0
+                } else {
+                    lcp(&text[sorted[i - 1]..], &text[sorted[i]..])
+                }
+            })
+            .collect();
+        assert_eq!(sa.lcp(), &lcps[..]);
+        assert_eq!(lcp_array(&text, &sorted), lcps);
+        for pattern in &patterns {
+            let expected = naive_matches(&text, pattern);
+            let block = sa.find_range(pattern);
+            assert_eq!(block.len(), expected.len());
+            assert!(sa.sa()[block]
+                .iter()
+                .all(|&i| text[i..].starts_with(pattern)));
+            assert_eq!(sa.positions(pattern), expected);
+            assert_eq!(sa.count(pattern), expected.len());
+            assert_eq!(sa.contains(pattern), !expected.is_empty());
+        }
+        assert_eq!(sa.distinct_substrings(), distinct_substrings(&text).len());
+        let repeat = longest_repeat_len(&text);
+        match sa.longest_repeat() {
+            Some((start, len)) => {
+                assert_eq!(len, repeat);
+                assert!(naive_matches(&text, &text[start..start + len]).len() >= 2);
+            }
+            None => assert_eq!(repeat, 0),
+        }
+    });
+}
+
+#[test]
+fn suffix_automaton_matches_naive_search() {
+    cases(147, 300, |rng| {
+        let (text, patterns) = text_and_patterns(rng);
+        let automaton = SuffixAutomaton::new(&text);
+        // at most 2n - 1 states for n >= 2
+        assert!(automaton.state_count() <= (2 * text.len()).max(2));
+        for pattern in &patterns {
+            let expected = naive_matches(&text, pattern);
+            assert_eq!(automaton.contains(pattern), !expected.is_empty());
+            assert_eq!(automaton.occurrences(pattern), expected.len());
+        }
+        // every position, the end of the text included
+        assert_eq!(automaton.occurrences(b""), text.len() + 1);
+        assert_eq!(
+            automaton.distinct_substrings(),
+            distinct_substrings(&text).len()
+        );
+        // the first of the longest, by where it ends in the other text
+        let other = text_and_patterns(rng).0;
+        let within = distinct_substrings(&text);
+        let expected = (0..=other.len())
+            .rev()
+            .find_map(|len| {
+                (0..=other.len() - len)
+                    .find(|&i| len == 0 || within.contains(&other[i..i + len]))
+                    .map(|i| i..i + len)
+            })
+            .unwrap();
+        let found = automaton.longest_common_substring(&other);
+        assert_eq!(found.len(), expected.len());
+        if !expected.is_empty() {
+            assert_eq!(found, expected);
+        }
+        assert_eq!(longest_common_substring(&text, &other), &other[found]);
+    });
+}
+
+// a repeat that loses an occurrence when extended by a byte on either
+// side, or runs into an end of the text
+fn naive_maximal_repeats(text: &[u8], min_len: usize) -> BTreeSet<(&[u8], usize)> {
+    distinct_substrings(text)
+        .into_iter()
+        .filter(|s| s.len() >= min_len)
+        .filter_map(|s| {
+            let starts = naive_matches(text, s);
+            let before: BTreeSet<Option<u8>> = starts
+                .iter()
+                .map(|&i| i.checked_sub(1).map(|j| text[j]))
+                .collect();
+            let after: BTreeSet<Option<u8>> = starts
+                .iter()
+                .map(|&i| text.get(i + s.len()).copied())
+                .collect();
+            let maximal = |sides: &BTreeSet<Option<u8>>| sides.len() > 1 || sides.contains(&None);
+            (starts.len() >= 2 && maximal(&before) && maximal(&after)).then_some((s, starts.len()))
+        })
+        .collect()
+}
+
+#[test]
+fn suffix_tree_matches_naive_search() {
+    cases(148, 300, |rng| {
+        let (text, patterns) = text_and_patterns(rng);
+        let tree = SuffixTree::new(&text);
+        assert_eq!(tree.len(), text.len());
+        assert_eq!(tree.is_empty(), text.is_empty());
+        // n + 1 leaves with the terminator, and every inner node branches
+        assert!(tree.node_count() <= 2 * (text.len() + 1));
+        for pattern in &patterns {
+            let expected = naive_matches(&text, pattern);
+            assert_eq!(tree.positions(pattern), expected);
+            assert_eq!(tree.count(pattern), expected.len());
+            assert_eq!(tree.contains(pattern), !expected.is_empty());
+        }
+        let repeat = longest_repeat_len(&text);
+        match tree.longest_repeat() {
+            Some(range) => {
+                assert_eq!(range.len(), repeat);
+                assert!(naive_matches(&text, &text[range]).len() >= 2);
+            }
+            None => assert_eq!(repeat, 0),
+        }
+        let min_len = rng.gen_range(4);
+        let repeats: BTreeSet<(&[u8], usize)> = tree
+            .maximal_repeats(min_len)
+            .into_iter()
+            .map(|r| (&text[r.start..r.start + r.len], r.count))
+            .collect();
+        assert_eq!(repeats, naive_maximal_repeats(&text, min_len));
+    });
+}
+
+// Trie and RadixTree have the same surface, so one trace drives both
+macro_rules! key_tree_trace {
+    ($tree:expr, $model:expr, $rng:expr) => {{
+        let (tree, model, rng) = (&mut $tree, &mut $model, &mut *$rng);
+        for _ in 0..rng.gen_range(300) {
+            let key = random_bytes(rng.gen_range(6), 1 + rng.gen_range(3) as u8, rng);
+            match rng.gen_range(10) {
+                0..=4 => {
+                    let v = rng.next_u64() as u32;
+                    assert_eq!(tree.insert(&key, v), model.insert(key.clone(), v));
+                }
+                5..=6 => assert_eq!(tree.remove(&key), model.remove(&key)),
+                7 => {
+                    if let Some(v) = tree.get_mut(&key) {
+                        *v += 1;
+                    }
+                    if let Some(v) = model.get_mut(&key) {
+                        *v += 1;
+                    }
+                    assert_eq!(tree.get(&key), model.get(&key));
+                }
+                8 => {
+                    let expected = (0..=key.len())
+                        .rev()
+                        .find_map(|l| model.get(&key[..l]).map(|v| (l, v)));
+                    assert_eq!(tree.longest_prefix(&key), expected);
+                }
+                _ => {
+                    let prefix = &key[..rng.gen_range(key.len() + 1)];
+                    let expected: Vec<(Vec<u8>, &u32)> = model
+                        .range(prefix.to_vec()..)
+                        .take_while(|(k, _)| k.starts_with(prefix))
+                        .map(|(k, v)| (k.clone(), v))
+                        .collect();
+                    assert_eq!(tree.iter_prefix(prefix).collect::<Vec<_>>(), expected);
+                }
+            }
+            assert_eq!(tree.contains(&key), model.contains_key(&key));
+            assert_eq!(tree.len(), model.len());
+            assert_eq!(tree.is_empty(), model.is_empty());
+        }
+        let all: Vec<(Vec<u8>, &u32)> = model.iter().map(|(k, v)| (k.clone(), v)).collect();
+        assert_eq!(tree.iter().collect::<Vec<_>>(), all);
+    }};
+}
+
+#[test]
+fn trie_matches_btree_map() {
+    cases(144, 300, |rng| {
+        let mut trie = Trie::new();
+        let mut model = BTreeMap::new();
+        key_tree_trace!(trie, model, rng);
+        let rebuilt: Trie<u8, u32> = model.clone().into_iter().collect();
+        assert_eq!(
+            rebuilt.iter().collect::<Vec<_>>(),
+            trie.iter().collect::<Vec<_>>()
+        );
+        trie.clear();
+        assert!(trie.is_empty() && trie.iter().next().is_none());
+    });
+}
+
+#[test]
+fn radix_tree_matches_btree_map() {
+    cases(145, 300, |rng| {
+        let mut radix = RadixTree::new();
+        let mut model = BTreeMap::new();
+        key_tree_trace!(radix, model, rng);
+        // compressed from a trie holding the same keys, then used as is
+        let trie: Trie<u8, u32> = model.clone().into_iter().collect();
+        let trie_nodes = trie.node_count();
+        let mut compressed = RadixTree::from(trie);
+        assert!(compressed.node_count() <= trie_nodes);
+        assert_eq!(
+            compressed.iter().collect::<Vec<_>>(),
+            radix.iter().collect::<Vec<_>>()
+        );
+        key_tree_trace!(compressed, model, rng);
+        radix.clear();
+        assert!(radix.is_empty() && radix.iter().next().is_none());
+    });
+}
+
+#[test]
+fn aho_corasick_matches_naive_search() {
+    cases(149, 300, |rng| {
+        let (text, mut patterns) = text_and_patterns(rng);
+        patterns.truncate(rng.gen_range(patterns.len() + 1));
+        if rng.gen_bool(0.2) {
+            // empty patterns never match, but keep their ids
+            patterns.insert(rng.gen_range(patterns.len() + 1), Vec::new());
+        }
+        let ac = AhoCorasick::new(&patterns);
+        assert_eq!(ac.pattern_count(), patterns.len());
+        let mut expected: Vec<Match> = patterns
+            .iter()
+            .enumerate()
+            .flat_map(|(id, p)| {
+                naive_matches(&text, p).into_iter().map(move |start| Match {
+                    pattern: id,
+                    start,
+                    end: start + p.len(),
+                })
+            })
+            .collect();
+        // by end, then longest first, then by id for duplicate patterns
+        expected.sort_by_key(|m| (m.end, Reverse(m.end - m.start), m.pattern));
+        let found: Vec<Match> = ac.find_iter(text.iter().copied()).collect();
+        assert_eq!(found, expected);
+        assert_eq!(ac.is_match(&text), !expected.is_empty());
+    });
+}
@@ -0,0 +1,112 @@
+// JavaScript facade over the graph crate, built with
+// `wasm-pack build --target web`. Vertices are the ids 0..n so results
+// can come back as flat arrays indexed by vertex.
+
+use wasm_bindgen::prelude::*;
+
+use graph::adjlist::Graph;
+use graph::csr::CsrGraph;
+use graph::dot::to_dot;
+use graph::shortest_path::dijkstra;
+
+#[wasm_bindgen]
+pub struct WasmGraph {
+    graph: Graph<u32>,
+    edges: Vec<(usize, usize, i32)>,
+}
+
+#[wasm_bindgen]
+impl WasmGraph {
+    #[wasm_bindgen(constructor)]
+    pub fn new(vertices: u32) -> WasmGraph {
+        let mut graph = Graph::new();
+        for v in 0..vertices {
+            graph.add_vertex(&v);
+        }
+        WasmGraph {
+            graph,
+            edges: Vec::new(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = vertexCount)]
+    pub fn vertex_count(&self) -> u32 {
+        self.graph.vertex_num()
+    }
+
+    #[wasm_bindgen(js_name = edgeCount)]
+    pub fn edge_count(&self) -> u32 {
+        self.graph.edge_num()
+    }
+
+    // grows the graph by one vertex and returns its id
+    #[wasm_bindgen(js_name = addVertex)]
+    pub fn add_vertex(&mut self) -> u32 {
+        let id = self.graph.vertex_num();
+        self.graph.add_vertex(&id);
+        id
+    }
+
+    #[wasm_bindgen(js_name = addEdge)]
+    pub fn add_edge(&mut self, from: u32, to: u32, weight: i32) -> Result<(), JsError> {
+        self.check(from)?;
+        self.check(to)?;
+        self.graph.add_edge(&from, &to, weight);
+        self.edges.push((from as usize, to as usize, weight));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = addUndirectedEdge)]
+    pub fn add_undirected_edge(&mut self, a: u32, b: u32, weight: i32) -> Result<(), JsError> {
+        self.add_edge(a, b, weight)?;
+        self.add_edge(b, a, weight)
+    }
+
+    // hop counts from start, -1 for unreachable vertices
+    pub fn bfs(&self, start: u32) -> Result<Vec<i32>, JsError> {
+        self.check(start)?;
+        let csr = CsrGraph::from_edges(self.graph.vertex_num() as usize, &self.edges);
+        Ok(csr
+            .bfs(start as usize)
+            .into_iter()
+            .map(|d| d.map_or(-1, |d| d as i32))
+            .collect())
+    }
+
+    // weighted distances from source, Infinity for unreachable vertices
+    pub fn dijkstra(&self, source: u32) -> Result<Vec<f64>, JsError> {
+        self.check(source)?;
+        if self.edges.iter().any(|&(_, _, wt)| wt < 0) {
+            return Err(JsError::new("dijkstra needs non-negative edge weights"));
+        }
+        let sp = dijkstra(&self.graph, &source);
+        Ok((0..self.graph.vertex_num())
+            .map(|v| sp.dist.get(&v).map_or(f64::INFINITY, |&d| d as f64))
+            .collect())
+    }
+
+    // vertex ids source..=target, empty when target is unreachable
+    #[wasm_bindgen(js_name = shortestPath)]
+    pub fn shortest_path(&self, source: u32, target: u32) -> Result<Vec<u32>, JsError> {
+        self.check(source)?;
+        self.check(target)?;
+        Ok(dijkstra(&self.graph, &source)
+            .path_to(&target)
+            .unwrap_or_default())
+    }
+
+    #[wasm_bindgen(js_name = toDot)]
+    pub fn to_dot(&self, name: &str) -> String {
+        to_dot(&self.graph, name)
+    }
+}
+
+impl WasmGraph {
+    fn check(&self, v: u32) -> Result<(), JsError> {
+        if v < self.graph.vertex_num() {
+            Ok(())
+        } else {
+            Err(JsError::new(&format!("vertex {v} out of range")))
+        }
+    }
+}